@@ -1,243 +1,778 @@
+//! Reads the format written by [`crate::ser`]. Blank lines and lines starting with `#` are
+//! skipped.
+
 use crate::Error;
+use serde::de::{
+    DeserializeOwned, DeserializeSeed, Error as _, Visitor, value::BorrowedStrDeserializer,
+};
 use std::io::Read;
 
-#[derive(Debug)]
-struct Deserializer<R: Read> {
-    buf: R,
+pub fn from_str<'a, T>(s: &'a str) -> Result<T, Error>
+where
+    T: serde::Deserialize<'a>,
+{
+    T::deserialize(Deserializer::new(s))
+}
+
+pub fn from_reader<R, T>(mut reader: R) -> Result<T, Error>
+where
+    R: Read,
+    T: DeserializeOwned,
+{
+    let mut s = String::new();
+    reader.read_to_string(&mut s)?;
+    from_str(&s)
+}
+
+/// Undoes the escapes written by [`std::fmt::Debug`] for `str` and `char`.
+fn unescape(s: &str) -> Result<String, Error> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        unescaped.push(match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some(c @ ('\\' | '"' | '\'')) => c,
+            Some('u') => {
+                let (hex, rest) = chars
+                    .as_str()
+                    .strip_prefix('{')
+                    .and_then(|s| s.split_once('}'))
+                    .ok_or_else(|| Error::custom("invalid unicode escape"))?;
+                chars = rest.chars();
+                u32::from_str_radix(hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| Error::custom(format_args!("invalid unicode escape: {hex}")))?
+            }
+            Some(c) => return Err(Error::custom(format_args!("invalid escape: \\{c}"))),
+            None => return Err(Error::custom("unterminated escape")),
+        });
+    }
+    Ok(unescaped)
+}
+
+/// The text between `quote`s of a quoted token, unescaped.
+fn unquote(token: &str, quote: char) -> Result<String, Error> {
+    token
+        .strip_prefix(quote)
+        .and_then(|s| s.strip_suffix(quote))
+        .ok_or_else(|| Error::custom(format_args!("expected {quote}-quoted, found `{token}`")))
+        .and_then(unescape)
+}
+
+/// Deserializes a whole document.
+struct Deserializer<'de> {
+    /// Without blank lines or comments
+    lines: Vec<Line<'de>>,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(s: &'de str) -> Self {
+        Self {
+            lines: s
+                .lines()
+                .enumerate()
+                .map(|(i, text)| Line::new(i + 1, text))
+                .filter(|line| !line.is_empty() && !line.rest().trim_start().starts_with('#'))
+                .collect(),
+        }
+    }
+
+    /// The only line, for values that aren't spread over several.
+    fn line(&self) -> Result<Line<'de>, Error> {
+        match self.lines.as_slice() {
+            &[line] => Ok(line),
+            [] => Err(Error::custom("expected a line, found nothing")),
+            [_, extra, ..] => Err(extra.locate(Error::custom(format_args!(
+                "expected one line, found {}",
+                self.lines.len()
+            )))),
+        }
+    }
+
+    /// Deserializes the only line with `f`, which must use all of it.
+    fn with_line<T>(&self, f: impl FnOnce(&mut Line<'de>) -> Result<T, Error>) -> Result<T, Error> {
+        self.line()?.read_all(f)
+    }
+}
+
+/// Deserializes the tokens of one line.
+#[derive(Debug, Clone, Copy)]
+struct Line<'de> {
+    /// Line number, from 1
+    number: usize,
+    /// The whole line as written
+    text: &'de str,
+    /// Byte offset of what hasn't been read yet
+    pos: usize,
+    /// Byte offset of the last token read or looked for, where errors are reported
+    token: usize,
+}
+
+impl<'de> Line<'de> {
+    const fn new(number: usize, text: &'de str) -> Self {
+        Self {
+            number,
+            text,
+            pos: 0,
+            token: 0,
+        }
+    }
+
+    fn rest(&self) -> &'de str {
+        &self.text[self.pos..]
+    }
+
+    /// Column of [`Self::token`], from 1.
+    fn column(&self) -> usize {
+        self.text[..self.token].chars().count() + 1
+    }
+
+    /// Gives `error` this line's position, unless it already has one.
+    fn locate(&self, error: Error) -> Error {
+        error.at(self.number, self.column())
+    }
+
+    /// Reads with `f`, failing if anything is left on the line afterward.
+    fn read_all<T>(mut self, f: impl FnOnce(&mut Self) -> Result<T, Error>) -> Result<T, Error> {
+        f(&mut self)
+            .and_then(|value| self.end().map(|()| value))
+            .map_err(|e| self.locate(e))
+    }
+
+    fn peek_token(&self) -> Option<&'de str> {
+        let s = self.rest().trim_start();
+        let end = match s.chars().next()? {
+            quote @ ('"' | '\'') => {
+                let mut is_escaped = false;
+                s.char_indices()
+                    .skip(1)
+                    .find(|&(_, c)| {
+                        let is_end = !is_escaped && c == quote;
+                        is_escaped = !is_escaped && c == '\\';
+                        is_end
+                    })
+                    .map_or(s.len(), |(i, c)| i + c.len_utf8())
+            }
+            _ => s.find(char::is_whitespace).unwrap_or(s.len()),
+        };
+        Some(&s[..end])
+    }
+
+    fn next_token(&mut self) -> Result<&'de str, Error> {
+        self.token = self.text.len() - self.rest().trim_start().len();
+        let token = self
+            .peek_token()
+            .ok_or_else(|| Error::custom("unexpected end of line"))?;
+        self.pos = self.token + token.len();
+        Ok(token)
+    }
+
+    fn parse<T: std::str::FromStr>(&mut self, expected: &str) -> Result<T, Error> {
+        let token = self.next_token()?;
+        token
+            .parse()
+            .map_err(|_| Error::custom(format_args!("expected {expected}, found `{token}`")))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rest().trim().is_empty()
+    }
+
+    /// Fails if anything is left on the line.
+    fn end(&mut self) -> Result<(), Error> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            self.token = self.text.len() - self.rest().trim_start().len();
+            Err(Error::custom(format_args!(
+                "trailing characters: `{}`",
+                self.rest().trim()
+            )))
+        }
+    }
+}
+
+/// Gives tuple elements, or with no length, the rest of the line.
+struct Elements<'s, 'de> {
+    line: &'s mut Line<'de>,
+    remaining: Option<usize>,
+}
+
+impl<'de> serde::de::SeqAccess<'de> for Elements<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match &mut self.remaining {
+            Some(0) => return Ok(None),
+            Some(n) => *n -= 1,
+            None if self.line.is_empty() => return Ok(None),
+            None => {}
+        }
+        seed.deserialize(&mut *self.line).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.remaining
+    }
+}
+
+/// Gives each line as an element.
+struct LineElements<'de> {
+    lines: std::vec::IntoIter<Line<'de>>,
+}
+
+impl<'de> serde::de::SeqAccess<'de> for LineElements<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.lines
+            .next()
+            .map(|line| line.read_all(|line| seed.deserialize(line)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.lines.len())
+    }
+}
+
+/// A key, and the rest of each line it starts.
+type Entry<'de> = (&'de str, Line<'de>, Vec<Line<'de>>);
+
+/// Gives the lines of each key, in the order the keys first appear.
+struct Entries<'de> {
+    entries: std::vec::IntoIter<Entry<'de>>,
+    value: Vec<Line<'de>>,
+}
+
+impl<'de> Entries<'de> {
+    fn new(lines: &[Line<'de>]) -> Result<Self, Error> {
+        let mut entries = Vec::<Entry<'de>>::new();
+        for &line in lines {
+            let key_line = line;
+            let mut line = line;
+            let key = line.next_token().map_err(|e| line.locate(e))?;
+            match entries.iter_mut().find(|(k, ..)| *k == key) {
+                Some((.., values)) => values.push(line),
+                None => entries.push((key, key_line, vec![line])),
+            }
+        }
+        Ok(Self {
+            entries: entries.into_iter(),
+            value: Vec::new(),
+        })
+    }
 }
 
-impl<R: Read> Deserializer<R> {
-    pub const fn new(buf: R) -> Self {
-        Self { buf }
+impl<'de> serde::de::MapAccess<'de> for Entries<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.entries
+            .next()
+            .map(|(_, mut key_line, value)| {
+                self.value = value;
+                seed.deserialize(&mut key_line)
+                    .map_err(|e| key_line.locate(e))
+            })
+            .transpose()
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(Deserializer {
+            lines: std::mem::take(&mut self.value),
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
     }
 }
 
-impl<'de, R: Read> serde::de::Deserializer<'de> for Deserializer<R> {
+/// Implements deserializer methods by deserializing the only line.
+macro_rules! forward_to_line {
+    ($($method:ident),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.with_line(|line| line.$method(visitor))
+            }
+        )*
+    };
+}
+
+impl<'de> serde::de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    forward_to_line! {
+        deserialize_any,
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_unit,
+        deserialize_identifier,
+        deserialize_ignored_any,
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.lines.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::custom(format_args!("unsupported type: {name}")))
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(LineElements {
+            lines: self.lines.into_iter(),
+        })
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.with_line(|line| line.deserialize_tuple(len, visitor))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.with_line(|line| line.deserialize_tuple_struct(name, len, visitor))
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(Entries::new(&self.lines)?)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.with_line(|line| line.deserialize_enum(name, variants, visitor))
+    }
+}
+
+impl<'de> serde::de::Deserializer<'de> for &mut Line<'de> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        let token = self
+            .peek_token()
+            .ok_or_else(|| Error::custom("unexpected end of line"))?;
+        if token.starts_with('"') {
+            self.deserialize_str(visitor)
+        } else if token.starts_with('\'') {
+            self.deserialize_char(visitor)
+        } else if token == "true" || token == "false" {
+            self.deserialize_bool(visitor)
+        } else if token.parse::<i64>().is_ok() {
+            self.deserialize_i64(visitor)
+        } else if token.parse::<u64>().is_ok() {
+            self.deserialize_u64(visitor)
+        } else if token.parse::<f64>().is_ok() {
+            self.deserialize_f64(visitor)
+        } else {
+            self.deserialize_identifier(visitor)
+        }
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_bool(self.parse("a bool")?)
     }
 
     fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_i8(self.parse("an i8")?)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_i16(self.parse("an i16")?)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_i32(self.parse("an i32")?)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_i64(self.parse("an i64")?)
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_i128(self.parse("an i128")?)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_u8(self.parse("a u8")?)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_u16(self.parse("a u16")?)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_u32(self.parse("a u32")?)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_u64(self.parse("a u64")?)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u128(self.parse("a u128")?)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_f32(self.parse("an f32")?)
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_f64(self.parse("an f64")?)
     }
 
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        let token = self.next_token()?;
+        let s = unquote(token, '\'')?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(Error::custom(format_args!(
+                "expected a char, found `{token}`"
+            ))),
+        }
     }
 
+    /// Borrows the string from the input unless it has escapes.
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        let token = self.next_token()?;
+        match token.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+            Some(s) if !s.contains('\\') => visitor.visit_borrowed_str(s),
+            _ => visitor.visit_string(unquote(token, '"')?),
+        }
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        self.deserialize_str(visitor)
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        self.deserialize_seq(visitor)
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        self.deserialize_seq(visitor)
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        if self.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
     }
 
-    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        Err(Error::custom("unsupported type"))
     }
 
     fn deserialize_unit_struct<V>(
         self,
         name: &'static str,
-        visitor: V,
+        _visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        Err(Error::custom(format_args!("unsupported type: {name}")))
     }
 
     fn deserialize_newtype_struct<V>(
         self,
-        name: &'static str,
+        _name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_newtype_struct(self)
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_seq(Elements {
+            line: self,
+            remaining: None,
+        })
     }
 
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_seq(Elements {
+            line: self,
+            remaining: Some(len),
+        })
     }
 
     fn deserialize_tuple_struct<V>(
         self,
-        name: &'static str,
+        _name: &'static str,
         len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        self.deserialize_tuple(len, visitor)
     }
 
-    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        Err(Error::custom("maps cannot be nested"))
     }
 
     fn deserialize_struct<V>(
         self,
         name: &'static str,
-        fields: &'static [&'static str],
-        visitor: V,
+        _fields: &'static [&'static str],
+        _visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        Err(Error::custom(format_args!(
+            "structs cannot be nested: {name}"
+        )))
     }
 
     fn deserialize_enum<V>(
         self,
-        name: &'static str,
-        variants: &'static [&'static str],
+        _name: &'static str,
+        _variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_enum(BorrowedStrDeserializer::<Error>::new(self.next_token()?))
     }
 
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_borrowed_str(self.next_token()?)
     }
 
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
-        V: serde::de::Visitor<'de>,
+        V: Visitor<'de>,
     {
-        todo!()
+        self.deserialize_any(visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Object<'a> {
+        o: &'a str,
+        #[serde(default)]
+        v: Vec<(f32, f32, f32)>,
+    }
+
+    #[test]
+    fn test_borrowed_str() {
+        let text = "# comment\no \"cube\"\nv 0 0 0\nv 1 0.5 -1\n";
+        assert_eq!(
+            from_str::<Object>(text).unwrap(),
+            Object {
+                o: "cube",
+                v: vec![(0.0, 0.0, 0.0), (1.0, 0.5, -1.0)],
+            }
+        );
+        assert_eq!(from_str::<(&str, u8)>(r#""a b" 1"#).unwrap(), ("a b", 1));
+        assert!(
+            from_str::<&str>(r#""a\nb""#).is_err(),
+            "escaped strings cannot be borrowed"
+        );
+        assert_eq!(from_str::<String>(r#""a\nb""#).unwrap(), "a\nb");
+    }
+
+    #[test]
+    fn test_error_position() {
+        let text = "o \"cube\"\n\nv 0 0 0\n  v 1 x 1\n";
+        let e = from_str::<Object>(text).unwrap_err();
+        assert_eq!(e.position(), Some((4, 7)), "{e}");
+
+        let e = from_str::<Vec<(u8, u8)>>("1 2\n3 4 5\n").unwrap_err();
+        assert_eq!(e.position(), Some((2, 5)), "{e}");
+
+        let e = from_str::<Vec<(u8, u8)>>("1 2\n3\n").unwrap_err();
+        assert_eq!(e.position(), Some((2, 2)), "{e}");
+
+        let e = from_str::<(u8, u8)>("1 2\n3 4\n").unwrap_err();
+        assert_eq!(e.position(), Some((2, 1)), "{e}");
     }
 }
@@ -1,11 +1,45 @@
 pub mod de;
 pub mod ser;
 
+pub use de::{from_reader, from_str};
+pub use ser::{to_string, to_writer};
+
 #[derive(Debug)]
 pub enum Error {
     IO(std::io::Error),
     Fmt(std::fmt::Error),
     Other(String),
+    /// An error in the input, and where it is.
+    At {
+        /// From 1
+        line: usize,
+        /// In characters, from 1
+        column: usize,
+        error: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Gives the error a position in the input, unless it already has one or isn't about the
+    /// input.
+    pub fn at(self, line: usize, column: usize) -> Self {
+        match self {
+            Self::At { .. } | Self::IO(_) => self,
+            error => Self::At {
+                line,
+                column,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// The `(line, column)` the error is at, if known.
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::At { line, column, .. } => Some((*line, *column)),
+            _ => None,
+        }
+    }
 }
 
 impl From<std::io::Error> for Error {
@@ -26,6 +60,11 @@ impl std::fmt::Display for Error {
             Error::IO(e) => write!(f, "{e}"),
             Error::Fmt(e) => write!(f, "{e}"),
             Error::Other(s) => f.write_str(s),
+            Error::At {
+                line,
+                column,
+                error,
+            } => write!(f, "{error} at line {line} column {column}"),
         }
     }
 }
@@ -36,6 +75,7 @@ impl std::error::Error for Error {
             Error::IO(e) => Some(e),
             Error::Fmt(e) => Some(e),
             Error::Other(_) => None,
+            Error::At { error, .. } => Some(error),
         }
     }
 }
@@ -1,7 +1,51 @@
+//! Writes values as lines of space-separated tokens, in the style of Wavefront OBJ files.
+//!
+//! - Numbers and booleans are written as is, strings and chars quoted and escaped like Rust
+//!   literals, and unit variants by name.
+//! - Tuples (nested or not) continue the current line, and so do sequences inside a line, which
+//!   take up the rest of it.
+//! - A sequence at the top level writes one line per element.
+//! - A struct or map writes one `key value` line per field. A field holding a sequence writes
+//!   one line per element, each starting with the key, and a field holding [`None`] or an empty
+//!   sequence writes nothing, so fields that may be empty need `#[serde(default)]` to be read
+//!   back.
+
 use crate::Error;
-use serde::ser::Error as _;
+use serde::{Serialize, ser::Error as _};
 use std::io::Write;
 
+/// Writes `value` to `writer` as it is serialized.
+pub fn to_writer<W, T>(mut writer: W, value: &T) -> Result<(), Error>
+where
+    W: Write,
+    T: ?Sized + Serialize,
+{
+    value.serialize(Serializer::new(&mut writer))?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn to_string<T>(value: &T) -> Result<String, Error>
+where
+    T: ?Sized + Serialize,
+{
+    let mut buf = Vec::new();
+    to_writer(&mut buf, value)?;
+    String::from_utf8(buf).map_err(Error::custom)
+}
+
+/// Implements serializer methods taking a value by passing the value to `$self.line`.
+macro_rules! forward_to_line {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                self.line(&v)
+            }
+        )*
+    };
+}
+
+/// Serializes a whole document.
 struct Serializer<'a> {
     buf: &'a mut dyn Write,
 }
@@ -11,136 +55,486 @@ impl<'a> Serializer<'a> {
         Self { buf }
     }
 
-    pub fn end(self) -> Result<&'a mut dyn Write, Error> {
-        self.buf.flush()?;
-        Ok(self.buf)
+    /// Writes `value` as a single line, without ending it.
+    fn line<T>(self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut Line::new(self.buf, false))
     }
 }
 
-struct TupleSerializer<'a> {
-    remaining: usize,
-    buf: &'a mut Serializer<'a>,
+/// Serializes tokens onto one line.
+struct Line<'a> {
+    buf: &'a mut dyn Write,
+    has_token: bool,
+    /// Whether [`serde::ser::SerializeTuple::end`] should end the line
+    ends_line: bool,
+}
+
+impl<'a> Line<'a> {
+    const fn new(buf: &'a mut dyn Write, ends_line: bool) -> Self {
+        Self {
+            buf,
+            has_token: false,
+            ends_line,
+        }
+    }
+
+    /// Writes `token`, separated from the one before it.
+    fn token(&mut self, token: std::fmt::Arguments<'_>) -> Result<(), Error> {
+        if self.has_token {
+            self.buf.write_all(b" ")?;
+        }
+        self.has_token = true;
+        self.buf.write_fmt(token)?;
+        Ok(())
+    }
+
+    fn end_line(self) -> Result<(), Error> {
+        if self.ends_line {
+            self.buf.write_all(b"\n")?;
+        }
+        Ok(())
+    }
 }
 
-impl<'a> serde::ser::SerializeTuple for TupleSerializer<'a> {
-    type Ok = &'a mut Serializer<'a>;
+impl serde::ser::SerializeTuple for Line<'_> {
+    type Ok = ();
     type Error = Error;
 
     fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: ?Sized + serde::Serialize,
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.end_line()
+    }
+}
+
+impl serde::ser::SerializeTupleStruct for Line<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.end_line()
+    }
+}
+
+impl serde::ser::SerializeSeq for &mut Line<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeTuple for &mut Line<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeTupleStruct for &mut Line<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+/// Serializes the elements of a top-level sequence, one per line.
+struct Lines<'a> {
+    buf: &'a mut dyn Write,
+}
+
+impl serde::ser::SerializeSeq for Lines<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut Line::new(&mut *self.buf, false))?;
+        self.buf.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+/// Serializes the fields of a top-level struct or map, one `key value` line each.
+struct Entries<'a> {
+    buf: &'a mut dyn Write,
+    /// The map key waiting for its value
+    key: String,
+}
+
+impl serde::ser::SerializeStruct for Entries<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(Field {
+            buf: &mut *self.buf,
+            key,
+        })
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeMap for Entries<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
     {
-        self.remaining -= 1;
-        if self.remaining > 0 {
-            write!(self.buf.buf, " ")?;
+        let mut buf = Vec::new();
+        let mut line = Line::new(&mut buf, false);
+        key.serialize(&mut line)?;
+        if line.has_token {
+            self.key = String::from_utf8(buf).map_err(Error::custom)?;
+            Ok(())
+        } else {
+            Err(Error::custom("map keys cannot be empty"))
         }
-        value.serialize(self.buf)?;
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(Field {
+            buf: &mut *self.buf,
+            key: &self.key,
+        })
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
         Ok(())
     }
+}
+
+/// Serializes the value of one field of a struct or map, starting each line with its key.
+struct Field<'a, 'k> {
+    buf: &'a mut dyn Write,
+    key: &'k str,
+}
+
+impl<'a> Field<'a, '_> {
+    /// Starts a line with the key.
+    fn start_line(self) -> Result<Line<'a>, Error> {
+        let mut line = Line::new(self.buf, true);
+        line.token(format_args!("{}", self.key))?;
+        Ok(line)
+    }
+
+    /// Writes `value` on its own line after the key.
+    fn line<T>(self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let mut line = self.start_line()?;
+        value.serialize(&mut line)?;
+        line.end_line()
+    }
+}
+
+/// Serializes the elements of a sequence held by a field, one line each.
+struct FieldLines<'a, 'k> {
+    buf: &'a mut dyn Write,
+    key: &'k str,
+}
+
+impl serde::ser::SerializeSeq for FieldLines<'_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Field {
+            buf: &mut *self.buf,
+            key: self.key,
+        }
+        .line(value)
+    }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(self.buf)
+        Ok(())
     }
 }
 
 impl<'a> serde::ser::Serializer for Serializer<'a> {
-    type Ok = &'a mut dyn Write;
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Lines<'a>;
+    type SerializeTuple = Line<'a>;
+    type SerializeTupleStruct = Line<'a>;
+    type SerializeTupleVariant = serde::ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Entries<'a>;
+    type SerializeStruct = Entries<'a>;
+    type SerializeStructVariant = serde::ser::Impossible<Self::Ok, Self::Error>;
+
+    forward_to_line! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(Error::custom("unsupported type"))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Err(Error::custom(format_args!("unsupported type: {name}")))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.line(variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::custom(format_args!("unsupported type: {name}")))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(Lines { buf: self.buf })
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(Line::new(self.buf, false))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(Line::new(self.buf, false))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(Error::custom(format_args!("unsupported type: {name}")))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(Entries {
+            buf: self.buf,
+            key: String::new(),
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(Entries {
+            buf: self.buf,
+            key: String::new(),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(Error::custom(format_args!("unsupported type: {name}")))
+    }
+}
+
+impl<'s, 'a> serde::ser::Serializer for &'s mut Line<'a> {
+    type Ok = ();
     type Error = Error;
-    type SerializeSeq = serde::ser::Impossible<Self::Ok, Self::Error>;
-    type SerializeTuple = serde::ser::Impossible<Self::Ok, Self::Error>;
-    type SerializeTupleStruct = serde::ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
     type SerializeTupleVariant = serde::ser::Impossible<Self::Ok, Self::Error>;
     type SerializeMap = serde::ser::Impossible<Self::Ok, Self::Error>;
     type SerializeStruct = serde::ser::Impossible<Self::Ok, Self::Error>;
     type SerializeStructVariant = serde::ser::Impossible<Self::Ok, Self::Error>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v}"))
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v}"))
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v}"))
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v}"))
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v}"))
     }
 
     fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v}"))
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v}"))
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v}"))
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v}"))
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v}"))
     }
 
     fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v}"))
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v}"))
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v}"))
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v:?}"))
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        write!(self.buf, "{v:?}")?;
-        Ok(self.buf)
+        self.token(format_args!("{v:?}"))
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        self.buf.write_all(v)?;
-        Ok(self.buf)
+        v.iter()
+            .try_for_each(|byte| self.token(format_args!("{byte}")))
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
-        Ok(self.buf)
+        Err(Error::custom(
+            "none is only supported for fields and whole values",
+        ))
     }
 
     fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
-        T: ?Sized + serde::Serialize,
+        T: ?Sized + Serialize,
     {
         value.serialize(self)
     }
@@ -155,24 +549,159 @@ impl<'a> serde::ser::Serializer for Serializer<'a> {
 
     fn serialize_unit_variant(
         self,
-        name: &'static str,
+        _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        Err(Error::custom(format_args!("unsupported type: {name}")))
+        self.token(format_args!("{variant}"))
     }
 
     fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
         self,
         name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
         _value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: ?Sized + serde::Serialize,
+        T: ?Sized + Serialize,
+    {
+        Err(Error::custom(format_args!("unsupported type: {name}")))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(Error::custom(format_args!("unsupported type: {name}")))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(Error::custom("maps cannot be nested"))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(Error::custom(format_args!(
+            "structs cannot be nested: {name}"
+        )))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(Error::custom(format_args!("unsupported type: {name}")))
+    }
+}
+
+impl<'a, 'k> serde::ser::Serializer for Field<'a, 'k> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = FieldLines<'a, 'k>;
+    type SerializeTuple = Line<'a>;
+    type SerializeTupleStruct = Line<'a>;
+    type SerializeTupleVariant = serde::ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = serde::ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = serde::ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = serde::ser::Impossible<Self::Ok, Self::Error>;
+
+    forward_to_line! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
     {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(Error::custom("unsupported type"))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
         Err(Error::custom(format_args!("unsupported type: {name}")))
     }
 
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        let mut line = self.start_line()?;
+        line.token(format_args!("{variant}"))?;
+        line.end_line()
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
     fn serialize_newtype_variant<T>(
         self,
         name: &'static str,
@@ -181,23 +710,28 @@ impl<'a> serde::ser::Serializer for Serializer<'a> {
         _value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: ?Sized + serde::Serialize,
+        T: ?Sized + Serialize,
     {
         Err(Error::custom(format_args!("unsupported type: {name}")))
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        todo!()
+        Ok(FieldLines {
+            buf: self.buf,
+            key: self.key,
+        })
     }
 
-    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {}
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.start_line()
+    }
 
     fn serialize_tuple_struct(
         self,
-        name: &'static str,
+        _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
-        Err(Error::custom(format_args!("unsupported type: {name}")))
+        self.start_line()
     }
 
     fn serialize_tuple_variant(
@@ -211,7 +745,7 @@ impl<'a> serde::ser::Serializer for Serializer<'a> {
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        todo!()
+        Err(Error::custom("maps cannot be nested"))
     }
 
     fn serialize_struct(
@@ -219,7 +753,9 @@ impl<'a> serde::ser::Serializer for Serializer<'a> {
         name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        Err(Error::custom(format_args!("unsupported type: {name}")))
+        Err(Error::custom(format_args!(
+            "structs cannot be nested: {name}"
+        )))
     }
 
     fn serialize_struct_variant(
@@ -236,7 +772,10 @@ impl<'a> serde::ser::Serializer for Serializer<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::de::from_str;
     use serde::Serialize;
+    use serde_derive::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
 
     #[test]
     fn test0() {
@@ -251,4 +790,109 @@ mod tests {
         (1, 5).serialize(Serializer::new(&mut buf)).unwrap();
         assert_eq!(&buf, b"1 5");
     }
+
+    #[test]
+    fn test_nested_tuples() {
+        let value = (1, (2.5, "two words".to_string()), ('x', (false,)));
+        let text = to_string(&value).unwrap();
+        assert_eq!(text, r#"1 2.5 "two words" 'x' false"#);
+        assert_eq!(
+            from_str::<(i32, (f32, String), (char, (bool,)))>(&text).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_seq_round_trip() {
+        let value = vec![(0, -1), (2, 3)];
+        let text = to_string(&value).unwrap();
+        assert_eq!(text, "0 -1\n2 3\n");
+        assert_eq!(from_str::<Vec<(i32, i32)>>(&text).unwrap(), value);
+
+        let ragged = vec![vec![1, 2, 3], vec![4]];
+        let text = to_string(&ragged).unwrap();
+        assert_eq!(text, "1 2 3\n4\n");
+        assert_eq!(from_str::<Vec<Vec<u8>>>(&text).unwrap(), ragged);
+    }
+
+    #[test]
+    fn test_map_round_trip() {
+        let value = BTreeMap::from([
+            ("a b".to_string(), (1u8, 2u8)),
+            ("quote\"d\n".to_string(), (3, 4)),
+        ]);
+        let text = to_string(&value).unwrap();
+        assert_eq!(text, "\"a b\" 1 2\n\"quote\\\"d\\n\" 3 4\n");
+        assert_eq!(
+            from_str::<BTreeMap<String, (u8, u8)>>(&text).unwrap(),
+            value
+        );
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Shading {
+        Flat,
+        Smooth,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Mesh {
+        o: String,
+        s: Shading,
+        #[serde(default)]
+        v: Vec<(f32, f32, f32)>,
+        #[serde(default)]
+        f: Vec<Vec<u32>>,
+        mtllib: Option<String>,
+    }
+
+    #[test]
+    fn test_struct_round_trip() {
+        let mesh = Mesh {
+            o: "cube".to_string(),
+            s: Shading::Smooth,
+            v: vec![(0.0, 0.0, 0.0), (1.0, 0.5, -1.25), (1.0, 1.0, 0.0)],
+            f: vec![vec![1, 2, 3], vec![3, 2, 1, 2]],
+            mtllib: None,
+        };
+        let mut buf = Vec::new();
+        to_writer(&mut buf, &mesh).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert_eq!(
+            text,
+            "o \"cube\"\ns smooth\nv 0 0 0\nv 1 0.5 -1.25\nv 1 1 0\nf 1 2 3\nf 3 2 1 2\n"
+        );
+        assert_eq!(from_str::<Mesh>(&text).unwrap(), mesh);
+
+        let empty = Mesh {
+            o: String::new(),
+            s: Shading::Flat,
+            v: Vec::new(),
+            f: Vec::new(),
+            mtllib: Some("cube.mtl".to_string()),
+        };
+        assert_eq!(
+            from_str::<Mesh>(&to_string(&empty).unwrap()).unwrap(),
+            empty
+        );
+    }
+
+    #[test]
+    fn test_nested_struct_fails() {
+        #[derive(Serialize)]
+        struct Outer {
+            inner: Mesh,
+        }
+        let outer = Outer {
+            inner: Mesh {
+                o: String::new(),
+                s: Shading::Flat,
+                v: Vec::new(),
+                f: Vec::new(),
+                mtllib: None,
+            },
+        };
+        assert!(to_string(&outer).is_err());
+    }
 }
@@ -262,11 +262,18 @@ pub trait Source {
         Self: 'a;
 
     fn get<'a>(&'a mut self, rl: &RaylibHandle) -> Self::Value<'a>;
+
+    /// Moves any state the source keeps between frames on by `dt` seconds. Sources that need
+    /// this call it from [`Self::get`], so it only needs calling directly on frames they aren't
+    /// read.
+    #[inline]
+    fn tick(&mut self, _rl: &RaylibHandle, _dt: f32) {}
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Event {
+    #[default]
     Inactive,
     Starting,
     Active,
@@ -343,6 +350,91 @@ impl Event {
     }
 }
 
+/// Seconds one finger must be held still for a [`TouchGesture::LongPress`].
+pub const LONG_PRESS_SECS: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TouchGesture {
+    /// Any finger on the screen
+    Touch,
+    /// One finger held still for [`LONG_PRESS_SECS`], lasting until it is lifted
+    LongPress,
+    /// Two or more fingers on the screen
+    TwoFinger,
+}
+
+/// A [`TouchGesture`] along with how it went last frame, since raylib only knows which fingers
+/// are down now. Written in the config as just the gesture.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "TouchGesture", into = "TouchGesture")]
+pub struct TouchSource {
+    pub gesture: TouchGesture,
+    state: Event,
+}
+
+impl From<TouchGesture> for TouchSource {
+    #[inline]
+    fn from(value: TouchGesture) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<TouchSource> for TouchGesture {
+    #[inline]
+    fn from(value: TouchSource) -> Self {
+        value.gesture
+    }
+}
+
+impl TouchSource {
+    #[inline]
+    pub const fn new(gesture: TouchGesture) -> Self {
+        Self {
+            gesture,
+            state: Event::Inactive,
+        }
+    }
+
+    /// The gesture as of the last call to [`Self::poll`]
+    #[inline]
+    pub const fn state(&self) -> Event {
+        self.state
+    }
+
+    /// Moves on to this frame. Should be called once per frame.
+    pub fn poll(&mut self, rl: &RaylibHandle) {
+        let count = rl.get_touch_point_count();
+        let is_down = match self.gesture {
+            TouchGesture::Touch => count > 0,
+            TouchGesture::LongPress if self.state.is_active() => count == 1,
+            TouchGesture::LongPress => {
+                count == 1
+                    && rl.is_gesture_detected(Gesture::GESTURE_HOLD)
+                    && rl.get_gesture_hold_duration() >= LONG_PRESS_SECS
+            }
+            TouchGesture::TwoFinger => count >= 2,
+        };
+        if is_down {
+            self.state.activate();
+        } else {
+            self.state.deactivate();
+        }
+    }
+}
+
+/// The middle of the fingers on the screen, along with how many there are, if there are at least
+/// `min`.
+fn touch_center(rl: &RaylibHandle, min: u32) -> Option<(u32, Vector2)> {
+    let count = rl.get_touch_point_count();
+    (count >= min && count > 0).then(|| {
+        let sum = (0..count)
+            .map(|n| rl.get_touch_position(n))
+            .fold(Vector2::zero(), |a, b| a + b);
+        (count, sum / count as f32)
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum EventCombo {
@@ -351,13 +443,138 @@ pub enum EventCombo {
     Not(Box<EventSource>),
 }
 
+/// Events that depend on how long `source` is held or how often it is pressed. Each keeps how it
+/// went last frame, which isn't written in the config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventTiming {
+    /// Active once `source` has been held for `seconds`, until it is released
+    Hold {
+        source: Box<EventSource>,
+        seconds: f32,
+        #[serde(skip)]
+        held: f32,
+        #[serde(skip)]
+        state: Event,
+    },
+    /// Active while `source` is held after being pressed a second time within `window` seconds
+    /// of the first
+    DoubleTap {
+        source: Box<EventSource>,
+        window: f32,
+        /// Seconds since the first press, if it could still be followed by a second
+        #[serde(skip)]
+        since_tap: Option<f32>,
+        #[serde(skip)]
+        state: Event,
+    },
+    /// Active while `source` is held, starting again after `delay` seconds and then `rate` times
+    /// a second, like a held key repeating
+    Repeat {
+        source: Box<EventSource>,
+        delay: f32,
+        rate: f32,
+        #[serde(skip)]
+        until_next: f32,
+        #[serde(skip)]
+        state: Event,
+    },
+}
+
+impl EventTiming {
+    /// The event as of the last call to [`Self::tick`]
+    #[inline]
+    pub const fn state(&self) -> Event {
+        match self {
+            Self::Hold { state, .. }
+            | Self::DoubleTap { state, .. }
+            | Self::Repeat { state, .. } => *state,
+        }
+    }
+
+    /// Moves on by `dt` seconds. Should be called once per frame.
+    pub fn tick(&mut self, rl: &RaylibHandle, dt: f32) {
+        match self {
+            Self::Hold {
+                source,
+                seconds,
+                held,
+                state,
+            } => {
+                source.tick(rl, dt);
+                if source.is_active(rl) {
+                    *held += dt;
+                } else {
+                    *held = 0.0;
+                }
+                if source.is_active(rl) && *held >= *seconds {
+                    state.activate();
+                } else {
+                    state.deactivate();
+                }
+            }
+            Self::DoubleTap {
+                source,
+                window,
+                since_tap,
+                state,
+            } => {
+                source.tick(rl, dt);
+                let elapsed = since_tap.map(|t| t + dt);
+                if source.is_starting(rl) {
+                    if elapsed.is_some_and(|t| t <= *window) {
+                        *since_tap = None;
+                        state.activate();
+                    } else {
+                        *since_tap = Some(0.0);
+                        state.deactivate();
+                    }
+                } else {
+                    *since_tap = elapsed;
+                    if state.is_active() && source.is_active(rl) {
+                        state.activate();
+                    } else {
+                        state.deactivate();
+                    }
+                }
+            }
+            Self::Repeat {
+                source,
+                delay,
+                rate,
+                until_next,
+                state,
+            } => {
+                source.tick(rl, dt);
+                if source.is_starting(rl) {
+                    *until_next = *delay;
+                    *state = Event::Starting;
+                } else if source.is_active(rl) {
+                    *until_next -= dt;
+                    if *until_next <= 0.0 {
+                        // a rate of zero never repeats
+                        *until_next += rate.max(0.0).recip();
+                        *state = Event::Starting;
+                    } else {
+                        *state = Event::Active;
+                    }
+                } else {
+                    state.deactivate();
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum EventSource {
     Constant(Event),
     Keyboard(#[serde(with = "KeyboardKeyDef")] KeyboardKey),
     Mouse(#[serde(with = "MouseButtonDef")] MouseButton),
+    Touch(TouchSource),
     Combo(EventCombo),
+    Timing(EventTiming),
 }
 
 impl EventSource {
@@ -367,8 +584,10 @@ impl EventSource {
             Self::Constant(event) => event.is_active(),
             Self::Keyboard(key) => rl.is_key_down(*key),
             Self::Mouse(button) => rl.is_mouse_button_down(*button),
-            Self::Combo(EventCombo::All(items)) => items.iter_mut().any(|x| x.is_active(rl)),
-            Self::Combo(EventCombo::Any(items)) => items.iter_mut().all(|x| x.is_active(rl)),
+            Self::Touch(touch) => touch.state().is_active(),
+            Self::Timing(timing) => timing.state().is_active(),
+            Self::Combo(EventCombo::All(items)) => items.iter_mut().all(|x| x.is_active(rl)),
+            Self::Combo(EventCombo::Any(items)) => items.iter_mut().any(|x| x.is_active(rl)),
             Self::Combo(EventCombo::Not(item)) => !item.is_active(rl),
         }
     }
@@ -379,11 +598,13 @@ impl EventSource {
             Self::Constant(event) => event.is_starting(),
             Self::Keyboard(key) => rl.is_key_pressed(*key),
             Self::Mouse(button) => rl.is_mouse_button_pressed(*button),
-            Self::Combo(EventCombo::All(items)) => items.iter_mut().any(|x| x.is_starting(rl)),
-            Self::Combo(EventCombo::Any(items)) => {
+            Self::Touch(touch) => touch.state().is_starting(),
+            Self::Timing(timing) => timing.state().is_starting(),
+            Self::Combo(EventCombo::All(items)) => {
                 items.iter_mut().any(|x| x.is_starting(rl))
                     && items.iter_mut().all(|x| x.is_active(rl))
             }
+            Self::Combo(EventCombo::Any(items)) => items.iter_mut().any(|x| x.is_starting(rl)),
             Self::Combo(EventCombo::Not(item)) => !item.is_starting(rl),
         }
     }
@@ -394,15 +615,17 @@ impl EventSource {
             Self::Constant(event) => event.is_ending(),
             Self::Keyboard(key) => rl.is_key_released(*key),
             Self::Mouse(button) => rl.is_mouse_button_released(*button),
+            Self::Touch(touch) => touch.state().is_ending(),
+            Self::Timing(timing) => timing.state().is_ending(),
             Self::Combo(EventCombo::All(items)) => {
                 items.iter_mut().any(|x| x.is_ending(rl))
-                    && items.iter_mut().all(
-                        |x| !x.is_active(rl), // assumes that if an item is ending, it is also inactive
-                    )
+                    && items.iter_mut().all(|x| x.is_active(rl) || x.is_ending(rl))
             }
             Self::Combo(EventCombo::Any(items)) => {
                 items.iter_mut().any(|x| x.is_ending(rl))
-                    && items.iter_mut().all(|x| x.is_active(rl) || x.is_ending(rl))
+                    && items.iter_mut().all(
+                        |x| !x.is_active(rl), // assumes that if an item is ending, it is also inactive
+                    )
             }
             Self::Combo(EventCombo::Not(item)) => !item.is_ending(rl),
         }
@@ -412,8 +635,10 @@ impl EventSource {
 impl Source for EventSource {
     type Value<'a> = Event;
 
-    /// Prefer calling [`Self::is_active`], [`Self::is_starting`], or [`Self::is_ending`] if you only need one
+    /// Prefer calling [`Self::is_active`], [`Self::is_starting`], or [`Self::is_ending`] if you only need one,
+    /// though touch gestures and timings only move on to the next frame here
     fn get(&mut self, rl: &RaylibHandle) -> Event {
+        self.tick(rl, rl.get_frame_time());
         if let Self::Constant(event) = self {
             *event
         } else if self.is_active(rl) {
@@ -428,6 +653,19 @@ impl Source for EventSource {
             Event::Inactive
         }
     }
+
+    /// Moves every [`TouchSource`] and [`EventTiming`] in `self` on to this frame.
+    fn tick(&mut self, rl: &RaylibHandle, dt: f32) {
+        match self {
+            Self::Touch(touch) => touch.poll(rl),
+            Self::Timing(timing) => timing.tick(rl, dt),
+            Self::Combo(EventCombo::All(items) | EventCombo::Any(items)) => {
+                items.iter_mut().for_each(|item| item.tick(rl, dt));
+            }
+            Self::Combo(EventCombo::Not(item)) => item.tick(rl, dt),
+            Self::Constant(_) | Self::Keyboard(_) | Self::Mouse(_) => {}
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -555,7 +793,42 @@ pub enum AxisSource {
     Constant(f32),
     #[serde(rename = "scroll")]
     MouseWheelMove,
+    /// How many times further apart the first two fingers are than last frame, in powers of two,
+    /// for pinching to zoom. Holds how many fingers there were and how far apart.
+    TouchPinch(#[serde(skip)] Option<(u32, f32)>),
     EventMix(SelectorSource<AxisSource>),
+    /// `src` while `when` is active, and zero otherwise, such as the wheel only while a modifier
+    /// is held.
+    Gated {
+        src: Box<Self>,
+        when: EventSource,
+    },
+    /// `src` with magnitudes up to `inner` read as zero and from `outer` on as one, rescaled in
+    /// between, for sticks that never quite rest at the center or reach the edge.
+    Deadzone {
+        src: Box<Self>,
+        inner: f32,
+        outer: f32,
+    },
+    /// `src` eased toward over time, closing the gap at `rate` per second in the sense of
+    /// exponential decay. Holds the value so far.
+    Smooth {
+        src: Box<Self>,
+        rate: f32,
+        #[serde(skip)]
+        value: f32,
+    },
+    Clamp {
+        src: Box<Self>,
+        min: f32,
+        max: f32,
+    },
+    /// `src` raised to `exponent`, keeping its sign, so that small movements can be made finer
+    /// without limiting large ones.
+    Curve {
+        src: Box<Self>,
+        exponent: f32,
+    },
     #[serde(rename = "+")]
     Sum(Box<[Self]>),
     #[serde(rename = "*")]
@@ -574,7 +847,54 @@ impl Source for AxisSource {
         match self {
             Self::Constant(x) => *x,
             Self::MouseWheelMove => rl.get_mouse_wheel_move(),
+            Self::TouchPinch(last) => {
+                let count = rl.get_touch_point_count();
+                let spread = (count >= 2).then(|| {
+                    (
+                        count,
+                        rl.get_touch_position(0)
+                            .distance_to(rl.get_touch_position(1)),
+                    )
+                });
+                let pinch = match (*last, spread) {
+                    // a finger joining or leaving would look like a pinch
+                    (Some((prev_count, prev)), Some((count, spread)))
+                        if prev_count == count && prev > 0.0 && spread > 0.0 =>
+                    {
+                        (spread / prev).log2()
+                    }
+                    _ => 0.0,
+                };
+                *last = spread;
+                pinch
+            }
             Self::EventMix(items) => items.get(rl).iter_mut().map(|x| x.get(rl)).sum(),
+            Self::Gated { src, when } => {
+                // read either way, so that touch sources keep track of last frame
+                let value = src.get(rl);
+                if when.get(rl).is_active() { value } else { 0.0 }
+            }
+            Self::Deadzone { src, inner, outer } => {
+                let value = src.get(rl);
+                let magnitude = if value.abs() <= *inner {
+                    0.0
+                } else if value.abs() >= *outer {
+                    1.0
+                } else {
+                    (value.abs() - *inner) / (*outer - *inner)
+                };
+                magnitude.copysign(value)
+            }
+            Self::Smooth { src, rate, value } => {
+                let target = src.get(rl);
+                *value += (target - *value) * (1.0 - (-*rate * rl.get_frame_time()).exp());
+                *value
+            }
+            Self::Clamp { src, min, max } => src.get(rl).max(*min).min(*max),
+            Self::Curve { src, exponent } => {
+                let value = src.get(rl);
+                value.abs().powf(*exponent).copysign(value)
+            }
             Self::Sum(items) => items.iter_mut().map(|x| x.get(rl)).sum(),
             Self::Prod(items) => items.iter_mut().map(|x| x.get(rl)).product(),
             Self::Neg(item) => -item.get(rl),
@@ -589,7 +909,19 @@ pub enum VectorSource {
     Constant(#[serde(with = "Vector2Def")] Vector2),
     MousePosition,
     MouseDelta,
+    /// Both axes of the mouse wheel, where `x` is horizontal scrolling on wheels and trackpads
+    /// that have it.
+    #[serde(rename = "scroll_xy")]
+    MouseWheelMoveV,
+    /// How far the middle of two or more fingers moved since last frame, for panning with two
+    /// fingers. Holds how many fingers there were and where their middle was.
+    TouchDelta(#[serde(skip)] Option<(u32, Vector2)>),
     EventMix(SelectorSource<VectorSource>),
+    /// `src` while `when` is active, and zero otherwise.
+    Gated {
+        src: Box<Self>,
+        when: EventSource,
+    },
     #[serde(rename = "xy")]
     AxisXY {
         x: AxisSource,
@@ -615,12 +947,34 @@ impl Source for VectorSource {
             Self::Constant(v) => *v,
             Self::MousePosition => rl.get_mouse_position(),
             Self::MouseDelta => rl.get_mouse_delta(),
+            Self::MouseWheelMoveV => rl.get_mouse_wheel_move_v().into(),
+            Self::TouchDelta(last) => {
+                let center = touch_center(rl, 2);
+                let delta = match (*last, center) {
+                    // a finger joining or leaving moves the middle without panning
+                    (Some((prev_count, prev)), Some((count, center))) if prev_count == count => {
+                        center - prev
+                    }
+                    _ => Vector2::zero(),
+                };
+                *last = center;
+                delta
+            }
             Self::EventMix(items) => items
                 .get(rl)
                 .iter_mut()
                 .map(|src| src.get(rl))
                 .reduce(|a, b| a + b)
                 .unwrap_or(Vector2::zero()),
+            Self::Gated { src, when } => {
+                // read either way, so that touch sources keep track of last frame
+                let value = src.get(rl);
+                if when.get(rl).is_active() {
+                    value
+                } else {
+                    Vector2::zero()
+                }
+            }
             Self::AxisXY { x, y } => Vector2::new(x.get(rl), y.get(rl)),
             Self::Sum(items) => items
                 .iter_mut()
@@ -0,0 +1,457 @@
+//! Zip archives of the whole workspace, made on a schedule or on demand and rotated so that only
+//! the newest few are kept.
+
+use crate::{
+    config::CONFIG_PATH,
+    console::{Console, GraphRef, LogType},
+    file::SAVE_EXTENSION,
+    graph::{GraphId, GraphList},
+    logln,
+    tab::{CameraBookmark, TabList},
+};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+pub const BACKUP_DIRECTORY: &str = "backups";
+pub const BACKUP_EXTENSION: &str = "zip";
+/// Name of the entry describing the rest of the archive.
+const MANIFEST_NAME: &str = "workspace.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// Minutes between scheduled backups; 0 only backs up on command.
+    pub interval_minutes: u64,
+    /// Archives to keep, oldest deleted first.
+    pub keep: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            interval_minutes: 10,
+            keep: 5,
+        }
+    }
+}
+
+/// A graph in the archive.
+#[derive(Debug, Serialize)]
+struct ManifestGraph {
+    id: GraphId,
+    /// Entry holding the graph's save
+    entry: String,
+    /// Where the graph was last saved, if it ever was
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<PathBuf>,
+}
+
+/// An editor tab in the archive.
+#[derive(Debug, Serialize)]
+struct ManifestTab {
+    /// Graph shown by the tab
+    graph: GraphId,
+    /// The tab's camera bookmarks, by slot number
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    bookmarks: BTreeMap<String, CameraBookmark>,
+}
+
+#[derive(Debug, Serialize)]
+struct Manifest {
+    /// Seconds since the Unix epoch
+    created: u64,
+    /// Every editor tab, in tab order
+    tabs: Vec<ManifestTab>,
+    graphs: Vec<ManifestGraph>,
+}
+
+/// Schedules backups by [`BackupSettings`].
+#[derive(Debug)]
+pub struct Backups {
+    pub settings: BackupSettings,
+    next_backup: Instant,
+}
+
+impl Backups {
+    pub fn new(settings: BackupSettings) -> Self {
+        let mut backups = Self {
+            settings,
+            next_backup: Instant::now(),
+        };
+        backups.reschedule();
+        backups
+    }
+
+    fn reschedule(&mut self) {
+        self.next_backup =
+            Instant::now() + Duration::from_secs(self.settings.interval_minutes * 60);
+    }
+
+    /// Backs up the workspace if a scheduled backup is due.
+    pub fn tick(&mut self, console: &mut Console, graphs: &GraphList, tabs: &TabList) {
+        if self.settings.interval_minutes != 0 && Instant::now() >= self.next_backup {
+            self.backup(console, graphs, tabs);
+        }
+    }
+
+    /// Backs up the workspace now, delays the next scheduled backup, and deletes the oldest
+    /// archives beyond [`BackupSettings::keep`].
+    pub fn backup(&mut self, console: &mut Console, graphs: &GraphList, tabs: &TabList) {
+        self.reschedule();
+        let path = unused_archive_path(BACKUP_DIRECTORY, "backup");
+        logln!(
+            console,
+            LogType::Attempt,
+            "backing up workspace to {}...",
+            path.display()
+        );
+        match write_backup(&path, console, graphs, tabs) {
+            Ok(()) => logln!(console, LogType::Success, "backed up {}", path.display()),
+            Err(e) => {
+                logln!(console, LogType::Error, "failed to back up: {e}");
+                return;
+            }
+        }
+        if let Err(e) = rotate_backups(self.settings.keep) {
+            logln!(
+                console,
+                LogType::Warning,
+                "failed to delete old backups: {e}"
+            );
+        }
+    }
+}
+
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+/// Returns a path in `directory` named `stem` followed by the current time that is not occupied
+/// yet.
+pub fn unused_archive_path(directory: &str, stem: &str) -> PathBuf {
+    let time = unix_time();
+    (0u32..)
+        .map(|n| {
+            let name = match n {
+                0 => format!("{stem}{time}.{BACKUP_EXTENSION}"),
+                n => format!("{stem}{time}-{n}.{BACKUP_EXTENSION}"),
+            };
+            Path::new(directory).join(name)
+        })
+        .find(|path| !path.exists())
+        .expect("should not run out of file names")
+}
+
+/// Archives every graph in the workspace, the config, and a manifest of the two.
+fn write_backup(
+    path: &Path,
+    console: &mut Console,
+    graphs: &GraphList,
+    tabs: &TabList,
+) -> std::io::Result<()> {
+    let mut zip = ZipWriter::new(unix_time());
+    let mut manifest = Manifest {
+        created: unix_time(),
+        tabs: Vec::new(),
+        graphs: Vec::new(),
+    };
+    for graph in graphs.workspace() {
+        let path = tabs
+            .editors_of_graph(&Arc::downgrade(graph))
+            .find_map(|tab| tab.path())
+            .map(Path::to_path_buf);
+        let Ok(graph) = graph.try_read() else {
+            logln!(console, LogType::Warning, "graph is busy, skipping it");
+            continue;
+        };
+        let id = *graph.id();
+        let entry = format!("graphs/{id}.{SAVE_EXTENSION}");
+        match toml::to_string(&*graph) {
+            Ok(s) => zip.add(&entry, s.as_bytes()),
+            Err(e) => {
+                logln!(
+                    console,
+                    LogType::Warning,
+                    "failed to serialize graph {}, skipping it: {e}",
+                    GraphRef(id)
+                );
+                continue;
+            }
+        }
+        manifest.graphs.push(ManifestGraph { id, entry, path });
+    }
+    manifest.tabs = tabs
+        .editors()
+        .filter(|tab| !tab.is_scratch())
+        .filter_map(|tab| {
+            let graph = *tab.graph.upgrade()?.try_read().ok()?.id();
+            let bookmarks = (1..)
+                .zip(tab.bookmarks())
+                .filter_map(|(n, bookmark)| Some((n.to_string(), (*bookmark)?)))
+                .collect();
+            Some(ManifestTab { graph, bookmarks })
+        })
+        .collect();
+    match std::fs::read(CONFIG_PATH) {
+        Ok(config) => zip.add(CONFIG_PATH, &config),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    zip.add(
+        MANIFEST_NAME,
+        toml::to_string_pretty(&manifest)
+            .map_err(std::io::Error::other)?
+            .as_bytes(),
+    );
+
+    std::fs::create_dir_all(BACKUP_DIRECTORY)?;
+    std::fs::File::create_new(path)?.write_all(&zip.finish())
+}
+
+/// Deletes all but the `keep` most recently modified archives in [`BACKUP_DIRECTORY`].
+fn rotate_backups(keep: usize) -> std::io::Result<()> {
+    let mut backups = std::fs::read_dir(BACKUP_DIRECTORY)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == BACKUP_EXTENSION))
+        .filter_map(|path| Some((path.metadata().ok()?.modified().ok()?, path)))
+        .collect::<Vec<_>>();
+    backups.sort_unstable_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in backups.into_iter().skip(keep) {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// CRC-32 (IEEE) lookup table, as used by zip.
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &b| {
+        CRC_TABLE[((crc ^ u32::from(b)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// `(year, month, day)` of the UTC date `days` days after 1970-01-01, months and days from 1.
+pub fn civil_date(days: u64) -> (i64, i64, i64) {
+    // Howard Hinnant's algorithm
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// MS-DOS `(time, date)` of a Unix time, as stored in zip headers.
+fn dos_datetime(unix_time: u64) -> (u16, u16) {
+    let (days, secs) = (unix_time / 86400, unix_time % 86400);
+    let (year, month, day) = civil_date(days);
+    let time = ((secs / 3600) << 11) | ((secs % 3600 / 60) << 5) | (secs % 60 / 2);
+    let date = ((year - 1980).clamp(0, 127) << 9) | (month << 5) | day;
+    (time as u16, date as u16)
+}
+
+/// Builds a zip archive in memory, storing entries without compression.
+pub struct ZipWriter {
+    bytes: Vec<u8>,
+    central_directory: Vec<u8>,
+    entry_count: u16,
+    time: u16,
+    date: u16,
+}
+
+impl ZipWriter {
+    pub fn new(unix_time: u64) -> Self {
+        let (time, date) = dos_datetime(unix_time);
+        Self {
+            bytes: Vec::new(),
+            central_directory: Vec::new(),
+            entry_count: 0,
+            time,
+            date,
+        }
+    }
+
+    pub fn add(&mut self, name: &str, data: &[u8]) {
+        let offset = self.bytes.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+        let name_len = name.len() as u16;
+
+        // local file header
+        self.bytes.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        self.bytes.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        self.bytes.extend_from_slice(&0x0800u16.to_le_bytes()); // names are utf-8
+        self.bytes.extend_from_slice(&0u16.to_le_bytes()); // stored
+        self.bytes.extend_from_slice(&self.time.to_le_bytes());
+        self.bytes.extend_from_slice(&self.date.to_le_bytes());
+        self.bytes.extend_from_slice(&crc.to_le_bytes());
+        self.bytes.extend_from_slice(&size.to_le_bytes()); // compressed
+        self.bytes.extend_from_slice(&size.to_le_bytes()); // uncompressed
+        self.bytes.extend_from_slice(&name_len.to_le_bytes());
+        self.bytes.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        self.bytes.extend_from_slice(name.as_bytes());
+        self.bytes.extend_from_slice(data);
+
+        // central directory header
+        let cd = &mut self.central_directory;
+        cd.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        cd.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        cd.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        cd.extend_from_slice(&0x0800u16.to_le_bytes());
+        cd.extend_from_slice(&0u16.to_le_bytes());
+        cd.extend_from_slice(&self.time.to_le_bytes());
+        cd.extend_from_slice(&self.date.to_le_bytes());
+        cd.extend_from_slice(&crc.to_le_bytes());
+        cd.extend_from_slice(&size.to_le_bytes());
+        cd.extend_from_slice(&size.to_le_bytes());
+        cd.extend_from_slice(&name_len.to_le_bytes());
+        cd.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        cd.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        cd.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        cd.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+        cd.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        cd.extend_from_slice(&offset.to_le_bytes());
+        cd.extend_from_slice(name.as_bytes());
+
+        self.entry_count += 1;
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        let offset = self.bytes.len() as u32;
+        let size = self.central_directory.len() as u32;
+        self.bytes.append(&mut self.central_directory);
+
+        // end of central directory record
+        self.bytes.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.bytes.extend_from_slice(&0u16.to_le_bytes()); // this disk
+        self.bytes.extend_from_slice(&0u16.to_le_bytes()); // disk with central directory
+        self.bytes
+            .extend_from_slice(&self.entry_count.to_le_bytes());
+        self.bytes
+            .extend_from_slice(&self.entry_count.to_le_bytes());
+        self.bytes.extend_from_slice(&size.to_le_bytes());
+        self.bytes.extend_from_slice(&offset.to_le_bytes());
+        self.bytes.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u16_at(bytes: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes(bytes[at..at + 2].try_into().unwrap())
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    /// Reads the entries of an archive by its central directory, checking each against its
+    /// local header.
+    fn read_zip(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let eocd = bytes.len() - 22;
+        assert_eq!(u32_at(bytes, eocd), 0x0605_4b50, "end of central directory");
+        let count = u16_at(bytes, eocd + 10);
+        assert_eq!(u16_at(bytes, eocd + 8), count);
+        let mut at = u32_at(bytes, eocd + 16) as usize;
+        assert_eq!(u32_at(bytes, eocd + 12) as usize, eocd - at);
+        (0..count)
+            .map(|_| {
+                assert_eq!(u32_at(bytes, at), 0x0201_4b50, "central directory header");
+                let crc = u32_at(bytes, at + 16);
+                let size = u32_at(bytes, at + 20) as usize;
+                let name_len = u16_at(bytes, at + 28) as usize;
+                let offset = u32_at(bytes, at + 42) as usize;
+                let name = &bytes[at + 46..at + 46 + name_len];
+                at += 46 + name_len;
+
+                assert_eq!(u32_at(bytes, offset), 0x0403_4b50, "local file header");
+                assert_eq!(u16_at(bytes, offset + 8), 0, "entries should be stored");
+                assert_eq!(u32_at(bytes, offset + 14), crc);
+                assert_eq!(u32_at(bytes, offset + 18) as usize, size);
+                assert_eq!(u32_at(bytes, offset + 22) as usize, size);
+                assert_eq!(u16_at(bytes, offset + 26) as usize, name_len);
+                assert_eq!(&bytes[offset + 30..offset + 30 + name_len], name);
+                let start = offset + 30 + name_len + u16_at(bytes, offset + 28) as usize;
+                let data = &bytes[start..start + size];
+                assert_eq!(crc32(data), crc);
+                (String::from_utf8(name.to_vec()).unwrap(), data.to_vec())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414f_a339
+        );
+    }
+
+    #[test]
+    fn test_civil_date() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(19_723), (2024, 1, 1));
+        assert_eq!(civil_date(19_782), (2024, 2, 29));
+        // 2024-02-29 13:45:30
+        assert_eq!(
+            dos_datetime(19_782 * 86400 + 13 * 3600 + 45 * 60 + 30),
+            ((13 << 11) | (45 << 5) | 15, (44 << 9) | (2 << 5) | 29)
+        );
+    }
+
+    #[test]
+    fn test_zip_read_back() {
+        let entries = [
+            ("workspace.toml", b"created = 0\n".as_slice()),
+            ("graphs/0.toml", b"".as_slice()),
+            ("graphs/\u{e9}.toml", b"nodes = []\n".as_slice()),
+        ];
+        let mut zip = ZipWriter::new(0);
+        for (name, data) in entries {
+            zip.add(name, data);
+        }
+        let read = read_zip(&zip.finish());
+        assert_eq!(
+            read,
+            entries.map(|(name, data)| (name.to_string(), data.to_vec()))
+        );
+        assert!(read_zip(&ZipWriter::new(0).finish()).is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{
+    console::{Console, GraphRef, LogType},
+    graph::{Graph, model::GraphModel, node::NodeId},
+    ivec::IVec2,
+    logln,
+    tab::{Tab, TabList},
+    toolpane::ToolPane,
+};
+use rustc_hash::FxHashSet;
+use serde_derive::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const BLUEPRINT_DIRECTORY: &str = "blueprints";
+pub const BLUEPRINT_EXTENSION: &str = "eabp";
+
+/// A named, reusable piece of circuit.
+///
+/// Node positions are relative to the top-left corner of the captured selection,
+/// so the blueprint can be pasted anywhere.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Blueprint {
+    pub name: String,
+    pub model: GraphModel,
+}
+
+impl Blueprint {
+    /// Returns [`None`] if none of the selected nodes are in `graph`.
+    pub fn capture(graph: &Graph, selection: &FxHashSet<NodeId>, name: String) -> Option<Self> {
+        let model = GraphModel::from_selection(graph, selection);
+        (!model.is_empty()).then_some(Self { name, model })
+    }
+
+    /// Pastes an instance of the blueprint with its top-left corner at `position` and returns
+    /// the IDs of the new nodes.
+    pub fn paste(&self, graph: &mut Graph, position: IVec2, console: &mut Console) -> Vec<NodeId> {
+        logln!(
+            console,
+            LogType::Info,
+            "paste blueprint \"{}\" into graph {}",
+            self.name,
+            GraphRef(*graph.id()),
+        );
+        self.model.paste(graph, position, console)
+    }
+
+    #[inline]
+    pub fn path(&self) -> PathBuf {
+        blueprint_path(&self.name)
+    }
+
+    /// Writes the blueprint to [`Blueprint::path`], creating [`BLUEPRINT_DIRECTORY`] if needed.
+    pub fn save(&self) -> std::io::Result<PathBuf> {
+        let path = self.path();
+        let s = toml::to_string(self).map_err(std::io::Error::other)?;
+        std::fs::create_dir_all(BLUEPRINT_DIRECTORY)?;
+        std::fs::write(&path, s)?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        toml::from_str(&std::fs::read_to_string(path)?).map_err(std::io::Error::other)
+    }
+}
+
+#[inline]
+pub fn blueprint_path(name: &str) -> PathBuf {
+    Path::new(BLUEPRINT_DIRECTORY).join(format!("{name}.{BLUEPRINT_EXTENSION}"))
+}
+
+/// Returns a blueprint name that is not used by any file in [`BLUEPRINT_DIRECTORY`] yet.
+pub fn unused_blueprint_name() -> String {
+    (0u32..)
+        .map(|n| format!("blueprint{n}"))
+        .find(|name| !blueprint_path(name).exists())
+        .expect("should not run out of blueprint names")
+}
+
+/// Paths of every blueprint in [`BLUEPRINT_DIRECTORY`], sorted by name.
+pub fn blueprint_paths() -> Vec<PathBuf> {
+    let mut paths = std::fs::read_dir(BLUEPRINT_DIRECTORY)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == BLUEPRINT_EXTENSION)
+        })
+        .collect::<Vec<_>>();
+    paths.sort_unstable();
+    paths
+}
+
+/// Loads the blueprint that comes after `current` in [`BLUEPRINT_DIRECTORY`], wrapping around
+/// to the first one. Blueprints that fail to load are reported and skipped.
+pub fn next_blueprint(current: Option<&Blueprint>, console: &mut Console) -> Option<Blueprint> {
+    let paths = blueprint_paths();
+    let start = current
+        .and_then(|current| {
+            let current = current.path();
+            paths.iter().position(|path| *path == current)
+        })
+        .map_or(0, |n| n + 1);
+    paths
+        .iter()
+        .cycle()
+        .skip(start)
+        .take(paths.len())
+        .find_map(|path| match Blueprint::load(path) {
+            Ok(blueprint) => Some(blueprint),
+            Err(e) => {
+                logln!(
+                    console,
+                    LogType::Error,
+                    "failed to load blueprint {}: {e}",
+                    path.display()
+                );
+                None
+            }
+        })
+}
+
+/// Performs the toolpane's Blueprints button.
+///
+/// If the focused tab has a selection, it is captured into a new blueprint that is saved and made
+/// current. Otherwise the next saved blueprint is made current.
+pub fn capture_or_cycle(console: &mut Console, toolpane: &mut ToolPane, tabs: &TabList) {
+    if let Some(Tab::Editor(tab)) = tabs.focused_tab()
+        && !tab.selection.is_empty()
+    {
+        let Some(graph) = tab.graph.upgrade() else {
+            logln!(console, LogType::Error, "tab graph no longer exists");
+            return;
+        };
+        let graph = graph.read().unwrap();
+        let Some(blueprint) = Blueprint::capture(&graph, &tab.selection, unused_blueprint_name())
+        else {
+            logln!(
+                console,
+                LogType::Warning,
+                "selection is no longer in the graph"
+            );
+            return;
+        };
+        match blueprint.save() {
+            Ok(path) => {
+                logln!(
+                    console,
+                    LogType::Success,
+                    "saved blueprint \"{}\" to {}",
+                    blueprint.name,
+                    path.display()
+                );
+            }
+            Err(e) => {
+                logln!(console, LogType::Error, "failed to save blueprint: {e}");
+            }
+        }
+        toolpane.set_blueprint(blueprint, console);
+    } else if let Some(blueprint) = next_blueprint(toolpane.blueprint.as_ref(), console) {
+        toolpane.set_blueprint(blueprint, console);
+    } else {
+        logln!(
+            console,
+            LogType::Info,
+            "no blueprints in {BLUEPRINT_DIRECTORY}; select nodes with the edit tool and press Blueprints to make one"
+        );
+    }
+}
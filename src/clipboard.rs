@@ -0,0 +1,121 @@
+use crate::{
+    console::{Console, GraphRef, LogType},
+    graph::model::GraphModel,
+    logln,
+    tab::{Tab, TabList},
+};
+use raylib::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClipboardAction {
+    /// Put the focused tab's selection on the system clipboard.
+    Copy,
+    /// Copy, then erase the selection from the graph.
+    Cut,
+    /// Paste the nodes on the system clipboard into the focused tab and select them.
+    Paste,
+}
+
+impl ClipboardAction {
+    /// What the toolpane's Clipboard button does: copy if something is selected, otherwise paste.
+    pub fn for_button(tabs: &TabList) -> Self {
+        match tabs.focused_tab() {
+            Some(Tab::Editor(tab)) if !tab.selection.is_empty() => Self::Copy,
+            _ => Self::Paste,
+        }
+    }
+
+    /// `position` is the screen position to paste the top-left corner of the clipboard at.
+    pub fn perform(
+        self,
+        rl: &mut RaylibHandle,
+        console: &mut Console,
+        tabs: &mut TabList,
+        position: Vector2,
+    ) {
+        let Some(Tab::Editor(tab)) = tabs.focused_tab_mut() else {
+            logln!(
+                console,
+                LogType::Warning,
+                "no tab to use the clipboard with"
+            );
+            return;
+        };
+        let Some(graph) = tab.graph.upgrade() else {
+            logln!(console, LogType::Error, "tab graph no longer exists");
+            return;
+        };
+        let Ok(mut graph) = graph.try_write() else {
+            logln!(console, LogType::Warning, "graph is busy, try again");
+            return;
+        };
+
+        match self {
+            ClipboardAction::Copy | ClipboardAction::Cut => {
+                let model = GraphModel::from_selection(&graph, &tab.selection);
+                if model.is_empty() {
+                    logln!(console, LogType::Info, "nothing selected to copy");
+                    return;
+                }
+                let text = toml::to_string(&model).expect("graph model should be serializable");
+                if let Err(e) = rl.set_clipboard_text(&text) {
+                    logln!(console, LogType::Error, "failed to copy: {e}");
+                    return;
+                }
+                logln!(
+                    console,
+                    LogType::Info,
+                    "copied {} nodes and {} wires from graph {}",
+                    model.nodes.len(),
+                    model.wires.len(),
+                    GraphRef(*graph.id()),
+                );
+                if self == ClipboardAction::Cut && tab.check_unlocked(console) {
+                    for id in tab.selection.drain() {
+                        _ = graph.destroy_node(&id, false, console);
+                    }
+                    tab.mark_modified();
+                }
+            }
+
+            ClipboardAction::Paste => {
+                if !tab.check_unlocked(console) {
+                    return;
+                }
+                let text = match rl.get_clipboard_text() {
+                    Ok(text) => text,
+                    Err(e) => {
+                        logln!(console, LogType::Error, "failed to read clipboard: {e}");
+                        return;
+                    }
+                };
+                let model = match toml::from_str::<GraphModel>(&text) {
+                    Ok(model) => model,
+                    Err(e) => {
+                        logln!(
+                            console,
+                            LogType::Warning,
+                            "clipboard does not contain a circuit: {e}"
+                        );
+                        return;
+                    }
+                };
+                let offset = tab
+                    .snap()
+                    .node_position(tab.screen_to_world(position), graph.grid_size());
+                tab.selection = model
+                    .paste(&mut graph, offset, console)
+                    .into_iter()
+                    .collect();
+                logln!(
+                    console,
+                    LogType::Info,
+                    "pasted {} nodes into graph {}",
+                    tab.selection.len(),
+                    GraphRef(*graph.id()),
+                );
+                tab.mark_modified();
+            }
+        }
+    }
+}
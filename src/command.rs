@@ -0,0 +1,1678 @@
+//! Commands typed into the console's input line.
+
+use crate::{
+    backup::Backups,
+    completion, config,
+    console::{Console, GateRef, GraphRef, LogType, NodeRef, PositionRef, ToolRef},
+    export::{DEFAULT_EXPORT_SCALE, ExportSettings},
+    file,
+    graph::{
+        EvalMode, Graph, GraphList, bench, jitter,
+        node::{Gate, GateId, Node, NodeId, Ntd},
+        region::RegionId,
+        wire::{Wire, WireId},
+    },
+    input_macro::MacroAction,
+    ivec::{IBounds, IVec2},
+    keybinds::{self, PresetAction},
+    logln, report,
+    tab::{SnapMode, Tab, TabList, ZOOM_PRESETS, zoom_percent},
+    theme::{ColorId, Theme, parse_color},
+    tool::ToolId,
+    toolpane::ToolPane,
+};
+use raylib::prelude::*;
+use std::{path::PathBuf, sync::Arc};
+
+/// Usage and description of every command, shown by `help`.
+pub const USAGE: &[(&str, &str)] = &[
+    ("help", "list commands"),
+    ("clear", "clear the log"),
+    (
+        "log time <on|off>",
+        "show or hide when each line of the log was logged",
+    ),
+    (
+        "log group <on|off>",
+        "show lines from raylib, graphs, files and the ui together, labeled with their source",
+    ),
+    (
+        "log export <path>",
+        "write the whole log to a text file, with timestamps and sources",
+    ),
+    (
+        "node create <gate> (x,y)",
+        "create a node in the focused graph",
+    ),
+    (
+        "node destroy <node> [soft]",
+        "destroy a node in the focused graph, wiring its inputs to its outputs if soft",
+    ),
+    (
+        "wire create <node> <node>",
+        "wire two nodes in the focused graph",
+    ),
+    (
+        "graph list",
+        "list every open graph except the scratch graph",
+    ),
+    (
+        "graph eval [ticks]",
+        "evaluate the focused graph, 1 tick by default",
+    ),
+    (
+        "graph jitter [seed|off]",
+        "evaluate the focused graph's nodes in a random order each tick",
+    ),
+    (
+        "graph truth [selection]",
+        "print the truth table of the focused graph, or only its selection, trying every combination of its inputless nodes",
+    ),
+    (
+        "graph stats",
+        "count the focused graph's nodes by gate, its wires and its strongly connected components, and measure its longest combinational path",
+    ),
+    (
+        "graph loops",
+        "list the focused graph's loops with no delay or capacitor, which may never settle",
+    ),
+    (
+        "graph grid <size>",
+        "change the focused graph's grid size, scaling its layout to fit",
+    ),
+    (
+        "name <node> [\"name\"]",
+        "show or set the name of a node, shown beside it and in the log, \"\" removes it",
+    ),
+    (
+        "note <node|wire> [\"text\"]",
+        "show or set the note on a node or wire, \"\" removes it",
+    ),
+    (
+        "region create (x,y) (x,y) [\"label\"]",
+        "add a labeled region between two corners of the focused graph",
+    ),
+    ("region label <region> \"label\"", "rename a region"),
+    (
+        "region color <region> <0-9>",
+        "color a region like an LED of that number",
+    ),
+    (
+        "region destroy <region>",
+        "remove a region, leaving the nodes in it",
+    ),
+    ("tool <tool>", "set the current tool"),
+    (
+        "gate <gate>",
+        "set the current gate, such as \"and\", \"led.3\" or \"ic.g1\"",
+    ),
+    (
+        "zoom <percent|actual>",
+        "zoom the focused tab to 25%, 50%, 100%, 200% or 400%",
+    ),
+    (
+        "snap <cell|half|off>",
+        "choose where the focused tab puts nodes, and make it the default for new tabs",
+    ),
+    (
+        "lock [on|off]",
+        "keep the focused tab from editing its graph, leaving only the interact tool, or toggle it",
+    ),
+    ("theme set <color> <value>", "change a theme color"),
+    (
+        "backup",
+        "archive every graph and the config in the backups directory",
+    ),
+    (
+        "report [workspace]",
+        "bundle the config, theme diagnostics, log and system info into a zip in the reports directory, with an anonymized copy of every graph if asked",
+    ),
+    (
+        "export <png|svg> [scale] [selection]",
+        "save a picture of the focused graph, or only its selection, in the exports directory",
+    ),
+    (
+        "open <path>",
+        "open a saved graph in a new tab, or focus the tab it is already open in",
+    ),
+    (
+        "save as <path>",
+        "save the focused tab to the path, which saving it goes to from then on",
+    ),
+    (
+        "export obj <path>",
+        "write the focused graph to a file in the line-based obj format",
+    ),
+    (
+        "import obj <path>",
+        "open a graph from a file in the line-based obj format in a new tab",
+    ),
+    (
+        "merge <path>",
+        "copy a saved graph into the focused one where the mouse last was, and select it",
+    ),
+    (
+        "binds export <preset>",
+        "save only the keybindings to a preset in the keybinds directory, or to a .toml path",
+    ),
+    (
+        "binds import <preset>",
+        "replace the keybindings with a preset and save them to the config",
+    ),
+    ("binds check", "list inputs bound to the same keys"),
+    (
+        "macro record <path>",
+        "record the input of every frame to a file until \"macro stop\", evaluating every few frames instead of by the clock",
+    ),
+    (
+        "macro play <path>",
+        "play back recorded input in place of your own until it ends or esc is pressed",
+    ),
+    ("macro stop", "stop recording or playing input"),
+    (
+        "trace probe <node>",
+        "start or stop tracing a node's state every tick in the focused graph",
+    ),
+    (
+        "trace export <path>",
+        "write the states of the focused graph's probed nodes to a VCD file for a waveform viewer",
+    ),
+    (
+        "trace clear",
+        "stop tracing the focused graph and discard what was traced",
+    ),
+    (
+        "bench eval <nodes> [ticks]",
+        "time ordering and evaluating a random graph of that many nodes, 100 ticks by default",
+    ),
+];
+
+/// A node in the focused graph, by ID (`n3`) or by position (`(8,16)`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeTarget {
+    Id(NodeId),
+    Position(IVec2),
+}
+
+impl std::str::FromStr for NodeTarget {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(Self::Id)
+            .or_else(|()| s.parse().map(|PositionRef(pos)| Self::Position(pos)))
+    }
+}
+
+impl std::fmt::Display for NodeTarget {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeTarget::Id(id) => id.fmt(f),
+            NodeTarget::Position(pos) => PositionRef(*pos).fmt(f),
+        }
+    }
+}
+
+impl NodeTarget {
+    pub fn resolve(self, graph: &Graph) -> Option<NodeId> {
+        match self {
+            NodeTarget::Id(id) => graph.node(&id).map(|_| id),
+            NodeTarget::Position(pos) => graph.find_node_at(pos).copied(),
+        }
+    }
+}
+
+/// A node or wire in the focused graph that a note can be attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteTarget {
+    Node(NodeTarget),
+    Wire(WireId),
+}
+
+impl std::str::FromStr for NoteTarget {
+    type Err = ();
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(Self::Wire)
+            .or_else(|()| s.parse().map(Self::Node))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Help,
+    Clear,
+    LogTime(bool),
+    LogGroup(bool),
+    LogExport(PathBuf),
+    NodeCreate {
+        gate: GateArg,
+        position: IVec2,
+    },
+    NodeDestroy {
+        target: NodeTarget,
+        soft: bool,
+    },
+    WireCreate {
+        src: NodeTarget,
+        dst: NodeTarget,
+    },
+    GraphList,
+    GraphEval {
+        ticks: usize,
+    },
+    /// [`None`] turns jitter off
+    GraphJitter(Option<JitterArg>),
+    GraphTruth {
+        selection_only: bool,
+    },
+    GraphStats,
+    GraphLoops,
+    GraphGrid {
+        size: u8,
+    },
+    Name {
+        target: NodeTarget,
+        text: Option<String>,
+    },
+    Note {
+        target: NoteTarget,
+        text: Option<String>,
+    },
+    RegionCreate {
+        bounds: IBounds,
+        label: String,
+    },
+    RegionLabel {
+        id: RegionId,
+        label: String,
+    },
+    RegionColor {
+        id: RegionId,
+        color: Ntd,
+    },
+    RegionDestroy(RegionId),
+    Tool(ToolId),
+    Gate(GateArg),
+    Zoom(ZoomArg),
+    Snap(SnapMode),
+    /// [`None`] toggles the lock
+    Lock(Option<bool>),
+    ThemeSet {
+        id: ColorId,
+        color: Color,
+    },
+    Backup,
+    Report {
+        include_workspace: bool,
+    },
+    Export(ExportSettings),
+    Open(PathBuf),
+    SaveAs(PathBuf),
+    ExportObj(PathBuf),
+    ImportObj(PathBuf),
+    Merge(PathBuf),
+    Binds(PresetAction),
+    Macro(MacroAction),
+    TraceProbe(NodeTarget),
+    TraceExport(PathBuf),
+    TraceClear,
+    BenchEval {
+        nodes: usize,
+        ticks: usize,
+    },
+}
+
+/// The seed to shuffle the eval order with, or a new one each run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JitterArg {
+    Seed(u64),
+    Random,
+}
+
+/// One of the [`ZOOM_PRESETS`] by percentage (`200%` or `200`), or `actual` for 100%.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZoomArg(pub i8);
+
+impl std::str::FromStr for ZoomArg {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "actual" {
+            return Ok(Self(0));
+        }
+        let percent = s
+            .strip_suffix('%')
+            .unwrap_or(s)
+            .parse::<f32>()
+            .map_err(|_| ())?;
+        ZOOM_PRESETS
+            .into_iter()
+            .find(|&zoom_exp| 100.0 * 2.0f32.powi(zoom_exp.into()) == percent)
+            .map(Self)
+            .ok_or(())
+    }
+}
+
+/// A gate with its non-transistor data, or just the kind of gate to use the toolpane's data with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GateArg {
+    Exact(Gate),
+    Id(GateId),
+}
+
+impl std::str::FromStr for GateArg {
+    type Err = ();
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(Self::Exact)
+            .or_else(|()| s.parse().map(Self::Id))
+    }
+}
+
+/// Splits a command into words at whitespace.
+///
+/// Double quotes group words into one argument (with `\"` for a literal quote), and whitespace
+/// inside parentheses is dropped so that `(1, 2)` is one argument.
+pub fn tokenize(s: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut token = None::<String>;
+    let mut chars = s.chars();
+    let mut depth = 0usize;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let token = token.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) => token.push(c),
+                            None => return Err("unterminated string".to_string()),
+                        },
+                        Some(c) => token.push(c),
+                        None => return Err("unterminated string".to_string()),
+                    }
+                }
+            }
+            c if c.is_whitespace() && depth > 0 => {}
+            c if c.is_whitespace() => tokens.extend(token.take()),
+            c => {
+                match c {
+                    '(' => depth += 1,
+                    ')' => {
+                        depth = depth
+                            .checked_sub(1)
+                            .ok_or_else(|| "unmatched ')'".to_string())?;
+                    }
+                    _ => {}
+                }
+                token.get_or_insert_default().push(c);
+            }
+        }
+    }
+    if depth > 0 {
+        return Err("unmatched '('".to_string());
+    }
+    tokens.extend(token);
+    Ok(tokens)
+}
+
+impl std::str::FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn arg<T: std::str::FromStr>(arg: Option<&str>, what: &str) -> Result<T, String> {
+            let arg = arg.ok_or_else(|| format!("missing {what}"))?;
+            arg.parse().map_err(|_| format!("invalid {what}: {arg}"))
+        }
+        fn switch(arg: Option<&str>) -> Result<bool, String> {
+            match arg {
+                Some("on") => Ok(true),
+                Some("off") => Ok(false),
+                Some(arg) => Err(format!("expected on or off: {arg}")),
+                None => Err("missing on or off".to_string()),
+            }
+        }
+
+        let tokens = tokenize(s)?;
+        let mut args = tokens.iter().map(String::as_str);
+        let command = match (args.next(), args.next()) {
+            (Some("help"), None) => Self::Help,
+            (Some("clear"), None) => Self::Clear,
+            (Some("log"), Some("time")) => Self::LogTime(switch(args.next())?),
+            (Some("log"), Some("group")) => Self::LogGroup(switch(args.next())?),
+            (Some("log"), Some("export")) => Self::LogExport(
+                args.next()
+                    .ok_or_else(|| "missing path".to_string())?
+                    .into(),
+            ),
+            (Some("node"), Some("create")) => Self::NodeCreate {
+                gate: arg(args.next(), "gate")?,
+                position: arg(args.next(), "position").map(|PositionRef(pos)| pos)?,
+            },
+            (Some("node"), Some("destroy")) => Self::NodeDestroy {
+                target: arg(args.next(), "node")?,
+                soft: match args.next() {
+                    None => false,
+                    Some("soft") => true,
+                    Some(extra) => return Err(format!("unexpected argument: {extra}")),
+                },
+            },
+            (Some("wire"), Some("create")) => Self::WireCreate {
+                src: arg(args.next(), "node")?,
+                dst: arg(args.next(), "node")?,
+            },
+            (Some("graph"), Some("list")) => Self::GraphList,
+            (Some("graph"), Some("eval")) => Self::GraphEval {
+                ticks: args.next().map_or(Ok(1), |s| arg(Some(s), "tick count"))?,
+            },
+            (Some("graph"), Some("jitter")) => Self::GraphJitter(match args.next() {
+                None => Some(JitterArg::Random),
+                Some("off") => None,
+                seed => Some(JitterArg::Seed(arg(seed, "seed")?)),
+            }),
+            (Some("graph"), Some("truth")) => Self::GraphTruth {
+                selection_only: match args.next() {
+                    None => false,
+                    Some("selection") => true,
+                    Some(extra) => return Err(format!("unexpected argument: {extra}")),
+                },
+            },
+            (Some("graph"), Some("stats")) => Self::GraphStats,
+            (Some("graph"), Some("loops")) => Self::GraphLoops,
+            (Some("graph"), Some("grid")) => Self::GraphGrid {
+                size: arg::<std::num::NonZeroU8>(args.next(), "grid size")?.get(),
+            },
+            (Some("name"), target) => Self::Name {
+                target: arg(target, "node")?,
+                text: args.next().map(str::to_string),
+            },
+            (Some("note"), target) => Self::Note {
+                target: arg(target, "node or wire")?,
+                text: args.next().map(str::to_string),
+            },
+            (Some("region"), Some("create")) => Self::RegionCreate {
+                bounds: IBounds::new(
+                    arg(args.next(), "corner").map(|PositionRef(pos)| pos)?,
+                    arg(args.next(), "corner").map(|PositionRef(pos)| pos)?,
+                ),
+                label: args.next().unwrap_or_default().to_string(),
+            },
+            (Some("region"), Some("label")) => Self::RegionLabel {
+                id: arg(args.next(), "region")?,
+                label: args
+                    .next()
+                    .ok_or_else(|| "missing label".to_string())?
+                    .to_string(),
+            },
+            (Some("region"), Some("color")) => Self::RegionColor {
+                id: arg(args.next(), "region")?,
+                color: arg(args.next(), "color")?,
+            },
+            (Some("region"), Some("destroy")) => Self::RegionDestroy(arg(args.next(), "region")?),
+            (Some("tool"), tool) => Self::Tool(arg(tool, "tool")?),
+            (Some("gate"), gate) => Self::Gate(arg(gate, "gate")?),
+            (Some("zoom"), zoom) => Self::Zoom(arg(zoom, "zoom")?),
+            (Some("snap"), snap) => Self::Snap(arg(snap, "snap mode")?),
+            (Some("lock"), None) => Self::Lock(None),
+            (Some("lock"), state) => Self::Lock(Some(switch(state)?)),
+            (Some("theme"), Some("set")) => Self::ThemeSet {
+                id: arg(args.next(), "color name")?,
+                color: args
+                    .next()
+                    .ok_or_else(|| "missing color".to_string())
+                    .and_then(|s| parse_color(s).map_err(|()| format!("invalid color: {s}")))?,
+            },
+            (Some("backup"), None) => Self::Backup,
+            (Some("report"), None) => Self::Report {
+                include_workspace: false,
+            },
+            (Some("report"), Some("workspace")) => Self::Report {
+                include_workspace: true,
+            },
+            (Some("open"), path) => {
+                Self::Open(path.ok_or_else(|| "missing path".to_string())?.into())
+            }
+            (Some("save"), Some("as")) => Self::SaveAs(
+                args.next()
+                    .ok_or_else(|| "missing path".to_string())?
+                    .into(),
+            ),
+            (Some("export"), Some("obj")) => Self::ExportObj(
+                args.next()
+                    .ok_or_else(|| "missing path".to_string())?
+                    .into(),
+            ),
+            (Some("import"), Some("obj")) => Self::ImportObj(
+                args.next()
+                    .ok_or_else(|| "missing path".to_string())?
+                    .into(),
+            ),
+            (Some("merge"), path) => {
+                Self::Merge(path.ok_or_else(|| "missing path".to_string())?.into())
+            }
+            (Some("export"), format) => {
+                let mut settings = ExportSettings {
+                    format: arg(format, "format")?,
+                    scale: DEFAULT_EXPORT_SCALE,
+                    selection_only: false,
+                };
+                // options may come in either order
+                for option in args.by_ref() {
+                    if option == "selection" {
+                        settings.selection_only = true;
+                    } else {
+                        settings.scale = arg::<f32>(Some(option), "scale")
+                            .ok()
+                            .filter(|scale| scale.is_finite() && *scale > 0.0)
+                            .ok_or_else(|| format!("invalid scale: {option}"))?;
+                    }
+                }
+                Self::Export(settings)
+            }
+            (Some("binds"), Some("export")) => Self::Binds(PresetAction::Export(
+                keybinds::preset_path(args.next().ok_or_else(|| "missing preset".to_string())?),
+            )),
+            (Some("binds"), Some("import")) => Self::Binds(PresetAction::Import(
+                keybinds::preset_path(args.next().ok_or_else(|| "missing preset".to_string())?),
+            )),
+            (Some("binds"), Some("check")) => Self::Binds(PresetAction::Check),
+            (Some("macro"), Some("record")) => Self::Macro(MacroAction::Record(
+                args.next()
+                    .ok_or_else(|| "missing path".to_string())?
+                    .into(),
+            )),
+            (Some("macro"), Some("play")) => Self::Macro(MacroAction::Play(
+                args.next()
+                    .ok_or_else(|| "missing path".to_string())?
+                    .into(),
+            )),
+            (Some("macro"), Some("stop")) => Self::Macro(MacroAction::Stop),
+            (Some("trace"), Some("probe")) => Self::TraceProbe(arg(args.next(), "node")?),
+            (Some("trace"), Some("export")) => Self::TraceExport(
+                args.next()
+                    .ok_or_else(|| "missing path".to_string())?
+                    .into(),
+            ),
+            (Some("trace"), Some("clear")) => Self::TraceClear,
+            (Some("bench"), Some("eval")) => Self::BenchEval {
+                nodes: arg::<std::num::NonZeroUsize>(args.next(), "node count")?.get(),
+                ticks: args
+                    .next()
+                    .map_or(Ok(100), |s| arg(Some(s), "tick count"))?,
+            },
+            (None, _) => return Err("empty command".to_string()),
+            _ => return Err(format!("unknown command: {s}; try \"help\"")),
+        };
+        match args.next() {
+            Some(extra) => Err(format!("unexpected argument: {extra}")),
+            None => Ok(command),
+        }
+    }
+}
+
+/// Where the note of a [`NoteTarget`] is stored.
+enum NoteSlot<'a> {
+    Node(&'a mut Node),
+    Wire(&'a mut Wire),
+}
+
+impl NoteSlot<'_> {
+    fn get(&self) -> Option<&str> {
+        match self {
+            NoteSlot::Node(node) => node.note(),
+            NoteSlot::Wire(wire) => wire.note(),
+        }
+    }
+
+    fn set(&mut self, note: String) -> Option<String> {
+        match self {
+            NoteSlot::Node(node) => node.set_note(note),
+            NoteSlot::Wire(wire) => wire.set_note(note),
+        }
+    }
+}
+
+/// Runs `f` on the graph of the focused editor tab, marking the tabs showing it as modified if
+/// `f` returns `true`.
+fn edit_focused_graph<F>(tabs: &mut TabList, console: &mut Console, f: F) -> bool
+where
+    F: FnOnce(&mut Graph, &mut Console) -> bool,
+{
+    let graph = match tabs.focused_tab() {
+        Some(Tab::Editor(tab)) => tab.graph.upgrade(),
+        None => None,
+    };
+    let Some(graph) = graph else {
+        logln!(console, LogType::Error, "no graph is focused");
+        return false;
+    };
+    let Ok(mut borrow) = graph.try_write() else {
+        logln!(console, LogType::Warning, "graph is busy, try again");
+        return false;
+    };
+    let is_modified = f(&mut borrow, console);
+    drop(borrow);
+    if is_modified {
+        for tab in tabs.editors_of_graph_mut(&Arc::downgrade(&graph)) {
+            tab.mark_modified();
+        }
+    }
+    is_modified
+}
+
+/// What running a command did, and what it leaves for the main loop.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Outcome {
+    /// Whether any graph was changed
+    pub is_modified: bool,
+    /// Exports draw offscreen, which needs the window, so they are handed back to be done there
+    pub export: Option<ExportSettings>,
+    /// The bindings are owned by the main loop
+    pub preset: Option<PresetAction>,
+    /// Opening a tab needs the window
+    pub open: Option<PathBuf>,
+    /// Saving notes where the graph is saved in the graph list, which the main loop owns
+    pub save_as: Option<PathBuf>,
+    /// Opening a tab needs the window
+    pub import: Option<PathBuf>,
+    /// The input macro is owned by the main loop
+    pub input_macro: Option<MacroAction>,
+}
+
+impl From<bool> for Outcome {
+    #[inline]
+    fn from(is_modified: bool) -> Self {
+        Self {
+            is_modified,
+            export: None,
+            preset: None,
+            open: None,
+            save_as: None,
+            import: None,
+            input_macro: None,
+        }
+    }
+}
+
+impl Command {
+    /// Whether the command changes the focused graph, and so cannot be run in a locked tab.
+    fn edits_graph(&self) -> bool {
+        match self {
+            Command::NodeCreate { .. }
+            | Command::NodeDestroy { .. }
+            | Command::WireCreate { .. }
+            | Command::GraphGrid { .. }
+            | Command::RegionCreate { .. }
+            | Command::RegionLabel { .. }
+            | Command::RegionColor { .. }
+            | Command::RegionDestroy(_) => true,
+            Command::Name { text, .. } | Command::Note { text, .. } => text.is_some(),
+            // merging checks the lock itself, since dropping a file merges too
+            _ => false,
+        }
+    }
+
+    pub fn execute(
+        self,
+        console: &mut Console,
+        graphs: &GraphList,
+        tabs: &mut TabList,
+        theme: &mut Theme,
+        toolpane: &mut ToolPane,
+        backups: &mut Backups,
+    ) -> Outcome {
+        if self.edits_graph()
+            && let Some(Tab::Editor(tab)) = tabs.focused_tab()
+            && !tab.check_unlocked(console)
+        {
+            return Outcome::default();
+        }
+        match self {
+            Command::Help => {
+                for (usage, description) in USAGE {
+                    logln!(console, LogType::Info, "{usage} - {description}");
+                }
+            }
+
+            Command::Clear => console.clear(),
+
+            Command::LogTime(show_time) => console.show_time = show_time,
+
+            Command::LogGroup(group_by_source) => {
+                console.group_by_source = group_by_source;
+                // the lines in view move around
+                console.bottom_offset = 0.0;
+            }
+
+            Command::LogExport(path) => match console.export(&path) {
+                Ok(()) => logln!(
+                    console,
+                    LogType::Success,
+                    "exported the log to {}",
+                    path.display()
+                ),
+                Err(e) => logln!(console, LogType::Error, "failed to export the log: {e}"),
+            },
+
+            Command::NodeCreate { gate, position } => {
+                let gate = match gate {
+                    GateArg::Exact(gate) => gate,
+                    GateArg::Id(id) => id.to_gate(toolpane.ntd),
+                };
+                return edit_focused_graph(tabs, console, |graph, console| {
+                    let position = position.snap(graph.grid_size().into());
+                    graph.create_node(gate, position, console).is_ok()
+                })
+                .into();
+            }
+
+            Command::NodeDestroy { target, soft } => {
+                let mut destroyed = None;
+                edit_focused_graph(tabs, console, |graph, console| {
+                    if let Some(id) = target.resolve(graph) {
+                        destroyed = graph.destroy_node(&id, soft, console).map(|_| id);
+                    } else {
+                        logln!(
+                            console,
+                            LogType::Error,
+                            "no node {target} in graph {}",
+                            GraphRef(*graph.id())
+                        );
+                    }
+                    destroyed.is_some()
+                });
+                if let Some(id) = destroyed {
+                    for tab in tabs.editors_mut() {
+                        tab.selection.remove(&id);
+                    }
+                    return true.into();
+                }
+            }
+
+            Command::WireCreate { src, dst } => {
+                let elbow = toolpane.elbow;
+                return edit_focused_graph(tabs, console, |graph, console| {
+                    match (src.resolve(graph), dst.resolve(graph)) {
+                        (Some(src), Some(dst)) if src == dst => {
+                            logln!(console, LogType::Error, "cannot wire a node to itself");
+                            false
+                        }
+                        (Some(src), Some(dst)) => {
+                            graph.create_wire(elbow, src, dst, console).is_ok()
+                        }
+                        (src_id, _) => {
+                            logln!(
+                                console,
+                                LogType::Error,
+                                "no node {} in graph {}",
+                                if src_id.is_none() { src } else { dst },
+                                GraphRef(*graph.id())
+                            );
+                            false
+                        }
+                    }
+                })
+                .into();
+            }
+
+            Command::GraphList => {
+                if graphs.workspace().next().is_none() {
+                    logln!(console, LogType::Info, "no graphs are open");
+                }
+                for graph in graphs.workspace() {
+                    if let Ok(graph) = graph.try_read() {
+                        logln!(
+                            console,
+                            LogType::Info,
+                            "graph {}: {} nodes, {} wires",
+                            GraphRef(*graph.id()),
+                            graph.nodes_iter().len(),
+                            graph.wires_iter().len(),
+                        );
+                    }
+                }
+            }
+
+            Command::GraphEval { ticks } => {
+                // node states are not saved, so this does not modify the graph
+                edit_focused_graph(tabs, console, |graph, console| {
+                    if graph.is_eval_order_dirty() {
+                        graph.refresh_eval_order();
+                    }
+                    for _ in 0..ticks {
+                        graph.evaluate();
+                    }
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "evaluated graph {} for {ticks} ticks",
+                        GraphRef(*graph.id())
+                    );
+                    false
+                });
+            }
+
+            Command::GraphJitter(jitter) => {
+                // the order is not saved, so this does not modify the graph
+                edit_focused_graph(tabs, console, |graph, console| {
+                    let graph_ref = GraphRef(*graph.id());
+                    let seed = jitter.map(|jitter| match jitter {
+                        JitterArg::Seed(seed) => seed,
+                        JitterArg::Random => jitter::time_seed(),
+                    });
+                    graph.set_jitter(seed);
+                    match seed {
+                        Some(seed) => {
+                            logln!(
+                                console,
+                                LogType::Info,
+                                "graph {graph_ref} now evaluates in a random order each tick, with seed {seed}"
+                            );
+                            if graph.eval_mode() == EvalMode::Synchronous {
+                                logln!(
+                                    console,
+                                    LogType::Warning,
+                                    "graph {graph_ref} is synchronous, where the order makes no difference"
+                                );
+                            }
+                        }
+                        None => logln!(
+                            console,
+                            LogType::Info,
+                            "graph {graph_ref} evaluates in eval order again"
+                        ),
+                    }
+                    false
+                });
+            }
+
+            Command::GraphTruth { selection_only } => {
+                let selection = match tabs.focused_tab() {
+                    Some(Tab::Editor(tab)) if selection_only => {
+                        if tab.selection.is_empty() {
+                            logln!(console, LogType::Error, "nothing is selected");
+                            return false.into();
+                        }
+                        Some(tab.selection.clone())
+                    }
+                    _ => None,
+                };
+                edit_focused_graph(tabs, console, |graph, console| {
+                    let graph_ref = GraphRef(*graph.id());
+                    match graph.truth_table(graphs, selection.as_ref()) {
+                        Ok(table) => {
+                            logln!(
+                                console,
+                                LogType::Info,
+                                "truth table of {} in graph {graph_ref}, with {} inputs and {} outputs:",
+                                if selection.is_some() {
+                                    "the selection"
+                                } else {
+                                    "every node"
+                                },
+                                table.inputs.len(),
+                                table.outputs.len()
+                            );
+                            for line in table.lines() {
+                                logln!(console, LogType::Info, "{line}");
+                            }
+                        }
+                        Err(e) => logln!(
+                            console,
+                            LogType::Error,
+                            "no truth table for graph {graph_ref}: {e}"
+                        ),
+                    }
+                    false
+                });
+            }
+
+            Command::GraphStats => {
+                edit_focused_graph(tabs, console, |graph, console| {
+                    let mut gate_counts = [0usize; GateId::ALL.len()];
+                    for node in graph.nodes_iter() {
+                        gate_counts[node.gate().as_gate().id() as usize] += 1;
+                    }
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "graph {}: {} nodes, {} wires, {} strongly connected components, longest combinational path of {} nodes",
+                        GraphRef(*graph.id()),
+                        graph.nodes_iter().len(),
+                        graph.wires_iter().len(),
+                        graph.strongly_connected_components().len(),
+                        graph.longest_combinational_path()
+                    );
+                    if gate_counts.iter().any(|&count| count > 0) {
+                        logln!(
+                            console,
+                            LogType::Info,
+                            "nodes by gate: {}",
+                            GateId::ALL
+                                .into_iter()
+                                .zip(gate_counts)
+                                .filter(|&(_, count)| count > 0)
+                                .map(|(id, count)| format!("{id} {count}"))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        );
+                    }
+                    false
+                });
+            }
+
+            Command::GraphLoops => {
+                edit_focused_graph(tabs, console, |graph, console| {
+                    let graph_ref = GraphRef(*graph.id());
+                    let loops = graph.combinational_loops();
+                    if loops.is_empty() {
+                        logln!(
+                            console,
+                            LogType::Info,
+                            "graph {graph_ref} has no loops without a delay or capacitor"
+                        );
+                    }
+                    for nodes in loops {
+                        logln!(
+                            console,
+                            LogType::Warning,
+                            "loop of {} nodes in graph {graph_ref} has no delay or capacitor: {}",
+                            nodes.len(),
+                            nodes
+                                .into_iter()
+                                .map(|id| NodeRef(*graph.id(), id).to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        );
+                    }
+                    false
+                });
+            }
+
+            Command::GraphGrid { size } => {
+                let is_modified = edit_focused_graph(tabs, console, |graph, console| {
+                    let graph_ref = GraphRef(*graph.id());
+                    if graph.grid_size() == size {
+                        logln!(
+                            console,
+                            LogType::Info,
+                            "graph {graph_ref} already has a grid size of {size}"
+                        );
+                        return false;
+                    }
+                    graph.set_grid_size(size);
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "graph {graph_ref} now has a grid size of {size}"
+                    );
+                    true
+                });
+                if is_modified {
+                    for tab in tabs.editors_mut() {
+                        tab.mark_grid_dirty();
+                    }
+                }
+                return is_modified.into();
+            }
+
+            Command::Name { target, text } => {
+                return edit_focused_graph(tabs, console, |graph, console| {
+                    let graph_ref = GraphRef(*graph.id());
+                    let Some(id) = target.resolve(graph) else {
+                        logln!(
+                            console,
+                            LogType::Error,
+                            "no node {target} in graph {graph_ref}"
+                        );
+                        return false;
+                    };
+                    let node_ref = graph_ref.node(id);
+                    let node = graph.node_mut(&id).expect("resolved node should be valid");
+                    let Some(text) = text else {
+                        match node.name() {
+                            // the link is shown as the name
+                            Some(_) => {
+                                logln!(console, LogType::Info, "{target} is named {node_ref}")
+                            }
+                            None => logln!(console, LogType::Info, "{node_ref} has no name"),
+                        }
+                        return false;
+                    };
+                    let prev = node.set_name(text);
+                    match node.name() {
+                        Some(_) => logln!(console, LogType::Info, "named {target} {node_ref}"),
+                        None => logln!(console, LogType::Info, "removed name of {node_ref}"),
+                    }
+                    prev.as_deref() != node.name()
+                })
+                .into();
+            }
+
+            Command::Note { target, text } => {
+                return edit_focused_graph(tabs, console, |graph, console| {
+                    let graph_ref = GraphRef(*graph.id());
+                    let (hyper_ref, mut note) = match target {
+                        NoteTarget::Node(node) => {
+                            let Some(id) = node.resolve(graph) else {
+                                logln!(
+                                    console,
+                                    LogType::Error,
+                                    "no node {node} in graph {graph_ref}"
+                                );
+                                return false;
+                            };
+                            let node = graph.node_mut(&id).expect("resolved node should be valid");
+                            (graph_ref.node(id).to_string(), NoteSlot::Node(node))
+                        }
+                        NoteTarget::Wire(id) => {
+                            let Some(wire) = graph.wire_mut(&id) else {
+                                logln!(
+                                    console,
+                                    LogType::Error,
+                                    "no wire {id} in graph {graph_ref}"
+                                );
+                                return false;
+                            };
+                            (graph_ref.wire(id).to_string(), NoteSlot::Wire(wire))
+                        }
+                    };
+                    if let Some(text) = text {
+                        let prev = note.set(text);
+                        match note.get() {
+                            Some(text) => {
+                                logln!(console, LogType::Info, "note on {hyper_ref}: {text}");
+                            }
+                            None => {
+                                logln!(console, LogType::Info, "removed note on {hyper_ref}");
+                            }
+                        }
+                        prev.as_deref() != note.get()
+                    } else {
+                        match note.get() {
+                            Some(text) => {
+                                logln!(console, LogType::Info, "note on {hyper_ref}: {text}");
+                            }
+                            None => {
+                                logln!(console, LogType::Info, "{hyper_ref} has no note");
+                            }
+                        }
+                        false
+                    }
+                })
+                .into();
+            }
+
+            Command::RegionCreate { bounds, label } => {
+                return edit_focused_graph(tabs, console, |graph, console| {
+                    graph.create_region(bounds, label, Ntd::default(), console);
+                    true
+                })
+                .into();
+            }
+
+            Command::RegionLabel { id, label } => {
+                return edit_focused_graph(tabs, console, |graph, console| {
+                    let Some(region) = graph.region_mut(&id) else {
+                        logln!(
+                            console,
+                            LogType::Error,
+                            "no region {id} in graph {}",
+                            GraphRef(*graph.id())
+                        );
+                        return false;
+                    };
+                    let is_changed = region.label != label;
+                    region.label = label;
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "region {id} is now {:?}",
+                        region.label
+                    );
+                    is_changed
+                })
+                .into();
+            }
+
+            Command::RegionColor { id, color } => {
+                return edit_focused_graph(tabs, console, |graph, console| {
+                    let Some(region) = graph.region_mut(&id) else {
+                        logln!(
+                            console,
+                            LogType::Error,
+                            "no region {id} in graph {}",
+                            GraphRef(*graph.id())
+                        );
+                        return false;
+                    };
+                    std::mem::replace(&mut region.color, color) != color
+                })
+                .into();
+            }
+
+            Command::RegionDestroy(id) => {
+                return edit_focused_graph(tabs, console, |graph, console| {
+                    if graph.destroy_region(&id).is_some() {
+                        logln!(console, LogType::Info, "destroy region {id}");
+                        true
+                    } else {
+                        logln!(
+                            console,
+                            LogType::Error,
+                            "no region {id} in graph {}",
+                            GraphRef(*graph.id())
+                        );
+                        false
+                    }
+                })
+                .into();
+            }
+
+            Command::Tool(id) => {
+                if !toolpane.set_tool(id, console) {
+                    logln!(console, LogType::Info, "tool is already {}", ToolRef(id));
+                }
+            }
+
+            Command::Gate(gate) => {
+                let change = match gate {
+                    GateArg::Exact(gate) => toolpane.set_exact_gate(gate, console),
+                    GateArg::Id(id) => toolpane.set_gate(id, console),
+                };
+                if !change {
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "gate is already {}",
+                        GateRef(toolpane.gate)
+                    );
+                }
+            }
+
+            Command::Zoom(ZoomArg(zoom_exp)) => match tabs.focused_tab_mut() {
+                Some(Tab::Editor(tab)) => {
+                    tab.set_zoom_exp(zoom_exp.into());
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "zoomed to {}",
+                        zoom_percent(zoom_exp.into())
+                    );
+                }
+                None => logln!(console, LogType::Error, "no tab is focused"),
+            },
+
+            Command::Snap(snap) => {
+                match tabs.focused_tab_mut() {
+                    Some(Tab::Editor(tab)) => {
+                        tab.set_snap(snap);
+                        logln!(console, LogType::Info, "snapping to {snap}");
+                    }
+                    None => logln!(console, LogType::Info, "no tab is focused"),
+                }
+                tabs.set_default_snap(snap);
+                if let Err(e) = config::save_entry("snap", &snap) {
+                    logln!(console, LogType::Error, "failed to save snap mode: {e}");
+                }
+            }
+
+            Command::Lock(locked) => match tabs.focused_tab_mut() {
+                Some(Tab::Editor(tab)) => {
+                    let locked = locked.unwrap_or(!tab.is_locked());
+                    tab.set_locked(locked);
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "{} the focused tab",
+                        if locked { "locked" } else { "unlocked" }
+                    );
+                }
+                None => logln!(console, LogType::Error, "no tab is focused"),
+            },
+
+            Command::ThemeSet { id, color } => {
+                theme[id] = color;
+                for tab in tabs.editors_mut() {
+                    tab.mark_grid_dirty();
+                }
+                logln!(console, LogType::Info, "set theme color {id}");
+            }
+
+            Command::Backup => backups.backup(console, graphs, tabs),
+
+            Command::Report { include_workspace } => {
+                logln!(console, LogType::Attempt, "writing issue report...");
+                match report::write_report(console, graphs, theme, include_workspace) {
+                    Ok(path) => logln!(
+                        console,
+                        LogType::Success,
+                        "wrote issue report to {}",
+                        path.display()
+                    ),
+                    Err(e) => logln!(console, LogType::Error, "failed to write issue report: {e}"),
+                }
+            }
+
+            Command::Export(settings) => {
+                return Outcome {
+                    export: Some(settings),
+                    ..Outcome::default()
+                };
+            }
+
+            Command::ExportObj(path) => {
+                edit_focused_graph(tabs, console, |graph, console| {
+                    logln!(
+                        console,
+                        LogType::Attempt,
+                        "exporting graph {} to {}...",
+                        GraphRef(*graph.id()),
+                        path.display()
+                    );
+                    match file::export_obj(graph, &path) {
+                        Ok(()) => {
+                            logln!(console, LogType::Success, "exported {}", path.display());
+                        }
+                        Err(e) => logln!(console, LogType::Error, "failed to export: {e}"),
+                    }
+                    false
+                });
+            }
+
+            Command::Open(path) => {
+                return Outcome {
+                    open: Some(path),
+                    ..Outcome::default()
+                };
+            }
+
+            Command::SaveAs(path) => {
+                return Outcome {
+                    save_as: Some(path),
+                    ..Outcome::default()
+                };
+            }
+
+            Command::ImportObj(path) => {
+                return Outcome {
+                    import: Some(path),
+                    ..Outcome::default()
+                };
+            }
+
+            Command::Merge(path) => {
+                return file::merge_path(console, graphs, tabs, &path, None).into();
+            }
+
+            Command::Binds(action) => {
+                return Outcome {
+                    preset: Some(action),
+                    ..Outcome::default()
+                };
+            }
+
+            Command::Macro(action) => {
+                return Outcome {
+                    input_macro: Some(action),
+                    ..Outcome::default()
+                };
+            }
+
+            Command::TraceProbe(target) => {
+                // probes are not saved, so this does not modify the graph
+                edit_focused_graph(tabs, console, |graph, console| {
+                    let graph_ref = GraphRef(*graph.id());
+                    match target.resolve(graph) {
+                        Some(id) => {
+                            let is_probed = graph
+                                .toggle_probe(&id)
+                                .expect("resolved node should be valid");
+                            logln!(
+                                console,
+                                LogType::Info,
+                                "{} {}",
+                                if is_probed {
+                                    "probing"
+                                } else {
+                                    "stopped probing"
+                                },
+                                graph_ref.node(id)
+                            );
+                        }
+                        None => logln!(
+                            console,
+                            LogType::Error,
+                            "no node {target} in graph {graph_ref}"
+                        ),
+                    }
+                    false
+                });
+            }
+
+            Command::TraceExport(path) => {
+                edit_focused_graph(tabs, console, |graph, console| {
+                    let graph_ref = GraphRef(*graph.id());
+                    let Some(trace) = graph.trace() else {
+                        logln!(
+                            console,
+                            LogType::Error,
+                            "no nodes of graph {graph_ref} are probed; probe some from their context menu"
+                        );
+                        return false;
+                    };
+                    match file::export_vcd(trace, &graph.id().to_string(), &path) {
+                        Ok(()) => logln!(
+                            console,
+                            LogType::Success,
+                            "exported {} ticks of {} probes to {}",
+                            trace.ticks(),
+                            trace.len(),
+                            path.display()
+                        ),
+                        Err(e) => logln!(console, LogType::Error, "failed to export: {e}"),
+                    }
+                    false
+                });
+            }
+
+            Command::TraceClear => {
+                edit_focused_graph(tabs, console, |graph, console| {
+                    graph.clear_trace();
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "stopped tracing graph {}",
+                        GraphRef(*graph.id())
+                    );
+                    false
+                });
+            }
+
+            Command::BenchEval { nodes, ticks } => {
+                logln!(
+                    console,
+                    LogType::Attempt,
+                    "benchmarking a random graph of {nodes} nodes for {ticks} ticks..."
+                );
+                let report = bench::bench_eval(nodes, ticks);
+                logln!(
+                    console,
+                    LogType::Success,
+                    "{} nodes, {} wires: eval order refreshed in {:?}, {} ticks in {:?} ({:?} per tick)",
+                    report.nodes,
+                    report.wires,
+                    report.refresh,
+                    report.ticks,
+                    report.eval,
+                    report.per_tick()
+                );
+            }
+        }
+        false.into()
+    }
+}
+
+/// Parses and executes a line typed into the console, logging the line first.
+pub fn run(
+    line: &str,
+    console: &mut Console,
+    graphs: &GraphList,
+    tabs: &mut TabList,
+    theme: &mut Theme,
+    toolpane: &mut ToolPane,
+    backups: &mut Backups,
+) -> Outcome {
+    logln!(console, LogType::Attempt, "> {line}");
+    match line.parse::<Command>() {
+        Ok(command) => command.execute(console, graphs, tabs, theme, toolpane, backups),
+        Err(e) => {
+            logln!(console, LogType::Error, "{e}");
+            Outcome::default()
+        }
+    }
+}
+
+/// Where submitted lines are kept between sessions, one per line.
+pub const HISTORY_PATH: &str = "console_history.txt";
+
+/// How many submitted lines are remembered, oldest forgotten first.
+const HISTORY_CAPACITY: usize = 500;
+
+/// Text being typed into the console, with the history of submitted lines.
+#[derive(Debug, Default)]
+pub struct CommandLine {
+    pub text: String,
+    history: Vec<String>,
+    /// Index into `history` while browsing it
+    history_cursor: Option<usize>,
+    /// What was being typed before browsing the history
+    draft: String,
+}
+
+impl CommandLine {
+    /// Starts with the history saved by [`Self::save_history`], if any.
+    pub fn load() -> Self {
+        let history = std::fs::read_to_string(HISTORY_PATH)
+            .map(|s| s.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        Self {
+            history,
+            ..Self::default()
+        }
+    }
+
+    pub fn save_history(&self) -> std::io::Result<()> {
+        let mut contents = self.history.join("\n");
+        contents.push('\n');
+        std::fs::write(HISTORY_PATH, contents)
+    }
+
+    /// What is left to type of the current command, see [`completion::hint`].
+    #[inline]
+    pub fn hint(&self) -> Option<String> {
+        completion::hint(&self.text)
+    }
+
+    /// Returns the line when it is submitted.
+    ///
+    /// `graph` is the focused graph, whose nodes and wires are completed by Tab.
+    pub fn tick(&mut self, rl: &mut RaylibHandle, graph: Option<&Graph>) -> Option<String> {
+        use KeyboardKey::*;
+
+        while let Some(c) = rl.get_char_pressed() {
+            if !c.is_control() {
+                self.text.push(c);
+                self.history_cursor = None;
+            }
+        }
+
+        if rl.is_key_pressed(KEY_BACKSPACE) || rl.is_key_pressed_repeat(KEY_BACKSPACE) {
+            self.text.pop();
+            self.history_cursor = None;
+        }
+
+        if rl.is_key_pressed(KEY_ESCAPE) {
+            self.text.clear();
+            self.history_cursor = None;
+        }
+
+        if rl.is_key_pressed(KEY_TAB)
+            && let Some(line) = completion::complete(&self.text, graph)
+        {
+            self.text = line;
+            self.history_cursor = None;
+        }
+
+        if rl.is_key_pressed(KEY_UP) && !self.history.is_empty() {
+            let n = match self.history_cursor {
+                Some(n) => n.saturating_sub(1),
+                None => {
+                    self.draft = std::mem::take(&mut self.text);
+                    self.history.len() - 1
+                }
+            };
+            self.history_cursor = Some(n);
+            self.text.clone_from(&self.history[n]);
+        }
+
+        if rl.is_key_pressed(KEY_DOWN)
+            && let Some(n) = self.history_cursor
+        {
+            if n + 1 < self.history.len() {
+                self.history_cursor = Some(n + 1);
+                self.text.clone_from(&self.history[n + 1]);
+            } else {
+                self.history_cursor = None;
+                self.text = std::mem::take(&mut self.draft);
+            }
+        }
+
+        if (rl.is_key_pressed(KEY_ENTER) || rl.is_key_pressed(KEY_KP_ENTER))
+            && !self.text.trim().is_empty()
+        {
+            self.history_cursor = None;
+            let line = std::mem::take(&mut self.text);
+            if self.history.last() != Some(&line) {
+                self.history.push(line.clone());
+                let excess = self.history.len().saturating_sub(HISTORY_CAPACITY);
+                self.history.drain(..excess);
+            }
+            return Some(line);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize(r#"  region create (0, 0) ( 16 ,16 ) "two \"word\" label" "#),
+            Ok([
+                "region",
+                "create",
+                "(0,0)",
+                "(16,16)",
+                r#"two "word" label"#
+            ]
+            .map(str::to_string)
+            .to_vec())
+        );
+        assert_eq!(
+            tokenize(r#"name n0 """#),
+            Ok(vec!["name".to_string(), "n0".to_string(), String::new()])
+        );
+        assert_eq!(tokenize("").map(|tokens| tokens.len()), Ok(0));
+        assert!(tokenize(r#"name n0 "text"#).is_err());
+        assert!(tokenize("node create or (0, 0").is_err());
+        assert!(tokenize("node create or 0, 0)").is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let n3 = "n3".parse::<NodeId>().unwrap();
+        for (s, command) in [
+            ("help", Command::Help),
+            (
+                "node create and (8, -16)",
+                Command::NodeCreate {
+                    gate: GateArg::Exact(Gate::And),
+                    position: IVec2::new(8, -16),
+                },
+            ),
+            (
+                "node destroy n3 soft",
+                Command::NodeDestroy {
+                    target: NodeTarget::Id(n3),
+                    soft: true,
+                },
+            ),
+            (
+                "wire create n3 (0,0)",
+                Command::WireCreate {
+                    src: NodeTarget::Id(n3),
+                    dst: NodeTarget::Position(IVec2::new(0, 0)),
+                },
+            ),
+            ("graph eval", Command::GraphEval { ticks: 1 }),
+            ("graph jitter off", Command::GraphJitter(None)),
+            (
+                "graph jitter 7",
+                Command::GraphJitter(Some(JitterArg::Seed(7))),
+            ),
+            (
+                "name n3 \"carry out\"",
+                Command::Name {
+                    target: NodeTarget::Id(n3),
+                    text: Some("carry out".to_string()),
+                },
+            ),
+            ("zoom 200%", Command::Zoom(ZoomArg(1))),
+            ("zoom actual", Command::Zoom(ZoomArg(0))),
+            ("lock", Command::Lock(None)),
+            ("lock off", Command::Lock(Some(false))),
+            (
+                "open saves/circuit0.ea",
+                Command::Open("saves/circuit0.ea".into()),
+            ),
+            (
+                "save as \"my graph.toml\"",
+                Command::SaveAs("my graph.toml".into()),
+            ),
+            (
+                "bench eval 64",
+                Command::BenchEval {
+                    nodes: 64,
+                    ticks: 100,
+                },
+            ),
+        ] {
+            assert_eq!(s.parse::<Command>(), Ok(command), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (s, err) in [
+            ("", "empty command"),
+            ("frobnicate", "unknown command: frobnicate; try \"help\""),
+            ("graph list all", "unexpected argument: all"),
+            ("log time maybe", "expected on or off: maybe"),
+            ("node create and", "missing position"),
+            ("node create and (8;16)", "invalid position: (8;16)"),
+            ("node destroy n3 hard", "unexpected argument: hard"),
+            ("graph grid 0", "invalid grid size: 0"),
+            ("zoom 300%", "invalid zoom: 300%"),
+            ("bench eval 0", "invalid node count: 0"),
+        ] {
+            assert_eq!(s.parse::<Command>(), Err(err.to_string()), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_usage() {
+        // every command listed should be known, even when missing its arguments
+        for (usage, _) in USAGE {
+            let command = usage
+                .split(' ')
+                .take_while(|word| !word.starts_with(['<', '[', '(']))
+                .collect::<Vec<_>>()
+                .join(" ");
+            if let Err(e) = command.parse::<Command>() {
+                assert!(!e.starts_with("unknown command"), "{usage}: {e}");
+            }
+        }
+    }
+}
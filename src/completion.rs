@@ -0,0 +1,210 @@
+//! Tab completion and inline hints for the console's input line, worked out from the usage of
+//! each command in [`USAGE`].
+
+use crate::{
+    command::USAGE,
+    graph::{Graph, node::GateId},
+    keybinds::PRESET_DIRECTORY,
+    tab::ZOOM_PRESETS,
+    theme::ColorId,
+    tool::ToolId,
+};
+
+/// Placeholders in [`USAGE`] that take any value, so there is nothing to suggest for them.
+const FREE_FORM: &[&str] = &[
+    "ticks",
+    "seed",
+    "size",
+    "value",
+    "scale",
+    "\"text\"",
+    "\"label\"",
+];
+
+/// Whether a word of a usage stands for an argument rather than being typed as is.
+fn is_placeholder(pattern: &str) -> bool {
+    pattern.starts_with(['<', '[', '(', '"'])
+}
+
+/// Splits `line` into the words before the one being typed, and the one being typed (which is
+/// empty after whitespace).
+fn split_line(line: &str) -> (Vec<&str>, &str) {
+    let start = line.trim_end_matches(|c: char| !c.is_whitespace()).len();
+    (line[..start].split_whitespace().collect(), &line[start..])
+}
+
+/// The words of every usage that `words` and then `partial` could be the start of.
+fn matching_usages<'a>(
+    words: &'a [&str],
+    partial: &'a str,
+) -> impl Iterator<Item = Vec<&'static str>> + 'a {
+    USAGE
+        .iter()
+        .map(|(usage, _)| usage.split_whitespace().collect::<Vec<_>>())
+        .filter(move |patterns| {
+            patterns.len() > words.len()
+                && patterns
+                    .iter()
+                    .zip(words)
+                    .all(|(pattern, word)| is_placeholder(pattern) || pattern == word)
+                && (is_placeholder(patterns[words.len()])
+                    || patterns[words.len()].starts_with(partial))
+        })
+}
+
+/// Files and directories starting with `partial`, directories ending in `/`.
+fn path_candidates(partial: &str) -> Vec<String> {
+    let dir = &partial[..partial.rfind('/').map_or(0, |i| i + 1)];
+    let Ok(entries) = std::fs::read_dir(if dir.is_empty() { "." } else { dir }) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            Some(if entry.file_type().ok()?.is_dir() {
+                format!("{dir}{name}/")
+            } else {
+                format!("{dir}{name}")
+            })
+        })
+        .collect()
+}
+
+/// Names of the presets saved in the [`PRESET_DIRECTORY`].
+fn preset_candidates() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(PRESET_DIRECTORY) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "toml" {
+                return None;
+            }
+            path.file_stem()?.to_str().map(str::to_string)
+        })
+        .collect()
+}
+
+/// Everything that could go where `pattern` is in a usage.
+fn pattern_candidates(pattern: &'static str, partial: &str, graph: Option<&Graph>) -> Vec<String> {
+    if !is_placeholder(pattern) {
+        return vec![pattern.to_string()];
+    }
+    let mut candidates = Vec::new();
+    for alternative in pattern.trim_matches(['<', '>', '[', ']']).split('|') {
+        match alternative {
+            "node" => candidates.extend(
+                graph
+                    .into_iter()
+                    .flat_map(Graph::nodes_iter)
+                    .map(|node| node.id().to_string()),
+            ),
+            "wire" => candidates.extend(
+                graph
+                    .into_iter()
+                    .flat_map(Graph::wires_iter)
+                    .map(|wire| wire.id().to_string()),
+            ),
+            "region" => candidates.extend(
+                graph
+                    .into_iter()
+                    .flat_map(Graph::regions_ordered)
+                    .map(|region| region.id().to_string()),
+            ),
+            "0-9" => candidates.extend((0..10).map(|n: u8| n.to_string())),
+            "gate" => candidates.extend(GateId::ALL.iter().map(GateId::to_string)),
+            "tool" => candidates.extend(ToolId::ALL.iter().map(ToolId::to_string)),
+            "color" => candidates.extend(ColorId::ALL.iter().map(ColorId::to_string)),
+            "percent" => candidates.extend(
+                ZOOM_PRESETS
+                    .iter()
+                    .map(|&zoom_exp| format!("{}%", 100.0 * 2.0f32.powi(zoom_exp.into()))),
+            ),
+            "preset" => {
+                candidates.extend(preset_candidates());
+                if partial.contains('/') || partial.starts_with('.') {
+                    candidates.extend(path_candidates(partial));
+                }
+            }
+            "path" => candidates.extend(path_candidates(partial)),
+            "(x,y)" => {}
+            free if FREE_FORM.contains(&free) => {}
+            literal => candidates.push(literal.to_string()),
+        }
+    }
+    candidates
+}
+
+/// `line` with its last word completed as far as every possibility for it agrees, or [`None`]
+/// if there is nothing to add.
+pub fn complete(line: &str, graph: Option<&Graph>) -> Option<String> {
+    let (words, partial) = split_line(line);
+    let mut candidates = matching_usages(&words, partial)
+        .flat_map(|patterns| pattern_candidates(patterns[words.len()], partial, graph))
+        .filter(|candidate| candidate.starts_with(partial))
+        .collect::<Vec<_>>();
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    let completed = match candidates.as_slice() {
+        [] => return None,
+        // directories may be followed by more of the path
+        [only] if only.ends_with('/') => only.clone(),
+        [only] => format!("{only} "),
+        [first, rest @ ..] => {
+            let len = first
+                .char_indices()
+                .map(|(i, c)| i + c.len_utf8())
+                .take_while(|&i| rest.iter().all(|other| other.get(..i) == first.get(..i)))
+                .last()
+                .unwrap_or(0);
+            first[..len].to_string()
+        }
+    };
+    (completed.len() > partial.len() || completed.ends_with(' ')).then(|| {
+        let mut line = line[..line.len() - partial.len()].to_string();
+        line.push_str(&completed);
+        line
+    })
+}
+
+/// What is left to type of the command being typed, shown after the text: the rest of the
+/// usage, up to and including the first word with more than one choice.
+pub fn hint(line: &str) -> Option<String> {
+    if line.is_empty() {
+        return None;
+    }
+    let (words, partial) = split_line(line);
+    let usages = matching_usages(&words, partial).collect::<Vec<_>>();
+    let mut hint = String::new();
+    for n in words.len().. {
+        let mut choices = Vec::new();
+        for pattern in usages.iter().filter_map(|patterns| patterns.get(n)) {
+            if !choices.contains(pattern) {
+                choices.push(*pattern);
+            }
+        }
+        let is_current = n == words.len();
+        match choices.as_slice() {
+            [] => break,
+            // the rest of the word being typed
+            [pattern] if is_current && !partial.is_empty() => {
+                if !is_placeholder(pattern) {
+                    hint.push_str(&pattern[partial.len()..]);
+                }
+            }
+            choices => {
+                if !is_current || !partial.is_empty() {
+                    hint.push(' ');
+                }
+                hint.push_str(&choices.join("|"));
+            }
+        }
+        if choices.len() > 1 {
+            break;
+        }
+    }
+    (!hint.trim().is_empty()).then_some(hint)
+}
@@ -1,9 +1,208 @@
-use crate::{input::Bindings, theme::Theme};
+use crate::{
+    DEFAULT_GRID_SIZE,
+    backup::BackupSettings,
+    console::{Console, LogType},
+    graph::{limits::FanLimits, node::GateId},
+    input::Bindings,
+    log_file::LogFileSettings,
+    logln,
+    sound::SoundSettings,
+    tab::SnapMode,
+    theme::Theme,
+};
+use serde::Serialize;
 use serde_derive::{Deserialize, Serialize};
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime},
+};
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+pub const CONFIG_PATH: &str = "config.toml";
+/// How often [`ConfigWatcher`] looks at the config file.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
+    /// Grid size of new graphs; opened graphs keep the one they were saved with
+    #[serde(default = "crate::default_grid_size")]
+    pub grid_size: u8,
+    /// Where nodes go in new tabs; each tab can be changed with the `snap` command
+    #[serde(default)]
+    pub snap: SnapMode,
+    /// Order of the toolpane's gate buttons; gates left out follow in their usual order
+    #[serde(default)]
+    pub gate_order: Vec<GateId>,
     pub theme: Theme,
     #[serde(rename = "input")]
     pub binds: Bindings,
+    #[serde(default)]
+    pub fan_limits: FanLimits,
+    #[serde(default)]
+    pub backup: BackupSettings,
+    /// Whether to point out the panels on startup; only new configs start with it on
+    #[serde(default)]
+    pub show_onboarding: bool,
+    #[serde(default)]
+    pub sound: SoundSettings,
+    #[serde(default)]
+    pub background: BackgroundSettings,
+    #[serde(default)]
+    pub log_file: LogFileSettings,
+    /// [Locale](crate::locale) file to translate the UI with; English if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<PathBuf>,
+    /// How many times larger to draw the UI; detected from the monitor's DPI if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ui_scale: Option<f32>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            grid_size: DEFAULT_GRID_SIZE,
+            snap: SnapMode::default(),
+            gate_order: GateId::ALL.to_vec(),
+            theme: Theme::default(),
+            binds: Bindings::default(),
+            fan_limits: FanLimits::default(),
+            backup: BackupSettings::default(),
+            show_onboarding: true,
+            sound: SoundSettings::default(),
+            background: BackgroundSettings::default(),
+            log_file: LogFileSettings::default(),
+            locale: None,
+            ui_scale: None,
+        }
+    }
+}
+
+/// Slower rates used while the window is unfocused, so that a simulation left running in the
+/// background uses less power.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackgroundSettings {
+    /// Whether to slow down at all while unfocused.
+    pub enabled: bool,
+    /// Frame rate limit while unfocused.
+    pub fps: u32,
+    /// Milliseconds between evaluation ticks while unfocused; 0 pauses evaluation.
+    pub eval_interval_ms: u64,
+}
+
+impl Default for BackgroundSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            fps: 10,
+            eval_interval_ms: 1000,
+        }
+    }
+}
+
+impl BackgroundSettings {
+    /// Time between evaluation ticks while unfocused, or [`None`] if evaluation is paused.
+    #[inline]
+    pub const fn eval_interval(&self) -> Option<Duration> {
+        match self.eval_interval_ms {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+}
+
+/// Writes `value` over the top-level `key` of the config file, keeping the rest of the file's
+/// settings. Comments in the file are not kept.
+pub fn save_entry<T: Serialize + ?Sized>(key: &str, value: &T) -> std::io::Result<()> {
+    let value = toml::Value::try_from(value).map_err(std::io::Error::other)?;
+    let mut config = match std::fs::read_to_string(CONFIG_PATH) {
+        Ok(s) => s.parse::<toml::Table>().map_err(std::io::Error::other)?,
+        // the rest of the config is needed for it to load
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            toml::Table::try_from(Config::default()).map_err(std::io::Error::other)?
+        }
+        Err(e) => return Err(e),
+    };
+    config.insert(key.to_string(), value);
+    let s = toml::to_string_pretty(&config).map_err(std::io::Error::other)?;
+    std::fs::write(CONFIG_PATH, s)
+}
+
+/// Notices when the theme in the config file is edited outside the program.
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    modified: Option<SystemTime>,
+    /// The file's theme as last seen, so that saving other settings doesn't reload it
+    theme: Option<toml::Value>,
+    next_check: Instant,
+}
+
+impl Default for ConfigWatcher {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigWatcher {
+    pub fn new() -> Self {
+        Self {
+            modified: Self::modified(),
+            theme: std::fs::read_to_string(CONFIG_PATH)
+                .ok()
+                .and_then(|s| s.parse::<toml::Table>().ok())
+                .and_then(|mut config| config.remove("theme")),
+            next_check: Instant::now() + WATCH_INTERVAL,
+        }
+    }
+
+    fn modified() -> Option<SystemTime> {
+        std::fs::metadata(CONFIG_PATH)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// Returns the file's theme if it changed since it was last seen. Its assets still need
+    /// to be loaded.
+    pub fn tick(&mut self, console: &mut Console) -> Option<Theme> {
+        let now = Instant::now();
+        if now < self.next_check {
+            return None;
+        }
+        self.next_check = now + WATCH_INTERVAL;
+
+        let modified = Self::modified();
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+
+        let theme = match std::fs::read_to_string(CONFIG_PATH)
+            .map_err(|e| e.to_string())
+            .and_then(|s| s.parse::<toml::Table>().map_err(|e| e.to_string()))
+        {
+            Ok(mut config) => config.remove("theme"),
+            Err(e) => {
+                logln!(console, LogType::Error, "failed to reload config: {e}");
+                return None;
+            }
+        };
+        if theme == self.theme {
+            return None;
+        }
+        self.theme.clone_from(&theme);
+        match theme?.try_into::<Theme>() {
+            Ok(theme) => {
+                logln!(
+                    console,
+                    LogType::Success,
+                    "Theme reloaded from {CONFIG_PATH}."
+                );
+                Some(theme)
+            }
+            Err(e) => {
+                logln!(console, LogType::Error, "failed to reload theme: {e}");
+                None
+            }
+        }
+    }
 }
@@ -1,21 +1,173 @@
 use crate::{
-    GRID_SIZE,
+    backup::civil_date,
+    command::CommandLine,
     graph::{
         Graph, GraphId, GraphList,
         node::{Gate, Node, NodeId},
         wire::{Wire, WireId},
     },
+    icon_sheets::ButtonIconId,
     input::Inputs,
     ivec::{AsIVec2, IBounds, IRect, IVec2},
-    rich_text::{ColorAct, ColorRef, RichStr, RichString},
-    tab::TabList,
-    theme::{ColorId, Theme},
+    log_file::LogFile,
+    logln,
+    rich_text::{ColorAct, ColorRef, IconAct, RichSpan, RichString},
+    tab::{Tab, TabList},
+    theme::{ColorId, Theme, ThemeFont},
     tool::ToolId,
     toolpane::{ButtonAction, ToolPane},
-    ui::{Panel, PanelContent},
+    ui::{Direction, Panel, PanelContent},
 };
 use raylib::prelude::*;
-use std::sync::{Arc, RwLock, RwLockReadGuard};
+use rustc_hash::FxHashSet;
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    path::Path,
+    sync::{
+        Arc, OnceLock, RwLock, RwLockReadGuard,
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{Receiver, SyncSender, TryRecvError, sync_channel},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// How many deferred lines can wait for [`Console::drain_deferred`] before more are dropped.
+const DEFERRED_CAPACITY: usize = 256;
+/// Width of the scrollbar along the right of the log.
+const SCROLLBAR_WIDTH: f32 = 8.0;
+/// Shortest the scrollbar thumb gets, so that it can still be grabbed in a long log.
+const MIN_THUMB_HEIGHT: f32 = 16.0;
+/// Most errors and warnings kept for [`Console::take_alerts`], after which the oldest are dropped.
+const MAX_ALERTS: usize = 16;
+
+/// Where [`log_deferred`] sends records, once [`Console::install_deferred`] has been called.
+static DEFERRED_SENDER: OnceLock<SyncSender<LogRecord>> = OnceLock::new();
+
+/// Deferred records dropped because the queue was full, reported by the next drain.
+static DEFERRED_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+/// Queues a record for the console from anywhere, without the console.
+///
+/// This never blocks or waits on the console, so it is safe from FFI callbacks and other
+/// threads. The record is only colored and added to the log by [`Console::drain_deferred`].
+/// Records are dropped (and counted) if the queue is full or not installed yet.
+pub fn log_deferred(record: LogRecord) {
+    let is_sent = DEFERRED_SENDER
+        .get()
+        .is_some_and(|sender| sender.try_send(record).is_ok());
+    if !is_sent {
+        DEFERRED_DROPPED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Passed to raylib to receive its trace log, which may be called from inside any raylib
+/// function.
+pub fn raylib_trace_log(level: raylib::core::callbacks::TraceLogLevel, text: &str) {
+    use raylib::core::callbacks::TraceLogLevel;
+    let ty = match level {
+        TraceLogLevel::LOG_WARNING => LogType::Warning,
+        TraceLogLevel::LOG_ERROR | TraceLogLevel::LOG_FATAL => LogType::Error,
+        TraceLogLevel::LOG_DEBUG | TraceLogLevel::LOG_TRACE => LogType::Debug,
+        _ => LogType::Info,
+    };
+    log_deferred(LogRecord::new(
+        ty,
+        LogSource::Raylib,
+        text.trim_end().to_owned(),
+    ));
+}
+
+/// What part of the program a message came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LogSource {
+    /// raylib's own trace log
+    Raylib,
+    /// Graphs and their evaluation
+    Graph,
+    /// Reading and writing files
+    File,
+    #[default]
+    Ui,
+}
+
+impl std::fmt::Display for LogSource {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogSource::Raylib => "raylib",
+            LogSource::Graph => "graph",
+            LogSource::File => "file",
+            LogSource::Ui => "ui",
+        }
+        .fmt(f)
+    }
+}
+
+impl LogSource {
+    pub const ALL: [Self; 4] = [Self::Raylib, Self::Graph, Self::File, Self::Ui];
+
+    /// The source of messages logged from the module at `path`, as given by [`module_path!`].
+    pub fn of_module(path: &str) -> Self {
+        match path.split("::").nth(1) {
+            Some("graph") => Self::Graph,
+            Some("backup" | "config" | "export" | "file" | "keybinds" | "report") => Self::File,
+            _ => Self::Ui,
+        }
+    }
+}
+
+/// A message for the log, before it is colored and added to the console.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub time: SystemTime,
+    pub level: LogType,
+    pub source: LogSource,
+    /// May contain [rich text](crate::rich_text) escape codes, without a trailing newline
+    pub message: String,
+}
+
+impl LogRecord {
+    /// A record made now.
+    pub fn new(level: LogType, source: LogSource, message: String) -> Self {
+        Self {
+            time: SystemTime::now(),
+            level,
+            source,
+            message,
+        }
+    }
+}
+
+/// `HH:MM:SS` of `time` in UTC, followed by `.mmm` if `with_millis`.
+fn format_time_of_day(time: SystemTime, with_millis: bool) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs() % 86400;
+    let mut text = format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    );
+    if with_millis {
+        _ = write!(text, ".{:03}", since_epoch.subsec_millis());
+    }
+    text
+}
+
+/// `time` in UTC as `YYYY-MM-DDTHH:MM:SS.mmmZ`.
+fn format_timestamp(time: SystemTime) -> String {
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / 86400;
+    let (year, month, day) = civil_date(days);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{}Z",
+        format_time_of_day(time, true)
+    )
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub enum LogType {
@@ -51,6 +203,15 @@ impl From<LogType> for ColorRef {
 }
 
 impl LogType {
+    pub const ALL: [Self; 6] = [
+        Self::Info,
+        Self::Debug,
+        Self::Attempt,
+        Self::Success,
+        Self::Warning,
+        Self::Error,
+    ];
+
     #[inline]
     pub const fn color(self) -> ColorRef {
         match self {
@@ -88,7 +249,8 @@ impl std::fmt::Display for GateRef {
         let &Self(g) = self;
         write!(
             f,
-            "{}[{g}]{}",
+            "{}{}[{g}]{}",
+            IconAct(g.id().into()),
             ColorAct::Push(ColorRef::Theme(ColorId::HyperRef)),
             ColorAct::Pop
         )
@@ -129,6 +291,9 @@ impl std::ops::DerefMut for ToolRef {
 impl std::fmt::Display for ToolRef {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let &Self(g) = self;
+        if let Some(icon) = ButtonIconId::of_tool(g) {
+            write!(f, "{}", IconAct(icon))?;
+        }
         write!(
             f,
             "{}[{g}]{}",
@@ -366,6 +531,76 @@ impl std::str::FromStr for HyperRef {
 }
 
 impl HyperRef {
+    /// What clicking the reference in the console does.
+    pub fn follow(
+        self,
+        console: &mut Console,
+        graphs: &GraphList,
+        tabs: &mut TabList,
+        toolpane: &mut ToolPane,
+    ) {
+        // where in which graph to show
+        let (graph, world_pos) = match self {
+            HyperRef::Gate(GateRef(gate)) => {
+                toolpane.set_gate(gate.id(), console);
+                if let Some(ntd) = gate.ntd() {
+                    toolpane.set_ntd(ntd, console);
+                }
+                return;
+            }
+
+            HyperRef::Tool(ToolRef(tool_id)) => {
+                toolpane.set_tool(tool_id, console);
+                return;
+            }
+
+            HyperRef::Position(PositionRef(pos)) => {
+                match tabs.focused_tab_mut() {
+                    Some(Tab::Editor(tab)) => {
+                        tab.animate_center_on(pos.as_vec2() + tab.cell_center());
+                    }
+                    None => logln!(console, LogType::Info, "no tab to show {self} in"),
+                }
+                return;
+            }
+
+            HyperRef::Graph(graph_ref) => (
+                graph_ref.deref_with(graphs, |g, _borrow| Arc::downgrade(g)),
+                None,
+            ),
+
+            HyperRef::Node(node_ref) => node_ref
+                .deref_with(graphs, |g, borrow, node| {
+                    (
+                        Arc::downgrade(g),
+                        node.position().as_vec2() + borrow.cell_center(),
+                    )
+                })
+                .unzip(),
+
+            HyperRef::Wire(wire_ref) => wire_ref
+                .deref_with(graphs, |g, borrow, wire| {
+                    let [_, elbow_pos, _] = wire
+                        .points(borrow, borrow.cell_center())
+                        .expect("all wires should be valid");
+                    (Arc::downgrade(g), elbow_pos)
+                })
+                .unzip(),
+        };
+
+        let Some(graph) = graph else {
+            logln!(console, LogType::Warning, "{self} no longer exists");
+            return;
+        };
+        let Some(tab) = tabs.focus_graph(&graph) else {
+            logln!(console, LogType::Info, "no tab shows {self}");
+            return;
+        };
+        if let Some(world_pos) = world_pos {
+            tab.animate_center_on(world_pos);
+        }
+    }
+
     fn draw_link<D>(
         &self,
         d: &mut D,
@@ -377,9 +612,6 @@ impl HyperRef {
     ) where
         D: RaylibDraw,
     {
-        const GRID_CENTER_OFFSET: Vector2 =
-            Vector2::new((GRID_SIZE / 2) as f32, (GRID_SIZE / 2) as f32);
-
         // highlight ref text
         d.draw_rectangle(rec.x, rec.y, rec.w, rec.h, theme.hyperref.alpha(0.2));
 
@@ -428,7 +660,7 @@ impl HyperRef {
 
             HyperRef::Position(position_ref) => {
                 for tab in tabs.editors() {
-                    let pos = tab.world_to_screen(position_ref.as_vec2() + GRID_CENTER_OFFSET);
+                    let pos = tab.world_to_screen(position_ref.as_vec2() + tab.cell_center());
                     d.draw_line_v(link_anchor, pos, theme.hyperref);
                 }
             }
@@ -442,10 +674,10 @@ impl HyperRef {
             }
 
             HyperRef::Node(node_ref) => {
-                node_ref.deref_with(graphs, |g, _borrow, node| {
+                node_ref.deref_with(graphs, |g, borrow, node| {
                     for tab in tabs.editors_of_graph(&Arc::downgrade(g)) {
                         let pos =
-                            tab.world_to_screen(node.position().as_vec2() + GRID_CENTER_OFFSET);
+                            tab.world_to_screen(node.position().as_vec2() + borrow.cell_center());
                         d.draw_line_v(link_anchor, pos, theme.hyperref);
                     }
                 });
@@ -454,12 +686,10 @@ impl HyperRef {
             HyperRef::Wire(wire_ref) => {
                 wire_ref.deref_with(graphs, |g, borrow, wire| {
                     for tab in tabs.editors_of_graph(&Arc::downgrade(g)) {
-                        let (start, end) = borrow
-                            .get_wire_nodes(wire)
+                        let [_, elbow_pos, _] = wire
+                            .points(borrow, borrow.cell_center())
                             .expect("all wires should be valid");
-                        let start_pos = start.position().as_vec2() + GRID_CENTER_OFFSET;
-                        let end_pos = end.position().as_vec2() + GRID_CENTER_OFFSET;
-                        let pos = tab.world_to_screen(wire.elbow.calculate(start_pos, end_pos));
+                        let pos = tab.world_to_screen(elbow_pos);
                         d.draw_line_v(link_anchor, pos, theme.hyperref);
                     }
                 });
@@ -476,11 +706,135 @@ pub struct ConsoleAnchoring {
     pub bottom: bool,
 }
 
+/// Something done in the console that affects the rest of the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleAction {
+    /// A line was entered into the command line.
+    Command(String),
+    /// A hyper-ref was clicked.
+    Follow(HyperRef),
+    /// A node's hyper-ref was right-clicked, to watch it.
+    Watch(NodeRef),
+}
+
+/// Starts a search of the log when typed at the start of the command line.
+pub const SEARCH_PREFIX: char = '/';
+
+/// A single line of the log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LogLine {
+    /// [`None`] for lines logged without a type, which are never hidden
+    ty: Option<LogType>,
+    source: LogSource,
+    /// When the record the line belongs to was made
+    time: SystemTime,
+    /// Ends with a newline unless it is the last line and still being written
+    text: RichString,
+}
+
+/// The name of the node `span` links to, which is shown in place of its ID, if it has one.
+fn node_name(span: RichSpan<'_>, graphs: &GraphList) -> Option<String> {
+    match span.text.parse::<HyperRef>() {
+        Ok(HyperRef::Node(node_ref)) => node_ref
+            .deref_with(graphs, |_, _, node| node.name().map(str::to_string))
+            .flatten(),
+        _ => None,
+    }
+}
+
+impl LogLine {
+    /// The parts of the line drawn differently, and the color of each.
+    fn segments(&self) -> impl Iterator<Item = (ColorRef, RichSpan<'_>)> {
+        // lines after the first of a multi-line entry have no color of their own
+        let base_color = self
+            .ty
+            .map_or(ColorRef::Theme(ColorId::Foreground), LogType::color);
+        self.text.as_rich_str().iter().map(move |item| match item {
+            Ok(span) => (span.color.unwrap_or(base_color), span),
+            Err(e) => panic!("{e}"),
+        })
+    }
+
+    /// The line without colors or its newline.
+    fn plain_text(&self) -> String {
+        let mut text = self
+            .segments()
+            .map(|(_, span)| span.text)
+            .collect::<String>();
+        if text.ends_with('\n') {
+            text.pop();
+        }
+        text
+    }
+
+    /// Left of the byte at `offset` into [`Self::plain_text`] when the line is drawn with `font`
+    /// from `left`.
+    fn x_of(&self, font: &ThemeFont, left: f32, offset: usize) -> f32 {
+        let mut x = left;
+        let mut start = 0;
+        for (_, span) in self.segments() {
+            x += span.icon_width(font);
+            if offset <= start + span.text.len() {
+                return x + font
+                    .measure_styled_text(&span.text[..offset - start], span.style)
+                    .x;
+            }
+            x += font.measure_styled_text(span.text, span.style).x;
+            start += span.text.len();
+        }
+        x
+    }
+
+    /// The line as exported: when it was logged, its source and its plain text.
+    fn export_line(&self) -> String {
+        format!(
+            "{} {:<6} {}",
+            format_timestamp(self.time),
+            self.source,
+            self.plain_text()
+        )
+    }
+
+    /// Byte ranges of [`Self::plain_text`] containing `query`, ignoring ASCII case.
+    fn matches(&self, query: &str) -> Vec<std::ops::Range<usize>> {
+        let text = self.plain_text().to_ascii_lowercase();
+        let query = query.to_ascii_lowercase();
+        text.match_indices(&query)
+            .map(|(n, s)| n..n + s.len())
+            .collect()
+    }
+}
+
 #[derive(Debug)]
 pub struct Console {
-    content: RichString,
+    records: VecDeque<LogLine>,
+    /// Total length of the text of `records`
+    len: usize,
+    /// How long `len` may get before the oldest lines are dropped
+    capacity: usize,
+    /// Types of lines not shown
+    hidden: FxHashSet<LogType>,
+    /// Whether to show when each line was logged
+    pub show_time: bool,
+    /// Whether to show lines from the same [`LogSource`] together, labeled with their source
+    pub group_by_source: bool,
+    /// Lines ever logged as [`LogType::Error`], including ones no longer in `records`
+    error_count: u64,
+    /// Shown lines scrolled back from the newest
     pub bottom_offset: f64,
+    /// Shown lines logged since the log was scrolled back from the newest, which are waiting
+    /// below the view
+    unseen_lines: usize,
+    /// How far below the top of the scrollbar thumb it was grabbed, while it is being dragged
+    thumb_grab: Option<f32>,
+    pub command_line: CommandLine,
     pub panel: Panel,
+    /// Errors and warnings logged since the last [`Self::take_alerts`]
+    alerts: VecDeque<LogRecord>,
+    /// Receives [`log_deferred`] lines, if this is the console they go to
+    deferred: Option<Receiver<LogRecord>>,
+    /// Where each line is also written once it is complete
+    file: Option<LogFile>,
 }
 
 impl PanelContent for Console {
@@ -503,12 +857,92 @@ impl PanelContent for Console {
 impl Console {
     pub fn new(panel: Panel, capacity: usize) -> Self {
         Self {
-            content: RichString::with_capacity(capacity),
+            records: VecDeque::new(),
+            len: 0,
+            capacity,
+            hidden: FxHashSet::default(),
+            show_time: false,
+            group_by_source: false,
+            error_count: 0,
             bottom_offset: 0.0,
+            unseen_lines: 0,
+            thumb_grab: None,
+            command_line: CommandLine::default(),
             panel,
+            alerts: VecDeque::new(),
+            deferred: None,
+            file: None,
+        }
+    }
+
+    /// Starts or stops writing each line to `file` as well, starting with the lines already
+    /// logged.
+    pub fn set_file(&mut self, file: Option<LogFile>) {
+        self.file = file;
+        let Some(file) = &mut self.file else {
+            return;
+        };
+        let result = self
+            .records
+            .iter()
+            .filter(|line| line.text.ends_with('\n'))
+            .try_for_each(|line| file.write_line(&line.export_line()));
+        if let Err(e) = result {
+            self.file = None;
+            logln!(
+                self,
+                LogType::Warning,
+                "failed to write the log to a file: {e}"
+            );
         }
     }
 
+    /// Makes this console the one [`log_deferred`] records go to. Only the first console
+    /// installed receives them.
+    pub fn install_deferred(&mut self) -> Result<(), ()> {
+        let (sender, receiver) = sync_channel(DEFERRED_CAPACITY);
+        DEFERRED_SENDER.set(sender).map_err(|_| ())?;
+        self.deferred = Some(receiver);
+        Ok(())
+    }
+
+    /// Logs the records queued by [`log_deferred`] since the last drain.
+    pub fn drain_deferred(&mut self) {
+        let Some(receiver) = self.deferred.take() else {
+            return;
+        };
+        loop {
+            match receiver.try_recv() {
+                Ok(record) => self.log_record(record),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => unreachable!("the sender is never dropped"),
+            }
+        }
+        self.deferred = Some(receiver);
+        let dropped = DEFERRED_DROPPED.swap(0, Ordering::Relaxed);
+        if dropped > 0 {
+            logln!(
+                self,
+                LogType::Warning,
+                "{dropped} log lines were dropped while the log was busy"
+            );
+        }
+    }
+
+    /// The errors and warnings logged since this was last called, oldest first.
+    pub fn take_alerts(&mut self) -> impl Iterator<Item = LogRecord> {
+        std::mem::take(&mut self.alerts).into_iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.len = 0;
+        self.bottom_offset = 0.0;
+        self.unseen_lines = 0;
+    }
+
+    /// Logs text without a type, which is shown whatever the filter.
+    ///
     /// NOTE: You will need to append with newline
     pub fn log(&mut self, text: std::fmt::Arguments<'_>) {
         let buf;
@@ -519,139 +953,487 @@ impl Console {
                 buf.as_str()
             }
         };
+        self.push(None, LogSource::Ui, SystemTime::now(), s);
+    }
+
+    /// Colors `record` by its level and logs it on its own line.
+    pub fn log_record(&mut self, record: LogRecord) {
+        if record.level == LogType::Error {
+            self.error_count += 1;
+        }
+        if matches!(record.level, LogType::Error | LogType::Warning) {
+            if self.alerts.len() == MAX_ALERTS {
+                self.alerts.pop_front();
+            }
+            self.alerts.push_back(record.clone());
+        }
+        let text = format!(
+            "{}[{}]: {}{}\n",
+            ColorAct::Push(record.level.into()),
+            record.level,
+            record.message,
+            ColorAct::Pop,
+        );
+        self.push(Some(record.level), record.source, record.time, &text);
+    }
+
+    fn push(&mut self, ty: Option<LogType>, source: LogSource, time: SystemTime, s: &str) {
+        let mut file_error = None;
         for mut line in s.split_inclusive('\n') {
-            if line.len() > self.content.capacity() {
-                self.content.clear();
-                line = &line[line.ceil_char_boundary(line.len() - self.content.capacity())..];
-            } else {
-                while self.content.len() + line.len() > self.content.capacity() {
-                    debug_assert!(
-                        !self.content.is_empty(),
-                        "if `line` exceeds capacity all by itself, this branch shouldn't have been reached"
-                    );
-                    match self.content.find('\n') {
-                        Some(n) => self.content.replace_range(..n + '\n'.len_utf8(), ""),
-                        None => self.content.clear(),
-                    }
-                }
+            if line.len() > self.capacity {
+                self.clear();
+                line = &line[line.ceil_char_boundary(line.len() - self.capacity)..];
+            }
+            match self.records.back_mut() {
+                Some(last) if !last.text.ends_with('\n') => last.text.push_str(line),
+                _ => self.records.push_back(LogLine {
+                    ty,
+                    source,
+                    time,
+                    text: RichString::from(line),
+                }),
+            }
+            if line.ends_with('\n')
+                && let Some(file) = &mut self.file
+                && let Some(last) = self.records.back()
+                && let Err(e) = file.write_line(&last.export_line())
+            {
+                self.file = None;
+                file_error = Some(e);
+            }
+            self.len += line.len();
+            while self.len > self.capacity {
+                let oldest = self
+                    .records
+                    .pop_front()
+                    .expect("`len` should be the length of the records");
+                self.len -= oldest.text.len();
             }
-            debug_assert!(
-                self.content.len() + line.len() <= self.content.capacity(),
-                "content should not grow"
+        }
+        if self.bottom_offset != 0.0 && ty.is_none_or(|ty| self.is_shown(ty)) {
+            // keep showing the same lines while reading scrollback
+            let count = s.matches('\n').count();
+            self.bottom_offset += count as f64;
+            self.unseen_lines += count;
+        }
+        if let Some(e) = file_error {
+            logln!(
+                self,
+                LogType::Warning,
+                "stopped writing the log to a file: {e}"
             );
-            self.content.push_str(line);
         }
-        self.bottom_offset = 0.0;
     }
 
+    /// The number of lines kept, and roughly how many bytes they take up.
+    pub fn buffer_usage(&self) -> (usize, usize) {
+        let text = self
+            .records
+            .iter()
+            .map(|line| line.text.capacity())
+            .sum::<usize>();
+        (
+            self.records.len(),
+            self.records.capacity() * size_of::<LogLine>() + text,
+        )
+    }
+
+    /// Number of lines ever logged as [`LogType::Error`], so that new errors can be noticed.
     #[inline]
-    pub const fn content_str(&self) -> &RichStr {
-        self.content.as_rich_str()
+    pub const fn error_count(&self) -> u64 {
+        self.error_count
     }
 
     #[inline]
-    pub fn displayable_lines(&self, theme: &Theme) -> usize {
-        ((self.panel.content_bounds(theme).height()
-            + /* Off by one otherwise */ theme.console_font.line_spacing)
-            / theme.console_font.line_height()) as usize
+    pub fn is_shown(&self, ty: LogType) -> bool {
+        !self.hidden.contains(&ty)
+    }
+
+    /// Shows or hides lines of type `ty`, keeping the newest lines in view.
+    pub fn set_shown(&mut self, ty: LogType, is_shown: bool) {
+        if is_shown {
+            self.hidden.remove(&ty);
+        } else {
+            self.hidden.insert(ty);
+        }
+        self.bottom_offset = 0.0;
+    }
+
+    /// Shows only lines of type `ty`, or every line if that is already the case.
+    pub fn solo(&mut self, ty: LogType) {
+        let is_solo = LogType::ALL
+            .into_iter()
+            .all(|other| self.is_shown(other) == (other == ty));
+        for other in LogType::ALL {
+            self.set_shown(other, is_solo || other == ty);
+        }
+    }
+
+    /// What is being searched for, if the command line holds a search.
+    pub fn search_query(&self) -> Option<&str> {
+        self.command_line
+            .text
+            .strip_prefix(SEARCH_PREFIX)
+            .filter(|query| !query.is_empty())
     }
 
-    pub fn content(&self) -> impl Iterator<Item = (ColorRef, &str)> {
-        let mut last_color = ColorRef::Theme(ColorId::Foreground);
-        RichStr::new(self.content.as_str())
+    /// The lines not hidden by the filter, oldest first, or by source then oldest first if
+    /// grouping by source.
+    fn shown_lines(&self) -> Vec<&LogLine> {
+        let mut lines = self
+            .records
             .iter()
-            .map(move |item| match item {
-                Ok((color, text)) => {
-                    if let Some(color) = color {
-                        last_color = color;
-                    }
-                    (last_color, text)
-                }
-                Err(e) => panic!("{e}"),
-            })
+            .filter(|line| line.ty.is_none_or(|ty| self.is_shown(ty)))
+            .collect::<Vec<_>>();
+        if self.group_by_source {
+            // stable, so each group stays in the order it was logged
+            lines.sort_by_key(|line| {
+                LogSource::ALL
+                    .iter()
+                    .position(|source| *source == line.source)
+            });
+        }
+        lines
     }
 
-    pub fn visible_content(&self, theme: &Theme) -> impl Iterator<Item = (ColorRef, &str)> {
+    /// How many shown lines are above the top visible line, leaving the rest of the visible lines
+    /// below it.
+    fn top_line(&self, theme: &Theme) -> usize {
         const MAX_ROW: f64 = (usize::MAX as f64).next_down();
-        let mut last_color = ColorRef::Theme(ColorId::Foreground);
-        self.content
-            .split_inclusive('\n')
-            .skip(
-                self.content
-                    .lines()
-                    .count()
-                    .saturating_sub(self.bottom_offset.trunc().clamp(0.0, MAX_ROW) as usize)
-                    .saturating_sub(self.displayable_lines(theme)),
+        self.shown_lines()
+            .len()
+            .saturating_sub(self.bottom_offset.trunc().clamp(0.0, MAX_ROW) as usize)
+            .saturating_sub(self.displayable_lines(theme))
+    }
+
+    /// Scrolls so that the nearest shown line matching `is_target` above
+    /// ([`Direction::Reverse`]) or below ([`Direction::Forward`]) the top visible line becomes the
+    /// top visible line.
+    ///
+    /// Returns `false` if there is no such line.
+    fn jump_to<F>(&mut self, theme: &Theme, direction: Direction, is_target: F) -> bool
+    where
+        F: Fn(&LogLine) -> bool,
+    {
+        let shown = self.shown_lines();
+        let max_offset = shown.len().saturating_sub(self.displayable_lines(theme));
+        let top = self.top_line(theme);
+        let lines = shown.into_iter().enumerate();
+        let found = match direction {
+            Direction::Reverse => lines.take(top).filter(|(_, line)| is_target(line)).last(),
+            Direction::Forward => lines.skip(top + 1).find(|(_, line)| is_target(line)),
+        };
+        if let Some((n, _)) = found {
+            self.bottom_offset = max_offset.saturating_sub(n) as f64;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Jumps to the previous or next match of the search if there is one, otherwise to the
+    /// previous or next error.
+    pub fn jump(&mut self, theme: &Theme, direction: Direction) -> bool {
+        match self.search_query().map(str::to_string) {
+            Some(query) => self.jump_to(theme, direction, |line| !line.matches(&query).is_empty()),
+            None => self.jump_to(theme, direction, |line| line.ty == Some(LogType::Error)),
+        }
+    }
+
+    /// Buttons left of the title and collapse button for jumping to the previous and next error,
+    /// or search match.
+    pub fn jump_buttons(&self, theme: &Theme) -> [(Rectangle, Direction); 2] {
+        let right = self
+            .panel
+            .collapse_rec(theme)
+            .or_else(|| self.panel.title_rec(theme))
+            .map_or(self.panel.bounds().max.x, |rec| rec.x);
+        let size = theme.title_font.line_height() + theme.title_padding.vertical();
+        let y = self.panel.bounds().min.y;
+        [
+            (
+                Rectangle::new(right - 2.0 * size, y, size, size),
+                Direction::Reverse,
+            ),
+            (
+                Rectangle::new(right - size, y, size, size),
+                Direction::Forward,
+            ),
+        ]
+    }
+
+    /// Toggles left of the jump buttons for showing and hiding each type of line.
+    pub fn filter_buttons(&self, theme: &Theme) -> [(Rectangle, LogType); LogType::ALL.len()] {
+        let [(jump, _), _] = self.jump_buttons(theme);
+        let size = jump.height;
+        let left = jump.x - LogType::ALL.len() as f32 * size;
+        std::array::from_fn(|n| {
+            (
+                Rectangle::new(left + n as f32 * size, jump.y, size, size),
+                LogType::ALL[n],
             )
-            .take(self.displayable_lines(theme))
-            .flat_map(|line| RichStr::new(line).iter())
-            .map(move |item| match item {
-                Ok((color, text)) => {
-                    if let Some(color) = color {
-                        last_color = color;
-                    }
-                    (last_color, text)
-                }
-                Err(e) => panic!("{e}"),
-            })
+        })
     }
 
-    pub fn tick(&mut self, theme: &Theme, input: &Inputs, graphs: &GraphList) {
-        self.bottom_offset = (self.bottom_offset + input.scroll_console as f64).clamp(
-            0.0,
-            self.content_str()
-                .lines()
-                .count()
-                .saturating_sub(self.displayable_lines(theme)) as f64,
+    #[inline]
+    /// Number of log lines that fit above the command line.
+    pub fn displayable_lines(&self, theme: &Theme) -> usize {
+        (((self.panel.content_bounds(theme).height()
+            + /* Off by one otherwise */ theme.console_font.line_spacing)
+            / theme.console_font.line_height()) as usize)
+            .saturating_sub(1)
+    }
+
+    /// Top of the first visible log line.
+    fn log_top(&self, theme: &Theme) -> f32 {
+        self.panel.content_bounds(theme).max.y
+            - (self.displayable_lines(theme) + 1) as f32 * theme.console_font.line_height()
+    }
+
+    /// Whether the scrollbar thumb is being dragged, which keeps the input even after the cursor
+    /// leaves the console.
+    #[inline]
+    pub const fn is_scrolling(&self) -> bool {
+        self.thumb_grab.is_some()
+    }
+
+    /// The scrollbar along the right of the visible log lines, and its thumb, unless every shown
+    /// line fits.
+    fn scrollbar(&self, theme: &Theme) -> Option<(Rectangle, Rectangle)> {
+        let total = self.shown_lines().len();
+        let displayable = self.displayable_lines(theme);
+        if total <= displayable {
+            return None;
+        }
+        let track = Rectangle::new(
+            self.panel.content_bounds(theme).max.x - SCROLLBAR_WIDTH,
+            self.log_top(theme),
+            SCROLLBAR_WIDTH,
+            displayable as f32 * theme.console_font.line_height(),
+        );
+        let height = (track.height * displayable as f32 / total as f32)
+            .max(MIN_THUMB_HEIGHT)
+            .min(track.height);
+        let max_offset = (total - displayable) as f64;
+        let scrolled = (self.bottom_offset.clamp(0.0, max_offset) / max_offset) as f32;
+        let thumb = Rectangle::new(
+            track.x,
+            track.y + (1.0 - scrolled) * (track.height - height),
+            track.width,
+            height,
         );
+        Some((track, thumb))
+    }
 
-        let Vector2 { mut x, mut y } = self.panel.content_bounds(theme).min;
-        let left = x;
-        for (_, text) in self.visible_content(theme) {
-            let text_size = theme.console_font.measure_text(text);
-            if Rectangle::new(x, y, text_size.x, text_size.y)
-                .check_collision_point_rec(input.cursor)
-                && let Ok(hyper_ref) = text.parse::<HyperRef>()
-            {
-                match hyper_ref {
-                    HyperRef::Gate(_gate_ref) => {
-                        // TODO
-                    }
+    /// Scrolls so that the top of the scrollbar thumb is as near to `top` as it can be.
+    fn scroll_thumb_to(&mut self, theme: &Theme, top: f32) {
+        let Some((track, thumb)) = self.scrollbar(theme) else {
+            return;
+        };
+        let travel = track.height - thumb.height;
+        if travel <= 0.0 {
+            return;
+        }
+        let max_offset = self
+            .shown_lines()
+            .len()
+            .saturating_sub(self.displayable_lines(theme)) as f64;
+        let scrolled = ((top - track.y) / travel).clamp(0.0, 1.0) as f64;
+        self.bottom_offset = ((1.0 - scrolled) * max_offset).round();
+    }
 
-                    HyperRef::Tool(_tool_ref) => {
-                        // TODO
-                    }
+    /// Text of the button for jumping back to the newest line, which is shown while scrolled
+    /// back from it.
+    fn newest_button_text(&self) -> String {
+        match self.unseen_lines {
+            0 => "newest v".to_string(),
+            1 => "1 new line v".to_string(),
+            n => format!("{n} new lines v"),
+        }
+    }
 
-                    HyperRef::Position(_position_ref) => {
-                        // TODO
-                    }
+    /// Bottom-right of the visible log lines, left of the scrollbar, while scrolled back from the
+    /// newest line. Clicking it scrolls back down and keeps following new lines.
+    fn newest_button(&self, theme: &Theme) -> Option<Rectangle> {
+        if self.bottom_offset == 0.0 {
+            return None;
+        }
+        let bounds = self.panel.content_bounds(theme);
+        let size = theme.console_font.measure_text(&self.newest_button_text());
+        let padding = theme.console_font.line_spacing;
+        let width = size.x + 2.0 * padding;
+        let height = theme.console_font.line_height();
+        Some(Rectangle::new(
+            bounds.max.x - SCROLLBAR_WIDTH - width,
+            bounds.max.y - 2.0 * height,
+            width,
+            height,
+        ))
+    }
 
-                    HyperRef::Graph(graph_ref) => {
-                        graph_ref.deref_with(graphs, |_g, _borrow| {
-                            // TODO
-                        });
-                    }
+    /// The whole log in the order it was logged, hidden lines included, without colors. Each
+    /// line starts with when it was logged and its source.
+    pub fn export_text(&self) -> String {
+        let mut text = String::new();
+        for line in &self.records {
+            _ = writeln!(text, "{}", line.export_line());
+        }
+        text
+    }
 
-                    HyperRef::Node(node_ref) => {
-                        node_ref.deref_with(graphs, |_g, _borrow, _node| {
-                            // TODO
-                        });
-                    }
+    /// Writes [`Self::export_text`] to `path`.
+    pub fn export(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.export_text())
+    }
 
-                    HyperRef::Wire(wire_ref) => {
-                        wire_ref.deref_with(graphs, |_g, _borrow, _wire| {
-                            // TODO
-                        });
-                    }
+    /// The shown lines that fit in the panel.
+    fn visible_lines(&self, theme: &Theme) -> impl Iterator<Item = &LogLine> {
+        self.shown_lines()
+            .into_iter()
+            .skip(self.top_line(theme))
+            .take(self.displayable_lines(theme))
+    }
+
+    /// Text shown left of `line`, saying when it was logged and where it came from, depending on
+    /// [`Self::show_time`] and [`Self::group_by_source`].
+    fn gutter(&self, line: &LogLine) -> Option<String> {
+        match (self.show_time, self.group_by_source) {
+            (false, false) => None,
+            (true, false) => Some(format!("{} ", format_time_of_day(line.time, false))),
+            (false, true) => Some(format!("{:<6} ", line.source)),
+            (true, true) => Some(format!(
+                "{} {:<6} ",
+                format_time_of_day(line.time, false),
+                line.source
+            )),
+        }
+    }
+
+    /// Left of the text of `line`, after its gutter.
+    fn text_left(&self, line: &LogLine, left: f32, theme: &Theme) -> f32 {
+        self.gutter(line).map_or(left, |gutter| {
+            left + theme.console_font.measure_text(&gutter).x
+        })
+    }
+
+    pub fn tick(
+        &mut self,
+        rl: &mut RaylibHandle,
+        theme: &Theme,
+        input: &Inputs,
+        graph: Option<&Graph>,
+        graphs: &GraphList,
+    ) -> Option<ConsoleAction> {
+        let mut action = None;
+        if let Some(line) = self.command_line.tick(rl, graph) {
+            if line.starts_with(SEARCH_PREFIX) {
+                // stays in the command line, so that entering it again finds the next older match
+                self.command_line.text = line;
+                if !self.jump(theme, Direction::Reverse) {
+                    logln!(self, LogType::Info, "no older matches");
                 }
+            } else {
+                action = Some(ConsoleAction::Command(line));
+                // show the result of the command
+                self.bottom_offset = 0.0;
             }
-            if text.ends_with('\n') {
-                y += theme.console_font.line_height();
-                x = left;
+            if let Err(e) = self.command_line.save_history() {
+                logln!(
+                    self,
+                    LogType::Warning,
+                    "failed to save console history: {e}"
+                );
+            }
+        }
+
+        if input.primary.is_starting()
+            && let Some((_, direction)) = self
+                .jump_buttons(theme)
+                .into_iter()
+                .find(|(rec, _)| rec.check_collision_point_rec(input.cursor))
+        {
+            self.jump(theme, direction);
+        }
+        if let Some((_, ty)) = self
+            .filter_buttons(theme)
+            .into_iter()
+            .find(|(rec, _)| rec.check_collision_point_rec(input.cursor))
+        {
+            if input.primary.is_starting() {
+                self.set_shown(ty, !self.is_shown(ty));
+            } else if input.secondary.is_starting() {
+                self.solo(ty);
+            }
+        }
+
+        let displayable = self.displayable_lines(theme) as f64;
+        let newest_button = self.newest_button(theme);
+        let scrollbar = self.scrollbar(theme);
+        let is_over_scrollbar = newest_button
+            .into_iter()
+            .chain(scrollbar.map(|(track, _)| track))
+            .any(|rec| rec.check_collision_point_rec(input.cursor));
+        if let Some(grab) = self.thumb_grab {
+            if input.primary.is_active() {
+                self.scroll_thumb_to(theme, input.cursor.y - grab);
             } else {
-                x += theme.console_font.measure_text(text).x;
+                self.thumb_grab = None;
+            }
+        } else if input.primary.is_starting() {
+            if newest_button.is_some_and(|rec| rec.check_collision_point_rec(input.cursor)) {
+                self.bottom_offset = 0.0;
+            } else if let Some((track, thumb)) = scrollbar
+                && track.check_collision_point_rec(input.cursor)
+            {
+                if thumb.check_collision_point_rec(input.cursor) {
+                    self.thumb_grab = Some(input.cursor.y - thumb.y);
+                } else if input.cursor.y < thumb.y {
+                    self.bottom_offset += displayable;
+                } else {
+                    self.bottom_offset -= displayable;
+                }
             }
         }
+
+        self.bottom_offset = (self.bottom_offset + input.scroll_console as f64).clamp(
+            0.0,
+            self.shown_lines()
+                .len()
+                .saturating_sub(self.displayable_lines(theme)) as f64,
+        );
+        if self.bottom_offset == 0.0 {
+            self.unseen_lines = 0;
+        }
+
+        let left = self.panel.content_bounds(theme).min.x;
+        let mut y = self.log_top(theme);
+        for line in self.visible_lines(theme) {
+            let mut x = self.text_left(line, left, theme);
+            for (_, span) in line.segments() {
+                let name = node_name(span, graphs);
+                let shown = name
+                    .as_deref()
+                    .map_or(span, |text| RichSpan { text, ..span });
+                let text_size = shown.measure(&theme.console_font);
+                if !is_over_scrollbar
+                    && Rectangle::new(x, y, text_size.x, text_size.y)
+                        .check_collision_point_rec(input.cursor)
+                    && let Ok(hyper_ref) = span.text.parse::<HyperRef>()
+                {
+                    if input.primary.is_starting() {
+                        action = Some(ConsoleAction::Follow(hyper_ref));
+                    } else if input.secondary.is_starting()
+                        && let HyperRef::Node(node_ref) = hyper_ref
+                    {
+                        action = Some(ConsoleAction::Watch(node_ref));
+                    }
+                }
+                x += text_size.x;
+            }
+            y += theme.console_font.line_height();
+        }
+
+        action
     }
 
     pub fn draw<D>(
@@ -665,68 +1447,214 @@ impl Console {
     ) where
         D: RaylibDraw,
     {
-        self.panel.draw(d, theme, move |d, bounds, theme| {
-            let mut x = bounds.min.x;
-            let mut y = bounds.max.y
-                - self.displayable_lines(theme) as f32 * theme.console_font.line_height();
-            let left = x;
-            for (color, text) in self.visible_content(theme) {
-                let size = theme.console_font.measure_text(text);
-                let hyper_rec = IRect::new(x as i32, y as i32, size.x as i32, size.y as i32);
-                let is_live = if let Ok(hr) = text.parse::<HyperRef>() {
-                    let is_live = match hr {
-                        HyperRef::Gate(_) => Some(()),
-                        HyperRef::Tool(_) => Some(()),
-                        HyperRef::Position(_) => Some(()),
-                        HyperRef::Graph(graph_ref) => graph_ref.deref_with(graphs, |_, _| {}),
-                        HyperRef::Node(node_ref) => node_ref.deref_with(graphs, |_, _, _| {}),
-                        HyperRef::Wire(wire_ref) => wire_ref.deref_with(graphs, |_, _, _| {}),
-                    }
-                    .is_some();
-
-                    if is_live
-                        && IBounds::from(hyper_rec).contains(input.cursor.as_ivec2())
-                        && let Ok(hr) = text.parse::<HyperRef>()
-                    {
-                        hr.draw_link(d, hyper_rec, theme, graphs, tabs, toolpane);
+        let query = self.search_query();
+        self.panel.draw(d, theme, |d, bounds, theme| {
+            let mut y = self.log_top(theme);
+            for line in self.visible_lines(theme) {
+                if let Some(gutter) = self.gutter(line) {
+                    theme.console_font.draw_text(
+                        d,
+                        &gutter,
+                        rvec2(bounds.min.x, y),
+                        theme.foreground3,
+                    );
+                }
+                let left = self.text_left(line, bounds.min.x, theme);
+                if let Some(query) = query {
+                    for range in line.matches(query) {
+                        let x = line.x_of(&theme.console_font, left, range.start);
+                        let width = line.x_of(&theme.console_font, left, range.end) - x;
+                        d.draw_rectangle_rec(
+                            Rectangle::new(x, y, width, theme.console_font.line_height()),
+                            theme.special.alpha(0.35),
+                        );
                     }
+                }
+                let mut x = left;
+                for (color, span) in line.segments() {
+                    let name = node_name(span, graphs);
+                    let shown = name
+                        .as_deref()
+                        .map_or(span, |text| RichSpan { text, ..span });
+                    let size = shown.measure(&theme.console_font);
+                    let hyper_rec = IRect::new(x as i32, y as i32, size.x as i32, size.y as i32);
+                    let is_live = if let Ok(hr) = span.text.parse::<HyperRef>() {
+                        let is_live = match hr {
+                            HyperRef::Gate(_) => Some(()),
+                            HyperRef::Tool(_) => Some(()),
+                            HyperRef::Position(_) => Some(()),
+                            HyperRef::Graph(graph_ref) => graph_ref.deref_with(graphs, |_, _| {}),
+                            HyperRef::Node(node_ref) => node_ref.deref_with(graphs, |_, _, _| {}),
+                            HyperRef::Wire(wire_ref) => wire_ref.deref_with(graphs, |_, _, _| {}),
+                        }
+                        .is_some();
+
+                        if is_live
+                            && IBounds::from(hyper_rec).contains(input.cursor.as_ivec2())
+                            && let Ok(hr) = span.text.parse::<HyperRef>()
+                        {
+                            hr.draw_link(d, hyper_rec, theme, graphs, tabs, toolpane);
+                        }
+
+                        Some(is_live)
+                    } else {
+                        None
+                    };
+                    shown.draw(
+                        d,
+                        theme,
+                        &theme.console_font,
+                        rvec2(x, y),
+                        if is_live.is_none_or(|x| x) {
+                            color.get(theme)
+                        } else {
+                            theme.dead_link
+                        },
+                    );
+                    x += size.x;
+                }
+                y += theme.console_font.line_height();
+            }
 
-                    Some(is_live)
-                } else {
-                    None
-                };
+            if let Some((track, thumb)) = self.scrollbar(theme) {
+                d.draw_rectangle_rec(track, theme.background1);
+                d.draw_rectangle_rec(
+                    thumb,
+                    if self.is_scrolling() || thumb.check_collision_point_rec(input.cursor) {
+                        theme.foreground2
+                    } else {
+                        theme.background3
+                    },
+                );
+            }
+            if let Some(rec) = self.newest_button(theme) {
+                d.draw_rectangle_rec(rec, theme.background2);
                 theme.console_font.draw_text(
                     d,
-                    text,
-                    rvec2(x, y),
-                    if is_live.is_none_or(|x| x) {
-                        color.get(theme)
+                    &self.newest_button_text(),
+                    rvec2(rec.x + theme.console_font.line_spacing, rec.y),
+                    if self.unseen_lines > 0 {
+                        theme.special
+                    } else if rec.check_collision_point_rec(input.cursor) {
+                        theme.foreground
                     } else {
-                        theme.dead_link
+                        theme.foreground2
                     },
                 );
-                if text.ends_with('\n') {
-                    y += theme.console_font.line_height();
-                    x = left;
-                } else {
-                    x += size.x;
+            }
+
+            // command line
+            let y = bounds.max.y - theme.console_font.line_height();
+            d.draw_rectangle_rec(
+                Rectangle::new(
+                    bounds.min.x,
+                    y,
+                    bounds.width(),
+                    theme.console_font.line_height(),
+                ),
+                theme.background1,
+            );
+            let prompt = if bounds.contains(input.cursor) {
+                format!("> {}_", self.command_line.text)
+            } else {
+                format!("> {}", self.command_line.text)
+            };
+            theme
+                .console_font
+                .draw_text(d, &prompt, rvec2(bounds.min.x, y), theme.foreground);
+            let hint = match query {
+                Some(query) => {
+                    let count = self
+                        .shown_lines()
+                        .into_iter()
+                        .map(|line| line.matches(query).len())
+                        .sum::<usize>();
+                    Some(format!("  {count} matches, enter for older"))
                 }
+                None => self.command_line.hint(),
+            };
+            if let Some(hint) = hint {
+                let x = bounds.min.x + theme.console_font.measure_text(&prompt).x;
+                theme
+                    .console_font
+                    .draw_text(d, &hint, rvec2(x, y), theme.foreground2);
             }
         });
+        for (rec, direction) in self.jump_buttons(theme) {
+            d.draw_rectangle_rec(rec, theme.background2);
+            let text = match direction {
+                Direction::Reverse => "^",
+                Direction::Forward => "v",
+            };
+            let text_size = theme.title_font.measure_text(text);
+            theme.title_font.draw_text(
+                d,
+                text,
+                Vector2::new(
+                    rec.x + 0.5 * (rec.width - text_size.x),
+                    rec.y + 0.5 * (rec.height - text_size.y),
+                ),
+                if !rec.check_collision_point_rec(input.cursor) {
+                    theme.foreground2
+                } else if query.is_some() {
+                    theme.special
+                } else {
+                    theme.error
+                },
+            );
+        }
+        for (rec, ty) in self.filter_buttons(theme) {
+            let is_shown = self.is_shown(ty);
+            d.draw_rectangle_rec(
+                rec,
+                if is_shown {
+                    theme.background3
+                } else {
+                    theme.background2
+                },
+            );
+            let text = ty.to_string()[..1].to_ascii_uppercase();
+            let text_size = theme.title_font.measure_text(&text);
+            theme.title_font.draw_text(
+                d,
+                &text,
+                Vector2::new(
+                    rec.x + 0.5 * (rec.width - text_size.x),
+                    rec.y + 0.5 * (rec.height - text_size.y),
+                ),
+                if is_shown {
+                    ty.color().get(theme)
+                } else {
+                    theme.foreground3
+                },
+            );
+        }
     }
 }
 
 #[macro_export]
 macro_rules! logln {
     ($console:expr, $ty:expr, $($args:tt)+) => {
-        $crate::console::Console::log(
+        $crate::console::Console::log_record(
             $console,
-            format_args!("{}[{}]: {}{}\n",
-                $crate::rich_text::ColorAct::Push(<$crate::rich_text::ColorRef as From<LogType>>::from($ty)),
+            $crate::console::LogRecord::new(
                 $ty,
-                format_args!($($args)+),
-                $crate::rich_text::ColorAct::Pop,
-            )
+                $crate::console::LogSource::of_module(module_path!()),
+                format!($($args)+),
+            ),
         )
     };
 }
+
+/// Like [`logln`], but without the console: the record is queued with [`log_deferred`] and only
+/// added once the console drains it. For code that mustn't wait on the console.
+#[macro_export]
+macro_rules! logln_deferred {
+    ($ty:expr, $($args:tt)+) => {
+        $crate::console::log_deferred($crate::console::LogRecord::new(
+            $ty,
+            $crate::console::LogSource::of_module(module_path!()),
+            format!($($args)+),
+        ))
+    };
+}
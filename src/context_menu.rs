@@ -0,0 +1,267 @@
+//! The menu opened by right-clicking in an editor tab.
+
+use crate::{
+    graph::{
+        node::{GateId, NodeId, Ntd},
+        region::RegionId,
+        wire::{Elbow, WireId},
+    },
+    input::Inputs,
+    ivec::{Bounds, IVec2},
+    theme::Theme,
+};
+use raylib::prelude::*;
+
+/// What clicking a context menu item does, carried out by the tab that opened the menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    DeleteNode(NodeId),
+    /// Copies the selection if the node is in it, otherwise just the node.
+    CopyNode(NodeId),
+    /// Starts or stops logging and plotting the node's state changes.
+    WatchNode(NodeId),
+    /// Starts or stops tracing the node's state for a VCD export.
+    ProbeNode(NodeId),
+    /// Shows the node in the properties panel.
+    InspectNode(NodeId),
+    /// Replaces the node's gate with the toolpane's.
+    ConvertNode(NodeId),
+    DeleteWire(WireId),
+    /// Swaps the wire's source and destination.
+    ReverseWire(WireId),
+    SetElbow(WireId, Elbow),
+    /// Pastes the clipboard with its top-left corner where the menu was opened.
+    Paste,
+    /// Places a gate at the grid position where the menu was opened.
+    PlaceGate(GateId, IVec2),
+    SelectAll,
+    /// Sets the tab's zoom exponent.
+    SetZoom(i8),
+    /// Adds a region around the selected nodes.
+    GroupSelection,
+    /// Starts tracing every selected node that isn't already.
+    ProbeSelection,
+    DeleteRegion(RegionId),
+    SetRegionColor(RegionId, Ntd),
+    /// Puts back what the erase tool's last sweep erased.
+    RestoreErased,
+    /// Puts back the gates last replaced through the tab.
+    RestoreGates,
+}
+
+impl MenuAction {
+    /// Whether the action changes the graph, and so cannot be taken in a locked tab.
+    pub const fn is_edit(self) -> bool {
+        match self {
+            Self::DeleteNode(_)
+            | Self::ConvertNode(_)
+            | Self::DeleteWire(_)
+            | Self::ReverseWire(_)
+            | Self::SetElbow(..)
+            | Self::Paste
+            | Self::PlaceGate(..)
+            | Self::GroupSelection
+            | Self::DeleteRegion(_)
+            | Self::SetRegionColor(..)
+            | Self::RestoreErased
+            | Self::RestoreGates => true,
+            Self::CopyNode(_)
+            | Self::WatchNode(_)
+            | Self::ProbeNode(_)
+            | Self::InspectNode(_)
+            | Self::SelectAll
+            | Self::SetZoom(_)
+            | Self::ProbeSelection => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum MenuItemKind {
+    Action(MenuAction),
+    /// Opens beside the menu while the item is hovered.
+    Submenu(Vec<MenuItem>),
+}
+
+#[derive(Debug, Clone)]
+pub struct MenuItem {
+    pub label: String,
+    pub kind: MenuItemKind,
+}
+
+impl MenuItem {
+    #[inline]
+    pub fn action(label: impl Into<String>, action: MenuAction) -> Self {
+        Self {
+            label: label.into(),
+            kind: MenuItemKind::Action(action),
+        }
+    }
+
+    #[inline]
+    pub fn submenu(label: impl Into<String>, items: Vec<MenuItem>) -> Self {
+        Self {
+            label: label.into(),
+            kind: MenuItemKind::Submenu(items),
+        }
+    }
+}
+
+/// How the menu responded to a tick of input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuResponse {
+    /// The menu stays open.
+    Idle,
+    /// Clicked outside the menu.
+    Dismiss,
+    Chosen(MenuAction),
+}
+
+/// Marks items that open a submenu.
+const SUBMENU_ARROW: &str = ">";
+
+/// A list of items at a screen position, with at most one submenu open.
+#[derive(Debug, Clone)]
+pub struct ContextMenu {
+    /// Screen position of the top-left corner
+    position: Vector2,
+    items: Vec<MenuItem>,
+    /// Index of the item whose submenu is open
+    open_submenu: Option<usize>,
+}
+
+impl ContextMenu {
+    pub const fn new(position: Vector2, items: Vec<MenuItem>) -> Self {
+        Self {
+            position,
+            items,
+            open_submenu: None,
+        }
+    }
+
+    /// Where the menu was opened, in screen space.
+    #[inline]
+    pub const fn position(&self) -> Vector2 {
+        self.position
+    }
+
+    /// The rectangle of each of `items`, listed downward from `position` and all as wide as the
+    /// widest.
+    fn item_recs(items: &[MenuItem], position: Vector2, theme: &Theme) -> Vec<Rectangle> {
+        let padding = theme.title_padding;
+        let arrow_width = theme.general_font.measure_text(SUBMENU_ARROW).x + padding.left;
+        let width = items
+            .iter()
+            .map(|item| {
+                let label_width = theme.general_font.measure_text(&item.label).x;
+                match item.kind {
+                    MenuItemKind::Action(_) => label_width,
+                    MenuItemKind::Submenu(_) => label_width + arrow_width,
+                }
+            })
+            .fold(0.0, f32::max)
+            + padding.horizontal();
+        let height = theme.general_font.line_height() + padding.vertical();
+        (0..items.len())
+            .map(|n| Rectangle::new(position.x, position.y + n as f32 * height, width, height))
+            .collect()
+    }
+
+    /// The menu's items and their rectangles, followed by the open submenu's.
+    fn layout(&self, theme: &Theme) -> Vec<(&MenuItem, Rectangle)> {
+        let recs = Self::item_recs(&self.items, self.position, theme);
+        let submenu = self.open_submenu.and_then(|n| {
+            let MenuItemKind::Submenu(items) = &self.items[n].kind else {
+                return None;
+            };
+            let parent = recs[n];
+            let recs = Self::item_recs(
+                items,
+                Vector2::new(parent.x + parent.width, parent.y),
+                theme,
+            );
+            Some(items.iter().zip(recs))
+        });
+        self.items
+            .iter()
+            .zip(recs.iter().copied())
+            .chain(submenu.into_iter().flatten())
+            .collect()
+    }
+
+    /// Whether `point` is over the menu or its open submenu.
+    pub fn contains(&self, point: Vector2, theme: &Theme) -> bool {
+        self.layout(theme)
+            .into_iter()
+            .any(|(_, rec)| Bounds::from(rec).contains(point))
+    }
+
+    pub fn tick(&mut self, theme: &Theme, input: &Inputs) -> MenuResponse {
+        let recs = Self::item_recs(&self.items, self.position, theme);
+        // hovering an item of the menu itself opens its submenu, or closes the open one
+        if let Some(n) = recs
+            .iter()
+            .position(|rec| Bounds::from(*rec).contains(input.cursor))
+        {
+            self.open_submenu = match self.items[n].kind {
+                MenuItemKind::Submenu(_) => Some(n),
+                MenuItemKind::Action(_) => None,
+            };
+        }
+        if !input.primary.is_starting() {
+            return MenuResponse::Idle;
+        }
+        match self
+            .layout(theme)
+            .into_iter()
+            .find(|(_, rec)| Bounds::from(*rec).contains(input.cursor))
+        {
+            Some((
+                MenuItem {
+                    kind: MenuItemKind::Action(action),
+                    ..
+                },
+                _,
+            )) => MenuResponse::Chosen(*action),
+            Some(_) => MenuResponse::Idle,
+            None => MenuResponse::Dismiss,
+        }
+    }
+
+    pub fn draw<D: RaylibDraw>(&self, d: &mut D, theme: &Theme, input: &Inputs) {
+        let padding = theme.title_padding;
+        for (item, rec) in self.layout(theme) {
+            let is_hovered = Bounds::from(rec).contains(input.cursor);
+            let is_open = matches!(
+                (&item.kind, self.open_submenu),
+                (MenuItemKind::Submenu(_), Some(n)) if std::ptr::eq(item, &self.items[n])
+            );
+            d.draw_rectangle_rec(
+                rec,
+                if is_hovered || is_open {
+                    theme.background3
+                } else {
+                    theme.background2
+                },
+            );
+            theme.general_font.draw_text(
+                d,
+                &item.label,
+                Vector2::new(rec.x + padding.left, rec.y + padding.top),
+                theme.foreground,
+            );
+            if let MenuItemKind::Submenu(_) = item.kind {
+                let arrow_width = theme.general_font.measure_text(SUBMENU_ARROW).x;
+                theme.general_font.draw_text(
+                    d,
+                    SUBMENU_ARROW,
+                    Vector2::new(
+                        rec.x + rec.width - padding.right - arrow_width,
+                        rec.y + padding.top,
+                    ),
+                    theme.foreground2,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,349 @@
+//! Moving panels by their titles. Dropping a panel against a side or corner of the window docks it
+//! there, dropping it on another panel's title stacks the two in one place, and dropping it
+//! anywhere else leaves it floating where it was dropped.
+//!
+//! Only the front panel of a stack is shown, with the titles of the rest beside its own; clicking
+//! one of those brings its panel to the front. Panels without a title, like the toolpane, stay
+//! where they are.
+
+use crate::{
+    input::Inputs,
+    ivec::Bounds,
+    locale::tr,
+    rich_text::RichStr,
+    theme::Theme,
+    ui::{Anchoring, ExactSizing, NcSizing, Panel, RectHoverRegion, title_tab_size},
+};
+use raylib::prelude::*;
+
+/// How close to the edge of the window a panel has to be dropped to dock against it.
+const DOCK_MARGIN: f32 = 32.0;
+/// How far a title has to be dragged before its panel comes loose, so that clicks don't move it.
+const DRAG_THRESHOLD: f32 = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DockTarget {
+    /// Against a side or corner of the window
+    Edge(RectHoverRegion),
+    /// With its top-left corner here
+    Float(Vector2),
+    /// In front of the panel at this index and any stacked with it
+    Stack(usize),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DockDrag {
+    /// Index of the panel being moved
+    panel: usize,
+    /// Where the title was grabbed
+    start: Vector2,
+    /// From the cursor to the top-left corner of the panel
+    grab: Vector2,
+    /// Where the panel would go if dropped now and the bounds it would take up there, once it has
+    /// been dragged past [`DRAG_THRESHOLD`]
+    target: Option<(DockTarget, Bounds)>,
+}
+
+/// Panels are referred to by name, which must be the same from frame to frame and session to
+/// session, and given in the same order to every method.
+#[derive(Debug, Default)]
+pub struct DockLayout {
+    /// Panels sharing one place, front first. Panels in none of them are alone.
+    pub stacks: Vec<Vec<String>>,
+    drag: Option<DockDrag>,
+}
+
+/// Where the panel at `dragged`, grabbed `grab` from its top-left corner, goes if dropped now.
+fn dock_target(
+    theme: &Theme,
+    cursor: Vector2,
+    grab: Vector2,
+    window: &Bounds,
+    panels: &[(&str, &Panel)],
+    dragged: usize,
+) -> (DockTarget, Bounds) {
+    let size = {
+        let bounds = panels[dragged].1.bounds();
+        Vector2::new(bounds.width(), bounds.height())
+    };
+    if let Some(n) = (0..panels.len()).find(|&n| {
+        let panel = panels[n].1;
+        n != dragged
+            && panel.is_shown()
+            && panel
+                .title_rec(theme)
+                .is_some_and(|rec| rec.check_collision_point_rec(cursor))
+    }) {
+        return (DockTarget::Stack(n), *panels[n].1.bounds());
+    }
+
+    let [left, top, right, bottom] = [
+        cursor.x - window.min.x,
+        cursor.y - window.min.y,
+        window.max.x - cursor.x,
+        window.max.y - cursor.y,
+    ]
+    .map(|distance| distance <= DOCK_MARGIN);
+    let region = match (left, top, right, bottom) {
+        (true, true, _, _) => Some(RectHoverRegion::TopLeft),
+        (_, true, true, _) => Some(RectHoverRegion::TopRight),
+        (true, _, _, true) => Some(RectHoverRegion::BottomLeft),
+        (_, _, true, true) => Some(RectHoverRegion::BottomRight),
+        (true, _, _, _) => Some(RectHoverRegion::Left),
+        (_, true, _, _) => Some(RectHoverRegion::Top),
+        (_, _, true, _) => Some(RectHoverRegion::Right),
+        (_, _, _, true) => Some(RectHoverRegion::Bottom),
+        _ => None,
+    };
+    match region {
+        Some(region) => (
+            DockTarget::Edge(region),
+            Anchoring::docked(region, size).bounds(window, size).0,
+        ),
+        None => {
+            // kept inside the window, so that it can't be lost off the edge
+            let position = Vector2::new(
+                (cursor.x + grab.x)
+                    .min(window.max.x - size.x)
+                    .max(window.min.x),
+                (cursor.y + grab.y)
+                    .min(window.max.y - size.y)
+                    .max(window.min.y),
+            );
+            (
+                DockTarget::Float(position),
+                Bounds::new(position, position + size),
+            )
+        }
+    }
+}
+
+/// The titles of the panels behind the front of `stack` and the index of each panel, leftward
+/// from the front panel's own title and collapse button.
+fn behind_titles(
+    theme: &Theme,
+    stack: &[String],
+    panels: &[(&str, &Panel)],
+) -> Vec<(usize, Rectangle)> {
+    let index_of = |name: &String| panels.iter().position(|(n, _)| n == name);
+    let Some(front) = stack.first().and_then(index_of) else {
+        return Vec::new();
+    };
+    let front = panels[front].1;
+    let Some(front_title) = front.title_rec(theme).filter(|_| front.is_shown()) else {
+        return Vec::new();
+    };
+    let mut x = front
+        .collapse_rec(theme)
+        .map_or(front_title.x, |button| button.x);
+    stack[1..]
+        .iter()
+        .filter_map(index_of)
+        .filter(|&n| !panels[n].1.title.is_empty())
+        .map(|n| {
+            let size = title_tab_size(theme, panels[n].1.title);
+            x -= size.x;
+            (n, Rectangle::new(x, front_title.y, size.x, size.y))
+        })
+        .collect()
+}
+
+impl DockLayout {
+    /// Whether a title is held, in which case no panel should take input.
+    #[inline]
+    pub const fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Hides every panel behind the front of a stack and shows the rest, after forgetting stacks
+    /// of panels that aren't in `panels`.
+    pub fn arrange(&mut self, panels: &mut [(&'static str, &mut Panel)]) {
+        for stack in &mut self.stacks {
+            stack.retain(|name| panels.iter().any(|(n, _)| n == name));
+        }
+        self.stacks.retain(|stack| stack.len() > 1);
+        for (name, panel) in panels.iter_mut() {
+            let is_behind = self
+                .stacks
+                .iter()
+                .any(|stack| stack[1..].iter().any(|n| n == *name));
+            panel.set_hidden(is_behind);
+        }
+    }
+
+    /// Moves the panel at `index` to the front of its stack, where it takes the place of the
+    /// panel that was there.
+    fn bring_to_front(&mut self, index: usize, panels: &mut [(&'static str, &mut Panel)]) {
+        let name = panels[index].0;
+        let Some(stack) = self
+            .stacks
+            .iter_mut()
+            .find(|stack| stack.iter().any(|n| n == name))
+        else {
+            return;
+        };
+        if let Some(front) = panels.iter().position(|(n, _)| *n == stack[0]) {
+            panels[index].1.anchoring = panels[front].1.anchoring;
+        }
+        stack.retain(|n| n != name);
+        stack.insert(0, name.to_string());
+        self.arrange(panels);
+    }
+
+    /// Takes the panel at `index` out of its stack, leaving the next panel in its place.
+    fn leave_stack(&mut self, index: usize, panels: &mut [(&'static str, &mut Panel)]) {
+        let name = panels[index].0;
+        let anchoring = panels[index].1.anchoring;
+        if let Some(stack) = self
+            .stacks
+            .iter_mut()
+            .find(|stack| stack.iter().any(|n| n == name))
+        {
+            stack.retain(|n| n != name);
+            if let Some(next) = panels.iter_mut().find(|(n, _)| *n == stack[0]) {
+                next.1.anchoring = anchoring;
+            }
+        }
+        self.arrange(panels);
+    }
+
+    fn drop_panel(
+        &mut self,
+        index: usize,
+        target: DockTarget,
+        panels: &mut [(&'static str, &mut Panel)],
+    ) {
+        let size = {
+            let bounds = panels[index].1.bounds();
+            Vector2::new(bounds.width(), bounds.height())
+        };
+        self.leave_stack(index, panels);
+        match target {
+            DockTarget::Edge(region) => {
+                panels[index].1.reanchor(Anchoring::docked(region, size));
+            }
+            DockTarget::Float(position) => {
+                let exact = |val| {
+                    NcSizing::Exact(ExactSizing {
+                        val,
+                        min: None,
+                        max: None,
+                    })
+                };
+                panels[index].1.reanchor(Anchoring::Floating {
+                    x: position.x,
+                    y: position.y,
+                    w: exact(size.x),
+                    h: exact(size.y),
+                });
+            }
+            DockTarget::Stack(onto) => {
+                panels[index].1.anchoring = panels[onto].1.anchoring;
+                let (name, onto) = (panels[index].0, panels[onto].0);
+                match self
+                    .stacks
+                    .iter_mut()
+                    .find(|stack| stack.iter().any(|n| n == onto))
+                {
+                    Some(stack) => stack.insert(0, name.to_string()),
+                    None => self.stacks.push(vec![name.to_string(), onto.to_string()]),
+                }
+                self.arrange(panels);
+            }
+        }
+    }
+
+    /// Picks up, moves and drops panels by their titles. Should come after the panels are
+    /// resized, so that grabbing an edge under a title resizes rather than moves the panel.
+    pub fn tick(
+        &mut self,
+        theme: &Theme,
+        input: &Inputs,
+        window: &Bounds,
+        panels: &mut [(&'static str, &mut Panel)],
+    ) {
+        let view = panels
+            .iter()
+            .map(|(name, panel)| (*name, &**panel))
+            .collect::<Vec<_>>();
+        match self.drag {
+            None if input.primary.is_starting() => {
+                if let Some(index) = self.stacks.iter().find_map(|stack| {
+                    behind_titles(theme, stack, &view)
+                        .into_iter()
+                        .find(|(_, rec)| rec.check_collision_point_rec(input.cursor))
+                        .map(|(n, _)| n)
+                }) {
+                    self.bring_to_front(index, panels);
+                } else if let Some(index) = view.iter().position(|(_, panel)| {
+                    panel.is_shown()
+                        && !panel.is_collapsed
+                        && !panel.is_dragging()
+                        && panel
+                            .title_rec(theme)
+                            .is_some_and(|rec| rec.check_collision_point_rec(input.cursor))
+                }) {
+                    self.drag = Some(DockDrag {
+                        panel: index,
+                        start: input.cursor,
+                        grab: view[index].1.bounds().min - input.cursor,
+                        target: None,
+                    });
+                }
+            }
+            None => {}
+            Some(drag) if input.primary.is_ending() => {
+                self.drag = None;
+                if let Some((target, _)) = drag.target {
+                    self.drop_panel(drag.panel, target, panels);
+                }
+            }
+            Some(mut drag) => {
+                if drag.target.is_some() || drag.start.distance_to(input.cursor) > DRAG_THRESHOLD {
+                    drag.target = Some(dock_target(
+                        theme,
+                        input.cursor,
+                        drag.grab,
+                        window,
+                        &view,
+                        drag.panel,
+                    ));
+                    self.drag = Some(drag);
+                }
+            }
+        }
+    }
+
+    /// Draws the titles of the panels behind each stack, and where the held panel would go.
+    pub fn draw<D: RaylibDraw>(&self, d: &mut D, theme: &Theme, panels: &[(&str, &Panel)]) {
+        for stack in &self.stacks {
+            for (n, rec) in behind_titles(theme, stack, panels) {
+                d.draw_rectangle_rec(rec, theme.background2);
+                d.draw_rectangle_rec(
+                    Rectangle::new(rec.x + 1.0, rec.y + 1.0, rec.width - 2.0, rec.height - 1.0),
+                    theme.background1,
+                );
+                RichStr::new(tr(panels[n].1.title)).draw(
+                    d,
+                    theme,
+                    &theme.title_font,
+                    Vector2::new(
+                        rec.x + theme.title_padding.left,
+                        rec.y + theme.title_padding.top,
+                    ),
+                    theme.foreground2,
+                );
+            }
+        }
+
+        if let Some(DockDrag {
+            target: Some((_, bounds)),
+            ..
+        }) = &self.drag
+        {
+            let rec = Rectangle::from(*bounds);
+            d.draw_rectangle_rec(rec, theme.interact.alpha(0.25));
+            d.draw_rectangle_lines_ex(rec, 1.0, theme.interact);
+        }
+    }
+}
@@ -0,0 +1,509 @@
+//! Pictures of a graph or its selection: PNGs drawn the way the editor draws nodes, and SVGs of
+//! each gate's symbol.
+
+use crate::{
+    console::{Console, GraphRef, LogType},
+    graph::{
+        Graph,
+        node::{GateInstance, Node, NodeId},
+        wire::{BUS_THICKNESS, Wire},
+    },
+    icon_sheets::{NodeIconLookup, NodeIconSheetSetId},
+    ivec::{IBounds, IVec2},
+    logln,
+    tab::{Tab, TabList, icon_placement, ntd_tint},
+    theme::Theme,
+};
+use raylib::prelude::*;
+use rustc_hash::FxHashSet;
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+pub const EXPORT_DIRECTORY: &str = "exports";
+/// Pixels per world unit when none is chosen.
+pub const DEFAULT_EXPORT_SCALE: f32 = 4.0;
+/// Widest or tallest PNG, in pixels, since larger render textures may not fit on the GPU.
+const MAX_PNG_SIZE: f32 = 8192.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    Png,
+    Svg,
+}
+
+impl std::fmt::Display for ExportFormat {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportFormat::Png => "png",
+            ExportFormat::Svg => "svg",
+        }
+        .fmt(f)
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = ();
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "png" => Ok(ExportFormat::Png),
+            "svg" => Ok(ExportFormat::Svg),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What to export and how, as given to the `export` command.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExportSettings {
+    pub format: ExportFormat,
+    /// Pixels per world unit
+    pub scale: f32,
+    /// Whether to export only the selected nodes rather than the whole graph
+    pub selection_only: bool,
+}
+
+/// The nodes to export and the wires between them.
+struct Scene<'a> {
+    graph: &'a Graph,
+    nodes: Vec<&'a Node>,
+    /// World space covered, with a cell of space around the nodes
+    bounds: IBounds,
+}
+
+impl<'a> Scene<'a> {
+    /// Returns [`None`] if there are no nodes to export.
+    fn new(graph: &'a Graph, selection: Option<&FxHashSet<NodeId>>) -> Option<Self> {
+        let nodes = graph
+            .nodes_iter()
+            .filter(|node| selection.is_none_or(|selection| selection.contains(node.id())))
+            .collect::<Vec<_>>();
+        let grid_size = i32::from(graph.grid_size());
+        let (min, max) = nodes.iter().map(|node| node.position()).fold(
+            None,
+            |bounds: Option<(IVec2, IVec2)>, p| {
+                Some(bounds.map_or((p, p), |(min, max)| {
+                    (
+                        IVec2::new(min.x.min(p.x), min.y.min(p.y)),
+                        IVec2::new(max.x.max(p.x), max.y.max(p.y)),
+                    )
+                }))
+            },
+        )?;
+        Some(Self {
+            graph,
+            nodes,
+            bounds: IBounds::new(
+                IVec2::new(min.x - grid_size, min.y - grid_size),
+                IVec2::new(max.x + 2 * grid_size, max.y + 2 * grid_size),
+            ),
+        })
+    }
+
+    fn wires(&self) -> impl Iterator<Item = &'a Wire> {
+        let graph = self.graph;
+        let ids = self
+            .nodes
+            .iter()
+            .map(|node| *node.id())
+            .collect::<FxHashSet<_>>();
+        graph
+            .wires_iter()
+            .filter(move |wire| ids.contains(wire.src()) && ids.contains(wire.dst()))
+    }
+
+    /// Color of a node or the wires leaving it, as the editor draws them outside interact mode.
+    fn state_color(&self, theme: &Theme, id: &NodeId) -> Color {
+        if self.graph.displayed_state(id) {
+            theme.active
+        } else {
+            theme.foreground
+        }
+    }
+}
+
+/// Renders the scene at `scale` pixels per world unit and writes it to `path` as a PNG.
+fn write_png(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    theme: &Theme,
+    scene: &Scene,
+    scale: f32,
+    path: &Path,
+) -> std::io::Result<()> {
+    let width = (scene.bounds.width() as f32 * scale).ceil();
+    let height = (scene.bounds.height() as f32 * scale).ceil();
+    if width > MAX_PNG_SIZE || height > MAX_PNG_SIZE {
+        return Err(std::io::Error::other(format!(
+            "{width}x{height} is larger than {MAX_PNG_SIZE}x{MAX_PNG_SIZE} pixels; try a smaller scale"
+        )));
+    }
+    let mut texture = rl
+        .load_render_texture(thread, width as u32, height as u32)
+        .map_err(std::io::Error::other)?;
+
+    let grid_size = scene.graph.grid_size();
+    let cell_center = scene.graph.cell_center();
+    // the sheet drawn at the size nodes appear in the picture
+    let zoom_exp = (scale * f32::from(grid_size) / NodeIconSheetSetId::X8.icon_width() as f32)
+        .log2()
+        .ceil() as i32;
+    let icons = NodeIconSheetSetId::from_zoom_exp(zoom_exp)
+        .map(|scale| NodeIconLookup::new(&theme.node_icons, scale));
+    {
+        let mut d = rl.begin_texture_mode(thread, &mut texture);
+        d.clear_background(theme.background);
+        let mut d = d.begin_mode2D(Camera2D {
+            offset: Vector2::zero(),
+            target: scene.bounds.min.as_vec2(),
+            rotation: 0.0,
+            zoom: scale,
+        });
+        for wire in scene.wires() {
+            wire.draw(
+                &mut d,
+                scene.graph,
+                cell_center,
+                scene.state_color(theme, wire.src()),
+                1.0,
+                scale.recip(),
+            )
+            .expect("all wires should be valid");
+        }
+        for node in &scene.nodes {
+            let node_position = node.position().as_vec2();
+            let rec = Rectangle {
+                x: node_position.x,
+                y: node_position.y,
+                width: grid_size.into(),
+                height: grid_size.into(),
+            };
+            let color = scene.state_color(theme, node.id());
+            if let Some(icons) = &icons {
+                let (dest, origin, rotation) = icon_placement(rec, node.orientation());
+                let src_rec = icons.src_rec(node.gate().as_gate().id());
+                d.draw_texture_pro(
+                    icons.background,
+                    src_rec,
+                    dest,
+                    origin,
+                    rotation,
+                    theme.background,
+                );
+                d.draw_texture_pro(icons.basic, src_rec, dest, origin, rotation, color);
+                if let Some(color) = ntd_tint(theme, node.gate()) {
+                    d.draw_texture_pro(icons.ntd, src_rec, dest, origin, rotation, color);
+                }
+            } else {
+                d.draw_rectangle_rec(rec, color);
+            }
+        }
+    }
+
+    let mut image = texture
+        .texture()
+        .load_image()
+        .map_err(std::io::Error::other)?;
+    // render textures are stored upside down
+    image.flip_vertical();
+    let filename = std::ffi::CString::new(path.as_os_str().as_encoded_bytes())
+        .map_err(std::io::Error::other)?;
+    // SAFETY: `image` was just loaded and `filename` is a nul-terminated string that outlives the
+    // call, which only reads them
+    if unsafe { ffi::ExportImage(*image, filename.as_ptr()) } {
+        Ok(())
+    } else {
+        Err(std::io::Error::other("could not write the image"))
+    }
+}
+
+/// `attribute` set to the color, along with its opacity if it is translucent.
+fn svg_paint(attribute: &str, Color { r, g, b, a }: Color) -> String {
+    if a == u8::MAX {
+        format!("{attribute}=\"#{r:02x}{g:02x}{b:02x}\"")
+    } else {
+        format!(
+            "{attribute}=\"#{r:02x}{g:02x}{b:02x}\" {attribute}-opacity=\"{:.3}\"",
+            f32::from(a) / 255.0
+        )
+    }
+}
+
+/// The scene as an SVG of each gate's symbol, `scale` pixels per world unit.
+fn write_svg(theme: &Theme, scene: &Scene, scale: f32) -> String {
+    let IBounds { min, .. } = scene.bounds;
+    let (width, height) = (scene.bounds.width(), scene.bounds.height());
+    let size = f32::from(scene.graph.grid_size());
+    let cell_center = scene.graph.cell_center();
+    // lines as thin as the editor draws them at 100%
+    let stroke = size / 8.0;
+
+    let mut svg = String::new();
+    _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {width} {height}\">",
+        width as f32 * scale,
+        height as f32 * scale,
+        min.x,
+        min.y,
+    );
+    _ = writeln!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{width}\" height=\"{height}\" {}/>",
+        min.x,
+        min.y,
+        svg_paint("fill", theme.background),
+    );
+
+    for wire in scene.wires() {
+        let points = wire
+            .path(scene.graph, cell_center)
+            .expect("all wires should be valid");
+        let is_bus = scene
+            .graph
+            .node(wire.src())
+            .is_some_and(|node| node.width() > 1);
+        let points = points
+            .iter()
+            .map(|point| format!("{},{}", point.x, point.y))
+            .collect::<Vec<_>>()
+            .join(" ");
+        _ = writeln!(
+            svg,
+            "<polyline points=\"{points}\" fill=\"none\" stroke-width=\"{}\" {}/>",
+            if is_bus { size * BUS_THICKNESS } else { stroke },
+            svg_paint("stroke", scene.state_color(theme, wire.src())),
+        );
+    }
+
+    for node in &scene.nodes {
+        let IVec2 { x, y } = node.position();
+        let (x, y) = (x as f32, y as f32);
+        let (cx, cy) = (x + cell_center.x, y + cell_center.y);
+        let color = scene.state_color(theme, node.id());
+        let fill = svg_paint("fill", color);
+        let outline = format!(
+            "fill=\"none\" stroke-width=\"{stroke}\" {}",
+            svg_paint("stroke", color)
+        );
+        // shapes are inset by half a stroke so outlines stay inside the cell
+        let (inset, inner) = (stroke * 0.5, size - stroke);
+        let square = |attributes: &str| {
+            format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{inner}\" height=\"{inner}\" {attributes}/>",
+                x + inset,
+                y + inset
+            )
+        };
+        let circle = |r: f32, attributes: &str| {
+            format!("<circle cx=\"{cx}\" cy=\"{cy}\" r=\"{r}\" {attributes}/>")
+        };
+        let ntd = ntd_tint(theme, node.gate()).map(|tint| svg_paint("fill", tint));
+
+        let shapes = match node.gate() {
+            GateInstance::Or => circle(size * 0.5, &fill),
+            GateInstance::Nor => circle((size - stroke) * 0.5, &outline),
+            GateInstance::Xor => {
+                circle((size - stroke) * 0.5, &outline) + &circle(size * 0.25, &fill)
+            }
+            GateInstance::And => {
+                format!("<rect x=\"{x}\" y=\"{y}\" width=\"{size}\" height=\"{size}\" {fill}/>")
+            }
+            GateInstance::Resistor { .. } | GateInstance::Capacitor { .. } => {
+                square(&outline)
+                    + &format!(
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+                        x + size * 0.25,
+                        y + size * 0.25,
+                        size * 0.5,
+                        size * 0.5,
+                        ntd.unwrap_or_default(),
+                    )
+            }
+            GateInstance::Battery => {
+                square(&outline)
+                    + &format!(
+                        "<rect x=\"{x}\" y=\"{y}\" width=\"{}\" height=\"{size}\" {fill}/>",
+                        size * 0.5,
+                    )
+            }
+            GateInstance::Delay { .. } => {
+                square(&outline)
+                    + &format!(
+                        "<line x1=\"{cx}\" y1=\"{y}\" x2=\"{cx}\" y2=\"{}\" stroke-width=\"{stroke}\" {}/>",
+                        y + size,
+                        svg_paint("stroke", color),
+                    )
+            }
+            GateInstance::Led { .. } => {
+                format!(
+                    "<polygon points=\"{},{} {},{} {},{}\" {outline}/>",
+                    x + inset,
+                    y + inset,
+                    x + size - inset,
+                    cy,
+                    x + inset,
+                    y + size - inset,
+                ) + &circle(size * 0.125, &ntd.unwrap_or_default())
+            }
+            GateInstance::Split { .. } | GateInstance::Merge { .. } => {
+                // a bar at the back for split, where the bus arrives, and at the front for merge
+                let bar_x = match node.gate() {
+                    GateInstance::Split { .. } => x + size * 0.25,
+                    _ => x + size * 0.5,
+                };
+                square(&outline)
+                    + &format!(
+                        "<rect x=\"{bar_x}\" y=\"{}\" width=\"{}\" height=\"{}\" {fill}/>",
+                        y + size * 0.25,
+                        size * 0.25,
+                        size * 0.5,
+                    )
+            }
+            GateInstance::Input { .. } | GateInstance::Output { .. } => {
+                // a tag pointing forward, filled for inputs and hollow for outputs
+                let points = format!(
+                    "{},{} {},{} {},{cy} {},{} {},{}",
+                    x + inset,
+                    y + size * 0.2,
+                    x + size * 0.55,
+                    y + size * 0.2,
+                    x + size - inset,
+                    x + size * 0.55,
+                    y + size * 0.8,
+                    x + inset,
+                    y + size * 0.8,
+                );
+                let paint = match node.gate() {
+                    GateInstance::Input { .. } => &fill,
+                    _ => &outline,
+                };
+                format!("<polygon points=\"{points}\" {paint}/>")
+            }
+            GateInstance::Switch { on } => {
+                // a pill with its knob toward the front while on
+                let knob_x = if *on {
+                    x + size * 0.625
+                } else {
+                    x + size * 0.375
+                };
+                format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" {outline}/>",
+                    x + inset,
+                    y + size * 0.25,
+                    inner,
+                    size * 0.5,
+                    size * 0.25,
+                ) + &format!(
+                    "<circle cx=\"{knob_x}\" cy=\"{cy}\" r=\"{}\" {fill}/>",
+                    size * 0.125,
+                )
+            }
+            GateInstance::Button { .. } => {
+                circle((size - stroke) * 0.375, &outline) + &circle(size * 0.125, &fill)
+            }
+            GateInstance::Ic { .. } => {
+                square(&outline)
+                    + &format!(
+                        "<text x=\"{cx}\" y=\"{cy}\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\" {fill}>IC</text>",
+                        size * 0.4,
+                    )
+            }
+        };
+        _ = writeln!(
+            svg,
+            "<g transform=\"rotate({} {cx} {cy})\">{shapes}</g>",
+            node.orientation().degrees(),
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Returns a path in [`EXPORT_DIRECTORY`] named after `name` and the current time that is not
+/// occupied yet.
+fn unused_export_path(name: &str, format: ExportFormat) -> PathBuf {
+    let time = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    (0u32..)
+        .map(|n| {
+            let file_name = match n {
+                0 => format!("{name}-{time}.{format}"),
+                n => format!("{name}-{time}-{n}.{format}"),
+            };
+            Path::new(EXPORT_DIRECTORY).join(file_name)
+        })
+        .find(|path| !path.exists())
+        .expect("should not run out of file names")
+}
+
+/// Exports the focused tab's graph, or only its selected nodes, to a new file in
+/// [`EXPORT_DIRECTORY`].
+pub fn export_focused(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    console: &mut Console,
+    theme: &Theme,
+    tabs: &TabList,
+    settings: ExportSettings,
+) {
+    let ExportSettings {
+        format,
+        scale,
+        selection_only,
+    } = settings;
+    let Some(Tab::Editor(tab)) = tabs.focused_tab() else {
+        logln!(console, LogType::Error, "no graph is focused");
+        return;
+    };
+    let Some(graph) = tab.graph.upgrade() else {
+        logln!(console, LogType::Error, "tab graph no longer exists");
+        return;
+    };
+    let Ok(graph) = graph.try_read() else {
+        logln!(console, LogType::Warning, "graph is busy, try again");
+        return;
+    };
+    let selection = selection_only.then_some(&tab.selection);
+    let Some(scene) = Scene::new(&graph, selection) else {
+        logln!(
+            console,
+            LogType::Error,
+            "nothing to export; {} is empty",
+            if selection_only {
+                "the selection".to_string()
+            } else {
+                format!("graph {}", GraphRef(*graph.id()))
+            }
+        );
+        return;
+    };
+
+    let name = tab.path().and_then(Path::file_stem).map_or_else(
+        || graph.id().to_string(),
+        |stem| stem.to_string_lossy().into_owned(),
+    );
+    let path = unused_export_path(&name, format);
+    logln!(
+        console,
+        LogType::Attempt,
+        "exporting {} nodes to {}...",
+        scene.nodes.len(),
+        path.display()
+    );
+    let result = std::fs::create_dir_all(EXPORT_DIRECTORY).and_then(|()| match format {
+        ExportFormat::Png => write_png(rl, thread, theme, &scene, scale, &path),
+        ExportFormat::Svg => std::fs::write(&path, write_svg(theme, &scene, scale)),
+    });
+    match result {
+        Ok(()) => logln!(console, LogType::Success, "exported {}", path.display()),
+        Err(e) => logln!(console, LogType::Error, "failed to export: {e}"),
+    }
+}
@@ -0,0 +1,383 @@
+use crate::{
+    console::{Console, GraphRef, LogType},
+    graph::{Graph, GraphList, obj_format, trace::Trace},
+    logln,
+    tab::{EditorTab, Tab, TabList},
+};
+use raylib::prelude::*;
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+pub const SAVE_DIRECTORY: &str = "saves";
+pub const SAVE_EXTENSION: &str = "ea";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileAction {
+    /// Save the focused tab to its path, or to a new path if it has never been saved.
+    Save,
+    /// Ask for a path in the console to save the focused tab to.
+    SaveAs,
+    /// Ask for a path in the console to open, starting from the most recently modified save
+    /// that is not already open.
+    Open,
+}
+
+pub fn save_graph(graph: &Graph, path: &Path) -> std::io::Result<()> {
+    let s = toml::to_string(graph).map_err(std::io::Error::other)?;
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, s)
+}
+
+pub fn load_graph(path: &Path) -> std::io::Result<Graph> {
+    toml::from_str(&std::fs::read_to_string(path)?).map_err(std::io::Error::other)
+}
+
+/// Loads the graph at `path` into `graphs`, unless a graph there was already opened from or saved
+/// to `path`, along with the graphs run by its IC nodes that aren't open yet. Returns the graph,
+/// and the paths of IC graphs that failed to load, with why.
+pub fn open_graph(
+    graphs: &mut GraphList,
+    path: &Path,
+) -> std::io::Result<(Arc<RwLock<Graph>>, Vec<(PathBuf, std::io::Error)>)> {
+    let mut failed = Vec::new();
+    let graph = open_graph_into(graphs, path, &mut failed)?;
+    Ok((graph, failed))
+}
+
+fn open_graph_into(
+    graphs: &mut GraphList,
+    path: &Path,
+    failed: &mut Vec<(PathBuf, std::io::Error)>,
+) -> std::io::Result<Arc<RwLock<Graph>>> {
+    if let Some(graph) = graphs.find_path(path).and_then(|id| graphs.get(&id)) {
+        return Ok(graph.clone());
+    }
+    let graph = graphs.insert_graph(load_graph(path)?).clone();
+    graphs.set_path(*graph.read().unwrap().id(), path.to_path_buf());
+    // the graph is already known by its path, so ICs of it inside the graphs it runs find it
+    let mut borrow = graph.write().unwrap();
+    for ic_path in borrow.resolve_ics(graphs) {
+        if let Err(e) = open_graph_into(graphs, &ic_path, failed) {
+            failed.push((ic_path, e));
+        }
+    }
+    borrow.resolve_ics(graphs);
+    drop(borrow);
+    Ok(graph)
+}
+
+/// Logs the IC graphs that [`open_graph`] failed to load.
+pub fn log_failed_ics(console: &mut Console, failed: Vec<(PathBuf, std::io::Error)>) {
+    for (path, e) in failed {
+        logln!(
+            console,
+            LogType::Warning,
+            "failed to open {} for the ICs that run it: {e}",
+            path.display()
+        );
+    }
+}
+
+/// Writes the graph to `path` in the [`obj_format`].
+pub fn export_obj(graph: &Graph, path: &Path) -> std::io::Result<()> {
+    let s = obj_format::to_obj_string(graph).map_err(std::io::Error::other)?;
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, s)
+}
+
+pub fn import_obj_graph(path: &Path) -> std::io::Result<Graph> {
+    obj_format::from_obj_str(&std::fs::read_to_string(path)?).map_err(std::io::Error::other)
+}
+
+/// Reads the graph at `path`, in the [`obj_format`] if the extension says so.
+pub fn read_any_graph(path: &Path) -> std::io::Result<Graph> {
+    if path
+        .extension()
+        .is_some_and(|ext| ext == obj_format::OBJ_EXTENSION)
+    {
+        import_obj_graph(path)
+    } else {
+        load_graph(path)
+    }
+}
+
+/// Writes `trace` as a VCD file, with its signals in a module called `scope`.
+pub fn export_vcd(trace: &Trace, scope: &str, path: &Path) -> std::io::Result<()> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    trace.write_vcd(&mut out, scope)?;
+    out.flush()
+}
+
+/// Returns a path in [`SAVE_DIRECTORY`] that is not occupied by any file yet.
+pub fn unused_save_path() -> PathBuf {
+    (0u32..)
+        .map(|n| Path::new(SAVE_DIRECTORY).join(format!("circuit{n}.{SAVE_EXTENSION}")))
+        .find(|path| !path.exists())
+        .expect("should not run out of file names")
+}
+
+/// Returns the most recently modified save in [`SAVE_DIRECTORY`] for which `skip` returns false.
+pub fn latest_save(mut skip: impl FnMut(&Path) -> bool) -> Option<PathBuf> {
+    std::fs::read_dir(SAVE_DIRECTORY)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == SAVE_EXTENSION))
+        .filter(|path| !skip(path))
+        .filter_map(|path| Some((path.metadata().ok()?.modified().ok()?, path)))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+impl FileAction {
+    pub fn perform(self, console: &mut Console, graphs: &mut GraphList, tabs: &mut TabList) {
+        match self {
+            FileAction::Save => save_focused(console, graphs, tabs, None),
+
+            FileAction::SaveAs => {
+                let Some(Tab::Editor(tab)) = tabs.focused_tab() else {
+                    logln!(console, LogType::Warning, "no tab to save");
+                    return;
+                };
+                let path = tab.path().map_or_else(unused_save_path, Path::to_path_buf);
+                console.command_line.text = format!("save as {}", command_arg(&path));
+                logln!(
+                    console,
+                    LogType::Info,
+                    "edit the path in the console and press enter to save there"
+                );
+            }
+
+            FileAction::Open => {
+                let path = latest_save(|path| tabs.editors().any(|tab| tab.path() == Some(path)))
+                    .unwrap_or_else(|| Path::new(SAVE_DIRECTORY).join(""));
+                console.command_line.text = format!("open {}", command_arg(&path));
+                logln!(
+                    console,
+                    LogType::Info,
+                    "edit the path in the console and press enter to open it, or drop a .{SAVE_EXTENSION} file onto the window"
+                );
+            }
+        }
+    }
+}
+
+/// `path` as an argument of a console command, in quotes if it would otherwise be split.
+fn command_arg(path: &Path) -> String {
+    let path = path.display().to_string();
+    if path.contains(|c: char| c.is_whitespace() || c == '"') {
+        format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        path
+    }
+}
+
+/// Saves the focused tab to `path`, or to its own path if [`None`], or to a new path in
+/// [`SAVE_DIRECTORY`] if it has never been saved.
+pub fn save_focused(
+    console: &mut Console,
+    graphs: &mut GraphList,
+    tabs: &mut TabList,
+    path: Option<PathBuf>,
+) {
+    let Some(Tab::Editor(tab)) = tabs.focused_tab_mut() else {
+        logln!(console, LogType::Warning, "no tab to save");
+        return;
+    };
+    if tab.is_scratch() {
+        logln!(
+            console,
+            LogType::Warning,
+            "the scratch tab is never saved; copy what you want to keep into another tab"
+        );
+        return;
+    }
+    let path = path
+        .or_else(|| tab.path().map(Path::to_path_buf))
+        .unwrap_or_else(unused_save_path);
+    save_tab(tab, graphs, path, console);
+}
+
+/// Saves the tab's graph to `path`, along with where the graphs run by its ICs are saved, and
+/// marks the tab as saved if successful.
+pub fn save_tab(tab: &mut EditorTab, graphs: &mut GraphList, path: PathBuf, console: &mut Console) {
+    let Some(graph) = tab.graph.upgrade() else {
+        logln!(console, LogType::Error, "tab graph no longer exists");
+        return;
+    };
+    let mut graph = graph.write().unwrap();
+    graph.record_ic_paths(graphs);
+    logln!(
+        console,
+        LogType::Attempt,
+        "saving graph {} to {}...",
+        GraphRef(*graph.id()),
+        path.display(),
+    );
+    match save_graph(&graph, &path) {
+        Ok(()) => {
+            logln!(console, LogType::Success, "saved {}", path.display());
+            graphs.set_path(*graph.id(), path.clone());
+            tab.mark_saved(path);
+        }
+        Err(e) => {
+            logln!(console, LogType::Error, "failed to save: {e}");
+        }
+    }
+}
+
+/// Loads the graph at `path` into a new tab and focuses it, or opens a tab of the graph if it is
+/// already open.
+pub fn open_path(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    console: &mut Console,
+    graphs: &mut GraphList,
+    tabs: &mut TabList,
+    path: PathBuf,
+) {
+    logln!(console, LogType::Attempt, "opening {}...", path.display());
+    let graph = match open_graph(graphs, &path) {
+        Ok((graph, failed)) => {
+            log_failed_ics(console, failed);
+            graph
+        }
+        Err(e) => {
+            logln!(console, LogType::Error, "failed to open: {e}");
+            return;
+        }
+    };
+    let graph_id = *graph.read().unwrap().id();
+    let graph = Arc::downgrade(&graph);
+    match tabs.push_editor(rl, thread, graph) {
+        Ok(tab) => {
+            tab.mark_saved(path);
+            logln!(
+                console,
+                LogType::Success,
+                "opened graph {}",
+                GraphRef(graph_id)
+            );
+        }
+        Err(e) => {
+            logln!(console, LogType::Error, "failed to create tab: {e}");
+        }
+    }
+}
+
+/// Loads the graph in the [`obj_format`] file at `path` into a new tab and focuses it. The tab is
+/// left unsaved, since saving it writes the usual format rather than back to `path`.
+pub fn import_obj(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    console: &mut Console,
+    graphs: &mut GraphList,
+    tabs: &mut TabList,
+    path: &Path,
+) {
+    logln!(console, LogType::Attempt, "importing {}...", path.display());
+    let mut graph = match import_obj_graph(path) {
+        Ok(graph) => graph,
+        Err(e) => {
+            logln!(console, LogType::Error, "failed to import: {e}");
+            return;
+        }
+    };
+    for ic_path in graph.resolve_ics(graphs) {
+        logln!(
+            console,
+            LogType::Warning,
+            "{} is not open, so the ICs that run it won't run anything",
+            ic_path.display()
+        );
+    }
+    let graph = graphs.insert_graph(graph);
+    let graph_id = *graph.read().unwrap().id();
+    let graph = Arc::downgrade(graph);
+    match tabs.push_editor(rl, thread, graph) {
+        Ok(tab) => {
+            tab.mark_modified();
+            logln!(
+                console,
+                LogType::Success,
+                "imported graph {}",
+                GraphRef(graph_id)
+            );
+        }
+        Err(e) => {
+            logln!(console, LogType::Error, "failed to create tab: {e}");
+        }
+    }
+}
+
+/// Copies the graph at `path` into the focused tab's graph rather than opening a new tab, with
+/// its top-left node at `screen_pos` or where the mouse was last seen over the tab, and selects
+/// what was copied. Returns whether anything was.
+///
+/// IC nodes run the graphs in `graphs` they were saved with, or nothing if those aren't open.
+pub fn merge_path(
+    console: &mut Console,
+    graphs: &GraphList,
+    tabs: &mut TabList,
+    path: &Path,
+    screen_pos: Option<Vector2>,
+) -> bool {
+    logln!(console, LogType::Attempt, "merging {}...", path.display());
+    let mut source = match read_any_graph(path) {
+        Ok(graph) => graph,
+        Err(e) => {
+            logln!(console, LogType::Error, "failed to merge: {e}");
+            return false;
+        }
+    };
+    for ic_path in source.resolve_ics(graphs) {
+        logln!(
+            console,
+            LogType::Warning,
+            "{} is not open, so the ICs that run it won't run anything",
+            ic_path.display()
+        );
+    }
+    let Some(Tab::Editor(tab)) = tabs.focused_tab_mut() else {
+        logln!(console, LogType::Error, "no graph is focused");
+        return false;
+    };
+    if !tab.check_unlocked(console) {
+        return false;
+    }
+    let Some(graph) = tab.graph.upgrade() else {
+        logln!(console, LogType::Error, "no graph is focused");
+        return false;
+    };
+    let Ok(mut borrow) = graph.try_write() else {
+        logln!(console, LogType::Warning, "graph is busy, try again");
+        return false;
+    };
+    let node_ids = borrow.merge(&source, tab.node_position_at(screen_pos), console);
+    drop(borrow);
+    if node_ids.is_empty() {
+        return false;
+    }
+    tab.selection = node_ids.into_iter().collect();
+    for tab in tabs.editors_of_graph_mut(&Arc::downgrade(&graph)) {
+        tab.mark_modified();
+    }
+    true
+}
@@ -0,0 +1,141 @@
+//! A wheel of gates around the cursor, opened by holding [`Inputs::gate_wheel`] over an editor,
+//! for switching gates without going over to the toolpane. Letting go picks the gate in the
+//! direction the cursor was moved, or none if it was hardly moved at all.
+
+use crate::{
+    graph::node::GateId,
+    icon_sheets::{ButtonIconId, ButtonIconSheetId},
+    input::Inputs,
+    locale::tr,
+    theme::Theme,
+};
+use raylib::prelude::*;
+use std::f32::consts::TAU;
+
+/// From the center of the wheel to the center of each icon.
+const WHEEL_RADIUS: f32 = 72.0;
+/// How far the cursor has to be moved from the center before a gate is picked.
+const DEAD_ZONE: f32 = 16.0;
+const ICON_SHEET: ButtonIconSheetId = ButtonIconSheetId::X32;
+
+/// The [locale](crate::locale) key of the name of `gate`.
+const fn name_key(gate: GateId) -> &'static str {
+    match gate {
+        GateId::Or => "gate.or",
+        GateId::And => "gate.and",
+        GateId::Nor => "gate.nor",
+        GateId::Xor => "gate.xor",
+        GateId::Resistor => "gate.resistor",
+        GateId::Capacitor => "gate.capacitor",
+        GateId::Led => "gate.led",
+        GateId::Delay => "gate.delay",
+        GateId::Battery => "gate.battery",
+        GateId::Ic => "gate.ic",
+        GateId::Split => "gate.split",
+        GateId::Merge => "gate.merge",
+        GateId::Input => "gate.input",
+        GateId::Output => "gate.output",
+        GateId::Switch => "gate.switch",
+        GateId::Button => "gate.button",
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct GateWheel {
+    /// Where the wheel was opened, while it is open
+    center: Option<Vector2>,
+    /// Clockwise from the top
+    gates: Vec<GateId>,
+}
+
+impl GateWheel {
+    /// Whether the wheel is open, in which case no panel should take input.
+    #[inline]
+    pub const fn is_open(&self) -> bool {
+        self.center.is_some()
+    }
+
+    /// Opens the wheel around `center`, with `gates` clockwise from the top.
+    pub fn open(&mut self, center: Vector2, gates: Vec<GateId>) {
+        self.center = Some(center);
+        self.gates = gates;
+    }
+
+    /// Center of the icon of the gate at `index`.
+    fn icon_center(&self, center: Vector2, index: usize) -> Vector2 {
+        let angle = index as f32 * TAU / self.gates.len() as f32;
+        center + Vector2::new(angle.sin(), -angle.cos()) * WHEEL_RADIUS
+    }
+
+    /// The gate in the direction of `cursor` from the center, if the wheel is open and the cursor
+    /// is past [`DEAD_ZONE`].
+    fn pointed(&self, cursor: Vector2) -> Option<GateId> {
+        let offset = cursor - self.center?;
+        if self.gates.is_empty() || offset.length() < DEAD_ZONE {
+            return None;
+        }
+        let step = TAU / self.gates.len() as f32;
+        // clockwise from the top, with each gate in the middle of its slice
+        let angle = (offset.x.atan2(-offset.y) + 0.5 * step).rem_euclid(TAU);
+        Some(self.gates[((angle / step) as usize).min(self.gates.len() - 1)])
+    }
+
+    /// Closes the wheel once [`Inputs::gate_wheel`] is let go, returning the gate picked.
+    pub fn tick(&mut self, input: &Inputs) -> Option<GateId> {
+        if !self.is_open() || input.gate_wheel.is_active() {
+            return None;
+        }
+        let picked = self.pointed(input.cursor);
+        self.center = None;
+        picked
+    }
+
+    /// Draws the wheel, marking the `current` gate and the one that would be picked.
+    pub fn draw<D: RaylibDraw>(&self, d: &mut D, theme: &Theme, input: &Inputs, current: GateId) {
+        let Some(center) = self.center else {
+            return;
+        };
+        let pointed = self.pointed(input.cursor);
+        let icon_width = ICON_SHEET.icon_width();
+        let icon_size = icon_width as f32;
+        d.draw_circle_v(
+            center,
+            WHEEL_RADIUS + icon_size,
+            theme.background1.alpha(0.85),
+        );
+        d.draw_circle_v(center, DEAD_ZONE, theme.background2);
+        for (n, &gate) in self.gates.iter().enumerate() {
+            let position = self.icon_center(center, n);
+            let is_pointed = pointed == Some(gate);
+            if is_pointed {
+                d.draw_circle_v(position, 0.75 * icon_size, theme.background3);
+            }
+            d.draw_texture_pro(
+                &theme.button_icons[ICON_SHEET],
+                ButtonIconId::from(gate).icon_cell_irec(icon_width).as_rec(),
+                Rectangle::new(
+                    position.x - 0.5 * icon_size,
+                    position.y - 0.5 * icon_size,
+                    icon_size,
+                    icon_size,
+                ),
+                Vector2::zero(),
+                0.0,
+                if is_pointed {
+                    theme.foreground1
+                } else if gate == current {
+                    theme.foreground
+                } else {
+                    theme.foreground2
+                },
+            );
+        }
+        if let Some(gate) = pointed {
+            let name = tr(name_key(gate));
+            let size = theme.general_font.measure_text(name);
+            theme
+                .general_font
+                .draw_text(d, name, center - size * 0.5, theme.foreground);
+        }
+    }
+}
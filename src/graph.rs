@@ -1,26 +1,52 @@
 use crate::{
-    GRID_SIZE,
+    DEFAULT_GRID_SIZE,
     console::{Console, GateRef, GraphRef, LogType, NodeRef, PositionRef},
     graph::{
-        node::{Gate, Node, NodeId},
-        wire::{Elbow, Flow, Wire, WireId},
+        annotation::{Annotation, AnnotationId},
+        bitset::BitSet,
+        history::{HISTORY_CAPACITY, History},
+        ic::{IcError, IcInstance},
+        jitter::Jitter,
+        limits::FanLimits,
+        node::{Bits, Gate, GateId, GateInstance, Node, NodeId, Ntd},
+        node_grid::NodeGrid,
+        region::{Region, RegionId},
+        trace::Trace,
+        wire::{Elbow, Flow, Wire, WireEnd, WireId},
     },
-    ivec::IVec2,
+    ivec::{IBounds, IVec2},
     logln,
 };
+use raylib::prelude::Vector2;
 use rustc_hash::{FxHashMap, FxHashSet};
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 use std::{
     collections::VecDeque,
     marker::PhantomData,
-    sync::{Arc, RwLock},
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError, Weak},
 };
 
+pub mod annotation;
+pub mod bench;
+pub mod bitset;
+pub mod crossing;
 pub mod eag;
+pub mod history;
+pub mod ic;
+pub mod jitter;
+pub mod limits;
+pub mod model;
 pub mod node;
+pub mod node_grid;
+pub mod obj_format;
+pub mod region;
+pub mod route;
+pub mod trace;
+pub mod truth_table;
 pub mod wire;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct GraphId(u32);
 
 /// Defaults to [`Self::INVALID`]
@@ -220,17 +246,135 @@ impl ExactSizeIterator for RevEvalOrderIter<'_> {
 
 impl std::iter::FusedIterator for RevEvalOrderIter<'_> {}
 
+/// How [`Graph::evaluate`] propagates states within a single tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum EvalMode {
+    /// Nodes are updated in place following the eval order, so a node sees the states its
+    /// inputs were given earlier in the same tick.
+    #[default]
+    Asynchronous,
+    /// Every node reads the states from the previous tick, like a circuit driven by a shared clock.
+    Synchronous,
+}
+
+impl std::fmt::Display for EvalMode {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Asynchronous => "asynchronous",
+            Self::Synchronous => "synchronous",
+        }
+        .fmt(f)
+    }
+}
+
+impl EvalMode {
+    #[inline]
+    pub const fn toggled(self) -> Self {
+        match self {
+            Self::Asynchronous => Self::Synchronous,
+            Self::Synchronous => Self::Asynchronous,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
-#[serde(from = "eag::GraphTemplate")]
+#[serde(try_from = "eag::GraphTemplate")]
 pub struct Graph {
     next_node_id: NodeId,
     next_wire_id: WireId,
     id: GraphId,
     nodes: FxHashMap<NodeId, Node>,
     wires: FxHashMap<WireId, Wire>,
-    node_grid: FxHashMap<IVec2, NodeId>,
+    node_grid: NodeGrid,
+    next_region_id: RegionId,
+    /// Labeled areas drawn behind the nodes, which don't affect evaluation
+    regions: FxHashMap<RegionId, Region>,
+    next_annotation_id: AnnotationId,
+    /// Free text drawn in the editor, which doesn't affect evaluation
+    annotations: FxHashMap<AnnotationId, Annotation>,
     eval_order: Vec<NodeId>,
     is_eval_order_dirty: bool,
+    eval_mode: EvalMode,
+    /// Shuffles the eval order every tick while set; not saved
+    jitter: Option<Jitter>,
+    /// Node states in eval order, kept in sync with each node's own state
+    states: Vec<Bits>,
+    /// Next states in eval order, only used by [`EvalMode::Synchronous`]
+    next_states: Vec<Bits>,
+    /// Where each node's inputs start in `eval_inputs`, in eval order, followed by the end
+    eval_input_starts: Vec<usize>,
+    /// The eval order index of each node's inputs
+    eval_inputs: Vec<usize>,
+    /// Where each node's outputs start in `eval_outputs`, in eval order, followed by the end
+    eval_output_starts: Vec<usize>,
+    /// The eval order index of each node's outputs
+    eval_outputs: Vec<usize>,
+    /// Eval order indices of nodes that an input changed for since they were last evaluated
+    eval_pending: BitSet,
+    /// Eval order indices of nodes that change over time by themselves, evaluated every tick
+    eval_always: BitSet,
+    /// The [`Self::revision`] `eval_pending` and `eval_always` are up to date with, or [`None`]
+    /// if every node should be evaluated next tick
+    pending_revision: Option<u64>,
+    fan_limits: FanLimits,
+    /// Node states after each tick, while recording
+    history: Option<History>,
+    /// States of the probed nodes after each tick, while any are probed
+    trace: Option<Trace>,
+    /// Ticks evaluated since the graph was created or loaded
+    ticks: u64,
+    /// The tick each node's state last changed on, for [`Self::ticks_since_change`]. Nodes
+    /// that haven't changed since the graph was loaded are left out.
+    changed_at: FxHashMap<NodeId, u64>,
+    /// Incremented by every change that could affect how the graph behaves as an IC
+    revision: u64,
+    /// Width and height of a cell in world units, which node positions are multiples of
+    grid_size: u8,
+    /// The graph copy run by each IC node, or why it has none
+    ics: FxHashMap<NodeId, Result<IcInstance, IcError>>,
+    /// Where the graph run by each IC node is saved, written with the graph so that the IC can
+    /// find it again once graph IDs have changed
+    ic_paths: FxHashMap<NodeId, PathBuf>,
+}
+
+/// A weakly connected component of a graph, see [`Graph::islands`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Island {
+    pub nodes: FxHashSet<NodeId>,
+    /// The top-left-most node of the island, for referring to it.
+    pub representative: NodeId,
+    /// Positions of the nodes, inclusive of `max`
+    pub bounds: IBounds,
+}
+
+/// Why [`Graph::create_wire`] refused to create a wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CreateWireError {
+    /// An identical wire already exists.
+    Exists(WireId),
+    /// The source node already has as many outputs as its gate's fan-out limit allows.
+    FanOut,
+    /// The destination node already has as many inputs as its gate's fan-in limit allows.
+    FanIn,
+}
+
+/// What [`Graph::erase_node`] and [`Graph::erase_wire`] took out of a graph, to be put back by
+/// [`Graph::restore`].
+#[derive(Debug, Clone, Default)]
+pub struct Erased {
+    nodes: Vec<Node>,
+    wires: Vec<Wire>,
+    /// Wires created to keep chains through soft-erased nodes connected, which go again when
+    /// the nodes come back
+    bypasses: Vec<WireId>,
+}
+
+impl Erased {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.wires.is_empty()
+    }
 }
 
 type EvalOrder = std::iter::Rev<std::vec::IntoIter<NodeId>>;
@@ -247,28 +391,175 @@ impl Graph {
             id,
             nodes: FxHashMap::default(),
             wires: FxHashMap::default(),
-            node_grid: FxHashMap::default(),
+            node_grid: NodeGrid::default(),
+            next_region_id: RegionId(0),
+            regions: FxHashMap::default(),
+            next_annotation_id: AnnotationId(0),
+            annotations: FxHashMap::default(),
             eval_order: Vec::new(),
             is_eval_order_dirty: false,
+            eval_mode: EvalMode::default(),
+            jitter: None,
+            states: Vec::new(),
+            next_states: Vec::new(),
+            eval_input_starts: Vec::new(),
+            eval_inputs: Vec::new(),
+            eval_output_starts: Vec::new(),
+            eval_outputs: Vec::new(),
+            eval_pending: BitSet::default(),
+            eval_always: BitSet::default(),
+            pending_revision: None,
+            fan_limits: FanLimits::NONE,
+            history: None,
+            trace: None,
+            ticks: 0,
+            changed_at: FxHashMap::default(),
+            revision: 0,
+            ics: FxHashMap::default(),
+            ic_paths: FxHashMap::default(),
+            grid_size: DEFAULT_GRID_SIZE,
         }
     }
 
+    /// The cell containing the point `world_pos`.
+    #[inline]
+    const fn cell_at(world_pos: IVec2, grid_size: u8) -> IVec2 {
+        let size = grid_size as i32;
+        IVec2::new(world_pos.x.div_euclid(size), world_pos.y.div_euclid(size))
+    }
+
+    /// The cell a node at `position` is filed under: the one holding its center, so that nodes
+    /// placed off the grid still take up one cell each.
     #[inline]
-    fn world_to_grid(world_pos: IVec2) -> IVec2 {
-        IVec2::new(
-            world_pos.x / i32::from(GRID_SIZE),
-            world_pos.y / i32::from(GRID_SIZE),
+    const fn node_cell(position: IVec2, grid_size: u8) -> IVec2 {
+        let half = grid_size as i32 / 2;
+        Self::cell_at(
+            IVec2::new(
+                position.x.saturating_add(half),
+                position.y.saturating_add(half),
+            ),
+            grid_size,
         )
     }
 
+    /// Where a node at `position` on a grid of `old` size goes on a grid of `new` size: into
+    /// the matching cell, with nodes off the grid keeping their offset from it, scaled and kept
+    /// inside the cell.
+    fn rescale_position(position: IVec2, old: u8, new: u8) -> IVec2 {
+        let cell = Self::node_cell(position, old);
+        let (old, new) = (i32::from(old), i32::from(new));
+        let scale = |cell: i32, p: i32| {
+            let offset = p - cell * old;
+            cell * new
+                + (offset * new)
+                    .div_euclid(old)
+                    .clamp(-(new / 2), new - new / 2 - 1)
+        };
+        IVec2::new(scale(cell.x, position.x), scale(cell.y, position.y))
+    }
+
+    #[inline]
+    pub const fn grid_size(&self) -> u8 {
+        self.grid_size
+    }
+
+    /// Changes the grid size, scaling every node's position to stay in the same cell.
+    pub fn set_grid_size(&mut self, grid_size: u8) {
+        assert_ne!(grid_size, 0, "grid size should be positive");
+        let (old, new) = (i32::from(self.grid_size), i32::from(grid_size));
+        for node in self.nodes.values_mut() {
+            node.position = Self::rescale_position(node.position, self.grid_size, grid_size);
+        }
+        let scale = |p: IVec2| IVec2::new((p.x * new).div_euclid(old), (p.y * new).div_euclid(old));
+        for region in self.regions.values_mut() {
+            region.bounds = IBounds::new(scale(region.bounds.min), scale(region.bounds.max));
+        }
+        for annotation in self.annotations.values_mut() {
+            annotation.position = scale(annotation.position);
+        }
+        // route corners are always on the grid
+        for corner in self
+            .wires
+            .values_mut()
+            .flat_map(|wire| wire.route.iter_mut().flatten())
+        {
+            *corner = IVec2::new(
+                corner.x.div_euclid(old) * new,
+                corner.y.div_euclid(old) * new,
+            );
+        }
+        self.grid_size = grid_size;
+        self.revision += 1;
+    }
+
+    /// From a node's position to its center.
+    #[inline]
+    pub fn cell_center(&self) -> Vector2 {
+        let half = f32::from(self.grid_size) * 0.5;
+        Vector2::new(half, half)
+    }
+
     #[inline]
     pub const fn id(&self) -> &GraphId {
         &self.id
     }
 
+    /// Changes whenever the graph does, for noticing changes without comparing it.
     #[inline]
+    pub const fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// The node covering the point `pos`, if any.
     pub fn find_node_at(&self, pos: IVec2) -> Option<&NodeId> {
-        self.node_grid.get(&Self::world_to_grid(pos))
+        let cell = Self::cell_at(pos, self.grid_size);
+        let size = i32::from(self.grid_size);
+        // nodes off the grid reach into the cells around the one they're filed under
+        [
+            (0, 0),
+            (-1, 0),
+            (0, -1),
+            (-1, -1),
+            (1, 0),
+            (0, 1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+        ]
+        .into_iter()
+        .filter_map(|(x, y)| self.node_grid.get(&IVec2::new(cell.x + x, cell.y + y)))
+        .find(|id| {
+            let position = self.nodes[*id].position;
+            (position.x..position.x.saturating_add(size)).contains(&pos.x)
+                && (position.y..position.y.saturating_add(size)).contains(&pos.y)
+        })
+    }
+
+    /// The nodes positioned within `bounds` (see [`IBounds::contains`]), in no particular order.
+    ///
+    /// Only visits the part of the graph near `bounds`, unlike filtering [`Self::nodes_iter`].
+    pub fn nodes_in_bounds(&self, bounds: IBounds) -> impl Iterator<Item = &NodeId> {
+        self.node_grid
+            .in_cells(
+                Self::node_cell(bounds.min, self.grid_size),
+                Self::node_cell(bounds.max, self.grid_size),
+            )
+            .map(|(_, id)| id)
+            .filter(move |id| bounds.contains(self.nodes[*id].position))
+    }
+
+    /// Returns the wire whose path passes closest to `pos`, if any are within `tolerance`.
+    /// Wires run between the sides of their nodes given by each node's orientation.
+    pub fn find_wire_at(&self, pos: Vector2, tolerance: f32) -> Option<&WireId> {
+        let offset = self.cell_center();
+        self.wires
+            .iter()
+            .filter_map(|(id, wire)| {
+                let distance = Wire::polyline_distance(&wire.path(self, offset)?, pos);
+                (distance <= tolerance).then_some((id, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(id, _)| id)
     }
 
     #[inline]
@@ -278,6 +569,8 @@ impl Graph {
 
     #[inline]
     pub fn node_mut(&mut self, id: &NodeId) -> Option<&mut Node> {
+        // the caller may change the gate
+        self.revision += 1;
         self.nodes.get_mut(id)
     }
 
@@ -299,7 +592,7 @@ impl Graph {
         console: &mut Console,
     ) -> Result<&mut Node, NodeId> {
         let id = self.next_node_id.step().expect("out of IDs");
-        let grid_pos = Self::world_to_grid(position);
+        let grid_pos = Self::node_cell(position, self.grid_size);
         if let Some(&existing) = self.node_grid.get(&grid_pos) {
             logln!(
                 console,
@@ -314,9 +607,10 @@ impl Graph {
             let node = self
                 .nodes
                 .entry(id)
-                .insert_entry(Node::new(id, gate, position, false))
+                .insert_entry(Node::new(id, gate, position, Bits::ZERO))
                 .into_mut();
             self.is_eval_order_dirty = true;
+            self.revision += 1;
 
             logln!(
                 console,
@@ -330,6 +624,8 @@ impl Graph {
         }
     }
 
+    /// Routed wires of the node find a new way around the nodes in their way.
+    ///
     /// Returns [`None`] if `id` is not a node in this graph.
     pub fn translate_node(
         &mut self,
@@ -337,54 +633,395 @@ impl Graph {
         new_position: IVec2,
         console: &mut Console,
     ) -> Option<()> {
-        self.nodes.get_mut(id).map(|node| {
-            let old_grid_position = Self::world_to_grid(node.position);
-            let new_grid_position = Self::world_to_grid(new_position);
-            if old_grid_position != new_grid_position {
-                let id = self
-                    .node_grid
-                    .remove(&old_grid_position)
-                    .filter(|x| x == id)
-                    .expect(
-                        "nodes should not be moved without updating their position in node_grid",
-                    );
-                self.node_grid.insert(new_grid_position, id);
+        let is_moved = self.nodes.get_mut(id).map(|node| {
+            let old_grid_position = Self::node_cell(node.position, self.grid_size);
+            let new_grid_position = Self::node_cell(new_position, self.grid_size);
+            if let Some(&existing) = self.node_grid.get(&new_grid_position)
+                && existing != *id
+            {
+                logln!(
+                    console,
+                    LogType::Info,
+                    "node at {} already exists: {}",
+                    PositionRef(new_position),
+                    NodeRef(self.id, existing),
+                );
+                false
+            } else if node.position != new_position {
+                if old_grid_position != new_grid_position {
+                    self.node_grid
+                        .remove(&old_grid_position)
+                        .filter(|x| x == id)
+                        .expect(
+                            "nodes should not be moved without updating their position in node_grid",
+                        );
+                    self.node_grid.insert(new_grid_position, *id);
+                }
+                // pins of ICs of this graph are ordered by position
+                self.revision += 1;
 
                 let old_position = std::mem::replace(&mut node.position, new_position);
                 logln!(
                     console,
                     LogType::Info,
                     "move node {} from {} to {}",
-                    NodeRef(self.id, id),
+                    NodeRef(self.id, *id),
                     PositionRef(old_position),
                     PositionRef(new_position),
                 );
+                true
+            } else {
+                false
+            }
+        })?;
+        if is_moved {
+            self.reroute_wires_of(id);
+        }
+        Some(())
+    }
+
+    /// Replaces the gate of a node, discarding anything the old gate stored. A node with more
+    /// wires than the new gate's fan limits allow keeps its gate.
+    ///
+    /// Returns [`None`] if `id` is not a node in this graph.
+    pub fn set_node_gate(&mut self, id: &NodeId, gate: Gate, console: &mut Console) -> Option<()> {
+        self.nodes.get(id)?;
+        self.replace_gates([(*id, gate)], console);
+        Some(())
+    }
+
+    /// Replaces the gate of node `id` with `gate` without logging it, unless it already has
+    /// that gate or has more wires than the fan limits of `gate` allow. Returns the old gate if
+    /// it was replaced.
+    fn swap_node_gate(&mut self, id: &NodeId, gate: Gate, console: &mut Console) -> Option<Gate> {
+        let old_gate = self.nodes.get(id)?.gate.as_gate();
+        if old_gate == gate || !self.check_gate_fan(id, gate.id(), console) {
+            return None;
+        }
+        self.nodes.get_mut(id)?.set_gate(gate);
+        Some(old_gate)
+    }
+
+    /// Checks whether node `id` has few enough wires for the fan limits of `gate_id`, logging
+    /// why not, so that replacing its gate can't get around [`Self::create_wire`].
+    fn check_gate_fan(&self, id: &NodeId, gate_id: GateId, console: &mut Console) -> bool {
+        let limit = self.fan_limits[gate_id];
+        if limit.max_in.is_none() && limit.max_out.is_none() {
+            return true;
+        }
+        let fan_in = self.wires.values().filter(|wire| wire.dst == *id).count();
+        let fan_out = self.wires.values().filter(|wire| wire.src == *id).count();
+        if let Some(max) = limit.max_in
+            && fan_in > max
+        {
+            logln!(
+                console,
+                LogType::Error,
+                "cannot replace the gate of {}: {gate_id} gates may have at most {max} inputs \
+                 and it has {fan_in}",
+                GraphRef(self.id).node(*id),
+            );
+            return false;
+        }
+        if let Some(max) = limit.max_out
+            && fan_out > max
+        {
+            logln!(
+                console,
+                LogType::Error,
+                "cannot replace the gate of {}: {gate_id} gates may have at most {max} outputs \
+                 and it has {fan_out}",
+                GraphRef(self.id).node(*id),
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Changes the NTD value of a node, keeping as much of its gate's state as still fits.
+    /// Nothing happens if its gate has no NTD value.
+    ///
+    /// Returns [`None`] if `id` is not a node in this graph.
+    pub fn set_node_ntd(&mut self, id: &NodeId, value: Ntd, console: &mut Console) -> Option<()> {
+        self.nodes.get_mut(id).map(|node| {
+            if let Some(old_value) = node.gate.as_gate().ntd()
+                && old_value != value
+            {
+                node.gate.set_ntd(value);
+                self.revision += 1;
+                logln!(
+                    console,
+                    LogType::Info,
+                    "set NTD of node {} from {old_value} to {value}",
+                    NodeRef(self.id, *id),
+                );
+            }
+        })
+    }
+
+    /// Replaces the gate of every node in `ids` that has a different one, as a single change.
+    /// Each node keeps its NTD value if the new gate has one. Nodes with more wires than the
+    /// new gate's fan limits allow are skipped. Returns how many were replaced.
+    pub fn set_nodes_gate<'a>(
+        &mut self,
+        ids: impl IntoIterator<Item = &'a NodeId>,
+        gate_id: GateId,
+        console: &mut Console,
+    ) -> usize {
+        let mut count = 0;
+        for id in ids {
+            if let Some(old_gate) = self.nodes.get(id).map(|node| node.gate.as_gate())
+                && old_gate.id() != gate_id
+                && self
+                    .swap_node_gate(
+                        id,
+                        gate_id.to_gate(old_gate.ntd().unwrap_or_default()),
+                        console,
+                    )
+                    .is_some()
+            {
+                count += 1;
+            }
+        }
+        if count > 0 {
+            self.revision += 1;
+            logln!(
+                console,
+                LogType::Info,
+                "replace {count} nodes with {gate_id}"
+            );
+        }
+        count
+    }
+
+    /// Replaces the gate and NTD value of every node in `ids` with `gate`, keeping their wires.
+    /// Nodes with more wires than the fan limits of `gate` allow are skipped.
+    ///
+    /// Returns the replaced nodes with their old gates, which [`Self::replace_gates`] can put
+    /// back.
+    pub fn replace_nodes_gate<'a>(
+        &mut self,
+        ids: impl IntoIterator<Item = &'a NodeId>,
+        gate: Gate,
+        console: &mut Console,
+    ) -> Vec<(NodeId, Gate)> {
+        self.replace_gates(ids.into_iter().map(|id| (*id, gate)), console)
+    }
+
+    /// Replaces the gate of each node with the gate paired with it, as a single change, logging
+    /// each replacement. Nodes that are missing, already have their gate, or have more wires than
+    /// its fan limits allow are skipped.
+    ///
+    /// Returns the replaced nodes with their old gates, so that passing them back in undoes the
+    /// replacement.
+    pub fn replace_gates(
+        &mut self,
+        gates: impl IntoIterator<Item = (NodeId, Gate)>,
+        console: &mut Console,
+    ) -> Vec<(NodeId, Gate)> {
+        let mut replaced = Vec::new();
+        for (id, gate) in gates {
+            if let Some(old_gate) = self.swap_node_gate(&id, gate, console) {
+                logln!(
+                    console,
+                    LogType::Info,
+                    "replace {} node {} with {}",
+                    GateRef(old_gate),
+                    NodeRef(self.id, id),
+                    GateRef(gate),
+                );
+                replaced.push((id, old_gate));
+            }
+        }
+        if !replaced.is_empty() {
+            self.revision += 1;
+        }
+        replaced
+    }
+
+    /// Changes the NTD value of every node in `ids` that has a different one, as a single change.
+    /// Nodes whose gates have no NTD value are left alone. Returns how many were changed.
+    pub fn set_nodes_ntd<'a>(
+        &mut self,
+        ids: impl IntoIterator<Item = &'a NodeId>,
+        value: Ntd,
+        console: &mut Console,
+    ) -> usize {
+        let mut count = 0;
+        for id in ids {
+            if let Some(node) = self.nodes.get_mut(id)
+                && node
+                    .gate
+                    .as_gate()
+                    .ntd()
+                    .is_some_and(|old_value| old_value != value)
+            {
+                node.gate.set_ntd(value);
+                count += 1;
+            }
+        }
+        if count > 0 {
+            self.revision += 1;
+            logln!(
+                console,
+                LogType::Info,
+                "set NTD of {count} nodes to {value}",
+            );
+        }
+        count
+    }
+
+    /// Returns [`None`] if `id` is not a node in this graph.
+    pub fn set_node_width(&mut self, id: &NodeId, width: u8, console: &mut Console) -> Option<()> {
+        self.nodes.get_mut(id).map(|node| {
+            let old_width = node.width;
+            node.set_width(width);
+            if node.width != old_width {
+                self.revision += 1;
+                logln!(
+                    console,
+                    LogType::Info,
+                    "set width of node {} from {old_width} to {}",
+                    NodeRef(self.id, *id),
+                    node.width,
+                );
             }
         })
     }
 
     /// Returns [`None`] if `id` is not a node in this graph.
+    ///
+    /// If `soft`, every node wired into the node is wired straight to every node it was wired to,
+    /// so that removing a node from the middle of a chain keeps the chain connected. Each new
+    /// wire has the elbow of the wire it replaces that led out of the node.
     #[must_use]
+    #[inline]
     pub fn destroy_node(&mut self, id: &NodeId, soft: bool, console: &mut Console) -> Option<Node> {
-        self.nodes.remove(id).inspect(|node| {
-            self.node_grid
-                .remove(&Self::world_to_grid(node.position))
-                .filter(|x| x == id)
-                .expect("nodes should not be moved without updating their position in node_grid");
-            if soft {
-                todo!()
-            } else {
-                self.wires
-                    .retain(|_, wire| &wire.src != id && &wire.dst != id);
+        self.remove_node(id, soft, console).map(|(node, ..)| node)
+    }
+
+    /// [`Self::destroy_node`], also returning the wires removed with the node and the IDs of the
+    /// wires created to bypass it.
+    fn remove_node(
+        &mut self,
+        id: &NodeId,
+        soft: bool,
+        console: &mut Console,
+    ) -> Option<(Node, Vec<Wire>, Vec<WireId>)> {
+        let node = self.nodes.remove(id)?;
+        self.changed_at.remove(id);
+        self.node_grid
+            .remove(&Self::node_cell(node.position, self.grid_size))
+            .filter(|x| x == id)
+            .expect("nodes should not be moved without updating their position in node_grid");
+        let (mut srcs, mut dsts) = (Vec::new(), Vec::new());
+        let mut removed = Vec::new();
+        for wire in self.wires.values() {
+            if &wire.dst == id {
+                srcs.push(wire.src);
+                removed.push(*wire.id());
+            } else if &wire.src == id {
+                dsts.push((wire.dst, wire.elbow));
+                removed.push(*wire.id());
+            }
+        }
+        let wires = removed
+            .iter()
+            .filter_map(|wire_id| self.wires.remove(wire_id))
+            .collect();
+        self.is_eval_order_dirty = true;
+        self.revision += 1;
+        logln!(
+            console,
+            LogType::Info,
+            "destroy node {}",
+            NodeRef(self.id, *id)
+        );
+        let mut bypasses = Vec::new();
+        if soft {
+            // in a consistent order, so that the same deletion always gives the same wire IDs
+            srcs.sort_unstable_by_key(|src| src.0);
+            dsts.sort_unstable_by_key(|(dst, _)| dst.0);
+            for &src in &srcs {
+                for &(dst, elbow) in &dsts {
+                    // a node can't be wired to itself, and `create_wire` skips wires that
+                    // already exist or would break a fan limit
+                    if src != dst
+                        && let Ok(wire) = self.create_wire(elbow, src, dst, console)
+                    {
+                        bypasses.push(*wire.id());
+                    }
+                }
+            }
+        }
+        Some((node, wires, bypasses))
+    }
+
+    /// [`Self::destroy_node`], keeping what was removed in `erased`. Returns whether `id` was a
+    /// node in this graph.
+    pub fn erase_node(
+        &mut self,
+        id: &NodeId,
+        soft: bool,
+        erased: &mut Erased,
+        console: &mut Console,
+    ) -> bool {
+        let Some((node, wires, bypasses)) = self.remove_node(id, soft, console) else {
+            return false;
+        };
+        erased.nodes.push(node);
+        erased.wires.extend(wires);
+        erased.bypasses.extend(bypasses);
+        true
+    }
+
+    /// [`Self::destroy_wire`], keeping the wire in `erased`. Returns whether `id` was a wire in
+    /// this graph.
+    pub fn erase_wire(&mut self, id: &WireId, erased: &mut Erased) -> bool {
+        self.destroy_wire(id)
+            .map(|wire| erased.wires.push(wire))
+            .is_some()
+    }
+
+    /// Puts back what was erased into `erased` with the IDs it had, and takes away the wires
+    /// that bypassed soft-erased nodes. Nodes whose cells have been taken since are left out,
+    /// as are wires whose ends are missing, that were made again, or that would break a fan
+    /// limit of the gates at their ends. Returns how many nodes and wires came back.
+    pub fn restore(&mut self, erased: Erased, console: &mut Console) -> (usize, usize) {
+        for id in &erased.bypasses {
+            _ = self.destroy_wire(id);
+        }
+        let mut nodes = 0;
+        for node in erased.nodes {
+            let cell = Self::node_cell(node.position, self.grid_size);
+            if !self.node_grid.contains_key(&cell) && !self.nodes.contains_key(node.id()) {
+                self.node_grid.insert(cell, *node.id());
+                self.nodes.insert(*node.id(), node);
+                nodes += 1;
+            }
+        }
+        let mut wires = 0;
+        for wire in erased.wires {
+            if !erased.bypasses.contains(wire.id())
+                && self.nodes.contains_key(&wire.src)
+                && self.nodes.contains_key(&wire.dst)
+                && !self.wires.contains_key(wire.id())
+                && self.check_new_wire(wire.src, wire.dst, console).is_ok()
+            {
+                self.wires.insert(*wire.id(), wire);
+                wires += 1;
             }
+        }
+        if nodes + wires > 0 {
             self.is_eval_order_dirty = true;
+            self.revision += 1;
             logln!(
                 console,
                 LogType::Info,
-                "destroy node {}",
-                NodeRef(self.id, *id)
+                "restore {nodes} nodes and {wires} wires"
             );
-        })
+        }
+        (nodes, wires)
     }
 
     /// # Errors
@@ -398,15 +1035,43 @@ impl Graph {
         src: NodeId,
         dst: NodeId,
         console: &mut Console,
-    ) -> Result<&mut Wire, WireId> {
+    ) -> Result<&mut Wire, CreateWireError> {
         assert_ne!(src, dst, "cannot wire a node directly to itself");
+        self.check_new_wire(src, dst, console)?;
+
+        let id = self.next_wire_id.step().expect("out of IDs");
+        let wire = self
+            .wires
+            .entry(id)
+            .insert_entry(Wire::new(id, elbow, src, dst))
+            .into_mut();
+        self.is_eval_order_dirty = true;
+        self.revision += 1;
+        logln!(
+            console,
+            LogType::Info,
+            "create wire {} from {} to {}",
+            GraphRef(self.id).wire(*wire.id()),
+            GraphRef(self.id).node(src),
+            GraphRef(self.id).node(dst),
+        );
+        Ok(wire)
+    }
+
+    /// Checks whether a wire from `src` to `dst` may be added to the graph, logging why not.
+    fn check_new_wire(
+        &self,
+        src: NodeId,
+        dst: NodeId,
+        console: &mut Console,
+    ) -> Result<(), CreateWireError> {
+        let graph_ref = GraphRef(self.id);
         if let Some(existing) = self
             .wires
             .iter()
             .find(|(_, wire)| wire.src == src && wire.dst == dst)
             .map(|(id, _)| *id)
         {
-            let graph_ref = GraphRef(self.id);
             logln!(
                 console,
                 LogType::Info,
@@ -415,26 +1080,115 @@ impl Graph {
                 graph_ref.node(dst),
                 graph_ref.wire(existing),
             );
-            Err(existing)
-        } else {
-            let graph_ref = GraphRef(self.id);
-            let id = self.next_wire_id.step().expect("out of IDs");
-            let wire = self
-                .wires
-                .entry(id)
-                .insert_entry(Wire::new(id, elbow, src, dst))
-                .into_mut();
-            self.is_eval_order_dirty = true;
+            return Err(CreateWireError::Exists(existing));
+        }
+
+        let src_gate = self.nodes[&src].gate.as_gate();
+        if let Some(max) = self.fan_limits[src_gate.id()].max_out
+            && self.wires.values().filter(|wire| wire.src == src).count() >= max
+        {
             logln!(
                 console,
-                LogType::Info,
-                "create wire {} from {} to {}",
-                graph_ref.wire(*wire.id()),
+                LogType::Error,
+                "cannot wire from {}: {} gates may have at most {max} outputs",
                 graph_ref.node(src),
+                GateRef(src_gate),
+            );
+            return Err(CreateWireError::FanOut);
+        }
+
+        let dst_gate = self.nodes[&dst].gate.as_gate();
+        if let Some(max) = self.fan_limits[dst_gate.id()].max_in
+            && self.wires.values().filter(|wire| wire.dst == dst).count() >= max
+        {
+            logln!(
+                console,
+                LogType::Error,
+                "cannot wire to {}: {} gates may have at most {max} inputs",
                 graph_ref.node(dst),
+                GateRef(dst_gate),
+            );
+            return Err(CreateWireError::FanIn);
+        }
+
+        Ok(())
+    }
+
+    /// Moves one end of an existing wire to `node`, keeping its ID and elbow.
+    ///
+    /// # Errors
+    /// Same as [`Self::create_wire`] for the wire that would result.
+    ///
+    /// # Panics
+    /// This method may panic if `id` is not a wire in this graph, or if the wire would connect a
+    /// node directly to itself
+    pub fn reattach_wire(
+        &mut self,
+        id: &WireId,
+        end: WireEnd,
+        node: NodeId,
+        console: &mut Console,
+    ) -> Result<&mut Wire, CreateWireError> {
+        let wire = &self.wires[id];
+        let (src, dst) = match end {
+            WireEnd::Src => (node, wire.dst),
+            WireEnd::Dst => (wire.src, node),
+        };
+        assert_ne!(src, dst, "cannot wire a node directly to itself");
+        if *wire.end(end) != node {
+            self.check_new_wire(src, dst, console)?;
+            logln!(
+                console,
+                LogType::Info,
+                "move {end} of wire {} from {} to {}",
+                GraphRef(self.id).wire(*id),
+                GraphRef(self.id).node(*wire.end(end)),
+                GraphRef(self.id).node(node),
             );
-            Ok(wire)
+            self.is_eval_order_dirty = true;
+            self.revision += 1;
+        }
+        let wire = self.wires.get_mut(id).expect("wire should be valid");
+        wire.src = src;
+        wire.dst = dst;
+        if wire.route.is_some() {
+            self.route_wire(id);
+        }
+        Ok(self.wires.get_mut(id).expect("wire should be valid"))
+    }
+
+    /// Swaps the source and destination of an existing wire, keeping its ID, elbow, and route.
+    ///
+    /// # Errors
+    /// Same as [`Self::create_wire`] for the wire that would result.
+    ///
+    /// # Panics
+    /// This method may panic if `id` is not a wire in this graph
+    pub fn reverse_wire(
+        &mut self,
+        id: &WireId,
+        console: &mut Console,
+    ) -> Result<&mut Wire, CreateWireError> {
+        let wire = &self.wires[id];
+        let (src, dst) = (wire.dst, wire.src);
+        self.check_new_wire(src, dst, console)?;
+        logln!(
+            console,
+            LogType::Info,
+            "reverse wire {} to go from {} to {}",
+            GraphRef(self.id).wire(*id),
+            GraphRef(self.id).node(src),
+            GraphRef(self.id).node(dst),
+        );
+        self.is_eval_order_dirty = true;
+        self.revision += 1;
+        let wire = self.wires.get_mut(id).expect("wire should be valid");
+        wire.src = src;
+        wire.dst = dst;
+        if let Some(route) = &mut wire.route {
+            route.reverse();
         }
+        Ok(wire)
     }
 
     /// Returns [`None`] if `id` is not a wire in this graph.
@@ -443,23 +1197,252 @@ impl Graph {
     pub fn destroy_wire(&mut self, id: &WireId) -> Option<Wire> {
         self.wires.remove(id).inspect(|_| {
             self.is_eval_order_dirty = true;
+            self.revision += 1;
         })
     }
 
-    #[inline]
-    pub fn nodes_iter(&self) -> NodesIter<'_> {
-        self.nodes.values()
-    }
-
-    #[inline]
-    pub fn wires_iter(&self) -> WiresIter<'_> {
-        self.wires.values()
+    /// Adds a region covering `bounds`, whichever corners they were given by.
+    pub fn create_region(
+        &mut self,
+        bounds: IBounds,
+        label: String,
+        color: Ntd,
+        console: &mut Console,
+    ) -> &Region {
+        let id = self.next_region_id.step().expect("out of IDs");
+        let bounds = IBounds::new(
+            IVec2::new(
+                bounds.min.x.min(bounds.max.x),
+                bounds.min.y.min(bounds.max.y),
+            ),
+            IVec2::new(
+                bounds.min.x.max(bounds.max.x),
+                bounds.min.y.max(bounds.max.y),
+            ),
+        );
+        logln!(
+            console,
+            LogType::Info,
+            "create region {id} at {}",
+            PositionRef(bounds.min),
+        );
+        self.regions
+            .entry(id)
+            .or_insert(Region::new(id, bounds, label, color))
     }
 
-    #[inline]
-    pub fn wires_to<'a: 'b, 'b>(
-        &'a self,
-        node: &'b NodeId,
+    /// Returns [`None`] if `id` is not a region in this graph.
+    pub fn translate_region(
+        &mut self,
+        id: &RegionId,
+        new_position: IVec2,
+        console: &mut Console,
+    ) -> Option<()> {
+        self.regions.get_mut(id).map(|region| {
+            let old_position = region.bounds.min;
+            if old_position != new_position {
+                region.set_position(new_position);
+                logln!(
+                    console,
+                    LogType::Info,
+                    "move region {id} from {} to {}",
+                    PositionRef(old_position),
+                    PositionRef(new_position),
+                );
+            }
+        })
+    }
+
+    pub fn destroy_region(&mut self, id: &RegionId) -> Option<Region> {
+        self.regions.remove(id)
+    }
+
+    #[inline]
+    pub fn region(&self, id: &RegionId) -> Option<&Region> {
+        self.regions.get(id)
+    }
+
+    #[inline]
+    pub fn region_mut(&mut self, id: &RegionId) -> Option<&mut Region> {
+        self.regions.get_mut(id)
+    }
+
+    /// Every region, oldest first, which is the order they are drawn in.
+    pub fn regions_ordered(&self) -> Vec<&Region> {
+        let mut regions = self.regions.values().collect::<Vec<_>>();
+        regions.sort_unstable_by_key(|region| *region.id());
+        regions
+    }
+
+    pub fn create_annotation(
+        &mut self,
+        position: IVec2,
+        text: String,
+        console: &mut Console,
+    ) -> &Annotation {
+        let id = self.next_annotation_id.step().expect("out of IDs");
+        logln!(
+            console,
+            LogType::Info,
+            "create annotation {id} at {}",
+            PositionRef(position),
+        );
+        self.annotations
+            .entry(id)
+            .or_insert(Annotation::new(id, position, text))
+    }
+
+    /// Returns [`None`] if `id` is not an annotation in this graph.
+    pub fn translate_annotation(
+        &mut self,
+        id: &AnnotationId,
+        new_position: IVec2,
+        console: &mut Console,
+    ) -> Option<()> {
+        self.annotations.get_mut(id).map(|annotation| {
+            let old_position = annotation.position;
+            if old_position != new_position {
+                annotation.position = new_position;
+                logln!(
+                    console,
+                    LogType::Info,
+                    "move annotation {id} from {} to {}",
+                    PositionRef(old_position),
+                    PositionRef(new_position),
+                );
+            }
+        })
+    }
+
+    pub fn destroy_annotation(&mut self, id: &AnnotationId) -> Option<Annotation> {
+        self.annotations.remove(id)
+    }
+
+    #[inline]
+    pub fn annotation(&self, id: &AnnotationId) -> Option<&Annotation> {
+        self.annotations.get(id)
+    }
+
+    #[inline]
+    pub fn annotation_mut(&mut self, id: &AnnotationId) -> Option<&mut Annotation> {
+        self.annotations.get_mut(id)
+    }
+
+    /// Clears every note, node name, region label and annotation text, leaving only the circuit.
+    /// The paths of the graphs run by ICs go as well, since they show where the user keeps files.
+    pub fn strip_text(&mut self) {
+        self.ic_paths.clear();
+        for node in self.nodes.values_mut() {
+            node.note = None;
+            node.name = None;
+        }
+        for wire in self.wires.values_mut() {
+            wire.note = None;
+        }
+        for region in self.regions.values_mut() {
+            region.label.clear();
+        }
+        for annotation in self.annotations.values_mut() {
+            annotation.text.clear();
+        }
+    }
+
+    /// Every annotation, oldest first, which is the order they are drawn in.
+    pub fn annotations_ordered(&self) -> Vec<&Annotation> {
+        let mut annotations = self.annotations.values().collect::<Vec<_>>();
+        annotations.sort_unstable_by_key(|annotation| *annotation.id());
+        annotations
+    }
+
+    #[inline]
+    pub fn nodes_iter(&self) -> NodesIter<'_> {
+        self.nodes.values()
+    }
+
+    #[inline]
+    pub fn wires_iter(&self) -> WiresIter<'_> {
+        self.wires.values()
+    }
+
+    /// Iterates over every node in the graph along with its position, in no particular order.
+    #[inline]
+    pub fn nodes_with_positions(&self) -> impl Iterator<Item = (&NodeId, IVec2)> {
+        self.nodes.iter().map(|(id, node)| (id, node.position))
+    }
+
+    /// Iterates over every wire in the graph as `(wire, src, dst)`, in no particular order.
+    #[inline]
+    pub fn edges(&self) -> impl Iterator<Item = (&WireId, &NodeId, &NodeId)> {
+        self.wires
+            .iter()
+            .map(|(id, wire)| (id, &wire.src, &wire.dst))
+    }
+
+    /// Iterates over the weakly connected components of the graph.
+    /// Every node appears in exactly one component, including nodes with no wires at all.
+    pub fn components(&self) -> impl Iterator<Item = FxHashSet<NodeId>> {
+        let (adj_in, adj_out) = self.adjacent();
+        let mut discovered = FxHashSet::default();
+        let mut roots = self.nodes.keys().copied();
+        std::iter::from_fn(move || {
+            let root = roots.find(|&v| discovered.insert(v))?;
+            let mut component = FxHashSet::default();
+            let mut stack = vec![root];
+            while let Some(v) = stack.pop() {
+                component.insert(v);
+                stack.extend(
+                    adj_in
+                        .get(&v)
+                        .into_iter()
+                        .chain(adj_out.get(&v))
+                        .flatten()
+                        .copied()
+                        .filter(|&w| discovered.insert(w)),
+                );
+            }
+            Some(component)
+        })
+    }
+
+    /// [`Self::components`] with their bounds, largest first.
+    /// Islands of the same size are ordered top-to-bottom, then left-to-right.
+    pub fn islands(&self) -> Vec<Island> {
+        let mut islands = self
+            .components()
+            .map(|nodes| {
+                let position = |id: &NodeId| self.nodes[id].position;
+                let representative = *nodes
+                    .iter()
+                    .min_by_key(|id| (position(id).y, position(id).x))
+                    .expect("components should never be empty");
+                let start = position(&representative);
+                let bounds = nodes.iter().map(position).fold(
+                    IBounds::new(start, start),
+                    |IBounds { min, max }, p| {
+                        IBounds::new(
+                            IVec2::new(min.x.min(p.x), min.y.min(p.y)),
+                            IVec2::new(max.x.max(p.x), max.y.max(p.y)),
+                        )
+                    },
+                );
+                Island {
+                    nodes,
+                    representative,
+                    bounds,
+                }
+            })
+            .collect::<Vec<_>>();
+        islands.sort_by_key(|island| {
+            let start = self.nodes[&island.representative].position;
+            (std::cmp::Reverse(island.nodes.len()), start.y, start.x)
+        });
+        islands
+    }
+
+    #[inline]
+    pub fn wires_to<'a: 'b, 'b>(
+        &'a self,
+        node: &'b NodeId,
     ) -> impl Iterator<Item = (&'a WireId, &'a Wire)> {
         self.wires.iter().filter(move |(_, wire)| &wire.dst == node)
     }
@@ -521,6 +1504,30 @@ impl Graph {
             .filter(move |node| !output_giving.contains(node))
     }
 
+    /// The [`Input`](Gate::Input) and [`Output`](Gate::Output) nodes marking what the graph
+    /// takes and gives when used as a circuit, each ordered by pin number then top to bottom,
+    /// then left to right. [`None`] if the graph has neither, in which case ICs, truth tables and
+    /// headless runs fall back on its inputless and outputless nodes.
+    pub fn interface(&self) -> Option<(Vec<NodeId>, Vec<NodeId>)> {
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        for node in self.nodes.values() {
+            match node.gate {
+                GateInstance::Input { pin } => inputs.push((pin, node)),
+                GateInstance::Output { pin } => outputs.push((pin, node)),
+                _ => {}
+            }
+        }
+        if inputs.is_empty() && outputs.is_empty() {
+            return None;
+        }
+        let pins = |mut pins: Vec<(Ntd, &Node)>| {
+            pins.sort_unstable_by_key(|(pin, node)| (*pin, node.position.y, node.position.x));
+            pins.into_iter().map(|(_, node)| *node.id()).collect()
+        };
+        Some((pins(inputs), pins(outputs)))
+    }
+
     #[inline]
     pub fn adjacent(
         &self,
@@ -579,191 +1586,877 @@ impl Graph {
                 self.nodes.len(),
                 "every node should be visited by eval_order"
             );
+            let index_of = self
+                .eval_order
+                .iter()
+                .enumerate()
+                .map(|(n, id)| (*id, n))
+                .collect::<FxHashMap<_, _>>();
+            let adj = self.adjacent_in();
+            self.eval_input_starts.clear();
+            self.eval_inputs.clear();
+            for id in &self.eval_order {
+                self.eval_input_starts.push(self.eval_inputs.len());
+                self.eval_inputs
+                    .extend(adj.get(id).into_iter().flatten().map(|id| index_of[id]));
+            }
+            self.eval_input_starts.push(self.eval_inputs.len());
+            let adj = self.adjacent_out();
+            self.eval_output_starts.clear();
+            self.eval_outputs.clear();
+            for id in &self.eval_order {
+                self.eval_output_starts.push(self.eval_outputs.len());
+                self.eval_outputs
+                    .extend(adj.get(id).into_iter().flatten().map(|id| index_of[id]));
+            }
+            self.eval_output_starts.push(self.eval_outputs.len());
+            // the indices changed
+            self.pending_revision = None;
+            self.states = self
+                .eval_order
+                .iter()
+                .map(|id| self.nodes[id].state)
+                .collect();
+            self.next_states = vec![Bits::ZERO; self.states.len()];
         }
     }
 
     #[inline]
-    pub const fn eval_order(&self) -> &[NodeId] {
-        self.eval_order.as_slice()
+    pub const fn fan_limits(&self) -> &FanLimits {
+        &self.fan_limits
     }
 
-    pub fn evaluate(&mut self) {
-        assert!(
-            !self.is_eval_order_dirty,
-            "should not evaluate while evel order is dirty, remember to call refresh_eval_order"
-        );
-        assert_eq!(
-            self.eval_order.len(),
-            self.nodes.len(),
-            "every node must be visited during eval; refresh_eval_order may need to be called"
-        );
-        let adj = self.adjacent_in();
-        let mut input_buf = Vec::new();
-        for id in &self.eval_order {
-            input_buf.clear();
-            input_buf.extend(adj.get(id).into_iter().flatten().map(|id| {
-                self.nodes
-                    .get(id)
-                    .expect("all nodes in adj should be valid")
-                    .state
-            }));
-            let node = self
-                .nodes
-                .get_mut(id)
-                .expect("all nodes in eval_order should be valid");
-            node.state = node.gate.evaluate(input_buf.iter().copied());
+    /// Only affects wires created afterwards.
+    #[inline]
+    pub fn set_fan_limits(&mut self, limits: FanLimits) {
+        self.fan_limits = limits;
+    }
+
+    /// Nodes with more inputs or outputs than the soft limit of their gate.
+    pub fn soft_limit_violations(&self) -> FxHashSet<NodeId> {
+        let mut fan = FxHashMap::<NodeId, (usize, usize)>::default();
+        for wire in self.wires.values() {
+            fan.entry(wire.dst).or_default().0 += 1;
+            fan.entry(wire.src).or_default().1 += 1;
         }
+        fan.into_iter()
+            .filter(|(id, (fan_in, fan_out))| {
+                self.fan_limits[self.nodes[id].gate.as_gate().id()].exceeds_soft(*fan_in, *fan_out)
+            })
+            .map(|(id, _)| id)
+            .collect()
     }
-}
 
-#[derive(Debug)]
-pub struct GraphList {
-    next_graph_id: GraphId,
-    graphs: Vec<Arc<RwLock<Graph>>>,
-}
+    /// [`Self::adjacent_out`] without the wires out of nodes whose gates
+    /// [hold a signal](GateInstance::holds_signal) until a later tick instead of passing it on
+    /// in the same one.
+    fn combinational_adjacent_out(&self) -> FxHashMap<NodeId, FxHashSet<NodeId>> {
+        let mut adj = self.adjacent_out();
+        adj.retain(|id, _| !self.nodes[id].gate.holds_signal());
+        adj
+    }
+
+    /// The strongly connected components of `adj` by Tarjan's algorithm, each after every
+    /// component it has wires to.
+    fn strongly_connected(&self, adj: &FxHashMap<NodeId, FxHashSet<NodeId>>) -> Vec<Vec<NodeId>> {
+        let outputs = |id: NodeId| adj.get(&id).into_iter().flatten().copied();
+        let mut next_index = 0;
+        // the order each node was found in, and the earliest node it can reach still on the stack
+        let mut links = FxHashMap::<NodeId, (usize, usize)>::default();
+        let mut stack = Vec::new();
+        let mut on_stack = FxHashSet::default();
+        let mut components = Vec::new();
+        for &root in self.nodes.keys() {
+            if links.contains_key(&root) {
+                continue;
+            }
+            links.insert(root, (next_index, next_index));
+            next_index += 1;
+            stack.push(root);
+            on_stack.insert(root);
+            // explicit, so that long chains can't overflow the call stack
+            let mut work = vec![(root, outputs(root))];
+            while let Some((v, unvisited)) = work.last_mut() {
+                let v = *v;
+                if let Some(w) = unvisited.next() {
+                    if let Some(&(index, _)) = links.get(&w) {
+                        if on_stack.contains(&w) {
+                            let low = &mut links.get_mut(&v).unwrap().1;
+                            *low = (*low).min(index);
+                        }
+                    } else {
+                        links.insert(w, (next_index, next_index));
+                        next_index += 1;
+                        stack.push(w);
+                        on_stack.insert(w);
+                        work.push((w, outputs(w)));
+                    }
+                    continue;
+                }
+
+                work.pop();
+                let (index, low) = links[&v];
+                if let Some(&(parent, _)) = work.last() {
+                    let parent_low = &mut links.get_mut(&parent).unwrap().1;
+                    *parent_low = (*parent_low).min(low);
+                }
+                if index == low {
+                    let mut component = Vec::new();
+                    while let Some(w) = stack.pop() {
+                        on_stack.remove(&w);
+                        component.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+        components
+    }
+
+    /// Groups of nodes that can all reach each other through wires. Every node is in exactly
+    /// one, so a node outside of any cycle is a component of its own.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        self.strongly_connected(&self.adjacent_out())
+    }
+
+    /// Cycles with no delay or capacitor to hold their state between ticks, each in reading order.
+    /// Whether these settle or oscillate depends on the order they are evaluated in, since
+    /// [`Self::refresh_eval_order`] has to start them somewhere arbitrary.
+    ///
+    /// Each loop is a strongly connected component, so loops sharing nodes are reported as one.
+    pub fn combinational_loops(&self) -> Vec<Vec<NodeId>> {
+        let adj = self.combinational_adjacent_out();
+        let position = |id: &NodeId| {
+            let position = self.nodes[id].position;
+            (position.y, position.x)
+        };
+        let mut loops = self
+            .strongly_connected(&adj)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || adj
+                        .get(&component[0])
+                        .is_some_and(|outputs| outputs.contains(&component[0]))
+            })
+            .map(|mut component| {
+                component.sort_unstable_by_key(position);
+                component
+            })
+            .collect::<Vec<_>>();
+        loops.sort_unstable_by_key(|component| position(&component[0]));
+        loops
+    }
 
-impl std::ops::Deref for GraphList {
-    type Target = Vec<Arc<RwLock<Graph>>>;
+    /// The most nodes a signal can pass through in one tick, from a node with no inputs or a
+    /// delay or capacitor to a node with no outputs or one of those again. Loops count as one node.
+    pub fn longest_combinational_path(&self) -> usize {
+        let adj = self.combinational_adjacent_out();
+        let components = self.strongly_connected(&adj);
+        let component_of = components
+            .iter()
+            .enumerate()
+            .flat_map(|(n, component)| component.iter().map(move |id| (*id, n)))
+            .collect::<FxHashMap<_, _>>();
+        // components come after the ones they have wires to, so those are already measured
+        let mut depths = Vec::<usize>::with_capacity(components.len());
+        for (n, component) in components.iter().enumerate() {
+            let deepest_output = component
+                .iter()
+                .flat_map(|id| adj.get(id).into_iter().flatten())
+                .map(|id| component_of[id])
+                .filter(|&m| m != n)
+                .map(|m| depths[m])
+                .max()
+                .unwrap_or(0);
+            depths.push(deepest_output + 1);
+        }
+        depths.into_iter().max().unwrap_or(0)
+    }
 
     #[inline]
-    fn deref(&self) -> &Self::Target {
-        &self.graphs
+    pub const fn eval_mode(&self) -> EvalMode {
+        self.eval_mode
     }
-}
 
-impl std::ops::DerefMut for GraphList {
     #[inline]
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.graphs
+    pub fn set_eval_mode(&mut self, mode: EvalMode) {
+        self.eval_mode = mode;
+        self.revision += 1;
     }
-}
 
-impl Default for GraphList {
+    /// The seed of the random order nodes are evaluated in, if they are not evaluated in the
+    /// eval order.
     #[inline]
-    fn default() -> Self {
-        Self::new()
+    pub fn jitter_seed(&self) -> Option<u64> {
+        self.jitter.as_ref().map(Jitter::seed)
     }
-}
 
-impl GraphList {
-    pub const fn new() -> Self {
-        Self {
-            next_graph_id: GraphId(0),
-            graphs: Vec::new(),
-        }
+    /// Evaluates nodes in a new random order every tick, generated from `seed`, or in the eval
+    /// order if [`None`]. In [`EvalMode::Synchronous`] the order makes no difference.
+    #[inline]
+    pub fn set_jitter(&mut self, seed: Option<u64>) {
+        self.jitter = seed.map(Jitter::new);
     }
 
     #[inline]
-    pub fn create_graph(&mut self) -> &mut Arc<RwLock<Graph>> {
-        self.graphs.push(Arc::new(RwLock::new(Graph::new(
-            self.next_graph_id.step().expect("out of IDs"),
-        ))));
-        self.graphs.last_mut().expect("just pushed")
+    pub const fn eval_order(&self) -> &[NodeId] {
+        self.eval_order.as_slice()
     }
 
     #[inline]
-    pub fn try_get(&self, id: &GraphId) -> Option<&Arc<RwLock<Graph>>> {
-        self.graphs
-            .iter()
-            .find(|g| g.try_read().unwrap().id() == id)
+    pub const fn is_recording(&self) -> bool {
+        self.history.is_some()
+    }
+
+    /// Starting a recording discards any previous one.
+    pub fn set_recording(&mut self, recording: bool) {
+        self.history = recording.then(|| History::new(HISTORY_CAPACITY));
     }
 
     #[inline]
-    pub fn try_get_mut(&mut self, id: &GraphId) -> Option<&mut Arc<RwLock<Graph>>> {
-        self.graphs
-            .iter_mut()
-            .find(|g| g.try_read().unwrap().id() == id)
+    pub const fn history(&self) -> Option<&History> {
+        self.history.as_ref()
     }
 
     #[inline]
-    pub fn get(&self, id: &GraphId) -> Option<&Arc<RwLock<Graph>>> {
-        self.graphs.iter().find(|g| g.read().unwrap().id() == id)
+    pub const fn history_mut(&mut self) -> Option<&mut History> {
+        self.history.as_mut()
     }
 
+    /// How many ticks ago the state of `id` last changed, or [`None`] if it hasn't since the
+    /// graph was loaded.
     #[inline]
-    pub fn get_mut(&mut self, id: &GraphId) -> Option<&mut Arc<RwLock<Graph>>> {
-        self.graphs
-            .iter_mut()
-            .find(|g| g.read().unwrap().id() == id)
+    pub fn ticks_since_change(&self, id: &NodeId) -> Option<u64> {
+        self.changed_at.get(id).map(|&tick| self.ticks - tick)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::graph::node::GateInstance;
+    #[inline]
+    pub const fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
 
-    fn gen_graph(
-        id: GraphId,
-        nodes: impl IntoIterator<Item = (NodeId, Gate)>,
-        wires: impl IntoIterator<Item = (WireId, (NodeId, NodeId))>,
-    ) -> Graph {
-        let mut next_node_id = NodeId(0);
-        let mut next_wire_id = WireId(0);
-        let nodes = nodes
-            .into_iter()
-            .map(|(id, gate)| {
-                next_node_id.0 = id.0.max(next_node_id.0);
-                (id, Node::new(id, gate, IVec2::default(), false))
-            })
-            .collect();
-        let wires = wires
-            .into_iter()
-            .map(|(id, (src, dst))| {
-                next_wire_id.0 = id.0.max(next_wire_id.0);
-                (id, Wire::new(id, Elbow::default(), src, dst))
-            })
-            .collect();
-        _ = next_node_id.step();
-        _ = next_wire_id.step();
-        Graph {
-            id,
-            nodes,
-            wires,
-            node_grid: FxHashMap::default(),
-            next_node_id,
-            next_wire_id,
-            eval_order: Vec::new(),
-            is_eval_order_dirty: true,
+    #[inline]
+    pub fn is_probed(&self, id: &NodeId) -> bool {
+        self.trace.as_ref().is_some_and(|trace| trace.is_probed(id))
+    }
+
+    /// Starts tracing `id` if it isn't already, otherwise stops, returning whether it is now
+    /// probed. The trace is discarded along with its last probe.
+    ///
+    /// Returns [`None`] if `id` is not in the graph.
+    pub fn toggle_probe(&mut self, id: &NodeId) -> Option<bool> {
+        let node = self.nodes.get(id)?;
+        let trace = self.trace.get_or_insert_default();
+        let is_probed = !trace.unprobe(id);
+        if is_probed {
+            trace.probe(node);
+        } else if trace.is_empty() {
+            self.trace = None;
         }
+        Some(is_probed)
     }
 
-    /// must contain every node, but order does not matter
-    struct Unordered<T>(FxHashSet<T>);
+    /// Stops tracing every node, discarding the trace.
+    #[inline]
+    pub fn clear_trace(&mut self) {
+        self.trace = None;
+    }
 
-    impl<T: std::fmt::Debug> std::fmt::Debug for Unordered<T> {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            f.debug_set().entries(&self.0).finish()
-        }
+    /// Whether a recorded tick is being shown instead of the live states.
+    #[inline]
+    pub fn is_replaying(&self) -> bool {
+        self.history.as_ref().is_some_and(History::is_replaying)
     }
 
-    impl<T> FromIterator<T> for Unordered<T>
-    where
-        FxHashSet<T>: FromIterator<T>,
-    {
-        fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-            Self(FxHashSet::from_iter(iter))
-        }
+    /// The state to show for `id`: the replayed state while replaying, otherwise the live state.
+    ///
+    /// Returns `false` for nodes that are not in the graph.
+    #[inline]
+    pub fn displayed_state(&self, id: &NodeId) -> bool {
+        self.displayed_bits(id).any()
     }
 
-    impl<T> PartialEq<[T]> for Unordered<T>
-    where
-        T: Copy,
-        FxHashSet<T>: FromIterator<T> + PartialEq,
-    {
-        fn eq(&self, other: &[T]) -> bool {
-            self.0 == other.iter().copied().collect::<FxHashSet<T>>()
-        }
+    /// Every bit of [`Self::displayed_state`], for buses.
+    pub fn displayed_bits(&self, id: &NodeId) -> Bits {
+        self.history
+            .as_ref()
+            .and_then(|history| history.replayed_bits(id))
+            .unwrap_or_else(|| self.nodes.get(id).map_or(Bits::ZERO, Node::bits))
     }
 
-    impl<T> Unordered<T> {
-        #[inline]
-        pub fn len(&self) -> usize {
-            self.0.len()
+    /// The graph copy run by the IC node `id`, or why it has none.
+    ///
+    /// Returns [`None`] if `id` is not an IC node, or if [`Self::refresh_ics`] has not seen it yet.
+    #[inline]
+    pub fn ic(&self, id: &NodeId) -> Option<&Result<IcInstance, IcError>> {
+        self.ics.get(id)
+    }
+
+    /// Copies the graphs that IC nodes refer to, replacing copies of graphs that have changed.
+    ///
+    /// `stack` holds the graphs that contain this one, including itself.
+    fn build_ics(&mut self, graphs: &GraphList, stack: &mut Vec<GraphId>) {
+        let ic_nodes = self
+            .nodes
+            .values()
+            .filter_map(|node| match node.gate {
+                GateInstance::Ic { graph } => Some((*node.id(), graph)),
+                _ => None,
+            })
+            .collect::<FxHashMap<NodeId, GraphId>>();
+        self.ics.retain(|id, _| ic_nodes.contains_key(id));
+        for (id, graph) in ic_nodes {
+            // errors are retried, since the graphs involved may have changed
+            let is_current = self.ics.get(&id).is_some_and(|ic| {
+                ic.as_ref()
+                    .is_ok_and(|ic| ic.source() == graph && ic.is_current(graphs))
+            });
+            if !is_current {
+                self.ics.insert(id, IcInstance::new(graphs, graph, stack));
+            }
         }
     }
 
-    /// must be in order, but can start with any subset
+    /// Brings the graph copies run by IC nodes up to date, returning the IC nodes that could run
+    /// before but cannot now, with why.
+    pub fn update_ics(&mut self, graphs: &GraphList) -> Vec<(NodeId, IcError)> {
+        let had_error = self
+            .ics
+            .iter()
+            .filter_map(|(id, ic)| Some((*id, *ic.as_ref().err()?)))
+            .collect::<FxHashMap<_, _>>();
+        self.build_ics(graphs, &mut vec![self.id]);
+        self.ics
+            .iter()
+            .filter_map(|(id, ic)| Some((*id, *ic.as_ref().err()?)))
+            .filter(|(id, e)| had_error.get(id) != Some(e))
+            .collect()
+    }
+
+    /// Where the graph run by the IC node `id` is saved, as of the last
+    /// [`Self::record_ic_paths`] or as loaded.
+    #[inline]
+    pub fn ic_path(&self, id: &NodeId) -> Option<&Path> {
+        self.ic_paths.get(id).map(PathBuf::as_path)
+    }
+
+    /// Notes where the graph run by each IC node is saved, to be written with this graph. IC
+    /// nodes that don't run any graph keep the path they were loaded with, in case the graph is
+    /// opened later.
+    pub fn record_ic_paths(&mut self, graphs: &GraphList) {
+        let mut ic_paths = std::mem::take(&mut self.ic_paths);
+        ic_paths.retain(|id, _| {
+            self.nodes.get(id).is_some_and(|node| {
+                node.gate
+                    == GateInstance::Ic {
+                        graph: GraphId::INVALID,
+                    }
+            })
+        });
+        for node in self.nodes.values() {
+            if let GateInstance::Ic { graph } = node.gate
+                && let Some(path) = graphs.path(&graph)
+            {
+                ic_paths.insert(*node.id(), path.to_path_buf());
+            }
+        }
+        self.ic_paths = ic_paths;
+    }
+
+    /// Points the IC nodes that don't run any graph at the graphs in `graphs` opened from the
+    /// paths they were saved with, returning the paths that none of the graphs were opened
+    /// from.
+    pub fn resolve_ics(&mut self, graphs: &GraphList) -> Vec<PathBuf> {
+        let mut missing = Vec::new();
+        for (id, path) in &self.ic_paths {
+            let Some(node) = self.nodes.get_mut(id) else {
+                continue;
+            };
+            let GateInstance::Ic { graph } = &mut node.gate else {
+                continue;
+            };
+            if *graph != GraphId::INVALID {
+                continue;
+            }
+            if let Some(found) = graphs.find_path(path) {
+                *graph = found;
+                self.revision += 1;
+            } else if !missing.contains(path) {
+                missing.push(path.clone());
+            }
+        }
+        missing
+    }
+
+    /// [`Self::update_ics`], logging IC nodes that cannot run.
+    pub fn refresh_ics(&mut self, graphs: &GraphList, console: &mut Console) {
+        for (id, e) in self.update_ics(graphs) {
+            logln!(
+                console,
+                LogType::Warning,
+                "IC {} cannot run: {e}",
+                NodeRef(self.id, id)
+            );
+        }
+    }
+
+    pub fn evaluate(&mut self) {
+        self.evaluate_driven(None);
+    }
+
+    /// Marks every node to be evaluated next tick, and finds the nodes evaluated every tick,
+    /// unless that is already done for the current revision.
+    fn refresh_pending(&mut self) {
+        if self.pending_revision == Some(self.revision) {
+            return;
+        }
+        self.eval_pending = std::iter::repeat_n(true, self.eval_order.len()).collect();
+        self.eval_always = self
+            .eval_order
+            .iter()
+            .map(|id| self.nodes[id].gate.is_time_dependent())
+            .collect();
+        self.pending_revision = Some(self.revision);
+    }
+
+    /// Marks the outputs of the node at eval order index `n` to be evaluated.
+    #[inline]
+    fn mark_outputs_pending(&mut self, n: usize) {
+        for &output in
+            &self.eval_outputs[self.eval_output_starts[n]..self.eval_output_starts[n + 1]]
+        {
+            self.eval_pending.set(output, true);
+        }
+    }
+
+    /// Evaluates one tick, except that the nodes at the eval order indices in `driven` keep the
+    /// states they were given.
+    ///
+    /// Only nodes that an input changed for since they were last evaluated are evaluated, along
+    /// with nodes whose gates change over time by themselves. The rest would give the same state
+    /// again.
+    fn evaluate_driven(&mut self, driven: Option<&BitSet>) {
+        assert!(
+            !self.is_eval_order_dirty,
+            "should not evaluate while evel order is dirty, remember to call refresh_eval_order"
+        );
+        assert_eq!(
+            self.eval_order.len(),
+            self.nodes.len(),
+            "every node must be visited during eval; refresh_eval_order may need to be called"
+        );
+        self.refresh_pending();
+        if self.eval_mode == EvalMode::Synchronous {
+            // nodes that aren't evaluated keep their states
+            self.next_states.copy_from_slice(&self.states);
+        }
+        // nodes whose state changed this tick, for synchronous mode, where their outputs only see
+        // the change next tick
+        let mut changed = Vec::new();
+        self.ticks += 1;
+        let mut jitter = self.jitter.take();
+        let shuffled = jitter
+            .as_mut()
+            .map(|jitter| jitter.shuffle(self.eval_order.len()));
+        for i in 0..self.eval_order.len() {
+            let n = shuffled.map_or(i, |order| order[i]);
+            if driven.is_some_and(|driven| driven.get(n)) {
+                self.nodes
+                    .get_mut(&self.eval_order[n])
+                    .expect("all nodes in eval_order should be valid")
+                    .state = self.states[n];
+                // given from outside, so whether it changed isn't known
+                self.mark_outputs_pending(n);
+                continue;
+            }
+            if !self.eval_pending.get(n) && !self.eval_always.get(n) {
+                continue;
+            }
+            self.eval_pending.set(n, false);
+            let id = &self.eval_order[n];
+            let inputs =
+                &self.eval_inputs[self.eval_input_starts[n]..self.eval_input_starts[n + 1]];
+            let node = self
+                .nodes
+                .get_mut(id)
+                .expect("all nodes in eval_order should be valid");
+            let inputs = inputs.iter().map(|&input| self.states[input]);
+            let state = if let GateInstance::Ic { .. } = node.gate {
+                match self.ics.get_mut(id) {
+                    Some(Ok(ic)) => Bits::from(ic.evaluate(inputs.map(Bits::any))),
+                    _ => Bits::ZERO,
+                }
+            } else {
+                node.gate.evaluate(inputs, node.width)
+            };
+            let is_changed = state != self.states[n];
+            match self.eval_mode {
+                EvalMode::Asynchronous => {
+                    self.states[n] = state;
+                    node.state = state;
+                    if is_changed {
+                        self.changed_at.insert(*id, self.ticks);
+                        self.mark_outputs_pending(n);
+                    }
+                }
+                EvalMode::Synchronous => {
+                    self.next_states[n] = state;
+                    if is_changed {
+                        changed.push(n);
+                    }
+                }
+            }
+        }
+        self.jitter = jitter;
+        if self.eval_mode == EvalMode::Synchronous {
+            std::mem::swap(&mut self.states, &mut self.next_states);
+            for n in changed {
+                self.nodes
+                    .get_mut(&self.eval_order[n])
+                    .expect("all nodes in eval_order should be valid")
+                    .state = self.states[n];
+                self.changed_at.insert(self.eval_order[n], self.ticks);
+                self.mark_outputs_pending(n);
+            }
+        }
+        if let Some(history) = &mut self.history {
+            history.record(&self.eval_order, &self.states);
+        }
+        if let Some(trace) = &mut self.trace {
+            trace.sample(&self.nodes);
+        }
+    }
+}
+
+/// Why a graph in a [`GraphList`] could not be borrowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GraphAccessError {
+    /// No graph in the list has the ID.
+    Missing(GraphId),
+    /// The graph is borrowed somewhere else right now.
+    Busy(GraphId),
+    /// Something panicked while it was changing the graph.
+    Poisoned(GraphId),
+}
+
+impl std::fmt::Display for GraphAccessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing(id) => write!(f, "graph {id} does not exist"),
+            Self::Busy(id) => write!(f, "graph {id} is busy"),
+            Self::Poisoned(id) => write!(f, "graph {id} was left broken by a crash"),
+        }
+    }
+}
+
+impl std::error::Error for GraphAccessError {}
+
+impl GraphAccessError {
+    fn from_lock<T>(id: GraphId, e: TryLockError<T>) -> Self {
+        match e {
+            TryLockError::WouldBlock => Self::Busy(id),
+            TryLockError::Poisoned(_) => Self::Poisoned(id),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GraphList {
+    next_graph_id: GraphId,
+    /// Every graph in the order it was added, with its ID so that it can be found without
+    /// locking it
+    graphs: Vec<(GraphId, Arc<RwLock<Graph>>)>,
+    /// Index of each graph in `graphs`
+    index: FxHashMap<GraphId, usize>,
+    /// Given to every graph in the list
+    fan_limits: FanLimits,
+    /// Given to graphs created by the list, while inserted graphs keep their own
+    grid_size: u8,
+    /// The graph behind the scratch tab, which is never saved and is left out of the workspace
+    scratch: Weak<RwLock<Graph>>,
+    /// Where each graph was last saved to or opened from, for finding the graphs run by ICs
+    paths: FxHashMap<GraphId, PathBuf>,
+}
+
+impl Default for GraphList {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GraphList {
+    pub fn new() -> Self {
+        Self {
+            next_graph_id: GraphId(0),
+            graphs: Vec::new(),
+            index: FxHashMap::default(),
+            fan_limits: FanLimits::NONE,
+            grid_size: DEFAULT_GRID_SIZE,
+            scratch: Weak::new(),
+            paths: FxHashMap::default(),
+        }
+    }
+
+    /// Sets the grid size of graphs created from now on.
+    #[inline]
+    pub fn set_grid_size(&mut self, grid_size: u8) {
+        assert_ne!(grid_size, 0, "grid size should be positive");
+        self.grid_size = grid_size;
+    }
+
+    #[inline]
+    pub const fn fan_limits(&self) -> &FanLimits {
+        &self.fan_limits
+    }
+
+    /// Applies `limits` to every graph in the list, including ones added later. Graphs left
+    /// broken by a crash are skipped.
+    pub fn set_fan_limits(&mut self, limits: FanLimits, console: &mut Console) {
+        self.fan_limits = limits;
+        for (id, graph) in &self.graphs {
+            match graph.write() {
+                Ok(mut graph) => graph.set_fan_limits(limits),
+                Err(_) => logln!(
+                    console,
+                    LogType::Error,
+                    "cannot apply fan limits: {}",
+                    GraphAccessError::Poisoned(*id)
+                ),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.graphs.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.graphs.is_empty()
+    }
+
+    /// Every graph, in the order they were added.
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Arc<RwLock<Graph>>> + Clone {
+        self.graphs.iter().map(|(_, graph)| graph)
+    }
+
+    /// Adds `graph`, which must already have its new ID.
+    fn push(&mut self, graph: Graph) -> &mut Arc<RwLock<Graph>> {
+        let id = graph.id;
+        self.index.insert(id, self.graphs.len());
+        self.graphs.push((id, Arc::new(RwLock::new(graph))));
+        &mut self.graphs.last_mut().expect("just pushed").1
+    }
+
+    #[inline]
+    pub fn create_graph(&mut self) -> &mut Arc<RwLock<Graph>> {
+        let mut graph = Graph::new(self.next_graph_id.step().expect("out of IDs"));
+        graph.fan_limits = self.fan_limits;
+        graph.grid_size = self.grid_size;
+        self.push(graph)
+    }
+
+    /// Creates a graph for the scratch tab, replacing the previous scratch graph as the one
+    /// left out of [`Self::workspace`].
+    pub fn create_scratch_graph(&mut self) -> &mut Arc<RwLock<Graph>> {
+        self.scratch = Arc::downgrade(self.create_graph());
+        &mut self.graphs.last_mut().expect("just pushed").1
+    }
+
+    /// Every graph except the scratch graph.
+    #[inline]
+    pub fn workspace(&self) -> impl DoubleEndedIterator<Item = &Arc<RwLock<Graph>>> + Clone {
+        self.iter()
+            .filter(|g| !std::ptr::eq(Arc::as_ptr(g), self.scratch.as_ptr()))
+    }
+
+    /// Adds an existing graph to the list, replacing its ID with a new one.
+    #[inline]
+    pub fn insert_graph(&mut self, mut graph: Graph) -> &mut Arc<RwLock<Graph>> {
+        graph.id = self.next_graph_id.step().expect("out of IDs");
+        graph.fan_limits = self.fan_limits;
+        self.push(graph)
+    }
+
+    /// Keeps only the graphs `f` returns `true` for, in the same order.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Arc<RwLock<Graph>>) -> bool,
+    {
+        self.graphs.retain(|(_, graph)| f(graph));
+        self.index = self
+            .graphs
+            .iter()
+            .enumerate()
+            .map(|(n, (id, _))| (*id, n))
+            .collect();
+        self.paths.retain(|id, _| self.index.contains_key(id));
+    }
+
+    /// Where the graph `id` was last saved to or opened from.
+    #[inline]
+    pub fn path(&self, id: &GraphId) -> Option<&Path> {
+        self.paths.get(id).map(PathBuf::as_path)
+    }
+
+    /// Notes that the graph `id` was saved to or opened from `path`, which no other graph is
+    /// then known by.
+    pub fn set_path(&mut self, id: GraphId, path: PathBuf) {
+        self.paths.retain(|_, other| *other != path);
+        self.paths.insert(id, path);
+    }
+
+    /// The graph last saved to or opened from `path`.
+    pub fn find_path(&self, path: &Path) -> Option<GraphId> {
+        self.paths
+            .iter()
+            .find_map(|(id, other)| (other == path).then_some(*id))
+    }
+
+    /// The graph `id`, found without locking any graph.
+    #[inline]
+    pub fn get(&self, id: &GraphId) -> Option<&Arc<RwLock<Graph>>> {
+        self.index.get(id).map(|&n| &self.graphs[n].1)
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, id: &GraphId) -> Option<&mut Arc<RwLock<Graph>>> {
+        self.index.get(id).map(|&n| &mut self.graphs[n].1)
+    }
+
+    /// Reads the graph `id` without waiting for it if it is being written.
+    pub fn try_read(&self, id: &GraphId) -> Result<RwLockReadGuard<'_, Graph>, GraphAccessError> {
+        self.get(id)
+            .ok_or(GraphAccessError::Missing(*id))?
+            .try_read()
+            .map_err(|e| GraphAccessError::from_lock(*id, e))
+    }
+
+    /// Writes the graph `id` without waiting for it if it is being read or written.
+    pub fn try_write(&self, id: &GraphId) -> Result<RwLockWriteGuard<'_, Graph>, GraphAccessError> {
+        self.get(id)
+            .ok_or(GraphAccessError::Missing(*id))?
+            .try_write()
+            .map_err(|e| GraphAccessError::from_lock(*id, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::node::GateInstance;
+
+    fn gen_graph(
+        id: GraphId,
+        nodes: impl IntoIterator<Item = (NodeId, Gate)>,
+        wires: impl IntoIterator<Item = (WireId, (NodeId, NodeId))>,
+    ) -> Graph {
+        let mut next_node_id = NodeId(0);
+        let mut next_wire_id = WireId(0);
+        let nodes = nodes
+            .into_iter()
+            .map(|(id, gate)| {
+                next_node_id.0 = id.0.max(next_node_id.0);
+                (id, Node::new(id, gate, IVec2::default(), Bits::ZERO))
+            })
+            .collect();
+        let wires = wires
+            .into_iter()
+            .map(|(id, (src, dst))| {
+                next_wire_id.0 = id.0.max(next_wire_id.0);
+                (id, Wire::new(id, Elbow::default(), src, dst))
+            })
+            .collect();
+        _ = next_node_id.step();
+        _ = next_wire_id.step();
+        Graph {
+            id,
+            nodes,
+            wires,
+            node_grid: NodeGrid::default(),
+            next_node_id,
+            next_wire_id,
+            next_region_id: RegionId(0),
+            regions: FxHashMap::default(),
+            next_annotation_id: AnnotationId(0),
+            annotations: FxHashMap::default(),
+            eval_order: Vec::new(),
+            is_eval_order_dirty: true,
+            eval_mode: EvalMode::default(),
+            jitter: None,
+            states: Vec::new(),
+            next_states: Vec::new(),
+            eval_input_starts: Vec::new(),
+            eval_inputs: Vec::new(),
+            eval_output_starts: Vec::new(),
+            eval_outputs: Vec::new(),
+            eval_pending: BitSet::default(),
+            eval_always: BitSet::default(),
+            pending_revision: None,
+            fan_limits: FanLimits::NONE,
+            history: None,
+            trace: None,
+            ticks: 0,
+            changed_at: FxHashMap::default(),
+            revision: 0,
+            ics: FxHashMap::default(),
+            ic_paths: FxHashMap::default(),
+            grid_size: DEFAULT_GRID_SIZE,
+        }
+    }
+
+    fn test_console() -> Console {
+        use crate::ui::{Anchoring, Padding, Panel};
+
+        Console::new(
+            Panel::new("", Anchoring::Fill, |_| Padding::amount(0.0)),
+            4096,
+        )
+    }
+
+    /// must contain every node, but order does not matter
+    struct Unordered<T>(FxHashSet<T>);
+
+    impl<T: std::fmt::Debug> std::fmt::Debug for Unordered<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_set().entries(&self.0).finish()
+        }
+    }
+
+    impl<T> FromIterator<T> for Unordered<T>
+    where
+        FxHashSet<T>: FromIterator<T>,
+    {
+        fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+            Self(FxHashSet::from_iter(iter))
+        }
+    }
+
+    impl<T> PartialEq<[T]> for Unordered<T>
+    where
+        T: Copy,
+        FxHashSet<T>: FromIterator<T> + PartialEq,
+    {
+        fn eq(&self, other: &[T]) -> bool {
+            self.0 == other.iter().copied().collect::<FxHashSet<T>>()
+        }
+    }
+
+    impl<T> Unordered<T> {
+        #[inline]
+        pub fn len(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    /// must be in order, but can start with any subset
     struct RingOrder<T>(VecDeque<Unordered<T>>);
 
     impl<T: std::fmt::Debug> std::fmt::Debug for RingOrder<T> {
@@ -903,7 +2596,7 @@ mod tests {
                         FxHashMap::from_iter([$(($eval_id, $value)),*]),
                         g.nodes
                             .iter()
-                            .map(|(id, node)| (*id, node.state))
+                            .map(|(id, node)| (*id, node.state()))
                             .collect::<FxHashMap<_, _>>(),
                         $($($eval_args)*)?
                     );)?
@@ -1068,4 +2761,1811 @@ mod tests {
             ("2: should remain latched after inputs are turned back off")
         };
     }
+
+    #[test]
+    fn test_synchronous_eval() {
+        let (mut g, [a, b, c]) = test_graph! {
+            {Nor} a;
+            {Or} b;
+            {Or} c;
+            a -> b;
+            b -> c;
+            [({a}), ({b}), ({c})];
+        };
+        let states = |g: &Graph| [a, b, c].map(|id| g.node(&id).unwrap().state());
+
+        g.refresh_eval_order();
+        g.evaluate();
+        assert_eq!(
+            states(&g),
+            [true, true, true],
+            "asynchronous eval should propagate through the whole chain in one tick"
+        );
+
+        let (mut g, _) = test_graph! {
+            {Nor} a;
+            {Or} b;
+            {Or} c;
+            a -> b;
+            b -> c;
+            [({a}), ({b}), ({c})];
+        };
+        g.set_eval_mode(EvalMode::Synchronous);
+        g.refresh_eval_order();
+        for expected in [
+            [true, false, false],
+            [true, true, false],
+            [true, true, true],
+        ] {
+            g.evaluate();
+            assert_eq!(
+                states(&g),
+                expected,
+                "synchronous eval should propagate one node per tick"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pending_eval_matches_full_eval() {
+        let states = |g: &Graph| {
+            let mut states = g
+                .nodes_iter()
+                .map(|node| (node.id().0, node.bits()))
+                .collect::<Vec<_>>();
+            states.sort_unstable_by_key(|&(id, _)| id);
+            states
+        };
+        for mode in [EvalMode::Asynchronous, EvalMode::Synchronous] {
+            let mut pending = bench::random_graph(200, 3);
+            let mut full = bench::random_graph(200, 3);
+            for g in [&mut pending, &mut full] {
+                g.set_eval_mode(mode);
+                g.refresh_eval_order();
+            }
+            for tick in 0..50 {
+                full.pending_revision = None;
+                pending.evaluate();
+                full.evaluate();
+                assert_eq!(
+                    states(&pending),
+                    states(&full),
+                    "{mode:?} tick {tick} should be the same as evaluating every node"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_jitter_is_reproducible() {
+        let run = |seed| {
+            let (mut g, ids) = test_graph! {
+                {Nor} a;
+                {Or} b;
+                {Or} c;
+                {Or} d;
+                a -> b;
+                b -> c;
+                c -> d;
+                [({a}), ({b}), ({c}), ({d})];
+            };
+            g.set_jitter(Some(seed));
+            assert_eq!(g.jitter_seed(), Some(seed));
+            g.refresh_eval_order();
+            (0..4)
+                .map(|_| {
+                    g.evaluate();
+                    ids.map(|id| g.node(&id).unwrap().state())
+                })
+                .collect::<Vec<_>>()
+        };
+        let states = run(42);
+        assert_eq!(states, run(42), "the same seed should give the same order");
+        assert_eq!(
+            states.last(),
+            Some(&[true; 4]),
+            "a chain should settle within a tick per node in any order"
+        );
+    }
+
+    #[test]
+    fn test_delay_ticks() {
+        use crate::graph::node::Ntd;
+
+        let (mut g, [_, b]) = test_graph! {
+            {Battery} a;
+            {Delay { ticks: Ntd::Three }} b;
+            a -> b;
+            [({a}), ({b})];
+        };
+        for expected in [false, false, false, true, true] {
+            g.evaluate();
+            assert_eq!(
+                g.node(&b).unwrap().state(),
+                expected,
+                "delay should give its input three ticks after it arrives"
+            );
+        }
+
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let a = *g
+            .create_node(Gate::Battery, IVec2::new(0, 0), &mut console)
+            .unwrap()
+            .id();
+        let b = *g
+            .create_node(
+                GateId::Delay.to_gate(Ntd::default()),
+                IVec2::new(8, 0),
+                &mut console,
+            )
+            .unwrap()
+            .id();
+        g.create_wire(Elbow::default(), a, b, &mut console).unwrap();
+        g.refresh_eval_order();
+        for expected in [false, true] {
+            g.evaluate();
+            assert_eq!(
+                g.node(&b).unwrap().state(),
+                expected,
+                "a delay placed with the default setting should still delay by one tick"
+            );
+        }
+
+        let g = toml::from_str::<Graph>("nodes = [[\";\", [0, 0], false]]\nwires = []")
+            .expect("delays saved without a tick count should still load");
+        assert_eq!(
+            g.nodes_iter().next().unwrap().gate().as_gate(),
+            Gate::Delay { ticks: Ntd::One },
+            "delays saved without a tick count always delayed by one tick"
+        );
+    }
+
+    #[test]
+    fn test_load_rejects_broken_graphs() {
+        let load = |wires: &str| {
+            toml::from_str::<Graph>(&format!(
+                "nodes = [[\"|\", [0, 0], false], [\"|\", [8, 0], false]]\nwires = [{wires}]"
+            ))
+        };
+        assert!(load(r#"["-", 0, 1], ["-", 1, 0]"#).is_ok());
+        for (wires, why) in [
+            (r#"["-", 0, 2]"#, "wires to missing nodes"),
+            (r#"["-", 7, 0]"#, "wires from missing nodes"),
+            (r#"["-", 1, 1]"#, "self-wires"),
+            (r#"["-", 0, 1], ["|", 0, 1]"#, "duplicate wires"),
+        ] {
+            assert!(load(wires).is_err(), "{why} should not load");
+        }
+        assert!(
+            toml::from_str::<Graph>(
+                "nodes = [[\"|\", [0, 0], false], [\"&\", [1, 1], false]]\nwires = []"
+            )
+            .is_err(),
+            "nodes sharing a cell should not load"
+        );
+        assert!(
+            obj_format::from_obj_str(
+                &obj_format::to_obj_string(&load(r#"["-", 0, 1]"#).unwrap()).unwrap()
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_ticks_since_change() {
+        use crate::graph::node::Ntd;
+
+        let (mut g, [a, b]) = test_graph! {
+            {Battery} a;
+            {Delay { ticks: Ntd::Two }} b;
+            a -> b;
+            [({a}), ({b})];
+        };
+        assert_eq!(
+            g.ticks_since_change(&a),
+            None,
+            "nothing has changed before the first tick"
+        );
+        g.evaluate();
+        assert_eq!(g.ticks_since_change(&a), Some(0));
+        assert_eq!(g.ticks_since_change(&b), None);
+        g.evaluate();
+        g.evaluate();
+        assert_eq!(
+            g.ticks_since_change(&a),
+            Some(2),
+            "a battery should only change on its first tick"
+        );
+        assert_eq!(
+            g.ticks_since_change(&b),
+            Some(0),
+            "the delay should change two ticks after its input"
+        );
+    }
+
+    #[test]
+    fn test_bus_split_merge() {
+        use crate::graph::node::{Bits, Ntd};
+
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let mut node = |gate, x, y| {
+            *g.create_node(gate, IVec2::new(x, y), &mut console)
+                .unwrap()
+                .id()
+        };
+        let battery = node(Gate::Battery, 0, 0);
+        let merge_0 = node(Gate::Merge { bit: Ntd::Zero }, 8, 0);
+        let merge_2 = node(Gate::Merge { bit: Ntd::Two }, 8, 8);
+        let bus = node(Gate::Or, 16, 0);
+        let mask = node(Gate::Battery, 16, 8);
+        let and = node(Gate::And, 24, 0);
+        let split_1 = node(Gate::Split { bit: Ntd::One }, 32, 0);
+        let split_2 = node(Gate::Split { bit: Ntd::Two }, 32, 8);
+        for (src, dst) in [
+            (battery, merge_0),
+            (battery, merge_2),
+            (merge_0, bus),
+            (merge_2, bus),
+            (bus, and),
+            (mask, and),
+            (and, split_1),
+            (and, split_2),
+        ] {
+            g.create_wire(Elbow::default(), src, dst, &mut console)
+                .unwrap();
+        }
+        for id in [bus, mask, and] {
+            g.node_mut(&id).unwrap().set_width(4);
+        }
+        g.refresh_eval_order();
+        for _ in 0..4 {
+            g.evaluate();
+        }
+        assert_eq!(g.node(&mask).unwrap().bits(), Bits(0b1111));
+        assert_eq!(
+            g.node(&bus).unwrap().bits(),
+            Bits(0b0101),
+            "merges should put their inputs on separate bits"
+        );
+        assert_eq!(
+            g.node(&and).unwrap().bits(),
+            Bits(0b0101),
+            "and should work on each bit of a bus"
+        );
+        assert_eq!(
+            [split_1, split_2].map(|id| g.node(&id).unwrap().bits()),
+            [Bits::ZERO, Bits::ONE],
+            "splits should take a single bit out of a bus"
+        );
+    }
+
+    #[test]
+    fn test_switch_and_button() {
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let mut node = |gate, x| {
+            *g.create_node(gate, IVec2::new(x, 0), &mut console)
+                .unwrap()
+                .id()
+        };
+        let battery = node(Gate::Battery, 0);
+        let switch = node(Gate::Switch { on: false }, 8);
+        let button = node(Gate::Button, 16);
+        g.create_wire(Elbow::default(), battery, switch, &mut console)
+            .unwrap();
+        g.refresh_eval_order();
+        g.evaluate();
+        assert!(
+            !g.node(&switch).unwrap().state(),
+            "a switch should ignore its inputs"
+        );
+
+        for id in [switch, button] {
+            match g.node_mut(&id).unwrap().gate_mut() {
+                GateInstance::Switch { on } => *on = true,
+                GateInstance::Button { pressed } => *pressed = true,
+                _ => unreachable!(),
+            }
+        }
+        g.evaluate();
+        assert!(g.node(&switch).unwrap().state());
+        assert!(g.node(&button).unwrap().state());
+
+        let text = obj_format::to_obj_string(&g).expect("graph should serialize");
+        let h = obj_format::from_obj_str(&text).expect("serialized graph should deserialize");
+        let h_gate = |x| {
+            h.node(h.find_node_at(IVec2::new(x, 0)).unwrap())
+                .unwrap()
+                .gate()
+                .as_gate()
+        };
+        assert_eq!(
+            h_gate(8),
+            Gate::Switch { on: true },
+            "switches keep their state"
+        );
+        assert_eq!(h_gate(16), Gate::Button);
+        let h = toml::from_str::<Graph>(&toml::to_string(&g).expect("graph should serialize"))
+            .expect("serialized graph should deserialize");
+        assert_eq!(
+            h.node(h.find_node_at(IVec2::new(8, 0)).unwrap())
+                .unwrap()
+                .gate()
+                .as_gate(),
+            Gate::Switch { on: true }
+        );
+    }
+
+    #[test]
+    fn test_states_survive_refresh() {
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        g.set_eval_mode(EvalMode::Synchronous);
+        let [a, b] = [0, 16].map(|x| {
+            *g.create_node(Gate::Nor, IVec2::new(x, 0), &mut console)
+                .unwrap()
+                .id()
+        });
+        g.create_wire(Elbow::Horizontal, a, b, &mut console)
+            .unwrap();
+        g.refresh_eval_order();
+        g.evaluate();
+        g.evaluate();
+        assert_eq!([a, b].map(|id| g.node(&id).unwrap().state()), [true, false]);
+
+        let c = *g
+            .create_node(Gate::Or, IVec2::new(32, 0), &mut console)
+            .unwrap()
+            .id();
+        g.create_wire(Elbow::Horizontal, a, c, &mut console)
+            .unwrap();
+        g.refresh_eval_order();
+        g.evaluate();
+        assert_eq!(
+            [a, b, c].map(|id| g.node(&id).unwrap().state()),
+            [true, false, true],
+            "c reads the state a had before eval order was rebuilt"
+        );
+    }
+
+    #[test]
+    fn test_history_replay() {
+        let (mut g, [a, b, c]) = test_graph! {
+            {Nor} a;
+            {Or} b;
+            {Or} c;
+            a -> b;
+            b -> c;
+            [({a}), ({b}), ({c})];
+        };
+        let displayed = |g: &Graph| [a, b, c].map(|id| g.displayed_state(&id));
+
+        g.set_eval_mode(EvalMode::Synchronous);
+        g.set_recording(true);
+        g.refresh_eval_order();
+        for _ in 0..3 {
+            g.evaluate();
+        }
+        assert_eq!(g.history().unwrap().len(), 3);
+        assert!(!g.is_replaying());
+
+        g.history_mut().unwrap().step(-2);
+        assert_eq!(
+            displayed(&g),
+            [true, false, false],
+            "replay should show the first recorded tick"
+        );
+        assert_eq!(
+            [a, b, c].map(|id| g.node(&id).unwrap().state()),
+            [true, true, true],
+            "replay should not change the live states"
+        );
+
+        g.history_mut().unwrap().step(1);
+        assert_eq!(displayed(&g), [true, true, false]);
+
+        g.history_mut().unwrap().step(1);
+        assert!(
+            !g.is_replaying(),
+            "stepping onto the latest tick should go live"
+        );
+        assert_eq!(displayed(&g), [true, true, true]);
+    }
+
+    #[test]
+    fn test_bus_states_kept() {
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let bus = *g
+            .create_node(Gate::Or, IVec2::new(0, 0), &mut console)
+            .unwrap()
+            .id();
+        let battery = *g
+            .create_node(Gate::Battery, IVec2::new(8, 0), &mut console)
+            .unwrap()
+            .id();
+        for id in [bus, battery] {
+            g.set_node_width(&id, 4, &mut console).unwrap();
+        }
+        g.node_mut(&bus).unwrap().state = Bits(0b1010);
+        let bits = |g: &Graph| {
+            let mut nodes = g.nodes.values().collect::<Vec<_>>();
+            nodes.sort_unstable_by_key(|node| node.id().0);
+            nodes.into_iter().map(Node::bits).collect::<Vec<_>>()
+        };
+        let expected = [Bits(0b1010), Bits::ZERO];
+
+        let model = model::GraphModel::from(&g);
+        assert_eq!(bits(&model.to_graph(GraphId(1)).unwrap()), expected);
+        let mut h = Graph::new(GraphId(2));
+        model.paste(&mut h, IVec2::zero(), &mut console);
+        assert_eq!(bits(&h), expected, "pasting should keep every bit");
+        let h = toml::from_str::<Graph>(&toml::to_string(&g).expect("graph should serialize"))
+            .expect("serialized graph should deserialize");
+        assert_eq!(bits(&h), expected, "saving should keep every bit");
+        let h = obj_format::from_obj_str(&obj_format::to_obj_string(&g).unwrap()).unwrap();
+        assert_eq!(bits(&h), expected);
+
+        g.set_recording(true);
+        g.refresh_eval_order();
+        g.evaluate();
+        g.evaluate();
+        g.history_mut().unwrap().step(-1);
+        assert!(g.is_replaying());
+        assert_eq!(
+            g.displayed_bits(&battery),
+            Bits(0b1111),
+            "replay should show every bit of a bus"
+        );
+    }
+
+    #[test]
+    fn test_trace_vcd() {
+        let (mut g, [a, b, c]) = test_graph! {
+            {Nor} a;
+            {Or} b;
+            {Or} c;
+            a -> b;
+            b -> c;
+            [({a}), ({b}), ({c})];
+        };
+
+        g.set_eval_mode(EvalMode::Synchronous);
+        g.refresh_eval_order();
+        assert_eq!(g.toggle_probe(&a), Some(true));
+        assert_eq!(g.toggle_probe(&c), Some(true));
+        g.evaluate();
+        assert_eq!(
+            g.toggle_probe(&b),
+            Some(true),
+            "probing should work mid-trace"
+        );
+        g.evaluate();
+        g.evaluate();
+
+        let mut vcd = Vec::new();
+        g.trace().unwrap().write_vcd(&mut vcd, "test").unwrap();
+        let vcd = String::from_utf8(vcd).unwrap();
+        let (_, changes) = vcd.split_once("$enddefinitions $end\n").unwrap();
+        assert_eq!(
+            changes, "#0\n$dumpvars\n0!\n0\"\nx#\n$end\n#1\n1!\n0#\n#2\n1#\n#3\n1\"\n",
+            "b should be unknown until it was probed"
+        );
+
+        for id in [a, b, c] {
+            assert_eq!(g.toggle_probe(&id), Some(false));
+        }
+        assert!(
+            g.trace().is_none(),
+            "the trace should be discarded with its last probe"
+        );
+    }
+
+    #[test]
+    fn test_truth_table() {
+        let (mut g, [a, b, c]) = test_graph! {
+            {Or} a;
+            {Or} b;
+            {And} c;
+            a -> c;
+            b -> c;
+            [({a, b}), ({c})];
+        };
+        for (id, position) in [
+            (a, IVec2::new(0, 0)),
+            (b, IVec2::new(0, 1)),
+            (c, IVec2::new(1, 0)),
+        ] {
+            g.nodes.get_mut(&id).unwrap().position = position;
+        }
+
+        let table = g.truth_table(&GraphList::new(), None).unwrap();
+        assert_eq!(table.inputs, [a, b]);
+        assert_eq!(table.outputs, [c]);
+        assert_eq!(
+            table.rows,
+            [[Some(false)], [Some(false)], [Some(false)], [Some(true)]]
+        );
+        assert_eq!(
+            g.nodes_iter().map(Node::state).collect::<Vec<_>>(),
+            [false; 3],
+            "the graph itself should not be evaluated"
+        );
+
+        let selection = FxHashSet::from_iter([a, c]);
+        let table = g.truth_table(&GraphList::new(), Some(&selection)).unwrap();
+        assert_eq!(table.inputs, [a], "b should be left out with the selection");
+        assert_eq!(table.rows, [[Some(false)], [Some(true)]]);
+    }
+
+    #[test]
+    fn test_interface() {
+        let (mut g, [a, b, c, d]) = test_graph! {
+            {Input { pin: Ntd::One }} a;
+            {Input { pin: Ntd::Zero }} b;
+            {Xor} c;
+            {Output { pin: Ntd::Zero }} d;
+            a -> c;
+            b -> c;
+            c -> d;
+            [({a, b}), ({c}), ({d})];
+        };
+        g.nodes.get_mut(&a).unwrap().position = IVec2::new(0, 0);
+        g.nodes.get_mut(&b).unwrap().position = IVec2::new(0, 1);
+        g.node_mut(&d).unwrap().set_name("sum".to_string());
+        assert_eq!(
+            g.interface(),
+            Some((vec![b, a], vec![d])),
+            "pins should be ordered by number before position"
+        );
+
+        let table = g.truth_table(&GraphList::new(), None).unwrap();
+        assert_eq!(table.inputs, [b, a]);
+        assert_eq!(table.outputs, [d]);
+        assert_eq!(
+            table.rows,
+            [[Some(false)], [Some(true)], [Some(true)], [Some(false)]]
+        );
+        assert!(table.lines()[0].ends_with("| sum"));
+    }
+
+    #[test]
+    fn test_components() {
+        let mut next_node_id = NodeId(0);
+        let mut next_wire_id = WireId(0);
+        let [a, b, c, d, e] = std::array::from_fn(|_| next_node_id.step().unwrap());
+        let g = gen_graph(
+            GraphId(0),
+            [a, b, c, d, e].map(|id| (id, Gate::Or)),
+            [(a, b), (c, b), (d, e), (e, d)].map(|x| (next_wire_id.step().unwrap(), x)),
+        );
+        let mut components = g.components().collect::<Vec<_>>();
+        components.sort_by_key(|set| std::cmp::Reverse(set.len()));
+        assert_eq!(
+            components,
+            [
+                FxHashSet::from_iter([a, b, c]),
+                FxHashSet::from_iter([d, e]),
+            ],
+            "wire direction should not split a component"
+        );
+    }
+
+    #[test]
+    fn test_combinational_loops() {
+        let mut next_node_id = NodeId(0);
+        let mut next_wire_id = WireId(0);
+        let [a, b, c, d, e, f, h, i] = std::array::from_fn(|_| next_node_id.step().unwrap());
+        let mut g = gen_graph(
+            GraphId(0),
+            [
+                (a, Gate::Nor),
+                (b, Gate::Nor),
+                (c, Gate::Or),
+                (d, Gate::Delay { ticks: Ntd::One }),
+                (e, Gate::Or),
+                (f, Gate::Or),
+                (h, Gate::Or),
+                (i, Gate::Delay { ticks: Ntd::Zero }),
+            ],
+            [(a, b), (b, a), (c, d), (d, c), (e, f), (h, i), (i, h)]
+                .map(|x| (next_wire_id.step().unwrap(), x)),
+        );
+        for (n, id) in [b, a, c, d, e, f, h, i].into_iter().enumerate() {
+            g.nodes.get_mut(&id).unwrap().position = IVec2::new(0, n as i32);
+        }
+        assert_eq!(
+            g.combinational_loops(),
+            [vec![b, a], vec![h, i]],
+            "the delay should hold its loop unless it has no ticks, and loops should be in \
+             reading order"
+        );
+    }
+
+    #[test]
+    fn test_longest_combinational_path() {
+        let mut next_node_id = NodeId(0);
+        let mut next_wire_id = WireId(0);
+        let [a, b, c, d, e, f, g] = std::array::from_fn(|_| next_node_id.step().unwrap());
+        let graph = gen_graph(
+            GraphId(0),
+            [
+                (a, Gate::Or),
+                (b, Gate::Or),
+                (c, Gate::Or),
+                (d, Gate::Delay { ticks: Ntd::One }),
+                (e, Gate::Or),
+                (f, Gate::Nor),
+                (g, Gate::Nor),
+            ],
+            [(a, b), (b, c), (c, d), (d, e), (f, g), (g, f), (g, a)]
+                .map(|x| (next_wire_id.step().unwrap(), x)),
+        );
+        assert_eq!(graph.strongly_connected_components().len(), 6);
+        assert_eq!(
+            graph.longest_combinational_path(),
+            5,
+            "the loop should count once, and the path should end at the delay"
+        );
+    }
+
+    #[test]
+    fn test_islands() {
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let mut node = |x, y| {
+            *g.create_node(Gate::Or, IVec2::new(x, y), &mut console)
+                .unwrap()
+                .id()
+        };
+        let lone_b = node(40, 0);
+        let lone_a = node(32, 8);
+        let [a, b, c] = [(0, 16), (8, 8), (16, 24)].map(|(x, y)| node(x, y));
+        g.create_wire(Elbow::default(), a, b, &mut console).unwrap();
+        g.create_wire(Elbow::default(), c, b, &mut console).unwrap();
+
+        let islands = g.islands();
+        assert_eq!(
+            islands
+                .iter()
+                .map(|island| island.representative)
+                .collect::<Vec<_>>(),
+            [b, lone_b, lone_a],
+            "islands should be largest first, then top-to-bottom"
+        );
+        assert_eq!(
+            islands[0].bounds,
+            IBounds::new(IVec2::new(0, 8), IVec2::new(16, 24))
+        );
+    }
+
+    #[test]
+    fn test_model_round_trip() {
+        let (mut g, [_, b]) = test_graph! {
+            {Or} a;
+            {Nor} b;
+            a -> b;
+            [({a}), ({b})];
+        };
+        g.node_mut(&b).unwrap().position = IVec2::new(8, 16);
+        let model = model::GraphModel::from(&g);
+        let h = model
+            .to_graph(GraphId(1))
+            .expect("model of a valid graph should be valid");
+        assert_eq!(model, model::GraphModel::from(&h));
+    }
+
+    #[test]
+    fn test_selection_paste() {
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let a = *g
+            .create_node(Gate::Or, IVec2::new(16, 8), &mut console)
+            .unwrap()
+            .id();
+        let b = *g
+            .create_node(Gate::Nor, IVec2::new(24, 16), &mut console)
+            .unwrap()
+            .id();
+        let c = *g
+            .create_node(Gate::And, IVec2::new(0, 0), &mut console)
+            .unwrap()
+            .id();
+        g.create_wire(Elbow::default(), a, b, &mut console).unwrap();
+        g.create_wire(Elbow::default(), b, c, &mut console).unwrap();
+
+        let model = model::GraphModel::from_selection(&g, &FxHashSet::from_iter([a, b]));
+        assert_eq!(model.nodes.len(), 2);
+        assert_eq!(
+            model.wires.len(),
+            1,
+            "only wires inside the selection are captured"
+        );
+        assert_eq!((model.nodes[0].x, model.nodes[0].y), (0, 0));
+        assert_eq!((model.nodes[1].x, model.nodes[1].y), (8, 8));
+
+        let pasted = model.paste(&mut g, IVec2::new(-16, -16), &mut console);
+        assert_eq!(pasted.len(), 2);
+        assert!(pasted.iter().all(|id| ![a, b, c].contains(id)));
+        assert_eq!(g.find_node_at(IVec2::new(-16, -16)), Some(&pasted[0]));
+        assert_eq!(g.find_node_at(IVec2::new(-8, -8)), Some(&pasted[1]));
+        assert!(
+            g.wires_from(&pasted[0])
+                .any(|(_, wire)| *wire.dst() == pasted[1])
+        );
+
+        // pasting onto itself collides with every node, so nothing is created
+        let pasted = model.paste(&mut g, IVec2::new(16, 8), &mut console);
+        assert!(pasted.is_empty());
+        assert_eq!(g.nodes_iter().len(), 5);
+    }
+
+    #[test]
+    fn test_fan_limits() {
+        use crate::graph::limits::{FanLimit, FanLimits};
+
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        g.set_fan_limits(FanLimits {
+            and: FanLimit {
+                max_in: Some(2),
+                soft_in: Some(1),
+                ..FanLimit::NONE
+            },
+            ..FanLimits::NONE
+        });
+        let [a, b, c, d] = [0, 8, 16, 24].map(|x| {
+            *g.create_node(Gate::Or, IVec2::new(x, 0), &mut console)
+                .unwrap()
+                .id()
+        });
+        let and = *g
+            .create_node(Gate::And, IVec2::new(0, 8), &mut console)
+            .unwrap()
+            .id();
+
+        g.create_wire(Elbow::default(), a, and, &mut console)
+            .unwrap();
+        assert!(g.soft_limit_violations().is_empty());
+        g.create_wire(Elbow::default(), b, and, &mut console)
+            .unwrap();
+        assert_eq!(g.soft_limit_violations(), FxHashSet::from_iter([and]));
+        assert_eq!(
+            g.create_wire(Elbow::default(), c, and, &mut console)
+                .map(|wire| *wire.id()),
+            Err(CreateWireError::FanIn),
+        );
+        assert!(
+            g.create_wire(Elbow::default(), and, d, &mut console)
+                .is_ok(),
+            "fan-out is not limited"
+        );
+
+        let or = *g
+            .create_node(Gate::Or, IVec2::new(8, 8), &mut console)
+            .unwrap()
+            .id();
+        for src in [a, b, c] {
+            g.create_wire(Elbow::default(), src, or, &mut console)
+                .unwrap();
+        }
+        assert!(
+            g.replace_nodes_gate([&or], Gate::And, &mut console)
+                .is_empty()
+        );
+        assert_eq!(g.set_nodes_gate([&or], GateId::And, &mut console), 0);
+        g.set_node_gate(&or, Gate::And, &mut console).unwrap();
+        assert_eq!(
+            g.node(&or).unwrap().gate().as_gate(),
+            Gate::Or,
+            "gates should not be replaced by ones whose fan-in limit the node exceeds"
+        );
+        assert_eq!(
+            g.replace_nodes_gate([&and], Gate::Nor, &mut console),
+            [(and, Gate::And)],
+            "nodes within the limits should still be replaceable"
+        );
+        // nor gates have no fan-in limit, so the and gate can't be put back after another input
+        g.create_wire(Elbow::default(), c, and, &mut console)
+            .unwrap();
+        assert!(g.replace_gates([(and, Gate::And)], &mut console).is_empty());
+        assert_eq!(g.node(&and).unwrap().gate().as_gate(), Gate::Nor);
+    }
+
+    #[test]
+    fn test_restore_erased() {
+        use crate::graph::limits::{FanLimit, FanLimits};
+
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let [a, b, c] = [0, 16, 32].map(|x| {
+            *g.create_node(Gate::Or, IVec2::new(x, 0), &mut console)
+                .unwrap()
+                .id()
+        });
+        let ab = *g
+            .create_wire(Elbow::Horizontal, a, b, &mut console)
+            .unwrap()
+            .id();
+        let bc = *g
+            .create_wire(Elbow::Horizontal, b, c, &mut console)
+            .unwrap()
+            .id();
+
+        let mut erased = Erased::default();
+        assert!(g.erase_node(&b, true, &mut erased, &mut console));
+        assert!(!g.erase_node(&b, true, &mut erased, &mut console));
+        assert_eq!(g.wires.len(), 1, "a and c should be bypassed");
+        assert_eq!(g.restore(erased, &mut console), (1, 2));
+        assert!(g.node(&b).is_some());
+        assert_eq!(
+            g.wires.keys().copied().collect::<FxHashSet<_>>(),
+            FxHashSet::from_iter([ab, bc]),
+            "the bypass should go when the node comes back"
+        );
+
+        let mut erased = Erased::default();
+        assert!(g.erase_wire(&ab, &mut erased));
+        assert!(g.erase_node(&c, false, &mut erased, &mut console));
+        let d = *g
+            .create_node(Gate::And, IVec2::new(32, 0), &mut console)
+            .unwrap()
+            .id();
+        assert_eq!(
+            g.restore(erased, &mut console),
+            (0, 1),
+            "nodes should not come back into taken cells, nor wires to missing nodes"
+        );
+        assert!(g.wires.contains_key(&ab));
+
+        let mut erased = Erased::default();
+        assert!(g.erase_wire(&ab, &mut erased));
+        g.set_fan_limits(FanLimits {
+            or: FanLimit {
+                max_in: Some(1),
+                ..FanLimit::NONE
+            },
+            ..FanLimits::NONE
+        });
+        g.create_wire(Elbow::Horizontal, d, b, &mut console)
+            .unwrap();
+        assert_eq!(
+            g.restore(erased, &mut console),
+            (0, 0),
+            "wires should not come back past a fan limit"
+        );
+    }
+
+    #[test]
+    fn test_rewire() {
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let [a, b, c] = [0, 16, 32].map(|x| {
+            *g.create_node(Gate::Or, IVec2::new(x, 0), &mut console)
+                .unwrap()
+                .id()
+        });
+        let ab = *g
+            .create_wire(Elbow::Horizontal, a, b, &mut console)
+            .unwrap()
+            .id();
+        let bc = *g
+            .create_wire(Elbow::Horizontal, b, c, &mut console)
+            .unwrap()
+            .id();
+
+        // wires run along the middle of the cells
+        let y = g.cell_center().y;
+        assert_eq!(g.find_wire_at(Vector2::new(10.0, y + 1.0), 2.0), Some(&ab));
+        assert_eq!(g.find_wire_at(Vector2::new(30.0, y), 2.0), Some(&bc));
+        assert_eq!(g.find_wire_at(Vector2::new(10.0, y + 3.0), 2.0), None);
+
+        assert_eq!(
+            g.reattach_wire(&ab, WireEnd::Dst, b, &mut console)
+                .map(|wire| *wire.id()),
+            Ok(ab),
+            "reattaching to the same node changes nothing"
+        );
+        assert_eq!(
+            g.reattach_wire(&ab, WireEnd::Src, c, &mut console)
+                .map(|wire| (*wire.id(), *wire.src(), *wire.dst())),
+            Ok((ab, c, b)),
+        );
+        assert_eq!(
+            g.reattach_wire(&bc, WireEnd::Dst, a, &mut console)
+                .map(|wire| (*wire.src(), *wire.dst())),
+            Ok((b, a)),
+        );
+        g.reattach_wire(&ab, WireEnd::Dst, a, &mut console).unwrap();
+        assert_eq!(
+            g.reattach_wire(&bc, WireEnd::Src, c, &mut console)
+                .map(|wire| *wire.id()),
+            Err(CreateWireError::Exists(ab)),
+            "c -> a already exists"
+        );
+        assert_eq!(g.wire(&bc).map(|wire| *wire.src()), Some(b));
+        assert_eq!(
+            g.reverse_wire(&bc, &mut console)
+                .map(|wire| (*wire.id(), *wire.src(), *wire.dst())),
+            Ok((bc, a, b)),
+        );
+        assert_eq!(g.destroy_wire(&ab).map(|wire| *wire.id()), Some(ab));
+        assert_eq!(g.find_wire_at(Vector2::new(10.0, y), 2.0), Some(&bc));
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        use crate::graph::node::Ntd;
+
+        let (mut g, [_, b, c]) = test_graph! {
+            {Or} a;
+            {Resistor { resistance: Ntd::Three }} b;
+            {Led { color: Ntd::Five }} c;
+            a -> b;
+            b -> c;
+            [({a}), ({b}), ({c})];
+        };
+        g.node_mut(&b).unwrap().position = IVec2::new(8, 0);
+        g.node_mut(&c).unwrap().position = IVec2::new(16, 0);
+        let h = toml::from_str::<Graph>(&toml::to_string(&g).expect("graph should serialize"))
+            .expect("serialized graph should deserialize");
+
+        let summarize = |g: &Graph| {
+            let nodes = g
+                .nodes_iter()
+                .map(|node| (node.position(), node.gate().as_gate()))
+                .collect::<FxHashSet<_>>();
+            let wires = g
+                .wires_iter()
+                .map(|wire| {
+                    let (src, dst) = g.get_wire_nodes(wire).unwrap();
+                    (src.position(), dst.position())
+                })
+                .collect::<FxHashSet<_>>();
+            (nodes, wires)
+        };
+        assert_eq!(summarize(&g), summarize(&h));
+        assert!(h.find_node_at(IVec2::new(16, 0)).is_some());
+    }
+    #[test]
+    fn test_notes_round_trip() {
+        let (mut g, [a, b]) = test_graph! {
+            {Or} a;
+            {And} b;
+            a -> b;
+            [({a}), ({b})];
+        };
+        g.node_mut(&b).unwrap().position = IVec2::new(8, 0);
+        let wire = *g.wires_iter().next().unwrap().id();
+        assert_eq!(g.node_mut(&a).unwrap().set_note("carry".to_string()), None);
+        g.wire_mut(&wire).unwrap().set_note("carry out".to_string());
+        g.node_mut(&a).unwrap().set_name("c0".to_string());
+        assert_eq!(
+            g.node_mut(&b).unwrap().set_note(String::new()),
+            None,
+            "empty notes are not stored"
+        );
+
+        let h = toml::from_str::<Graph>(&toml::to_string(&g).expect("graph should serialize"))
+            .expect("serialized graph should deserialize");
+        let note_at = |pos| {
+            h.node(h.find_node_at(pos).unwrap())
+                .unwrap()
+                .note()
+                .map(str::to_string)
+        };
+        assert_eq!(note_at(IVec2::new(0, 0)).as_deref(), Some("carry"));
+        assert_eq!(note_at(IVec2::new(8, 0)), None);
+        assert_eq!(h.wires_iter().next().unwrap().note(), Some("carry out"));
+        let a = h.node(h.find_node_at(IVec2::new(0, 0)).unwrap()).unwrap();
+        assert_eq!(a.name(), Some("c0"));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut console = test_console();
+        let mut source = Graph::new(GraphId(1));
+        let a = *source
+            .create_node(Gate::Or, IVec2::new(8, 8), &mut console)
+            .unwrap()
+            .id();
+        let b = *source
+            .create_node(Gate::And, IVec2::new(16, 8), &mut console)
+            .unwrap()
+            .id();
+        source
+            .create_wire(Elbow::default(), a, b, &mut console)
+            .unwrap();
+
+        let mut g = Graph::new(GraphId(0));
+        let existing = *g
+            .create_node(Gate::Xor, IVec2::new(0, 16), &mut console)
+            .unwrap()
+            .id();
+        let merged = g.merge(&source, IVec2::new(0, 0), &mut console);
+        assert_eq!(merged.len(), 2);
+        assert!(
+            !merged.contains(&existing),
+            "merged nodes should get fresh IDs"
+        );
+        assert_eq!(
+            g.wires_iter().count(),
+            1,
+            "wires between merged nodes are kept"
+        );
+
+        let merged = g.merge(&source, IVec2::new(8, 0), &mut console);
+        assert_eq!(
+            merged.len(),
+            1,
+            "nodes landing on occupied cells are left out"
+        );
+        assert_eq!(g.wires_iter().count(), 1, "and so are their wires");
+    }
+
+    #[test]
+    fn test_route_around_nodes() {
+        let mut g = Graph::new(GraphId(0));
+        let mut console = test_console();
+        let size = i32::from(g.grid_size());
+        let cell = |x: i32, y: i32| IVec2::new(x * size, y * size);
+        let a = *g
+            .create_node(Gate::Or, cell(0, 0), &mut console)
+            .unwrap()
+            .id();
+        let b = *g
+            .create_node(Gate::Or, cell(4, 0), &mut console)
+            .unwrap()
+            .id();
+        assert_eq!(g.find_route(&a, &b), Some(Vec::new()), "nothing in the way");
+
+        for y in -1..=1 {
+            g.create_node(Gate::And, cell(2, y), &mut console).unwrap();
+        }
+        let route = g.find_route(&a, &b).expect("should get around the wall");
+        assert_eq!(route.len(), 2, "should turn as few times as it can");
+        assert!(
+            route == [cell(0, -2), cell(4, -2)] || route == [cell(0, 2), cell(4, 2)],
+            "should pass just outside the wall: {route:?}"
+        );
+
+        let wire = *g
+            .create_wire(Elbow::default(), a, b, &mut console)
+            .unwrap()
+            .id();
+        assert!(g.route_wire(&wire));
+        g.translate_node(&b, cell(4, 3), &mut console).unwrap();
+        let route = g.wire(&wire).unwrap().route().unwrap().to_vec();
+        assert_eq!(
+            route,
+            [cell(0, 3)],
+            "moving a node should reroute its wires"
+        );
+
+        let h = toml::from_str::<Graph>(&toml::to_string(&g).expect("graph should serialize"))
+            .expect("serialized graph should deserialize");
+        assert_eq!(h.wires_iter().next().unwrap().route(), Some(&route[..]));
+        let h = obj_format::from_obj_str(&obj_format::to_obj_string(&g).unwrap()).unwrap();
+        assert_eq!(h.wires_iter().next().unwrap().route(), Some(&route[..]));
+    }
+
+    #[test]
+    fn test_regions_round_trip() {
+        let mut g = Graph::new(GraphId(0));
+        let mut console = test_console();
+        let adder = *g
+            .create_region(
+                IBounds::new(IVec2::new(16, 24), IVec2::new(-8, 0)),
+                "adder".to_string(),
+                Ntd::Three,
+                &mut console,
+            )
+            .id();
+        g.create_region(
+            IBounds::new(IVec2::new(0, 0), IVec2::new(8, 8)),
+            String::new(),
+            Ntd::Zero,
+            &mut console,
+        );
+        assert_eq!(
+            *g.region(&adder).unwrap().bounds(),
+            IBounds::new(IVec2::new(-8, 0), IVec2::new(16, 24)),
+            "corners are put in order"
+        );
+        g.translate_region(&adder, IVec2::new(8, 8), &mut console)
+            .unwrap();
+
+        let h = toml::from_str::<Graph>(&toml::to_string(&g).expect("graph should serialize"))
+            .expect("serialized graph should deserialize");
+        let regions = h.regions_ordered();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].label, "adder");
+        assert_eq!(regions[0].color, Ntd::Three);
+        assert_eq!(
+            *regions[0].bounds(),
+            IBounds::new(IVec2::new(8, 8), IVec2::new(32, 32))
+        );
+        assert_eq!(regions[1].label, "");
+    }
+
+    #[test]
+    fn test_annotations_round_trip() {
+        let mut g = Graph::new(GraphId(0));
+        let mut console = test_console();
+        let id = *g
+            .create_annotation(
+                IVec2::new(8, 16),
+                "clock\ndivider".to_string(),
+                &mut console,
+            )
+            .id();
+        g.translate_annotation(&id, IVec2::new(-8, 0), &mut console)
+            .unwrap();
+
+        let h = toml::from_str::<Graph>(&toml::to_string(&g).expect("graph should serialize"))
+            .expect("serialized graph should deserialize");
+        let annotations = h.annotations_ordered();
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].text, "clock\ndivider");
+        assert_eq!(annotations[0].position(), IVec2::new(-8, 0));
+        assert_eq!(h.nodes_iter().count(), 0, "annotations are not nodes");
+    }
+
+    #[test]
+    fn test_obj_format_round_trip() {
+        let (mut g, [a, b]) = test_graph! {
+            {Or} a;
+            {Delay { ticks: Ntd::Two }} b;
+            a -> b;
+            [({a}), ({b})];
+        };
+        g.node_mut(&b).unwrap().position = IVec2::new(8, 0);
+        g.node_mut(&a).unwrap().set_note("clock \"in\"".to_string());
+
+        let text = obj_format::to_obj_string(&g).expect("graph should serialize");
+        assert!(text.contains("\nversion 1\n"), "{text}");
+        let h = obj_format::from_obj_str(&text).expect("serialized graph should deserialize");
+        let a = h.find_node_at(IVec2::new(0, 0)).unwrap();
+        let b = h.find_node_at(IVec2::new(8, 0)).unwrap();
+        assert_eq!(h.node(a).unwrap().note(), Some("clock \"in\""));
+        assert_eq!(
+            h.node(b).unwrap().gate.as_gate(),
+            Gate::Delay { ticks: Ntd::Two }
+        );
+        let wire = h.wires_iter().next().unwrap();
+        assert_eq!((&wire.src, &wire.dst), (a, b));
+
+        assert!(
+            obj_format::from_obj_str("version 2\n").is_err(),
+            "newer versions are refused"
+        );
+        assert!(
+            obj_format::from_obj_str("version 1\nn \"or\" 0 0 false E 1\nw 1 2 /\n").is_err(),
+            "wires to missing nodes are refused"
+        );
+    }
+
+    #[test]
+    fn test_curve_elbow() {
+        let (start, end) = (Vector2::new(0.0, 0.0), Vector2::new(16.0, 8.0));
+        let path = Elbow::curve_path(start, end);
+        assert_eq!(path[0], start);
+        assert_eq!(path[wire::CURVE_POINTS - 1], end);
+        assert_eq!(
+            path[wire::CURVE_POINTS / 2],
+            Elbow::Curve.calculate(start, end)
+        );
+
+        let points = [start, Elbow::Curve.calculate(start, end), end];
+        assert!(
+            Wire::path_distance(points, Elbow::Curve, Vector2::new(2.0, 0.0)) < 0.5,
+            "curves leave their start horizontally"
+        );
+        assert!(
+            Wire::path_distance(points, Elbow::Straight, Vector2::new(2.0, 0.0)) > 0.5,
+            "straight wires head directly to the end"
+        );
+        assert_eq!(
+            Elbow::ALL.map(Elbow::next),
+            [
+                Elbow::DiagonalStart,
+                Elbow::Vertical,
+                Elbow::DiagonalEnd,
+                Elbow::Straight,
+                Elbow::Curve,
+                Elbow::Horizontal,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_orientation_round_trip() {
+        use crate::graph::{model::GraphModel, node::Orientation};
+
+        let (mut g, [a, b]) = test_graph! {
+            {Or} a;
+            {Or} b;
+            a -> b;
+            [({a}), ({b})];
+        };
+        g.node_mut(&b).unwrap().position = IVec2::new(8, 0);
+        g.node_mut(&b).unwrap().set_orientation(Orientation::North);
+
+        let wire = g.wires_iter().next().unwrap();
+        let [start, _, end] = wire.points(&g, Vector2::new(4.0, 4.0)).unwrap();
+        assert_eq!(
+            start,
+            Vector2::new(8.0, 4.0),
+            "a outputs from its east side"
+        );
+        assert_eq!(
+            end,
+            Vector2::new(12.0, 8.0),
+            "b takes inputs at its south side"
+        );
+
+        let h = toml::from_str::<Graph>(&toml::to_string(&g).expect("graph should serialize"))
+            .expect("serialized graph should deserialize");
+        let orientation_at =
+            |g: &Graph, pos| g.node(g.find_node_at(pos).unwrap()).unwrap().orientation();
+        assert_eq!(orientation_at(&h, IVec2::new(0, 0)), Orientation::East);
+        assert_eq!(orientation_at(&h, IVec2::new(8, 0)), Orientation::North);
+
+        let m = GraphModel::from(&g).to_graph(GraphId(1)).unwrap();
+        assert_eq!(orientation_at(&m, IVec2::new(8, 0)), Orientation::North);
+    }
+
+    #[test]
+    fn test_ic() {
+        use crate::graph::ic::IcError;
+
+        let mut console = test_console();
+        let mut graphs = GraphList::new();
+        let inner = graphs.create_graph().clone();
+        let outer = graphs.create_graph().clone();
+        let (inner_id, outer_id) = (*inner.read().unwrap().id(), *outer.read().unwrap().id());
+
+        {
+            let mut g = inner.write().unwrap();
+            let a = *g
+                .create_node(Gate::Or, IVec2::new(0, 0), &mut console)
+                .unwrap()
+                .id();
+            let n = *g
+                .create_node(Gate::Nor, IVec2::new(8, 0), &mut console)
+                .unwrap()
+                .id();
+            g.create_wire(Elbow::default(), a, n, &mut console).unwrap();
+        }
+
+        let mut g = outer.write().unwrap();
+        let [off, on] = [(Gate::Or, 0), (Gate::Battery, 8)].map(|(gate, y)| {
+            *g.create_node(gate, IVec2::new(0, y), &mut console)
+                .unwrap()
+                .id()
+        });
+        let [off_ic, on_ic] = [off, on].map(|src| {
+            let y = g.node(&src).unwrap().position.y;
+            let ic = *g
+                .create_node(Gate::Ic { graph: inner_id }, IVec2::new(8, y), &mut console)
+                .unwrap()
+                .id();
+            g.create_wire(Elbow::default(), src, ic, &mut console)
+                .unwrap();
+            ic
+        });
+        g.refresh_eval_order();
+        g.refresh_ics(&graphs, &mut console);
+        assert!(g.ic(&off_ic).unwrap().is_ok());
+        g.evaluate();
+        assert!(
+            g.node(&off_ic).unwrap().state(),
+            "IC should run its graph with its input off"
+        );
+        assert!(
+            !g.node(&on_ic).unwrap().state(),
+            "IC should run its graph with its input on"
+        );
+
+        inner
+            .write()
+            .unwrap()
+            .create_node(
+                Gate::Ic { graph: outer_id },
+                IVec2::new(16, 0),
+                &mut console,
+            )
+            .unwrap();
+        g.refresh_ics(&graphs, &mut console);
+        assert_eq!(
+            g.ic(&off_ic).unwrap().as_ref().err(),
+            Some(&IcError::Cycle(outer_id)),
+            "IC should notice that its graph was changed to contain the graph it is in"
+        );
+    }
+
+    #[test]
+    fn test_ic_paths_round_trip() {
+        let mut console = test_console();
+        let mut graphs = GraphList::new();
+        let inner = graphs.create_graph().clone();
+        let outer = graphs.create_graph().clone();
+        let inner_id = *inner.read().unwrap().id();
+        graphs.set_path(inner_id, PathBuf::from("inner.ea"));
+        inner
+            .write()
+            .unwrap()
+            .create_node(Gate::Battery, IVec2::new(0, 0), &mut console)
+            .unwrap();
+        let ic = {
+            let mut g = outer.write().unwrap();
+            let ic = *g
+                .create_node(Gate::Ic { graph: inner_id }, IVec2::new(0, 0), &mut console)
+                .unwrap()
+                .id();
+            g.record_ic_paths(&graphs);
+            ic
+        };
+        let inner_save = toml::to_string(&*inner.read().unwrap()).unwrap();
+        let outer_save = toml::to_string(&*outer.read().unwrap()).unwrap();
+
+        // opened the other way around, so that the graphs swap IDs
+        let mut graphs = GraphList::new();
+        let outer = graphs
+            .insert_graph(toml::from_str(&outer_save).unwrap())
+            .clone();
+        let mut g = outer.write().unwrap();
+        assert_eq!(
+            g.node(&ic).unwrap().gate().as_gate(),
+            Gate::Ic {
+                graph: GraphId::INVALID
+            },
+            "ICs should not run whichever graph has their saved ID"
+        );
+        assert_eq!(g.resolve_ics(&graphs), [PathBuf::from("inner.ea")]);
+
+        let inner = graphs
+            .insert_graph(toml::from_str(&inner_save).unwrap())
+            .clone();
+        let inner_id = *inner.read().unwrap().id();
+        graphs.set_path(inner_id, PathBuf::from("inner.ea"));
+        assert!(g.resolve_ics(&graphs).is_empty());
+        assert_eq!(
+            g.node(&ic).unwrap().gate().as_gate(),
+            Gate::Ic { graph: inner_id }
+        );
+        g.refresh_ics(&graphs, &mut console);
+        assert!(g.ic(&ic).unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_nodes_in_bounds() {
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let [a, b, c, d] = [(0, 0), (-16, 8), (40, 40), (8000, -8000)].map(|(x, y)| {
+            *g.create_node(Gate::Or, IVec2::new(x, y), &mut console)
+                .unwrap()
+                .id()
+        });
+        let in_bounds = |g: &Graph, (min_x, min_y), (max_x, max_y)| {
+            g.nodes_in_bounds(IBounds::new(
+                IVec2::new(min_x, min_y),
+                IVec2::new(max_x, max_y),
+            ))
+            .copied()
+            .collect::<FxHashSet<NodeId>>()
+        };
+
+        assert_eq!(
+            in_bounds(&g, (-16, 0), (40, 40)),
+            FxHashSet::from_iter([a, b]),
+            "max should be exclusive"
+        );
+        assert_eq!(
+            in_bounds(&g, (-8, -8), (41, 41)),
+            FxHashSet::from_iter([a, c])
+        );
+        assert_eq!(
+            in_bounds(&g, (i32::MIN, i32::MIN), (i32::MAX, i32::MAX)),
+            FxHashSet::from_iter([a, b, c, d])
+        );
+
+        g.translate_node(&c, IVec2::new(-800, 0), &mut console)
+            .unwrap();
+        _ = g.destroy_node(&d, false, &mut console).unwrap();
+        assert_eq!(
+            in_bounds(&g, (-800, -8000), (8, 8000)),
+            FxHashSet::from_iter([a, b, c])
+        );
+        assert!(in_bounds(&g, (8, -8000), (8008, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_set_grid_size() {
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let a = *g
+            .create_node(Gate::Or, IVec2::new(-16, 24), &mut console)
+            .unwrap()
+            .id();
+        let revision = g.revision();
+
+        g.set_grid_size(16);
+        assert_eq!(g.grid_size(), 16);
+        assert_ne!(g.revision(), revision);
+        assert_eq!(g.node(&a).unwrap().position(), IVec2::new(-32, 48));
+        assert_eq!(g.cell_center(), Vector2::new(8.0, 8.0));
+        assert_eq!(
+            g.nodes_in_bounds(IBounds::new(IVec2::new(-32, 48), IVec2::new(-16, 64)))
+                .copied()
+                .collect::<Vec<_>>(),
+            [a],
+            "nodes should stay indexed after rescaling"
+        );
+    }
+
+    #[test]
+    fn test_soft_destroy_node() {
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let [a, c, m, d] = std::array::from_fn(|n| {
+            *g.create_node(Gate::Or, IVec2::new(n as i32 * 8, 0), &mut console)
+                .unwrap()
+                .id()
+        });
+        for (elbow, src, dst) in [
+            (Elbow::Horizontal, a, m),
+            (Elbow::Horizontal, c, m),
+            (Elbow::Vertical, m, d),
+            (Elbow::Straight, m, a),
+            (Elbow::Straight, c, d),
+        ] {
+            g.create_wire(elbow, src, dst, &mut console).unwrap();
+        }
+
+        _ = g.destroy_node(&m, true, &mut console).unwrap();
+        let mut edges = g
+            .wires_iter()
+            .map(|wire| (wire.src, wire.dst, wire.elbow))
+            .collect::<Vec<_>>();
+        edges.sort_unstable_by_key(|&(src, dst, _)| (src.0, dst.0));
+        assert_eq!(
+            edges,
+            [
+                (a, d, Elbow::Vertical),
+                (c, a, Elbow::Straight),
+                (c, d, Elbow::Straight),
+            ],
+            "inputs should be wired to outputs, skipping loops and wires that already exist"
+        );
+    }
+
+    #[test]
+    fn test_wire_hops_and_junctions() {
+        use raylib::prelude::Rectangle;
+
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let [h_src, h_dst, v_src, v_dst] = [(0, 16), (48, 16), (24, 0), (24, 40)].map(|(x, y)| {
+            *g.create_node(Gate::Or, IVec2::new(x, y), &mut console)
+                .unwrap()
+                .id()
+        });
+        let h = *g
+            .create_wire(Elbow::Straight, h_src, h_dst, &mut console)
+            .unwrap()
+            .id();
+        g.create_wire(Elbow::Vertical, v_src, v_dst, &mut console)
+            .unwrap();
+        // shares a node with both, so crossing either is not a hop
+        g.create_wire(Elbow::Straight, h_src, v_dst, &mut console)
+            .unwrap();
+
+        let offset = g.cell_center();
+        let view = Rectangle::new(-100.0, -100.0, 300.0, 300.0);
+        let hops = g.find_hops(offset, view, 6.0);
+        assert_eq!(hops.len(), 1);
+        assert!(hops[0].pos.distance_to(Vector2::new(32.0, 20.0)) < 0.001);
+        assert_eq!(
+            hops[0].over, h,
+            "the horizontal wire should hop over the vertical one"
+        );
+        assert_eq!(hops[0].over_dir, Vector2::new(1.0, 0.0));
+        assert!(
+            g.find_hops(offset, Rectangle::new(40.0, 0.0, 20.0, 20.0), 6.0)
+                .is_empty(),
+            "crossings out of view should be left out"
+        );
+
+        let mut junctions = g
+            .find_junctions(offset, view)
+            .into_iter()
+            .map(|junction| junction.pos)
+            .collect::<Vec<_>>();
+        junctions.sort_unstable_by(|a, b| a.x.total_cmp(&b.x));
+        assert_eq!(
+            junctions,
+            [Vector2::new(8.0, 20.0), Vector2::new(24.0, 44.0)]
+        );
+    }
+
+    #[test]
+    fn test_set_node_properties() {
+        use crate::graph::node::{Bits, Ntd};
+
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let a = *g
+            .create_node(
+                Gate::Delay { ticks: Ntd::Two },
+                IVec2::new(0, 0),
+                &mut console,
+            )
+            .unwrap()
+            .id();
+        let revision = g.revision();
+
+        g.set_node_ntd(&a, Ntd::Two, &mut console).unwrap();
+        assert_eq!(g.revision(), revision, "unchanged values should not count");
+        g.set_node_ntd(&a, Ntd::Five, &mut console).unwrap();
+        assert_eq!(
+            g.node(&a).unwrap().gate().as_gate(),
+            Gate::Delay { ticks: Ntd::Five }
+        );
+        assert_ne!(g.revision(), revision);
+
+        g.set_node_width(&a, 4, &mut console).unwrap();
+        g.node_mut(&a).unwrap().state = Bits(0b1111);
+        g.set_node_gate(&a, Gate::Split { bit: Ntd::Zero }, &mut console)
+            .unwrap();
+        assert_eq!(
+            g.node(&a).unwrap().bits(),
+            Bits::ONE,
+            "bits the new gate does not output should be turned off"
+        );
+        g.set_node_ntd(&a, Ntd::Five, &mut console).unwrap();
+        g.set_node_gate(&a, Gate::Or, &mut console).unwrap();
+        g.set_node_ntd(&a, Ntd::One, &mut console).unwrap();
+        assert_eq!(g.node(&a).unwrap().gate().as_gate(), Gate::Or);
+        assert!(
+            g.set_node_gate(&NodeId(99), Gate::Or, &mut console)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_set_nodes_gate_and_ntd() {
+        use crate::graph::node::Ntd;
+
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let mut node = |gate, x| {
+            *g.create_node(gate, IVec2::new(x, 0), &mut console)
+                .unwrap()
+                .id()
+        };
+        let a = node(
+            Gate::Resistor {
+                resistance: Ntd::One,
+            },
+            0,
+        );
+        let b = node(
+            Gate::Resistor {
+                resistance: Ntd::Five,
+            },
+            8,
+        );
+        let c = node(Gate::Or, 16);
+        let revision = g.revision();
+
+        assert_eq!(g.set_nodes_ntd([&a, &b, &c], Ntd::Five, &mut console), 1);
+        assert_eq!(
+            g.node(&a).unwrap().gate().as_gate(),
+            Gate::Resistor {
+                resistance: Ntd::Five
+            }
+        );
+        assert_eq!(g.node(&c).unwrap().gate().as_gate(), Gate::Or);
+        assert_ne!(g.revision(), revision);
+
+        let revision = g.revision();
+        assert_eq!(
+            g.set_nodes_gate([&a, &b, &c], GateId::Delay, &mut console),
+            3
+        );
+        assert_eq!(
+            g.revision(),
+            revision + 1,
+            "replacing several gates should be one change"
+        );
+        assert_eq!(
+            g.node(&b).unwrap().gate().as_gate(),
+            Gate::Delay { ticks: Ntd::Five },
+            "NTD values should be kept"
+        );
+        assert_eq!(
+            g.set_nodes_gate([&a, &b, &c], GateId::Delay, &mut console),
+            0
+        );
+        assert_eq!(g.revision(), revision + 1);
+    }
+
+    #[test]
+    fn test_replace_nodes_gate() {
+        use crate::graph::node::Ntd;
+
+        let (mut g, [a, b]) = test_graph! {
+            {Or} a;
+            {Nor} b;
+            a -> b;
+            [({a}), ({b})];
+        };
+        let mut console = test_console();
+        let gate = Gate::Led { color: Ntd::Three };
+        let replaced = g.replace_nodes_gate([&a, &b], gate, &mut console);
+        assert_eq!(replaced, [(a, Gate::Or), (b, Gate::Nor)]);
+        assert_eq!(g.node(&b).unwrap().gate().as_gate(), gate);
+        assert_eq!(g.wires_iter().len(), 1, "wires should be kept");
+        assert!(g.replace_nodes_gate([&a], gate, &mut console).is_empty());
+
+        assert_eq!(
+            g.replace_gates(replaced, &mut console),
+            [(a, gate), (b, gate)],
+            "passing the old gates back should undo the replacement"
+        );
+        assert_eq!(g.node(&a).unwrap().gate().as_gate(), Gate::Or);
+        assert_eq!(g.node(&b).unwrap().gate().as_gate(), Gate::Nor);
+    }
+
+    #[test]
+    fn test_off_grid_nodes() {
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let a = *g
+            .create_node(Gate::Or, IVec2::new(4, -4), &mut console)
+            .unwrap()
+            .id();
+        assert_eq!(
+            g.find_node_at(IVec2::new(11, 3)),
+            Some(&a),
+            "nodes should be found from every cell they cover"
+        );
+        assert_eq!(g.find_node_at(IVec2::new(3, 0)), None);
+        assert!(
+            g.create_node(Gate::Or, IVec2::new(8, 0), &mut console)
+                .is_err(),
+            "nodes should not share the cell holding their centers"
+        );
+        let b = *g
+            .create_node(Gate::Or, IVec2::new(12, 0), &mut console)
+            .unwrap()
+            .id();
+
+        g.translate_node(&a, IVec2::new(5, -3), &mut console)
+            .unwrap();
+        assert_eq!(
+            g.node(&a).unwrap().position(),
+            IVec2::new(5, -3),
+            "moves within a cell should still move the node"
+        );
+        g.translate_node(&a, IVec2::new(13, 0), &mut console)
+            .unwrap();
+        assert_eq!(
+            g.node(&a).unwrap().position(),
+            IVec2::new(5, -3),
+            "nodes should not be moved into an occupied cell"
+        );
+        assert_eq!(g.find_node_at(IVec2::new(19, 7)), Some(&b));
+    }
+
+    #[test]
+    fn test_model_off_grid_positions() {
+        let mut console = test_console();
+        let mut g = Graph::new(GraphId(0));
+        let a = *g
+            .create_node(Gate::Or, IVec2::new(4, -4), &mut console)
+            .unwrap()
+            .id();
+        let b = *g
+            .create_node(Gate::Nor, IVec2::new(-24, -16), &mut console)
+            .unwrap()
+            .id();
+        let model = model::GraphModel::from(&g);
+
+        let mut h = Graph::new(GraphId(1));
+        let pasted = model.paste(&mut h, IVec2::zero(), &mut console);
+        assert_eq!(
+            pasted
+                .iter()
+                .map(|id| h.node(id).unwrap().position())
+                .collect::<Vec<_>>(),
+            [a, b].map(|id| g.node(&id).unwrap().position()),
+            "positions should be kept as they are on the same grid"
+        );
+
+        let mut h = Graph::new(GraphId(2));
+        h.set_grid_size(16);
+        let pasted = model.paste(&mut h, IVec2::zero(), &mut console);
+        assert_eq!(
+            pasted
+                .iter()
+                .map(|id| h.node(id).unwrap().position())
+                .collect::<Vec<_>>(),
+            [IVec2::new(8, -8), IVec2::new(-48, -32)],
+            "positions should be scaled with their offsets from the grid"
+        );
+    }
+
+    #[test]
+    fn test_scratch_not_in_workspace() {
+        let mut graphs = GraphList::new();
+        let a = Arc::downgrade(graphs.create_graph());
+        let scratch = Arc::downgrade(graphs.create_scratch_graph());
+        assert_eq!(graphs.len(), 2);
+        let workspace = graphs.workspace().collect::<Vec<_>>();
+        assert_eq!(workspace.len(), 1);
+        assert!(a.ptr_eq(&Arc::downgrade(workspace[0])));
+
+        graphs.retain(|g| !scratch.ptr_eq(&Arc::downgrade(g)));
+        assert_eq!(graphs.workspace().count(), 1);
+    }
+
+    #[test]
+    fn test_graph_list_lookup() {
+        let mut graphs = GraphList::new();
+        let [a, b, c] = std::array::from_fn(|_| *graphs.create_graph().read().unwrap().id());
+        let graph_b = graphs.get(&b).unwrap().clone();
+        let lock = graph_b.write().unwrap();
+        assert_eq!(
+            graphs.try_read(&b).err(),
+            Some(GraphAccessError::Busy(b)),
+            "a graph being written should not block lookups"
+        );
+        assert_eq!(graphs.try_read(&c).unwrap().id(), &c);
+        drop(lock);
+
+        graphs.retain(|g| g.read().unwrap().id() != &a);
+        assert_eq!(
+            graphs.try_read(&a).err(),
+            Some(GraphAccessError::Missing(a))
+        );
+        assert_eq!(graphs.try_write(&b).unwrap().id(), &b);
+        assert_eq!(graphs.try_read(&c).unwrap().id(), &c);
+        assert_eq!(
+            graphs
+                .iter()
+                .map(|g| *g.read().unwrap().id())
+                .collect::<Vec<_>>(),
+            [b, c],
+            "graphs should stay in the order they were added"
+        );
+    }
+
+    #[test]
+    fn test_bench_graph_is_reproducible() {
+        let edges = |g: &Graph| {
+            let mut edges = g
+                .edges()
+                .map(|(_, src, dst)| (src.0, dst.0))
+                .collect::<Vec<_>>();
+            edges.sort_unstable();
+            edges
+        };
+        let a = bench::random_graph(64, 7);
+        let b = bench::random_graph(64, 7);
+        assert_eq!(a.nodes.len(), 64);
+        assert!(!a.wires.is_empty());
+        assert_eq!(
+            edges(&a),
+            edges(&b),
+            "the same seed should give the same wires"
+        );
+
+        let report = bench::bench_eval(64, 10);
+        assert_eq!(report.nodes, 64);
+        assert_eq!(report.ticks, 10);
+    }
 }
@@ -0,0 +1,73 @@
+use crate::ivec::IVec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AnnotationId(pub(super) u128);
+
+/// Defaults to [`Self::INVALID`]
+impl Default for AnnotationId {
+    fn default() -> Self {
+        Self::INVALID
+    }
+}
+
+impl std::fmt::Display for AnnotationId {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "a{:x}", self.0)
+    }
+}
+
+impl std::str::FromStr for AnnotationId {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_prefix('a')
+            .ok_or(())
+            .and_then(|x| u128::from_str_radix(x, 16).map_err(|_| ()))
+            .map(Self)
+    }
+}
+
+impl AnnotationId {
+    pub const INVALID: Self = Self(!0);
+
+    /// Returns the current value and increments `self`.
+    /// Returns [`None`] if [`Self::INVALID`] would have been returned.
+    /// Does not increment if `self` is [`Self::INVALID`].
+    #[inline]
+    pub const fn step(&mut self) -> Option<Self> {
+        const INVALID: AnnotationId = AnnotationId::INVALID;
+        match *self {
+            INVALID => None,
+            id => {
+                self.0 += 1;
+                Some(id)
+            }
+        }
+    }
+}
+
+/// Free text placed in the editor. It is never evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    id: AnnotationId,
+    /// Top-left corner of the text, in world units
+    pub(super) position: IVec2,
+    pub text: String,
+}
+
+impl Annotation {
+    pub const fn new(id: AnnotationId, position: IVec2, text: String) -> Self {
+        Self { id, position, text }
+    }
+
+    #[inline]
+    pub const fn id(&self) -> &AnnotationId {
+        &self.id
+    }
+
+    #[inline]
+    pub const fn position(&self) -> IVec2 {
+        self.position
+    }
+}
@@ -0,0 +1,121 @@
+//! Timing evaluation on large random graphs, so that changes to [`Graph::evaluate`] and
+//! [`Graph::refresh_eval_order`] can be compared.
+//!
+//! The graphs come from a fixed seed so that runs on different builds time the same graph.
+
+use super::{
+    Graph, GraphId,
+    jitter::Rng,
+    node::{Bits, Gate, Node, NodeId, Ntd},
+    wire::{Elbow, Wire, WireId},
+};
+use crate::ivec::IVec2;
+use rustc_hash::FxHashSet;
+use std::time::{Duration, Instant};
+
+/// Seed of every benchmark graph.
+pub const BENCH_SEED: u64 = 0x00ea_be4c;
+
+/// Most inputs wired to each node of a benchmark graph.
+const MAX_INPUTS: usize = 3;
+
+/// Times the eval order is refreshed, keeping the fastest so that a stall doesn't count.
+const REFRESH_RUNS: usize = 5;
+
+/// Gates of benchmark graphs, in proportion; ICs are left out since they need other graphs.
+const GATES: [Gate; 8] = [
+    Gate::Or,
+    Gate::Or,
+    Gate::And,
+    Gate::Nor,
+    Gate::Nor,
+    Gate::Xor,
+    Gate::Battery,
+    Gate::Delay { ticks: Ntd::One },
+];
+
+/// A graph of `nodes` nodes in a square, each wired from up to [`MAX_INPUTS`] others picked at
+/// random, cycles included. The same `seed` always gives the same graph.
+pub fn random_graph(nodes: usize, seed: u64) -> Graph {
+    let mut rng = Rng::new(seed);
+    let mut graph = Graph::new(GraphId::INVALID);
+    let grid_size = i32::from(graph.grid_size);
+    let side = nodes.isqrt().max(1);
+    let ids = (0..nodes)
+        .map(|n| {
+            let id = graph.next_node_id.step().expect("out of IDs");
+            let position = IVec2::new((n % side) as i32 * grid_size, (n / side) as i32 * grid_size);
+            let gate = GATES[rng.below(GATES.len())];
+            graph
+                .node_grid
+                .insert(Graph::node_cell(position, graph.grid_size), id);
+            graph
+                .nodes
+                .insert(id, Node::new(id, gate, position, Bits::ZERO));
+            id
+        })
+        .collect::<Vec<NodeId>>();
+    if nodes > 1 {
+        let mut edges = FxHashSet::default();
+        for &dst in &ids {
+            for _ in 0..=rng.below(MAX_INPUTS) {
+                let src = ids[rng.below(nodes)];
+                if src != dst && edges.insert((src, dst)) {
+                    let id: WireId = graph.next_wire_id.step().expect("out of IDs");
+                    graph
+                        .wires
+                        .insert(id, Wire::new(id, Elbow::default(), src, dst));
+                }
+            }
+        }
+    }
+    graph.is_eval_order_dirty = true;
+    graph
+}
+
+/// Timings of one benchmark run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchReport {
+    pub nodes: usize,
+    pub wires: usize,
+    /// Fastest of [`REFRESH_RUNS`] refreshes of the eval order
+    pub refresh: Duration,
+    pub ticks: usize,
+    /// All `ticks` evaluations together
+    pub eval: Duration,
+}
+
+impl BenchReport {
+    /// Mean time of one evaluation.
+    #[inline]
+    pub fn per_tick(&self) -> Duration {
+        self.eval.div_f64(self.ticks.max(1) as f64)
+    }
+}
+
+/// Times refreshing the eval order of a [`random_graph`] of `nodes` nodes and evaluating it for
+/// `ticks` ticks.
+pub fn bench_eval(nodes: usize, ticks: usize) -> BenchReport {
+    let mut graph = random_graph(nodes, BENCH_SEED);
+    let refresh = (0..REFRESH_RUNS)
+        .map(|_| {
+            graph.is_eval_order_dirty = true;
+            let start = Instant::now();
+            graph.refresh_eval_order();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default();
+    let start = Instant::now();
+    for _ in 0..ticks {
+        graph.evaluate();
+    }
+    let eval = start.elapsed();
+    BenchReport {
+        nodes: graph.nodes.len(),
+        wires: graph.wires.len(),
+        refresh,
+        ticks,
+        eval,
+    }
+}
@@ -0,0 +1,88 @@
+//! A fixed-length packed set of bits, for storing one state per node.
+
+/// `len` bits packed into words, all initially off.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Box<[u64]>,
+    len: usize,
+}
+
+impl BitSet {
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)].into_boxed_slice(),
+            len,
+        }
+    }
+
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// # Panics
+    ///
+    /// If `n` is out of bounds.
+    #[inline]
+    pub fn get(&self, n: usize) -> bool {
+        assert!(
+            n < self.len,
+            "bit {n} out of bounds for length {}",
+            self.len
+        );
+        self.words[n / 64] & (1 << (n % 64)) != 0
+    }
+
+    /// # Panics
+    ///
+    /// If `n` is out of bounds.
+    #[inline]
+    pub fn set(&mut self, n: usize, value: bool) {
+        assert!(
+            n < self.len,
+            "bit {n} out of bounds for length {}",
+            self.len
+        );
+        let mask = 1 << (n % 64);
+        if value {
+            self.words[n / 64] |= mask;
+        } else {
+            self.words[n / 64] &= !mask;
+        }
+    }
+
+    /// Turns every bit off, keeping the length.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = bool> + '_ {
+        (0..self.len).map(|n| self.get(n))
+    }
+}
+
+impl FromIterator<bool> for BitSet {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut words = Vec::new();
+        let mut len = 0;
+        for value in iter {
+            if len % 64 == 0 {
+                words.push(0);
+            }
+            if value {
+                *words.last_mut().unwrap() |= 1 << (len % 64);
+            }
+            len += 1;
+        }
+        Self {
+            words: words.into_boxed_slice(),
+            len,
+        }
+    }
+}
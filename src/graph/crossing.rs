@@ -0,0 +1,238 @@
+//! Where wires cross or meet, so that crossings can be drawn hopping over each other and
+//! junctions can be drawn with a dot.
+//!
+//! Only wires passing through the part of the graph in view are looked at, and segments are
+//! bucketed into cells so that only segments sharing a cell are tested against each other.
+
+use super::{
+    Graph,
+    node::NodeId,
+    wire::{Elbow, WireEnd, WireId},
+};
+use raylib::prelude::*;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// Radius of a hop, relative to the grid size.
+pub const HOP_RADIUS: f32 = 0.375;
+/// Radius of a junction dot, relative to the grid size.
+pub const JUNCTION_RADIUS: f32 = 0.2;
+/// Width of the cells segments are bucketed into, relative to the grid size.
+const BUCKET_CELLS: f32 = 4.0;
+/// Crossings at a shallower angle than this (30°) are left alone, since a hop couldn't clear
+/// the other wire.
+const MIN_HOP_SINE: f32 = 0.5;
+
+/// Two unrelated wires crossing, with `over` drawn hopping across `under`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hop {
+    pub pos: Vector2,
+    pub over: WireId,
+    /// Direction of `over` at the crossing, pointing right, or up if `over` is vertical
+    pub over_dir: Vector2,
+    pub under: WireId,
+    /// Direction of `under` at the crossing
+    pub under_dir: Vector2,
+}
+
+/// A side of a node that more than one wire leaves or reaches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Junction {
+    pub pos: Vector2,
+    /// Whether any of the wires meeting there is on
+    pub active: bool,
+}
+
+/// A straight piece of the path of a wire.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    /// Index of the wire in the list of paths
+    path: usize,
+    start: Vector2,
+    end: Vector2,
+}
+
+/// The path of a wire in view.
+#[derive(Debug)]
+struct Path {
+    id: WireId,
+    src: NodeId,
+    dst: NodeId,
+    /// Points the wire turns sharply at, ends included; hops too close to these would leave the
+    /// wire.
+    corners: Vec<Vector2>,
+}
+
+/// Where the segments `a` and `b` cross, away from the ends of either; [`None`] if they don't.
+fn intersect(a: &Segment, b: &Segment) -> Option<Vector2> {
+    let r = a.end - a.start;
+    let s = b.end - b.start;
+    let denom = r.x * s.y - r.y * s.x;
+    if denom == 0.0 {
+        return None;
+    }
+    let qp = b.start - a.start;
+    let t = (qp.x * s.y - qp.y * s.x) / denom;
+    let u = (qp.x * r.y - qp.y * r.x) / denom;
+    (t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0).then(|| a.start + r * t)
+}
+
+/// Smallest rectangle holding both ends of a segment.
+fn segment_bounds(start: Vector2, end: Vector2) -> Rectangle {
+    Rectangle::new(
+        start.x.min(end.x),
+        start.y.min(end.y),
+        (start.x - end.x).abs(),
+        (start.y - end.y).abs(),
+    )
+}
+
+impl Graph {
+    /// Every crossing of two wires sharing no node within `view`, leaving out ones closer than
+    /// `radius` to a corner or end of either wire.
+    ///
+    /// Of the two wires, the one running closer to horizontal hops over the other.
+    pub fn find_hops(&self, offset: Vector2, view: Rectangle, radius: f32) -> Vec<Hop> {
+        let cell_size = f32::from(self.grid_size) * BUCKET_CELLS;
+        let cell_of = |pos: Vector2| {
+            (
+                (pos.x / cell_size).floor() as i32,
+                (pos.y / cell_size).floor() as i32,
+            )
+        };
+        let mut paths = Vec::new();
+        let mut segments = Vec::new();
+        for wire in self.wires_iter() {
+            let Some(path) = wire.path(self, offset) else {
+                continue;
+            };
+            let first = segments.len();
+            segments.extend(
+                path.windows(2)
+                    .filter(|pair| pair[0] != pair[1])
+                    .filter(|pair| segment_bounds(pair[0], pair[1]).check_collision_recs(&view))
+                    .map(|pair| Segment {
+                        path: paths.len(),
+                        start: pair[0],
+                        end: pair[1],
+                    }),
+            );
+            if segments.len() > first {
+                paths.push(Path {
+                    id: *wire.id(),
+                    src: *wire.src(),
+                    dst: *wire.dst(),
+                    // the middle of a straight wire or curve is not a corner
+                    corners: if wire.route().is_none()
+                        && matches!(wire.elbow, Elbow::Straight | Elbow::Curve)
+                    {
+                        vec![path[0], path[path.len() - 1]]
+                    } else {
+                        path
+                    },
+                });
+            }
+        }
+
+        // only the part of each segment in view is bucketed, so that long wires running far
+        // out of view don't fill the map
+        let view_min = cell_of(Vector2::new(view.x, view.y));
+        let view_max = cell_of(Vector2::new(view.x + view.width, view.y + view.height));
+        let mut buckets = FxHashMap::<(i32, i32), Vec<usize>>::default();
+        for (n, segment) in segments.iter().enumerate() {
+            let (min_x, min_y) = cell_of(Vector2::new(
+                segment.start.x.min(segment.end.x),
+                segment.start.y.min(segment.end.y),
+            ));
+            let (max_x, max_y) = cell_of(Vector2::new(
+                segment.start.x.max(segment.end.x),
+                segment.start.y.max(segment.end.y),
+            ));
+            for x in min_x.max(view_min.0)..=max_x.min(view_max.0) {
+                for y in min_y.max(view_min.1)..=max_y.min(view_max.1) {
+                    buckets.entry((x, y)).or_default().push(n);
+                }
+            }
+        }
+
+        let mut pairs = FxHashSet::default();
+        for bucket in buckets.values() {
+            for (i, &a) in bucket.iter().enumerate() {
+                for &b in &bucket[i + 1..] {
+                    let (path_a, path_b) = (&paths[segments[a].path], &paths[segments[b].path]);
+                    let related = path_a.src == path_b.src
+                        || path_a.src == path_b.dst
+                        || path_a.dst == path_b.src
+                        || path_a.dst == path_b.dst;
+                    if !related {
+                        pairs.insert((a.min(b), a.max(b)));
+                    }
+                }
+            }
+        }
+        let mut pairs = pairs.into_iter().collect::<Vec<_>>();
+        pairs.sort_unstable();
+
+        pairs
+            .into_iter()
+            .filter_map(|(a, b)| {
+                let (a, b) = (&segments[a], &segments[b]);
+                let pos = intersect(a, b)?;
+                let dir_a = (a.end - a.start).normalized();
+                let dir_b = (b.end - b.start).normalized();
+                let clear = |path: &Path| path.corners.iter().all(|c| c.distance_to(pos) >= radius);
+                let (path_a, path_b) = (&paths[a.path], &paths[b.path]);
+                if (dir_a.x * dir_b.y - dir_a.y * dir_b.x).abs() < MIN_HOP_SINE
+                    || !view.check_collision_point_rec(pos)
+                    || !clear(path_a)
+                    || !clear(path_b)
+                {
+                    return None;
+                }
+                let a_over = match dir_a.x.abs().total_cmp(&dir_b.x.abs()) {
+                    std::cmp::Ordering::Equal => path_a.id.0 > path_b.id.0,
+                    ord => ord.is_gt(),
+                };
+                let ((over, over_dir), (under, under_dir)) = if a_over {
+                    ((path_a.id, dir_a), (path_b.id, dir_b))
+                } else {
+                    ((path_b.id, dir_b), (path_a.id, dir_a))
+                };
+                let over_dir = if over_dir.x < 0.0 || (over_dir.x == 0.0 && over_dir.y > 0.0) {
+                    -over_dir
+                } else {
+                    over_dir
+                };
+                Some(Hop {
+                    pos,
+                    over,
+                    over_dir,
+                    under,
+                    under_dir,
+                })
+            })
+            .collect()
+    }
+
+    /// Every side of a node within `view` that two or more wires leave or reach.
+    pub fn find_junctions(&self, offset: Vector2, view: Rectangle) -> Vec<Junction> {
+        let mut ends = FxHashMap::<(NodeId, WireEnd), (Vector2, usize, bool)>::default();
+        for wire in self.wires_iter() {
+            let Some([start, _, end]) = wire.points(self, offset) else {
+                continue;
+            };
+            let active = self.displayed_state(wire.src());
+            for (node, which, pos) in [
+                (wire.src(), WireEnd::Src, start),
+                (wire.dst(), WireEnd::Dst, end),
+            ] {
+                let entry = ends.entry((*node, which)).or_insert((pos, 0, false));
+                entry.1 += 1;
+                entry.2 |= active;
+            }
+        }
+        ends.into_iter()
+            .filter(|&(_, (pos, count, _))| count > 1 && view.check_collision_point_rec(pos))
+            .map(|(_, (pos, _, active))| Junction { pos, active })
+            .collect()
+    }
+}
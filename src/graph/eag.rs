@@ -1,10 +1,14 @@
 use crate::{
     graph::{
-        Graph, GraphId, GraphList,
-        node::{Node, NodeId},
+        EvalMode, Graph, GraphId, GraphList,
+        annotation::{Annotation, AnnotationId},
+        bitset::BitSet,
+        limits::FanLimits,
+        node::{Bits, Gate, GateInstance, Node, NodeId, Ntd, Orientation},
+        region::{Region, RegionId},
         wire::{Wire, WireId},
     },
-    ivec::IVec2,
+    ivec::{IBounds, IVec2},
 };
 use rustc_hash::{FxBuildHasher, FxHashMap};
 use serde::{
@@ -12,7 +16,7 @@ use serde::{
     ser::{Serialize, SerializeSeq, SerializeStruct, Serializer},
 };
 use serde_derive::Deserialize;
-use std::sync::{Arc, RwLock};
+use std::path::PathBuf;
 
 impl Serialize for Graph {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -29,7 +33,7 @@ impl Serialize for Graph {
                 let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
                 for node in self.0.values() {
                     seq.serialize_element(&(
-                        node.gate,
+                        node.gate.as_gate(),
                         (node.position.x, node.position.y),
                         node.state,
                     ))?;
@@ -61,7 +65,79 @@ impl Serialize for Graph {
             }
         }
 
-        let mut graph = serializer.serialize_struct("Graph", 2)?;
+        let node_notes = self
+            .nodes
+            .values()
+            .enumerate()
+            .filter_map(|(n, node)| Some((n, node.note()?)))
+            .collect::<Vec<_>>();
+        let node_names = self
+            .nodes
+            .values()
+            .enumerate()
+            .filter_map(|(n, node)| Some((n, node.name()?)))
+            .collect::<Vec<_>>();
+        let node_orientations = self
+            .nodes
+            .values()
+            .enumerate()
+            .filter(|(_, node)| node.orientation != Orientation::default())
+            .map(|(n, node)| (n, node.orientation))
+            .collect::<Vec<_>>();
+        let node_widths = self
+            .nodes
+            .values()
+            .enumerate()
+            .filter(|(_, node)| node.width != Node::default_width())
+            .map(|(n, node)| (n, node.width))
+            .collect::<Vec<_>>();
+        let ic_paths = self
+            .nodes
+            .values()
+            .enumerate()
+            .filter(|(_, node)| matches!(node.gate, GateInstance::Ic { .. }))
+            .filter_map(|(n, node)| Some((n, self.ic_path(node.id())?)))
+            .collect::<Vec<_>>();
+        let wire_notes = self
+            .wires
+            .values()
+            .enumerate()
+            .filter_map(|(n, wire)| Some((n, wire.note()?)))
+            .collect::<Vec<_>>();
+        let wire_routes = self
+            .wires
+            .values()
+            .enumerate()
+            .filter_map(|(n, wire)| {
+                let route = wire.route()?;
+                Some((n, route.iter().map(|c| (c.x, c.y)).collect::<Vec<_>>()))
+            })
+            .collect::<Vec<_>>();
+        let regions = self
+            .regions_ordered()
+            .into_iter()
+            .map(|region| {
+                let IBounds { min, max } = *region.bounds();
+                (
+                    (min.x, min.y),
+                    (max.x, max.y),
+                    region.label.as_str(),
+                    region.color,
+                )
+            })
+            .collect::<Vec<_>>();
+        let annotations = self
+            .annotations_ordered()
+            .into_iter()
+            .map(|annotation| {
+                let IVec2 { x, y } = annotation.position();
+                ((x, y), annotation.text.as_str())
+            })
+            .collect::<Vec<_>>();
+
+        let mut graph = serializer.serialize_struct("Graph", 13)?;
+        graph.serialize_field("eval_mode", &self.eval_mode)?;
+        graph.serialize_field("grid_size", &self.grid_size)?;
         graph.serialize_field("nodes", &Nodes(&self.nodes))?;
         graph.serialize_field(
             "wires",
@@ -74,12 +150,83 @@ impl Serialize for Graph {
                     .collect(),
             ),
         )?;
+        if node_notes.is_empty() {
+            graph.skip_field("node_notes")?;
+        } else {
+            graph.serialize_field("node_notes", &node_notes)?;
+        }
+        if node_names.is_empty() {
+            graph.skip_field("node_names")?;
+        } else {
+            graph.serialize_field("node_names", &node_names)?;
+        }
+        if node_orientations.is_empty() {
+            graph.skip_field("node_orientations")?;
+        } else {
+            graph.serialize_field("node_orientations", &node_orientations)?;
+        }
+        if node_widths.is_empty() {
+            graph.skip_field("node_widths")?;
+        } else {
+            graph.serialize_field("node_widths", &node_widths)?;
+        }
+        if ic_paths.is_empty() {
+            graph.skip_field("ic_paths")?;
+        } else {
+            graph.serialize_field("ic_paths", &ic_paths)?;
+        }
+        if wire_notes.is_empty() {
+            graph.skip_field("wire_notes")?;
+        } else {
+            graph.serialize_field("wire_notes", &wire_notes)?;
+        }
+        if wire_routes.is_empty() {
+            graph.skip_field("wire_routes")?;
+        } else {
+            graph.serialize_field("wire_routes", &wire_routes)?;
+        }
+        if regions.is_empty() {
+            graph.skip_field("regions")?;
+        } else {
+            graph.serialize_field("regions", &regions)?;
+        }
+        if annotations.is_empty() {
+            graph.skip_field("annotations")?;
+        } else {
+            graph.serialize_field("annotations", &annotations)?;
+        }
         graph.end()
     }
 }
 
+/// A gate as saved by any version, since delays were saved without a tick count before they
+/// had one.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SavedGate {
+    Current(Gate),
+    Legacy(LegacyGate),
+}
+
+#[derive(Debug, Deserialize)]
+enum LegacyGate {
+    /// Always delayed by one tick
+    #[serde(rename = ";")]
+    Delay,
+}
+
+impl From<SavedGate> for Gate {
+    #[inline]
+    fn from(value: SavedGate) -> Self {
+        match value {
+            SavedGate::Current(gate) => gate,
+            SavedGate::Legacy(LegacyGate::Delay) => Gate::Delay { ticks: Ntd::One },
+        }
+    }
+}
+
 #[derive(Debug)]
-struct Nodes(FxHashMap<NodeId, Node>, NodeId);
+pub(super) struct Nodes(pub(super) FxHashMap<NodeId, Node>, pub(super) NodeId);
 
 impl<'de> Deserialize<'de> for Nodes {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -105,9 +252,11 @@ impl<'de> Deserialize<'de> for Nodes {
                     .unwrap_or_default();
 
                 let mut next_node_id = NodeId(0);
-                while let Some((gate, (x, y), state)) = seq.next_element()? {
+                while let Some((gate, (x, y), state)) =
+                    seq.next_element::<(SavedGate, (i32, i32), Bits)>()?
+                {
                     let id = next_node_id.step().unwrap();
-                    value.insert(id, Node::new(id, gate, IVec2 { x, y }, state));
+                    value.insert(id, Node::new(id, gate.into(), IVec2 { x, y }, state));
                 }
                 Ok(Nodes(value, next_node_id))
             }
@@ -118,7 +267,7 @@ impl<'de> Deserialize<'de> for Nodes {
 }
 
 #[derive(Debug)]
-struct Wires(FxHashMap<WireId, Wire>, WireId);
+pub(super) struct Wires(pub(super) FxHashMap<WireId, Wire>, pub(super) WireId);
 
 impl<'de> Deserialize<'de> for Wires {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -158,30 +307,210 @@ impl<'de> Deserialize<'de> for Wires {
 
 #[derive(Debug, Deserialize)]
 pub struct GraphTemplate {
-    nodes: Nodes,
-    wires: Wires,
+    #[serde(default)]
+    pub(super) eval_mode: EvalMode,
+    /// Saves from before grid sizes were saved all used the default
+    #[serde(default = "crate::default_grid_size")]
+    pub(super) grid_size: u8,
+    pub(super) nodes: Nodes,
+    pub(super) wires: Wires,
+    /// `(node index, note)`
+    #[serde(default)]
+    pub(super) node_notes: Vec<(u128, String)>,
+    /// `(node index, name)`
+    #[serde(default)]
+    pub(super) node_names: Vec<(u128, String)>,
+    /// `(node index, orientation)`, for nodes not facing the default way
+    #[serde(default)]
+    pub(super) node_orientations: Vec<(u128, Orientation)>,
+    /// `(node index, width)`, for buses
+    #[serde(default)]
+    pub(super) node_widths: Vec<(u128, u8)>,
+    /// `(node index, path)`, for IC nodes of graphs that were saved
+    #[serde(default)]
+    pub(super) ic_paths: Vec<(u128, PathBuf)>,
+    /// `(wire index, note)`
+    #[serde(default)]
+    pub(super) wire_notes: Vec<(u128, String)>,
+    /// `(wire index, corners)`, for wires routed around nodes
+    #[serde(default)]
+    pub(super) wire_routes: Vec<(u128, Vec<(i32, i32)>)>,
+    /// `(min, max, label, color)`, oldest first
+    #[serde(default)]
+    pub(super) regions: Vec<((i32, i32), (i32, i32), String, Ntd)>,
+    /// `(position, text)`, oldest first
+    #[serde(default)]
+    pub(super) annotations: Vec<((i32, i32), String)>,
+}
+
+impl GraphTemplate {
+    /// Checks that the wires and nodes make a graph the editor can work with: every wire runs
+    /// between two different nodes that exist, no two wires run between the same nodes, and no
+    /// two nodes share a cell.
+    fn validate(&self, grid_size: u8) -> Result<(), String> {
+        let Nodes(nodes, _) = &self.nodes;
+        let Wires(wires, _) = &self.wires;
+        let mut cells = FxHashMap::default();
+        let mut node_ids = nodes.keys().collect::<Vec<_>>();
+        node_ids.sort_unstable_by_key(|id| id.0);
+        for id in node_ids {
+            let cell = Graph::node_cell(nodes[id].position, grid_size);
+            if let Some(other) = cells.insert(cell, id) {
+                return Err(format!(
+                    "nodes {} and {} are in the same cell",
+                    other.0, id.0
+                ));
+            }
+        }
+        let mut ends = FxHashMap::default();
+        let mut wire_ids = wires.keys().collect::<Vec<_>>();
+        wire_ids.sort_unstable_by_key(|id| id.0);
+        for id in wire_ids {
+            let wire = &wires[id];
+            for end in [wire.src, wire.dst] {
+                if !nodes.contains_key(&end) {
+                    return Err(format!(
+                        "wire {} connects node {}, which does not exist",
+                        id.0, end.0
+                    ));
+                }
+            }
+            if wire.src == wire.dst {
+                return Err(format!(
+                    "wire {} connects node {} to itself",
+                    id.0, wire.src.0
+                ));
+            }
+            if let Some(other) = ends.insert((wire.src, wire.dst), id) {
+                return Err(format!(
+                    "wires {} and {} both connect node {} to node {}",
+                    other.0, id.0, wire.src.0, wire.dst.0
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
-impl From<GraphTemplate> for Graph {
-    fn from(
-        GraphTemplate {
-            nodes: Nodes(nodes, next_node_id),
-            wires: Wires(wires, next_wire_id),
-        }: GraphTemplate,
-    ) -> Self {
-        Self {
+impl TryFrom<GraphTemplate> for Graph {
+    type Error = String;
+
+    fn try_from(template: GraphTemplate) -> Result<Self, Self::Error> {
+        // positions are divided by the grid size
+        let grid_size = template.grid_size.max(1);
+        template.validate(grid_size)?;
+        let GraphTemplate {
+            eval_mode,
+            grid_size: _,
+            nodes: Nodes(mut nodes, next_node_id),
+            wires: Wires(mut wires, next_wire_id),
+            node_notes,
+            node_names,
+            node_orientations,
+            node_widths,
+            ic_paths,
+            wire_notes,
+            wire_routes,
+            regions,
+            annotations,
+        } = template;
+        for (n, orientation) in node_orientations {
+            if let Some(node) = nodes.get_mut(&NodeId(n)) {
+                node.orientation = orientation;
+            }
+        }
+        for (n, width) in node_widths {
+            if let Some(node) = nodes.get_mut(&NodeId(n)) {
+                node.set_width(width);
+            }
+        }
+        for (n, note) in node_notes {
+            if let Some(node) = nodes.get_mut(&NodeId(n)) {
+                node.set_note(note);
+            }
+        }
+        for (n, name) in node_names {
+            if let Some(node) = nodes.get_mut(&NodeId(n)) {
+                node.set_name(name);
+            }
+        }
+        // graph IDs change every session, so ICs wait to be pointed at their graphs by path
+        for node in nodes.values_mut() {
+            if let GateInstance::Ic { graph } = &mut node.gate {
+                *graph = GraphId::INVALID;
+            }
+        }
+        let ic_paths = ic_paths
+            .into_iter()
+            .map(|(n, path)| (NodeId(n), path))
+            .filter(|(id, _)| nodes.contains_key(id))
+            .collect();
+        for (n, note) in wire_notes {
+            if let Some(wire) = wires.get_mut(&WireId(n)) {
+                wire.set_note(note);
+            }
+        }
+        for (n, route) in wire_routes {
+            if let Some(wire) = wires.get_mut(&WireId(n)) {
+                wire.set_route(Some(
+                    route.into_iter().map(|(x, y)| IVec2::new(x, y)).collect(),
+                ));
+            }
+        }
+        let mut next_region_id = RegionId(0);
+        let regions = regions
+            .into_iter()
+            .map(|((min_x, min_y), (max_x, max_y), label, color)| {
+                let id = next_region_id.step().unwrap();
+                let bounds = IBounds::new(IVec2::new(min_x, min_y), IVec2::new(max_x, max_y));
+                (id, Region::new(id, bounds, label, color))
+            })
+            .collect();
+        let mut next_annotation_id = AnnotationId(0);
+        let annotations = annotations
+            .into_iter()
+            .map(|((x, y), text)| {
+                let id = next_annotation_id.step().unwrap();
+                (id, Annotation::new(id, IVec2::new(x, y), text))
+            })
+            .collect();
+        Ok(Self {
             next_node_id,
             next_wire_id,
             id: GraphId(0),
             node_grid: nodes
                 .values()
-                .map(|node| (node.position, *node.id()))
+                .map(|node| (Self::node_cell(node.position, grid_size), *node.id()))
                 .collect(),
             nodes,
             wires,
+            next_region_id,
+            regions,
+            next_annotation_id,
+            annotations,
             eval_order: Vec::default(),
             is_eval_order_dirty: true,
-        }
+            eval_mode,
+            jitter: None,
+            states: Vec::default(),
+            next_states: Vec::default(),
+            eval_input_starts: Vec::default(),
+            eval_inputs: Vec::default(),
+            eval_output_starts: Vec::default(),
+            eval_outputs: Vec::default(),
+            eval_pending: BitSet::default(),
+            eval_always: BitSet::default(),
+            pending_revision: None,
+            fan_limits: FanLimits::NONE,
+            history: None,
+            trace: None,
+            ticks: 0,
+            changed_at: FxHashMap::default(),
+            revision: 0,
+            ics: FxHashMap::default(),
+            ic_paths,
+            grid_size,
+        })
     }
 }
 
@@ -190,8 +519,8 @@ impl Serialize for GraphList {
     where
         S: Serializer,
     {
-        let mut seq = serializer.serialize_seq(Some(self.graphs.len()))?;
-        for graph in &self.graphs {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for graph in self.iter() {
             seq.serialize_element(&*graph.read().unwrap())?;
         }
         seq.end()
@@ -216,16 +545,11 @@ impl<'de> Deserialize<'de> for GraphList {
             where
                 A: serde::de::SeqAccess<'de>,
             {
-                let mut graphs = seq.size_hint().map(Vec::with_capacity).unwrap_or_default();
-                let mut next_graph_id = GraphId(0);
-                while let Some(mut value) = seq.next_element::<Graph>()? {
-                    value.id = next_graph_id.step().unwrap();
-                    graphs.push(Arc::new(RwLock::new(value)));
+                let mut graphs = GraphList::new();
+                while let Some(value) = seq.next_element::<Graph>()? {
+                    graphs.insert_graph(value);
                 }
-                Ok(GraphList {
-                    graphs,
-                    next_graph_id,
-                })
+                Ok(graphs)
             }
         }
 
@@ -0,0 +1,158 @@
+//! Recording of node states over time, for replaying a circuit without re-simulating it.
+//!
+//! Each tick stores one bit per node, and the whole state of the buses that one bit can't
+//! hold. Frames refer to a shared [`FrameLayout`] so that the eval order only has to be stored
+//! again when it changes.
+
+use crate::graph::{
+    bitset::BitSet,
+    node::{Bits, NodeId},
+};
+use rustc_hash::FxHashMap;
+use std::{collections::VecDeque, sync::Arc};
+
+/// How many ticks a recording keeps before dropping the oldest.
+pub const HISTORY_CAPACITY: usize = 1024;
+
+/// Which bit of a frame belongs to which node.
+#[derive(Debug, PartialEq, Eq)]
+struct FrameLayout {
+    order: Vec<NodeId>,
+    index_of: FxHashMap<NodeId, usize>,
+}
+
+impl FrameLayout {
+    fn new(order: &[NodeId]) -> Self {
+        Self {
+            order: order.to_vec(),
+            index_of: order.iter().enumerate().map(|(n, id)| (*id, n)).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Frame {
+    layout: Arc<FrameLayout>,
+    /// Whether each node is on
+    states: BitSet,
+    /// `(index, state)` of the buses with a bit other than the lowest on, by index
+    buses: Box<[(usize, Bits)]>,
+}
+
+impl Frame {
+    /// Returns [`None`] if the node did not exist when the frame was recorded.
+    #[inline]
+    fn bits(&self, id: &NodeId) -> Option<Bits> {
+        let n = *self.layout.index_of.get(id)?;
+        Some(
+            match self.buses.binary_search_by_key(&n, |&(index, _)| index) {
+                Ok(i) => self.buses[i].1,
+                Err(_) => Bits::from(self.states.get(n)),
+            },
+        )
+    }
+}
+
+/// A bounded recording of every node's state after each evaluated tick.
+#[derive(Debug, Clone)]
+pub struct History {
+    /// Oldest first
+    frames: VecDeque<Frame>,
+    capacity: usize,
+    /// Frame being replayed, [`None`] while live
+    cursor: Option<usize>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(capacity.min(HISTORY_CAPACITY)),
+            capacity,
+            cursor: None,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Index of the frame being replayed, or [`None`] while showing the live states.
+    #[inline]
+    pub const fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    #[inline]
+    pub const fn is_replaying(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    /// Adds a frame with `states`, the state of each node in `order`, dropping the oldest frame if
+    /// full.
+    ///
+    /// Stops replaying, since the recording has moved on.
+    pub fn record(&mut self, order: &[NodeId], states: &[Bits]) {
+        debug_assert_eq!(order.len(), states.len(), "should have one state per node");
+        self.cursor = None;
+        if self.capacity == 0 {
+            return;
+        }
+        let layout = match self.frames.back() {
+            Some(frame) if frame.layout.order == order => frame.layout.clone(),
+            _ => Arc::new(FrameLayout::new(order)),
+        };
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(Frame {
+            layout,
+            states: states.iter().map(|state| state.any()).collect(),
+            buses: states
+                .iter()
+                .enumerate()
+                .filter(|(_, state)| state.0 > 1)
+                .map(|(n, state)| (n, *state))
+                .collect(),
+        });
+    }
+
+    /// The state of `id` in the frame being replayed.
+    ///
+    /// Returns [`None`] while live. Nodes that did not exist yet are replayed as off.
+    #[inline]
+    pub fn replayed_bits(&self, id: &NodeId) -> Option<Bits> {
+        self.cursor
+            .map(|n| self.frames[n].bits(id).unwrap_or(Bits::ZERO))
+    }
+
+    /// Replays `frame`, going live if it is the latest frame or beyond.
+    pub fn seek(&mut self, frame: usize) {
+        self.cursor = (frame + 1 < self.frames.len()).then_some(frame);
+    }
+
+    /// Moves the replay `delta` frames forward (or backward if negative), starting from the
+    /// latest frame if live.
+    pub fn step(&mut self, delta: isize) {
+        if let Some(latest) = self.frames.len().checked_sub(1) {
+            let from = self.cursor.unwrap_or(latest);
+            self.seek(from.saturating_add_signed(delta).min(latest));
+        }
+    }
+
+    /// Stops replaying and shows the live states again.
+    #[inline]
+    pub fn go_live(&mut self) {
+        self.cursor = None;
+    }
+}
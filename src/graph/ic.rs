@@ -0,0 +1,153 @@
+//! Nodes that run another graph as a subcircuit.
+//!
+//! Each IC node owns a private copy of the graph it refers to, so that several ICs of the same
+//! graph keep separate states. The copy's [interface](Graph::interface) is driven by the IC's
+//! inputs and makes up its outputs. Graphs without one use their inputless nodes as inputs and
+//! their outputless nodes as outputs, both ordered top to bottom, then left to right. A node only
+//! has one state, so the IC is on while any of its outputs is.
+
+use crate::graph::{
+    Graph, GraphAccessError, GraphId, GraphList,
+    bitset::BitSet,
+    model::GraphModel,
+    node::{Bits, NodeId},
+};
+use rustc_hash::FxHashMap;
+
+/// Why an IC node has no copy of its graph to run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IcError {
+    /// The graph is not in the graph list, or can't be read right now.
+    Unreadable(GraphAccessError),
+    /// The graph contains an IC of itself, directly or through other ICs.
+    Cycle(GraphId),
+}
+
+impl std::fmt::Display for IcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unreadable(e) => e.fmt(f),
+            Self::Cycle(id) => write!(f, "graph {id} would contain itself"),
+        }
+    }
+}
+
+/// A private copy of a graph, run one tick at a time by an IC node.
+#[derive(Debug)]
+pub struct IcInstance {
+    graph: Box<Graph>,
+    /// Eval order indices of the nodes driven by the IC's inputs
+    inputs: Vec<usize>,
+    /// Eval order indices of the nodes whose states make up the IC's state
+    outputs: Vec<usize>,
+    /// The eval order indices in `inputs`
+    driven: BitSet,
+    /// The revision of every graph copied into this instance, including by nested ICs
+    revisions: Vec<(GraphId, u64)>,
+}
+
+impl IcInstance {
+    /// Copies the graph `id` out of `graphs`, along with the graphs of any ICs inside it.
+    ///
+    /// `stack` holds the graphs that already contain this one, for detecting cycles.
+    pub fn new(graphs: &GraphList, id: GraphId, stack: &mut Vec<GraphId>) -> Result<Self, IcError> {
+        if stack.contains(&id) {
+            return Err(IcError::Cycle(id));
+        }
+        let source = graphs.try_read(&id).map_err(IcError::Unreadable)?;
+        let mut graph = GraphModel::from(&*source)
+            .to_graph(id)
+            .expect("a graph's own model should be valid");
+        graph.set_eval_mode(source.eval_mode());
+        let mut revisions = vec![(id, source.revision)];
+        drop(source);
+
+        graph.refresh_eval_order();
+        stack.push(id);
+        graph.build_ics(graphs, stack);
+        stack.pop();
+        for ic in graph.ics.values() {
+            // an IC that cannot run would make this one silently wrong, so it cannot run either
+            revisions.extend_from_slice(&ic.as_ref().map_err(|e| *e)?.revisions);
+        }
+
+        let index_of = graph
+            .eval_order
+            .iter()
+            .enumerate()
+            .map(|(n, id)| (*id, n))
+            .collect::<FxHashMap<NodeId, usize>>();
+        let (inputs, outputs) = graph.interface().unwrap_or_else(|| {
+            let in_reading_order = |mut ids: Vec<NodeId>| {
+                ids.sort_unstable_by_key(|id| {
+                    let position = graph.nodes[id].position;
+                    (position.y, position.x)
+                });
+                ids
+            };
+            (
+                in_reading_order(graph.inputless_nodes().collect()),
+                in_reading_order(graph.outputless_nodes().collect()),
+            )
+        });
+        let pins = |ids: Vec<NodeId>| ids.into_iter().map(|id| index_of[&id]).collect::<Vec<_>>();
+        let (inputs, outputs) = (pins(inputs), pins(outputs));
+        let mut driven = BitSet::new(graph.eval_order.len());
+        for &n in &inputs {
+            driven.set(n, true);
+        }
+
+        Ok(Self {
+            graph: Box::new(graph),
+            inputs,
+            outputs,
+            driven,
+            revisions,
+        })
+    }
+
+    /// The graph this is a copy of.
+    #[inline]
+    pub fn source(&self) -> GraphId {
+        *self.graph.id()
+    }
+
+    #[inline]
+    pub fn input_count(&self) -> usize {
+        self.inputs.len()
+    }
+
+    #[inline]
+    pub fn output_count(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// Whether every graph copied into this instance is unchanged since it was copied.
+    ///
+    /// Graphs that cannot be read right now are assumed to be unchanged, but removed graphs are
+    /// not.
+    pub fn is_current(&self, graphs: &GraphList) -> bool {
+        self.revisions
+            .iter()
+            .all(|&(id, revision)| match graphs.try_read(&id) {
+                Ok(graph) => graph.revision == revision,
+                Err(GraphAccessError::Missing(_)) => false,
+                Err(GraphAccessError::Busy(_) | GraphAccessError::Poisoned(_)) => true,
+            })
+    }
+
+    /// Runs the copy for one tick with `inputs` driving its input nodes, returning the IC's state.
+    ///
+    /// Missing inputs are off and extra inputs are ignored.
+    pub fn evaluate<I>(&mut self, inputs: I) -> bool
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        let inputs = inputs.into_iter().chain(std::iter::repeat(false));
+        for (&n, state) in self.inputs.iter().zip(inputs) {
+            self.graph.states[n] = Bits::from(state);
+        }
+        self.graph.evaluate_driven(Some(&self.driven));
+        self.outputs.iter().any(|&n| self.graph.states[n].any())
+    }
+}
@@ -0,0 +1,74 @@
+//! Randomized evaluation order, for finding circuits that only work because of the order their
+//! nodes happen to be evaluated in.
+//!
+//! The order comes from a seeded generator so that a run that misbehaves can be repeated.
+
+use std::time::SystemTime;
+
+/// SplitMix64, which is small and plenty random for shuffling.
+#[derive(Debug, Clone)]
+pub(super) struct Rng(u64);
+
+impl Rng {
+    #[inline]
+    pub(super) const fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(super) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, which must not be empty.
+    pub(super) fn below(&mut self, n: usize) -> usize {
+        debug_assert_ne!(n, 0, "range should not be empty");
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// A seed that differs between runs, for when the user doesn't pick one.
+pub fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos() as u64)
+}
+
+/// Shuffles the order nodes are evaluated in on every tick.
+#[derive(Debug, Clone)]
+pub struct Jitter {
+    seed: u64,
+    rng: Rng,
+    /// Eval order indices in the order of the current tick
+    order: Vec<usize>,
+}
+
+impl Jitter {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: Rng::new(seed),
+            order: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the indices `0..len` in a new random order.
+    pub fn shuffle(&mut self, len: usize) -> &[usize] {
+        self.order.clear();
+        self.order.extend(0..len);
+        // Fisher-Yates
+        for i in (1..len).rev() {
+            let j = self.rng.below(i + 1);
+            self.order.swap(i, j);
+        }
+        &self.order
+    }
+}
@@ -0,0 +1,107 @@
+//! Optional limits on how many wires may enter or leave each kind of gate, for designing with
+//! real hardware constraints in mind.
+//!
+//! Hard limits are enforced by [`Graph::create_wire`](super::Graph::create_wire); soft limits
+//! only mark the nodes that exceed them.
+
+use crate::graph::node::GateId;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FanLimit {
+    /// Wires that would give the gate more inputs than this are refused.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_in: Option<usize>,
+    /// Wires that would give the gate more outputs than this are refused.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_out: Option<usize>,
+    /// Gates with more inputs than this are marked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soft_in: Option<usize>,
+    /// Gates with more outputs than this are marked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soft_out: Option<usize>,
+}
+
+impl FanLimit {
+    pub const NONE: Self = Self {
+        max_in: None,
+        max_out: None,
+        soft_in: None,
+        soft_out: None,
+    };
+
+    #[inline]
+    pub fn exceeds_soft(&self, fan_in: usize, fan_out: usize) -> bool {
+        self.soft_in.is_some_and(|n| fan_in > n) || self.soft_out.is_some_and(|n| fan_out > n)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FanLimits {
+    pub or: FanLimit,
+    pub and: FanLimit,
+    pub nor: FanLimit,
+    pub xor: FanLimit,
+    pub resistor: FanLimit,
+    pub capacitor: FanLimit,
+    pub led: FanLimit,
+    pub delay: FanLimit,
+    pub battery: FanLimit,
+    pub ic: FanLimit,
+    pub split: FanLimit,
+    pub merge: FanLimit,
+    pub input: FanLimit,
+    pub output: FanLimit,
+    pub switch: FanLimit,
+    pub button: FanLimit,
+}
+
+impl FanLimits {
+    pub const NONE: Self = Self {
+        or: FanLimit::NONE,
+        and: FanLimit::NONE,
+        nor: FanLimit::NONE,
+        xor: FanLimit::NONE,
+        resistor: FanLimit::NONE,
+        capacitor: FanLimit::NONE,
+        led: FanLimit::NONE,
+        delay: FanLimit::NONE,
+        battery: FanLimit::NONE,
+        ic: FanLimit::NONE,
+        split: FanLimit::NONE,
+        merge: FanLimit::NONE,
+        input: FanLimit::NONE,
+        output: FanLimit::NONE,
+        switch: FanLimit::NONE,
+        button: FanLimit::NONE,
+    };
+}
+
+impl std::ops::Index<GateId> for FanLimits {
+    type Output = FanLimit;
+
+    #[inline]
+    fn index(&self, index: GateId) -> &Self::Output {
+        match index {
+            GateId::Or => &self.or,
+            GateId::And => &self.and,
+            GateId::Nor => &self.nor,
+            GateId::Xor => &self.xor,
+            GateId::Resistor => &self.resistor,
+            GateId::Capacitor => &self.capacitor,
+            GateId::Led => &self.led,
+            GateId::Delay => &self.delay,
+            GateId::Battery => &self.battery,
+            GateId::Ic => &self.ic,
+            GateId::Split => &self.split,
+            GateId::Merge => &self.merge,
+            GateId::Input => &self.input,
+            GateId::Output => &self.output,
+            GateId::Switch => &self.switch,
+            GateId::Button => &self.button,
+        }
+    }
+}
@@ -0,0 +1,330 @@
+//! Plain data representation of a [`Graph`] for tools that need to read or produce circuits
+//! without depending on the editor's internal bookkeeping.
+//!
+//! Nodes are listed in ID order and wires refer to nodes by their index in [`GraphModel::nodes`],
+//! so the same graph always produces the same model.
+
+use crate::{
+    DEFAULT_GRID_SIZE,
+    console::{Console, GraphRef, LogType},
+    graph::{
+        Graph, GraphId,
+        node::{Bits, Gate, Node, NodeId, Orientation},
+        wire::{Elbow, Wire, WireId},
+    },
+    ivec::IVec2,
+    logln,
+};
+use rustc_hash::{FxBuildHasher, FxHashMap, FxHashSet};
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeModel {
+    pub gate: Gate,
+    pub x: i32,
+    pub y: i32,
+    /// Every bit of a bus, saved as a bool for a single wire
+    #[serde(default)]
+    pub state: Bits,
+    #[serde(default)]
+    pub orientation: Orientation,
+    #[serde(default = "Node::default_width")]
+    pub width: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WireModel {
+    #[serde(default)]
+    pub elbow: Elbow,
+    pub src: usize,
+    pub dst: usize,
+    /// Corners of a wire routed around nodes, positioned like the nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<Vec<(i32, i32)>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphModel {
+    /// Grid size of the graph the model was taken from, which the positions are in
+    #[serde(default = "crate::default_grid_size")]
+    pub grid_size: u8,
+    #[serde(default)]
+    pub nodes: Vec<NodeModel>,
+    #[serde(default)]
+    pub wires: Vec<WireModel>,
+}
+
+impl Default for GraphModel {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            grid_size: DEFAULT_GRID_SIZE,
+            nodes: Vec::new(),
+            wires: Vec::new(),
+        }
+    }
+}
+
+impl From<&Graph> for GraphModel {
+    #[inline]
+    fn from(graph: &Graph) -> Self {
+        Self::capture(graph, graph.nodes.keys().copied().collect(), IVec2::zero())
+    }
+}
+
+impl GraphModel {
+    /// Models the nodes in `node_ids` and the wires between them, with positions relative to `origin`.
+    fn capture(graph: &Graph, mut node_ids: Vec<NodeId>, origin: IVec2) -> Self {
+        node_ids.sort_unstable_by_key(|id| id.0);
+        let index_of = node_ids
+            .iter()
+            .enumerate()
+            .map(|(n, id)| (*id, n))
+            .collect::<FxHashMap<NodeId, usize>>();
+
+        let nodes = node_ids
+            .iter()
+            .map(|id| {
+                let node = &graph.nodes[id];
+                NodeModel {
+                    gate: node.gate.as_gate(),
+                    x: node.position.x - origin.x,
+                    y: node.position.y - origin.y,
+                    state: node.state,
+                    orientation: node.orientation,
+                    width: node.width,
+                }
+            })
+            .collect();
+
+        let mut wires = graph
+            .wires
+            .values()
+            .filter_map(|wire| {
+                Some((
+                    wire.id().0,
+                    WireModel {
+                        elbow: wire.elbow,
+                        src: *index_of.get(&wire.src)?,
+                        dst: *index_of.get(&wire.dst)?,
+                        route: wire.route().map(|route| {
+                            route
+                                .iter()
+                                .map(|corner| (corner.x - origin.x, corner.y - origin.y))
+                                .collect()
+                        }),
+                    },
+                ))
+            })
+            .collect::<Vec<_>>();
+        wires.sort_unstable_by_key(|(id, _)| *id);
+        let wires = wires.into_iter().map(|(_, wire)| wire).collect();
+
+        Self {
+            grid_size: graph.grid_size,
+            nodes,
+            wires,
+        }
+    }
+
+    /// Models the selected nodes and only the wires whose ends are both selected.
+    ///
+    /// Positions are made relative to the top-left corner of the selection's bounding box.
+    /// IDs in `selection` that are not in `graph` are ignored.
+    pub fn from_selection(graph: &Graph, selection: &FxHashSet<NodeId>) -> Self {
+        let node_ids = selection
+            .iter()
+            .copied()
+            .filter(|id| graph.nodes.contains_key(id))
+            .collect::<Vec<_>>();
+        let origin = node_ids
+            .iter()
+            .map(|id| graph.nodes[id].position)
+            .reduce(|a, b| IVec2::new(a.x.min(b.x), a.y.min(b.y)))
+            .unwrap_or(IVec2::zero());
+        Self::capture(graph, node_ids, origin)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// A position in the model rescaled to `grid_size` and shifted by `offset`.
+    ///
+    /// Positions off the grid keep their offset from their cell, scaled like
+    /// [`Graph::set_grid_size`] does.
+    fn place(&self, (x, y): (i32, i32), grid_size: u8, offset: IVec2) -> IVec2 {
+        let from = self.grid_size.max(1);
+        let position = if from == grid_size {
+            IVec2::new(x, y)
+        } else {
+            Graph::rescale_position(IVec2::new(x, y), from, grid_size)
+        };
+        IVec2::new(position.x + offset.x, position.y + offset.y)
+    }
+
+    /// Positions of the nodes, in order, rescaled to `grid_size` and shifted by `offset`.
+    pub fn positions(&self, grid_size: u8, offset: IVec2) -> impl Iterator<Item = IVec2> {
+        self.nodes
+            .iter()
+            .map(move |model| self.place((model.x, model.y), grid_size, offset))
+    }
+
+    /// Creates a copy of the model in `graph` with every node moved by `offset` and returns the
+    /// IDs of the new nodes in model order.
+    ///
+    /// Positions are scaled from the model's grid size to the graph's. Nodes that would land on
+    /// an occupied cell are skipped, along with their wires.
+    pub fn paste(&self, graph: &mut Graph, offset: IVec2, console: &mut Console) -> Vec<NodeId> {
+        let grid_size = graph.grid_size;
+        let node_ids = self
+            .nodes
+            .iter()
+            .zip(self.positions(grid_size, offset))
+            .map(|(model, position)| {
+                graph
+                    .create_node(model.gate, position, console)
+                    .ok()
+                    .map(|node| {
+                        node.orientation = model.orientation;
+                        node.state = model.state;
+                        node.set_width(model.width);
+                        *node.id()
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        for model in &self.wires {
+            if let Some(&Some(src)) = node_ids.get(model.src)
+                && let Some(&Some(dst)) = node_ids.get(model.dst)
+                && src != dst
+                && let Ok(wire) = graph.create_wire(model.elbow, src, dst, console)
+                && let Some(route) = &model.route
+            {
+                wire.set_route(Some(
+                    route
+                        .iter()
+                        .map(|&corner| self.place(corner, grid_size, offset))
+                        .collect(),
+                ));
+            }
+        }
+
+        node_ids.into_iter().flatten().collect()
+    }
+
+    /// Builds a graph with fresh node and wire IDs.
+    ///
+    /// Returns [`None`] if a wire refers to a node index that does not exist, if a wire connects
+    /// a node to itself, or if two nodes occupy the same grid cell.
+    pub fn to_graph(&self, id: GraphId) -> Option<Graph> {
+        let mut graph = Graph::new(id);
+        graph.grid_size = self.grid_size.max(1);
+        graph.nodes.reserve(self.nodes.len());
+        let mut node_ids = Vec::with_capacity(self.nodes.len());
+        for model in &self.nodes {
+            let node_id = graph.next_node_id.step().expect("out of IDs");
+            let position = IVec2::new(model.x, model.y);
+            if graph
+                .node_grid
+                .insert(Graph::node_cell(position, graph.grid_size), node_id)
+                .is_some()
+            {
+                return None;
+            }
+            let mut node = Node::new(node_id, model.gate, position, model.state);
+            node.orientation = model.orientation;
+            node.set_width(model.width);
+            graph.nodes.insert(node_id, node);
+            node_ids.push(node_id);
+        }
+
+        let mut wires = FxHashMap::with_capacity_and_hasher(self.wires.len(), FxBuildHasher);
+        for model in &self.wires {
+            let src = *node_ids.get(model.src)?;
+            let dst = *node_ids.get(model.dst)?;
+            if src == dst {
+                return None;
+            }
+            let wire_id: WireId = graph.next_wire_id.step().expect("out of IDs");
+            let mut wire = Wire::new(wire_id, model.elbow, src, dst);
+            wire.set_route(
+                model
+                    .route
+                    .as_ref()
+                    .map(|route| route.iter().map(|&(x, y)| IVec2::new(x, y)).collect()),
+            );
+            wires.insert(wire_id, wire);
+        }
+        graph.wires = wires;
+        graph.is_eval_order_dirty = true;
+        Some(graph)
+    }
+}
+
+impl Graph {
+    /// Copies the nodes of `source` and the wires between them into this graph with fresh IDs,
+    /// its top-left node going to `position`. Returns the IDs of the new nodes.
+    ///
+    /// Whatever is left behind is logged: nodes that would land on an occupied cell and their
+    /// wires, as well as notes, names, regions, and annotations, which models don't carry.
+    pub fn merge(&mut self, source: &Graph, position: IVec2, console: &mut Console) -> Vec<NodeId> {
+        let model = GraphModel::from_selection(source, &source.nodes.keys().copied().collect());
+        let wire_count = self.wires.len();
+        let node_ids = model.paste(self, position, console);
+        let lost_nodes = model.nodes.len() - node_ids.len();
+        let lost_wires = model.wires.len() - (self.wires.len() - wire_count);
+        if lost_nodes > 0 {
+            logln!(
+                console,
+                LogType::Warning,
+                "left out {lost_nodes} nodes that would land on occupied cells, and {lost_wires} wires"
+            );
+        }
+        if model.grid_size != self.grid_size {
+            logln!(
+                console,
+                LogType::Info,
+                "rescaled from grid size {} to {}",
+                model.grid_size,
+                self.grid_size
+            );
+        }
+        let lost_text = source
+            .nodes
+            .values()
+            .map(|node| usize::from(node.note().is_some()) + usize::from(node.name().is_some()))
+            .chain(
+                source
+                    .wires
+                    .values()
+                    .map(|wire| usize::from(wire.note().is_some())),
+            )
+            .sum::<usize>();
+        if lost_text > 0 {
+            logln!(
+                console,
+                LogType::Info,
+                "left out {lost_text} notes and names"
+            );
+        }
+        if !source.regions.is_empty() || !source.annotations.is_empty() {
+            logln!(
+                console,
+                LogType::Info,
+                "left out {} regions and {} annotations",
+                source.regions.len(),
+                source.annotations.len()
+            );
+        }
+        logln!(
+            console,
+            LogType::Success,
+            "merged {} nodes into graph {}",
+            node_ids.len(),
+            GraphRef(self.id)
+        );
+        node_ids
+    }
+}
@@ -1,4 +1,8 @@
-use crate::ivec::IVec2;
+use crate::{
+    graph::{GraphId, wire::WireEnd},
+    ivec::IVec2,
+};
+use raylib::prelude::Vector2;
 use serde_derive::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
@@ -68,6 +72,13 @@ pub enum GateId {
     Led,
     Delay,
     Battery,
+    Ic,
+    Split,
+    Merge,
+    Input,
+    Output,
+    Switch,
+    Button,
 }
 
 impl std::fmt::Display for GateId {
@@ -83,6 +94,13 @@ impl std::fmt::Display for GateId {
             GateId::Led => "led",
             GateId::Delay => "delay",
             GateId::Battery => "battery",
+            GateId::Ic => "ic",
+            GateId::Split => "split",
+            GateId::Merge => "merge",
+            GateId::Input => "input",
+            GateId::Output => "output",
+            GateId::Switch => "switch",
+            GateId::Button => "button",
         }
         .fmt(f)
     }
@@ -103,12 +121,40 @@ impl std::str::FromStr for GateId {
             "led" => Ok(GateId::Led),
             "delay" => Ok(GateId::Delay),
             "battery" => Ok(GateId::Battery),
+            "ic" => Ok(GateId::Ic),
+            "split" => Ok(GateId::Split),
+            "merge" => Ok(GateId::Merge),
+            "input" => Ok(GateId::Input),
+            "output" => Ok(GateId::Output),
+            "switch" => Ok(GateId::Switch),
+            "button" => Ok(GateId::Button),
             _ => Err(()),
         }
     }
 }
 
 impl GateId {
+    /// Every gate, in declaration order (so `GateId::ALL[id as usize] == id`).
+    pub const ALL: [GateId; 16] = [
+        GateId::Or,
+        GateId::And,
+        GateId::Nor,
+        GateId::Xor,
+        GateId::Resistor,
+        GateId::Capacitor,
+        GateId::Led,
+        GateId::Delay,
+        GateId::Battery,
+        GateId::Ic,
+        GateId::Split,
+        GateId::Merge,
+        GateId::Input,
+        GateId::Output,
+        GateId::Switch,
+        GateId::Button,
+    ];
+
+    /// [`GateId::Ic`] gives an IC of [`GraphId::INVALID`], which is always off.
     #[inline]
     pub const fn to_gate(self, ntd: Ntd) -> Gate {
         match self {
@@ -119,8 +165,17 @@ impl GateId {
             GateId::Resistor => Gate::Resistor { resistance: ntd },
             GateId::Capacitor => Gate::Capacitor { capacity: ntd },
             GateId::Led => Gate::Led { color: ntd },
-            GateId::Delay => Gate::Delay,
+            GateId::Delay => Gate::delay(ntd),
             GateId::Battery => Gate::Battery,
+            GateId::Ic => Gate::Ic {
+                graph: GraphId::INVALID,
+            },
+            GateId::Split => Gate::Split { bit: ntd },
+            GateId::Merge => Gate::Merge { bit: ntd },
+            GateId::Input => Gate::Input { pin: ntd },
+            GateId::Output => Gate::Output { pin: ntd },
+            GateId::Switch => Gate::Switch { on: false },
+            GateId::Button => Gate::Button,
         }
     }
 }
@@ -289,24 +344,42 @@ pub enum Gate {
     #[serde(rename = "^")]
     Xor,
     #[serde(rename = ">")]
-    Resistor {
-        #[serde(flatten)]
-        resistance: Ntd,
-    },
+    Resistor { resistance: Ntd },
     #[serde(rename = "%")]
-    Capacitor {
-        #[serde(flatten)]
-        capacity: Ntd,
-    },
+    Capacitor { capacity: Ntd },
     #[serde(rename = "l")]
-    Led {
-        #[serde(flatten)]
-        color: Ntd,
-    },
+    Led { color: Ntd },
+    /// Gives its inputs as they were `ticks` ticks ago. Built with at least one tick by
+    /// [`Gate::delay`]; zero, only found in older saves, gives them immediately.
     #[serde(rename = ";")]
-    Delay,
+    Delay { ticks: Ntd },
     #[serde(rename = "T")]
     Battery,
+    /// Runs another graph as a subcircuit, see [`crate::graph::ic`].
+    #[serde(rename = "#")]
+    Ic { graph: GraphId },
+    /// Takes bit `bit` out of a bus as a single wire.
+    #[serde(rename = "s")]
+    Split { bit: Ntd },
+    /// Puts a single wire onto bit `bit` of a bus.
+    #[serde(rename = "m")]
+    Merge { bit: Ntd },
+    /// Pin `pin` of the inputs the graph takes when run as an IC, see [`Graph::interface`].
+    ///
+    /// [`Graph::interface`]: crate::graph::Graph::interface
+    #[serde(rename = "i")]
+    Input { pin: Ntd },
+    /// Pin `pin` of the outputs the graph gives when run as an IC, see [`Graph::interface`].
+    ///
+    /// [`Graph::interface`]: crate::graph::Graph::interface
+    #[serde(rename = "o")]
+    Output { pin: Ntd },
+    /// Stays on or off until toggled with the interact tool, ignoring its inputs.
+    #[serde(rename = "w")]
+    Switch { on: bool },
+    /// On only while held down with the interact tool, ignoring its inputs.
+    #[serde(rename = "b")]
+    Button,
 }
 
 impl std::fmt::Display for Gate {
@@ -319,8 +392,15 @@ impl std::fmt::Display for Gate {
             Gate::Resistor { resistance } => write!(f, "resistor.{resistance}"),
             Gate::Capacitor { capacity } => write!(f, "capacitor.{capacity}"),
             Gate::Led { color } => write!(f, "led.{color}"),
-            Gate::Delay => write!(f, "delay"),
+            Gate::Delay { ticks } => write!(f, "delay.{ticks}"),
             Gate::Battery => "battery".fmt(f),
+            Gate::Ic { graph } => write!(f, "ic.{graph}"),
+            Gate::Split { bit } => write!(f, "split.{bit}"),
+            Gate::Merge { bit } => write!(f, "merge.{bit}"),
+            Gate::Input { pin } => write!(f, "input.{pin}"),
+            Gate::Output { pin } => write!(f, "output.{pin}"),
+            Gate::Switch { on } => write!(f, "switch.{}", if on { "on" } else { "off" }),
+            Gate::Button => "button".fmt(f),
         }
     }
 }
@@ -335,14 +415,30 @@ impl std::str::FromStr for Gate {
             "nor" => Ok(Gate::Nor),
             "xor" => Ok(Gate::Xor),
             "battery" => Ok(Gate::Battery),
+            "button" => Ok(Gate::Button),
             _ => s
                 .split_once('.')
-                .and_then(|(name, value)| value.parse().ok().map(|val| (name, val)))
                 .and_then(|(name, value)| match name {
-                    "resistor" => Some(Gate::Resistor { resistance: value }),
-                    "capacitor" => Some(Gate::Capacitor { capacity: value }),
-                    "led" => Some(Gate::Led { color: value }),
-                    "delay" => Some(Gate::Delay),
+                    "resistor" => value
+                        .parse()
+                        .ok()
+                        .map(|resistance| Gate::Resistor { resistance }),
+                    "capacitor" => value
+                        .parse()
+                        .ok()
+                        .map(|capacity| Gate::Capacitor { capacity }),
+                    "led" => value.parse().ok().map(|color| Gate::Led { color }),
+                    "delay" => value.parse().ok().map(Gate::delay),
+                    "ic" => value.parse().ok().map(|graph| Gate::Ic { graph }),
+                    "split" => value.parse().ok().map(|bit| Gate::Split { bit }),
+                    "merge" => value.parse().ok().map(|bit| Gate::Merge { bit }),
+                    "input" => value.parse().ok().map(|pin| Gate::Input { pin }),
+                    "output" => value.parse().ok().map(|pin| Gate::Output { pin }),
+                    "switch" => match value {
+                        "on" => Some(Gate::Switch { on: true }),
+                        "off" => Some(Gate::Switch { on: false }),
+                        _ => None,
+                    },
                     _ => None,
                 })
                 .ok_or(()),
@@ -351,6 +447,17 @@ impl std::str::FromStr for Gate {
 }
 
 impl Gate {
+    /// A delay of `ticks` ticks, or of one tick for zero, so that delays always delay.
+    #[inline]
+    pub const fn delay(ticks: Ntd) -> Self {
+        Self::Delay {
+            ticks: match ticks {
+                Ntd::Zero => Ntd::One,
+                ticks => ticks,
+            },
+        }
+    }
+
     #[inline]
     pub const fn id(self) -> GateId {
         match self {
@@ -361,28 +468,59 @@ impl Gate {
             Gate::Resistor { .. } => GateId::Resistor,
             Gate::Capacitor { .. } => GateId::Capacitor,
             Gate::Led { .. } => GateId::Led,
-            Gate::Delay => GateId::Delay,
+            Gate::Delay { .. } => GateId::Delay,
             Gate::Battery => GateId::Battery,
+            Gate::Ic { .. } => GateId::Ic,
+            Gate::Split { .. } => GateId::Split,
+            Gate::Merge { .. } => GateId::Merge,
+            Gate::Input { .. } => GateId::Input,
+            Gate::Output { .. } => GateId::Output,
+            Gate::Switch { .. } => GateId::Switch,
+            Gate::Button => GateId::Button,
         }
     }
 
     #[inline]
     pub const fn ntd(self) -> Option<Ntd> {
         match self {
-            Self::Or | Self::And | Self::Nor | Self::Xor | Self::Delay | Self::Battery => None,
+            Self::Or
+            | Self::And
+            | Self::Nor
+            | Self::Xor
+            | Self::Battery
+            | Self::Ic { .. }
+            | Self::Switch { .. }
+            | Self::Button => None,
             Self::Resistor { resistance: n }
             | Self::Capacitor { capacity: n }
-            | Self::Led { color: n } => Some(n),
+            | Self::Led { color: n }
+            | Self::Delay { ticks: n }
+            | Self::Split { bit: n }
+            | Self::Merge { bit: n }
+            | Self::Input { pin: n }
+            | Self::Output { pin: n } => Some(n),
         }
     }
 
     #[inline]
     pub const fn with_ntd(self, value: Ntd) -> Self {
         match self {
-            Self::Or | Self::And | Self::Nor | Self::Xor | Self::Delay | Self::Battery => self,
+            Self::Or
+            | Self::And
+            | Self::Nor
+            | Self::Xor
+            | Self::Battery
+            | Self::Ic { .. }
+            | Self::Switch { .. }
+            | Self::Button => self,
             Self::Resistor { .. } => Self::Resistor { resistance: value },
             Self::Capacitor { .. } => Self::Capacitor { capacity: value },
             Self::Led { .. } => Self::Led { color: value },
+            Self::Delay { .. } => Self::delay(value),
+            Self::Split { .. } => Self::Split { bit: value },
+            Self::Merge { .. } => Self::Merge { bit: value },
+            Self::Input { .. } => Self::Input { pin: value },
+            Self::Output { .. } => Self::Output { pin: value },
         }
     }
 }
@@ -405,9 +543,34 @@ pub enum GateInstance {
         color: Ntd,
     },
     Delay {
-        prev: bool,
+        ticks: Ntd,
+        /// The inputs of recent ticks, one per bit with the latest in the lowest, so that
+        /// changing `ticks` reads from inputs that already happened
+        history: u16,
     },
     Battery,
+    Ic {
+        graph: GraphId,
+    },
+    Split {
+        bit: Ntd,
+    },
+    Merge {
+        bit: Ntd,
+    },
+    Input {
+        pin: Ntd,
+    },
+    Output {
+        pin: Ntd,
+    },
+    Switch {
+        on: bool,
+    },
+    Button {
+        /// Whether the interact tool is holding the button down, which is never saved
+        pressed: bool,
+    },
 }
 
 impl GateInstance {
@@ -424,8 +587,15 @@ impl GateInstance {
                 stored: Ntd::Zero,
             },
             Gate::Led { color } => Self::Led { color },
-            Gate::Delay => Self::Delay { prev: false },
+            Gate::Delay { ticks } => Self::Delay { ticks, history: 0 },
             Gate::Battery => Self::Battery,
+            Gate::Ic { graph } => Self::Ic { graph },
+            Gate::Split { bit } => Self::Split { bit },
+            Gate::Merge { bit } => Self::Merge { bit },
+            Gate::Input { pin } => Self::Input { pin },
+            Gate::Output { pin } => Self::Output { pin },
+            Gate::Switch { on } => Self::Switch { on },
+            Gate::Button => Self::Button { pressed: false },
         }
     }
 
@@ -442,63 +612,389 @@ impl GateInstance {
                 stored: _,
             } => Gate::Capacitor { capacity },
             Self::Led { color } => Gate::Led { color },
-            Self::Delay { prev: _ } => Gate::Delay {},
+            Self::Delay { ticks, history: _ } => Gate::Delay { ticks },
             Self::Battery => Gate::Battery {},
+            Self::Ic { graph } => Gate::Ic { graph },
+            Self::Split { bit } => Gate::Split { bit },
+            Self::Merge { bit } => Gate::Merge { bit },
+            Self::Input { pin } => Gate::Input { pin },
+            Self::Output { pin } => Gate::Output { pin },
+            Self::Switch { on } => Gate::Switch { on },
+            Self::Button { pressed: _ } => Gate::Button {},
+        }
+    }
+
+    /// Changes the gate's NTD value, keeping as much of its state as still fits.
+    pub fn set_ntd(&mut self, value: Ntd) {
+        match self {
+            Self::Or
+            | Self::And
+            | Self::Nor
+            | Self::Xor
+            | Self::Battery
+            | Self::Ic { .. }
+            | Self::Switch { .. }
+            | Self::Button { .. } => {}
+            Self::Resistor { resistance: n }
+            | Self::Led { color: n }
+            | Self::Split { bit: n }
+            | Self::Merge { bit: n }
+            | Self::Input { pin: n }
+            | Self::Output { pin: n } => {
+                *n = value;
+            }
+            Self::Delay { ticks, .. } => {
+                *ticks = value.max(Ntd::One);
+            }
+            Self::Capacitor { capacity, stored } => {
+                *capacity = value;
+                *stored = (*stored).min(value);
+            }
+        }
+    }
+
+    /// How many bits the gate outputs on a node `width` bits wide.
+    #[inline]
+    pub fn output_width(&self, width: u8) -> u8 {
+        match *self {
+            Self::Split { .. } => 1,
+            Self::Merge { bit } => width.max(u8::from(bit) + 1),
+            _ => width,
+        }
+    }
+
+    /// Whether the gate keeps state of its own between ticks, so that its output can change while
+    /// its inputs stay the same.
+    #[inline]
+    pub const fn is_time_dependent(&self) -> bool {
+        matches!(
+            self,
+            Self::Capacitor { .. } | Self::Delay { .. } | Self::Ic { .. }
+        )
+    }
+
+    /// Whether the gate is meant to be flipped or held by hand with the interact tool.
+    #[inline]
+    pub const fn is_interactive(&self) -> bool {
+        matches!(self, Self::Switch { .. } | Self::Button { .. })
+    }
+
+    /// Whether the gate holds a signal until a later tick instead of passing it on in the same
+    /// one. Zero-tick delays and capacitors that store nothing pass it on.
+    #[inline]
+    pub const fn holds_signal(&self) -> bool {
+        match self {
+            Self::Delay { ticks, .. } => !matches!(ticks, Ntd::Zero),
+            Self::Capacitor { capacity, .. } => !matches!(capacity, Ntd::Zero),
+            _ => false,
         }
     }
 
-    pub fn evaluate<I>(&mut self, inputs: I) -> bool
+    /// Gates that combine their inputs (Or, And, Nor, Xor) work on each bit of a bus on its own.
+    /// The rest, and any gate on a node one bit wide, treat an input as on if any of its bits are.
+    pub fn evaluate<I>(&mut self, inputs: I, width: u8) -> Bits
     where
-        I: IntoIterator<Item = bool>,
+        I: IntoIterator<Item = Bits>,
     {
-        let mut inputs = inputs.into_iter().peekable();
-        match *self {
-            GateInstance::Or | GateInstance::Led { .. } => inputs.any(|x| x),
-            GateInstance::And => inputs.peek().is_some() && inputs.all(|x| x),
-            GateInstance::Nor => !inputs.any(|x| x),
-            GateInstance::Xor => inputs.filter(|&x| x).count() == 1,
-            GateInstance::Resistor { resistance } => {
+        let is_single = width == 1 && !matches!(self, Self::Split { .. });
+        let mut inputs = inputs
+            .into_iter()
+            .map(|x| if is_single { Bits::from(x.any()) } else { x })
+            .peekable();
+        let output_width = self.output_width(width);
+        let output = match *self {
+            GateInstance::Or
+            | GateInstance::Led { .. }
+            | GateInstance::Input { .. }
+            | GateInstance::Output { .. } => inputs.fold(Bits::ZERO, |a, b| a | b),
+            GateInstance::And if inputs.peek().is_some() => {
+                inputs.fold(Bits::mask(width), |a, b| a & b)
+            }
+            GateInstance::And => Bits::ZERO,
+            GateInstance::Nor => !inputs.fold(Bits::ZERO, |a, b| a | b),
+            GateInstance::Xor => {
+                // bits that are on in at least one input, and in at least two
+                let (once, twice) = inputs.fold((Bits::ZERO, Bits::ZERO), |(once, twice), x| {
+                    (once | x, twice | (once & x))
+                });
+                once & !twice
+            }
+            GateInstance::Split { bit } => {
+                Bits::from(inputs.fold(Bits::ZERO, |a, b| a | b).get(u8::from(bit)))
+            }
+            GateInstance::Merge { bit } => Bits::from(inputs.any(|x| x.any())) << u8::from(bit),
+            GateInstance::Resistor { resistance } => Bits::from(
                 *inputs
-                    .map(Ntd::from)
+                    .map(|x| Ntd::from(x.any()))
                     .map(SaturatingNtd)
                     .sum::<SaturatingNtd>()
-                    > resistance
-            }
+                    > resistance,
+            ),
             GateInstance::Capacitor {
                 capacity,
                 ref mut stored,
             } => {
                 let total = *inputs
-                    .map(Ntd::from)
+                    .map(|x| Ntd::from(x.any()))
                     .map(SaturatingNtd)
                     .sum::<SaturatingNtd>();
                 *stored = (*stored + total).min(capacity);
-                total > Ntd::Zero || {
-                    *stored = stored.saturating_sub(Ntd::One);
-                    *stored > Ntd::Zero
+                Bits::from(
+                    total > Ntd::Zero || {
+                        *stored = stored.saturating_sub(Ntd::One);
+                        *stored > Ntd::Zero
+                    },
+                )
+            }
+            GateInstance::Delay {
+                ticks,
+                ref mut history,
+            } => {
+                let input = inputs.any(|x| x.any());
+                let output = match u8::from(ticks) {
+                    0 => input,
+                    n => (*history >> (n - 1)) & 1 != 0,
+                };
+                *history = (*history << 1) | u16::from(input);
+                Bits::from(output)
+            }
+            GateInstance::Battery => Bits::mask(width),
+            GateInstance::Switch { on } | GateInstance::Button { pressed: on } => {
+                if on {
+                    Bits::mask(width)
+                } else {
+                    Bits::ZERO
                 }
             }
-            GateInstance::Delay { ref mut prev } => std::mem::replace(prev, inputs.any(|x| x)),
-            GateInstance::Battery => true,
+            // evaluated by the graph, which owns the copy of the graph it runs
+            GateInstance::Ic { .. } => Bits::ZERO,
+        };
+        output.masked(output_width)
+    }
+}
+
+/// Which way a node faces. Outputs leave from the front and inputs arrive at the back.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub enum Orientation {
+    #[serde(rename = "N")]
+    North,
+    #[default]
+    #[serde(rename = "E")]
+    East,
+    #[serde(rename = "S")]
+    South,
+    #[serde(rename = "W")]
+    West,
+}
+
+impl std::fmt::Display for Orientation {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::North => "north",
+            Self::East => "east",
+            Self::South => "south",
+            Self::West => "west",
         }
+        .fmt(f)
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+impl Orientation {
+    pub const ALL: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    /// The next orientation clockwise.
+    #[inline]
+    pub const fn rotated_cw(self) -> Self {
+        match self {
+            Self::North => Self::East,
+            Self::East => Self::South,
+            Self::South => Self::West,
+            Self::West => Self::North,
+        }
+    }
+
+    /// Clockwise rotation from [`Self::East`], the way icons are drawn, in degrees.
+    #[inline]
+    pub const fn degrees(self) -> f32 {
+        match self {
+            Self::East => 0.0,
+            Self::South => 90.0,
+            Self::West => 180.0,
+            Self::North => 270.0,
+        }
+    }
+
+    /// Unit vector pointing out of the front of the node, in world space (y down).
+    #[inline]
+    pub const fn direction(self) -> Vector2 {
+        match self {
+            Self::North => Vector2::new(0.0, -1.0),
+            Self::East => Vector2::new(1.0, 0.0),
+            Self::South => Vector2::new(0.0, 1.0),
+            Self::West => Vector2::new(-1.0, 0.0),
+        }
+    }
+
+    /// Where the given end of a wire attaches, relative to the center of a node `grid_size` wide.
+    ///
+    /// A node is the [`WireEnd::Src`] of its outputs and the [`WireEnd::Dst`] of its inputs.
+    #[inline]
+    pub fn anchor(self, end: WireEnd, grid_size: u8) -> Vector2 {
+        let half = f32::from(grid_size) * 0.5;
+        match end {
+            WireEnd::Src => self.direction() * half,
+            WireEnd::Dst => self.direction() * -half,
+        }
+    }
+}
+
+/// The widest a bus can be.
+pub const MAX_BUS_WIDTH: u8 = 32;
+
+/// The state of each wire in a bus, with bit 0 in the lowest.
+/// A node one bit wide is the same as a single wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(from = "SavedBits", into = "SavedBits")]
+pub struct Bits(pub u32);
+
+/// [`Bits`] as saved: a bool while only the lowest bit can be on, as single wires were saved
+/// before buses, and a number otherwise.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(untagged)]
+enum SavedBits {
+    Wire(bool),
+    Bus(u32),
+}
+
+impl From<SavedBits> for Bits {
+    #[inline]
+    fn from(value: SavedBits) -> Self {
+        match value {
+            SavedBits::Wire(state) => Self::from(state),
+            SavedBits::Bus(bits) => Self(bits),
+        }
+    }
+}
+
+impl From<Bits> for SavedBits {
+    #[inline]
+    fn from(value: Bits) -> Self {
+        match value.0 {
+            0 | 1 => Self::Wire(value.any()),
+            bits => Self::Bus(bits),
+        }
+    }
+}
+
+impl Bits {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(1);
+
+    /// Every bit of a bus `width` wide turned on.
+    #[inline]
+    pub const fn mask(width: u8) -> Self {
+        match width {
+            0 => Self(0),
+            MAX_BUS_WIDTH.. => Self(u32::MAX),
+            n => Self((1 << n) - 1),
+        }
+    }
+
+    /// Turns off bits past `width`.
+    #[inline]
+    pub const fn masked(self, width: u8) -> Self {
+        Self(self.0 & Self::mask(width).0)
+    }
+
+    /// Whether any bit is on.
+    #[inline]
+    pub const fn any(self) -> bool {
+        self.0 != 0
+    }
+
+    #[inline]
+    pub const fn get(self, n: u8) -> bool {
+        n < MAX_BUS_WIDTH && (self.0 >> n) & 1 != 0
+    }
+}
+
+impl From<bool> for Bits {
+    #[inline]
+    fn from(value: bool) -> Self {
+        Self(u32::from(value))
+    }
+}
+
+impl std::ops::BitOr for Bits {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitAnd for Bits {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl std::ops::Not for Bits {
+    type Output = Self;
+
+    #[inline]
+    fn not(self) -> Self::Output {
+        Self(!self.0)
+    }
+}
+
+impl std::ops::Shl<u8> for Bits {
+    type Output = Self;
+
+    #[inline]
+    fn shl(self, rhs: u8) -> Self::Output {
+        Self(self.0.checked_shl(rhs.into()).unwrap_or(0))
+    }
+}
+
+impl std::fmt::Binary for Bits {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Binary::fmt(&self.0, f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Node {
-    pub(super) state: bool,
+    pub(super) state: Bits,
+    /// How many bits the node carries, 1 for a single wire
+    pub(super) width: u8,
     id: NodeId,
     pub(super) gate: GateInstance,
     pub(super) position: IVec2,
+    pub(super) orientation: Orientation,
+    pub(super) note: Option<String>,
+    pub(super) name: Option<String>,
 }
 
 impl Node {
-    pub const fn new(id: NodeId, gate: Gate, position: IVec2, state: bool) -> Self {
+    pub const fn new(id: NodeId, gate: Gate, position: IVec2, state: Bits) -> Self {
         Self {
             state,
+            width: 1,
             id,
             gate: GateInstance::from_gate(gate),
             position,
+            orientation: Orientation::East,
+            note: None,
+            name: None,
         }
     }
 
@@ -507,16 +1003,50 @@ impl Node {
         &self.id
     }
 
+    /// Whether the node is on, which for a bus is whether any of its bits are.
     #[inline]
     pub const fn state(&self) -> bool {
+        self.state.any()
+    }
+
+    #[inline]
+    pub const fn bits(&self) -> Bits {
         self.state
     }
 
+    #[inline]
+    pub const fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// The width of a single wire, for deserializing files from before buses.
+    #[inline]
+    pub const fn default_width() -> u8 {
+        1
+    }
+
+    /// Clamped to `1..=MAX_BUS_WIDTH`. Bits that no longer fit are turned off.
+    #[inline]
+    pub fn set_width(&mut self, width: u8) {
+        self.width = width.clamp(1, MAX_BUS_WIDTH);
+        self.state = self.state.masked(self.gate.output_width(self.width));
+    }
+
     #[inline]
     pub const fn position(&self) -> IVec2 {
         self.position
     }
 
+    #[inline]
+    pub const fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    #[inline]
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+    }
+
     #[inline]
     pub const fn gate(&self) -> &GateInstance {
         &self.gate
@@ -526,4 +1056,35 @@ impl Node {
     pub const fn gate_mut(&mut self) -> &mut GateInstance {
         &mut self.gate
     }
+
+    /// Replaces the gate, discarding anything the old one stored. Bits the new gate does not
+    /// output are turned off.
+    pub fn set_gate(&mut self, gate: Gate) {
+        self.gate = GateInstance::from_gate(gate);
+        self.set_width(self.width);
+    }
+
+    /// A comment written by the user
+    #[inline]
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    /// Returns the previous note. An empty note removes it.
+    #[inline]
+    pub fn set_note(&mut self, note: String) -> Option<String> {
+        std::mem::replace(&mut self.note, Some(note).filter(|note| !note.is_empty()))
+    }
+
+    /// A short label given by the user, shown beside the node and in place of its ID in the log
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the previous name. An empty name removes it.
+    #[inline]
+    pub fn set_name(&mut self, name: String) -> Option<String> {
+        std::mem::replace(&mut self.name, Some(name).filter(|name| !name.is_empty()))
+    }
 }
@@ -0,0 +1,88 @@
+//! Lookup of nodes by grid cell, and by region through square chunks of cells.
+
+use crate::{graph::node::NodeId, ivec::IVec2};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// Width and height of a chunk, in cells.
+const CHUNK_SIZE: i32 = 16;
+
+#[inline]
+const fn chunk_of(cell: IVec2) -> IVec2 {
+    IVec2::new(cell.x.div_euclid(CHUNK_SIZE), cell.y.div_euclid(CHUNK_SIZE))
+}
+
+/// At most one node per cell.
+#[derive(Debug, Clone, Default)]
+pub struct NodeGrid {
+    cells: FxHashMap<IVec2, NodeId>,
+    /// The occupied cells of each chunk that has any
+    chunks: FxHashMap<IVec2, FxHashSet<IVec2>>,
+}
+
+impl NodeGrid {
+    #[inline]
+    pub fn get(&self, cell: &IVec2) -> Option<&NodeId> {
+        self.cells.get(cell)
+    }
+
+    /// Returns the node that was in `cell`, if any.
+    pub fn insert(&mut self, cell: IVec2, id: NodeId) -> Option<NodeId> {
+        self.chunks.entry(chunk_of(cell)).or_default().insert(cell);
+        self.cells.insert(cell, id)
+    }
+
+    pub fn remove(&mut self, cell: &IVec2) -> Option<NodeId> {
+        let id = self.cells.remove(cell)?;
+        let chunk = chunk_of(*cell);
+        if let Some(cells) = self.chunks.get_mut(&chunk) {
+            cells.remove(cell);
+            if cells.is_empty() {
+                self.chunks.remove(&chunk);
+            }
+        }
+        Some(id)
+    }
+
+    /// The nodes in cells from `min` to `max` inclusive, in no particular order.
+    pub fn in_cells(&self, min: IVec2, max: IVec2) -> impl Iterator<Item = (&IVec2, &NodeId)> {
+        let (min_chunk, max_chunk) = (chunk_of(min), chunk_of(max));
+        let chunk_count = (i64::from(max_chunk.x) - i64::from(min_chunk.x) + 1).max(0)
+            * (i64::from(max_chunk.y) - i64::from(min_chunk.y) + 1).max(0);
+        // for a huge region, checking the chunks that exist is faster than looking up every one
+        let is_huge = chunk_count > self.chunks.len() as i64;
+        let chunks: Box<dyn Iterator<Item = &FxHashSet<IVec2>> + '_> = if is_huge {
+            Box::new(self.chunks.iter().filter_map(move |(chunk, cells)| {
+                ((min_chunk.x..=max_chunk.x).contains(&chunk.x)
+                    && (min_chunk.y..=max_chunk.y).contains(&chunk.y))
+                .then_some(cells)
+            }))
+        } else {
+            Box::new((min_chunk.y..=max_chunk.y).flat_map(move |y| {
+                (min_chunk.x..=max_chunk.x).filter_map(move |x| self.chunks.get(&IVec2::new(x, y)))
+            }))
+        };
+        chunks
+            .flatten()
+            .filter(move |cell| {
+                (min.x..=max.x).contains(&cell.x) && (min.y..=max.y).contains(&cell.y)
+            })
+            .map(move |cell| {
+                (
+                    cell,
+                    self.cells
+                        .get(cell)
+                        .expect("chunks should only contain occupied cells"),
+                )
+            })
+    }
+}
+
+impl FromIterator<(IVec2, NodeId)> for NodeGrid {
+    fn from_iter<I: IntoIterator<Item = (IVec2, NodeId)>>(iter: I) -> Self {
+        let mut grid = Self::default();
+        for (cell, id) in iter {
+            grid.insert(cell, id);
+        }
+        grid
+    }
+}
@@ -0,0 +1,254 @@
+//! Graphs in the line-oriented format of the [`obj`] crate, for sharing circuits with tools that
+//! read OBJ-like text. Nodes are numbered from 1 in the order they are written, the way OBJ faces
+//! refer to vertices.
+
+use crate::{
+    graph::{
+        EvalMode, Graph,
+        eag::{GraphTemplate, Nodes, Wires},
+        node::{Bits, Gate, Node, NodeId, Ntd, Orientation},
+        wire::{Elbow, Wire, WireId},
+    },
+    ivec::{IBounds, IVec2},
+};
+use rustc_hash::FxHashMap;
+use serde_derive::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+pub const OBJ_EXTENSION: &str = "obj";
+/// Written in the `version` line; files from a newer version are refused rather than misread.
+pub const OBJ_VERSION: u32 = 1;
+/// Comment written at the top of every file.
+const OBJ_HEADER: &str = "# ea-rs-3 graph";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ObjGraph {
+    version: u32,
+    #[serde(default)]
+    eval_mode: EvalMode,
+    #[serde(default = "crate::default_grid_size")]
+    grid_size: u8,
+    /// `(gate, x, y, state, orientation, width)`, with the state of a bus as a number
+    #[serde(default, rename = "n")]
+    nodes: Vec<(String, i32, i32, Bits, Orientation, u8)>,
+    /// `(src, dst, elbow)`
+    #[serde(default, rename = "w")]
+    wires: Vec<(usize, usize, Elbow)>,
+    /// `(node, note)`
+    #[serde(default, rename = "nn")]
+    node_notes: Vec<(usize, String)>,
+    /// `(node, name)`
+    #[serde(default, rename = "nm")]
+    node_names: Vec<(usize, String)>,
+    /// `(node, path)`, for IC nodes of graphs that were saved
+    #[serde(default, rename = "ip")]
+    ic_paths: Vec<(usize, PathBuf)>,
+    /// `(wire, note)`, wires numbered from 1 as well
+    #[serde(default, rename = "wn")]
+    wire_notes: Vec<(usize, String)>,
+    /// `(wire, corners)`, for wires routed around nodes
+    #[serde(default, rename = "wr")]
+    wire_routes: Vec<(usize, Vec<(i32, i32)>)>,
+    /// `(min x, min y, max x, max y, color, label)`, oldest first
+    #[serde(default, rename = "r")]
+    regions: Vec<(i32, i32, i32, i32, Ntd, String)>,
+    /// `(x, y, text)`, oldest first
+    #[serde(default, rename = "a")]
+    annotations: Vec<(i32, i32, String)>,
+}
+
+impl From<&Graph> for ObjGraph {
+    fn from(graph: &Graph) -> Self {
+        let mut nodes = graph.nodes.values().collect::<Vec<_>>();
+        nodes.sort_unstable_by_key(|node| node.id().0);
+        let node_numbers = nodes
+            .iter()
+            .enumerate()
+            .map(|(n, node)| (*node.id(), n + 1))
+            .collect::<FxHashMap<_, _>>();
+        let mut wires = graph.wires.values().collect::<Vec<_>>();
+        wires.sort_unstable_by_key(|wire| wire.id().0);
+
+        Self {
+            version: OBJ_VERSION,
+            eval_mode: graph.eval_mode,
+            grid_size: graph.grid_size,
+            nodes: nodes
+                .iter()
+                .map(|node| {
+                    (
+                        node.gate.as_gate().to_string(),
+                        node.position.x,
+                        node.position.y,
+                        node.state,
+                        node.orientation,
+                        node.width,
+                    )
+                })
+                .collect(),
+            wires: wires
+                .iter()
+                .map(|wire| (node_numbers[&wire.src], node_numbers[&wire.dst], wire.elbow))
+                .collect(),
+            node_notes: nodes
+                .iter()
+                .enumerate()
+                .filter_map(|(n, node)| Some((n + 1, node.note()?.to_string())))
+                .collect(),
+            node_names: nodes
+                .iter()
+                .enumerate()
+                .filter_map(|(n, node)| Some((n + 1, node.name()?.to_string())))
+                .collect(),
+            ic_paths: nodes
+                .iter()
+                .enumerate()
+                .filter_map(|(n, node)| Some((n + 1, graph.ic_path(node.id())?.to_path_buf())))
+                .collect(),
+            wire_notes: wires
+                .iter()
+                .enumerate()
+                .filter_map(|(n, wire)| Some((n + 1, wire.note()?.to_string())))
+                .collect(),
+            wire_routes: wires
+                .iter()
+                .enumerate()
+                .filter_map(|(n, wire)| {
+                    let route = wire.route()?;
+                    Some((n + 1, route.iter().map(|c| (c.x, c.y)).collect()))
+                })
+                .collect(),
+            regions: graph
+                .regions_ordered()
+                .into_iter()
+                .map(|region| {
+                    let IBounds { min, max } = *region.bounds();
+                    (
+                        min.x,
+                        min.y,
+                        max.x,
+                        max.y,
+                        region.color,
+                        region.label.clone(),
+                    )
+                })
+                .collect(),
+            annotations: graph
+                .annotations_ordered()
+                .into_iter()
+                .map(|annotation| {
+                    let IVec2 { x, y } = annotation.position();
+                    (x, y, annotation.text.clone())
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<ObjGraph> for GraphTemplate {
+    type Error = String;
+
+    fn try_from(value: ObjGraph) -> Result<Self, Self::Error> {
+        if value.version > OBJ_VERSION {
+            return Err(format!(
+                "made by a newer version (format {}, this reads up to {OBJ_VERSION})",
+                value.version
+            ));
+        }
+        let node_count = value.nodes.len();
+        // numbered from 1, and ids are handed out from 0 in the same order
+        let index = |n: usize, what: &str, count: usize| {
+            n.checked_sub(1)
+                .filter(|&i| i < count)
+                .map(|i| i as u128)
+                .ok_or_else(|| format!("{what} {n} does not exist"))
+        };
+
+        let mut nodes = FxHashMap::default();
+        let mut next_node_id = NodeId(0);
+        let mut node_orientations = Vec::new();
+        let mut node_widths = Vec::new();
+        for (gate, x, y, state, orientation, width) in value.nodes {
+            let gate = gate
+                .parse::<Gate>()
+                .map_err(|()| format!("invalid gate: {gate}"))?;
+            let id = next_node_id.step().unwrap();
+            nodes.insert(id, Node::new(id, gate, IVec2::new(x, y), state));
+            if orientation != Orientation::default() {
+                node_orientations.push((id.0, orientation));
+            }
+            if width != Node::default_width() {
+                node_widths.push((id.0, width));
+            }
+        }
+
+        let mut wires = FxHashMap::default();
+        let mut next_wire_id = WireId(0);
+        let wire_count = value.wires.len();
+        for (src, dst, elbow) in value.wires {
+            let src = NodeId(index(src, "node", node_count)?);
+            let dst = NodeId(index(dst, "node", node_count)?);
+            let id = next_wire_id.step().unwrap();
+            wires.insert(id, Wire::new(id, elbow, src, dst));
+        }
+
+        Ok(Self {
+            eval_mode: value.eval_mode,
+            grid_size: value.grid_size,
+            nodes: Nodes(nodes, next_node_id),
+            wires: Wires(wires, next_wire_id),
+            node_notes: value
+                .node_notes
+                .into_iter()
+                .map(|(n, note)| Ok((index(n, "node", node_count)?, note)))
+                .collect::<Result<_, String>>()?,
+            node_names: value
+                .node_names
+                .into_iter()
+                .map(|(n, name)| Ok((index(n, "node", node_count)?, name)))
+                .collect::<Result<_, String>>()?,
+            node_orientations,
+            node_widths,
+            ic_paths: value
+                .ic_paths
+                .into_iter()
+                .map(|(n, path)| Ok((index(n, "node", node_count)?, path)))
+                .collect::<Result<_, String>>()?,
+            wire_notes: value
+                .wire_notes
+                .into_iter()
+                .map(|(n, note)| Ok((index(n, "wire", wire_count)?, note)))
+                .collect::<Result<_, String>>()?,
+            wire_routes: value
+                .wire_routes
+                .into_iter()
+                .map(|(n, route)| Ok((index(n, "wire", wire_count)?, route)))
+                .collect::<Result<_, String>>()?,
+            regions: value
+                .regions
+                .into_iter()
+                .map(|(min_x, min_y, max_x, max_y, color, label)| {
+                    ((min_x, min_y), (max_x, max_y), label, color)
+                })
+                .collect(),
+            annotations: value
+                .annotations
+                .into_iter()
+                .map(|(x, y, text)| ((x, y), text))
+                .collect(),
+        })
+    }
+}
+
+/// Writes `graph` in the obj format, headed by a comment naming what it is.
+pub fn to_obj_string(graph: &Graph) -> Result<String, obj::Error> {
+    let mut text = format!("{OBJ_HEADER}\n");
+    text.push_str(&obj::to_string(&ObjGraph::from(graph))?);
+    Ok(text)
+}
+
+/// Reads a graph written by [`to_obj_string`] or by hand.
+pub fn from_obj_str(s: &str) -> Result<Graph, String> {
+    let value = obj::from_str::<ObjGraph>(s).map_err(|e| e.to_string())?;
+    GraphTemplate::try_from(value).and_then(Graph::try_from)
+}
@@ -0,0 +1,94 @@
+use super::node::Ntd;
+use crate::ivec::{IBounds, IVec2};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RegionId(pub(super) u128);
+
+/// Defaults to [`Self::INVALID`]
+impl Default for RegionId {
+    fn default() -> Self {
+        Self::INVALID
+    }
+}
+
+impl std::fmt::Display for RegionId {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "r{:x}", self.0)
+    }
+}
+
+impl std::str::FromStr for RegionId {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_prefix('r')
+            .ok_or(())
+            .and_then(|x| u128::from_str_radix(x, 16).map_err(|_| ()))
+            .map(Self)
+    }
+}
+
+impl RegionId {
+    pub const INVALID: Self = Self(!0);
+
+    /// Returns the current value and increments `self`.
+    /// Returns [`None`] if [`Self::INVALID`] would have been returned.
+    /// Does not increment if `self` is [`Self::INVALID`].
+    #[inline]
+    pub const fn step(&mut self) -> Option<Self> {
+        const INVALID: RegionId = RegionId::INVALID;
+        match *self {
+            INVALID => None,
+            id => {
+                self.0 += 1;
+                Some(id)
+            }
+        }
+    }
+}
+
+/// A labeled rectangle drawn behind the nodes, for grouping and documenting parts of a circuit.
+/// It doesn't affect how the graph behaves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    id: RegionId,
+    /// In world units, exclusive of `max`
+    pub(super) bounds: IBounds,
+    pub label: String,
+    /// Index into the theme's resistance colors, like LEDs
+    pub color: Ntd,
+}
+
+impl Region {
+    pub const fn new(id: RegionId, bounds: IBounds, label: String, color: Ntd) -> Self {
+        Self {
+            id,
+            bounds,
+            label,
+            color,
+        }
+    }
+
+    #[inline]
+    pub const fn id(&self) -> &RegionId {
+        &self.id
+    }
+
+    #[inline]
+    pub const fn bounds(&self) -> &IBounds {
+        &self.bounds
+    }
+
+    /// Moves the region so that its top-left corner is at `position`, keeping its size.
+    #[inline]
+    pub const fn set_position(&mut self, position: IVec2) {
+        self.bounds = IBounds::new(
+            position,
+            IVec2::new(
+                position.x + self.bounds.width(),
+                position.y + self.bounds.height(),
+            ),
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Wire routes that go around nodes rather than over them, found by an A* search over grid
+//! cells.
+//!
+//! Routes are kept as the cells the wire turns in, so that a wire running past many cells only
+//! stores a few corners.
+
+use super::{Graph, node::NodeId, wire::WireId};
+use crate::ivec::IVec2;
+use rustc_hash::FxHashMap;
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// How many cells a route may stray outside the box around both of its ends.
+const ROUTE_MARGIN: i32 = 8;
+/// Extra cost of a turn, in steps, so that routes bend as few times as they can.
+const TURN_COST: u32 = 3;
+/// Steps a route can take, as `(x, y)`. Indices are used as directions in the search.
+const STEPS: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+/// Direction of the first cell, which any step can leave without turning.
+const NO_DIRECTION: u8 = STEPS.len() as u8;
+
+/// A cell and the direction it was entered from.
+type State = (i32, i32, u8);
+
+impl Graph {
+    /// Positions of the cells where the shortest path from `src` to `dst` around every other
+    /// node turns, preferring fewer turns over a shorter path.
+    ///
+    /// Returns [`None`] if either node does not exist or if the nodes are walled off from each
+    /// other within [`ROUTE_MARGIN`] cells. A route with no corners means the nodes are in a
+    /// clear line.
+    pub fn find_route(&self, src: &NodeId, dst: &NodeId) -> Option<Vec<IVec2>> {
+        let grid_size = self.grid_size;
+        let start = Self::node_cell(self.node(src)?.position(), grid_size);
+        let goal = Self::node_cell(self.node(dst)?.position(), grid_size);
+        let (min_x, max_x) = (
+            start.x.min(goal.x) - ROUTE_MARGIN,
+            start.x.max(goal.x) + ROUTE_MARGIN,
+        );
+        let (min_y, max_y) = (
+            start.y.min(goal.y) - ROUTE_MARGIN,
+            start.y.max(goal.y) + ROUTE_MARGIN,
+        );
+        let is_open = |x: i32, y: i32| {
+            (x, y) == (goal.x, goal.y)
+                || ((min_x..=max_x).contains(&x)
+                    && (min_y..=max_y).contains(&y)
+                    && self.node_grid.get(&IVec2::new(x, y)).is_none())
+        };
+        let estimate = |x: i32, y: i32| (x - goal.x).unsigned_abs() + (y - goal.y).unsigned_abs();
+
+        let first = (start.x, start.y, NO_DIRECTION);
+        let mut costs = FxHashMap::<State, u32>::default();
+        let mut came_from = FxHashMap::<State, State>::default();
+        let mut open = BinaryHeap::new();
+        costs.insert(first, 0);
+        open.push(Reverse((estimate(start.x, start.y), 0, first)));
+        let last = loop {
+            let Reverse((_, cost, state @ (x, y, dir))) = open.pop()?;
+            if (x, y) == (goal.x, goal.y) {
+                break state;
+            }
+            if costs.get(&state).is_some_and(|&best| best < cost) {
+                continue;
+            }
+            for (next_dir, (dx, dy)) in STEPS.into_iter().enumerate() {
+                let next_dir = next_dir as u8;
+                let (next_x, next_y) = (x + dx, y + dy);
+                if !is_open(next_x, next_y) {
+                    continue;
+                }
+                let turn = if dir == NO_DIRECTION || dir == next_dir {
+                    0
+                } else {
+                    TURN_COST
+                };
+                let next = (next_x, next_y, next_dir);
+                let next_cost = cost + 1 + turn;
+                if costs.get(&next).is_none_or(|&best| next_cost < best) {
+                    costs.insert(next, next_cost);
+                    came_from.insert(next, state);
+                    open.push(Reverse((
+                        next_cost + estimate(next_x, next_y),
+                        next_cost,
+                        next,
+                    )));
+                }
+            }
+        };
+
+        // walked back from the goal, so corners come out last to first
+        let size = i32::from(grid_size);
+        let mut corners = Vec::new();
+        let mut state = last;
+        while let Some(&prev) = came_from.get(&state) {
+            let (x, y, dir) = prev;
+            if dir != NO_DIRECTION && dir != state.2 {
+                corners.push(IVec2::new(x * size, y * size));
+            }
+            state = prev;
+        }
+        corners.reverse();
+        Some(corners)
+    }
+
+    /// Routes the wire around the nodes in the way, returning whether a route was found. If
+    /// not, the wire is shaped by its elbow.
+    pub fn route_wire(&mut self, id: &WireId) -> bool {
+        let Some(wire) = self.wires.get(id) else {
+            return false;
+        };
+        let route = self.find_route(&wire.src, &wire.dst);
+        let is_found = route.is_some();
+        self.wires
+            .get_mut(id)
+            .expect("wire was just found")
+            .set_route(route);
+        is_found
+    }
+
+    /// Finds new routes for the routed wires of `id`, after it has moved.
+    pub(super) fn reroute_wires_of(&mut self, id: &NodeId) {
+        let routed = self
+            .wires
+            .values()
+            .filter(|wire| (wire.src == *id || wire.dst == *id) && wire.route.is_some())
+            .map(|wire| *wire.id())
+            .collect::<Vec<_>>();
+        for wire_id in routed {
+            self.route_wire(&wire_id);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Recording the states of chosen nodes, called probes, after every tick, to be exported as a VCD
+//! file for inspecting the timing of a circuit in a waveform viewer such as GTKWave.
+//!
+//! Unlike [`History`](super::history::History), a trace only covers the probed nodes, but keeps
+//! every tick since it started and every bit of a bus. Only changes are stored.
+
+use super::node::{Bits, Node, NodeId};
+use rustc_hash::FxHashMap;
+use std::io::Write;
+
+/// A node being traced.
+#[derive(Debug, Clone)]
+struct Probe {
+    id: NodeId,
+    /// What the signal is called in the VCD file
+    name: String,
+    width: u8,
+    /// Each value the node took and the tick it took it on, starting from when it was probed
+    changes: Vec<(u64, Bits)>,
+}
+
+/// The short code a VCD file refers to the `n`th signal by, from the printable characters.
+fn vcd_code(mut n: usize) -> String {
+    const FIRST: u8 = b'!';
+    const COUNT: usize = (b'~' - b'!') as usize + 1;
+    let mut code = String::new();
+    loop {
+        code.push(char::from(FIRST + (n % COUNT) as u8));
+        n /= COUNT;
+        if n == 0 {
+            break code;
+        }
+        n -= 1;
+    }
+}
+
+/// A VCD identifier for `node`, since dots separate scopes in some viewers and gates like
+/// `led.3` have them.
+fn signal_name(node: &Node) -> String {
+    format!("{}_{}", node.id(), node.gate().as_gate())
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    /// In the order they were probed
+    probes: Vec<Probe>,
+    /// Ticks sampled so far
+    ticks: u64,
+}
+
+impl Trace {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.probes.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.probes.is_empty()
+    }
+
+    /// Ticks sampled since the trace started.
+    #[inline]
+    pub const fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    #[inline]
+    pub fn is_probed(&self, id: &NodeId) -> bool {
+        self.probes.iter().any(|probe| &probe.id == id)
+    }
+
+    /// Starts tracing `node` from its current state. Does nothing if it is already probed.
+    pub fn probe(&mut self, node: &Node) {
+        if !self.is_probed(node.id()) {
+            self.probes.push(Probe {
+                id: *node.id(),
+                name: signal_name(node),
+                width: node.width(),
+                changes: vec![(self.ticks, node.bits().masked(node.width()))],
+            });
+        }
+    }
+
+    /// Stops tracing `id`, discarding what was recorded of it. Returns whether it was probed.
+    pub fn unprobe(&mut self, id: &NodeId) -> bool {
+        let len = self.probes.len();
+        self.probes.retain(|probe| &probe.id != id);
+        self.probes.len() != len
+    }
+
+    /// Records the state of every probed node after a tick. Nodes that no longer exist keep
+    /// their last state.
+    pub fn sample(&mut self, nodes: &FxHashMap<NodeId, Node>) {
+        self.ticks += 1;
+        for probe in &mut self.probes {
+            if let Some(node) = nodes.get(&probe.id) {
+                let bits = node.bits().masked(probe.width);
+                if probe.changes.last().is_none_or(|&(_, last)| last != bits) {
+                    probe.changes.push((self.ticks, bits));
+                }
+            }
+        }
+    }
+
+    /// Writes the trace as a VCD file, with the probes in a module called `scope` and one unit
+    /// of time per tick. Probes are unknown (`x`) before the tick they were probed on.
+    pub fn write_vcd(&self, out: &mut impl Write, scope: &str) -> std::io::Result<()> {
+        let codes = (0..self.probes.len()).map(vcd_code).collect::<Vec<_>>();
+        let value = |probe: &Probe, code: &str, bits: Option<Bits>| match (probe.width, bits) {
+            (1, Some(bits)) => format!("{}{code}", u8::from(bits.any())),
+            (1, None) => format!("x{code}"),
+            (width, Some(bits)) => {
+                format!("b{:0width$b} {code}", bits.0, width = usize::from(width))
+            }
+            (_, None) => format!("bx {code}"),
+        };
+
+        writeln!(out, "$version Electron Architect $end")?;
+        writeln!(out, "$comment each unit of time is one tick $end")?;
+        writeln!(out, "$timescale 1 s $end")?;
+        writeln!(out, "$scope module {scope} $end")?;
+        for (probe, code) in self.probes.iter().zip(&codes) {
+            writeln!(out, "$var wire {} {code} {} $end", probe.width, probe.name)?;
+        }
+        writeln!(out, "$upscope $end")?;
+        writeln!(out, "$enddefinitions $end")?;
+
+        writeln!(out, "#0")?;
+        writeln!(out, "$dumpvars")?;
+        for (probe, code) in self.probes.iter().zip(&codes) {
+            let initial = probe
+                .changes
+                .first()
+                .filter(|&&(tick, _)| tick == 0)
+                .map(|&(_, bits)| bits);
+            writeln!(out, "{}", value(probe, code, initial))?;
+        }
+        writeln!(out, "$end")?;
+
+        let mut changes = self
+            .probes
+            .iter()
+            .enumerate()
+            .flat_map(|(n, probe)| {
+                probe
+                    .changes
+                    .iter()
+                    .map(move |&(tick, bits)| (tick, n, bits))
+            })
+            .filter(|&(tick, _, _)| tick > 0)
+            .collect::<Vec<_>>();
+        changes.sort_unstable_by_key(|&(tick, n, _)| (tick, n));
+        let mut time = 0;
+        for (tick, n, bits) in changes {
+            if tick != time {
+                writeln!(out, "#{tick}")?;
+                time = tick;
+            }
+            writeln!(out, "{}", value(&self.probes[n], &codes[n], Some(bits)))?;
+        }
+        // so that the trace lasts every tick, even if nothing changed on the last ones
+        if time != self.ticks {
+            writeln!(out, "#{}", self.ticks)?;
+        }
+        Ok(())
+    }
+}
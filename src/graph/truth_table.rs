@@ -0,0 +1,232 @@
+//! Truth tables of combinational circuits, found by trying every combination of inputs on a copy
+//! of the circuit.
+//!
+//! As with [ICs](super::ic), the inputs and outputs are the circuit's
+//! [interface](Graph::interface) if it has one. Otherwise the inputless nodes are the inputs and
+//! the outputless nodes are the outputs, both ordered top to bottom, then left to right, and nodes
+//! with neither are only inputs.
+//! Each combination is evaluated until the copy stops changing; outputs of combinations that
+//! never settle, such as those of oscillators, are unknown. Circuits that remember state, like
+//! latches, give whatever the previous combination left them in.
+
+use super::{
+    Graph, GraphList,
+    bitset::BitSet,
+    model::GraphModel,
+    node::{Bits, NodeId},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// Most inputs a truth table can have, since every row is printed.
+pub const MAX_TRUTH_TABLE_INPUTS: usize = 8;
+
+/// Why a circuit has no truth table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruthTableError {
+    /// There are no nodes to take a truth table of.
+    Empty,
+    /// Every node has an output, so the circuit has nothing to show.
+    NoOutputs,
+    /// More than [`MAX_TRUTH_TABLE_INPUTS`] inputs.
+    TooManyInputs(usize),
+}
+
+impl std::fmt::Display for TruthTableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("there are no nodes"),
+            Self::NoOutputs => f.write_str("every node has an output, so none are outputs"),
+            Self::TooManyInputs(n) => write!(
+                f,
+                "{n} nodes have no inputs, but at most {MAX_TRUTH_TABLE_INPUTS} inputs are supported"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruthTable {
+    pub inputs: Vec<NodeId>,
+    pub outputs: Vec<NodeId>,
+    /// Names of the inputs and outputs that have one, shown in place of their IDs
+    pub names: FxHashMap<NodeId, String>,
+    /// The outputs for each combination of inputs, counting up in binary with the first input
+    /// as the highest bit. [`None`] where the circuit never settled.
+    pub rows: Vec<Vec<Option<bool>>>,
+}
+
+impl TruthTable {
+    /// The table as lines of text, with a header naming the nodes, inputs and outputs separated by
+    /// a bar, and `~` for outputs that never settled.
+    pub fn lines(&self) -> Vec<String> {
+        let names = |ids: &[NodeId]| {
+            ids.iter()
+                .map(|id| {
+                    self.names
+                        .get(id)
+                        .cloned()
+                        .unwrap_or_else(|| id.to_string())
+                })
+                .collect::<Vec<_>>()
+        };
+        let (input_names, output_names) = (names(&self.inputs), names(&self.outputs));
+        let row = |inputs: Vec<&str>, outputs: Vec<&str>| {
+            let cells = |values: Vec<&str>, names: &[String]| {
+                values
+                    .into_iter()
+                    .zip(names)
+                    .map(|(value, name)| format!("{value:<width$}", width = name.len()))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            format!(
+                "{} | {}",
+                cells(inputs, &input_names),
+                cells(outputs, &output_names)
+            )
+            .trim()
+            .to_string()
+        };
+
+        let mut lines = Vec::with_capacity(self.rows.len() + 1);
+        lines.push(row(
+            input_names.iter().map(String::as_str).collect(),
+            output_names.iter().map(String::as_str).collect(),
+        ));
+        for (combination, outputs) in self.rows.iter().enumerate() {
+            let inputs = (0..self.inputs.len())
+                .rev()
+                .map(|bit| {
+                    if combination >> bit & 1 != 0 {
+                        "1"
+                    } else {
+                        "0"
+                    }
+                })
+                .collect();
+            let outputs = outputs
+                .iter()
+                .map(|state| match state {
+                    Some(true) => "1",
+                    Some(false) => "0",
+                    None => "~",
+                })
+                .collect();
+            lines.push(row(inputs, outputs));
+        }
+        lines
+    }
+}
+
+impl Graph {
+    /// Tries every combination of inputs on a copy of the nodes in `selection` and the wires
+    /// between them, or of the whole graph if [`None`]. The graph itself is left alone.
+    ///
+    /// `graphs` provides the graphs run by any IC nodes.
+    pub fn truth_table(
+        &self,
+        graphs: &GraphList,
+        selection: Option<&FxHashSet<NodeId>>,
+    ) -> Result<TruthTable, TruthTableError> {
+        let (model, mut original_ids) = match selection {
+            Some(selection) => (
+                GraphModel::from_selection(self, selection),
+                selection
+                    .iter()
+                    .copied()
+                    .filter(|id| self.nodes.contains_key(id))
+                    .collect::<Vec<_>>(),
+            ),
+            None => (GraphModel::from(self), self.nodes.keys().copied().collect()),
+        };
+        if model.is_empty() {
+            return Err(TruthTableError::Empty);
+        }
+        let mut copy = model
+            .to_graph(self.id)
+            .expect("a graph's own model should be valid");
+        copy.set_eval_mode(self.eval_mode);
+        copy.refresh_eval_order();
+        copy.build_ics(graphs, &mut vec![self.id]);
+
+        // models list nodes in ID order, and the copy's IDs are handed out in model order
+        original_ids.sort_unstable_by_key(|id| id.0);
+        let mut copy_ids = copy.nodes.keys().copied().collect::<Vec<_>>();
+        copy_ids.sort_unstable_by_key(|id| id.0);
+        let original_of = copy_ids
+            .into_iter()
+            .zip(original_ids)
+            .collect::<FxHashMap<_, _>>();
+
+        let index_of = copy
+            .eval_order
+            .iter()
+            .enumerate()
+            .map(|(n, id)| (*id, n))
+            .collect::<FxHashMap<NodeId, usize>>();
+        let (inputs, outputs) = copy.interface().unwrap_or_else(|| {
+            let pins = |mut ids: Vec<NodeId>| {
+                ids.sort_unstable_by_key(|id| {
+                    let position = copy.nodes[id].position;
+                    (position.y, position.x)
+                });
+                ids
+            };
+            let inputs = pins(copy.inputless_nodes().collect());
+            let outputs = pins(
+                copy.outputless_nodes()
+                    .filter(|id| !inputs.contains(id))
+                    .collect(),
+            );
+            (inputs, outputs)
+        });
+        if outputs.is_empty() {
+            return Err(TruthTableError::NoOutputs);
+        }
+        if inputs.len() > MAX_TRUTH_TABLE_INPUTS {
+            return Err(TruthTableError::TooManyInputs(inputs.len()));
+        }
+        let input_indices = inputs.iter().map(|id| index_of[id]).collect::<Vec<_>>();
+        let output_indices = outputs.iter().map(|id| index_of[id]).collect::<Vec<_>>();
+        let mut driven = BitSet::new(copy.eval_order.len());
+        for &n in &input_indices {
+            driven.set(n, true);
+        }
+
+        // a change takes at most one tick per node to pass through a circuit without loops
+        let max_ticks = copy.eval_order.len() + 1;
+        let rows = (0..1usize << inputs.len())
+            .map(|combination| {
+                for (bit, &n) in input_indices.iter().rev().enumerate() {
+                    copy.states[n] = Bits::from(combination >> bit & 1 != 0);
+                }
+                let mut is_settled = false;
+                for _ in 0..max_ticks {
+                    let before = copy.states.clone();
+                    copy.evaluate_driven(Some(&driven));
+                    if copy.states == before {
+                        is_settled = true;
+                        break;
+                    }
+                }
+                output_indices
+                    .iter()
+                    .map(|&n| is_settled.then(|| copy.states[n].any()))
+                    .collect()
+            })
+            .collect();
+
+        let names = inputs
+            .iter()
+            .chain(&outputs)
+            .map(|id| original_of[id])
+            .filter_map(|id| Some((id, self.nodes[&id].name()?.to_string())))
+            .collect();
+        Ok(TruthTable {
+            inputs: inputs.iter().map(|id| original_of[id]).collect(),
+            outputs: outputs.iter().map(|id| original_of[id]).collect(),
+            names,
+            rows,
+        })
+    }
+}
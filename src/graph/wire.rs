@@ -1,7 +1,13 @@
 use super::{Graph, node::NodeId};
+use crate::ivec::IVec2;
 use raylib::prelude::*;
 use serde_derive::{Deserialize, Serialize};
 
+/// Thickness of a wire leaving a bus, relative to the grid size.
+pub const BUS_THICKNESS: f32 = 0.25;
+/// Points a [`Elbow::Curve`] wire is drawn through, ends included.
+pub const CURVE_POINTS: usize = 17;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct WireId(pub(super) u128);
 
@@ -62,9 +68,65 @@ pub enum Elbow {
     #[default]
     #[serde(rename = "/")]
     DiagonalEnd,
+    /// Straight from end to end at any angle
+    #[serde(rename = ".")]
+    Straight,
+    /// An S-curve leaving and reaching both ends horizontally
+    #[serde(rename = "~")]
+    Curve,
+}
+
+impl std::fmt::Display for Elbow {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Elbow::Horizontal => "horizontal",
+            Elbow::DiagonalStart => "diagonal at start",
+            Elbow::Vertical => "vertical",
+            Elbow::DiagonalEnd => "diagonal at end",
+            Elbow::Straight => "straight",
+            Elbow::Curve => "curve",
+        }
+        .fmt(f)
+    }
 }
 
 impl Elbow {
+    pub const ALL: [Self; 6] = [
+        Self::Horizontal,
+        Self::DiagonalStart,
+        Self::Vertical,
+        Self::DiagonalEnd,
+        Self::Straight,
+        Self::Curve,
+    ];
+
+    /// The style after this one in [`Self::ALL`], wrapping around.
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Horizontal => Self::DiagonalStart,
+            Self::DiagonalStart => Self::Vertical,
+            Self::Vertical => Self::DiagonalEnd,
+            Self::DiagonalEnd => Self::Straight,
+            Self::Straight => Self::Curve,
+            Self::Curve => Self::Horizontal,
+        }
+    }
+
+    /// The elbow style that puts the elbow of a wire from `start_pos` to `end_pos` closest to `target`.
+    pub fn nearest(start_pos: Vector2, end_pos: Vector2, target: Vector2) -> Self {
+        Self::ALL
+            .into_iter()
+            .min_by(|a, b| {
+                let a = a.calculate(start_pos, end_pos).distance_to(target);
+                let b = b.calculate(start_pos, end_pos).distance_to(target);
+                a.total_cmp(&b)
+            })
+            .expect("ALL should not be empty")
+    }
+
+    /// The point the wire bends at. Wires without a corner give their midpoint, which a curve
+    /// passes through as well.
     pub const fn calculate(self, start_pos: Vector2, end_pos: Vector2) -> Vector2 {
         let x_delta = end_pos.x - start_pos.x;
         let y_delta = end_pos.y - start_pos.y;
@@ -89,8 +151,29 @@ impl Elbow {
             }
             Elbow::DiagonalStart => start_pos,
             Elbow::DiagonalEnd => end_pos,
+            Elbow::Straight | Elbow::Curve => Vector2::new(
+                (start_pos.x + end_pos.x) * 0.5,
+                (start_pos.y + end_pos.y) * 0.5,
+            ),
         }
     }
+
+    /// Points along a [`Self::Curve`] from `start_pos` to `end_pos`, to be joined by straight
+    /// lines. Eases vertically the same way as raylib's `DrawLineBezier`.
+    pub fn curve_path(start_pos: Vector2, end_pos: Vector2) -> [Vector2; CURVE_POINTS] {
+        std::array::from_fn(|i| {
+            let t = i as f32 / (CURVE_POINTS - 1) as f32;
+            let eased = if t < 0.5 {
+                4.0 * t * t * t
+            } else {
+                1.0 - (2.0 - 2.0 * t).powi(3) * 0.5
+            };
+            Vector2::new(
+                start_pos.x + (end_pos.x - start_pos.x) * t,
+                start_pos.y + (end_pos.y - start_pos.y) * eased,
+            )
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -112,12 +195,34 @@ impl Flow {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// One of the two ends of a wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WireEnd {
+    Src,
+    Dst,
+}
+
+impl std::fmt::Display for WireEnd {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WireEnd::Src => "start",
+            WireEnd::Dst => "end",
+        }
+        .fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Wire {
     id: WireId,
     pub elbow: Elbow,
     pub(super) src: NodeId,
     pub(super) dst: NodeId,
+    pub(super) note: Option<String>,
+    /// Positions of the cells the wire turns in on its way around nodes, in order from `src`,
+    /// or [`None`] if the wire is shaped by its elbow instead
+    pub(super) route: Option<Vec<IVec2>>,
 }
 
 impl Wire {
@@ -127,9 +232,42 @@ impl Wire {
             elbow,
             src,
             dst,
+            note: None,
+            route: None,
         }
     }
 
+    /// Corners found by [`Graph::route_wire`]. A routed wire without corners runs straight.
+    #[inline]
+    pub fn route(&self) -> Option<&[IVec2]> {
+        self.route.as_deref()
+    }
+
+    /// Returns the previous route. [`None`] shapes the wire by its elbow again.
+    #[inline]
+    pub fn set_route(&mut self, route: Option<Vec<IVec2>>) -> Option<Vec<IVec2>> {
+        std::mem::replace(&mut self.route, route)
+    }
+
+    /// Reshapes the wire by `elbow`, dropping its route if it had one.
+    #[inline]
+    pub fn set_elbow(&mut self, elbow: Elbow) {
+        self.elbow = elbow;
+        self.route = None;
+    }
+
+    /// A comment written by the user
+    #[inline]
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    /// Returns the previous note. An empty note removes it.
+    #[inline]
+    pub fn set_note(&mut self, note: String) -> Option<String> {
+        std::mem::replace(&mut self.note, Some(note).filter(|note| !note.is_empty()))
+    }
+
     #[inline]
     pub const fn id(&self) -> &WireId {
         &self.id
@@ -145,6 +283,76 @@ impl Wire {
         &self.dst
     }
 
+    #[inline]
+    pub const fn end(&self, end: WireEnd) -> &NodeId {
+        match end {
+            WireEnd::Src => &self.src,
+            WireEnd::Dst => &self.dst,
+        }
+    }
+
+    /// Start, elbow, and end positions of the wire, or [`None`] if wire is not valid for the graph
+    ///
+    /// `offset` is from a node's position to its center; each end is then moved to the side of
+    /// its node given by the node's [`Orientation`](super::node::Orientation).
+    pub fn points(&self, graph: &Graph, offset: Vector2) -> Option<[Vector2; 3]> {
+        let (start, end) = graph.get_wire_nodes(self)?;
+        let grid_size = graph.grid_size();
+        let start_pos = start.position().as_vec2()
+            + offset
+            + start.orientation().anchor(WireEnd::Src, grid_size);
+        let end_pos =
+            end.position().as_vec2() + offset + end.orientation().anchor(WireEnd::Dst, grid_size);
+        Some([start_pos, self.elbow.calculate(start_pos, end_pos), end_pos])
+    }
+
+    /// Every point the wire is drawn through, ends included, or [`None`] if wire is not valid
+    /// for the graph
+    ///
+    /// See [`Self::points`] for `offset`, which also moves each corner of a route to the middle
+    /// of its cell.
+    pub fn path(&self, graph: &Graph, offset: Vector2) -> Option<Vec<Vector2>> {
+        let points @ [start, _, end] = self.points(graph, offset)?;
+        Some(if let Some(route) = &self.route {
+            std::iter::once(start)
+                .chain(route.iter().map(|corner| corner.as_vec2() + offset))
+                .chain(std::iter::once(end))
+                .collect()
+        } else if self.elbow == Elbow::Curve {
+            Elbow::curve_path(start, end).to_vec()
+        } else {
+            points.to_vec()
+        })
+    }
+
+    /// Distance from `pos` to the nearest point on the lines joining `path`.
+    pub fn polyline_distance(path: &[Vector2], pos: Vector2) -> f32 {
+        fn segment_distance(a: Vector2, b: Vector2, pos: Vector2) -> f32 {
+            let ab = b - a;
+            let len_sqr = ab.length_sqr();
+            let t = if len_sqr == 0.0 {
+                0.0
+            } else {
+                ((pos - a).dot(ab) / len_sqr).clamp(0.0, 1.0)
+            };
+            pos.distance_to(a + ab * t)
+        }
+        path.windows(2)
+            .map(|segment| segment_distance(segment[0], segment[1], pos))
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// Distance from `pos` to the nearest point on the path of a wire shaped by `elbow` through
+    /// `points`.
+    pub fn path_distance(points: [Vector2; 3], elbow: Elbow, pos: Vector2) -> f32 {
+        let [start, _, end] = points;
+        if elbow == Elbow::Curve {
+            Self::polyline_distance(&Elbow::curve_path(start, end), pos)
+        } else {
+            Self::polyline_distance(&points, pos)
+        }
+    }
+
     pub fn draw_immediate<D: RaylibDraw>(
         d: &mut D,
         start_pos: Vector2,
@@ -152,11 +360,29 @@ impl Wire {
         elbow: Elbow,
         color: Color,
     ) {
-        let elbow_pos = elbow.calculate(start_pos, end_pos);
-        d.draw_line_strip(&[start_pos, elbow_pos, end_pos], color);
+        if elbow == Elbow::Curve {
+            d.draw_line_strip(&Elbow::curve_path(start_pos, end_pos), color);
+        } else {
+            let elbow_pos = elbow.calculate(start_pos, end_pos);
+            d.draw_line_strip(&[start_pos, elbow_pos, end_pos], color);
+        }
+    }
+
+    /// How thick the wire is drawn, or [`None`] if wire is not valid for the graph
+    ///
+    /// Wires leaving a node more than one bit wide are drawn thicker. `scale` multiplies the
+    /// thickness of every wire, with `pixel` being the size of a screen pixel in the world.
+    pub fn thickness(&self, graph: &Graph, scale: f32, pixel: f32) -> Option<f32> {
+        Some(if graph.node(&self.src)?.width() > 1 {
+            f32::from(graph.grid_size()) * BUS_THICKNESS * scale
+        } else {
+            pixel * scale
+        })
     }
 
     /// Returns [`None`] if wire is not valid for the graph
+    ///
+    /// See [`Self::thickness`] for `scale` and `pixel`.
     #[must_use]
     pub fn draw<D: RaylibDraw>(
         &self,
@@ -164,15 +390,31 @@ impl Wire {
         graph: &Graph,
         offset: Vector2,
         color: Color,
+        scale: f32,
+        pixel: f32,
     ) -> Option<()> {
-        let (start, end) = graph.get_wire_nodes(self)?;
-        Self::draw_immediate(
-            d,
-            start.position().as_vec2() + offset,
-            end.position().as_vec2() + offset,
-            self.elbow,
-            color,
-        );
+        let [start_pos, elbow_pos, end_pos] = self.points(graph, offset)?;
+        let thick = self.thickness(graph, scale, pixel)?;
+        let is_bus = graph.node(&self.src)?.width() > 1;
+        if self.route.is_some() {
+            let path = self.path(graph, offset)?;
+            if is_bus || scale != 1.0 {
+                for segment in path.windows(2) {
+                    d.draw_line_ex(segment[0], segment[1], thick, color);
+                }
+            } else {
+                d.draw_line_strip(&path, color);
+            }
+        } else if is_bus || scale != 1.0 {
+            if self.elbow == Elbow::Curve {
+                d.draw_line_bezier(start_pos, end_pos, thick, color);
+            } else {
+                d.draw_line_ex(start_pos, elbow_pos, thick, color);
+                d.draw_line_ex(elbow_pos, end_pos, thick, color);
+            }
+        } else {
+            Self::draw_immediate(d, start_pos, end_pos, self.elbow, color);
+        }
         Some(())
     }
 }
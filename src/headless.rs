@@ -0,0 +1,189 @@
+//! Running a graph from the command line without opening a window:
+//!
+//! ```text
+//! ea-rs-3 --headless <circuit file> [--ticks <n>] [--vcd]
+//! ```
+//!
+//! The graph is evaluated for [`DEFAULT_TICKS`] ticks unless `--ticks` says otherwise, after which
+//! every node's state is printed to stdout, one node per line in reading order. With `--vcd`, the
+//! state of every node on every tick is printed as a VCD trace instead, for a waveform viewer.
+//! Graphs with an [interface](Graph::interface) only have its inputs and outputs printed, in pin
+//! order.
+//!
+//! Files ending in `.obj` are read in the [`obj_format`](crate::graph::obj_format); anything else
+//! is read as a save. ICs can only run graphs in the same file, since no others are open.
+
+use crate::{
+    file,
+    graph::{Graph, GraphList, node::Node, trace::Trace},
+};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Ticks to evaluate for when `--ticks` is not given.
+pub const DEFAULT_TICKS: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadlessArgs {
+    pub path: PathBuf,
+    pub ticks: usize,
+    pub vcd: bool,
+}
+
+impl HeadlessArgs {
+    /// Reads the arguments after the program name. Returns [`None`] if they don't ask for
+    /// headless mode, so that the window should open as usual.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Option<Result<Self, String>> {
+        let args = args.into_iter().collect::<Vec<_>>();
+        if !args.iter().any(|arg| arg == "--headless") {
+            return None;
+        }
+        let mut path = None;
+        let mut ticks = DEFAULT_TICKS;
+        let mut vcd = false;
+        let mut args = args.into_iter();
+        let result = loop {
+            match args.next().as_deref() {
+                Some("--headless") => match args.next() {
+                    Some(arg) => path = Some(PathBuf::from(arg)),
+                    None => break Err("missing circuit file after --headless".to_string()),
+                },
+                Some("--ticks") => match args.next().map(|arg| arg.parse()) {
+                    Some(Ok(n)) => ticks = n,
+                    Some(Err(e)) => break Err(format!("invalid tick count: {e}")),
+                    None => break Err("missing tick count after --ticks".to_string()),
+                },
+                Some("--vcd") => vcd = true,
+                Some(arg) => break Err(format!("unexpected argument: {arg}")),
+                None => break Ok(()),
+            }
+        };
+        Some(result.and_then(|()| {
+            Ok(Self {
+                path: path.ok_or_else(|| "missing circuit file".to_string())?,
+                ticks,
+                vcd,
+            })
+        }))
+    }
+}
+
+/// Loads the graph, evaluates it and prints the result to stdout. Returns a message for stderr
+/// if anything goes wrong.
+pub fn run(args: &HeadlessArgs) -> Result<(), String> {
+    let mut graphs = GraphList::new();
+    let graph = if args.path.extension().is_some_and(|ext| ext == "obj") {
+        file::import_obj_graph(&args.path).map(|graph| graphs.insert_graph(graph).clone())
+    } else {
+        file::open_graph(&mut graphs, &args.path).map(|(graph, failed)| {
+            for (path, e) in failed {
+                eprintln!(
+                    "failed to open {} for the ICs that run it: {e}",
+                    path.display()
+                );
+            }
+            graph
+        })
+    }
+    .map_err(|e| format!("failed to open {}: {e}", args.path.display()))?;
+    let mut graph = graph
+        .try_write()
+        .map_err(|_| "graph should not be in use".to_string())?;
+    for (id, e) in graph.update_ics(&graphs) {
+        eprintln!("IC {id} cannot run: {e}");
+    }
+    if graph.is_eval_order_dirty() {
+        graph.refresh_eval_order();
+    }
+
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    let result = if args.vcd {
+        write_vcd(&mut out, &mut graph, &args.path, args.ticks)
+    } else {
+        for _ in 0..args.ticks {
+            graph.evaluate();
+        }
+        write_states(&mut out, &graph)
+    };
+    result
+        .and_then(|()| out.flush())
+        .map_err(|e| format!("failed to write to stdout: {e}"))
+}
+
+/// The inputs then outputs of `graph`'s interface if it has one, or else every node in reading
+/// order, by row then column.
+fn nodes_in_order(graph: &Graph) -> Vec<&Node> {
+    if let Some((inputs, outputs)) = graph.interface() {
+        return inputs
+            .iter()
+            .chain(&outputs)
+            .map(|id| graph.node(id).expect("interface nodes should be valid"))
+            .collect();
+    }
+    let mut nodes = graph.nodes_iter().collect::<Vec<_>>();
+    nodes.sort_unstable_by_key(|node| {
+        let position = node.position();
+        (position.y, position.x)
+    });
+    nodes
+}
+
+/// `0` or `1` for a single wire, or every bit from the highest for a bus.
+fn state_text(node: &Node) -> String {
+    if node.width() > 1 {
+        format!(
+            "{:0width$b}",
+            node.bits(),
+            width = usize::from(node.width())
+        )
+    } else {
+        u8::from(node.state()).to_string()
+    }
+}
+
+/// One line per node: its ID, gate, position and state.
+fn write_states(out: &mut impl Write, graph: &Graph) -> std::io::Result<()> {
+    for node in nodes_in_order(graph) {
+        let position = node.position();
+        writeln!(
+            out,
+            "{} {} ({},{}) {}",
+            node.id(),
+            node.gate().as_gate(),
+            position.x,
+            position.y,
+            state_text(node)
+        )?;
+    }
+    Ok(())
+}
+
+/// Probes every node, evaluates `graph` for `ticks` ticks and writes the trace.
+fn write_vcd(
+    out: &mut impl Write,
+    graph: &mut Graph,
+    path: &Path,
+    ticks: usize,
+) -> std::io::Result<()> {
+    let scope = path.file_stem().map_or_else(
+        || "graph".to_string(),
+        |stem| stem.to_string_lossy().replace(' ', "_"),
+    );
+    let ids = nodes_in_order(graph)
+        .into_iter()
+        .map(|node| *node.id())
+        .collect::<Vec<_>>();
+    for id in &ids {
+        graph.toggle_probe(id);
+    }
+    for _ in 0..ticks {
+        graph.evaluate();
+    }
+    match graph.trace() {
+        Some(trace) => trace.write_vcd(out, &scope),
+        // a graph with no nodes has nothing to probe
+        None => Trace::default().write_vcd(out, &scope),
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     graph::node::GateId,
     ivec::{IRect, IVec2},
+    tool::ToolId,
 };
 use raylib::prelude::*;
 use serde_derive::{Deserialize, Serialize};
@@ -48,6 +49,13 @@ impl GateId {
             GateId::Led => IVec2::new(2, 1),
             GateId::Delay => IVec2::new(3, 1),
             GateId::Battery => IVec2::new(0, 2),
+            GateId::Ic => IVec2::new(1, 2),
+            GateId::Split => IVec2::new(2, 2),
+            GateId::Merge => IVec2::new(3, 2),
+            GateId::Input => IVec2::new(0, 3),
+            GateId::Output => IVec2::new(1, 3),
+            GateId::Switch => IVec2::new(2, 3),
+            GateId::Button => IVec2::new(3, 3),
         }
     }
 
@@ -91,6 +99,36 @@ impl NodeIconSheetSetId {
     }
 }
 
+/// The sheets and source rectangles of one [`NodeIconSheetSet`], looked up once per frame
+/// instead of once per node.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeIconLookup<'a> {
+    pub basic: &'a Texture2D,
+    pub background: &'a Texture2D,
+    pub highlight: &'a Texture2D,
+    pub ntd: &'a Texture2D,
+    src_recs: [Rectangle; GateId::ALL.len()],
+}
+
+impl<'a> NodeIconLookup<'a> {
+    pub fn new(sheets: &'a NodeIconSheetSets, scale: NodeIconSheetSetId) -> Self {
+        let set = &sheets[scale];
+        let icon_width = scale.icon_width();
+        Self {
+            basic: &set.basic,
+            background: &set.background,
+            highlight: &set.highlight,
+            ntd: &set.ntd,
+            src_recs: GateId::ALL.map(|id| id.icon_cell_irec(icon_width).as_rec()),
+        }
+    }
+
+    #[inline]
+    pub const fn src_rec(&self, id: GateId) -> Rectangle {
+        self.src_recs[id as usize]
+    }
+}
+
 #[derive(Debug)]
 pub struct NodeIconSheetSets {
     pub x8: NodeIconSheetSet,
@@ -158,12 +196,120 @@ pub enum ButtonIconId {
     Led,
     Delay,
     Battery,
+    Ic,
+    Split,
+    Merge,
+    Input,
+    Output,
+    Switch,
+    Button,
     BlueprintSelect,
     Clipboard,
     Settings,
 }
 
+impl std::fmt::Display for ButtonIconId {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pen => "pen",
+            Self::Erase => "erase",
+            Self::Edit => "edit",
+            Self::Interact => "interact",
+            Self::Or => "or",
+            Self::And => "and",
+            Self::Nor => "nor",
+            Self::Xor => "xor",
+            Self::Resistor => "resistor",
+            Self::Capacitor => "capacitor",
+            Self::Led => "led",
+            Self::Delay => "delay",
+            Self::Battery => "battery",
+            Self::Ic => "ic",
+            Self::Split => "split",
+            Self::Merge => "merge",
+            Self::Input => "input",
+            Self::Output => "output",
+            Self::Switch => "switch",
+            Self::Button => "button",
+            Self::BlueprintSelect => "blueprint_select",
+            Self::Clipboard => "clipboard",
+            Self::Settings => "settings",
+        }
+        .fmt(f)
+    }
+}
+
+impl std::str::FromStr for ButtonIconId {
+    type Err = ();
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pen" => Ok(Self::Pen),
+            "erase" => Ok(Self::Erase),
+            "edit" => Ok(Self::Edit),
+            "interact" => Ok(Self::Interact),
+            "or" => Ok(Self::Or),
+            "and" => Ok(Self::And),
+            "nor" => Ok(Self::Nor),
+            "xor" => Ok(Self::Xor),
+            "resistor" => Ok(Self::Resistor),
+            "capacitor" => Ok(Self::Capacitor),
+            "led" => Ok(Self::Led),
+            "delay" => Ok(Self::Delay),
+            "battery" => Ok(Self::Battery),
+            "ic" => Ok(Self::Ic),
+            "split" => Ok(Self::Split),
+            "merge" => Ok(Self::Merge),
+            "input" => Ok(Self::Input),
+            "output" => Ok(Self::Output),
+            "switch" => Ok(Self::Switch),
+            "button" => Ok(Self::Button),
+            "blueprint_select" => Ok(Self::BlueprintSelect),
+            "clipboard" => Ok(Self::Clipboard),
+            "settings" => Ok(Self::Settings),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<GateId> for ButtonIconId {
+    #[inline]
+    fn from(value: GateId) -> Self {
+        match value {
+            GateId::Or => Self::Or,
+            GateId::And => Self::And,
+            GateId::Nor => Self::Nor,
+            GateId::Xor => Self::Xor,
+            GateId::Resistor => Self::Resistor,
+            GateId::Capacitor => Self::Capacitor,
+            GateId::Led => Self::Led,
+            GateId::Delay => Self::Delay,
+            GateId::Battery => Self::Battery,
+            GateId::Ic => Self::Ic,
+            GateId::Split => Self::Split,
+            GateId::Merge => Self::Merge,
+            GateId::Input => Self::Input,
+            GateId::Output => Self::Output,
+            GateId::Switch => Self::Switch,
+            GateId::Button => Self::Button,
+        }
+    }
+}
+
 impl ButtonIconId {
+    /// The icon of the tool's button in the toolpane, if it has one.
+    pub const fn of_tool(tool: ToolId) -> Option<Self> {
+        match tool {
+            ToolId::Create => Some(Self::Pen),
+            ToolId::Erase => Some(Self::Erase),
+            ToolId::Edit => Some(Self::Edit),
+            ToolId::Interact => Some(Self::Interact),
+            ToolId::Annotate => None,
+        }
+    }
+
     pub const fn icon_cell(self) -> IVec2 {
         match self {
             Self::Pen => IVec2::new(2, 0),
@@ -179,6 +325,13 @@ impl ButtonIconId {
             Self::Led => IVec2::new(0, 3),
             Self::Delay => IVec2::new(1, 3),
             Self::Battery => IVec2::new(0, 4),
+            Self::Ic => IVec2::new(1, 4),
+            Self::Split => IVec2::new(2, 4),
+            Self::Merge => IVec2::new(3, 4),
+            Self::Input => IVec2::new(0, 5),
+            Self::Output => IVec2::new(1, 5),
+            Self::Switch => IVec2::new(2, 5),
+            Self::Button => IVec2::new(3, 5),
             Self::BlueprintSelect => IVec2::new(2, 2),
             Self::Clipboard => IVec2::new(3, 2),
             Self::Settings => IVec2::new(2, 3),
@@ -1,12 +1,24 @@
-use crate::{graph::node::GateId, tool::ToolId, ui::Visibility};
+use crate::{
+    clipboard::ClipboardAction, file::FileAction, graph::node::GateId, ivec::IVec2, keybinds,
+    tab::BookmarkAction, tool::ToolId, ui::Visibility,
+};
 use raylib::prelude::*;
 use rl_input::{
     AxisSource, BoolSource, Event, EventCombo, EventSource, SelectorItem, SelectorSource, Source,
-    VectorSource,
+    TouchGesture, TouchSource, VectorSource,
 };
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Vector2")]
+struct Vector2Def {
+    x: f32,
+    y: f32,
+}
+
+/// The bindings as read on one frame, serializable so that an
+/// [`InputMacro`](crate::input_macro::InputMacro) can record them.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 pub struct Inputs {
     pub primary: Event,
     pub secondary: Event,
@@ -14,8 +26,12 @@ pub struct Inputs {
     pub parallel: Event,
     pub zoom: f32,
     pub scroll_console: f32,
+    #[serde(with = "Vector2Def")]
     pub cursor: Vector2,
+    #[serde(with = "Vector2Def")]
     pub pan: Vector2,
+    #[serde(with = "Vector2Def")]
+    pub resize_panel: Vector2,
     pub or_gate_hotkey: Event,
     pub and_gate_hotkey: Event,
     pub nor_gate_hotkey: Event,
@@ -32,9 +48,143 @@ pub struct Inputs {
     pub hide_toolpane: Event,
     pub collapse_toolpane: Event,
     pub expand_toolpane: Event,
+    pub save: Event,
+    pub save_as: Event,
+    pub open: Event,
+    pub paste_blueprint: Event,
+    pub toggle_eval_mode: Event,
+    pub copy: Event,
+    pub cut: Event,
+    pub paste: Event,
+    pub next_island: Event,
+    pub rotate_node: Event,
+    pub cycle_elbow: Event,
+    pub close_tab: Event,
+    pub open_view: Event,
+    pub open_scratch: Event,
+    pub toggle_mute: Event,
+    pub toggle_camera_sync: Event,
+    pub toggle_recording: Event,
+    pub replay_back: Event,
+    pub replay_forward: Event,
+    pub toggle_eval_order_overlay: Event,
+    pub actual_size: Event,
+    pub zoom_in_preset: Event,
+    pub zoom_out_preset: Event,
+    pub set_bookmark_1: Event,
+    pub set_bookmark_2: Event,
+    pub set_bookmark_3: Event,
+    pub set_bookmark_4: Event,
+    pub set_bookmark_5: Event,
+    pub set_bookmark_6: Event,
+    pub set_bookmark_7: Event,
+    pub set_bookmark_8: Event,
+    pub set_bookmark_9: Event,
+    pub jump_bookmark_1: Event,
+    pub jump_bookmark_2: Event,
+    pub jump_bookmark_3: Event,
+    pub jump_bookmark_4: Event,
+    pub jump_bookmark_5: Event,
+    pub jump_bookmark_6: Event,
+    pub jump_bookmark_7: Event,
+    pub jump_bookmark_8: Event,
+    pub jump_bookmark_9: Event,
+    pub toggle_console: Event,
+    pub toggle_properties: Event,
+    pub gate_wheel: Event,
+    pub toggle_profiler: Event,
+    pub key_cursor_up: Event,
+    pub key_cursor_down: Event,
+    pub key_cursor_left: Event,
+    pub key_cursor_right: Event,
+    pub place_node: Event,
+    pub cycle_nodes: Event,
+    pub stamp: Event,
+    pub toggle_auto_route: Event,
+    pub toggle_lock: Event,
 }
 
 impl Inputs {
+    /// Every [`Event`], in the order of [`Bindings::events_mut`].
+    pub fn events_mut(&mut self) -> [&mut Event; 74] {
+        [
+            &mut self.primary,
+            &mut self.secondary,
+            &mut self.alternate,
+            &mut self.parallel,
+            &mut self.or_gate_hotkey,
+            &mut self.and_gate_hotkey,
+            &mut self.nor_gate_hotkey,
+            &mut self.xor_gate_hotkey,
+            &mut self.resistor_gate_hotkey,
+            &mut self.capacitor_gate_hotkey,
+            &mut self.led_gate_hotkey,
+            &mut self.delay_gate_hotkey,
+            &mut self.battery_gate_hotkey,
+            &mut self.create_tool_hotkey,
+            &mut self.erase_tool_hotkey,
+            &mut self.edit_tool_hotkey,
+            &mut self.interact_tool_hotkey,
+            &mut self.hide_toolpane,
+            &mut self.collapse_toolpane,
+            &mut self.expand_toolpane,
+            &mut self.save,
+            &mut self.save_as,
+            &mut self.open,
+            &mut self.paste_blueprint,
+            &mut self.toggle_eval_mode,
+            &mut self.copy,
+            &mut self.cut,
+            &mut self.paste,
+            &mut self.next_island,
+            &mut self.rotate_node,
+            &mut self.cycle_elbow,
+            &mut self.close_tab,
+            &mut self.open_view,
+            &mut self.open_scratch,
+            &mut self.toggle_mute,
+            &mut self.toggle_camera_sync,
+            &mut self.toggle_recording,
+            &mut self.replay_back,
+            &mut self.replay_forward,
+            &mut self.toggle_eval_order_overlay,
+            &mut self.actual_size,
+            &mut self.zoom_in_preset,
+            &mut self.zoom_out_preset,
+            &mut self.set_bookmark_1,
+            &mut self.set_bookmark_2,
+            &mut self.set_bookmark_3,
+            &mut self.set_bookmark_4,
+            &mut self.set_bookmark_5,
+            &mut self.set_bookmark_6,
+            &mut self.set_bookmark_7,
+            &mut self.set_bookmark_8,
+            &mut self.set_bookmark_9,
+            &mut self.jump_bookmark_1,
+            &mut self.jump_bookmark_2,
+            &mut self.jump_bookmark_3,
+            &mut self.jump_bookmark_4,
+            &mut self.jump_bookmark_5,
+            &mut self.jump_bookmark_6,
+            &mut self.jump_bookmark_7,
+            &mut self.jump_bookmark_8,
+            &mut self.jump_bookmark_9,
+            &mut self.toggle_console,
+            &mut self.toggle_properties,
+            &mut self.gate_wheel,
+            &mut self.toggle_profiler,
+            &mut self.key_cursor_up,
+            &mut self.key_cursor_down,
+            &mut self.key_cursor_left,
+            &mut self.key_cursor_right,
+            &mut self.place_node,
+            &mut self.cycle_nodes,
+            &mut self.stamp,
+            &mut self.toggle_auto_route,
+            &mut self.toggle_lock,
+        ]
+    }
+
     pub fn gate(&self) -> Option<GateId> {
         [
             (self.or_gate_hotkey, GateId::Or),
@@ -64,6 +214,19 @@ impl Inputs {
         .map(|(_, tool)| *tool)
     }
 
+    /// The cell the key cursor is being moved by, if any.
+    pub fn key_cursor_step(&self) -> Option<IVec2> {
+        [
+            (self.key_cursor_up, IVec2::new(0, -1)),
+            (self.key_cursor_down, IVec2::new(0, 1)),
+            (self.key_cursor_left, IVec2::new(-1, 0)),
+            (self.key_cursor_right, IVec2::new(1, 0)),
+        ]
+        .iter()
+        .find(|(src, _)| src.is_starting())
+        .map(|(_, step)| *step)
+    }
+
     pub fn toolpane_vis(&self) -> Option<Visibility> {
         [
             (self.hide_toolpane, Visibility::Hidden),
@@ -74,9 +237,66 @@ impl Inputs {
         .find(|(src, _)| src.is_starting())
         .map(|(_, vis)| *vis)
     }
+
+    pub fn file_action(&self) -> Option<FileAction> {
+        [
+            // save_as is checked first because its default binding is a superset of save's
+            (self.save_as, FileAction::SaveAs),
+            (self.save, FileAction::Save),
+            (self.open, FileAction::Open),
+        ]
+        .iter()
+        .find(|(src, _)| src.is_starting())
+        .map(|(_, action)| *action)
+    }
+
+    /// Which bookmark slot to set or jump to, counting from 0.
+    pub fn bookmark(&self) -> Option<(BookmarkAction, usize)> {
+        let set = [
+            self.set_bookmark_1,
+            self.set_bookmark_2,
+            self.set_bookmark_3,
+            self.set_bookmark_4,
+            self.set_bookmark_5,
+            self.set_bookmark_6,
+            self.set_bookmark_7,
+            self.set_bookmark_8,
+            self.set_bookmark_9,
+        ]
+        .iter()
+        .position(|src| src.is_starting())
+        .map(|slot| (BookmarkAction::Set, slot));
+        let jump = [
+            self.jump_bookmark_1,
+            self.jump_bookmark_2,
+            self.jump_bookmark_3,
+            self.jump_bookmark_4,
+            self.jump_bookmark_5,
+            self.jump_bookmark_6,
+            self.jump_bookmark_7,
+            self.jump_bookmark_8,
+            self.jump_bookmark_9,
+        ]
+        .iter()
+        .position(|src| src.is_starting())
+        .map(|slot| (BookmarkAction::Jump, slot));
+        set.or(jump)
+    }
+
+    pub fn clipboard_action(&self) -> Option<ClipboardAction> {
+        [
+            (self.copy, ClipboardAction::Copy),
+            (self.cut, ClipboardAction::Cut),
+            (self.paste, ClipboardAction::Paste),
+        ]
+        .iter()
+        .find(|(src, _)| src.is_starting())
+        .map(|(_, action)| *action)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Bindings {
     pub primary: EventSource,
     pub secondary: EventSource,
@@ -86,6 +306,7 @@ pub struct Bindings {
     pub scroll_console: AxisSource,
     pub cursor: VectorSource,
     pub pan: VectorSource,
+    pub resize_panel: VectorSource,
     pub or_gate_hotkey: EventSource,
     pub and_gate_hotkey: EventSource,
     pub nor_gate_hotkey: EventSource,
@@ -102,6 +323,63 @@ pub struct Bindings {
     pub hide_toolpane: EventSource,
     pub collapse_toolpane: EventSource,
     pub expand_toolpane: EventSource,
+    pub save: EventSource,
+    pub save_as: EventSource,
+    pub open: EventSource,
+    pub paste_blueprint: EventSource,
+    pub toggle_eval_mode: EventSource,
+    pub copy: EventSource,
+    pub cut: EventSource,
+    pub paste: EventSource,
+    pub next_island: EventSource,
+    pub rotate_node: EventSource,
+    pub cycle_elbow: EventSource,
+    pub close_tab: EventSource,
+    pub open_view: EventSource,
+    pub open_scratch: EventSource,
+    pub toggle_mute: EventSource,
+    pub toggle_camera_sync: EventSource,
+    pub toggle_recording: EventSource,
+    pub replay_back: EventSource,
+    pub replay_forward: EventSource,
+    pub toggle_eval_order_overlay: EventSource,
+    pub actual_size: EventSource,
+    pub zoom_in_preset: EventSource,
+    pub zoom_out_preset: EventSource,
+    pub set_bookmark_1: EventSource,
+    pub set_bookmark_2: EventSource,
+    pub set_bookmark_3: EventSource,
+    pub set_bookmark_4: EventSource,
+    pub set_bookmark_5: EventSource,
+    pub set_bookmark_6: EventSource,
+    pub set_bookmark_7: EventSource,
+    pub set_bookmark_8: EventSource,
+    pub set_bookmark_9: EventSource,
+    pub jump_bookmark_1: EventSource,
+    pub jump_bookmark_2: EventSource,
+    pub jump_bookmark_3: EventSource,
+    pub jump_bookmark_4: EventSource,
+    pub jump_bookmark_5: EventSource,
+    pub jump_bookmark_6: EventSource,
+    pub jump_bookmark_7: EventSource,
+    pub jump_bookmark_8: EventSource,
+    pub jump_bookmark_9: EventSource,
+    pub toggle_console: EventSource,
+    pub toggle_properties: EventSource,
+    pub gate_wheel: EventSource,
+    pub toggle_profiler: EventSource,
+    pub key_cursor_up: EventSource,
+    pub key_cursor_down: EventSource,
+    pub key_cursor_left: EventSource,
+    pub key_cursor_right: EventSource,
+    pub place_node: EventSource,
+    pub cycle_nodes: EventSource,
+    pub stamp: EventSource,
+    pub toggle_auto_route: EventSource,
+    pub toggle_lock: EventSource,
+    /// [`keybinds::shadowed`], found again after the bindings may have changed
+    #[serde(skip)]
+    shadowed: Option<Vec<(usize, usize)>>,
 }
 
 impl Default for Bindings {
@@ -110,37 +388,140 @@ impl Default for Bindings {
         use MouseButton::*;
         Self {
             primary: EventSource::Mouse(MOUSE_BUTTON_LEFT),
-            secondary: EventSource::Mouse(MOUSE_BUTTON_RIGHT),
+            secondary: EventSource::Combo(EventCombo::Any(Box::from([
+                EventSource::Mouse(MOUSE_BUTTON_RIGHT),
+                EventSource::Touch(TouchSource::new(TouchGesture::LongPress)),
+            ]))),
             alternate: EventSource::Keyboard(KEY_LEFT_CONTROL),
             parallel: EventSource::Keyboard(KEY_LEFT_SHIFT),
-            zoom: AxisSource::MouseWheelMove,
+            zoom: AxisSource::Sum(Box::from([
+                // ctrl+wheel zooms, leaving the wheel on its own to scroll
+                AxisSource::Gated {
+                    src: Box::new(AxisSource::MouseWheelMove),
+                    when: EventSource::Combo(EventCombo::Any(Box::from([
+                        EventSource::Keyboard(KEY_LEFT_CONTROL),
+                        EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                    ]))),
+                },
+                AxisSource::TouchPinch(None),
+            ])),
             scroll_console: AxisSource::MouseWheelMove,
             cursor: VectorSource::MousePosition,
-            pan: VectorSource::EventMix(SelectorSource::from([
+            pan: VectorSource::Sum(Box::from([
+                // the graph follows two fingers dragged across it, undoing the editor's pan speed
+                VectorSource::Prod(Box::from([
+                    VectorSource::TouchDelta(None),
+                    VectorSource::Constant(Vector2::new(-0.2, -0.2)),
+                ])),
+                // the wheel scrolls vertically, or horizontally where the wheel or trackpad can
+                VectorSource::Gated {
+                    src: Box::new(VectorSource::Prod(Box::from([
+                        VectorSource::MouseWheelMoveV,
+                        VectorSource::Constant(Vector2::new(-8.0, -8.0)),
+                    ]))),
+                    when: EventSource::Combo(EventCombo::Not(Box::new(EventSource::Combo(
+                        EventCombo::Any(Box::from([
+                            EventSource::Keyboard(KEY_LEFT_CONTROL),
+                            EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                            EventSource::Keyboard(KEY_LEFT_SHIFT),
+                            EventSource::Keyboard(KEY_RIGHT_SHIFT),
+                        ])),
+                    )))),
+                },
+                // shift+wheel scrolls horizontally
+                VectorSource::Gated {
+                    src: Box::new(VectorSource::AxisXY {
+                        x: AxisSource::Prod(Box::from([
+                            AxisSource::MouseWheelMove,
+                            AxisSource::Constant(-8.0),
+                        ])),
+                        y: AxisSource::Constant(0.0),
+                    }),
+                    when: EventSource::Combo(EventCombo::Any(Box::from([
+                        EventSource::Keyboard(KEY_LEFT_SHIFT),
+                        EventSource::Keyboard(KEY_RIGHT_SHIFT),
+                    ]))),
+                },
+                VectorSource::EventMix(SelectorSource::from([
+                    SelectorItem {
+                        src: BoolSource::Event {
+                            what: EventSource::Keyboard(KEY_D),
+                            when: Event::Active,
+                        },
+                        val: VectorSource::Constant(rvec2(1, 0)),
+                    },
+                    SelectorItem {
+                        src: BoolSource::Event {
+                            what: EventSource::Keyboard(KEY_A),
+                            when: Event::Active,
+                        },
+                        val: VectorSource::Constant(rvec2(-1, 0)),
+                    },
+                    SelectorItem {
+                        src: BoolSource::Event {
+                            what: EventSource::Keyboard(KEY_W),
+                            when: Event::Active,
+                        },
+                        val: VectorSource::Constant(rvec2(0, -1)),
+                    },
+                    SelectorItem {
+                        src: BoolSource::Event {
+                            what: EventSource::Keyboard(KEY_S),
+                            when: Event::Active,
+                        },
+                        val: VectorSource::Constant(rvec2(0, 1)),
+                    },
+                ])),
+            ])),
+            resize_panel: VectorSource::EventMix(SelectorSource::from([
                 SelectorItem {
                     src: BoolSource::Event {
-                        what: EventSource::Keyboard(KEY_D),
+                        what: EventSource::Combo(EventCombo::All(Box::from([
+                            EventSource::Combo(EventCombo::Any(Box::from([
+                                EventSource::Keyboard(KEY_LEFT_CONTROL),
+                                EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                            ]))),
+                            EventSource::Keyboard(KEY_RIGHT),
+                        ]))),
                         when: Event::Active,
                     },
                     val: VectorSource::Constant(rvec2(1, 0)),
                 },
                 SelectorItem {
                     src: BoolSource::Event {
-                        what: EventSource::Keyboard(KEY_A),
+                        what: EventSource::Combo(EventCombo::All(Box::from([
+                            EventSource::Combo(EventCombo::Any(Box::from([
+                                EventSource::Keyboard(KEY_LEFT_CONTROL),
+                                EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                            ]))),
+                            EventSource::Keyboard(KEY_LEFT),
+                        ]))),
                         when: Event::Active,
                     },
                     val: VectorSource::Constant(rvec2(-1, 0)),
                 },
                 SelectorItem {
                     src: BoolSource::Event {
-                        what: EventSource::Keyboard(KEY_W),
+                        what: EventSource::Combo(EventCombo::All(Box::from([
+                            EventSource::Combo(EventCombo::Any(Box::from([
+                                EventSource::Keyboard(KEY_LEFT_CONTROL),
+                                EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                            ]))),
+                            EventSource::Keyboard(KEY_UP),
+                        ]))),
                         when: Event::Active,
                     },
                     val: VectorSource::Constant(rvec2(0, -1)),
                 },
                 SelectorItem {
                     src: BoolSource::Event {
-                        what: EventSource::Keyboard(KEY_S),
+                        what: EventSource::Combo(EventCombo::All(Box::from([
+                            EventSource::Combo(EventCombo::Any(Box::from([
+                                EventSource::Keyboard(KEY_LEFT_CONTROL),
+                                EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                            ]))),
+                            EventSource::Keyboard(KEY_DOWN),
+                        ]))),
                         when: Event::Active,
                     },
                     val: VectorSource::Constant(rvec2(0, 1)),
@@ -180,13 +561,427 @@ impl Default for Bindings {
                 ]))),
                 EventSource::Keyboard(KEY_B),
             ]))),
+            save: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_S),
+            ]))),
+            save_as: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_SHIFT),
+                    EventSource::Keyboard(KEY_RIGHT_SHIFT),
+                ]))),
+                EventSource::Keyboard(KEY_S),
+            ]))),
+            open: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_O),
+            ]))),
+            paste_blueprint: EventSource::Keyboard(KEY_P),
+            toggle_eval_mode: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_E),
+            ]))),
+            copy: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_C),
+            ]))),
+            cut: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_X),
+            ]))),
+            paste: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_V),
+            ]))),
+            next_island: EventSource::Keyboard(KEY_I),
+            rotate_node: EventSource::Keyboard(KEY_R),
+            cycle_elbow: EventSource::Keyboard(KEY_Q),
+            close_tab: EventSource::Mouse(MOUSE_BUTTON_MIDDLE),
+            open_view: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_T),
+            ]))),
+            open_scratch: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_N),
+            ]))),
+            toggle_mute: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_M),
+            ]))),
+            toggle_camera_sync: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_L),
+            ]))),
+            toggle_recording: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_R),
+            ]))),
+            replay_back: EventSource::Keyboard(KEY_COMMA),
+            replay_forward: EventSource::Keyboard(KEY_PERIOD),
+            toggle_eval_order_overlay: EventSource::Keyboard(KEY_F3),
+            actual_size: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_ZERO),
+            ]))),
+            zoom_in_preset: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_EQUAL),
+            ]))),
+            zoom_out_preset: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_MINUS),
+            ]))),
+            set_bookmark_1: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_ONE),
+            ]))),
+            set_bookmark_2: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_TWO),
+            ]))),
+            set_bookmark_3: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_THREE),
+            ]))),
+            set_bookmark_4: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_FOUR),
+            ]))),
+            set_bookmark_5: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_FIVE),
+            ]))),
+            set_bookmark_6: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_SIX),
+            ]))),
+            set_bookmark_7: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_SEVEN),
+            ]))),
+            set_bookmark_8: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_EIGHT),
+            ]))),
+            set_bookmark_9: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_NINE),
+            ]))),
+            jump_bookmark_1: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_ALT),
+                    EventSource::Keyboard(KEY_RIGHT_ALT),
+                ]))),
+                EventSource::Keyboard(KEY_ONE),
+            ]))),
+            jump_bookmark_2: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_ALT),
+                    EventSource::Keyboard(KEY_RIGHT_ALT),
+                ]))),
+                EventSource::Keyboard(KEY_TWO),
+            ]))),
+            jump_bookmark_3: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_ALT),
+                    EventSource::Keyboard(KEY_RIGHT_ALT),
+                ]))),
+                EventSource::Keyboard(KEY_THREE),
+            ]))),
+            jump_bookmark_4: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_ALT),
+                    EventSource::Keyboard(KEY_RIGHT_ALT),
+                ]))),
+                EventSource::Keyboard(KEY_FOUR),
+            ]))),
+            jump_bookmark_5: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_ALT),
+                    EventSource::Keyboard(KEY_RIGHT_ALT),
+                ]))),
+                EventSource::Keyboard(KEY_FIVE),
+            ]))),
+            jump_bookmark_6: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_ALT),
+                    EventSource::Keyboard(KEY_RIGHT_ALT),
+                ]))),
+                EventSource::Keyboard(KEY_SIX),
+            ]))),
+            jump_bookmark_7: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_ALT),
+                    EventSource::Keyboard(KEY_RIGHT_ALT),
+                ]))),
+                EventSource::Keyboard(KEY_SEVEN),
+            ]))),
+            jump_bookmark_8: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_ALT),
+                    EventSource::Keyboard(KEY_RIGHT_ALT),
+                ]))),
+                EventSource::Keyboard(KEY_EIGHT),
+            ]))),
+            jump_bookmark_9: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_ALT),
+                    EventSource::Keyboard(KEY_RIGHT_ALT),
+                ]))),
+                EventSource::Keyboard(KEY_NINE),
+            ]))),
+            toggle_console: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_GRAVE),
+            ]))),
+            toggle_properties: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_I),
+            ]))),
+            gate_wheel: EventSource::Keyboard(KEY_TAB),
+            toggle_profiler: EventSource::Keyboard(KEY_F12),
+            // ctrl+arrows resize panels
+            key_cursor_up: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Not(Box::new(EventSource::Combo(
+                    EventCombo::Any(Box::from([
+                        EventSource::Keyboard(KEY_LEFT_CONTROL),
+                        EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                    ])),
+                )))),
+                EventSource::Keyboard(KEY_UP),
+            ]))),
+            key_cursor_down: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Not(Box::new(EventSource::Combo(
+                    EventCombo::Any(Box::from([
+                        EventSource::Keyboard(KEY_LEFT_CONTROL),
+                        EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                    ])),
+                )))),
+                EventSource::Keyboard(KEY_DOWN),
+            ]))),
+            key_cursor_left: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Not(Box::new(EventSource::Combo(
+                    EventCombo::Any(Box::from([
+                        EventSource::Keyboard(KEY_LEFT_CONTROL),
+                        EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                    ])),
+                )))),
+                EventSource::Keyboard(KEY_LEFT),
+            ]))),
+            key_cursor_right: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Not(Box::new(EventSource::Combo(
+                    EventCombo::Any(Box::from([
+                        EventSource::Keyboard(KEY_LEFT_CONTROL),
+                        EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                    ])),
+                )))),
+                EventSource::Keyboard(KEY_RIGHT),
+            ]))),
+            place_node: EventSource::Combo(EventCombo::Any(Box::from([
+                EventSource::Keyboard(KEY_ENTER),
+                EventSource::Keyboard(KEY_KP_ENTER),
+            ]))),
+            // shares its key with the gate wheel, which only opens while the key cursor is hidden
+            cycle_nodes: EventSource::Keyboard(KEY_TAB),
+            stamp: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_D),
+            ]))),
+            toggle_auto_route: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_SHIFT),
+                    EventSource::Keyboard(KEY_RIGHT_SHIFT),
+                ]))),
+                EventSource::Keyboard(KEY_Q),
+            ]))),
+            toggle_lock: EventSource::Combo(EventCombo::All(Box::from([
+                EventSource::Combo(EventCombo::Any(Box::from([
+                    EventSource::Keyboard(KEY_LEFT_CONTROL),
+                    EventSource::Keyboard(KEY_RIGHT_CONTROL),
+                ]))),
+                EventSource::Keyboard(KEY_K),
+            ]))),
+            shadowed: None,
         }
     }
 }
 
 impl Bindings {
+    /// Inputs that are not a single [`EventSource`], which can only be bound in the config.
+    pub const NON_EVENTS: [&str; 5] = ["zoom", "scroll_console", "cursor", "pan", "resize_panel"];
+
+    /// Every [`EventSource`] binding, named as in the config.
+    pub fn events_mut(&mut self) -> [(&'static str, &mut EventSource); 74] {
+        // any of them could be rebound
+        self.shadowed = None;
+        [
+            ("primary", &mut self.primary),
+            ("secondary", &mut self.secondary),
+            ("alternate", &mut self.alternate),
+            ("parallel", &mut self.parallel),
+            ("or_gate_hotkey", &mut self.or_gate_hotkey),
+            ("and_gate_hotkey", &mut self.and_gate_hotkey),
+            ("nor_gate_hotkey", &mut self.nor_gate_hotkey),
+            ("xor_gate_hotkey", &mut self.xor_gate_hotkey),
+            ("resistor_gate_hotkey", &mut self.resistor_gate_hotkey),
+            ("capacitor_gate_hotkey", &mut self.capacitor_gate_hotkey),
+            ("led_gate_hotkey", &mut self.led_gate_hotkey),
+            ("delay_gate_hotkey", &mut self.delay_gate_hotkey),
+            ("battery_gate_hotkey", &mut self.battery_gate_hotkey),
+            ("create_tool_hotkey", &mut self.create_tool_hotkey),
+            ("erase_tool_hotkey", &mut self.erase_tool_hotkey),
+            ("edit_tool_hotkey", &mut self.edit_tool_hotkey),
+            ("interact_tool_hotkey", &mut self.interact_tool_hotkey),
+            ("hide_toolpane", &mut self.hide_toolpane),
+            ("collapse_toolpane", &mut self.collapse_toolpane),
+            ("expand_toolpane", &mut self.expand_toolpane),
+            ("save", &mut self.save),
+            ("save_as", &mut self.save_as),
+            ("open", &mut self.open),
+            ("paste_blueprint", &mut self.paste_blueprint),
+            ("toggle_eval_mode", &mut self.toggle_eval_mode),
+            ("copy", &mut self.copy),
+            ("cut", &mut self.cut),
+            ("paste", &mut self.paste),
+            ("next_island", &mut self.next_island),
+            ("rotate_node", &mut self.rotate_node),
+            ("cycle_elbow", &mut self.cycle_elbow),
+            ("close_tab", &mut self.close_tab),
+            ("open_view", &mut self.open_view),
+            ("open_scratch", &mut self.open_scratch),
+            ("toggle_mute", &mut self.toggle_mute),
+            ("toggle_camera_sync", &mut self.toggle_camera_sync),
+            ("toggle_recording", &mut self.toggle_recording),
+            ("replay_back", &mut self.replay_back),
+            ("replay_forward", &mut self.replay_forward),
+            (
+                "toggle_eval_order_overlay",
+                &mut self.toggle_eval_order_overlay,
+            ),
+            ("actual_size", &mut self.actual_size),
+            ("zoom_in_preset", &mut self.zoom_in_preset),
+            ("zoom_out_preset", &mut self.zoom_out_preset),
+            ("set_bookmark_1", &mut self.set_bookmark_1),
+            ("set_bookmark_2", &mut self.set_bookmark_2),
+            ("set_bookmark_3", &mut self.set_bookmark_3),
+            ("set_bookmark_4", &mut self.set_bookmark_4),
+            ("set_bookmark_5", &mut self.set_bookmark_5),
+            ("set_bookmark_6", &mut self.set_bookmark_6),
+            ("set_bookmark_7", &mut self.set_bookmark_7),
+            ("set_bookmark_8", &mut self.set_bookmark_8),
+            ("set_bookmark_9", &mut self.set_bookmark_9),
+            ("jump_bookmark_1", &mut self.jump_bookmark_1),
+            ("jump_bookmark_2", &mut self.jump_bookmark_2),
+            ("jump_bookmark_3", &mut self.jump_bookmark_3),
+            ("jump_bookmark_4", &mut self.jump_bookmark_4),
+            ("jump_bookmark_5", &mut self.jump_bookmark_5),
+            ("jump_bookmark_6", &mut self.jump_bookmark_6),
+            ("jump_bookmark_7", &mut self.jump_bookmark_7),
+            ("jump_bookmark_8", &mut self.jump_bookmark_8),
+            ("jump_bookmark_9", &mut self.jump_bookmark_9),
+            ("toggle_console", &mut self.toggle_console),
+            ("toggle_properties", &mut self.toggle_properties),
+            ("gate_wheel", &mut self.gate_wheel),
+            ("toggle_profiler", &mut self.toggle_profiler),
+            ("key_cursor_up", &mut self.key_cursor_up),
+            ("key_cursor_down", &mut self.key_cursor_down),
+            ("key_cursor_left", &mut self.key_cursor_left),
+            ("key_cursor_right", &mut self.key_cursor_right),
+            ("place_node", &mut self.place_node),
+            ("cycle_nodes", &mut self.cycle_nodes),
+            ("stamp", &mut self.stamp),
+            ("toggle_auto_route", &mut self.toggle_auto_route),
+            ("toggle_lock", &mut self.toggle_lock),
+        ]
+    }
+
+    /// Reads every binding. Inputs whose keys are part of a longer chord that is held are left
+    /// inactive, so that `ctrl + r` doesn't also trigger whatever `r` is bound to.
     pub fn get_all(&mut self, rl: &RaylibHandle) -> Inputs {
-        Inputs {
+        let mut inputs = Inputs {
             primary: self.primary.get(rl),
             secondary: self.secondary.get(rl),
             alternate: self.alternate.get(rl),
@@ -195,6 +990,7 @@ impl Bindings {
             scroll_console: self.scroll_console.get(rl),
             cursor: self.cursor.get(rl),
             pan: self.pan.get(rl),
+            resize_panel: self.resize_panel.get(rl),
             or_gate_hotkey: self.or_gate_hotkey.get(rl),
             and_gate_hotkey: self.and_gate_hotkey.get(rl),
             nor_gate_hotkey: self.nor_gate_hotkey.get(rl),
@@ -211,6 +1007,73 @@ impl Bindings {
             hide_toolpane: self.hide_toolpane.get(rl),
             collapse_toolpane: self.collapse_toolpane.get(rl),
             expand_toolpane: self.expand_toolpane.get(rl),
+            save: self.save.get(rl),
+            save_as: self.save_as.get(rl),
+            open: self.open.get(rl),
+            paste_blueprint: self.paste_blueprint.get(rl),
+            toggle_eval_mode: self.toggle_eval_mode.get(rl),
+            copy: self.copy.get(rl),
+            cut: self.cut.get(rl),
+            paste: self.paste.get(rl),
+            next_island: self.next_island.get(rl),
+            rotate_node: self.rotate_node.get(rl),
+            cycle_elbow: self.cycle_elbow.get(rl),
+            close_tab: self.close_tab.get(rl),
+            open_view: self.open_view.get(rl),
+            open_scratch: self.open_scratch.get(rl),
+            toggle_mute: self.toggle_mute.get(rl),
+            toggle_camera_sync: self.toggle_camera_sync.get(rl),
+            toggle_recording: self.toggle_recording.get(rl),
+            replay_back: self.replay_back.get(rl),
+            replay_forward: self.replay_forward.get(rl),
+            toggle_eval_order_overlay: self.toggle_eval_order_overlay.get(rl),
+            actual_size: self.actual_size.get(rl),
+            zoom_in_preset: self.zoom_in_preset.get(rl),
+            zoom_out_preset: self.zoom_out_preset.get(rl),
+            set_bookmark_1: self.set_bookmark_1.get(rl),
+            set_bookmark_2: self.set_bookmark_2.get(rl),
+            set_bookmark_3: self.set_bookmark_3.get(rl),
+            set_bookmark_4: self.set_bookmark_4.get(rl),
+            set_bookmark_5: self.set_bookmark_5.get(rl),
+            set_bookmark_6: self.set_bookmark_6.get(rl),
+            set_bookmark_7: self.set_bookmark_7.get(rl),
+            set_bookmark_8: self.set_bookmark_8.get(rl),
+            set_bookmark_9: self.set_bookmark_9.get(rl),
+            jump_bookmark_1: self.jump_bookmark_1.get(rl),
+            jump_bookmark_2: self.jump_bookmark_2.get(rl),
+            jump_bookmark_3: self.jump_bookmark_3.get(rl),
+            jump_bookmark_4: self.jump_bookmark_4.get(rl),
+            jump_bookmark_5: self.jump_bookmark_5.get(rl),
+            jump_bookmark_6: self.jump_bookmark_6.get(rl),
+            jump_bookmark_7: self.jump_bookmark_7.get(rl),
+            jump_bookmark_8: self.jump_bookmark_8.get(rl),
+            jump_bookmark_9: self.jump_bookmark_9.get(rl),
+            toggle_console: self.toggle_console.get(rl),
+            toggle_properties: self.toggle_properties.get(rl),
+            gate_wheel: self.gate_wheel.get(rl),
+            toggle_profiler: self.toggle_profiler.get(rl),
+            key_cursor_up: self.key_cursor_up.get(rl),
+            key_cursor_down: self.key_cursor_down.get(rl),
+            key_cursor_left: self.key_cursor_left.get(rl),
+            key_cursor_right: self.key_cursor_right.get(rl),
+            place_node: self.place_node.get(rl),
+            cycle_nodes: self.cycle_nodes.get(rl),
+            stamp: self.stamp.get(rl),
+            toggle_auto_route: self.toggle_auto_route.get(rl),
+            toggle_lock: self.toggle_lock.get(rl),
+        };
+        let shadowed = match self.shadowed.take() {
+            Some(shadowed) => shadowed,
+            None => keybinds::shadowed(self),
+        };
+        let is_active = inputs.events_mut().map(|event| event.is_active());
+        let mut events = inputs.events_mut();
+        for &(small, big) in &shadowed {
+            if is_active[big] {
+                *events[small] = Event::Inactive;
+            }
         }
+        self.shadowed = Some(shadowed);
+        inputs
     }
 }
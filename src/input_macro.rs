@@ -0,0 +1,364 @@
+//! Recording the [`Inputs`] of every frame to a file and playing them back in place of the real
+//! ones, so that a bug can be reported along with the exact input that causes it, and tools can
+//! be tried out without anyone at the controls.
+//!
+//! While recording or playing, graphs are evaluated every so many frames rather than by the
+//! clock, so that each frame of the playback sees the graphs as they were on that frame of the
+//! recording. Text typed into the console isn't part of [`Inputs`], so it isn't recorded.
+
+use crate::{
+    console::{Console, LogType},
+    input::Inputs,
+    logln,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A recording as written to its file. Only the inputs that changed are written, on the frames
+/// they changed on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    /// Size of the window while recording, since the cursor is in screen space
+    window: (i32, i32),
+    /// Frames from one evaluation tick to the next
+    frames_per_tick: u32,
+    /// Frames recorded, including any after the last change
+    frames: usize,
+    /// In order of frame
+    #[serde(default)]
+    changes: Vec<Change>,
+}
+
+/// The inputs that changed on a frame, by their names in [`Inputs`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    frame: usize,
+    inputs: toml::Table,
+}
+
+#[derive(Debug, Default)]
+pub enum InputMacro {
+    #[default]
+    Idle,
+    Recording {
+        path: PathBuf,
+        recording: Recording,
+        /// The inputs of the last frame, to find what changed on the next
+        last: toml::Table,
+    },
+    Playing {
+        recording: Recording,
+        /// Frames played so far
+        frame: usize,
+        /// Index of the first change not yet played
+        next_change: usize,
+        /// The inputs as of the last change played
+        table: toml::Table,
+        inputs: Option<Inputs>,
+    },
+}
+
+impl InputMacro {
+    #[inline]
+    pub const fn is_idle(&self) -> bool {
+        matches!(self, Self::Idle)
+    }
+
+    #[inline]
+    pub const fn is_playing(&self) -> bool {
+        matches!(self, Self::Playing { .. })
+    }
+
+    /// Whether the graphs should be evaluated on the frame just begun by [`Self::next`], or
+    /// [`None`] if evaluation should follow the clock.
+    pub fn is_tick_frame(&self) -> Option<bool> {
+        let (recording, frames) = match self {
+            Self::Idle => return None,
+            Self::Recording { recording, .. } => (recording, recording.frames),
+            Self::Playing {
+                recording, frame, ..
+            } => (recording, *frame),
+        };
+        Some(frames.saturating_sub(1) % recording.frames_per_tick.max(1) as usize == 0)
+    }
+
+    /// Starts recording, to be written to `path` once stopped. Stops anything already going.
+    pub fn record(
+        &mut self,
+        console: &mut Console,
+        path: PathBuf,
+        window: (i32, i32),
+        frames_per_tick: u32,
+    ) {
+        self.stop(console);
+        logln!(
+            console,
+            LogType::Info,
+            "recording input to {}",
+            path.display()
+        );
+        *self = Self::Recording {
+            path,
+            recording: Recording {
+                window,
+                frames_per_tick,
+                frames: 0,
+                changes: Vec::new(),
+            },
+            last: toml::Table::new(),
+        };
+    }
+
+    /// Starts playing the recording at `path`. Stops anything already going.
+    pub fn play(&mut self, console: &mut Console, path: &Path, window: (i32, i32)) {
+        self.stop(console);
+        let recording = match std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| toml::from_str::<Recording>(&s).map_err(|e| e.to_string()))
+        {
+            Ok(recording) => recording,
+            Err(e) => {
+                logln!(
+                    console,
+                    LogType::Error,
+                    "failed to load input from {}: {e}",
+                    path.display()
+                );
+                return;
+            }
+        };
+        if recording.window != window {
+            let ((width, height), (rec_width, rec_height)) = (window, recording.window);
+            logln!(
+                console,
+                LogType::Warning,
+                "input was recorded in a {rec_width}x{rec_height} window, but the window is {width}x{height}; the cursor may miss what it was pointing at"
+            );
+        }
+        logln!(
+            console,
+            LogType::Info,
+            "playing {} frames of input from {}; press esc to stop",
+            recording.frames,
+            path.display()
+        );
+        *self = Self::Playing {
+            recording,
+            frame: 0,
+            next_change: 0,
+            table: toml::Table::new(),
+            inputs: None,
+        };
+    }
+
+    /// Stops recording or playing, writing out the recording if there is one.
+    pub fn stop(&mut self, console: &mut Console) {
+        match std::mem::take(self) {
+            Self::Idle => {}
+            Self::Recording {
+                path, recording, ..
+            } => {
+                let result = toml::to_string(&recording)
+                    .map_err(std::io::Error::other)
+                    .and_then(|s| std::fs::write(&path, s));
+                match result {
+                    Ok(()) => logln!(
+                        console,
+                        LogType::Success,
+                        "recorded {} frames of input to {}",
+                        recording.frames,
+                        path.display()
+                    ),
+                    Err(e) => logln!(
+                        console,
+                        LogType::Error,
+                        "failed to write input to {}: {e}",
+                        path.display()
+                    ),
+                }
+            }
+            Self::Playing { frame, .. } => {
+                logln!(
+                    console,
+                    LogType::Info,
+                    "stopped playing input at frame {frame}"
+                );
+            }
+        }
+    }
+
+    /// Begins a frame with `live` as read from the bindings, giving the inputs to use for it:
+    /// `live` itself unless playing.
+    pub fn next(&mut self, console: &mut Console, live: Inputs) -> Inputs {
+        match self {
+            Self::Idle => live,
+
+            Self::Recording {
+                recording, last, ..
+            } => {
+                let table = toml::Table::try_from(live).expect("inputs should be serializable");
+                let inputs = table
+                    .iter()
+                    .filter(|&(name, value)| last.get(name) != Some(value))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect::<toml::Table>();
+                if !inputs.is_empty() {
+                    recording.changes.push(Change {
+                        frame: recording.frames,
+                        inputs,
+                    });
+                }
+                *last = table;
+                recording.frames += 1;
+                live
+            }
+
+            Self::Playing {
+                recording,
+                frame,
+                next_change,
+                table,
+                inputs,
+            } => {
+                if *frame >= recording.frames {
+                    logln!(console, LogType::Success, "finished playing input");
+                    *self = Self::Idle;
+                    return live;
+                }
+                let mut is_changed = false;
+                while let Some(change) = recording.changes.get(*next_change)
+                    && change.frame <= *frame
+                {
+                    for (name, value) in &change.inputs {
+                        table.insert(name.clone(), value.clone());
+                    }
+                    *next_change += 1;
+                    is_changed = true;
+                }
+                if is_changed {
+                    match table.clone().try_into::<Inputs>() {
+                        Ok(played) => *inputs = Some(played),
+                        Err(e) => {
+                            logln!(
+                                console,
+                                LogType::Error,
+                                "recorded input is invalid at frame {frame}: {e}"
+                            );
+                            *self = Self::Idle;
+                            return live;
+                        }
+                    }
+                }
+                *frame += 1;
+                inputs.unwrap_or(live)
+            }
+        }
+    }
+}
+
+/// An input macro command, handed back from the console since the macro belongs to the main
+/// loop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroAction {
+    Record(PathBuf),
+    Play(PathBuf),
+    Stop,
+}
+
+impl MacroAction {
+    pub fn run(
+        self,
+        console: &mut Console,
+        input_macro: &mut InputMacro,
+        window: (i32, i32),
+        frames_per_tick: u32,
+    ) {
+        match self {
+            Self::Record(path) => input_macro.record(console, path, window, frames_per_tick),
+            Self::Play(path) => input_macro.play(console, &path, window),
+            Self::Stop if input_macro.is_idle() => {
+                logln!(
+                    console,
+                    LogType::Info,
+                    "no input is being recorded or played"
+                );
+            }
+            Self::Stop => input_macro.stop(console),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::{Anchoring, Padding, Panel};
+    use raylib::prelude::Vector2;
+    use rl_input::Event;
+
+    #[test]
+    fn test_record_and_play() {
+        let mut console = Console::new(
+            Panel::new("", Anchoring::Fill, |_| Padding::amount(0.0)),
+            4096,
+        );
+        let path = std::env::temp_dir().join(format!("input-macro-{}.toml", std::process::id()));
+        let frames = [
+            Inputs::default(),
+            Inputs {
+                primary: Event::Starting,
+                cursor: Vector2::new(10.0, 20.0),
+                ..Inputs::default()
+            },
+            Inputs {
+                primary: Event::Active,
+                cursor: Vector2::new(10.0, 20.0),
+                ..Inputs::default()
+            },
+            Inputs {
+                primary: Event::Active,
+                cursor: Vector2::new(10.0, 20.0),
+                ..Inputs::default()
+            },
+        ];
+
+        let mut input_macro = InputMacro::default();
+        input_macro.record(&mut console, path.clone(), (640, 480), 2);
+        let ticks = frames
+            .map(|inputs| {
+                assert_eq!(input_macro.next(&mut console, inputs), inputs);
+                input_macro.is_tick_frame()
+            })
+            .map(Option::unwrap);
+        assert_eq!(ticks, [true, false, true, false]);
+        let InputMacro::Recording { recording, .. } = &input_macro else {
+            panic!("should be recording");
+        };
+        assert_eq!(
+            recording
+                .changes
+                .iter()
+                .map(|change| (change.frame, change.inputs.len()))
+                .collect::<Vec<_>>()[1..],
+            [(1, 2), (2, 1)],
+            "only inputs that changed should be recorded"
+        );
+        input_macro.stop(&mut console);
+
+        input_macro.play(&mut console, &path, (640, 480));
+        assert!(input_macro.is_playing());
+        let live = Inputs {
+            secondary: Event::Active,
+            ..Inputs::default()
+        };
+        for inputs in frames {
+            assert_eq!(input_macro.next(&mut console, live), inputs);
+        }
+        assert_eq!(
+            input_macro.next(&mut console, live),
+            live,
+            "live input should be used once the recording ends"
+        );
+        assert!(input_macro.is_idle());
+        _ = std::fs::remove_file(path);
+    }
+}
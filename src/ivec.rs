@@ -118,12 +118,11 @@ impl IVec2 {
         }
     }
 
+    /// The top-left corner of the cell containing this point, rounding down on both sides of zero.
     pub const fn snap(self, grid_size: i32) -> Self {
-        let x = self.x; // + self.x.signum() * grid_size / 2;
-        let y = self.y; // + self.y.signum() * grid_size / 2;
         Self {
-            x: x - (x % grid_size),
-            y: y - (y % grid_size),
+            x: self.x - self.x.rem_euclid(grid_size),
+            y: self.y - self.y.rem_euclid(grid_size),
         }
     }
 }
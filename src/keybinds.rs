@@ -0,0 +1,622 @@
+//! The settings screen opened by the toolpane's settings button, for rebinding inputs by pressing
+//! them instead of writing them into the config by hand.
+//!
+//! Bindings can also be shared as presets: files holding only the config's `input` table, without
+//! the theme and paths the rest of a config carries.
+
+use crate::{
+    config::{self, CONFIG_PATH},
+    console::{Console, LogType},
+    input::{Bindings, Inputs},
+    ivec::Bounds,
+    logln,
+    theme::Theme,
+};
+use raylib::prelude::*;
+use rl_input::{EventCombo, EventSource, EventTiming};
+use std::path::{Path, PathBuf};
+
+/// Where presets named without a path are kept.
+pub const PRESET_DIRECTORY: &str = "keybinds";
+
+/// Space between the screen and the window's edges, leaving the editor visible around it.
+const MARGIN: f32 = 40.0;
+const TITLE: &str = "Keybindings";
+const HINT: &str = "Click a binding and press the new keys. Esc cancels, right-click resets.";
+/// Shown in place of the bindings the editor can't record.
+const NON_EVENT_LABEL: &str = "edit in config";
+const CAPTURING_LABEL: &str = "press keys...";
+
+/// Keys held alongside another to make a chord, each beside the same key on the other side of the
+/// keyboard, in the order a chord lists them.
+const MODIFIERS: [[KeyboardKey; 2]; 4] = {
+    use KeyboardKey::*;
+    [
+        [KEY_LEFT_CONTROL, KEY_RIGHT_CONTROL],
+        [KEY_LEFT_SHIFT, KEY_RIGHT_SHIFT],
+        [KEY_LEFT_ALT, KEY_RIGHT_ALT],
+        [KEY_LEFT_SUPER, KEY_RIGHT_SUPER],
+    ]
+};
+
+const MOUSE_BUTTONS: [MouseButton; 7] = {
+    use MouseButton::*;
+    [
+        MOUSE_BUTTON_LEFT,
+        MOUSE_BUTTON_RIGHT,
+        MOUSE_BUTTON_MIDDLE,
+        MOUSE_BUTTON_SIDE,
+        MOUSE_BUTTON_EXTRA,
+        MOUSE_BUTTON_FORWARD,
+        MOUSE_BUTTON_BACK,
+    ]
+};
+
+/// `last` held with every modifier that is currently down, either side of each standing in for
+/// both.
+fn chord(rl: &RaylibHandle, last: EventSource) -> EventSource {
+    let mut items = MODIFIERS
+        .iter()
+        .filter(|pair| pair.iter().any(|&key| rl.is_key_down(key)))
+        .map(|&[left, right]| {
+            EventSource::Combo(EventCombo::Any(Box::from([
+                EventSource::Keyboard(left),
+                EventSource::Keyboard(right),
+            ])))
+        })
+        .collect::<Vec<_>>();
+    if items.is_empty() {
+        last
+    } else {
+        items.push(last);
+        EventSource::Combo(EventCombo::All(items.into_boxed_slice()))
+    }
+}
+
+/// A binding written the way the config names its keys, such as `ctrl + s` for either control
+/// key held with S.
+fn describe(source: &EventSource) -> String {
+    match source {
+        EventSource::Constant(event) => format!("{event:?}").to_lowercase(),
+        EventSource::Keyboard(_) | EventSource::Mouse(_) | EventSource::Touch(_) => {
+            match toml::Value::try_from(source) {
+                Ok(toml::Value::String(name)) => name,
+                _ => "?".to_string(),
+            }
+        }
+        EventSource::Combo(EventCombo::All(items)) => items
+            .iter()
+            .map(|item| match item {
+                EventSource::Combo(EventCombo::All(_)) => format!("({})", describe(item)),
+                _ => describe(item),
+            })
+            .collect::<Vec<_>>()
+            .join(" + "),
+        EventSource::Combo(EventCombo::Any(items)) => {
+            let names = items.iter().map(describe).collect::<Vec<_>>();
+            // both sides of a modifier are written as the modifier
+            if let [left, right] = names.as_slice()
+                && let Some(left) = left.strip_prefix("l_")
+                && right.strip_prefix("r_") == Some(left)
+            {
+                left.to_string()
+            } else {
+                format!("({})", names.join(" / "))
+            }
+        }
+        EventSource::Combo(EventCombo::Not(item)) => format!("not {}", describe(item)),
+        EventSource::Timing(EventTiming::Hold {
+            source, seconds, ..
+        }) => format!("hold {} {seconds}s", describe(source)),
+        EventSource::Timing(EventTiming::DoubleTap { source, .. }) => {
+            format!("double tap {}", describe(source))
+        }
+        EventSource::Timing(EventTiming::Repeat { source, .. }) => {
+            format!("repeat {}", describe(source))
+        }
+    }
+}
+
+/// Pairs of inputs bound to the same keys, with the keys they share.
+///
+/// Pairs that share their keys in [`Bindings::default`] too are left out, since those inputs are
+/// told apart by what is under the cursor or what state the editor is in.
+pub fn conflicts(binds: &mut Bindings) -> Vec<(&'static str, &'static str, String)> {
+    let described = |binds: &mut Bindings| {
+        binds
+            .events_mut()
+            .map(|(name, source)| (name, describe(source)))
+    };
+    let defaults = described(&mut Bindings::default());
+    let current = described(binds);
+    let mut conflicts = Vec::new();
+    for (i, (a, binding)) in current.iter().enumerate() {
+        for (j, (b, other)) in current.iter().enumerate().skip(i + 1) {
+            if binding == other && defaults[i].1 != defaults[j].1 {
+                conflicts.push((*a, *b, binding.clone()));
+            }
+        }
+    }
+    conflicts
+}
+
+/// The parts of a [described](describe) binding that must all be held, such as `ctrl` and `s`
+/// for `ctrl + s`. Parenthesized parts are kept whole.
+fn chord_parts(binding: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in binding.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            '+' if depth == 0 => {
+                parts.push(binding[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(binding[start..].trim());
+    parts
+}
+
+/// Pairs of inputs where holding the second's keys always holds the first's too, like `r` and
+/// `ctrl + r`, as indices into [`Bindings::events_mut`]. While the second is held, the first is
+/// left inactive so that a chord only triggers its own action.
+pub fn shadowed(binds: &mut Bindings) -> Vec<(usize, usize)> {
+    let chords = binds
+        .events_mut()
+        .map(|(_, source)| describe(source))
+        .map(|binding| {
+            chord_parts(&binding)
+                .into_iter()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        });
+    let mut pairs = Vec::new();
+    for (i, small) in chords.iter().enumerate() {
+        for (j, big) in chords.iter().enumerate() {
+            if small.len() < big.len() && small.iter().all(|part| big.contains(part)) {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+/// A chord for `binding` with one more modifier, that nothing in `binds` is bound to yet.
+fn suggest_chord(binds: &mut Bindings, binding: &str) -> Option<String> {
+    let taken = binds.events_mut().map(|(_, source)| describe(source));
+    let parts = chord_parts(binding);
+    ["ctrl", "shift", "alt"]
+        .into_iter()
+        .filter(|modifier| !parts.contains(modifier))
+        .map(|modifier| format!("{modifier} + {binding}"))
+        .find(|chord| !taken.contains(chord))
+}
+
+/// Logs every pair of conflicting bindings as a warning, suggesting a free chord for the second
+/// of each.
+pub fn report_conflicts(console: &mut Console, binds: &mut Bindings) {
+    for (a, b, binding) in conflicts(binds) {
+        match suggest_chord(binds, &binding) {
+            Some(chord) => logln!(
+                console,
+                LogType::Warning,
+                "{a} and {b} are both bound to {binding}, so both trigger at once; try binding {b} to {chord}"
+            ),
+            None => logln!(
+                console,
+                LogType::Warning,
+                "{a} and {b} are both bound to {binding}, so both trigger at once"
+            ),
+        }
+    }
+}
+
+/// The file of a preset, either `name` in [`PRESET_DIRECTORY`] or `name` itself if it has an
+/// extension.
+pub fn preset_path(name: &str) -> PathBuf {
+    let path = Path::new(name);
+    if path.extension().is_some() {
+        path.to_path_buf()
+    } else {
+        Path::new(PRESET_DIRECTORY).join(format!("{name}.toml"))
+    }
+}
+
+/// Writes the current bindings, and nothing else from the config, to the preset at `path`.
+pub fn export_preset(console: &mut Console, binds: &Bindings, path: &Path) {
+    logln!(
+        console,
+        LogType::Attempt,
+        "exporting keybindings to {}...",
+        path.display()
+    );
+    let result = toml::Table::try_from(binds)
+        .and_then(|table| toml::to_string_pretty(&table))
+        .map_err(std::io::Error::other)
+        .and_then(|s| {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, s)
+        });
+    match result {
+        Ok(()) => logln!(console, LogType::Success, "exported {}", path.display()),
+        Err(e) => logln!(console, LogType::Error, "failed to export keybindings: {e}"),
+    }
+}
+
+/// Replaces the current bindings with the preset at `path` and saves them to the config,
+/// warning about any conflicts between them. Inputs the preset leaves out get their defaults.
+pub fn import_preset(console: &mut Console, binds: &mut Bindings, path: &Path) {
+    logln!(
+        console,
+        LogType::Attempt,
+        "importing keybindings from {}...",
+        path.display()
+    );
+    let preset = match std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| toml::from_str::<Bindings>(&s).map_err(|e| e.to_string()))
+    {
+        Ok(preset) => preset,
+        Err(e) => {
+            logln!(console, LogType::Error, "failed to import keybindings: {e}");
+            return;
+        }
+    };
+    *binds = preset;
+    logln!(console, LogType::Success, "imported {}", path.display());
+    report_conflicts(console, binds);
+    if let Err(e) = config::save_entry("input", binds) {
+        logln!(console, LogType::Error, "failed to save keybindings: {e}");
+    }
+}
+
+/// A preset command, handed back from the console since the bindings belong to the main loop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PresetAction {
+    Export(PathBuf),
+    Import(PathBuf),
+    /// List the bindings that conflict without changing them
+    Check,
+}
+
+impl PresetAction {
+    pub fn run(self, console: &mut Console, binds: &mut Bindings) {
+        match self {
+            Self::Export(path) => export_preset(console, binds, &path),
+            Self::Import(path) => import_preset(console, binds, &path),
+            Self::Check => {
+                if conflicts(binds).is_empty() {
+                    logln!(console, LogType::Info, "no keybindings conflict");
+                } else {
+                    report_conflicts(console, binds);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Row {
+    /// Name of the input in the config
+    name: &'static str,
+    /// [`None`] for inputs the editor can't record
+    binding: Option<String>,
+}
+
+/// Where everything in the editor goes for a size of window.
+struct Layout {
+    frame: Rectangle,
+    title_position: Vector2,
+    hint_position: Vector2,
+    save: Rectangle,
+    close: Rectangle,
+    /// Where rows are visible
+    list: Bounds,
+    row_height: f32,
+}
+
+/// Lists every input with its binding, recording a new binding for the clicked one from the
+/// next keys pressed.
+#[derive(Debug, Clone)]
+pub struct KeybindEditor {
+    rows: Vec<Row>,
+    /// Pixels the list is scrolled down by
+    scroll: f32,
+    /// Index of the row waiting for keys
+    capturing: Option<usize>,
+    /// A modifier pressed while capturing, bound alone if it is released before another key
+    /// is pressed
+    lone_modifier: Option<KeyboardKey>,
+    /// Whether bindings changed since opening or saving
+    is_unsaved: bool,
+}
+
+impl KeybindEditor {
+    pub fn new(binds: &mut Bindings) -> Self {
+        let mut editor = Self {
+            rows: Vec::new(),
+            scroll: 0.0,
+            capturing: None,
+            lone_modifier: None,
+            is_unsaved: false,
+        };
+        editor.refresh_rows(binds);
+        editor
+    }
+
+    fn refresh_rows(&mut self, binds: &mut Bindings) {
+        self.rows = binds
+            .events_mut()
+            .into_iter()
+            .map(|(name, source)| Row {
+                name,
+                binding: Some(describe(source)),
+            })
+            .chain(Bindings::NON_EVENTS.into_iter().map(|name| Row {
+                name,
+                binding: None,
+            }))
+            .collect();
+    }
+
+    fn layout(&self, theme: &Theme, window: Bounds) -> Layout {
+        let padding = theme.title_padding;
+        let frame = Rectangle::new(
+            window.min.x + MARGIN,
+            window.min.y + MARGIN,
+            (window.width() - 2.0 * MARGIN).max(0.0),
+            (window.height() - 2.0 * MARGIN).max(0.0),
+        );
+        let title_size = theme.title_font.measure_text(TITLE);
+        let title_position = Vector2::new(frame.x + padding.left, frame.y + padding.top);
+        let row_height = theme.general_font.line_height() + padding.vertical();
+
+        let button = |text: &str, right: f32| {
+            let width = theme.general_font.measure_text(text).x + padding.horizontal();
+            Rectangle::new(right - width, frame.y + padding.top, width, row_height)
+        };
+        let close = button("Close", frame.x + frame.width - padding.right);
+        let save = button("Save", close.x - padding.right);
+
+        let header_bottom = title_position.y + title_size.y.max(row_height) + padding.bottom;
+        let hint_position = Vector2::new(frame.x + padding.left, header_bottom);
+        let list_top = hint_position.y + theme.general_font.line_height() + padding.vertical();
+        Layout {
+            frame,
+            title_position,
+            hint_position,
+            save,
+            close,
+            list: Bounds::new(
+                Vector2::new(frame.x + padding.left, list_top),
+                Vector2::new(
+                    frame.x + frame.width - padding.right,
+                    (frame.y + frame.height - padding.bottom).max(list_top),
+                ),
+            ),
+            row_height,
+        }
+    }
+
+    fn row_rec(layout: &Layout, scroll: f32, n: usize) -> Rectangle {
+        Rectangle::new(
+            layout.list.min.x,
+            layout.list.min.y + n as f32 * layout.row_height - scroll,
+            layout.list.width(),
+            layout.row_height,
+        )
+    }
+
+    /// Takes all input while open. Returns whether the editor should stay open.
+    pub fn tick(
+        &mut self,
+        rl: &mut RaylibHandle,
+        console: &mut Console,
+        theme: &Theme,
+        input: &Inputs,
+        binds: &mut Bindings,
+        window: Bounds,
+    ) -> bool {
+        if let Some(n) = self.capturing {
+            let source = if let Some(key) = rl.get_key_pressed() {
+                if key == KeyboardKey::KEY_ESCAPE {
+                    self.capturing = None;
+                    self.lone_modifier = None;
+                    return true;
+                } else if MODIFIERS.as_flattened().contains(&key) {
+                    self.lone_modifier = Some(key);
+                    None
+                } else {
+                    Some(chord(rl, EventSource::Keyboard(key)))
+                }
+            } else if let Some(button) = MOUSE_BUTTONS
+                .into_iter()
+                .find(|&button| rl.is_mouse_button_pressed(button))
+            {
+                Some(chord(rl, EventSource::Mouse(button)))
+            } else if let Some(key) = self.lone_modifier
+                && rl.is_key_released(key)
+            {
+                Some(EventSource::Keyboard(key))
+            } else {
+                None
+            };
+            if let Some(source) = source {
+                let name = self.rows[n].name;
+                logln!(
+                    console,
+                    LogType::Info,
+                    "bound {name} to {}",
+                    describe(&source)
+                );
+                *binds.events_mut()[n].1 = source;
+                for (a, b, binding) in conflicts(binds) {
+                    if a == name || b == name {
+                        let other = if a == name { b } else { a };
+                        logln!(
+                            console,
+                            LogType::Warning,
+                            "{other} is also bound to {binding}"
+                        );
+                    }
+                }
+                self.refresh_rows(binds);
+                self.capturing = None;
+                self.lone_modifier = None;
+                self.is_unsaved = true;
+            }
+            return true;
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            return false;
+        }
+
+        let layout = self.layout(theme, window);
+        let max_scroll =
+            (self.rows.len() as f32 * layout.row_height - layout.list.height()).max(0.0);
+        self.scroll =
+            (self.scroll - input.scroll_console * layout.row_height).clamp(0.0, max_scroll);
+
+        if input.primary.is_starting() {
+            if Bounds::from(layout.close).contains(input.cursor) {
+                return false;
+            } else if Bounds::from(layout.save).contains(input.cursor) {
+                logln!(
+                    console,
+                    LogType::Attempt,
+                    "Saving keybindings to {CONFIG_PATH}..."
+                );
+                match config::save_entry("input", binds) {
+                    Ok(()) => {
+                        logln!(console, LogType::Success, "Keybindings saved.");
+                        self.is_unsaved = false;
+                    }
+                    Err(e) => {
+                        logln!(console, LogType::Error, "failed to save keybindings: {e}");
+                    }
+                }
+            } else if let Some(n) = self.hovered_row(&layout, input.cursor)
+                && self.rows[n].binding.is_some()
+            {
+                self.capturing = Some(n);
+            }
+        } else if input.secondary.is_starting()
+            && let Some(n) = self.hovered_row(&layout, input.cursor)
+            && self.rows[n].binding.is_some()
+        {
+            let name = self.rows[n].name;
+            let source = Bindings::default().events_mut()[n].1.clone();
+            logln!(
+                console,
+                LogType::Info,
+                "reset {name} to {}",
+                describe(&source)
+            );
+            *binds.events_mut()[n].1 = source;
+            self.refresh_rows(binds);
+            self.is_unsaved = true;
+        }
+
+        true
+    }
+
+    fn hovered_row(&self, layout: &Layout, cursor: Vector2) -> Option<usize> {
+        if !layout.list.contains(cursor) {
+            return None;
+        }
+        let n = ((cursor.y - layout.list.min.y + self.scroll) / layout.row_height) as usize;
+        (n < self.rows.len()).then_some(n)
+    }
+
+    pub fn draw<D: RaylibDraw>(&self, d: &mut D, theme: &Theme, input: &Inputs, window: Bounds) {
+        let layout = self.layout(theme, window);
+        let padding = theme.title_padding;
+
+        d.draw_rectangle_rec(layout.frame, theme.background2);
+        d.draw_rectangle_rec(
+            Rectangle::new(
+                layout.frame.x + 1.0,
+                layout.frame.y + 1.0,
+                layout.frame.width - 2.0,
+                layout.frame.height - 2.0,
+            ),
+            theme.background1,
+        );
+
+        let title = if self.is_unsaved {
+            format!("{TITLE} (unsaved)")
+        } else {
+            TITLE.to_string()
+        };
+        theme
+            .title_font
+            .draw_text(d, &title, layout.title_position, theme.foreground);
+        theme
+            .general_font
+            .draw_text(d, HINT, layout.hint_position, theme.foreground2);
+
+        for (text, rec) in [("Save", layout.save), ("Close", layout.close)] {
+            let is_hovered = Bounds::from(rec).contains(input.cursor);
+            d.draw_rectangle_rec(
+                rec,
+                if is_hovered {
+                    theme.background3
+                } else {
+                    theme.background2
+                },
+            );
+            theme.general_font.draw_text(
+                d,
+                text,
+                Vector2::new(rec.x + padding.left, rec.y + padding.top),
+                theme.foreground,
+            );
+        }
+
+        let hovered = self.hovered_row(&layout, input.cursor);
+        let list = Rectangle::from(layout.list);
+        let mut d = d.begin_scissor_mode(
+            list.x as i32,
+            list.y as i32,
+            list.width as i32,
+            list.height as i32,
+        );
+        let binding_x = list.x + list.width * 0.5;
+        for (n, row) in self.rows.iter().enumerate() {
+            let rec = Self::row_rec(&layout, self.scroll, n);
+            if rec.y + rec.height < list.y || rec.y > list.y + list.height {
+                continue;
+            }
+            let is_capturing = self.capturing == Some(n);
+            if is_capturing || (hovered == Some(n) && row.binding.is_some()) {
+                d.draw_rectangle_rec(rec, theme.background3);
+            } else if n % 2 == 1 {
+                d.draw_rectangle_rec(rec, theme.background);
+            }
+            theme.general_font.draw_text(
+                &mut d,
+                &row.name.replace('_', " "),
+                Vector2::new(rec.x + padding.left, rec.y + padding.top),
+                theme.foreground,
+            );
+            let (binding, color) = match &row.binding {
+                _ if is_capturing => (CAPTURING_LABEL, theme.special),
+                Some(binding) => (binding.as_str(), theme.foreground1),
+                None => (NON_EVENT_LABEL, theme.foreground3),
+            };
+            theme.general_font.draw_text(
+                &mut d,
+                binding,
+                Vector2::new(binding_x, rec.y + padding.top),
+                color,
+            );
+        }
+    }
+}
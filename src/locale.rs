@@ -0,0 +1,253 @@
+//! Text shown in the UI, looked up by key so that it can be translated without recompiling.
+//!
+//! A locale file is a TOML table of keys to translated text, such as `panel.log = "Journal"`, or
+//! with the same key split into tables, `[panel]` then `log = "Journal"`. Keys the file leaves
+//! out are shown in English. The file is chosen with `locale` in the config, and is read once on
+//! startup.
+
+use std::{collections::HashMap, path::Path, sync::OnceLock};
+
+/// The translations of the locale file, once [`install`] has been called.
+static LOCALE: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Every key and its English text, which is used wherever the locale leaves a key out.
+const ENGLISH: &[(&str, &str)] = &[
+    ("panel.editor", "Editor"),
+    ("panel.log", "Log"),
+    ("panel.map", "Map"),
+    ("panel.properties", "Properties"),
+    ("panel.waveform", "Waveform"),
+    ("section.annotation", "Annotation"),
+    ("section.elbow", "Wire elbow"),
+    ("section.gate", "Gate"),
+    ("section.node", "Node"),
+    ("section.selection", "Selection"),
+    ("section.timeline", "Timeline"),
+    ("section.tool", "Tool"),
+    ("button.annotate", "Annotate"),
+    ("button.export", "Export PNG"),
+    ("button.open", "Open"),
+    ("button.save", "Save"),
+    ("button.save_as", "Save As"),
+    ("button.theme", "Theme"),
+    ("tool.create", "Create"),
+    (
+        "tool.create.desc",
+        "Place nodes with primary input. Placing or clicking a node automatically begins \
+        creating a wire that will connect to the next placed or clicked node. Cycle the shape \
+        of new wires with the cycle elbow hotkey, or have them routed around nodes with Shift+Q. \
+        Without a mouse, move the key cursor with the \
+        arrow keys and press Enter to place or click there, or Tab to pick the nodes around it \
+        in turn. Ctrl+D stamps a copy of the selection, wires and all, with every placement \
+        until secondary input puts it down.",
+    ),
+    ("tool.erase", "Erase"),
+    (
+        "tool.erase.desc",
+        "Click nodes or wires to delete them, or drag to delete everything swept over. A deleted \
+        node will delete all its wires as well.",
+    ),
+    ("tool.edit", "Edit"),
+    (
+        "tool.edit.desc",
+        "Drag nodes with primary input. Drag a wire's elbow to change its shape, or drag near one \
+        of its ends to move that end to another node. Rotate the hovered node clockwise with the \
+        rotate hotkey, along with the rest of the selection if it is selected. Click a node to \
+        change its gate, NTD value, width and position from this panel. Click a node with \
+        secondary input while holding alternate input to replace its gate with the current gate \
+        and NTD value, along with the rest of the selection if it is selected; its wires are kept, \
+        and the context menu can put the old gates back. Hold alternate input to select: click \
+        nodes to add or remove them, \
+        or drag from empty space to select every node in a box. The gates and NTD values of \
+        every selected node can be set at once from this panel.",
+    ),
+    ("tool.interact", "Interact"),
+    (
+        "tool.interact.desc",
+        "Click a Switch to flip it on or off, or hold down a Button to turn it on until \
+        released. This is the only tool that works in a tab locked with Ctrl+K.",
+    ),
+    ("tool.annotate", "Annotate"),
+    (
+        "tool.annotate.desc",
+        "Click empty space to place a text annotation, or click an annotation to pick it. \
+        Type with the cursor over this panel to edit the picked annotation's text.",
+    ),
+    ("gate.or", "Or"),
+    ("gate.or.desc", "True when one input or more is true."),
+    ("gate.and", "And"),
+    (
+        "gate.and.desc",
+        "True when every input is true and at least one input exists.",
+    ),
+    ("gate.nor", "Nor"),
+    ("gate.nor.desc", "True when every input is false."),
+    ("gate.xor", "Xor"),
+    ("gate.xor.desc", "True when exactly one input is true."),
+    ("gate.resistor", "Resistor"),
+    (
+        "gate.resistor.desc",
+        "True when the number of true inputs exceed the NTD value.",
+    ),
+    ("gate.capacitor", "Capacitor"),
+    (
+        "gate.capacitor.desc",
+        "Stores the quantity of true inputs up to a maximum of the NTD value, \
+        losing charge every tick that no input is true. True as long as the charge is not zero.",
+    ),
+    ("gate.led", "Led"),
+    (
+        "gate.led.desc",
+        "Like Or, but in Inspect mode, fills its cell with the color of the NTD value when true.",
+    ),
+    ("gate.delay", "Delay"),
+    (
+        "gate.delay.desc",
+        "Like Or, but gives the output that would have been given as many ticks ago as the NTD \
+        value. An NTD value of zero gives it immediately.",
+    ),
+    ("gate.battery", "Battery"),
+    ("gate.battery.desc", "Always true."),
+    ("gate.ic", "IC"),
+    (
+        "gate.ic.desc",
+        "Runs its own copy of another graph, driving that graph's inputless nodes with its inputs \
+        from top to bottom. True if any of that graph's outputless nodes are true.",
+    ),
+    ("gate.split", "Split"),
+    (
+        "gate.split.desc",
+        "Takes the bit numbered by the NTD value out of the bus formed by its inputs, \
+        as a single wire.",
+    ),
+    ("gate.merge", "Merge"),
+    (
+        "gate.merge.desc",
+        "Puts its inputs onto the bit of a bus numbered by the NTD value. \
+        Combine several with an Or to build a bus.",
+    ),
+    ("gate.input", "Input"),
+    (
+        "gate.input.desc",
+        "Like Or, but marks an input of the graph, numbered by the NTD value. When the graph \
+        has any Input or Output nodes, an IC of it is driven through its Inputs and shows its \
+        Outputs, in order of number, instead of using its inputless and outputless nodes.",
+    ),
+    ("gate.output", "Output"),
+    (
+        "gate.output.desc",
+        "Like Or, but marks an output of the graph, numbered by the NTD value. \
+        Name a node with the name command to label its pin.",
+    ),
+    ("gate.switch", "Switch"),
+    (
+        "gate.switch.desc",
+        "Outputs on or off, ignoring its inputs, and stays that way until it is clicked \
+        with the interact tool. Saved with the graph.",
+    ),
+    ("gate.button", "Button"),
+    (
+        "gate.button.desc",
+        "Outputs on only while it is held down with the interact tool, ignoring its inputs.",
+    ),
+];
+
+/// Adds the entries of `table` to `strings`, with the keys of nested tables prefixed by the keys
+/// of the tables they are in.
+fn flatten(table: toml::Table, prefix: &str, strings: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            toml::Value::String(text) => _ = strings.insert(key, text),
+            toml::Value::Table(table) => flatten(table, &key, strings),
+            // left out, so that the key falls back to English
+            _ => {}
+        }
+    }
+}
+
+/// Reads the locale file at `path` for [`tr`] to look keys up in.
+///
+/// Returns the keys in the file that aren't used anywhere, which are likely misspelled.
+pub fn install(path: &Path) -> std::io::Result<Vec<String>> {
+    let table = std::fs::read_to_string(path)?
+        .parse::<toml::Table>()
+        .map_err(std::io::Error::other)?;
+    let mut strings = HashMap::new();
+    flatten(table, "", &mut strings);
+    let mut unknown = strings
+        .keys()
+        .filter(|key| !ENGLISH.iter().any(|(k, _)| k == key))
+        .cloned()
+        .collect::<Vec<_>>();
+    unknown.sort_unstable();
+    LOCALE
+        .set(strings)
+        .map_err(|_| std::io::Error::other("a locale was already installed"))?;
+    Ok(unknown)
+}
+
+/// The text of `key` in the installed locale, or in English if the locale leaves it out or none
+/// is installed. Keys with no English text are shown as they are.
+pub fn tr(key: &'static str) -> &'static str {
+    LOCALE
+        .get()
+        .and_then(|strings| strings.get(key))
+        .map(String::as_str)
+        .or_else(|| {
+            ENGLISH
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, text)| *text)
+        })
+        .unwrap_or(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flatten() {
+        let table = r#"
+            "panel.log" = "Journal"
+            count = 3
+
+            [tool]
+            erase = "Gomme"
+            edit.desc = "Modifier"
+        "#
+        .parse::<toml::Table>()
+        .unwrap();
+        let mut strings = HashMap::new();
+        flatten(table, "", &mut strings);
+        assert_eq!(
+            strings,
+            HashMap::from_iter(
+                [
+                    ("panel.log", "Journal"),
+                    ("tool.erase", "Gomme"),
+                    ("tool.edit.desc", "Modifier"),
+                ]
+                .map(|(key, text)| (key.to_string(), text.to_string()))
+            ),
+            "non-string values should be left out"
+        );
+    }
+
+    #[test]
+    fn test_english() {
+        for (n, (key, _)) in ENGLISH.iter().enumerate() {
+            assert!(
+                !ENGLISH[..n].iter().any(|(k, _)| k == key),
+                "{key} is in ENGLISH twice"
+            );
+        }
+        assert_eq!(tr("panel.log"), "Log");
+        assert_eq!(tr("no.such.key"), "no.such.key");
+    }
+}
@@ -0,0 +1,116 @@
+//! A copy of the log appended to a file line by line, so that it is still there after a crash,
+//! rotated so that it never grows too big.
+
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogFileSettings {
+    /// Whether to write the log to a file at all.
+    pub enabled: bool,
+    /// File the log is appended to.
+    pub path: PathBuf,
+    /// Kilobytes the file may grow to before it is rotated.
+    pub max_size_kb: u64,
+    /// Rotated files to keep, named like `path` followed by `.1` (the newest), `.2` and so on.
+    pub keep: usize,
+}
+
+impl Default for LogFileSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: PathBuf::from("logs/log.txt"),
+            max_size_kb: 1024,
+            keep: 3,
+        }
+    }
+}
+
+/// The file the log is being appended to.
+#[derive(Debug)]
+pub struct LogFile {
+    /// [`None`] only while rotating, so that the file is closed before it is renamed
+    file: Option<File>,
+    path: PathBuf,
+    /// Bytes in the file
+    len: u64,
+    max_len: u64,
+    keep: usize,
+}
+
+impl LogFile {
+    /// Opens the file of `settings` to append to, or returns [`None`] if they disable it.
+    pub fn open(settings: &LogFileSettings) -> std::io::Result<Option<Self>> {
+        if !settings.enabled {
+            return Ok(None);
+        }
+        if let Some(parent) = settings.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = Self::open_append(&settings.path)?;
+        Ok(Some(Self {
+            len: file.metadata()?.len(),
+            file: Some(file),
+            path: settings.path.clone(),
+            max_len: settings.max_size_kb.saturating_mul(1024),
+            keep: settings.keep,
+        }))
+    }
+
+    fn open_append(path: &Path) -> std::io::Result<File> {
+        File::options().create(true).append(true).open(path)
+    }
+
+    /// Appends `line` and a newline, rotating first if the file would grow too big.
+    pub fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let line = format!("{line}\n");
+        let len = line.len() as u64;
+        if self.len > 0 && self.len + len > self.max_len {
+            self.rotate()?;
+        }
+        let mut file = match self.file.take() {
+            Some(file) => file,
+            // a previous rotation failed partway
+            None => Self::open_append(&self.path)?,
+        };
+        // in one write, so that a crash never leaves half a line
+        file.write_all(line.as_bytes())?;
+        self.file = Some(file);
+        self.len += len;
+        Ok(())
+    }
+
+    /// Where the `n`th newest rotated file goes.
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{n}"));
+        path.into()
+    }
+
+    /// Moves the file to the first rotated path, shifting the older ones along and deleting the
+    /// oldest, and starts a new one.
+    fn rotate(&mut self) -> std::io::Result<()> {
+        // some systems can't rename open files
+        self.file = None;
+        if self.keep == 0 {
+            self.file = Some(File::create(&self.path)?);
+        } else {
+            for n in (1..self.keep).rev() {
+                let from = self.rotated_path(n);
+                if from.exists() {
+                    std::fs::rename(from, self.rotated_path(n + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = Some(Self::open_append(&self.path)?);
+        }
+        self.len = 0;
+        Ok(())
+    }
+}
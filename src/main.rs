@@ -2,50 +2,110 @@
 #![allow(dead_code, reason = "for future use")]
 
 use crate::{
-    config::Config,
-    console::{Console, LogType},
+    backup::Backups,
+    clipboard::ClipboardAction,
+    command::CommandLine,
+    config::{CONFIG_PATH, Config, ConfigWatcher},
+    console::{Console, ConsoleAction, LogType},
+    dock::DockLayout,
+    export::{DEFAULT_EXPORT_SCALE, ExportFormat, ExportSettings},
+    gate_wheel::GateWheel,
     graph::{GraphList, node::Gate, wire::Elbow},
+    input_macro::InputMacro,
     ivec::{Bounds, IVec2},
-    properties::PropertiesPanel,
+    keybinds::{self, KeybindEditor},
+    log_file::LogFile,
+    minimap::Minimap,
+    onboarding::Onboarding,
+    profiler::{Phase, Profiler},
+    properties::{PropertiesPanel, SelectionStats},
+    session::{SESSION_PATH, Session},
+    sound::{SoundEffect, SoundPack},
     tab::{EditorTab, Tab, TabList},
     theme::Theme,
+    theme_editor::ThemeEditor,
+    toast::Toasts,
     tool::Tool,
-    toolpane::ToolPane,
+    toolpane::{ButtonAction, ToolPane},
     ui::{Anchoring, ExactSizing, NcSizing, Padding, Panel, PanelContent, Sizing},
+    waveform::Waveform,
 };
 use raylib::prelude::*;
 use std::{
     io::Write,
+    path::PathBuf,
     sync::Arc,
     time::{Duration, Instant},
 };
 
+mod backup;
+mod blueprint;
+mod clipboard;
+mod command;
+mod completion;
 mod config;
 mod console;
+mod context_menu;
+mod dock;
+mod export;
+mod file;
+mod gate_wheel;
 mod graph;
+mod headless;
 mod icon_sheets;
 mod input;
+mod input_macro;
 mod ivec;
+mod keybinds;
+mod locale;
+mod log_file;
+mod minimap;
+mod onboarding;
+mod profiler;
 mod properties;
+mod report;
 mod rich_text;
+mod session;
+mod sound;
 mod tab;
 mod theme;
+mod theme_editor;
+mod toast;
 mod tool;
 mod toolpane;
 mod ui;
+mod waveform;
 
-pub const GRID_SIZE: u8 = 8;
+/// Grid size of new graphs, unless the config sets another.
+pub const DEFAULT_GRID_SIZE: u8 = 8;
+
+/// [`DEFAULT_GRID_SIZE`] for `#[serde(default = ...)]`, which only takes functions.
+pub const fn default_grid_size() -> u8 {
+    DEFAULT_GRID_SIZE
+}
 
 fn main() {
+    if let Some(args) = headless::HeadlessArgs::parse(std::env::args().skip(1)) {
+        let result = args.and_then(|args| headless::run(&args));
+        if let Err(e) = result {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+    // skips restoring the last session
+    let is_fresh = std::env::args().skip(1).any(|arg| arg == "--fresh");
+
     let mut console = Console::new(
         Panel::new(
-            "Log",
+            "panel.log",
             Anchoring::Bottom {
                 h: Sizing::Exact(ExactSizing {
                     val: 150.0,
                     min: Some(|theme, _, _| {
                         Some(
-                            theme.console_font.line_height()
+                            // one log line and the command line
+                            2.0 * theme.console_font.line_height()
                                 + theme.console_font.line_spacing
                                 + theme.console_padding.vertical(),
                         )
@@ -57,6 +117,14 @@ fn main() {
         ),
         4096 * 80,
     );
+    console.command_line = CommandLine::load();
+    if console.install_deferred().is_err() {
+        logln!(
+            &mut console,
+            LogType::Warning,
+            "deferred log was already installed"
+        );
+    }
 
     let program_icon =
         Image::load_image_from_mem(".png", include_bytes!("../assets/program_icon32x.png")).ok();
@@ -71,12 +139,18 @@ fn main() {
     unsafe {
         ffi::SetTraceLogLevel(ffi::TraceLogLevel::LOG_WARNING as i32);
     }
+    if let Err(e) = raylib::core::callbacks::set_trace_log_callback(console::raylib_trace_log) {
+        logln!(
+            &mut console,
+            LogType::Warning,
+            "failed to capture raylib's log: {e}"
+        );
+    }
 
-    rl.set_target_fps(
-        get_monitor_refresh_rate(get_current_monitor())
-            .try_into()
-            .unwrap(),
-    );
+    let foreground_fps = get_monitor_refresh_rate(get_current_monitor())
+        .try_into()
+        .unwrap();
+    rl.set_target_fps(foreground_fps);
 
     rl.set_exit_key(None);
 
@@ -84,7 +158,6 @@ fn main() {
         rl.set_window_icon(icon);
     }
 
-    const CONFIG_PATH: &str = "config.toml";
     logln!(
         &mut console,
         LogType::Attempt,
@@ -93,8 +166,19 @@ fn main() {
 
     // load preferences
     let Config {
+        grid_size,
+        snap,
+        gate_order,
         mut theme,
         mut binds,
+        fan_limits,
+        backup,
+        show_onboarding,
+        sound: sound_settings,
+        background,
+        log_file,
+        locale: locale_path,
+        ui_scale,
     } = {
         match std::fs::read_to_string(CONFIG_PATH) {
             Ok(s) => match toml::from_str(&s) {
@@ -135,12 +219,68 @@ fn main() {
             }
         }
     };
+    keybinds::report_conflicts(&mut console, &mut binds);
+    if let Some(path) = &locale_path {
+        match locale::install(path) {
+            Ok(unknown) if unknown.is_empty() => {}
+            Ok(unknown) => logln!(
+                &mut console,
+                LogType::Warning,
+                "locale {} has keys that aren't used: {}",
+                path.display(),
+                unknown.join(", ")
+            ),
+            Err(e) => logln!(
+                &mut console,
+                LogType::Error,
+                "failed to load locale {}: {e}",
+                path.display()
+            ),
+        }
+    }
+    match LogFile::open(&log_file) {
+        Ok(file) => console.set_file(file),
+        Err(e) => logln!(
+            &mut console,
+            LogType::Warning,
+            "failed to open log file {}: {e}",
+            log_file.path.display()
+        ),
+    }
+    // the monitor's scale unless the config sets one, such as 2 on most 4K displays
+    let ui_scale = ui_scale.unwrap_or_else(|| rl.get_window_scale_dpi().x);
+    theme.set_ui_scale(ui_scale);
     theme.reload_assets(&mut rl, &thread).unwrap();
+    let mut config_watcher = ConfigWatcher::new();
+
+    let mut backups = Backups::new(backup);
+
+    let audio = RaylibAudio::init_audio_device()
+        .ok()
+        .filter(RaylibAudio::is_audio_device_ready);
+    if audio.is_none() {
+        logln!(
+            &mut console,
+            LogType::Warning,
+            "no audio device; editor sounds are off"
+        );
+    }
+    let mut sounds = SoundPack::load(audio.as_ref(), sound_settings, &mut console);
 
     let mut graphs = GraphList::new();
+    graphs.set_fan_limits(fan_limits, &mut console);
+    if grid_size == 0 {
+        logln!(
+            &mut console,
+            LogType::Error,
+            "grid_size must be positive, using {DEFAULT_GRID_SIZE}"
+        );
+    } else {
+        graphs.set_grid_size(grid_size);
+    }
 
     let mut tabs = TabList::with_tabs(
-        Panel::new("Editor", Anchoring::Fill, |_| Padding::amount(0.0)),
+        Panel::new("panel.editor", Anchoring::Fill, |_| Padding::amount(0.0)),
         [Tab::Editor(
             EditorTab::new(
                 &mut rl,
@@ -152,13 +292,17 @@ fn main() {
             .unwrap(),
         )],
     );
+    tabs.set_default_snap(snap);
+    for tab in tabs.editors_mut() {
+        tab.set_snap(snap);
+    }
 
     let mut toolpane = ToolPane::new(
         Panel::new(
             "",
             Anchoring::Floating {
                 x: 3.0,
-                y: 3.0,
+                y: 3.0 + TabList::bar_height(&theme),
                 w: NcSizing::FitContent,
                 h: NcSizing::FitContent,
             },
@@ -169,11 +313,12 @@ fn main() {
         Elbow::default(),
         theme.toolpane_orientation,
         theme.toolpane_visibility,
-        theme.button_icon_scale,
+        theme.button_icon_sheet(),
     );
+    toolpane.set_gate_order(&gate_order);
 
     let mut properties = PropertiesPanel::new(Panel::new(
-        "Properties",
+        "panel.properties",
         Anchoring::Right {
             w: Sizing::Exact(ExactSizing {
                 val: 200.0,
@@ -184,8 +329,92 @@ fn main() {
         |theme| theme.properties_padding,
     ));
 
+    let mut minimap = Minimap::new(Panel::new(
+        "panel.map",
+        Anchoring::BottomRight {
+            w: Sizing::Exact(ExactSizing {
+                val: 160.0,
+                min: Some(|_, _, _| Some(0.0)),
+                max: Some(|_, container_size, _content_size| Some(container_size)),
+            }),
+            h: Sizing::Exact(ExactSizing {
+                val: 120.0,
+                min: Some(|_, _, _| Some(0.0)),
+                max: Some(|_, container_size, _content_size| Some(container_size)),
+            }),
+        },
+        |theme| theme.properties_padding,
+    ));
+
+    let mut waveform = Waveform::new(Panel::new(
+        "panel.waveform",
+        Anchoring::BottomLeft {
+            w: Sizing::Exact(ExactSizing {
+                val: 320.0,
+                min: Some(|_, _, _| Some(0.0)),
+                max: Some(|_, container_size, _content_size| Some(container_size)),
+            }),
+            h: Sizing::Exact(ExactSizing {
+                val: 120.0,
+                min: Some(|_, _, _| Some(0.0)),
+                max: Some(|_, container_size, _content_size| Some(container_size)),
+            }),
+        },
+        |theme| theme.properties_padding,
+    ));
+
+    for panel in [
+        &mut console.panel,
+        &mut properties.panel,
+        &mut minimap.panel,
+        &mut waveform.panel,
+    ] {
+        panel.anchoring.scale_sizes(theme.ui_scale);
+    }
+
+    let mut keybind_editor: Option<KeybindEditor> = None;
+    let mut theme_editor: Option<ThemeEditor> = None;
+    let mut onboarding = show_onboarding.then(Onboarding::default);
+
     let mut next_eval_tick = Instant::now();
     let eval_duration = Duration::from_millis(200);
+    let mut is_backgrounded = false;
+    // while recording or playing input, evaluation ticks as often as it would at full speed
+    let macro_frames_per_tick =
+        ((eval_duration.as_secs_f32() * foreground_fps as f32).round() as u32).max(1);
+    let mut input_macro = InputMacro::default();
+
+    let session = if is_fresh {
+        None
+    } else {
+        match Session::load() {
+            Ok(session) => session,
+            Err(e) => {
+                logln!(
+                    &mut console,
+                    LogType::Error,
+                    "Failed to read {SESSION_PATH}: {e}"
+                );
+                None
+            }
+        }
+    };
+    let mut dock = DockLayout::default();
+    let mut toasts = Toasts::default();
+    let mut gate_wheel = GateWheel::default();
+    let mut profiler = Profiler::default();
+    if let Some(session) = &session {
+        session.restore_panels(
+            &mut dock,
+            &mut [
+                ("console", &mut console.panel),
+                ("toolpane", &mut toolpane.panel),
+                ("properties", &mut properties.panel),
+                ("minimap", &mut minimap.panel),
+                ("waveform", &mut waveform.panel),
+            ],
+        );
+    }
 
     // initialize bounds
     {
@@ -221,15 +450,61 @@ fn main() {
             container = new_container;
         }
 
+        if let Some(new_container) =
+            minimap
+                .panel
+                .update_bounds(&theme, &container, Vector2::zero(/* TODO */))
+        {
+            container = new_container;
+        }
+
+        if let Some(new_container) =
+            waveform
+                .panel
+                .update_bounds(&theme, &container, Vector2::zero(/* TODO */))
+        {
+            container = new_container;
+        }
+
         _ = container;
     }
 
+    if let Some(session) = &session {
+        session.restore_tabs(&mut rl, &thread, &mut console, &mut graphs, &mut tabs);
+    }
+
     logln!(&mut console, LogType::Success, "initialized");
 
     while !rl.window_should_close() {
+        profiler.begin_frame();
+        console.drain_deferred();
+
         // Tick
 
-        let input = binds.get_all(&rl);
+        if input_macro.is_playing() && rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            input_macro.stop(&mut console);
+        }
+        let input = input_macro.next(&mut console, binds.get_all(&rl));
+        profiler.lap(Phase::Input);
+        let mut clipboard_action = None;
+        let mut pending_export = None::<ExportSettings>;
+        let error_count = console.error_count();
+
+        if (background.enabled && !rl.is_window_focused()) != is_backgrounded {
+            is_backgrounded = !is_backgrounded;
+            if is_backgrounded {
+                rl.set_target_fps(background.fps.max(1));
+            } else {
+                rl.set_target_fps(foreground_fps);
+                // don't wait out the rest of a slow background tick
+                next_eval_tick = Instant::now();
+            }
+        }
+        let eval_duration = if is_backgrounded {
+            background.eval_interval()
+        } else {
+            Some(eval_duration)
+        };
 
         if rl.is_window_resized() {
             let window_width = rl.get_screen_width();
@@ -244,66 +519,402 @@ fn main() {
             // TODO: refresh bounds on other panels
         }
 
-        Panel::tick_resize_set(
-            Bounds::new(
-                Vector2::zero(),
-                rvec2(rl.get_screen_width(), rl.get_screen_height()),
-            ),
-            &theme,
-            &input,
-            [
-                // tabs only changes when window does, for now
-                &mut properties,
-                &mut console,
-                &mut toolpane,
-            ] as [&mut dyn PanelContent; _],
+        let window = Bounds::new(
+            Vector2::zero(),
+            rvec2(rl.get_screen_width(), rl.get_screen_height()),
         );
 
-        let focused_panel = {
+        let onboarding_panels = [
+            *toolpane.panel.bounds(),
+            *console.panel.bounds(),
+            *properties.panel.bounds(),
+        ];
+
+        // settings screens and the welcome overlay cover every panel and take all input while open
+        let is_in_settings = if let Some(editor) = keybind_editor.as_mut() {
+            if !editor.tick(&mut rl, &mut console, &theme, &input, &mut binds, window) {
+                keybind_editor = None;
+            }
+            true
+        } else if let Some(editor) = theme_editor.as_mut() {
+            if !editor.tick(&rl, &mut console, &mut theme, &mut tabs, &input, window) {
+                theme_editor = None;
+            }
+            true
+        } else if let Some(overlay) = onboarding.as_mut() {
+            if !overlay.tick(&rl, &mut console, &theme, &input, onboarding_panels, window) {
+                onboarding = None;
+            }
+            true
+        } else {
+            false
+        };
+
+        let mut file_action = if is_in_settings {
+            None
+        } else {
+            input.file_action()
+        };
+
+        if !is_in_settings && input.toggle_mute.is_starting() {
+            sounds.toggle_mute(&mut console);
+        }
+
+        if !is_in_settings && input.toggle_profiler.is_starting() {
+            profiler.is_shown = !profiler.is_shown;
+        }
+
+        if !is_in_settings {
+            for (toggle, panel) in [
+                (input.toggle_console, &mut console.panel),
+                (input.toggle_properties, &mut properties.panel),
+                (input.hide_toolpane, &mut toolpane.panel),
+            ] {
+                if toggle.is_starting() {
+                    panel.toggle_closed();
+                }
+            }
+        }
+
+        if !is_in_settings {
+            Panel::tick_resize_set(
+                window,
+                &theme,
+                &input,
+                [
+                    // tabs only changes when window does, for now
+                    &mut properties,
+                    &mut console,
+                    &mut minimap,
+                    &mut waveform,
+                    &mut toolpane,
+                ] as [&mut dyn PanelContent; _],
+            );
+            dock.tick(
+                &theme,
+                &input,
+                &window,
+                &mut [
+                    ("console", &mut console.panel),
+                    ("toolpane", &mut toolpane.panel),
+                    ("properties", &mut properties.panel),
+                    ("minimap", &mut minimap.panel),
+                    ("waveform", &mut waveform.panel),
+                ],
+            );
+        }
+
+        for record in console.take_alerts() {
+            toasts.push(&record);
+        }
+        // toasts are above every panel, so they take the input where they are
+        let is_toast_hovered = !is_in_settings && toasts.is_hovered(&theme, &window, input.cursor);
+        if is_in_settings {
+            toasts.expire();
+        } else {
+            toasts.tick(&theme, &input, &window);
+        }
+
+        if !is_in_settings && let Some(gate) = gate_wheel.tick(&input) {
+            toolpane.set_gate(gate, &mut console);
+        }
+
+        // the gate wheel is above every panel while it is open, and takes all of the input
+        let is_overlaid = is_toast_hovered || gate_wheel.is_open();
+        let focused_panel = if is_in_settings || dock.is_dragging() || is_overlaid {
+            std::ptr::null()
+        } else {
             let panels = [
                 &toolpane.panel,
                 &properties.panel,
                 &console.panel,
+                &minimap.panel,
+                &waveform.panel,
                 tabs.panel(),
             ];
+            // the context menu may cover other panels
+            let is_menu_hovered = matches!(
+                tabs.focused_tab(),
+                Some(Tab::Editor(tab)) if tab.is_context_menu_hovered(&theme, input.cursor)
+            );
             panels
                 .iter()
                 .find(|panel| panel.is_dragging())
-                .or_else(|| panels.iter().find(|panel| panel.interactable(input.cursor)))
+                .or_else(|| toolpane.is_dragging_gate().then_some(&panels[0]))
+                .or_else(|| console.is_scrolling().then_some(&panels[2]))
+                .or_else(|| minimap.is_panning().then_some(&panels[3]))
+                .or_else(|| is_menu_hovered.then_some(&panels[5]))
+                .or_else(|| {
+                    panels
+                        .iter()
+                        .find(|panel| panel.interactable(&theme, input.cursor))
+                })
                 .map(|&panel| panel as *const Panel)
                 .unwrap_or_else(std::ptr::null)
         };
 
+        // the key cursor takes the wheel's binding for cycling through nodes while it is shown
+        if std::ptr::eq(focused_panel, tabs.panel())
+            && input.gate_wheel.is_starting()
+            && !matches!(tabs.focused_tab(), Some(Tab::Editor(tab)) if tab.is_key_cursor_shown())
+        {
+            gate_wheel.open(input.cursor, toolpane.gate_order());
+        }
+
         if std::ptr::eq(focused_panel, &toolpane.panel) {
-            toolpane.tick(&mut console, &theme, &input);
+            match toolpane.tick(&mut console, &theme, &input) {
+                Some(ButtonAction::File(action)) => file_action = Some(action),
+                Some(ButtonAction::Blueprints) => {
+                    blueprint::capture_or_cycle(&mut console, &mut toolpane, &tabs);
+                }
+                Some(ButtonAction::Clipboard) => {
+                    let bounds = tabs.panel().bounds();
+                    clipboard_action = Some((
+                        ClipboardAction::for_button(&tabs),
+                        (bounds.min + bounds.max) * 0.5,
+                    ));
+                }
+                Some(ButtonAction::Settings) => {
+                    keybind_editor = Some(KeybindEditor::new(&mut binds));
+                }
+                Some(ButtonAction::Theme) => {
+                    theme_editor = Some(ThemeEditor::new(&theme));
+                }
+                Some(ButtonAction::Export) => {
+                    pending_export = Some(ExportSettings {
+                        format: ExportFormat::Png,
+                        scale: DEFAULT_EXPORT_SCALE,
+                        selection_only: matches!(
+                            tabs.focused_tab(),
+                            Some(Tab::Editor(tab)) if !tab.selection.is_empty()
+                        ),
+                    });
+                }
+                _ => {}
+            }
         } else if std::ptr::eq(focused_panel, &properties.panel) {
             properties.tick(&theme, |properties, bounds, theme| {
                 let mut y = bounds.min.y;
-                if let Tool::Edit {
-                    target: Some(tool::EditDragging { id, .. }),
-                } = &toolpane.tool
-                    && let Some(Tab::Editor(tab)) = tabs.focused_tab()
+                let mut edited_graph = None;
+                let mut watch_toggled = None;
+                if let Some(Tab::Editor(tab)) = tabs.focused_tab()
+                    && let Some(id) = tab.inspected_node(&toolpane.tool)
+                    && let Some(graph) = tab.graph.upgrade()
+                    && let Ok(mut borrow) = graph.write()
+                    && let Some(node) = borrow.node(&id)
+                {
+                    let mut section =
+                        properties.node_section(node, borrow.grid_size(), tab.is_watched(&id));
+                    y = properties.tick_section(&mut rl, &thread, theme, &input, y, &mut section);
+                    let revision = borrow.revision();
+                    let (edits, toggle_watch) = properties.finish_node_section(section);
+                    if !edits.is_empty() && tab.check_unlocked(&mut console) {
+                        for edit in edits {
+                            edit.apply(&mut borrow, &id, &mut console);
+                        }
+                    }
+                    if borrow.revision() != revision {
+                        edited_graph = Some(Arc::downgrade(&graph));
+                    }
+                    if toggle_watch {
+                        watch_toggled = Some(id);
+                    }
+                }
+                // watching belongs to the tab, which is borrowed above
+                if let Some(id) = watch_toggled
+                    && let Some(Tab::Editor(tab)) = tabs.focused_tab_mut()
+                    && let Some(graph) = tab.graph.upgrade()
+                    && let Ok(borrow) = graph.read()
+                {
+                    tab.toggle_watch(&borrow, id, &mut console);
+                }
+                if let Some(Tab::Editor(tab)) = tabs.focused_tab()
+                    && !tab.is_locked()
+                    && let Tool::Annotate {
+                        target: Some(id), ..
+                    } = toolpane.tool
                     && let Some(graph) = tab.graph.upgrade()
                     && let Ok(mut borrow) = graph.write()
+                    && let Some(annotation) = borrow.annotation_mut(&id)
                 {
-                    let node = borrow.node_mut(id).expect("edit target should be valid");
-                    y = properties.tick_section(&mut rl, &thread, theme, &input, y, node);
+                    let before = annotation.text.clone();
+                    y = properties.tick_section(&mut rl, &thread, theme, &input, y, annotation);
+                    if annotation.text != before {
+                        edited_graph = Some(Arc::downgrade(&graph));
+                    }
+                }
+                if let Some(Tab::Editor(tab)) = tabs.focused_tab()
+                    && let Some(graph) = tab.graph.upgrade()
+                    && let Ok(mut borrow) = graph.write()
+                    && let Some(mut stats) = SelectionStats::new(&borrow, &tab.selection)
+                {
+                    y = properties.tick_section(&mut rl, &thread, theme, &input, y, &mut stats);
+                    let edits = stats.into_edits();
+                    let mut changed = 0;
+                    if !edits.is_empty() && tab.check_unlocked(&mut console) {
+                        for edit in edits {
+                            changed += edit.apply(&mut borrow, &tab.selection, &mut console);
+                        }
+                    }
+                    if changed > 0 {
+                        edited_graph = Some(Arc::downgrade(&graph));
+                    }
+                }
+                if let Some(graph) = edited_graph {
+                    for tab in tabs.editors_of_graph_mut(&graph) {
+                        tab.mark_modified();
+                    }
+                }
+                if let Some(Tab::Editor(tab)) = tabs.focused_tab()
+                    && let Some(graph) = tab.graph.upgrade()
+                    && let Ok(mut borrow) = graph.write()
+                    && let Some(history) = borrow.history_mut()
+                {
+                    y = properties.tick_section(&mut rl, &thread, theme, &input, y, history);
                 }
                 y = properties.tick_section(&mut rl, &thread, theme, &input, y, &mut toolpane.tool);
+                if let Tool::Create { .. } = toolpane.tool {
+                    let before = toolpane.elbow;
+                    y = properties.tick_section(
+                        &mut rl,
+                        &thread,
+                        theme,
+                        &input,
+                        y,
+                        &mut toolpane.elbow,
+                    );
+                    if toolpane.elbow != before {
+                        logln!(
+                            &mut console,
+                            LogType::Info,
+                            "wire elbow: {}",
+                            toolpane.elbow
+                        );
+                    }
+                }
                 y = properties.tick_section(&mut rl, &thread, theme, &input, y, &mut toolpane.gate);
                 _ = y;
             });
         } else if std::ptr::eq(focused_panel, &console.panel) {
-            console.tick(&theme, &input, &graphs);
+            let graph = match tabs.focused_tab() {
+                Some(Tab::Editor(tab)) => tab.graph.upgrade(),
+                None => None,
+            };
+            let graph = graph.as_ref().and_then(|graph| graph.try_read().ok());
+            let action = console.tick(&mut rl, &theme, &input, graph.as_deref(), &graphs);
+            drop(graph);
+            match action {
+                Some(ConsoleAction::Command(line)) => {
+                    let outcome = command::run(
+                        &line,
+                        &mut console,
+                        &graphs,
+                        &mut tabs,
+                        &mut theme,
+                        &mut toolpane,
+                        &mut backups,
+                    );
+                    if outcome.is_modified {
+                        // refresh immediately on change
+                        next_eval_tick = Instant::now();
+                    }
+                    pending_export = pending_export.or(outcome.export);
+                    if let Some(action) = outcome.preset {
+                        action.run(&mut console, &mut binds);
+                    }
+                    if let Some(action) = outcome.input_macro {
+                        let window = (rl.get_screen_width(), rl.get_screen_height());
+                        action.run(
+                            &mut console,
+                            &mut input_macro,
+                            window,
+                            macro_frames_per_tick,
+                        );
+                    }
+                    if let Some(path) = outcome.open {
+                        file::open_path(
+                            &mut rl,
+                            &thread,
+                            &mut console,
+                            &mut graphs,
+                            &mut tabs,
+                            path,
+                        );
+                    }
+                    if let Some(path) = outcome.save_as {
+                        file::save_focused(&mut console, &mut graphs, &mut tabs, Some(path));
+                    }
+                    if let Some(path) = outcome.import {
+                        file::import_obj(
+                            &mut rl,
+                            &thread,
+                            &mut console,
+                            &mut graphs,
+                            &mut tabs,
+                            &path,
+                        );
+                    }
+                }
+                Some(ConsoleAction::Follow(hyper_ref)) => {
+                    hyper_ref.follow(&mut console, &graphs, &mut tabs, &mut toolpane);
+                }
+                Some(ConsoleAction::Watch(node_ref)) => {
+                    let found = node_ref.deref_with(&graphs, |graph, borrow, _| {
+                        // watched in the tab the graph is shown in, so that it is plotted
+                        match tabs.focus_graph(&Arc::downgrade(graph)) {
+                            Some(tab) => tab.toggle_watch(borrow, node_ref.1, &mut console),
+                            None => logln!(&mut console, LogType::Info, "no tab shows {node_ref}"),
+                        }
+                    });
+                    if found.is_none() {
+                        logln!(
+                            &mut console,
+                            LogType::Warning,
+                            "{node_ref} no longer exists"
+                        );
+                    }
+                }
+                None => {}
+            }
+        } else if std::ptr::eq(focused_panel, &minimap.panel) {
+            if let Some(Tab::Editor(tab)) = tabs.focused_tab_mut() {
+                minimap.tick(tab, &theme, &input);
+            }
         } else if std::ptr::eq(focused_panel, tabs.panel()) {
-            if let Some(tab) = tabs.focused_tab_mut() {
+            if tabs.tick_bar(&mut rl, &thread, &mut console, &mut graphs, &theme, &input) {
+                // the tab bar is using the input
+            } else if input.open_view.is_starting() {
+                if let Err(e) = tabs.open_view(&mut rl, &thread) {
+                    logln!(&mut console, LogType::Error, "failed to create tab: {e}");
+                }
+            } else if input.open_scratch.is_starting() {
+                if let Err(e) = tabs.open_scratch(&mut rl, &thread, &mut graphs) {
+                    logln!(&mut console, LogType::Error, "failed to create tab: {e}");
+                }
+            } else if let Some(tab) = tabs.focused_tab_mut() {
+                clipboard_action = input
+                    .clipboard_action()
+                    .map(|action| (action, input.cursor));
                 match tab {
                     Tab::Editor(tab) => {
-                        let is_dirty = tab.tick(&mut console, &mut toolpane, &theme, &input);
+                        let size = sound::graph_size(tab);
+                        let is_dirty = tab.tick(
+                            &mut console,
+                            &mut toolpane,
+                            &theme,
+                            &input,
+                            &mut clipboard_action,
+                        );
                         if is_dirty {
                             // refresh immediately on change
                             next_eval_tick = Instant::now();
                         }
+                        if let Some(effect) = size
+                            .zip(sound::graph_size(tab))
+                            .and_then(|(before, after)| SoundEffect::for_change(before, after))
+                        {
+                            sounds.play(effect);
+                        }
                     }
                 }
             } else {
@@ -311,19 +922,73 @@ fn main() {
             }
         }
 
+        // whichever panel has the cursor, so that a button can't get stuck down
+        if (!input.primary.is_active() || !rl.is_window_focused()) && toolpane.tool.release_button()
+        {
+            // refresh immediately on change
+            next_eval_tick = Instant::now();
+        }
+
+        if let Some(Tab::Editor(tab)) = tabs.focused_tab_mut() {
+            tab.advance_camera_animation();
+        }
+        tabs.sync_cameras();
+
+        if let Some((action, position)) = clipboard_action {
+            action.perform(&mut rl, &mut console, &mut tabs, position);
+            // refresh immediately on change
+            next_eval_tick = Instant::now();
+        }
+
+        if let Some(action) = file_action {
+            action.perform(&mut console, &mut graphs, &mut tabs);
+        }
+
+        if let Some(settings) = pending_export {
+            export::export_focused(&mut rl, &thread, &mut console, &theme, &tabs, settings);
+        }
+
+        if rl.is_file_dropped() {
+            let paths = rl
+                .load_dropped_files()
+                .paths()
+                .into_iter()
+                .map(PathBuf::from)
+                .collect::<Vec<_>>();
+            // holding alternate merges them into the focused graph where they were dropped
+            let merge_at = input.alternate.is_active().then_some(input.cursor);
+            for path in paths {
+                if merge_at.is_some() {
+                    file::merge_path(&mut console, &graphs, &mut tabs, &path, merge_at);
+                } else {
+                    file::open_path(&mut rl, &thread, &mut console, &mut graphs, &mut tabs, path);
+                }
+            }
+        }
+
         {
             let viewport = *tabs.panel().bounds();
             if let Some(focused_tab) = tabs.focused_tab_mut() {
                 match focused_tab {
-                    Tab::Editor(tab) => tab.refresh_grid(&mut rl, &thread, &theme, &viewport),
+                    Tab::Editor(tab) => {
+                        tab.refresh_grid(&mut rl, &thread, &theme, &viewport);
+                        tab.refresh_layer(&mut rl, &thread, &theme, &viewport, &toolpane.tool);
+                    }
                 }
             }
+            let focused_editor = match tabs.focused_tab() {
+                Some(Tab::Editor(tab)) => Some(tab),
+                None => None,
+            };
+            minimap.refresh(&mut rl, &thread, &theme, focused_editor);
         }
 
         rl.set_mouse_cursor(
             [
                 console.panel.hover.as_ref(),
                 properties.panel.hover.as_ref(),
+                minimap.panel.hover.as_ref(),
+                waveform.panel.hover.as_ref(),
                 toolpane.panel.hover.as_ref(),
                 tabs.panel().hover.as_ref(),
             ]
@@ -341,19 +1006,83 @@ fn main() {
             }),
         );
 
-        for mut graph in graphs.iter_mut().filter_map(|g| g.try_write().ok()) {
+        profiler.lap(Phase::Tick);
+        let macro_tick = input_macro.is_tick_frame();
+        for arc in graphs.iter() {
+            let Ok(mut graph) = arc.try_write() else {
+                continue;
+            };
+            let weak = Arc::downgrade(arc);
             if graph.is_eval_order_dirty() {
                 graph.refresh_eval_order();
             }
+            graph.refresh_ics(&graphs, &mut console);
+            if let Some(is_tick) = macro_tick {
+                // replaying shows recorded states, so the live circuit waits
+                if is_tick && !graph.is_replaying() {
+                    graph.evaluate();
+                    for tab in tabs.editors_of_graph_mut(&weak) {
+                        tab.sample_watched(&graph);
+                    }
+                }
+                continue;
+            }
             let now = Instant::now();
-            while now >= next_eval_tick {
-                graph.evaluate();
+            while let Some(eval_duration) = eval_duration
+                && now >= next_eval_tick
+            {
+                // replaying shows recorded states, so the live circuit waits
+                if !graph.is_replaying() {
+                    graph.evaluate();
+                    for tab in tabs.editors_of_graph_mut(&weak) {
+                        tab.sample_watched(&graph);
+                    }
+                }
                 next_eval_tick += eval_duration;
             }
         }
+        if macro_tick.is_some() {
+            // so that the clock doesn't catch up on every tick skipped once the macro stops
+            next_eval_tick = Instant::now();
+        }
+        profiler.lap(Phase::Eval);
+
+        for tab in tabs.editors_mut() {
+            tab.log_watched(&mut console);
+            tab.refresh_graph_counts();
+        }
+
+        backups.tick(&mut console, &graphs, &tabs);
+
+        if console.error_count() != error_count {
+            sounds.play(SoundEffect::Error);
+        }
+
+        if let Some(mut new_theme) = config_watcher.tick(&mut console) {
+            new_theme.set_ui_scale(ui_scale);
+            match new_theme.reload_assets(&mut rl, &thread) {
+                Ok(()) => {
+                    theme = new_theme;
+                    for tab in tabs.editors_mut() {
+                        tab.mark_grid_dirty();
+                    }
+                    minimap.mark_dirty();
+                }
+                Err(e) => {
+                    logln!(
+                        &mut console,
+                        LogType::Error,
+                        "failed to load theme assets: {e}"
+                    );
+                }
+            }
+        }
+
+        profiler.lap(Phase::Tick);
 
         // Draw
 
+        let is_editor_focused = std::ptr::eq(focused_panel, tabs.panel());
         let mut d = rl.begin_drawing(&thread);
         d.clear_background(theme.background);
 
@@ -366,36 +1095,153 @@ fn main() {
                     }
                 }
             }
+            tabs.draw_bar(&mut d, &theme, &input);
         }
 
         // toolpane
-        {
+        if toolpane.panel.is_shown() {
             toolpane.draw(&mut d, &input, &theme);
         }
 
         // console
-        {
+        if console.panel.is_shown() {
             console.draw(&mut d, &theme, &input, &graphs, &tabs, &toolpane);
         }
 
+        // minimap
+        if minimap.panel.is_shown() {
+            let focused_editor = match tabs.focused_tab() {
+                Some(Tab::Editor(tab)) => Some(tab),
+                None => None,
+            };
+            minimap.draw(&mut d, &theme, focused_editor);
+        }
+
+        // waveform
+        if waveform.panel.is_shown() {
+            let focused_editor = match tabs.focused_tab() {
+                Some(Tab::Editor(tab)) => Some(tab),
+                None => None,
+            };
+            waveform.draw(&mut d, &theme, focused_editor);
+        }
+
         // properties
-        {
+        if properties.panel.is_shown() {
             properties.draw(&mut d, &theme, |properties, d, bounds, theme| {
                 let mut y = bounds.min.y;
-                if let Tool::Edit {
-                    target: Some(tool::EditDragging { id, .. }),
-                } = &toolpane.tool
-                    && let Some(Tab::Editor(tab)) = tabs.focused_tab()
+                if let Some(Tab::Editor(tab)) = tabs.focused_tab()
+                    && let Some(id) = tab.inspected_node(&toolpane.tool)
                     && let Some(graph) = tab.graph.upgrade()
                     && let Ok(borrow) = graph.read()
+                    && let Some(node) = borrow.node(&id)
                 {
-                    let node = borrow.node(id).expect("edit target should be valid");
-                    y = properties.draw_section(d, theme, bounds, y, node);
+                    let section =
+                        properties.node_section(node, borrow.grid_size(), tab.is_watched(&id));
+                    y = properties.draw_section(d, theme, bounds, y, &section);
+                }
+                if let Some(Tab::Editor(tab)) = tabs.focused_tab()
+                    && let Tool::Annotate {
+                        target: Some(id), ..
+                    } = toolpane.tool
+                    && let Some(graph) = tab.graph.upgrade()
+                    && let Ok(borrow) = graph.read()
+                    && let Some(annotation) = borrow.annotation(&id)
+                {
+                    y = properties.draw_section(d, theme, bounds, y, annotation);
+                }
+                if let Some(Tab::Editor(tab)) = tabs.focused_tab()
+                    && let Some(graph) = tab.graph.upgrade()
+                    && let Ok(borrow) = graph.read()
+                    && let Some(stats) = SelectionStats::new(&borrow, &tab.selection)
+                {
+                    y = properties.draw_section(d, theme, bounds, y, &stats);
+                }
+                if let Some(Tab::Editor(tab)) = tabs.focused_tab()
+                    && let Some(graph) = tab.graph.upgrade()
+                    && let Ok(borrow) = graph.read()
+                    && let Some(history) = borrow.history()
+                {
+                    y = properties.draw_section(d, theme, bounds, y, history);
                 }
                 y = properties.draw_section(d, theme, bounds, y, &toolpane.tool);
+                if let Tool::Create { .. } = toolpane.tool {
+                    y = properties.draw_section(d, theme, bounds, y, &toolpane.elbow);
+                }
                 y = properties.draw_section(d, theme, bounds, y, &toolpane.gate);
                 _ = y;
             });
         }
+
+        // titles of stacked panels, and where a panel being moved would go
+        dock.draw(
+            &mut d,
+            &theme,
+            &[
+                ("console", &console.panel),
+                ("toolpane", &toolpane.panel),
+                ("properties", &properties.panel),
+                ("minimap", &minimap.panel),
+                ("waveform", &waveform.panel),
+            ],
+        );
+
+        // tooltip and context menu, above every panel
+        if let Some(Tab::Editor(tab)) = tabs.focused_tab() {
+            if is_editor_focused {
+                tab.draw_node_tooltip(&mut d, tabs.panel().bounds(), &theme, &input, &toolpane);
+            }
+            tab.draw_context_menu(&mut d, &theme, &input);
+        }
+        gate_wheel.draw(&mut d, &theme, &input, toolpane.gate.id());
+
+        toasts.draw(&mut d, &theme, &input, &window);
+
+        if let Some(editor) = &keybind_editor {
+            editor.draw(&mut d, &theme, &input, window);
+        }
+        if let Some(editor) = &theme_editor {
+            editor.draw(&mut d, &theme, &input, window);
+        }
+        if let Some(overlay) = &onboarding {
+            overlay.draw(&mut d, &theme, &input, onboarding_panels, window);
+        }
+
+        let graph_counts = match tabs.focused_tab() {
+            Some(Tab::Editor(tab)) => tab.graph_counts(),
+            None => None,
+        };
+        let fps = d.get_fps();
+        profiler.draw(
+            &mut d,
+            &theme,
+            &window,
+            fps,
+            graph_counts,
+            console.buffer_usage(),
+        );
+        profiler.lap(Phase::Draw);
+    }
+
+    // a recording still going when the window closes is written out
+    input_macro.stop(&mut console);
+
+    let session = Session::capture(
+        &tabs,
+        &dock,
+        [
+            ("console", &console.panel),
+            ("toolpane", &toolpane.panel),
+            ("properties", &properties.panel),
+            ("minimap", &minimap.panel),
+            ("waveform", &waveform.panel),
+        ],
+    );
+    if let Err(e) = session.save() {
+        logln!(
+            &mut console,
+            LogType::Error,
+            "failed to save {SESSION_PATH}: {e}"
+        );
     }
 }
@@ -0,0 +1,257 @@
+//! A small overview of the focused graph, with the part of it in view outlined. Clicking the
+//! overview moves the view there, and dragging pans it.
+//!
+//! The graph is drawn onto a cached texture that is only redrawn after the graph is edited, so
+//! that large graphs don't need to be drawn twice every frame.
+
+use crate::{
+    graph::{Graph, GraphId},
+    input::Inputs,
+    ivec::Bounds,
+    tab::EditorTab,
+    theme::Theme,
+    ui::{Panel, PanelContent},
+};
+use raylib::prelude::*;
+
+/// Empty space around the graph, in grid cells.
+const MARGIN_CELLS: f32 = 4.0;
+
+#[derive(Debug)]
+struct MinimapCache {
+    texture: RenderTexture2D,
+    graph: GraphId,
+    /// [`Graph::revision`] when the texture was drawn
+    revision: u64,
+    /// The part of the world drawn onto the texture, with the same aspect ratio as the texture
+    world: Bounds,
+}
+
+#[derive(Debug)]
+pub struct Minimap {
+    pub panel: Panel,
+    cache: Option<MinimapCache>,
+    /// Whether the texture needs to be redrawn even if the graph hasn't changed
+    dirty: bool,
+    /// Whether the view is being dragged from the minimap
+    is_panning: bool,
+}
+
+impl PanelContent for Minimap {
+    #[inline]
+    fn panel(&self) -> &Panel {
+        &self.panel
+    }
+
+    #[inline]
+    fn panel_mut(&mut self) -> &mut Panel {
+        &mut self.panel
+    }
+
+    #[inline]
+    fn content_size(&self, _theme: &Theme) -> Vector2 {
+        Vector2::zero()
+    }
+}
+
+/// The area taken up by the graph's nodes, with a margin, stretched to the aspect ratio of
+/// `container`.
+fn world_bounds(graph: &Graph, container: Bounds) -> Bounds {
+    let grid_size = f32::from(graph.grid_size());
+    let margin = Vector2::new(grid_size, grid_size) * MARGIN_CELLS;
+    let mut positions = graph.nodes_iter().map(|node| node.position().as_vec2());
+    let bounds = match positions.next() {
+        Some(first) => {
+            let (min, max) = positions.fold((first, first), |(min, max), p| {
+                (
+                    Vector2::new(min.x.min(p.x), min.y.min(p.y)),
+                    Vector2::new(max.x.max(p.x), max.y.max(p.y)),
+                )
+            });
+            Bounds::new(
+                min - margin,
+                max + Vector2::new(grid_size, grid_size) + margin,
+            )
+        }
+        None => Bounds::new(-margin, margin),
+    };
+    // grow the shorter side so the graph isn't stretched
+    let scale = (container.width() / bounds.width()).min(container.height() / bounds.height());
+    let size = Vector2::new(container.width(), container.height()) / scale;
+    let center = (bounds.min + bounds.max) * 0.5;
+    Bounds::new(center - size * 0.5, center + size * 0.5)
+}
+
+impl Minimap {
+    pub const fn new(panel: Panel) -> Self {
+        Self {
+            panel,
+            cache: None,
+            dirty: true,
+            is_panning: false,
+        }
+    }
+
+    /// Whether the view is being dragged from the minimap, which keeps the input even after the
+    /// cursor leaves it.
+    #[inline]
+    pub const fn is_panning(&self) -> bool {
+        self.is_panning
+    }
+
+    /// Redraw on the next refresh, such as after the theme changes.
+    #[inline]
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Where the world position under `screen_pos` is, if the minimap has been drawn.
+    fn screen_to_world(&self, theme: &Theme, screen_pos: Vector2) -> Option<Vector2> {
+        let cache = self.cache.as_ref()?;
+        let content = self.panel.content_bounds(theme);
+        let scale = content.width() / cache.world.width();
+        Some(cache.world.min + (screen_pos - content.min) / scale)
+    }
+
+    pub fn tick(&mut self, tab: &mut EditorTab, theme: &Theme, input: &Inputs) {
+        // the edges resize the panel instead
+        if input.primary.is_starting()
+            && self.panel.hover.is_none()
+            && self.panel.content_bounds(theme).contains(input.cursor)
+        {
+            self.is_panning = true;
+        } else if !input.primary.is_active() {
+            self.is_panning = false;
+        }
+        if self.is_panning
+            && let Some(world_pos) = self.screen_to_world(theme, input.cursor)
+        {
+            tab.center_on(world_pos);
+        }
+    }
+
+    /// Redraws the cached texture if the graph shown in `tab` was edited, or it was resized.
+    pub fn refresh(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        theme: &Theme,
+        tab: Option<&EditorTab>,
+    ) {
+        let Some(graph) = tab.and_then(|tab| tab.graph.upgrade()) else {
+            self.cache = None;
+            return;
+        };
+        let Ok(graph) = graph.try_read() else {
+            // busy, try again next frame
+            return;
+        };
+        let content = self.panel.content_bounds(theme);
+        let (width, height) = (content.width() as i32, content.height() as i32);
+        if width <= 0 || height <= 0 {
+            self.cache = None;
+            return;
+        }
+        let is_current = self.cache.as_ref().is_some_and(|cache| {
+            cache.graph == *graph.id()
+                && cache.revision == graph.revision()
+                && cache.texture.width() == width
+                && cache.texture.height() == height
+        });
+        if is_current && !self.dirty {
+            return;
+        }
+        self.dirty = false;
+
+        let texture = match self.cache.take() {
+            Some(cache) if cache.texture.width() == width && cache.texture.height() == height => {
+                cache.texture
+            }
+            _ => match rl.load_render_texture(thread, width as u32, height as u32) {
+                Ok(texture) => texture,
+                Err(_) => return,
+            },
+        };
+        let world = world_bounds(&graph, content);
+        let mut cache = MinimapCache {
+            texture,
+            graph: *graph.id(),
+            revision: graph.revision(),
+            world,
+        };
+
+        let grid_size = f32::from(graph.grid_size());
+        let cell_center = graph.cell_center();
+        let scale = content.width() / world.width();
+        // nodes stay at least a pixel wide however far the graph is zoomed out
+        let node_size = grid_size.max(1.0 / scale);
+        {
+            let mut d = rl.begin_texture_mode(thread, &mut cache.texture);
+            d.clear_background(Color::BLANK);
+            let mut d = d.begin_mode2D(Camera2D {
+                offset: Vector2::zero(),
+                target: world.min,
+                rotation: 0.0,
+                zoom: scale,
+            });
+            for wire in graph.wires_iter() {
+                if let Some(path) = wire.path(&graph, cell_center) {
+                    d.draw_line_strip(&path, theme.foreground2);
+                }
+            }
+            for node in graph.nodes_iter() {
+                let position = node.position().as_vec2();
+                d.draw_rectangle_rec(
+                    Rectangle::new(position.x, position.y, node_size, node_size),
+                    theme.foreground,
+                );
+            }
+        }
+        self.cache = Some(cache);
+    }
+
+    pub fn draw<D: RaylibDraw>(&self, d: &mut D, theme: &Theme, tab: Option<&EditorTab>) {
+        self.panel.draw(d, theme, |d, bounds, theme| {
+            let Some(cache) = &self.cache else {
+                return;
+            };
+            let Rectangle {
+                x,
+                y,
+                width,
+                height,
+            } = Rectangle::from(bounds);
+            let mut d = d.begin_scissor_mode(x as i32, y as i32, width as i32, height as i32);
+            // render textures are upside down
+            d.draw_texture_pro(
+                cache.texture.texture(),
+                Rectangle::new(
+                    0.0,
+                    0.0,
+                    cache.texture.width() as f32,
+                    -cache.texture.height() as f32,
+                ),
+                Rectangle::new(
+                    x,
+                    y,
+                    cache.texture.width() as f32,
+                    cache.texture.height() as f32,
+                ),
+                Vector2::zero(),
+                0.0,
+                Color::WHITE,
+            );
+            if let Some(tab) = tab {
+                let scale = width / cache.world.width();
+                let view = tab.view_bounds();
+                let min = bounds.min + (view.min - cache.world.min) * scale;
+                let max = bounds.min + (view.max - cache.world.min) * scale;
+                d.draw_rectangle_lines_ex(
+                    Rectangle::from(Bounds::new(min, max)),
+                    1.0,
+                    theme.interact,
+                );
+            }
+        });
+    }
+}
@@ -0,0 +1,242 @@
+//! Callouts shown over the panels on first run, saying what each one is for.
+
+use crate::{
+    config::{self, CONFIG_PATH},
+    console::{Console, LogType},
+    input::Inputs,
+    ivec::Bounds,
+    logln,
+    theme::Theme,
+};
+use raylib::prelude::*;
+
+const TITLE: &str = "Welcome to Electron Architect";
+const CHECKBOX_LABEL: &str = "Don't show again";
+const DONE_LABEL: &str = "Got it";
+/// Space between a callout and the panel it points at.
+const GAP: f32 = 24.0;
+
+/// Which side of its panel a callout sits on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Below,
+    Above,
+    Left,
+}
+
+/// The panels pointed out, in the order of [`Onboarding::tick`]'s `panels`.
+const CALLOUTS: [(Side, &str); 3] = [
+    (
+        Side::Below,
+        "Toolpane: pick a tool and a gate, then click the canvas to place nodes.",
+    ),
+    (
+        Side::Above,
+        "Log: messages appear here. Type \"help\" to list commands.",
+    ),
+    (
+        Side::Left,
+        "Properties: details of the tool, gate and inspected node.",
+    ),
+];
+
+/// Where everything in the overlay goes for a size of window.
+struct Layout {
+    /// `(box, point on the panel)` of each callout
+    callouts: [(Rectangle, Vector2); 3],
+    frame: Rectangle,
+    title_position: Vector2,
+    checkbox: Rectangle,
+    done: Rectangle,
+}
+
+/// Dims the window and points out the panels until dismissed.
+#[derive(Debug, Default)]
+pub struct Onboarding {
+    dont_show_again: bool,
+}
+
+impl Onboarding {
+    fn layout(theme: &Theme, panels: [Bounds; 3], window: Bounds) -> Layout {
+        let padding = theme.title_padding;
+        let line_height = theme.general_font.line_height();
+
+        let callouts = std::array::from_fn(|n| {
+            let (side, text) = CALLOUTS[n];
+            let panel = panels[n];
+            let size = theme.general_font.measure_text(text) + padding.size();
+            let (position, target) = match side {
+                Side::Below => (
+                    Vector2::new(panel.min.x, panel.max.y + GAP),
+                    Vector2::new((panel.min.x + panel.max.x) * 0.5, panel.max.y),
+                ),
+                Side::Above => (
+                    Vector2::new(panel.min.x + GAP, panel.min.y - GAP - size.y),
+                    Vector2::new((panel.min.x + panel.max.x) * 0.5, panel.min.y),
+                ),
+                Side::Left => (
+                    Vector2::new(panel.min.x - GAP - size.x, panel.min.y + GAP),
+                    Vector2::new(panel.min.x, panel.min.y + GAP + size.y * 0.5),
+                ),
+            };
+            // keep the whole callout on screen, even if its panel is not
+            let position = Vector2::new(
+                position
+                    .x
+                    .clamp(window.min.x, (window.max.x - size.x).max(window.min.x)),
+                position
+                    .y
+                    .clamp(window.min.y, (window.max.y - size.y).max(window.min.y)),
+            );
+            (
+                Rectangle::new(position.x, position.y, size.x, size.y),
+                target,
+            )
+        });
+
+        let title_size = theme.title_font.measure_text(TITLE);
+        let checkbox_width = line_height
+            + padding.left
+            + theme.general_font.measure_text(CHECKBOX_LABEL).x
+            + padding.horizontal();
+        let done_width = theme.general_font.measure_text(DONE_LABEL).x + padding.horizontal();
+        let row_height = line_height + padding.vertical();
+        let width =
+            title_size.x.max(checkbox_width + padding.left + done_width) + padding.horizontal();
+        let height = title_size.y + padding.vertical() + row_height + padding.bottom;
+        let frame = Rectangle::new(
+            (window.min.x + window.max.x - width) * 0.5,
+            (window.min.y + window.max.y - height) * 0.5,
+            width,
+            height,
+        );
+        let title_position = Vector2::new(frame.x + padding.left, frame.y + padding.top);
+        let row_y = title_position.y + title_size.y + padding.bottom;
+
+        Layout {
+            callouts,
+            frame,
+            title_position,
+            checkbox: Rectangle::new(frame.x + padding.left, row_y, checkbox_width, row_height),
+            done: Rectangle::new(
+                frame.x + frame.width - padding.right - done_width,
+                row_y,
+                done_width,
+                row_height,
+            ),
+        }
+    }
+
+    /// Takes all input while shown. `panels` are the bounds of the toolpane, the console and
+    /// the properties panel. Returns whether the overlay should stay shown.
+    pub fn tick(
+        &mut self,
+        rl: &RaylibHandle,
+        console: &mut Console,
+        theme: &Theme,
+        input: &Inputs,
+        panels: [Bounds; 3],
+        window: Bounds,
+    ) -> bool {
+        let layout = Self::layout(theme, panels, window);
+        let is_done = rl.is_key_pressed(KeyboardKey::KEY_ESCAPE)
+            || (input.primary.is_starting() && Bounds::from(layout.done).contains(input.cursor));
+        if input.primary.is_starting() && Bounds::from(layout.checkbox).contains(input.cursor) {
+            self.dont_show_again = !self.dont_show_again;
+        }
+        if !is_done {
+            return true;
+        }
+
+        if self.dont_show_again {
+            match config::save_entry("show_onboarding", &false) {
+                Ok(()) => logln!(
+                    console,
+                    LogType::Info,
+                    "the welcome overlay will not be shown again; set show_onboarding in {CONFIG_PATH} to bring it back"
+                ),
+                Err(e) => logln!(console, LogType::Error, "failed to save config: {e}"),
+            }
+        }
+        false
+    }
+
+    pub fn draw<D: RaylibDraw>(
+        &self,
+        d: &mut D,
+        theme: &Theme,
+        input: &Inputs,
+        panels: [Bounds; 3],
+        window: Bounds,
+    ) {
+        let layout = Self::layout(theme, panels, window);
+        let padding = theme.title_padding;
+        let line_height = theme.general_font.line_height();
+
+        d.draw_rectangle_rec(Rectangle::from(window), theme.background.alpha(0.6));
+
+        for ((rec, target), (_, text)) in layout.callouts.iter().zip(CALLOUTS) {
+            let center = Vector2::new(rec.x + rec.width * 0.5, rec.y + rec.height * 0.5);
+            d.draw_line_ex(center, *target, 2.0, theme.special);
+            d.draw_circle_v(*target, 4.0, theme.special);
+            d.draw_rectangle_rec(*rec, theme.background2);
+            d.draw_rectangle_lines_ex(*rec, 1.0, theme.special);
+            theme.general_font.draw_text(
+                d,
+                text,
+                Vector2::new(rec.x + padding.left, rec.y + padding.top),
+                theme.foreground,
+            );
+        }
+
+        d.draw_rectangle_rec(layout.frame, theme.background1);
+        d.draw_rectangle_lines_ex(layout.frame, 1.0, theme.foreground3);
+        theme
+            .title_font
+            .draw_text(d, TITLE, layout.title_position, theme.foreground);
+
+        let checkbox = layout.checkbox;
+        let box_rec = Rectangle::new(
+            checkbox.x + padding.left,
+            checkbox.y + padding.top,
+            line_height,
+            line_height,
+        );
+        if Bounds::from(checkbox).contains(input.cursor) {
+            d.draw_rectangle_rec(checkbox, theme.background3);
+        }
+        d.draw_rectangle_lines_ex(box_rec, 1.0, theme.foreground2);
+        if self.dont_show_again {
+            d.draw_rectangle_rec(
+                Rectangle::new(
+                    box_rec.x + 3.0,
+                    box_rec.y + 3.0,
+                    box_rec.width - 6.0,
+                    box_rec.height - 6.0,
+                ),
+                theme.foreground,
+            );
+        }
+        theme.general_font.draw_text(
+            d,
+            CHECKBOX_LABEL,
+            Vector2::new(box_rec.x + box_rec.width + padding.left, box_rec.y),
+            theme.foreground,
+        );
+
+        d.draw_rectangle_rec(
+            layout.done,
+            if Bounds::from(layout.done).contains(input.cursor) {
+                theme.background3
+            } else {
+                theme.background2
+            },
+        );
+        theme.general_font.draw_text(
+            d,
+            DONE_LABEL,
+            Vector2::new(layout.done.x + padding.left, layout.done.y + padding.top),
+            theme.foreground,
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! An overlay in the top-right corner of the window, toggled with [`Inputs::toggle_profiler`],
+//! showing the frame rate, how long each phase of a frame takes, the size of the focused graph
+//! and how much memory the log takes up.
+//!
+//! [`Inputs::toggle_profiler`]: crate::input::Inputs::toggle_profiler
+
+use crate::{ivec::Bounds, theme::Theme};
+use raylib::prelude::*;
+use std::time::{Duration, Instant};
+
+/// How much of each frame's timings go into the averages shown, so that they can be read instead
+/// of flickering.
+const SMOOTHING: f32 = 0.1;
+/// Space between the overlay and the edges of the window.
+const OVERLAY_MARGIN: f32 = 8.0;
+
+/// A part of a frame timed by the [`Profiler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Reading and replaying input
+    Input,
+    /// Everything that reacts to input, and background work such as backups
+    Tick,
+    /// Evaluating every graph
+    Eval,
+    /// Issuing draw calls, not counting the wait for the next frame
+    Draw,
+}
+
+impl Phase {
+    pub const ALL: [Self; 4] = [Self::Input, Self::Tick, Self::Eval, Self::Draw];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Input => "input",
+            Self::Tick => "tick",
+            Self::Eval => "eval",
+            Self::Draw => "draw",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Profiler {
+    pub is_shown: bool,
+    /// When the last [`Self::lap`] ended
+    lap_start: Instant,
+    /// Time spent in each [`Phase`] so far this frame
+    frame: [Duration; Phase::ALL.len()],
+    /// Seconds spent in each [`Phase`] per frame, smoothed over recent frames
+    average: [f32; Phase::ALL.len()],
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self {
+            is_shown: false,
+            lap_start: Instant::now(),
+            frame: [Duration::ZERO; Phase::ALL.len()],
+            average: [0.0; Phase::ALL.len()],
+        }
+    }
+}
+
+impl Profiler {
+    /// Adds the timings of the last frame to the averages, and starts timing the next one.
+    pub fn begin_frame(&mut self) {
+        for (average, time) in self.average.iter_mut().zip(self.frame) {
+            *average += (time.as_secs_f32() - *average) * SMOOTHING;
+        }
+        self.frame = [Duration::ZERO; Phase::ALL.len()];
+        self.lap_start = Instant::now();
+    }
+
+    /// Counts the time since the last lap towards `phase`.
+    pub fn lap(&mut self, phase: Phase) {
+        let now = Instant::now();
+        self.frame[phase as usize] += now - self.lap_start;
+        self.lap_start = now;
+    }
+
+    /// `graph_counts` are the nodes and wires of the focused graph, if there is one, and
+    /// `log_usage` is from [`Console::buffer_usage`](crate::console::Console::buffer_usage).
+    pub fn draw<D: RaylibDraw>(
+        &self,
+        d: &mut D,
+        theme: &Theme,
+        window: &Bounds,
+        fps: u32,
+        graph_counts: Option<(usize, usize)>,
+        log_usage: (usize, usize),
+    ) {
+        if !self.is_shown {
+            return;
+        }
+        let mut text = format!("{fps} fps");
+        for (phase, average) in Phase::ALL.into_iter().zip(self.average) {
+            text += &format!("\n{}: {:.2} ms", phase.name(), average * 1000.0);
+        }
+        text += &match graph_counts {
+            Some((nodes, wires)) => format!("\n{nodes} nodes, {wires} wires"),
+            None => "\nno graph".to_owned(),
+        };
+        let (lines, bytes) = log_usage;
+        text += &format!("\nlog: {lines} lines, {:.1} KiB", bytes as f32 / 1024.0);
+
+        let font = &theme.general_font;
+        let padding = theme.title_padding;
+        let size = font.measure_text(&text) + padding.size();
+        let rec = Rectangle::new(
+            window.max.x - OVERLAY_MARGIN - size.x,
+            window.min.y + OVERLAY_MARGIN,
+            size.x,
+            size.y,
+        );
+        d.draw_rectangle_rec(rec, theme.background1.alpha(0.85));
+        font.draw_text(
+            d,
+            &text,
+            Vector2::new(rec.x + padding.left, rec.y + padding.top),
+            theme.foreground,
+        );
+    }
+}
@@ -1,15 +1,26 @@
 use crate::{
-    graph::node::{Gate, Node},
+    console::Console,
+    graph::{
+        Graph,
+        annotation::Annotation,
+        history::History,
+        node::{Gate, GateId, Node, NodeId, Ntd},
+        wire::Elbow,
+    },
     icon_sheets::{ButtonIconId, ButtonIconSheetId},
     input::Inputs,
-    ivec::Bounds,
+    ivec::{Bounds, IVec2},
+    locale::tr,
     theme::{Theme, ThemeFont},
     tool::Tool,
     ui::{Panel, PanelContent},
 };
 use raylib::prelude::*;
+use rustc_hash::FxHashSet;
 
-fn wrap_text(s: &str, container_width: f32, font: &ThemeFont) -> String {
+/// Replaces spaces in `s` with newlines where needed to keep each line narrower than
+/// `container_width` when drawn with `font`.
+pub fn wrap_text(s: &str, container_width: f32, font: &ThemeFont) -> String {
     // size is not changed, some spaces are just replaced with newlines
     let mut string = String::with_capacity(s.len());
     let mut it = s.split(' ');
@@ -42,7 +53,7 @@ pub trait PropertySection: std::fmt::Debug {
     fn content_height(&self, container_width: f32, theme: &Theme) -> f32;
     fn tick(
         &mut self,
-        rl: &RaylibHandle,
+        rl: &mut RaylibHandle,
         thread: &RaylibThread,
         container: Bounds,
         theme: &Theme,
@@ -50,36 +61,36 @@ pub trait PropertySection: std::fmt::Debug {
     );
 }
 
-fn tool_data(tool: &Tool) -> (ButtonIconId, &'static str, &'static str) {
+fn tool_data(tool: &Tool) -> (Option<ButtonIconId>, &'static str, &'static str) {
     match tool {
         Tool::Create { .. } => (
-            ButtonIconId::Pen,
-            "Create",
-            "Place nodes with primary input. Placing or clicking a node automatically begins \
-            creating a wire that will connect to the next placed or clicked node.",
+            Some(ButtonIconId::Pen),
+            tr("tool.create"),
+            tr("tool.create.desc"),
         ),
         Tool::Erase { .. } => (
-            ButtonIconId::Erase,
-            "Erase",
-            "Click nodes to delete them. A deleted node will delete all its wires as well.",
+            Some(ButtonIconId::Erase),
+            tr("tool.erase"),
+            tr("tool.erase.desc"),
         ),
         Tool::Edit { .. } => (
-            ButtonIconId::Edit,
-            "Edit",
-            "Drag nodes with primary input. Replace the gate of the selected node(s) with secondary input.",
+            Some(ButtonIconId::Edit),
+            tr("tool.edit"),
+            tr("tool.edit.desc"),
         ),
         Tool::Interact { .. } => (
-            ButtonIconId::Interact,
-            "Interact",
-            "Interact with input nodes using primary input to toggle them on and off",
+            Some(ButtonIconId::Interact),
+            tr("tool.interact"),
+            tr("tool.interact.desc"),
         ),
+        Tool::Annotate { .. } => (None, tr("tool.annotate"), tr("tool.annotate.desc")),
     }
 }
 
 impl PropertySection for Tool {
     #[inline]
     fn title(&self) -> &str {
-        "Tool"
+        tr("section.tool")
     }
 
     fn content_height(&self, container_width: f32, theme: &Theme) -> f32 {
@@ -97,7 +108,7 @@ impl PropertySection for Tool {
 
     fn tick(
         &mut self,
-        _rl: &RaylibHandle,
+        _rl: &mut RaylibHandle,
         _thread: &RaylibThread,
         _container: Bounds,
         _theme: &Theme,
@@ -121,19 +132,21 @@ impl<D: RaylibDraw> DrawPropertySection<D> for Tool {
             text_size.y.max(icon_width as f32),
         );
         d.draw_rectangle_rec(rec, theme.background2);
-        d.draw_texture_pro(
-            &theme.button_icons[icon_scale],
-            icon_id.icon_cell_irec(icon_width).as_rec(),
-            Rectangle::new(
-                container.min.x,
-                container.min.y + 0.5 * (rec.height - icon_width as f32),
-                icon_width as f32,
-                icon_width as f32,
-            ),
-            Vector2::zero(),
-            0.0,
-            theme.foreground,
-        );
+        if let Some(icon_id) = icon_id {
+            d.draw_texture_pro(
+                &theme.button_icons[icon_scale],
+                icon_id.icon_cell_irec(icon_width).as_rec(),
+                Rectangle::new(
+                    container.min.x,
+                    container.min.y + 0.5 * (rec.height - icon_width as f32),
+                    icon_width as f32,
+                    icon_width as f32,
+                ),
+                Vector2::zero(),
+                0.0,
+                theme.foreground,
+            );
+        }
         theme.general_font.draw_text(
             d,
             name,
@@ -157,51 +170,53 @@ impl<D: RaylibDraw> DrawPropertySection<D> for Tool {
 
 fn gate_data(gate: &Gate) -> (ButtonIconId, &'static str, &'static str) {
     match gate {
-        Gate::Or => (
-            ButtonIconId::Or,
-            "Or",
-            "True when one input or more is true.",
-        ),
-        Gate::And => (
-            ButtonIconId::And,
-            "And",
-            "True when every input is true and at least one input exists.",
-        ),
-        Gate::Nor => (ButtonIconId::Nor, "Nor", "True when every input is false."),
-        Gate::Xor => (
-            ButtonIconId::Xor,
-            "Xor",
-            "True when exactly one input is true.",
-        ),
+        Gate::Or => (ButtonIconId::Or, tr("gate.or"), tr("gate.or.desc")),
+        Gate::And => (ButtonIconId::And, tr("gate.and"), tr("gate.and.desc")),
+        Gate::Nor => (ButtonIconId::Nor, tr("gate.nor"), tr("gate.nor.desc")),
+        Gate::Xor => (ButtonIconId::Xor, tr("gate.xor"), tr("gate.xor.desc")),
         Gate::Resistor { .. } => (
             ButtonIconId::Resistor,
-            "Resistor",
-            "True when the number of true inputs exceed the NTD value.",
+            tr("gate.resistor"),
+            tr("gate.resistor.desc"),
         ),
         Gate::Capacitor { .. } => (
             ButtonIconId::Capacitor,
-            "Capacitor",
-            "Stores the quantity of true inputs up to a maximum of the NTD value, \
-            losing charge every tick that no input is true. True as long as the charge is not zero.",
+            tr("gate.capacitor"),
+            tr("gate.capacitor.desc"),
+        ),
+        Gate::Led { .. } => (ButtonIconId::Led, tr("gate.led"), tr("gate.led.desc")),
+        Gate::Delay { .. } => (ButtonIconId::Delay, tr("gate.delay"), tr("gate.delay.desc")),
+        Gate::Battery => (
+            ButtonIconId::Battery,
+            tr("gate.battery"),
+            tr("gate.battery.desc"),
+        ),
+        Gate::Ic { .. } => (ButtonIconId::Ic, tr("gate.ic"), tr("gate.ic.desc")),
+        Gate::Split { .. } => (ButtonIconId::Split, tr("gate.split"), tr("gate.split.desc")),
+        Gate::Merge { .. } => (ButtonIconId::Merge, tr("gate.merge"), tr("gate.merge.desc")),
+        Gate::Input { .. } => (ButtonIconId::Input, tr("gate.input"), tr("gate.input.desc")),
+        Gate::Output { .. } => (
+            ButtonIconId::Output,
+            tr("gate.output"),
+            tr("gate.output.desc"),
         ),
-        Gate::Led { .. } => (
-            ButtonIconId::Led,
-            "Led",
-            "Like Or, but in Inspect mode, fills its cell with the color of the NTD value when true.",
+        Gate::Switch { .. } => (
+            ButtonIconId::Switch,
+            tr("gate.switch"),
+            tr("gate.switch.desc"),
         ),
-        Gate::Delay => (
-            ButtonIconId::Delay,
-            "Delay",
-            "Like Or, but gives the previous output that would have been given the previous tick.",
+        Gate::Button => (
+            ButtonIconId::Button,
+            tr("gate.button"),
+            tr("gate.button.desc"),
         ),
-        Gate::Battery => (ButtonIconId::Battery, "Battery", "Always true."),
     }
 }
 
 impl PropertySection for Gate {
     #[inline]
     fn title(&self) -> &str {
-        "Gate"
+        tr("section.gate")
     }
 
     fn content_height(&self, container_width: f32, theme: &Theme) -> f32 {
@@ -219,7 +234,7 @@ impl PropertySection for Gate {
 
     fn tick(
         &mut self,
-        _rl: &RaylibHandle,
+        _rl: &mut RaylibHandle,
         _thread: &RaylibThread,
         _container: Bounds,
         _theme: &Theme,
@@ -277,35 +292,803 @@ impl<D: RaylibDraw> DrawPropertySection<D> for Gate {
     }
 }
 
-impl PropertySection for Node {
+/// Gates offered by the node section's gate picker. IC nodes need a graph to run, so they are
+/// only placed from the toolpane.
+const PICKER_GATES: [GateId; 15] = [
+    GateId::Or,
+    GateId::And,
+    GateId::Nor,
+    GateId::Xor,
+    GateId::Resistor,
+    GateId::Capacitor,
+    GateId::Led,
+    GateId::Delay,
+    GateId::Battery,
+    GateId::Split,
+    GateId::Merge,
+    GateId::Input,
+    GateId::Output,
+    GateId::Switch,
+    GateId::Button,
+];
+
+/// Cells in each row of the gate picker.
+const GATE_PICKER_COLUMNS: usize = 6;
+
+/// The widths offered by the bus width picker.
+const BUS_WIDTHS: [u8; 6] = [1, 2, 4, 8, 16, 32];
+
+/// How far the cursor moves sideways to drag a position field by one grid cell.
+const DRAG_CELL_PIXELS: f32 = 8.0;
+
+fn row_height(theme: &Theme) -> f32 {
+    theme.general_font.line_height() + theme.general_font.line_spacing
+}
+
+/// A row of `N` picker cells across `width`, starting at `min`.
+fn picker_row<const N: usize>(min: Vector2, width: f32, theme: &Theme) -> [Rectangle; N] {
+    let cell_width = width / N as f32;
+    let height = theme.general_font.line_height();
+    std::array::from_fn(|n| {
+        Rectangle::new(min.x + n as f32 * cell_width, min.y, cell_width, height)
+    })
+}
+
+fn gate_picker_cell_height(theme: &Theme) -> f32 {
+    theme
+        .general_font
+        .line_height()
+        .max(ButtonIconSheetId::X16.icon_width() as f32)
+}
+
+fn gate_picker_height(theme: &Theme) -> f32 {
+    PICKER_GATES.len().div_ceil(GATE_PICKER_COLUMNS) as f32
+        * (gate_picker_cell_height(theme) + theme.general_font.line_spacing)
+}
+
+/// The cells of a gate picker, one per [`PICKER_GATES`], in rows across `width` starting at `min`.
+fn gate_picker(min: Vector2, width: f32, theme: &Theme) -> [Rectangle; PICKER_GATES.len()] {
+    let cell_width = width / GATE_PICKER_COLUMNS as f32;
+    let cell_height = gate_picker_cell_height(theme);
+    std::array::from_fn(|n| {
+        Rectangle::new(
+            min.x + (n % GATE_PICKER_COLUMNS) as f32 * cell_width,
+            min.y
+                + (n / GATE_PICKER_COLUMNS) as f32
+                    * (cell_height + theme.general_font.line_spacing),
+            cell_width,
+            cell_height,
+        )
+    })
+}
+
+fn draw_gate_picker<D: RaylibDraw>(
+    d: &mut D,
+    cells: [Rectangle; PICKER_GATES.len()],
+    selected: Option<GateId>,
+    theme: &Theme,
+) {
+    let icon_scale = ButtonIconSheetId::X16;
+    let icon_width = icon_scale.icon_width() as f32;
+    for (rec, gate_id) in cells.into_iter().zip(PICKER_GATES) {
+        let is_selected = selected == Some(gate_id);
+        draw_picker_cell(d, rec, "", is_selected, theme);
+        let (icon_id, _, _) = gate_data(&gate_id.to_gate(Ntd::default()));
+        d.draw_texture_pro(
+            &theme.button_icons[icon_scale],
+            icon_id.icon_cell_irec(icon_scale.icon_width()).as_rec(),
+            Rectangle::new(
+                rec.x + 0.5 * (rec.width - icon_width),
+                rec.y + 0.5 * (rec.height - icon_width),
+                icon_width,
+                icon_width,
+            ),
+            Vector2::zero(),
+            0.0,
+            if is_selected {
+                theme.foreground
+            } else {
+                theme.foreground2
+            },
+        );
+    }
+}
+
+/// Where each part of the node section goes.
+struct NodeLayout {
+    gates: [Rectangle; PICKER_GATES.len()],
+    /// From 0 to 9, if the gate has an NTD value
+    ntd: Option<[Rectangle; 10]>,
+    widths: [Rectangle; BUS_WIDTHS.len()],
+    /// Where the bits of a bus are written
+    bits: Option<Vector2>,
+    /// The x field, then the y field
+    position: [Rectangle; 2],
+    /// Adds the node to the waveform panel, or takes it off
+    watch: Rectangle,
+    note: Vector2,
+}
+
+impl NodeLayout {
+    fn new(node: &Node, container: Bounds, theme: &Theme) -> Self {
+        let width = container.width();
+        let mut pos = container.min;
+
+        let gates = gate_picker(pos, width, theme);
+        pos.y += gate_picker_height(theme);
+        let ntd = node.gate().as_gate().ntd().map(|_| {
+            let cells = picker_row(pos, width, theme);
+            pos.y += row_height(theme);
+            cells
+        });
+        let widths = picker_row(pos, width, theme);
+        pos.y += row_height(theme);
+        let bits = (node.width() > 1).then(|| {
+            let bits = pos;
+            pos.y += row_height(theme);
+            bits
+        });
+        let position = picker_row(pos, width, theme);
+        pos.y += row_height(theme);
+        let [watch] = picker_row(pos, width, theme);
+        pos.y += row_height(theme);
+
+        Self {
+            gates,
+            ntd,
+            widths,
+            bits,
+            position,
+            watch,
+            note: pos,
+        }
+    }
+}
+
+fn draw_picker_cell<D: RaylibDraw>(
+    d: &mut D,
+    rec: Rectangle,
+    text: &str,
+    is_selected: bool,
+    theme: &Theme,
+) {
+    d.draw_rectangle_rec(
+        rec,
+        if is_selected {
+            theme.background3
+        } else {
+            theme.background2
+        },
+    );
+    let text_width = theme.general_font.measure_text(text).x;
+    theme.general_font.draw_text(
+        d,
+        text,
+        Vector2::new(rec.x + 0.5 * (rec.width - text_width), rec.y),
+        if is_selected {
+            theme.foreground
+        } else {
+            theme.foreground2
+        },
+    );
+}
+
+/// A change to a node requested from the properties panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeEdit {
+    Gate(Gate),
+    Ntd(Ntd),
+    Width(u8),
+    Position(IVec2),
+}
+
+impl NodeEdit {
+    /// Makes the change through the graph, so that it is logged like any other edit.
+    ///
+    /// Returns [`None`] if `id` is not a node in `graph`.
+    pub fn apply(self, graph: &mut Graph, id: &NodeId, console: &mut Console) -> Option<()> {
+        match self {
+            Self::Gate(gate) => graph.set_node_gate(id, gate, console),
+            Self::Ntd(value) => graph.set_node_ntd(id, value, console),
+            Self::Width(width) => graph.set_node_width(id, width, console),
+            Self::Position(position) => graph.translate_node(id, position, console),
+        }
+    }
+}
+
+/// A position field being dragged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionDrag {
+    node: NodeId,
+    /// 0 for x, 1 for y
+    axis: usize,
+    start_cursor_x: f32,
+    start: IVec2,
+    /// The last position requested, so that a move is only requested once
+    target: IVec2,
+}
+
+/// The node being inspected and the edits made to it this frame. The node itself is left alone;
+/// the edits are applied to its graph afterward with [`NodeEdit::apply`].
+#[derive(Debug)]
+pub struct NodeSection<'a> {
+    node: &'a Node,
+    grid_size: u8,
+    drag: Option<PositionDrag>,
+    edits: Vec<NodeEdit>,
+    /// Whether the editor tab watches the node
+    is_watched: bool,
+    /// Whether the watch button was clicked
+    toggle_watch: bool,
+}
+
+impl PropertySection for NodeSection<'_> {
+    #[inline]
+    fn title(&self) -> &str {
+        tr("section.node")
+    }
+
+    fn content_height(&self, container_width: f32, theme: &Theme) -> f32 {
+        let container = Bounds::new(Vector2::zero(), Vector2::new(container_width, 0.0));
+        NodeLayout::new(self.node, container, theme).note.y
+            + self.node.note().map_or(0.0, |note| {
+                theme
+                    .general_font
+                    .measure_text(&wrap_text(note, container_width, &theme.general_font))
+                    .y
+            })
+    }
+
+    fn tick(
+        &mut self,
+        _rl: &mut RaylibHandle,
+        _thread: &RaylibThread,
+        container: Bounds,
+        theme: &Theme,
+        input: &Inputs,
+    ) {
+        let layout = NodeLayout::new(self.node, container, theme);
+        let is_hovered = |rec: &Rectangle| Bounds::from(*rec).contains(input.cursor);
+        let gate = self.node.gate().as_gate();
+        if let Some(drag) = &mut self.drag {
+            if input.primary.is_active() {
+                let cells = ((input.cursor.x - drag.start_cursor_x) / DRAG_CELL_PIXELS).round();
+                let offset = cells as i32 * i32::from(self.grid_size);
+                let mut target = drag.start;
+                if drag.axis == 0 {
+                    target.x += offset;
+                } else {
+                    target.y += offset;
+                }
+                if target != drag.target {
+                    drag.target = target;
+                    self.edits.push(NodeEdit::Position(target));
+                }
+            } else {
+                self.drag = None;
+            }
+        } else if input.primary.is_starting() {
+            if let Some(n) = layout.gates.iter().position(is_hovered) {
+                let ntd = gate.ntd().unwrap_or_default();
+                self.edits
+                    .push(NodeEdit::Gate(PICKER_GATES[n].to_gate(ntd)));
+            } else if let Some(n) = layout.widths.iter().position(is_hovered) {
+                self.edits.push(NodeEdit::Width(BUS_WIDTHS[n]));
+            } else if let Some(axis) = layout.position.iter().position(is_hovered) {
+                let position = self.node.position();
+                self.drag = Some(PositionDrag {
+                    node: *self.node.id(),
+                    axis,
+                    start_cursor_x: input.cursor.x,
+                    start: position,
+                    target: position,
+                });
+            } else if is_hovered(&layout.watch) {
+                self.toggle_watch = true;
+            }
+        }
+        // the NTD picker is a slider, following the cursor for as long as it is held
+        if self.drag.is_none()
+            && input.primary.is_active()
+            && let Some(cells) = layout.ntd
+            && let Some(n) = cells.iter().position(is_hovered)
+        {
+            let value = Ntd::try_from(n as u8).expect("there should be one cell per NTD value");
+            if gate.ntd() != Some(value) {
+                self.edits.push(NodeEdit::Ntd(value));
+            }
+        }
+    }
+}
+
+impl<D: RaylibDraw> DrawPropertySection<D> for NodeSection<'_> {
+    fn draw(&self, d: &mut D, container: Bounds, theme: &Theme) {
+        let layout = NodeLayout::new(self.node, container, theme);
+        let gate = self.node.gate().as_gate();
+        draw_gate_picker(d, layout.gates, Some(gate.id()), theme);
+        if let Some(cells) = layout.ntd {
+            let ntd = gate.ntd();
+            for (n, rec) in cells.into_iter().enumerate() {
+                let is_selected = ntd.is_some_and(|ntd| usize::from(ntd) == n);
+                draw_picker_cell(d, rec, &n.to_string(), is_selected, theme);
+            }
+        }
+        for (rec, width) in layout.widths.into_iter().zip(BUS_WIDTHS) {
+            draw_picker_cell(
+                d,
+                rec,
+                &width.to_string(),
+                self.node.width() == width,
+                theme,
+            );
+        }
+        if let Some(pos) = layout.bits {
+            theme.general_font.draw_text(
+                d,
+                &format!(
+                    "{:0width$b}",
+                    self.node.bits(),
+                    width = usize::from(self.node.width())
+                ),
+                pos,
+                theme.foreground2,
+            );
+        }
+        let IVec2 { x, y } = self.node.position();
+        for (axis, (rec, text)) in layout
+            .position
+            .into_iter()
+            .zip([format!("x {x}"), format!("y {y}")])
+            .enumerate()
+        {
+            let is_dragged = self.drag.is_some_and(|drag| drag.axis == axis);
+            draw_picker_cell(d, rec, &text, is_dragged, theme);
+        }
+        draw_picker_cell(
+            d,
+            layout.watch,
+            if self.is_watched {
+                "watching"
+            } else {
+                "add to watch"
+            },
+            self.is_watched,
+            theme,
+        );
+        if let Some(note) = self.node.note() {
+            theme.general_font.draw_text(
+                d,
+                &wrap_text(note, container.width(), &theme.general_font),
+                layout.note,
+                theme.foreground,
+            );
+        }
+    }
+}
+
+/// How the elbow is written in the elbow picker, the same as in saved graphs.
+const fn elbow_symbol(elbow: Elbow) -> &'static str {
+    match elbow {
+        Elbow::Horizontal => "-",
+        Elbow::DiagonalStart => "\\",
+        Elbow::Vertical => "|",
+        Elbow::DiagonalEnd => "/",
+        Elbow::Straight => ".",
+        Elbow::Curve => "~",
+    }
+}
+
+fn elbow_picker(container: Bounds, theme: &Theme) -> [Rectangle; Elbow::ALL.len()] {
+    picker_row(container.min, container.width(), theme)
+}
+
+impl PropertySection for Elbow {
     #[inline]
     fn title(&self) -> &str {
-        "Node"
+        tr("section.elbow")
     }
 
-    fn content_height(&self, _container_width: f32, _theme: &Theme) -> f32 {
-        0.0
+    fn content_height(&self, _container_width: f32, theme: &Theme) -> f32 {
+        row_height(theme) + theme.general_font.line_height()
     }
 
     fn tick(
         &mut self,
-        _rl: &RaylibHandle,
+        _rl: &mut RaylibHandle,
+        _thread: &RaylibThread,
+        container: Bounds,
+        theme: &Theme,
+        input: &Inputs,
+    ) {
+        if input.primary.is_starting()
+            && let Some(n) = elbow_picker(container, theme)
+                .iter()
+                .position(|rec| Bounds::from(*rec).contains(input.cursor))
+        {
+            *self = Elbow::ALL[n];
+        }
+    }
+}
+
+impl<D: RaylibDraw> DrawPropertySection<D> for Elbow {
+    fn draw(&self, d: &mut D, container: Bounds, theme: &Theme) {
+        for (rec, elbow) in elbow_picker(container, theme).into_iter().zip(Elbow::ALL) {
+            draw_picker_cell(d, rec, elbow_symbol(elbow), *self == elbow, theme);
+        }
+        theme.general_font.draw_text(
+            d,
+            &self.to_string(),
+            Vector2::new(container.min.x, container.min.y + row_height(theme)),
+            theme.foreground,
+        );
+    }
+}
+
+/// The text being edited, with a caret after it.
+fn annotation_text(annotation: &Annotation, container_width: f32, theme: &Theme) -> String {
+    let mut text = wrap_text(&annotation.text, container_width, &theme.general_font);
+    text.push('_');
+    text
+}
+
+impl PropertySection for Annotation {
+    #[inline]
+    fn title(&self) -> &str {
+        tr("section.annotation")
+    }
+
+    fn content_height(&self, container_width: f32, theme: &Theme) -> f32 {
+        theme
+            .general_font
+            .measure_text(&annotation_text(self, container_width, theme))
+            .y
+    }
+
+    fn tick(
+        &mut self,
+        rl: &mut RaylibHandle,
         _thread: &RaylibThread,
         _container: Bounds,
         _theme: &Theme,
         _input: &Inputs,
     ) {
-        // TODO
+        use KeyboardKey::*;
+
+        while let Some(c) = rl.get_char_pressed() {
+            if !c.is_control() {
+                self.text.push(c);
+            }
+        }
+        if rl.is_key_pressed(KEY_BACKSPACE) || rl.is_key_pressed_repeat(KEY_BACKSPACE) {
+            self.text.pop();
+        }
+        if rl.is_key_pressed(KEY_ENTER) || rl.is_key_pressed(KEY_KP_ENTER) {
+            self.text.push('\n');
+        }
+    }
+}
+
+impl<D: RaylibDraw> DrawPropertySection<D> for Annotation {
+    fn draw(&self, d: &mut D, container: Bounds, theme: &Theme) {
+        d.draw_rectangle_rec(Rectangle::from(container), theme.background2);
+        theme.general_font.draw_text(
+            d,
+            &annotation_text(self, container.width(), theme),
+            container.min,
+            theme.foreground,
+        );
+    }
+}
+
+/// The part of `container` taken up by the timeline slider.
+fn timeline_slider(container: Bounds, theme: &Theme) -> Bounds {
+    Bounds::new(
+        container.min,
+        Vector2::new(
+            container.max.x,
+            container.min.y + theme.general_font.line_height(),
+        ),
+    )
+}
+
+impl PropertySection for History {
+    #[inline]
+    fn title(&self) -> &str {
+        tr("section.timeline")
+    }
+
+    fn content_height(&self, _container_width: f32, theme: &Theme) -> f32 {
+        2.0 * theme.general_font.line_height() + theme.general_font.line_spacing
+    }
+
+    fn tick(
+        &mut self,
+        _rl: &mut RaylibHandle,
+        _thread: &RaylibThread,
+        container: Bounds,
+        theme: &Theme,
+        input: &Inputs,
+    ) {
+        let slider = timeline_slider(container, theme);
+        if input.primary.is_active() && slider.contains(input.cursor) && !self.is_empty() {
+            let t = (input.cursor.x - slider.min.x) / slider.width();
+            self.seek((t * self.len() as f32) as usize);
+        }
+    }
+}
+
+impl<D: RaylibDraw> DrawPropertySection<D> for History {
+    fn draw(&self, d: &mut D, container: Bounds, theme: &Theme) {
+        let slider = timeline_slider(container, theme);
+        d.draw_rectangle_rec(Rectangle::from(slider), theme.background2);
+        if let Some(latest) = self.len().checked_sub(1) {
+            let frame = self.cursor().unwrap_or(latest);
+            let t = if latest == 0 {
+                1.0
+            } else {
+                frame as f32 / latest as f32
+            };
+            let handle_x = slider.min.x + (slider.width() - 2.0) * t;
+            d.draw_rectangle_rec(
+                Rectangle::new(handle_x, slider.min.y, 2.0, slider.height()),
+                theme.interact,
+            );
+        }
+        let text = match self.cursor() {
+            Some(frame) => format!("tick -{} of {}", self.len() - 1 - frame, self.len()),
+            None => format!("live, {} ticks recorded", self.len()),
+        };
+        theme.general_font.draw_text(
+            d,
+            &text,
+            Vector2::new(
+                container.min.x,
+                slider.max.y + theme.general_font.line_spacing,
+            ),
+            theme.foreground,
+        );
+    }
+}
+
+/// Selected nodes with the same gate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GateCount {
+    gate: GateId,
+    nodes: usize,
+    /// The NTD value they all have, if they have the same one
+    ntd: Option<Ntd>,
+}
+
+impl GateCount {
+    const fn has_ntd(&self) -> bool {
+        self.gate.to_gate(Ntd::Zero).ntd().is_some()
+    }
+}
+
+/// A change to the selected nodes requested from the properties panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionEdit {
+    /// Replace the gate of every selected node
+    Gate(GateId),
+    /// Set the NTD value of every selected node with the gate
+    Ntd(GateId, Ntd),
+}
+
+impl SelectionEdit {
+    /// Makes the change through the graph as a single change, so that it is logged once.
+    /// Returns how many nodes were changed.
+    pub fn apply(
+        self,
+        graph: &mut Graph,
+        selection: &FxHashSet<NodeId>,
+        console: &mut Console,
+    ) -> usize {
+        match self {
+            Self::Gate(gate_id) => graph.set_nodes_gate(selection, gate_id, console),
+            Self::Ntd(gate_id, value) => {
+                let ids = selection
+                    .iter()
+                    .filter(|id| {
+                        graph
+                            .node(id)
+                            .is_some_and(|node| node.gate().as_gate().id() == gate_id)
+                    })
+                    .copied()
+                    .collect::<Vec<_>>();
+                graph.set_nodes_ntd(&ids, value, console)
+            }
+        }
+    }
+}
+
+/// Where each part of the selection section goes.
+struct SelectionLayout {
+    gates: [Rectangle; PICKER_GATES.len()],
+    /// Where each gate's count is written, and its NTD picker if it has an NTD value
+    counts: Vec<(Vector2, Option<[Rectangle; 10]>)>,
+    height: f32,
+}
+
+/// Counts describing the selected part of a graph, and the edits made to the selected nodes this
+/// frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectionStats {
+    nodes: usize,
+    /// Selected nodes of each gate with any, in [`GateId::ALL`] order
+    gates: Vec<GateCount>,
+    /// Wires between two selected nodes
+    internal_wires: usize,
+    /// Wires between a selected node and one that isn't
+    boundary_wires: usize,
+    /// Width and height of the area the selection covers, in grid cells
+    size: (i32, i32),
+    edits: Vec<SelectionEdit>,
+}
+
+impl SelectionStats {
+    /// Returns [`None`] if none of the selected nodes are in the graph.
+    pub fn new(graph: &Graph, selection: &FxHashSet<NodeId>) -> Option<Self> {
+        let first = selection.iter().find_map(|id| graph.node(id))?.position();
+        let mut gates = GateId::ALL.map(|gate| GateCount {
+            gate,
+            nodes: 0,
+            ntd: None,
+        });
+        let (mut min, mut max) = (first, first);
+        let mut count = 0;
+        for node in selection.iter().filter_map(|id| graph.node(id)) {
+            let p = node.position();
+            (min.x, min.y) = (min.x.min(p.x), min.y.min(p.y));
+            (max.x, max.y) = (max.x.max(p.x), max.y.max(p.y));
+            let gate = node.gate().as_gate();
+            let entry = &mut gates[gate.id() as usize];
+            entry.nodes += 1;
+            entry.ntd = if entry.nodes == 1 {
+                gate.ntd()
+            } else {
+                entry.ntd.filter(|&ntd| gate.ntd() == Some(ntd))
+            };
+            count += 1;
+        }
+        let (mut internal_wires, mut boundary_wires) = (0, 0);
+        for wire in graph.wires_iter() {
+            match (
+                selection.contains(wire.src()),
+                selection.contains(wire.dst()),
+            ) {
+                (true, true) => internal_wires += 1,
+                (true, false) | (false, true) => boundary_wires += 1,
+                (false, false) => {}
+            }
+        }
+        let grid_size = i32::from(graph.grid_size());
+        Some(Self {
+            nodes: count,
+            gates: gates.into_iter().filter(|count| count.nodes > 0).collect(),
+            internal_wires,
+            boundary_wires,
+            size: (
+                (max.x - min.x) / grid_size + 1,
+                (max.y - min.y) / grid_size + 1,
+            ),
+            edits: Vec::new(),
+        })
+    }
+
+    /// Returns the edits made from the section, to be applied with [`SelectionEdit::apply`].
+    pub fn into_edits(self) -> Vec<SelectionEdit> {
+        self.edits
+    }
+
+    fn text(&self) -> String {
+        format!(
+            "{} nodes in {}x{} cells\n{} internal wires, {} boundary wires",
+            self.nodes, self.size.0, self.size.1, self.internal_wires, self.boundary_wires,
+        )
+    }
+
+    fn layout(&self, container: Bounds, theme: &Theme) -> SelectionLayout {
+        let width = container.width();
+        let mut pos = container.min;
+        pos.y += theme.general_font.measure_text(&self.text()).y + theme.general_font.line_spacing;
+        let gates = gate_picker(pos, width, theme);
+        pos.y += gate_picker_height(theme);
+        let counts = self
+            .gates
+            .iter()
+            .map(|count| {
+                let text = pos;
+                pos.y += row_height(theme);
+                let ntd = count.has_ntd().then(|| {
+                    let cells = picker_row(pos, width, theme);
+                    pos.y += row_height(theme);
+                    cells
+                });
+                (text, ntd)
+            })
+            .collect();
+        SelectionLayout {
+            gates,
+            counts,
+            height: pos.y - container.min.y,
+        }
+    }
+}
+
+impl PropertySection for SelectionStats {
+    #[inline]
+    fn title(&self) -> &str {
+        tr("section.selection")
+    }
+
+    fn content_height(&self, container_width: f32, theme: &Theme) -> f32 {
+        let container = Bounds::new(Vector2::zero(), Vector2::new(container_width, 0.0));
+        self.layout(container, theme).height
+    }
+
+    fn tick(
+        &mut self,
+        _rl: &mut RaylibHandle,
+        _thread: &RaylibThread,
+        container: Bounds,
+        theme: &Theme,
+        input: &Inputs,
+    ) {
+        if !input.primary.is_starting() {
+            return;
+        }
+        let layout = self.layout(container, theme);
+        let is_hovered = |rec: &Rectangle| Bounds::from(*rec).contains(input.cursor);
+        if let Some(n) = layout.gates.iter().position(is_hovered) {
+            self.edits.push(SelectionEdit::Gate(PICKER_GATES[n]));
+        } else if let Some(edit) =
+            self.gates
+                .iter()
+                .zip(&layout.counts)
+                .find_map(|(count, (_, cells))| {
+                    let n = cells.as_ref()?.iter().position(is_hovered)?;
+                    let value =
+                        Ntd::try_from(n as u8).expect("there should be one cell per NTD value");
+                    Some(SelectionEdit::Ntd(count.gate, value))
+                })
+        {
+            self.edits.push(edit);
+        }
     }
 }
 
-impl<D: RaylibDraw> DrawPropertySection<D> for Node {
-    fn draw(&self, _d: &mut D, _container: Bounds, _theme: &Theme) {}
+impl<D: RaylibDraw> DrawPropertySection<D> for SelectionStats {
+    fn draw(&self, d: &mut D, container: Bounds, theme: &Theme) {
+        let layout = self.layout(container, theme);
+        theme
+            .general_font
+            .draw_text(d, &self.text(), container.min, theme.foreground);
+        // only one gate is picked when every selected node has it
+        let selected = match self.gates.as_slice() {
+            [count] => Some(count.gate),
+            _ => None,
+        };
+        draw_gate_picker(d, layout.gates, selected, theme);
+        for (count, (text_pos, cells)) in self.gates.iter().zip(layout.counts) {
+            theme.general_font.draw_text(
+                d,
+                &format!("{}: {}", count.gate, count.nodes),
+                text_pos,
+                theme.foreground,
+            );
+            for (n, rec) in cells.into_iter().flatten().enumerate() {
+                let is_selected = count.ntd.is_some_and(|ntd| usize::from(ntd) == n);
+                draw_picker_cell(d, rec, &n.to_string(), is_selected, theme);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct PropertiesPanel {
     pub panel: Panel,
+    /// Kept between frames, since a drag lasts longer than one
+    position_drag: Option<PositionDrag>,
 }
 
 impl PanelContent for PropertiesPanel {
@@ -327,7 +1110,34 @@ impl PanelContent for PropertiesPanel {
 
 impl PropertiesPanel {
     pub const fn new(panel: Panel) -> Self {
-        Self { panel }
+        Self {
+            panel,
+            position_drag: None,
+        }
+    }
+
+    /// The section for inspecting `node`, continuing any drag started on it.
+    pub fn node_section<'a>(
+        &self,
+        node: &'a Node,
+        grid_size: u8,
+        is_watched: bool,
+    ) -> NodeSection<'a> {
+        NodeSection {
+            node,
+            grid_size,
+            drag: self.position_drag.filter(|drag| &drag.node == node.id()),
+            edits: Vec::new(),
+            is_watched,
+            toggle_watch: false,
+        }
+    }
+
+    /// Returns the edits made from `section`, to be applied with [`NodeEdit::apply`], and
+    /// whether the node should start or stop being watched.
+    pub fn finish_node_section(&mut self, section: NodeSection<'_>) -> (Vec<NodeEdit>, bool) {
+        self.position_drag = section.drag;
+        (section.edits, section.toggle_watch)
     }
 
     pub fn tick_section<T>(
@@ -344,18 +1154,20 @@ impl PropertiesPanel {
     {
         // self.panel.tick_resize(rl, theme, input);
         let bounds = self.panel.content_bounds(theme);
-        y += theme.console_font.line_height() * section.title().lines().count() as f32;
+        y += theme.properties_header_font.measure_text(section.title()).y;
+        y += 2.0 * theme.properties_header_font.line_spacing + theme.general_font.line_spacing;
         let height = section.content_height(bounds.width(), theme);
         section.tick(
             rl,
             thread,
             Bounds::new(
-                Vector2::new(bounds.max.x, y),
-                Vector2::new(bounds.min.x, y + height),
+                Vector2::new(bounds.min.x, y.clamp(bounds.min.y, bounds.max.y)),
+                Vector2::new(bounds.max.x, (y + height).clamp(bounds.min.y, bounds.max.y)),
             ),
             theme,
             input,
         );
+        y += height + theme.properties_section_gap;
         y
     }
 
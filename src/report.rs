@@ -0,0 +1,173 @@
+//! Issue reports: a single zip holding everything useful for reproducing a bug, so that it can be
+//! attached to an issue as is.
+
+use crate::{
+    backup::{ZipWriter, unix_time, unused_archive_path},
+    config::CONFIG_PATH,
+    console::{Console, GraphRef, LogType},
+    file::SAVE_EXTENSION,
+    graph::{Graph, GraphList},
+    logln,
+    theme::{OptionalFont, Theme, ThemeFont},
+};
+use serde_derive::Serialize;
+use std::{fmt::Write as _, io::Write as _, path::PathBuf};
+
+pub const REPORT_DIRECTORY: &str = "reports";
+
+/// What the program was running on.
+#[derive(Debug, Serialize)]
+struct SystemInfo {
+    version: &'static str,
+    os: &'static str,
+    family: &'static str,
+    arch: &'static str,
+    /// Threads available to the program, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpus: Option<usize>,
+    debug_build: bool,
+    /// Seconds since the Unix epoch
+    created: u64,
+}
+
+impl SystemInfo {
+    fn current() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            family: std::env::consts::FAMILY,
+            arch: std::env::consts::ARCH,
+            cpus: std::thread::available_parallelism().ok().map(Into::into),
+            debug_build: cfg!(debug_assertions),
+            created: unix_time(),
+        }
+    }
+}
+
+/// Which fonts actually loaded, followed by the theme as it is in effect.
+fn theme_diagnostics(theme: &Theme) -> std::io::Result<String> {
+    let mut text = String::new();
+    for (name, font) in [
+        ("general_font", &theme.general_font),
+        ("title_font", &theme.title_font),
+        ("properties_header_font", &theme.properties_header_font),
+        ("console_font", &theme.console_font),
+    ] {
+        let ThemeFont { path, font, .. } = font;
+        let status = match font {
+            OptionalFont::Unloaded => "not loaded",
+            OptionalFont::Strong(_) => "loaded from file",
+            OptionalFont::Weak(_) => "using default font",
+        };
+        let path = path
+            .as_ref()
+            .map_or_else(|| "(none)".into(), |path| path.display().to_string());
+        _ = writeln!(text, "{name}: {path}, {status}");
+    }
+    _ = writeln!(text, "low_vision: {}", theme.low_vision);
+    text.push('\n');
+    text.push_str(&toml::to_string_pretty(theme).map_err(std::io::Error::other)?);
+    Ok(text)
+}
+
+/// A copy of `graph` with its notes, node names, labels, annotation text and IC paths removed.
+fn anonymized(graph: &Graph) -> Result<String, String> {
+    let mut copy = toml::to_string(graph)
+        .map_err(|e| e.to_string())
+        .and_then(|s| toml::from_str::<Graph>(&s).map_err(|e| e.to_string()))?;
+    copy.strip_text();
+    toml::to_string(&copy).map_err(|e| e.to_string())
+}
+
+/// Archives the config, theme diagnostics, log and system info in [`REPORT_DIRECTORY`], and an
+/// anonymized copy of every graph in the workspace if `include_workspace`. Returns where the
+/// archive was written.
+pub fn write_report(
+    console: &mut Console,
+    graphs: &GraphList,
+    theme: &Theme,
+    include_workspace: bool,
+) -> std::io::Result<PathBuf> {
+    let mut zip = ZipWriter::new(unix_time());
+    match std::fs::read(CONFIG_PATH) {
+        Ok(config) => zip.add(CONFIG_PATH, &config),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    zip.add("theme.txt", theme_diagnostics(theme)?.as_bytes());
+    zip.add(
+        "system.toml",
+        toml::to_string_pretty(&SystemInfo::current())
+            .map_err(std::io::Error::other)?
+            .as_bytes(),
+    );
+    if include_workspace {
+        for graph in graphs.workspace() {
+            let Ok(graph) = graph.try_read() else {
+                logln!(console, LogType::Warning, "graph is busy, skipping it");
+                continue;
+            };
+            match anonymized(&graph) {
+                Ok(s) => zip.add(
+                    &format!("workspace/{}.{SAVE_EXTENSION}", graph.id()),
+                    s.as_bytes(),
+                ),
+                Err(e) => logln!(
+                    console,
+                    LogType::Warning,
+                    "failed to copy graph {}, skipping it: {e}",
+                    GraphRef(*graph.id())
+                ),
+            }
+        }
+    }
+    // last, so that it includes any warnings from the steps above
+    zip.add("log.txt", console.export_text().as_bytes());
+
+    let path = unused_archive_path(REPORT_DIRECTORY, "report");
+    std::fs::create_dir_all(REPORT_DIRECTORY)?;
+    std::fs::File::create_new(&path)?.write_all(&zip.finish())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anonymized() {
+        let graph = toml::from_str::<Graph>(
+            r#"
+            nodes = [["|", [0, 0], false], ["|", [8, 0], false]]
+            wires = [["-", 0, 1]]
+            node_notes = [[0, "from the keyboard"]]
+            node_names = [[1, "carry"]]
+            "#,
+        )
+        .unwrap();
+        let anonymized = anonymized(&graph).unwrap().parse::<toml::Table>().unwrap();
+        for key in ["node_notes", "node_names"] {
+            assert!(!anonymized.contains_key(key), "{key} should be left out");
+        }
+        assert_eq!(anonymized["wires"].as_array().map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn test_anonymized_ic_paths() {
+        let graph = toml::from_str::<Graph>(
+            r##"
+            nodes = [[{ "#" = { graph = 0 } }, [0, 0], false]]
+            wires = []
+            ic_paths = [[0, "/home/someone/circuits/adder.ea"]]
+            "##,
+        )
+        .unwrap();
+        assert!(
+            toml::to_string(&graph).unwrap().contains("ic_paths"),
+            "saves should keep IC paths"
+        );
+        let anonymized = anonymized(&graph).unwrap();
+        assert!(!anonymized.contains("ic_paths"), "{anonymized}");
+        assert!(!anonymized.contains("someone"), "{anonymized}");
+    }
+}
@@ -1,4 +1,10 @@
-use crate::theme::{ColorId, Theme};
+//! Text with escape codes, each starting with `\x1B{` and ending with `}`, that change how the
+//! text after them is drawn: its color, whether it is bold or italic, and icons drawn inline.
+
+use crate::{
+    icon_sheets::{ButtonIconId, ButtonIconSheetId},
+    theme::{ColorId, Theme, ThemeFont},
+};
 use raylib::prelude::*;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -10,7 +16,9 @@ impl std::fmt::Display for RichStrError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RichStrError::InvalidEscapeCode => "escape code should match the pattern of `\\x1B{rgba(r,g,b,a)}` or `\\x1B{name}` \
-                where `r`, `g`, `b`, and `a` are integers between 0 and 255 inclusively, and `name` is the name of a theme color".fmt(f),
+                where `r`, `g`, `b`, and `a` are integers between 0 and 255 inclusively, and `name` is the name of a theme color, \
+                optionally prefixed with `push:`; or be `\\x1B{pop}`, `\\x1B{bold}`, `\\x1B{/bold}`, `\\x1B{italic}`, `\\x1B{/italic}` \
+                or `\\x1B{icon:name}` where `name` is the name of a button icon".fmt(f),
         }
     }
 }
@@ -113,16 +121,182 @@ impl std::str::FromStr for ColorAct {
     }
 }
 
+/// Emphasis of rich text, drawn with the variants of a [`ThemeFont`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TextStyle {
+    pub is_bold: bool,
+    pub is_italic: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleAct {
+    /// Starts (`true`) or ends (`false`) bold text
+    Bold(bool),
+    /// Starts (`true`) or ends (`false`) italic text
+    Italic(bool),
+}
+
+impl std::fmt::Display for StyleAct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StyleAct::Bold(true) => write!(f, "\x1B{{bold}}"),
+            StyleAct::Bold(false) => write!(f, "\x1B{{/bold}}"),
+            StyleAct::Italic(true) => write!(f, "\x1B{{italic}}"),
+            StyleAct::Italic(false) => write!(f, "\x1B{{/italic}}"),
+        }
+    }
+}
+
+impl std::str::FromStr for StyleAct {
+    type Err = RichStrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bold" => Ok(Self::Bold(true)),
+            "/bold" => Ok(Self::Bold(false)),
+            "italic" => Ok(Self::Italic(true)),
+            "/italic" => Ok(Self::Italic(false)),
+            _ => Err(RichStrError::InvalidEscapeCode),
+        }
+    }
+}
+
+impl StyleAct {
+    #[inline]
+    pub const fn apply(self, style: &mut TextStyle) {
+        match self {
+            Self::Bold(is_bold) => style.is_bold = is_bold,
+            Self::Italic(is_italic) => style.is_italic = is_italic,
+        }
+    }
+}
+
+/// An icon drawn inline, before the text following it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IconAct(pub ButtonIconId);
+
+impl std::fmt::Display for IconAct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\x1B{{icon:{}}}", self.0)
+    }
+}
+
+impl std::str::FromStr for IconAct {
+    type Err = RichStrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_prefix("icon:")
+            .and_then(|name| name.parse().ok())
+            .map(Self)
+            .ok_or(RichStrError::InvalidEscapeCode)
+    }
+}
+
+/// Any escape code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RichAct {
+    Color(ColorAct),
+    Style(StyleAct),
+    Icon(IconAct),
+}
+
+impl std::fmt::Display for RichAct {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RichAct::Color(act) => act.fmt(f),
+            RichAct::Style(act) => act.fmt(f),
+            RichAct::Icon(act) => act.fmt(f),
+        }
+    }
+}
+
+impl std::str::FromStr for RichAct {
+    type Err = RichStrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(act) = s.parse() {
+            Ok(Self::Style(act))
+        } else if s.starts_with("icon:") {
+            s.parse().map(Self::Icon)
+        } else {
+            s.parse().map(Self::Color)
+        }
+    }
+}
+
+/// A run of rich text drawn the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RichSpan<'a> {
+    /// [`None`] if the text has no color of its own
+    pub color: Option<ColorRef>,
+    pub style: TextStyle,
+    /// Drawn before `text`
+    pub icon: Option<ButtonIconId>,
+    pub text: &'a str,
+}
+
+impl RichSpan<'_> {
+    /// Space taken by `icon` when drawn with `font`, which is as tall as the font.
+    #[inline]
+    pub fn icon_width(&self, font: &ThemeFont) -> f32 {
+        if self.icon.is_some() {
+            font.font_size + font.char_spacing
+        } else {
+            0.0
+        }
+    }
+
+    pub fn measure(&self, font: &ThemeFont) -> Vector2 {
+        let size = font.measure_styled_text(self.text, self.style);
+        Vector2::new(self.icon_width(font) + size.x, size.y)
+    }
+
+    /// Draws the icon, if there is one, then the text, both in `tint`.
+    pub fn draw<D: RaylibDraw>(
+        &self,
+        d: &mut D,
+        theme: &Theme,
+        font: &ThemeFont,
+        position: Vector2,
+        tint: Color,
+    ) {
+        if let Some(icon) = self.icon {
+            let sheet = if font.font_size > ButtonIconSheetId::X16.icon_width() as f32 {
+                ButtonIconSheetId::X32
+            } else {
+                ButtonIconSheetId::X16
+            };
+            d.draw_texture_pro(
+                &theme.button_icons[sheet],
+                icon.icon_cell_irec(sheet.icon_width()).as_rec(),
+                Rectangle::new(position.x, position.y, font.font_size, font.font_size),
+                Vector2::zero(),
+                0.0,
+                tint,
+            );
+        }
+        font.draw_styled_text(
+            d,
+            self.text,
+            position + Vector2::new(self.icon_width(font), 0.0),
+            tint,
+            self.style,
+        );
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RichStrIter<'a> {
     color_stack: Vec<ColorRef>,
+    style: TextStyle,
     string: &'a str,
 }
 
 impl std::error::Error for RichStrError {}
 
 impl<'a> Iterator for RichStrIter<'a> {
-    type Item = Result<(Option<ColorRef>, &'a str), RichStrError>;
+    type Item = Result<RichSpan<'a>, RichStrError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut s = self.string;
@@ -133,7 +307,7 @@ impl<'a> Iterator for RichStrIter<'a> {
             Some(string) => match string.split_once('}') {
                 Some((code, rest)) => {
                     s = rest;
-                    Some(code.parse::<ColorAct>())
+                    Some(code.parse::<RichAct>())
                 }
                 None => {
                     s = &s["\x1B{".len()..];
@@ -144,23 +318,29 @@ impl<'a> Iterator for RichStrIter<'a> {
         };
         let text;
         (text, self.string) = s.split_at(s.find("\x1B{").unwrap_or(s.len()));
+        let mut icon = None;
         match act {
-            Some(Ok(a)) => {
-                match a {
-                    ColorAct::Pop => _ = self.color_stack.pop(),
-                    ColorAct::Repl(c) => match self.color_stack.last_mut() {
-                        Some(back) => *back = c,
-                        None => self.color_stack.push(c),
-                    },
-                    ColorAct::Push(c) => {
-                        self.color_stack.push(c);
-                    }
+            Some(Ok(RichAct::Color(a))) => match a {
+                ColorAct::Pop => _ = self.color_stack.pop(),
+                ColorAct::Repl(c) => match self.color_stack.last_mut() {
+                    Some(back) => *back = c,
+                    None => self.color_stack.push(c),
+                },
+                ColorAct::Push(c) => {
+                    self.color_stack.push(c);
                 }
-                Some(Ok((self.color_stack.last().copied(), text)))
-            }
-            Some(Err(e)) => Some(Err(e)),
-            None => Some(Ok((None, text))),
+            },
+            Some(Ok(RichAct::Style(a))) => a.apply(&mut self.style),
+            Some(Ok(RichAct::Icon(IconAct(id)))) => icon = Some(id),
+            Some(Err(e)) => return Some(Err(e)),
+            None => {}
         }
+        Some(Ok(RichSpan {
+            color: self.color_stack.last().copied(),
+            style: self.style,
+            icon,
+            text,
+        }))
     }
 
     #[inline]
@@ -176,22 +356,39 @@ impl<'a> DoubleEndedIterator for RichStrIter<'a> {
         if s.is_empty() {
             return None;
         }
-        let color = match s.rsplit_once("\x1B{") {
+        let act = match s.rsplit_once("\x1B{") {
             Some((pre, string)) => match string.split_once('}') {
                 Some((code, text)) => {
                     self.string = pre;
                     s = text;
-                    Some(code.parse::<ColorRef>())
+                    Some(code.parse::<RichAct>())
+                }
+                None => {
+                    self.string = pre;
+                    Some(Err(RichStrError::InvalidEscapeCode))
                 }
-                None => Some(Err(RichStrError::InvalidEscapeCode)),
             },
-            None => None,
+            None => {
+                self.string = "";
+                None
+            }
+        };
+        // without the codes before it, only the span's own code is known
+        let mut span = RichSpan {
+            color: None,
+            style: TextStyle::default(),
+            icon: None,
+            text: s,
         };
-        match color {
-            Some(Ok(c)) => Some(Ok((Some(c), s))),
-            Some(Err(e)) => Some(Err(e)),
-            None => Some(Ok((None, s))),
+        match act {
+            Some(Ok(RichAct::Color(ColorAct::Repl(c) | ColorAct::Push(c)))) => span.color = Some(c),
+            Some(Ok(RichAct::Color(ColorAct::Pop))) => {}
+            Some(Ok(RichAct::Style(a))) => a.apply(&mut span.style),
+            Some(Ok(RichAct::Icon(IconAct(id)))) => span.icon = Some(id),
+            Some(Err(e)) => return Some(Err(e)),
+            None => {}
         }
+        Some(Ok(span))
     }
 }
 
@@ -253,9 +450,46 @@ impl RichStr {
     pub const fn iter(&self) -> RichStrIter<'_> {
         RichStrIter {
             color_stack: Vec::new(),
+            style: TextStyle {
+                is_bold: false,
+                is_italic: false,
+            },
             string: &self.0,
         }
     }
+
+    /// The text without its escape codes. Invalid escape codes are left out.
+    pub fn plain_text(&self) -> String {
+        self.iter().flatten().map(|span| span.text).collect()
+    }
+
+    /// Size of the text drawn on one line with `font`. Invalid escape codes are left out.
+    pub fn measure(&self, font: &ThemeFont) -> Vector2 {
+        self.iter()
+            .flatten()
+            .map(|span| span.measure(font))
+            .fold(Vector2::new(0.0, font.font_size), |size, span| {
+                Vector2::new(size.x + span.x, size.y.max(span.y))
+            })
+    }
+
+    /// Draws the text on one line with `font`, in `color` where it has no color of its own.
+    /// Invalid escape codes are left out.
+    pub fn draw<D: RaylibDraw>(
+        &self,
+        d: &mut D,
+        theme: &Theme,
+        font: &ThemeFont,
+        position: Vector2,
+        color: Color,
+    ) {
+        let mut x = position.x;
+        for span in self.iter().flatten() {
+            let tint = span.color.map_or(color, |c| c.get(theme));
+            span.draw(d, theme, font, Vector2::new(x, position.y), tint);
+            x += span.measure(font).x;
+        }
+    }
 }
 
 impl<'a> IntoIterator for &'a RichStr {
@@ -355,3 +589,93 @@ impl RichString {
         RichStr::new_mut(self.0.as_mut_str())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color = Color::new(255, 0, 0, 255);
+
+    fn span(color: Option<Color>, is_bold: bool, text: &str) -> RichSpan<'_> {
+        RichSpan {
+            color: color.map(ColorRef::Exact),
+            style: TextStyle {
+                is_bold,
+                is_italic: false,
+            },
+            icon: None,
+            text,
+        }
+    }
+
+    #[test]
+    fn test_rich_str_iter() {
+        let s = RichStr::new("plain \x1B{push:rgba(255,0,0,255)}\x1B{bold}b\x1B{pop}\x1B{/bold}x");
+        let spans = [
+            span(None, false, "plain "),
+            span(Some(RED), false, ""),
+            span(Some(RED), true, "b"),
+            span(None, true, ""),
+            span(None, false, "x"),
+        ];
+        assert_eq!(s.iter().len(), spans.len());
+        assert_eq!(s.iter().collect::<Result<Vec<_>, _>>(), Ok(spans.to_vec()));
+        assert_eq!(s.plain_text(), "plain bx");
+
+        // going backwards, spans only know their own codes
+        let mut back = spans;
+        back[2].color = None;
+        back[3].style.is_bold = false;
+        back.reverse();
+        assert_eq!(
+            s.iter().rev().collect::<Result<Vec<_>, _>>(),
+            Ok(back.to_vec())
+        );
+    }
+
+    #[test]
+    fn test_invalid_escape_codes() {
+        for s in [
+            "a\x1B{rgba(1,2,3)}",
+            "a\x1B{rgba(1,2,3,256)}",
+            "a\x1B{push:}",
+            "a\x1B{icon:}",
+            "a\x1B{bold",
+        ] {
+            let s = RichStr::new(s);
+            assert_eq!(
+                s.iter().collect::<Vec<_>>(),
+                [
+                    Ok(span(None, false, "a")),
+                    Err(RichStrError::InvalidEscapeCode)
+                ],
+                "{s:?}"
+            );
+            assert_eq!(
+                s.iter().rev().last(),
+                Some(Ok(span(None, false, "a"))),
+                "{s:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rich_act_round_trip() {
+        for act in [
+            RichAct::Color(ColorAct::Pop),
+            RichAct::Color(ColorAct::Repl(ColorRef::Exact(RED))),
+            RichAct::Color(ColorAct::Push(ColorRef::Exact(Color::new(1, 2, 3, 4)))),
+            RichAct::Style(StyleAct::Bold(true)),
+            RichAct::Style(StyleAct::Bold(false)),
+            RichAct::Style(StyleAct::Italic(true)),
+            RichAct::Style(StyleAct::Italic(false)),
+        ] {
+            let code = act.to_string();
+            let code = code
+                .strip_prefix("\x1B{")
+                .and_then(|code| code.strip_suffix('}'))
+                .unwrap();
+            assert_eq!(code.parse(), Ok(act));
+        }
+    }
+}
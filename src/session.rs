@@ -0,0 +1,215 @@
+//! The workspace as it was when the window closed: which files were open in which tabs, where each
+//! tab's camera was, which tab was focused, where the panels were docked and which were collapsed
+//! or closed. It is saved alongside the config on exit and restored on the next launch, unless the
+//! program is started with `--fresh`.
+//!
+//! Only tabs of saved graphs are kept, and they reopen the file as it was last saved. Scratch tabs
+//! and graphs that were never saved are left out.
+
+use crate::{
+    console::{Console, GraphRef, LogType},
+    dock::DockLayout,
+    file,
+    graph::GraphList,
+    logln,
+    tab::{BOOKMARK_SLOTS, CameraBookmark, Tab, TabList},
+    ui::{Anchoring, Panel},
+};
+use raylib::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+
+pub const SESSION_PATH: &str = "workspace.toml";
+
+/// An editor tab of the session.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionTab {
+    /// Where the tab's graph is saved
+    pub path: PathBuf,
+    pub camera: CameraBookmark,
+    /// The tab's camera bookmarks, by slot number
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bookmarks: BTreeMap<String, CameraBookmark>,
+}
+
+/// A panel of the session.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionPanel {
+    #[serde(default)]
+    pub is_collapsed: bool,
+    #[serde(default)]
+    pub is_closed: bool,
+    pub anchoring: Anchoring,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Index into `tabs` of the focused tab
+    pub focused: usize,
+    /// Every editor tab kept, in tab order
+    pub tabs: Vec<SessionTab>,
+    /// Each panel, by name
+    pub panels: BTreeMap<String, SessionPanel>,
+    /// See [`DockLayout::stacks`]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stacks: Vec<Vec<String>>,
+}
+
+impl Session {
+    /// Takes the tabs of saved graphs from `tabs`, and the state of each of `panels`.
+    pub fn capture<'a>(
+        tabs: &TabList,
+        dock: &DockLayout,
+        panels: impl IntoIterator<Item = (&'static str, &'a Panel)>,
+    ) -> Self {
+        let mut focused = 0;
+        let mut session_tabs = Vec::new();
+        for (n, tab) in tabs.editors().enumerate() {
+            let Some(path) = tab.path().filter(|_| !tab.is_scratch()) else {
+                continue;
+            };
+            if tabs.focused_index() == Some(n) {
+                focused = session_tabs.len();
+            }
+            session_tabs.push(SessionTab {
+                path: path.to_path_buf(),
+                camera: tab.view(),
+                bookmarks: (1..)
+                    .zip(tab.bookmarks())
+                    .filter_map(|(n, bookmark)| Some((n.to_string(), (*bookmark)?)))
+                    .collect(),
+            });
+        }
+        Self {
+            focused,
+            tabs: session_tabs,
+            panels: panels
+                .into_iter()
+                .map(|(name, panel)| {
+                    let panel = SessionPanel {
+                        is_collapsed: panel.is_collapsed,
+                        is_closed: panel.is_closed,
+                        anchoring: panel.anchoring,
+                    };
+                    (name.to_string(), panel)
+                })
+                .collect(),
+            stacks: dock.stacks.clone(),
+        }
+    }
+
+    /// Reads the session at [`SESSION_PATH`], or [`None`] if there isn't one.
+    pub fn load() -> std::io::Result<Option<Self>> {
+        match std::fs::read_to_string(SESSION_PATH) {
+            Ok(s) => toml::from_str(&s).map(Some).map_err(std::io::Error::other),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let s = toml::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(SESSION_PATH, s)
+    }
+
+    /// Docks, collapses and closes each of `panels` like the panel of the same name was in the
+    /// session, and stacks them the same way.
+    pub fn restore_panels(&self, dock: &mut DockLayout, panels: &mut [(&'static str, &mut Panel)]) {
+        for (name, panel) in panels.iter_mut() {
+            if let Some(saved) = self.panels.get(*name) {
+                panel.reanchor(saved.anchoring);
+                panel.is_collapsed = saved.is_collapsed;
+                panel.is_closed = saved.is_closed;
+            }
+        }
+        dock.stacks.clone_from(&self.stacks);
+        dock.arrange(panels);
+    }
+
+    /// Reopens the tabs of the session in place of the untouched tabs in `tabs`, such as the empty
+    /// graph every launch starts with. Tabs of the same file share a graph, like views opened
+    /// with `view`, as do tabs of files already opened for the ICs that run them. Files that
+    /// can't be opened anymore are skipped.
+    pub fn restore_tabs(
+        &self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        console: &mut Console,
+        graphs: &mut GraphList,
+        tabs: &mut TabList,
+    ) {
+        let mut focused = None;
+        let first_restored = tabs.len();
+        for (n, saved) in self.tabs.iter().enumerate() {
+            let graph = match graphs.find_path(&saved.path).and_then(|id| graphs.get(&id)) {
+                Some(graph) => Arc::downgrade(graph),
+                None => match file::open_graph(graphs, &saved.path) {
+                    Ok((graph, failed)) => {
+                        logln!(
+                            console,
+                            LogType::Success,
+                            "reopened {} as graph {}",
+                            saved.path.display(),
+                            GraphRef(*graph.read().unwrap().id())
+                        );
+                        file::log_failed_ics(console, failed);
+                        Arc::downgrade(&graph)
+                    }
+                    Err(e) => {
+                        logln!(
+                            console,
+                            LogType::Error,
+                            "failed to reopen {}: {e}",
+                            saved.path.display()
+                        );
+                        continue;
+                    }
+                },
+            };
+            match tabs.push_editor(rl, thread, graph) {
+                Ok(tab) => {
+                    tab.mark_saved(saved.path.clone());
+                    tab.jump_to(saved.camera);
+                    for (slot, bookmark) in &saved.bookmarks {
+                        if let Ok(slot @ 1..=BOOKMARK_SLOTS) = slot.parse::<usize>() {
+                            tab.set_bookmark(slot - 1, Some(*bookmark));
+                        }
+                    }
+                    if n == self.focused {
+                        focused = Some(tabs.len() - 1);
+                    }
+                }
+                Err(e) => {
+                    logln!(console, LogType::Error, "failed to create tab: {e}");
+                }
+            }
+        }
+
+        if tabs.len() == first_restored {
+            return;
+        }
+        // the tabs open before the session was restored give way to it, unless they've been used
+        for index in (0..first_restored).rev() {
+            let is_untouched = match &tabs[index] {
+                Tab::Editor(tab) => !tab.is_modified() && tab.path().is_none() && !tab.is_scratch(),
+            };
+            if is_untouched {
+                match tabs.remove(index) {
+                    Tab::Editor(tab) => {
+                        if tabs.editors_of_graph(&tab.graph).next().is_none()
+                            && let Some(graph) = tab.graph.upgrade()
+                        {
+                            graphs.retain(|g| !Arc::ptr_eq(g, &graph));
+                        }
+                    }
+                }
+                focused = focused.map(|focused| focused - 1);
+            }
+        }
+        if let Some(focused) = focused {
+            tabs.focus(focused)
+                .expect("restored tab should be in range");
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Short sounds played as feedback on editor actions, loaded from a sound pack: a directory
+//! holding one file per [`SoundEffect`].
+
+use crate::{
+    config,
+    console::{Console, LogType},
+    logln,
+    tab::EditorTab,
+};
+use raylib::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+pub const DEFAULT_SOUND_PACK: &str = "assets/sounds";
+/// Formats tried for each sound, in order.
+const SOUND_EXTENSIONS: [&str; 3] = ["wav", "ogg", "mp3"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundEffect {
+    Place,
+    Connect,
+    Delete,
+    Error,
+}
+
+impl SoundEffect {
+    pub const ALL: [Self; 4] = [Self::Place, Self::Connect, Self::Delete, Self::Error];
+
+    /// File name of the sound in a pack, without the extension.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Place => "place",
+            Self::Connect => "connect",
+            Self::Delete => "delete",
+            Self::Error => "error",
+        }
+    }
+
+    /// The sound for a graph going from `before` to `after` `(nodes, wires)`, if it changed.
+    pub fn for_change(before: (usize, usize), after: (usize, usize)) -> Option<Self> {
+        if after.0 < before.0 || after.1 < before.1 {
+            Some(Self::Delete)
+        } else if after.1 > before.1 {
+            Some(Self::Connect)
+        } else if after.0 > before.0 {
+            Some(Self::Place)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundSettings {
+    /// Directory of the sound pack; sounds missing from it are not played.
+    pub pack: PathBuf,
+    /// From 0 (silent) to 1 (full volume).
+    pub volume: f32,
+    pub muted: bool,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            pack: PathBuf::from(DEFAULT_SOUND_PACK),
+            volume: 0.5,
+            muted: false,
+        }
+    }
+}
+
+/// `(nodes, wires)` of the tab's graph, if it can be read.
+pub fn graph_size(tab: &EditorTab) -> Option<(usize, usize)> {
+    let graph = tab.graph.upgrade()?;
+    let graph = graph.try_read().ok()?;
+    Some((graph.nodes_iter().len(), graph.wires_iter().len()))
+}
+
+/// The loaded sounds of a pack, played by [`SoundEffect`].
+#[derive(Debug)]
+pub struct SoundPack<'aud> {
+    pub settings: SoundSettings,
+    /// Indexed by [`SoundEffect`]
+    sounds: [Option<Sound<'aud>>; SoundEffect::ALL.len()],
+}
+
+impl<'aud> SoundPack<'aud> {
+    /// Loads the sounds in `settings.pack`. Without `audio`, such as when there is no audio
+    /// device, nothing is loaded and nothing plays.
+    pub fn load(
+        audio: Option<&'aud RaylibAudio>,
+        settings: SoundSettings,
+        console: &mut Console,
+    ) -> Self {
+        let volume = settings.volume.clamp(0.0, 1.0);
+        let sounds = SoundEffect::ALL.map(|effect| {
+            let audio = audio?;
+            let path = SOUND_EXTENSIONS
+                .iter()
+                .map(|ext| settings.pack.join(format!("{}.{ext}", effect.name())))
+                .find(|path| path.is_file())?;
+            match audio.new_sound(&path.to_string_lossy()) {
+                Ok(sound) => {
+                    sound.set_volume(volume);
+                    Some(sound)
+                }
+                Err(e) => {
+                    logln!(
+                        console,
+                        LogType::Warning,
+                        "failed to load sound {}: {e}",
+                        path.display()
+                    );
+                    None
+                }
+            }
+        });
+        if audio.is_some() && sounds.iter().all(Option::is_none) {
+            logln!(
+                console,
+                LogType::Info,
+                "no sounds found in {}; editor sounds are off",
+                settings.pack.display()
+            );
+        }
+        Self { settings, sounds }
+    }
+
+    pub fn play(&self, effect: SoundEffect) {
+        if !self.settings.muted
+            && let Some(sound) = &self.sounds[effect as usize]
+        {
+            sound.play();
+        }
+    }
+
+    /// Mutes or unmutes every sound, remembering the choice in the config.
+    pub fn toggle_mute(&mut self, console: &mut Console) {
+        self.settings.muted = !self.settings.muted;
+        if self.settings.muted {
+            for sound in self.sounds.iter().flatten() {
+                sound.stop();
+            }
+            logln!(console, LogType::Info, "sounds muted");
+        } else {
+            logln!(console, LogType::Info, "sounds unmuted");
+        }
+        if let Err(e) = config::save_entry("sound", &self.settings) {
+            logln!(console, LogType::Error, "failed to save config: {e}");
+        }
+    }
+}
@@ -1,28 +1,477 @@
 use crate::{
-    GRID_SIZE, IVec2, Theme,
-    console::Console,
+    DEFAULT_GRID_SIZE, IVec2, Theme,
+    clipboard::ClipboardAction,
+    console::{Console, GraphRef, LogType},
+    context_menu::{ContextMenu, MenuAction, MenuItem, MenuResponse},
     graph::{
-        Graph,
-        node::{GateInstance, NodeId},
-        wire::{Flow, Wire},
+        Erased, Graph, GraphId, GraphList,
+        annotation::{Annotation, AnnotationId},
+        crossing::{HOP_RADIUS, JUNCTION_RADIUS},
+        model::GraphModel,
+        node::{Gate, GateId, GateInstance, NodeId, Ntd, Orientation},
+        region::{Region, RegionId},
+        wire::{Elbow, Flow, Wire, WireEnd},
     },
-    icon_sheets::{NodeIconSheetId, NodeIconSheetSetId},
+    icon_sheets::{NodeIconLookup, NodeIconSheetSetId},
     input::Inputs,
-    ivec::{AsIVec2, Bounds},
-    tool::{EditDragging, Tool},
+    ivec::{AsIVec2, Bounds, IBounds, IRect},
+    logln,
+    tool::{EditDragging, EraseSweep, RegionDragging, Tool, WireDragging, WireHandle},
     toolpane::ToolPane,
-    ui::Panel,
+    ui::{self, Panel},
+    waveform::WAVEFORM_TICKS,
 };
 use raylib::prelude::*;
-use rustc_hash::FxHashSet;
-use std::sync::{RwLock, Weak};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock, Weak},
+    time::{Duration, Instant},
+};
+
+/// Space around a region's label, in world units.
+const REGION_LABEL_PADDING: f32 = 2.0;
+
+/// The strip along the top of a region showing its label, which the region is grabbed by.
+fn region_header(theme: &Theme, rec: Rectangle) -> Rectangle {
+    let height = theme.general_font.line_height() + 2.0 * REGION_LABEL_PADDING;
+    Rectangle::new(rec.x, rec.y, rec.width, height.min(rec.height))
+}
+
+/// The newest region whose header contains `world_pos`.
+fn region_header_at(graph: &Graph, theme: &Theme, world_pos: Vector2) -> Option<RegionId> {
+    graph
+        .regions_ordered()
+        .into_iter()
+        .rev()
+        .find(|region| {
+            region_header(theme, IRect::from(*region.bounds()).as_rec())
+                .check_collision_point_rec(world_pos)
+        })
+        .map(|region| *region.id())
+}
+
+/// Draws `region` with its top-left corner at `min`, which differs from its position while it is
+/// being dragged.
+/// The lines of the tooltip shown over a node: how to refer to it, its gate and state, and how
+/// many wires it has.
+fn node_tooltip(graph: &Graph, theme: &Theme, id: &NodeId) -> Option<Vec<(Color, String)>> {
+    let node = graph.node(id)?;
+    let bits = graph.displayed_bits(id);
+    let state = if node.width() > 1 {
+        format!("{bits:0width$b}", width = usize::from(node.width()))
+    } else if bits.any() {
+        "on".to_string()
+    } else {
+        "off".to_string()
+    };
+    let mut lines = vec![
+        (theme.hyperref, format!("{}-{id}", graph.id())),
+        (
+            theme.foreground,
+            format!("gate: {}", node.gate().as_gate().id()),
+        ),
+        (theme.foreground, format!("state: {state}")),
+    ];
+    let value = match *node.gate() {
+        GateInstance::Resistor { resistance } => Some(format!("resistance: {resistance}")),
+        GateInstance::Capacitor { capacity, stored } => {
+            Some(format!("stored: {stored} of {capacity}"))
+        }
+        GateInstance::Led { color } => Some(format!("color: {color}")),
+        GateInstance::Delay { ticks, .. } => Some(format!("delay: {ticks} ticks")),
+        GateInstance::Split { bit } | GateInstance::Merge { bit } => Some(format!("bit: {bit}")),
+        GateInstance::Input { pin } | GateInstance::Output { pin } => Some(format!("pin: {pin}")),
+        GateInstance::Or
+        | GateInstance::And
+        | GateInstance::Nor
+        | GateInstance::Xor
+        | GateInstance::Battery
+        | GateInstance::Ic { .. }
+        | GateInstance::Switch { .. }
+        | GateInstance::Button { .. } => None,
+    };
+    lines.extend(value.map(|value| (theme.foreground, value)));
+    lines.push((
+        theme.foreground2,
+        format!(
+            "{} in, {} out",
+            graph.wires_to(id).count(),
+            graph.wires_from(id).count()
+        ),
+    ));
+    Some(lines)
+}
+
+fn draw_region<D: RaylibDraw>(d: &mut D, theme: &Theme, region: &Region, min: Vector2, pixel: f32) {
+    let color = theme.resistance[usize::from(region.color)];
+    let bounds = region.bounds();
+    let rec = Rectangle::new(min.x, min.y, bounds.width() as f32, bounds.height() as f32);
+    d.draw_rectangle_rec(rec, color.alpha(0.125));
+    d.draw_rectangle_lines_ex(rec, pixel, color);
+    let header = region_header(theme, rec);
+    d.draw_rectangle_rec(header, color.alpha(0.375));
+    theme.general_font.draw_text(
+        d,
+        &region.label,
+        Vector2::new(
+            header.x + REGION_LABEL_PADDING,
+            header.y + REGION_LABEL_PADDING,
+        ),
+        theme.foreground,
+    );
+}
+
+/// Text placed by the annotate tool, to be edited from the properties panel.
+const DEFAULT_ANNOTATION_TEXT: &str = "note";
+
+/// The area covered by `annotation`'s text with its top-left corner at `min`.
+fn annotation_rec(theme: &Theme, annotation: &Annotation, min: Vector2) -> Rectangle {
+    let size = theme.general_font.measure_text(&annotation.text);
+    // keep empty annotations clickable
+    let width = size.x.max(theme.general_font.font_size);
+    let height = size.y.max(theme.general_font.line_height());
+    Rectangle::new(min.x, min.y, width, height)
+}
+
+/// The newest annotation whose text contains `world_pos`.
+fn annotation_at(graph: &Graph, theme: &Theme, world_pos: Vector2) -> Option<AnnotationId> {
+    graph
+        .annotations_ordered()
+        .into_iter()
+        .rev()
+        .find(|annotation| {
+            annotation_rec(theme, annotation, annotation.position().as_vec2())
+                .check_collision_point_rec(world_pos)
+        })
+        .map(|annotation| *annotation.id())
+}
+
+/// How far from a wire (in world units) the cursor can be while still pointing at it.
+fn wire_tolerance(grid_size: u8) -> f32 {
+    f32::from(grid_size) * 0.25
+}
+/// Points along the way from `from` to `to`, not including `from` unless they are the same, close
+/// enough together that no node or wire between them is skipped.
+fn sweep_points(from: Vector2, to: Vector2, grid_size: u8) -> impl Iterator<Item = Vector2> {
+    let steps = ((to - from).length() / wire_tolerance(grid_size))
+        .ceil()
+        .max(1.0) as usize;
+    (1..=steps).map(move |n| from.lerp(to, n as f32 / steps as f32))
+}
+
+/// Fewest pixels between the lines of the grid before coarser lines are drawn instead.
+const MIN_GRID_SPACING: f32 = 4.0;
+/// How long the eval order sweep stays on each node.
+const EVAL_SWEEP_STEP: Duration = Duration::from_millis(150);
+/// Zoom exponents offered by the zoom dropdown, the `zoom` command and the zoom preset keybinds.
+pub const ZOOM_PRESETS: [i8; 5] = [-2, -1, 0, 1, 2];
+/// How long the camera takes to glide to a new view.
+const CAMERA_ANIMATION_DURATION: Duration = Duration::from_millis(250);
+/// Node sheet zoom exponent (see [`NodeIconSheetSetId::from_zoom_exp`]) below which nodes are at
+/// most 4 pixels across, and are drawn as plain quads with their wires as straight lines.
+const FAR_ZOOM_EXP: i32 = -1;
+/// Ticks a wire takes to fade after its source changes state, with [`Theme::wire_fade`].
+const WIRE_FADE_TICKS: u64 = 8;
+/// How far toward the background a faded wire is.
+const WIRE_FADE_AMOUNT: f32 = 0.6;
+/// How many cells around the key cursor [`Inputs::cycle_nodes`] looks for nodes in.
+const KEY_CYCLE_RADIUS: i32 = 8;
+
+/// A zoom exponent as a percentage, such as `"200%"`.
+pub fn zoom_percent(zoom_exp: f32) -> String {
+    format!("{:.0}%", 100.0 * 2.0f32.powf(zoom_exp))
+}
+
+/// The color of a wire out of `src`: that of its state, faded toward the background by how long
+/// ago the state last changed if [`Theme::wire_fade`] is on.
+fn wire_color(theme: &Theme, graph: &Graph, src: &NodeId) -> Color {
+    let color = if graph.displayed_state(src) {
+        theme.active
+    } else {
+        theme.foreground
+    };
+    if !theme.wire_fade {
+        return color;
+    }
+    // fully faded if it has never changed
+    let age = graph.ticks_since_change(src).map_or(1.0, |ticks| {
+        ticks.min(WIRE_FADE_TICKS) as f32 / WIRE_FADE_TICKS as f32
+    });
+    color.lerp(theme.background, age * WIRE_FADE_AMOUNT)
+}
+
+/// Draws `wire` as the editor does, straight from end to end if `is_far` (see [`FAR_ZOOM_EXP`]).
+/// Returns [`None`] if `wire` is not valid for the graph.
+///
+/// See [`Wire::thickness`] for `scale` and `pixel`.
+#[must_use]
+fn draw_wire<D: RaylibDraw>(
+    d: &mut D,
+    graph: &Graph,
+    wire: &Wire,
+    color: Color,
+    scale: f32,
+    pixel: f32,
+    is_far: bool,
+) -> Option<()> {
+    if is_far {
+        let [start_pos, _, end_pos] = wire.points(graph, graph.cell_center())?;
+        d.draw_line_v(start_pos, end_pos, color);
+        Some(())
+    } else {
+        wire.draw(d, graph, graph.cell_center(), color, scale, pixel)
+    }
+}
+
+/// Wires `src` to `dst` for the create tool, routing the wire around nodes if `auto_route` is
+/// on, as with [`ToolPane::auto_route`].
+fn create_tool_wire(
+    graph: &mut Graph,
+    elbow: Elbow,
+    auto_route: bool,
+    src: NodeId,
+    dst: NodeId,
+    console: &mut Console,
+) {
+    if let Ok(wire) = graph.create_wire(elbow, src, dst, console)
+        && auto_route
+    {
+        let id = *wire.id();
+        graph.route_wire(&id);
+    }
+}
+
+/// Destination, origin, and rotation for drawing a node icon into `rec` facing `orientation`,
+/// since raylib rotates textures about their origin rather than their center.
+pub fn icon_placement(rec: Rectangle, orientation: Orientation) -> (Rectangle, Vector2, f32) {
+    let origin = Vector2::new(rec.width * 0.5, rec.height * 0.5);
+    let dest = Rectangle {
+        x: rec.x + origin.x,
+        y: rec.y + origin.y,
+        ..rec
+    };
+    (dest, origin, orientation.degrees())
+}
+
+/// Color of the part of a node's icon showing its non-transistor data, if its gate shows any.
+/// The node positions a box selection from `start` to `end` selects: those of the nodes whose
+/// centers are inside the box.
+fn marquee_bounds(start: Vector2, end: Vector2, cell_center: Vector2) -> IBounds {
+    let min = Vector2::new(start.x.min(end.x), start.y.min(end.y)) - cell_center;
+    let max = Vector2::new(start.x.max(end.x), start.y.max(end.y)) - cell_center;
+    IBounds::new(
+        IVec2::new(min.x.ceil() as i32, min.y.ceil() as i32),
+        IVec2::new(max.x.floor() as i32, max.y.floor() as i32),
+    )
+}
+
+pub fn ntd_tint(theme: &Theme, gate: &GateInstance) -> Option<Color> {
+    match *gate {
+        GateInstance::Or
+        | GateInstance::And
+        | GateInstance::Nor
+        | GateInstance::Xor
+        | GateInstance::Battery
+        | GateInstance::Delay { .. }
+        | GateInstance::Ic { .. }
+        | GateInstance::Split { .. }
+        | GateInstance::Merge { .. }
+        | GateInstance::Input { .. }
+        | GateInstance::Output { .. }
+        | GateInstance::Switch { .. }
+        | GateInstance::Button { .. } => None,
+
+        GateInstance::Resistor { resistance: n } | GateInstance::Led { color: n } => Some(
+            theme
+                .resistance
+                .get(n as usize)
+                .copied()
+                .expect("gate should never contain invalid NT data"),
+        ),
+
+        GateInstance::Capacitor { capacity, stored } => Some(
+            theme
+                .active
+                .alpha(u8::from(stored) as f32 / u8::from(capacity) as f32),
+        ),
+    }
+}
+
+/// Where nodes placed or moved under the cursor go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapMode {
+    /// Into the cell under the cursor
+    #[default]
+    Cell,
+    /// Centered on the nearest half-cell to the cursor
+    Half,
+    /// Centered on the cursor
+    Off,
+}
+
+impl std::fmt::Display for SnapMode {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cell => "cell",
+            Self::Half => "half",
+            Self::Off => "off",
+        }
+        .fmt(f)
+    }
+}
+
+impl std::str::FromStr for SnapMode {
+    type Err = ();
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cell" => Ok(Self::Cell),
+            "half" => Ok(Self::Half),
+            "off" => Ok(Self::Off),
+            _ => Err(()),
+        }
+    }
+}
+
+impl SnapMode {
+    /// The position of a node placed at `world_pos` on a grid of `grid_size`.
+    pub fn node_position(self, world_pos: Vector2, grid_size: u8) -> IVec2 {
+        let size = f32::from(grid_size);
+        // top-left corner of a node centered on the cursor
+        let corner = world_pos - Vector2::new(size, size) * 0.5;
+        match self {
+            Self::Cell => world_pos.as_ivec2().snap(grid_size.into()),
+            Self::Half => {
+                let step = f32::from(grid_size / 2).max(1.0);
+                IVec2::new(
+                    ((corner.x / step).round() * step) as i32,
+                    ((corner.y / step).round() * step) as i32,
+                )
+            }
+            Self::Off => IVec2::new(corner.x.round() as i32, corner.y.round() as i32),
+        }
+    }
+}
+
+/// Starts fast and slows down into `t = 1`.
+fn ease_out_cubic(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// How many camera bookmarks each editor tab has, numbered from 1.
+pub const BOOKMARK_SLOTS: usize = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BookmarkAction {
+    /// Remember the current view in the slot
+    Set,
+    /// Glide back to the view in the slot
+    Jump,
+}
+
+/// A view saved in one of an editor tab's bookmark slots.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraBookmark {
+    /// World position in the middle of the view
+    pub x: f32,
+    pub y: f32,
+    pub zoom_exp: f32,
+}
+
+/// A camera move in progress, advanced by [`EditorTab::advance_camera_animation`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CameraAnimation {
+    /// World position in the middle of the view when the animation started
+    from_center: Vector2,
+    from_zoom_exp: f32,
+    to_center: Vector2,
+    to_zoom_exp: f32,
+    started: Instant,
+}
+
+/// A node watched in an editor tab, whose state changes are logged and plotted in the
+/// [`Waveform`](crate::waveform::Waveform).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watched {
+    /// The state it was last logged in
+    logged_state: bool,
+    /// Its state after each of the last [`WAVEFORM_TICKS`] ticks, oldest first
+    pub ticks: VecDeque<bool>,
+}
+
+/// The cell moved around with the arrow keys, for placing and picking nodes without the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct KeyCursor {
+    /// Top-left corner of the cell
+    cell: IVec2,
+    /// Where [`Inputs::cycle_nodes`] started looking from and how many nodes it has gone
+    /// through, while it is being pressed without moving the cursor
+    cycle: Option<(IVec2, usize)>,
+}
+
+/// What the static layer of an editor tab was drawn for. It is redrawn when any of it changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LayerKey {
+    graph: GraphId,
+    /// [`Graph::revision`] when the layer was drawn
+    revision: u64,
+    camera_target: Vector2,
+    zoom_exp: f32,
+    viewport: Bounds,
+    /// Whether node backgrounds are drawn, which they aren't for [`Tool::Interact`]
+    with_backgrounds: bool,
+}
 
 #[derive(Debug)]
 pub struct EditorTab {
     camera_target: Vector2,
     zoom_exp: f32,
+    /// Where the camera is gliding to, if anywhere
+    camera_animation: Option<CameraAnimation>,
     grid: RenderTexture2D,
     dirty: bool,
+    /// Every wire in its inactive color and every node's background, which only change when the
+    /// graph, camera or theme do. Only active wires and the rest of each node are drawn over it
+    /// every frame.
+    layer: RenderTexture2D,
+    /// [`None`] if the layer needs to be redrawn regardless
+    layer_key: Option<LayerKey>,
+    path: Option<PathBuf>,
+    is_modified: bool,
+    /// Whether the graph is a throwaway one that is never saved or backed up
+    is_scratch: bool,
+    /// Index of the next island to visit with [`Inputs::next_island`]
+    island_cursor: usize,
+    /// Whether this tab pans and zooms together with other synced tabs of the same graph
+    camera_sync: bool,
+    /// Where nodes placed or moved in this tab go
+    snap: SnapMode,
+    /// When the eval order overlay was shown, for timing its sweep. [`None`] while hidden.
+    eval_order_overlay: Option<Instant>,
+    /// The right-click menu, while it is open
+    context_menu: Option<ContextMenu>,
+    /// Nodes whose state changes are logged and plotted
+    watched: FxHashMap<NodeId, Watched>,
+    /// The node picked from the context menu to show in the properties panel
+    inspected: Option<NodeId>,
+    /// The nodes whose gates were last replaced through this tab, with their old gates
+    replaced_gates: Vec<(NodeId, Gate)>,
+    /// `(revision, nodes, wires)` of the graph when last counted for the tab bar
+    graph_counts: Option<(u64, usize, usize)>,
+    /// Views set and jumped to with [`Inputs::bookmark`]
+    bookmarks: [Option<CameraBookmark>; BOOKMARK_SLOTS],
+    /// Hidden until an arrow key is pressed, and again once the mouse moves
+    key_cursor: Option<KeyCursor>,
+    /// Where the mouse was last frame, for noticing when it moves
+    last_mouse: Vector2,
+    /// Keeps the graph from being edited through this tab, leaving only the interact tool
+    is_locked: bool,
     pub graph: Weak<RwLock<Graph>>,
     pub selection: FxHashSet<NodeId>,
 }
@@ -36,16 +485,199 @@ impl EditorTab {
         graph: Weak<RwLock<Graph>>,
     ) -> Result<Self, raylib::error::Error> {
         let grid = rl.load_render_texture(thread, width, height)?;
+        let layer = rl.load_render_texture(thread, width, height)?;
         Ok(Self {
             camera_target: Vector2::zero(),
             zoom_exp: 0.0,
+            camera_animation: None,
             grid,
             dirty: true,
+            layer,
+            layer_key: None,
+            path: None,
+            is_modified: false,
+            is_scratch: false,
+            island_cursor: 0,
+            camera_sync: false,
+            snap: SnapMode::default(),
+            eval_order_overlay: None,
+            context_menu: None,
+            watched: FxHashMap::default(),
+            inspected: None,
+            replaced_gates: Vec::new(),
+            graph_counts: None,
+            bookmarks: [None; BOOKMARK_SLOTS],
+            key_cursor: None,
+            last_mouse: Vector2::zero(),
+            is_locked: false,
             graph,
             selection: FxHashSet::default(),
         })
     }
 
+    /// Creates another view of the same graph, starting with the same camera.
+    pub fn new_view(
+        &self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) -> Result<Self, raylib::error::Error> {
+        let mut tab = Self::new(
+            rl,
+            thread,
+            self.grid.width().try_into().unwrap(),
+            self.grid.height().try_into().unwrap(),
+            self.graph.clone(),
+        )?;
+        tab.camera_target = self.camera_target;
+        tab.zoom_exp = self.zoom_exp;
+        tab.path.clone_from(&self.path);
+        tab.is_modified = self.is_modified;
+        tab.is_scratch = self.is_scratch;
+        tab.camera_sync = self.camera_sync;
+        tab.snap = self.snap;
+        tab.is_locked = self.is_locked;
+        Ok(tab)
+    }
+
+    #[inline]
+    pub const fn is_camera_synced(&self) -> bool {
+        self.camera_sync
+    }
+
+    #[inline]
+    pub fn set_camera_sync(&mut self, sync: bool) {
+        self.camera_sync = sync;
+    }
+
+    #[inline]
+    pub const fn is_locked(&self) -> bool {
+        self.is_locked
+    }
+
+    #[inline]
+    pub fn set_locked(&mut self, locked: bool) {
+        self.is_locked = locked;
+    }
+
+    /// Warns if the tab is locked, returning whether the graph may be edited through it.
+    pub fn check_unlocked(&self, console: &mut Console) -> bool {
+        if self.is_locked {
+            logln!(
+                console,
+                LogType::Warning,
+                "this tab is locked, unlock it to edit the graph"
+            );
+        }
+        !self.is_locked
+    }
+
+    #[inline]
+    pub const fn snap(&self) -> SnapMode {
+        self.snap
+    }
+
+    /// Also redraws the grid, which shows half-cells while snapping to them.
+    #[inline]
+    pub fn set_snap(&mut self, snap: SnapMode) {
+        self.snap = snap;
+        self.dirty = true;
+    }
+
+    /// Copies the camera of `other`, if it differs.
+    fn follow_camera(&mut self, other: &Self) {
+        if self.camera_target != other.camera_target || self.zoom_exp != other.zoom_exp {
+            self.camera_target = other.camera_target;
+            self.zoom_exp = other.zoom_exp;
+            self.dirty = true;
+        }
+    }
+
+    /// The file this tab was last saved to or opened from.
+    #[inline]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// The name of the file if the tab has been saved, otherwise the graph ID.
+    pub fn title(&self) -> String {
+        if self.is_scratch {
+            "scratch".to_owned()
+        } else if let Some(name) = self.path.as_deref().and_then(Path::file_stem) {
+            name.to_string_lossy().into_owned()
+        } else if let Some(graph) = self.graph.upgrade()
+            && let Ok(graph) = graph.try_read()
+        {
+            format!("graph {}", graph.id())
+        } else {
+            "untitled".to_owned()
+        }
+    }
+
+    /// Recounts the graph's nodes and wires if it has changed since they were last counted.
+    pub fn refresh_graph_counts(&mut self) {
+        if let Some(graph) = self.graph.upgrade()
+            && let Ok(graph) = graph.try_read()
+            && self
+                .graph_counts
+                .is_none_or(|(revision, ..)| revision != graph.revision())
+        {
+            self.graph_counts = Some((
+                graph.revision(),
+                graph.nodes_iter().len(),
+                graph.wires_iter().len(),
+            ));
+        }
+    }
+
+    /// The node and wire counts of the graph, as of the last [`Self::refresh_graph_counts`].
+    #[inline]
+    pub fn graph_counts(&self) -> Option<(usize, usize)> {
+        self.graph_counts.map(|(_, nodes, wires)| (nodes, wires))
+    }
+
+    /// The node and wire counts shown beside the title, as of the last
+    /// [`Self::refresh_graph_counts`], marked if the tab is locked.
+    pub fn badge(&self) -> Option<String> {
+        self.graph_counts.map(|(_, nodes, wires)| {
+            if self.is_locked {
+                format!("{nodes}n {wires}w locked")
+            } else {
+                format!("{nodes}n {wires}w")
+            }
+        })
+    }
+
+    /// Whether the graph has changed since it was last saved. Scratch graphs are never saved,
+    /// so they never count as modified.
+    #[inline]
+    pub const fn is_modified(&self) -> bool {
+        self.is_modified && !self.is_scratch
+    }
+
+    #[inline]
+    pub const fn is_scratch(&self) -> bool {
+        self.is_scratch
+    }
+
+    /// Redraw the grid and the static layer on the next [`Self::refresh_grid`] and
+    /// [`Self::refresh_layer`], such as after the theme changes.
+    #[inline]
+    pub fn mark_grid_dirty(&mut self) {
+        self.dirty = true;
+        self.layer_key = None;
+    }
+
+    #[inline]
+    pub fn mark_modified(&mut self) {
+        self.is_modified = true;
+    }
+
+    #[inline]
+    pub fn mark_saved(&mut self, path: PathBuf) {
+        self.path = Some(path);
+        self.is_modified = false;
+    }
+
     #[inline]
     pub const fn zoom_exp(&self) -> f32 {
         self.zoom_exp
@@ -61,8 +693,202 @@ impl EditorTab {
         }
     }
 
-    /// `pan_speed` is scaled by zoom (zoom applied first)
+    /// The grid size of the tab's graph, or the default while the graph is busy.
+    pub fn grid_size(&self) -> u8 {
+        self.graph
+            .upgrade()
+            .and_then(|graph| graph.try_read().ok().map(|graph| graph.grid_size()))
+            .unwrap_or(DEFAULT_GRID_SIZE)
+    }
+
+    /// From a node's position to its center in the tab's graph.
+    pub fn cell_center(&self) -> Vector2 {
+        let half = f32::from(self.grid_size()) * 0.5;
+        Vector2::new(half, half)
+    }
+
+    /// The zoom exponent the camera is animating to, or the current one if it isn't animating.
+    #[inline]
+    pub fn target_zoom_exp(&self) -> f32 {
+        self.camera_animation
+            .map_or(self.zoom_exp, |animation| animation.to_zoom_exp)
+    }
+
+    /// Zooms about the middle of the view, gliding to the new zoom.
+    pub fn set_zoom_exp(&mut self, zoom_exp: f32) {
+        let center = self
+            .camera_animation
+            .map_or_else(|| self.view_center(), |animation| animation.to_center);
+        self.animate_to(center, zoom_exp);
+    }
+
+    /// The tab's bookmark slots, the first numbered 1.
+    #[inline]
+    pub const fn bookmarks(&self) -> &[Option<CameraBookmark>; BOOKMARK_SLOTS] {
+        &self.bookmarks
+    }
+
+    /// Puts `bookmark` in `slot`, or clears it if [`None`].
+    #[inline]
+    pub const fn set_bookmark(&mut self, slot: usize, bookmark: Option<CameraBookmark>) {
+        self.bookmarks[slot] = bookmark;
+    }
+
+    /// Saves the current view in `slot`, or glides back to the one saved there.
+    pub fn use_bookmark(&mut self, action: BookmarkAction, slot: usize, console: &mut Console) {
+        match action {
+            BookmarkAction::Set => {
+                self.bookmarks[slot] = Some(self.view());
+                logln!(
+                    console,
+                    LogType::Info,
+                    "bookmarked the view as {}",
+                    slot + 1
+                );
+            }
+            BookmarkAction::Jump => match self.bookmarks[slot] {
+                Some(CameraBookmark { x, y, zoom_exp }) => {
+                    self.animate_to(Vector2::new(x, y), zoom_exp);
+                }
+                None => logln!(
+                    console,
+                    LogType::Info,
+                    "no view is bookmarked as {} in this tab",
+                    slot + 1
+                ),
+            },
+        }
+    }
+
+    /// Opens a dropdown of the [`ZOOM_PRESETS`] with its top-left corner at `position`.
+    pub fn open_zoom_menu(&mut self, position: Vector2) {
+        let items = ZOOM_PRESETS
+            .into_iter()
+            .map(|zoom_exp| {
+                MenuItem::action(zoom_percent(zoom_exp.into()), MenuAction::SetZoom(zoom_exp))
+            })
+            .collect();
+        self.context_menu = Some(ContextMenu::new(position, items));
+    }
+
+    /// Moves the camera so that `world_pos` is in the middle of the view, stopping any animation.
+    pub fn center_on(&mut self, world_pos: Vector2) {
+        self.camera_animation = None;
+        self.set_view(world_pos, self.zoom_exp);
+    }
+
+    /// Glides the camera to the node-sized cell at `cell` if any of it is out of view.
+    fn reveal_cell(&mut self, cell: IVec2, grid_size: u8) {
+        let size = f32::from(grid_size);
+        let min = cell.as_vec2();
+        let view = self.view_bounds();
+        if !(view.contains(min) && view.contains(min + Vector2::new(size, size))) {
+            self.animate_center_on(min + Vector2::new(size, size) * 0.5);
+        }
+    }
+
+    /// The cell a stamp would be placed at: the key cursor's while it is shown, otherwise the one
+    /// under the mouse.
+    fn stamp_origin(&self, input: &Inputs, grid_size: u8) -> IVec2 {
+        self.key_cursor.map_or_else(
+            || {
+                self.snap
+                    .node_position(self.screen_to_world(input.cursor), grid_size)
+            },
+            |cursor| cursor.cell,
+        )
+    }
+
+    /// Whether the key cursor is shown, in which case [`Inputs::cycle_nodes`] goes to it rather
+    /// than anything sharing its binding.
+    #[inline]
+    pub const fn is_key_cursor_shown(&self) -> bool {
+        self.key_cursor.is_some()
+    }
+
+    /// Glides the camera until `world_pos` is in the middle of the view.
+    pub fn animate_center_on(&mut self, world_pos: Vector2) {
+        self.animate_to(world_pos, self.target_zoom_exp());
+    }
+
+    /// Glides the camera until `center` is in the middle of the view at `zoom_exp`, starting from
+    /// wherever it is now.
+    pub fn animate_to(&mut self, center: Vector2, zoom_exp: f32) {
+        let zoom_exp = zoom_exp.clamp(-3.0, 2.0);
+        let from_center = self.view_center();
+        if from_center == center && self.zoom_exp == zoom_exp {
+            self.camera_animation = None;
+            return;
+        }
+        self.camera_animation = Some(CameraAnimation {
+            from_center,
+            from_zoom_exp: self.zoom_exp,
+            to_center: center,
+            to_zoom_exp: zoom_exp,
+            started: Instant::now(),
+        });
+    }
+
+    /// Moves the camera along its animation, if it has one.
+    pub fn advance_camera_animation(&mut self) {
+        let Some(animation) = self.camera_animation else {
+            return;
+        };
+        let t = (animation.started.elapsed().as_secs_f32()
+            / CAMERA_ANIMATION_DURATION.as_secs_f32())
+        .min(1.0);
+        if t >= 1.0 {
+            self.camera_animation = None;
+        }
+        let eased = ease_out_cubic(t);
+        self.set_view(
+            animation.from_center + (animation.to_center - animation.from_center) * eased,
+            animation.from_zoom_exp + (animation.to_zoom_exp - animation.from_zoom_exp) * eased,
+        );
+    }
+
+    /// The world position in the middle of the view.
+    fn view_center(&self) -> Vector2 {
+        let view_size = rvec2(self.grid.width(), self.grid.height()) / self.camera().zoom;
+        self.camera_target + view_size * 0.5
+    }
+
+    /// The current view, as a bookmark would save it.
+    pub fn view(&self) -> CameraBookmark {
+        let center = self.view_center();
+        CameraBookmark {
+            x: center.x,
+            y: center.y,
+            zoom_exp: self.zoom_exp,
+        }
+    }
+
+    /// Moves straight to `view`, without gliding there.
+    #[inline]
+    pub fn jump_to(&mut self, view: CameraBookmark) {
+        self.set_view(Vector2::new(view.x, view.y), view.zoom_exp);
+    }
+
+    /// Puts `center` in the middle of the view at `zoom_exp`.
+    fn set_view(&mut self, center: Vector2, zoom_exp: f32) {
+        self.zoom_exp = zoom_exp;
+        let view_size = rvec2(self.grid.width(), self.grid.height()) / self.camera().zoom;
+        self.camera_target = center - view_size * 0.5;
+        self.dirty = true;
+    }
+
+    /// The part of the world in view, in world space.
+    pub fn view_bounds(&self) -> Bounds {
+        let view_size = rvec2(self.grid.width(), self.grid.height()) / self.camera().zoom;
+        Bounds::new(self.camera_target, self.camera_target + view_size)
+    }
+
+    /// `pan_speed` is scaled by zoom (zoom applied first). Any zoom or pan stops the camera's
+    /// animation, so that it doesn't fight the user.
     pub fn zoom_and_pan(&mut self, origin: Vector2, pan: Vector2, zoom: f32, pan_speed: f32) {
+        if zoom != 0.0 || pan.length_sqr() > 0.0 {
+            self.camera_animation = None;
+        }
         if zoom != 0.0 {
             let new_zoom = (self.zoom_exp + zoom).clamp(-3.0, 2.0);
             if self.zoom_exp != new_zoom {
@@ -105,7 +931,13 @@ impl EditorTab {
                 new_width.try_into().unwrap(),
                 new_height.try_into().unwrap(),
             )?;
+            self.layer = rl.load_render_texture(
+                thread,
+                new_width.try_into().unwrap(),
+                new_height.try_into().unwrap(),
+            )?;
             self.dirty = true;
+            self.layer_key = None;
         }
         Ok(())
     }
@@ -122,38 +954,159 @@ impl EditorTab {
 
             let camera = self.camera();
 
+            // zoomed out, only every other line is drawn, then every fourth, and so on
+            let mut step = i32::from(self.grid_size());
+            while step as f32 * camera.zoom < MIN_GRID_SPACING {
+                step *= 2;
+            }
+
             let mut start = IVec2::from_vec2(rl.get_screen_to_world2D(viewport.min, camera));
             let mut end = IVec2::from_vec2(rl.get_screen_to_world2D(viewport.max, camera));
 
-            start = start.snap(GRID_SIZE.into());
-            start.x -= i32::from(GRID_SIZE);
-            start.y -= i32::from(GRID_SIZE);
+            start = start.snap(step);
+            start.x -= step;
+            start.y -= step;
 
-            end = end.snap(GRID_SIZE.into());
-            end.x += i32::from(GRID_SIZE);
-            end.y += i32::from(GRID_SIZE);
+            end = end.snap(step);
+            end.x += step;
+            end.y += step;
 
             let mut d = rl.begin_texture_mode(thread, &mut self.grid);
             d.clear_background(Color::BLANK);
             {
                 let mut d = d.begin_mode2D(camera);
-                if camera.zoom.recip() >= f32::from(GRID_SIZE) {
-                    // size of 1 pixel is smaller than a grid
-                    d.clear_background(theme.background1);
-                } else {
-                    for y in (start.y..=end.y).step_by(GRID_SIZE as usize) {
-                        d.draw_line(start.x, y, end.x, y, theme.background1);
+                // half-cells, faintly, while they're snapped to and far enough apart to see
+                let half = step / 2;
+                if self.snap == SnapMode::Half
+                    && step == i32::from(self.grid_size())
+                    && half as f32 * camera.zoom >= MIN_GRID_SPACING
+                {
+                    let color = theme.background1.alpha(0.5);
+                    for y in (start.y + half..=end.y).step_by(step as usize) {
+                        d.draw_line(start.x, y, end.x, y, color);
                     }
-                    for x in (start.x..=end.x).step_by(GRID_SIZE as usize) {
-                        d.draw_line(x, start.y, x, end.y, theme.background1);
+                    for x in (start.x + half..=end.x).step_by(step as usize) {
+                        d.draw_line(x, start.y, x, end.y, color);
                     }
                 }
+                for y in (start.y..=end.y).step_by(step as usize) {
+                    d.draw_line(start.x, y, end.x, y, theme.background1);
+                }
+                for x in (start.x..=end.x).step_by(step as usize) {
+                    d.draw_line(x, start.y, x, end.y, theme.background1);
+                }
                 d.draw_line(start.x, 0, end.x, 0, theme.background2);
                 d.draw_line(0, start.y, 0, end.y, theme.background2);
             }
         }
     }
 
+    /// The zoom exponent of the node icon sheet matching the size nodes appear on screen, and
+    /// that sheet if nodes aren't too small for any.
+    fn node_icons<'a>(&self, theme: &'a Theme, grid_size: u8) -> (i32, Option<NodeIconLookup<'a>>) {
+        let icon_scale = f32::from(grid_size) / NodeIconSheetSetId::X8.icon_width() as f32;
+        let zoom_exp = (self.zoom_exp() + icon_scale.log2()).ceil() as i32;
+        let icons = NodeIconSheetSetId::from_zoom_exp(zoom_exp)
+            .map(|scale| NodeIconLookup::new(&theme.node_icons, scale));
+        (zoom_exp, icons)
+    }
+
+    /// The positions of the nodes at least partly inside `viewport`.
+    fn node_view(&self, viewport: &Bounds, grid_size: u8) -> IBounds {
+        let min = self.screen_to_world(viewport.min).as_ivec2();
+        let max = self.screen_to_world(viewport.max).as_ivec2();
+        IBounds::new(
+            IVec2::new(min.x - i32::from(grid_size), min.y - i32::from(grid_size)),
+            IVec2::new(max.x + 1, max.y + 1),
+        )
+    }
+
+    /// Redraws the static layer if the graph, the camera, `viewport` or whether `tool` shows node
+    /// backgrounds has changed since it was last drawn.
+    pub fn refresh_layer(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        theme: &Theme,
+        viewport: &Bounds,
+        tool: &Tool,
+    ) {
+        let Some(graph) = self.graph.upgrade() else {
+            return;
+        };
+        let Ok(graph) = graph.try_read() else {
+            // busy, try again next frame
+            return;
+        };
+        let key = LayerKey {
+            graph: *graph.id(),
+            revision: graph.revision(),
+            camera_target: self.camera_target,
+            zoom_exp: self.zoom_exp,
+            viewport: *viewport,
+            with_backgrounds: !matches!(tool, Tool::Interact { .. }),
+        };
+        if self.layer_key == Some(key) {
+            return;
+        }
+        self.layer_key = Some(key);
+
+        let grid_size = graph.grid_size();
+        let (zoom_exp, icons) = self.node_icons(theme, grid_size);
+        let is_far = zoom_exp < FAR_ZOOM_EXP;
+        let view = self.node_view(viewport, grid_size);
+        // the layer only covers the viewport, which isn't at the corner of the window
+        let camera = Camera2D {
+            offset: -viewport.min,
+            ..self.camera()
+        };
+        let pixel = camera.zoom.recip();
+        let wire_scale = theme.wire_scale();
+
+        let mut d = rl.begin_texture_mode(thread, &mut self.layer);
+        d.clear_background(Color::BLANK);
+        let mut d = d.begin_mode2D(camera);
+        // fading wires change color every tick, so they're all drawn every frame instead
+        for wire in graph.wires_iter().filter(|_| !theme.wire_fade) {
+            draw_wire(
+                &mut d,
+                &graph,
+                wire,
+                theme.foreground,
+                wire_scale,
+                pixel,
+                is_far,
+            )
+            .expect("all wires should be valid");
+        }
+        if key.with_backgrounds
+            && let Some(icons) = &icons
+        {
+            for node in graph.nodes_in_bounds(view).map(|id| {
+                graph
+                    .node(id)
+                    .expect("nodes_in_bounds should only give valid nodes")
+            }) {
+                let node_position = node.position().as_vec2();
+                let rec = Rectangle {
+                    x: node_position.x,
+                    y: node_position.y,
+                    width: grid_size.into(),
+                    height: grid_size.into(),
+                };
+                let (dest, origin, rotation) = icon_placement(rec, node.orientation());
+                d.draw_texture_pro(
+                    icons.background,
+                    icons.src_rec(node.gate().as_gate().id()),
+                    dest,
+                    origin,
+                    rotation,
+                    theme.background,
+                );
+            }
+        }
+    }
+
     #[inline]
     pub fn grid_tex(&self) -> &WeakTexture2D {
         self.grid.texture()
@@ -171,14 +1124,474 @@ impl EditorTab {
         unsafe { ffi::GetWorldToScreen2D(world_pos.into(), self.camera().into()) }.into()
     }
 
+    /// The position a node placed at `screen_pos` would snap to, or at wherever the mouse was
+    /// last seen over the tab if [`None`].
+    pub fn node_position_at(&self, screen_pos: Option<Vector2>) -> IVec2 {
+        let world_pos = self.screen_to_world(screen_pos.unwrap_or(self.last_mouse));
+        self.snap.node_position(world_pos, self.grid_size())
+    }
+
+    /// The part of a wire at `world_pos` that the edit tool would grab, if any.
+    ///
+    /// Near the elbow grabs the elbow; elsewhere grabs the nearer end.
+    fn wire_handle_at(graph: &Graph, world_pos: Vector2) -> Option<WireDragging> {
+        let grid_size = graph.grid_size();
+        let &id = graph.find_wire_at(world_pos, wire_tolerance(grid_size))?;
+        let [start_pos, elbow_pos, end_pos] = graph
+            .wire(&id)
+            .and_then(|wire| wire.points(graph, graph.cell_center()))
+            .expect("find_wire_at should never return an invalid wire");
+        let handle = if world_pos.distance_to(elbow_pos) <= f32::from(grid_size) * 0.5 {
+            WireHandle::Elbow
+        } else if world_pos.distance_to(start_pos) < world_pos.distance_to(end_pos) {
+            WireHandle::End(WireEnd::Src)
+        } else {
+            WireHandle::End(WireEnd::Dst)
+        };
+        Some(WireDragging { id, handle })
+    }
+
+    /// The items of the context menu for whatever is at `world_pos`.
+    fn context_menu_items(
+        &self,
+        graph: &Graph,
+        theme: &Theme,
+        toolpane: &ToolPane,
+        world_pos: Vector2,
+    ) -> Vec<MenuItem> {
+        if let Some(&id) = graph.find_node_at(world_pos.as_ivec2()) {
+            let mut items = vec![
+                MenuItem::action("Delete", MenuAction::DeleteNode(id)),
+                MenuItem::action("Copy", MenuAction::CopyNode(id)),
+                MenuItem::action(
+                    if self.watched.contains_key(&id) {
+                        "Unwatch"
+                    } else {
+                        "Watch"
+                    },
+                    MenuAction::WatchNode(id),
+                ),
+                MenuItem::action(
+                    if graph.is_probed(&id) {
+                        "Unprobe"
+                    } else {
+                        "Probe"
+                    },
+                    MenuAction::ProbeNode(id),
+                ),
+                MenuItem::action("Properties", MenuAction::InspectNode(id)),
+            ];
+            let gate = toolpane.gate.with_ntd(toolpane.ntd);
+            if graph
+                .node(&id)
+                .is_some_and(|node| node.gate().as_gate() != gate)
+            {
+                items.push(MenuItem::action(
+                    format!("Convert to {gate}"),
+                    MenuAction::ConvertNode(id),
+                ));
+            }
+            items
+        } else if let Some(&id) = graph.find_wire_at(world_pos, wire_tolerance(graph.grid_size())) {
+            vec![
+                MenuItem::action("Delete", MenuAction::DeleteWire(id)),
+                MenuItem::action("Reverse", MenuAction::ReverseWire(id)),
+                MenuItem::submenu(
+                    "Elbow",
+                    Elbow::ALL
+                        .into_iter()
+                        .map(|elbow| {
+                            let label = match elbow {
+                                Elbow::Horizontal => "Horizontal",
+                                Elbow::DiagonalStart => "Diagonal at start",
+                                Elbow::Vertical => "Vertical",
+                                Elbow::DiagonalEnd => "Diagonal at end",
+                                Elbow::Straight => "Straight",
+                                Elbow::Curve => "Curve",
+                            };
+                            MenuItem::action(label, MenuAction::SetElbow(id, elbow))
+                        })
+                        .collect(),
+                ),
+            ]
+        } else if let Some(id) = region_header_at(graph, theme, world_pos) {
+            vec![
+                MenuItem::action("Delete region", MenuAction::DeleteRegion(id)),
+                MenuItem::submenu(
+                    "Color",
+                    (0..10)
+                        .filter_map(|n| Ntd::try_from(n).ok())
+                        .map(|color| {
+                            MenuItem::action(
+                                color.to_string(),
+                                MenuAction::SetRegionColor(id, color),
+                            )
+                        })
+                        .collect(),
+                ),
+            ]
+        } else {
+            let mut items = vec![
+                MenuItem::action("Paste", MenuAction::Paste),
+                MenuItem::submenu(
+                    "Place gate",
+                    GateId::ALL
+                        .into_iter()
+                        .map(|gate_id| {
+                            MenuItem::action(
+                                toolpane.gate_of(gate_id).to_string(),
+                                MenuAction::PlaceGate(
+                                    gate_id,
+                                    self.snap.node_position(world_pos, graph.grid_size()),
+                                ),
+                            )
+                        })
+                        .collect(),
+                ),
+                MenuItem::action("Select all", MenuAction::SelectAll),
+            ];
+            if !self.selection.is_empty() {
+                items.push(MenuItem::action(
+                    "Group selection",
+                    MenuAction::GroupSelection,
+                ));
+                items.push(MenuItem::action(
+                    "Probe selection",
+                    MenuAction::ProbeSelection,
+                ));
+            }
+            if let Tool::Erase { sweep: Some(sweep) } = &toolpane.tool
+                && sweep.graph.ptr_eq(&self.graph)
+                && !sweep.erased.is_empty()
+            {
+                items.push(MenuItem::action(
+                    "Put back last erase",
+                    MenuAction::RestoreErased,
+                ));
+            }
+            if !self.replaced_gates.is_empty() {
+                items.push(MenuItem::action(
+                    "Put back replaced gates",
+                    MenuAction::RestoreGates,
+                ));
+            }
+            items
+        }
+    }
+
+    /// Carries out a context menu action, returning whether the graph changed.
+    ///
+    /// Clipboard actions need the window, so they are handed back through `clipboard_action`.
+    fn perform_menu_action(
+        &mut self,
+        action: MenuAction,
+        graph: &mut Graph,
+        console: &mut Console,
+        toolpane: &mut ToolPane,
+        clipboard_action: &mut Option<(ClipboardAction, Vector2)>,
+        menu_position: Vector2,
+    ) -> bool {
+        let graph_ref = GraphRef(*graph.id());
+        match action {
+            MenuAction::DeleteNode(id) => {
+                self.selection.remove(&id);
+                graph.destroy_node(&id, false, console).is_some()
+            }
+            MenuAction::CopyNode(id) => {
+                if !self.selection.contains(&id) {
+                    self.selection = FxHashSet::from_iter([id]);
+                }
+                *clipboard_action = Some((ClipboardAction::Copy, menu_position));
+                false
+            }
+            MenuAction::WatchNode(id) => {
+                self.toggle_watch(graph, id, console);
+                false
+            }
+            MenuAction::ProbeNode(id) => {
+                // probes are not saved, so this does not modify the graph
+                if let Some(is_probed) = graph.toggle_probe(&id) {
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "{} {}; export with: trace export <path>",
+                        if is_probed {
+                            "probing"
+                        } else {
+                            "stopped probing"
+                        },
+                        graph_ref.node(id)
+                    );
+                }
+                false
+            }
+            MenuAction::ProbeSelection => {
+                let mut ids = self
+                    .selection
+                    .iter()
+                    .copied()
+                    .filter(|id| !graph.is_probed(id))
+                    .collect::<Vec<_>>();
+                // probed in reading order, which is the order they are listed in the trace
+                ids.sort_unstable_by_key(|id| {
+                    graph
+                        .node(id)
+                        .map(|node| (node.position().y, node.position().x))
+                });
+                for id in &ids {
+                    graph.toggle_probe(id);
+                }
+                logln!(
+                    console,
+                    LogType::Info,
+                    "probing {} more nodes of graph {graph_ref}; export with: trace export <path>",
+                    ids.len()
+                );
+                false
+            }
+            MenuAction::InspectNode(id) => {
+                self.inspected = Some(id);
+                false
+            }
+            MenuAction::ConvertNode(id) => {
+                let replaced =
+                    graph.replace_nodes_gate([&id], toolpane.gate.with_ntd(toolpane.ntd), console);
+                let is_replaced = !replaced.is_empty();
+                if is_replaced {
+                    self.replaced_gates = replaced;
+                }
+                is_replaced
+            }
+            MenuAction::DeleteWire(id) => {
+                let is_destroyed = graph.destroy_wire(&id).is_some();
+                if is_destroyed {
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "destroy wire {}",
+                        graph_ref.wire(id)
+                    );
+                }
+                is_destroyed
+            }
+            MenuAction::ReverseWire(id) => {
+                graph.wire(&id).is_some() && graph.reverse_wire(&id, console).is_ok()
+            }
+            MenuAction::SetElbow(id, elbow) => {
+                if let Some(wire) = graph.wire_mut(&id)
+                    && (wire.elbow != elbow || wire.route().is_some())
+                {
+                    wire.set_elbow(elbow);
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "reshape wire {}",
+                        graph_ref.wire(id)
+                    );
+                    self.is_modified = true;
+                }
+                false
+            }
+            MenuAction::Paste => {
+                *clipboard_action = Some((ClipboardAction::Paste, menu_position));
+                false
+            }
+            MenuAction::PlaceGate(gate_id, pos) => graph
+                .create_node(toolpane.gate_of(gate_id), pos, console)
+                .is_ok(),
+            MenuAction::SetZoom(zoom_exp) => {
+                self.set_zoom_exp(zoom_exp.into());
+                false
+            }
+            MenuAction::SelectAll => {
+                self.selection = graph.nodes_iter().map(|node| *node.id()).collect();
+                logln!(
+                    console,
+                    LogType::Info,
+                    "selected all {} nodes of graph {graph_ref}",
+                    self.selection.len(),
+                );
+                false
+            }
+            MenuAction::GroupSelection => {
+                let grid_size = i32::from(graph.grid_size());
+                let mut positions = self
+                    .selection
+                    .iter()
+                    .filter_map(|id| graph.node(id))
+                    .map(|node| node.position());
+                if let Some(first) = positions.next() {
+                    let (min, max) = positions.fold((first, first), |(min, max), p| {
+                        (
+                            IVec2::new(min.x.min(p.x), min.y.min(p.y)),
+                            IVec2::new(max.x.max(p.x), max.y.max(p.y)),
+                        )
+                    });
+                    // a cell of margin, and room for the label above the nodes
+                    let id = *graph
+                        .create_region(
+                            IBounds::new(
+                                IVec2::new(min.x - grid_size, min.y - 2 * grid_size),
+                                IVec2::new(max.x + 2 * grid_size, max.y + 2 * grid_size),
+                            ),
+                            "group".to_string(),
+                            Ntd::default(),
+                            console,
+                        )
+                        .id();
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "rename it with: region label {id} \"name\"",
+                    );
+                    self.is_modified = true;
+                }
+                false
+            }
+            MenuAction::DeleteRegion(id) => {
+                if graph.destroy_region(&id).is_some() {
+                    logln!(console, LogType::Info, "destroy region {id}");
+                    self.is_modified = true;
+                }
+                false
+            }
+            MenuAction::SetRegionColor(id, color) => {
+                if let Some(region) = graph.region_mut(&id)
+                    && region.color != color
+                {
+                    region.color = color;
+                    self.is_modified = true;
+                }
+                false
+            }
+            MenuAction::RestoreErased => {
+                if let Tool::Erase { sweep } = &mut toolpane.tool
+                    && let Some(sweep) = sweep.take_if(|sweep| sweep.graph.ptr_eq(&self.graph))
+                {
+                    let (nodes, wires) = graph.restore(sweep.erased, console);
+                    nodes + wires > 0
+                } else {
+                    false
+                }
+            }
+            MenuAction::RestoreGates => !graph
+                .replace_gates(std::mem::take(&mut self.replaced_gates), console)
+                .is_empty(),
+        }
+    }
+
+    /// Whether the cursor is over this tab's context menu, which may reach outside the tab.
+    pub fn is_context_menu_hovered(&self, theme: &Theme, cursor: Vector2) -> bool {
+        self.context_menu
+            .as_ref()
+            .is_some_and(|menu| menu.contains(cursor, theme))
+    }
+
+    /// The node to show in the properties panel: the one being dragged by the edit tool,
+    /// otherwise the one last clicked with it or picked from the context menu.
+    pub fn inspected_node(&self, tool: &Tool) -> Option<NodeId> {
+        match tool {
+            Tool::Edit {
+                target: Some(EditDragging { id, .. }),
+                ..
+            } => Some(*id),
+            _ => self.inspected,
+        }
+    }
+
+    #[inline]
+    pub fn is_watched(&self, id: &NodeId) -> bool {
+        self.watched.contains_key(id)
+    }
+
+    /// The watched nodes, in no particular order.
+    #[inline]
+    pub fn watched(&self) -> impl ExactSizeIterator<Item = (&NodeId, &Watched)> {
+        self.watched.iter()
+    }
+
+    /// Starts watching `id` in `graph`, or stops if it is already watched.
+    pub fn toggle_watch(&mut self, graph: &Graph, id: NodeId, console: &mut Console) {
+        let graph_ref = GraphRef(*graph.id());
+        if self.watched.remove(&id).is_some() {
+            logln!(
+                console,
+                LogType::Info,
+                "stopped watching {}",
+                graph_ref.node(id)
+            );
+        } else if let Some(node) = graph.node(&id) {
+            self.watched.insert(
+                id,
+                Watched {
+                    logged_state: node.state(),
+                    ticks: VecDeque::with_capacity(WAVEFORM_TICKS),
+                },
+            );
+            logln!(
+                console,
+                LogType::Info,
+                "watching {}, currently {}",
+                graph_ref.node(id),
+                if node.state() { "on" } else { "off" },
+            );
+        }
+    }
+
+    /// Adds the state of every watched node to its waveform, after `graph` (the graph of this
+    /// tab) has been evaluated.
+    pub fn sample_watched(&mut self, graph: &Graph) {
+        for (id, watched) in &mut self.watched {
+            if let Some(node) = graph.node(id) {
+                if watched.ticks.len() == WAVEFORM_TICKS {
+                    watched.ticks.pop_front();
+                }
+                watched.ticks.push_back(node.state());
+            }
+        }
+    }
+
+    /// Logs the watched nodes whose states changed since they were last logged.
+    pub fn log_watched(&mut self, console: &mut Console) {
+        if self.watched.is_empty() {
+            return;
+        }
+        let Some(graph) = self.graph.upgrade() else {
+            return;
+        };
+        let Ok(graph) = graph.try_read() else {
+            return;
+        };
+        let graph_ref = GraphRef(*graph.id());
+        self.watched.retain(|id, watched| {
+            let Some(node) = graph.node(id) else {
+                return false;
+            };
+            if node.state() != watched.logged_state {
+                watched.logged_state = node.state();
+                logln!(
+                    console,
+                    LogType::Info,
+                    "{} turned {}",
+                    graph_ref.node(*id),
+                    if node.state() { "on" } else { "off" },
+                );
+            }
+            true
+        });
+    }
+
     pub fn tick(
         &mut self,
         console: &mut Console,
         toolpane: &mut ToolPane,
-        _theme: &Theme,
+        theme: &Theme,
         input: &Inputs,
+        clipboard_action: &mut Option<(ClipboardAction, Vector2)>,
     ) -> bool {
         let mut is_dirty = false;
+        // buttons are evaluated right away too, but are never saved
+        let mut is_pressed = false;
 
         if let Some(gate) = input.gate() {
             toolpane.set_gate(gate, console);
@@ -188,120 +1601,725 @@ impl EditorTab {
         }
 
         self.zoom_and_pan(input.cursor, input.pan, input.zoom, 5.0);
+        if input.actual_size.is_starting() {
+            self.set_zoom_exp(0.0);
+        }
+        if input.zoom_in_preset.is_starting()
+            && let Some(&zoom_exp) = ZOOM_PRESETS
+                .iter()
+                .find(|&&zoom_exp| f32::from(zoom_exp) > self.target_zoom_exp())
+        {
+            self.set_zoom_exp(zoom_exp.into());
+        }
+        if input.zoom_out_preset.is_starting()
+            && let Some(&zoom_exp) = ZOOM_PRESETS
+                .iter()
+                .rfind(|&&zoom_exp| f32::from(zoom_exp) < self.target_zoom_exp())
+        {
+            self.set_zoom_exp(zoom_exp.into());
+        }
+        if let Some((action, slot)) = input.bookmark() {
+            self.use_bookmark(action, slot, console);
+        }
 
         // `try_write`: if graph is being borrowed, don't edit it! it might be saving!
         if let Some(graph) = self.graph.upgrade()
             && let Ok(mut graph) = graph.try_write()
         {
-            let pos = self
-                .screen_to_world(input.cursor)
-                .as_ivec2()
-                .snap(GRID_SIZE.into());
-
-            match &mut toolpane.tool {
-                Tool::Create { current_node } => {
-                    if input.primary.is_starting() {
-                        if let Some(&id) = graph.find_node_at(pos) {
-                            // existing node
-                            if let Some(current_node) = *current_node
-                                && current_node != id
-                            {
-                                _ = graph.create_wire(toolpane.elbow, current_node, id, console);
-                            }
-                            *current_node = Some(id);
-                        } else {
-                            // new node
-                            let gate = toolpane.gate.with_ntd(toolpane.ntd);
-                            let new_node = graph
-                                .create_node(gate, pos, console)
-                                .expect("this branch implies the position is available");
-                            let new_node_id = *new_node.id();
-                            if let Some(current_node) = current_node.as_ref() {
-                                _ = graph.create_wire(
-                                    toolpane.elbow,
-                                    *current_node,
-                                    new_node_id,
-                                    console,
-                                );
-                            }
-                            *current_node = Some(new_node_id);
-                        }
-                        is_dirty = true;
+            let grid_size = graph.grid_size();
+            let cell_center = graph.cell_center();
+            let world_pos = self.screen_to_world(input.cursor);
+            let pos = world_pos.as_ivec2();
+
+            if input.toggle_eval_mode.is_starting() && self.check_unlocked(console) {
+                let mode = graph.eval_mode().toggled();
+                graph.set_eval_mode(mode);
+                logln!(
+                    console,
+                    LogType::Info,
+                    "graph {} now evaluates {mode}ly",
+                    GraphRef(*graph.id()),
+                );
+                self.is_modified = true;
+            }
+
+            if input.toggle_camera_sync.is_starting() {
+                self.camera_sync = !self.camera_sync;
+                logln!(
+                    console,
+                    LogType::Info,
+                    "camera sync {} for this view of graph {}",
+                    if self.camera_sync { "on" } else { "off" },
+                    GraphRef(*graph.id()),
+                );
+            }
+
+            if input.toggle_lock.is_starting() {
+                self.is_locked = !self.is_locked;
+                if self.is_locked && !matches!(toolpane.tool, Tool::Interact { .. }) {
+                    // a drag cut short by the lock would otherwise finish once unlocked
+                    toolpane.tool = toolpane.tool.id().init();
+                }
+                logln!(
+                    console,
+                    LogType::Info,
+                    "{} graph {} in this tab",
+                    if self.is_locked { "locked" } else { "unlocked" },
+                    GraphRef(*graph.id()),
+                );
+            }
+
+            if input.toggle_eval_order_overlay.is_starting() {
+                self.eval_order_overlay = match self.eval_order_overlay {
+                    Some(_) => None,
+                    None => Some(Instant::now()),
+                };
+                logln!(
+                    console,
+                    LogType::Info,
+                    "eval order overlay {} for this view of graph {}",
+                    if self.eval_order_overlay.is_some() {
+                        "on"
+                    } else {
+                        "off"
+                    },
+                    GraphRef(*graph.id()),
+                );
+            }
+
+            if input.toggle_recording.is_starting() {
+                let recording = !graph.is_recording();
+                graph.set_recording(recording);
+                logln!(
+                    console,
+                    LogType::Info,
+                    "{} graph {}",
+                    if recording {
+                        "recording"
+                    } else {
+                        "stopped recording"
+                    },
+                    GraphRef(*graph.id()),
+                );
+            }
+
+            if let Some(history) = graph.history_mut() {
+                if input.replay_back.is_starting() {
+                    history.step(-1);
+                }
+                if input.replay_forward.is_starting() {
+                    history.step(1);
+                }
+            }
+
+            if input.next_island.is_starting() {
+                let islands = graph.islands();
+                let graph_ref = GraphRef(*graph.id());
+                if islands.is_empty() {
+                    logln!(console, LogType::Info, "graph {graph_ref} is empty");
+                } else {
+                    if self.island_cursor >= islands.len() {
+                        self.island_cursor = 0;
                     }
-                    if input.secondary.is_starting() {
-                        *current_node = None;
+                    if self.island_cursor == 0 {
+                        logln!(
+                            console,
+                            LogType::Info,
+                            "graph {graph_ref} has {} islands:",
+                            islands.len()
+                        );
+                        for island in &islands {
+                            logln!(
+                                console,
+                                LogType::Info,
+                                "  {} nodes at {}",
+                                island.nodes.len(),
+                                graph_ref.node(island.representative),
+                            );
+                        }
                     }
+                    let island = &islands[self.island_cursor];
+                    let IBounds { min, max } = island.bounds;
+                    self.animate_center_on(
+                        (min.as_vec2() + max.as_vec2() + rvec2(grid_size, grid_size)) * 0.5,
+                    );
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "island {}/{}: {}",
+                        self.island_cursor + 1,
+                        islands.len(),
+                        graph_ref.node(island.representative),
+                    );
+                    self.island_cursor += 1;
                 }
+            }
 
-                Tool::Erase {} => {
-                    if input.primary.is_starting()
-                        && let Some(&id) = graph.find_node_at(pos)
-                    {
-                        graph
-                            .destroy_node(&id, false, console)
-                            .expect("cannot reach this branch if graph did not contain the node");
-                        is_dirty = true;
+            if input.cursor != self.last_mouse {
+                self.key_cursor = None;
+                self.last_mouse = input.cursor;
+            }
+            if let Some(step) = input.key_cursor_step() {
+                let cell = match self.key_cursor {
+                    Some(KeyCursor { cell, .. }) => IVec2::new(
+                        cell.x + step.x * i32::from(grid_size),
+                        cell.y + step.y * i32::from(grid_size),
+                    ),
+                    // starts under the mouse
+                    None => self.snap.node_position(world_pos, grid_size),
+                };
+                self.key_cursor = Some(KeyCursor { cell, cycle: None });
+                self.reveal_cell(cell, grid_size);
+            }
+            if input.cycle_nodes.is_starting()
+                && let Some(cursor) = &mut self.key_cursor
+            {
+                let (origin, count) = cursor.cycle.unwrap_or((cursor.cell, 0));
+                let reach = KEY_CYCLE_RADIUS * i32::from(grid_size);
+                let mut nearby = graph
+                    .nodes_in_bounds(IBounds::new(
+                        IVec2::new(origin.x - reach, origin.y - reach),
+                        IVec2::new(origin.x + reach, origin.y + reach),
+                    ))
+                    .map(|id| {
+                        let position = graph
+                            .node(id)
+                            .expect("nodes_in_bounds should only give valid nodes")
+                            .position();
+                        (*id, position)
+                    })
+                    .collect::<Vec<_>>();
+                // nearest first, then in reading order
+                nearby.sort_unstable_by_key(|&(_, position)| {
+                    let (dx, dy) = (position.x - origin.x, position.y - origin.y);
+                    (dx * dx + dy * dy, position.y, position.x)
+                });
+                if nearby.is_empty() {
+                    logln!(console, LogType::Info, "no nodes near the key cursor");
+                } else {
+                    let n = count % nearby.len();
+                    let (id, position) = nearby[n];
+                    cursor.cell = position;
+                    cursor.cycle = Some((origin, n + 1));
+                    self.selection = FxHashSet::from_iter([id]);
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "picked {} ({}/{})",
+                        GraphRef(*graph.id()).node(id),
+                        n + 1,
+                        nearby.len(),
+                    );
+                    self.reveal_cell(position, grid_size);
+                }
+            }
+            // the key cursor places nodes as a click in the middle of its cell would
+            let key_click = self
+                .key_cursor
+                .filter(|_| input.place_node.is_starting())
+                .map(|cursor| cursor.cell.as_vec2() + cell_center);
+
+            if input.paste_blueprint.is_starting()
+                && let Some(blueprint) = toolpane.blueprint.as_ref()
+                && self.check_unlocked(console)
+            {
+                self.selection = blueprint
+                    .paste(&mut graph, pos, console)
+                    .into_iter()
+                    .collect();
+                is_dirty = true;
+            }
+
+            if input.stamp.is_starting() && self.check_unlocked(console) {
+                if self.selection.is_empty() {
+                    logln!(console, LogType::Info, "select nodes to stamp first");
+                } else {
+                    let model = GraphModel::from_selection(&graph, &self.selection);
+                    logln!(
+                        console,
+                        LogType::Info,
+                        "stamping {} nodes, right click to stop",
+                        model.nodes.len()
+                    );
+                    toolpane.tool = Tool::Create {
+                        current_node: None,
+                        stamp: Some(model),
+                    };
+                }
+            }
+
+            // while the context menu is open, it takes the clicks that would go to the tool
+            let was_menu_open = self.context_menu.is_some();
+            if let Some(menu) = &mut self.context_menu {
+                match menu.tick(theme, input) {
+                    MenuResponse::Idle => {}
+                    MenuResponse::Dismiss => self.context_menu = None,
+                    MenuResponse::Chosen(action) if action.is_edit() && self.is_locked => {
+                        self.context_menu = None;
+                        self.check_unlocked(console);
+                    }
+                    MenuResponse::Chosen(action) => {
+                        let menu_position = menu.position();
+                        self.context_menu = None;
+                        is_dirty |= self.perform_menu_action(
+                            action,
+                            &mut graph,
+                            console,
+                            toolpane,
+                            clipboard_action,
+                            menu_position,
+                        );
                     }
                 }
+            }
+            let is_dragging = toolpane.tool.is_dragging();
+            // the edit tool takes alternate secondary clicks on nodes to replace their gates
+            let is_replacing = matches!(toolpane.tool, Tool::Edit { .. })
+                && !self.is_locked
+                && input.alternate.is_active()
+                && graph.find_node_at(pos).is_some();
+            if input.secondary.is_starting()
+                && !is_dragging
+                && !is_replacing
+                && !self.is_context_menu_hovered(theme, input.cursor)
+            {
+                let items = self.context_menu_items(&graph, theme, toolpane, world_pos);
+                self.context_menu = Some(ContextMenu::new(input.cursor, items));
+            }
 
-                Tool::Edit { target } => {
-                    if input.secondary.is_starting()
-                        && let Some(&id) = graph.find_node_at(pos)
-                    {
-                        *graph
-                            .node_mut(&id)
-                            .expect("hovered node should be valid")
-                            .gate_mut() = GateInstance::from_gate(toolpane.gate);
+            if !was_menu_open && self.is_locked && !matches!(toolpane.tool, Tool::Interact { .. }) {
+                // hovering still shows tooltips, but nothing else the tool does is allowed
+                if input.primary.is_starting()
+                    || input.rotate_node.is_starting()
+                    || key_click.is_some()
+                {
+                    self.check_unlocked(console);
+                }
+            } else if !was_menu_open {
+                match &mut toolpane.tool {
+                    Tool::Create {
+                        current_node,
+                        stamp,
+                    } => {
+                        let click = if input.primary.is_starting() {
+                            Some(world_pos)
+                        } else {
+                            key_click
+                        };
+                        if let Some(world_pos) = click
+                            && let Some(stamp) = stamp
+                        {
+                            // the stamp's top-left corner goes in the clicked cell
+                            self.selection = stamp
+                                .paste(
+                                    &mut graph,
+                                    self.snap.node_position(world_pos, grid_size),
+                                    console,
+                                )
+                                .into_iter()
+                                .collect();
+                            is_dirty = true;
+                        } else if let Some(world_pos) = click {
+                            let pos = world_pos.as_ivec2();
+                            if let Some(&id) = graph.find_node_at(pos) {
+                                // existing node
+                                if let Some(current_node) = *current_node
+                                    && current_node != id
+                                {
+                                    create_tool_wire(
+                                        &mut graph,
+                                        toolpane.elbow,
+                                        toolpane.auto_route,
+                                        current_node,
+                                        id,
+                                        console,
+                                    );
+                                }
+                                *current_node = Some(id);
+                            } else if let Ok(new_node) = graph.create_node(
+                                toolpane.gate.with_ntd(toolpane.ntd),
+                                self.snap.node_position(world_pos, grid_size),
+                                console,
+                            ) {
+                                // new node, unless it would share a cell with one off the grid
+                                let new_node_id = *new_node.id();
+                                if let Some(current_node) = current_node.as_ref() {
+                                    create_tool_wire(
+                                        &mut graph,
+                                        toolpane.elbow,
+                                        toolpane.auto_route,
+                                        *current_node,
+                                        new_node_id,
+                                        console,
+                                    );
+                                }
+                                *current_node = Some(new_node_id);
+                            }
+                            is_dirty = true;
+                        }
+                        if input.secondary.is_starting() {
+                            *current_node = None;
+                            *stamp = None;
+                        }
+                        if input.cycle_elbow.is_starting() {
+                            toolpane.elbow = toolpane.elbow.next();
+                            logln!(console, LogType::Info, "wire elbow: {}", toolpane.elbow);
+                        }
+                        if input.toggle_auto_route.is_starting() {
+                            toolpane.auto_route = !toolpane.auto_route;
+                            logln!(
+                                console,
+                                LogType::Info,
+                                "wire auto-routing {}",
+                                if toolpane.auto_route { "on" } else { "off" }
+                            );
+                        }
                     }
 
-                    if input.primary.is_starting()
-                        && let Some(&id) = graph.find_node_at(pos)
-                    {
-                        *target = Some(EditDragging {
-                            temp_pos: Vector2::default(),
-                            id,
-                        });
+                    Tool::Erase { sweep } => {
+                        if input.primary.is_starting() {
+                            *sweep = Some(EraseSweep {
+                                graph: self.graph.clone(),
+                                erased: Erased::default(),
+                                count: (0, 0),
+                                last_pos: Some(world_pos),
+                            });
+                        }
+                        // holding primary erases every node and wire swept over, while
+                        // annotations and regions are only erased by clicking them
+                        if let Some(EraseSweep {
+                            erased,
+                            count: (nodes, wires),
+                            last_pos: Some(last_pos),
+                            ..
+                        }) = sweep
+                            && input.primary.is_active()
+                        {
+                            let count = (*nodes, *wires);
+                            // keeps chains through the node connected
+                            let soft = input.alternate.is_active();
+                            // the whole way from last frame, which a fast drag may have crossed
+                            // many cells of
+                            for point in sweep_points(*last_pos, world_pos, grid_size) {
+                                if let Some(&id) = graph.find_node_at(point.as_ivec2()) {
+                                    graph.erase_node(&id, soft, erased, console);
+                                    self.selection.remove(&id);
+                                    *nodes += 1;
+                                } else if let Some(&id) =
+                                    graph.find_wire_at(point, wire_tolerance(grid_size))
+                                {
+                                    graph.erase_wire(&id, erased);
+                                    logln!(
+                                        console,
+                                        LogType::Info,
+                                        "destroy wire {}",
+                                        GraphRef(*graph.id()).wire(id)
+                                    );
+                                    *wires += 1;
+                                }
+                            }
+                            *last_pos = world_pos;
+                            if (*nodes, *wires) != count {
+                                is_dirty = true;
+                            } else if input.primary.is_starting()
+                                && let Some(id) = annotation_at(&graph, theme, world_pos)
+                            {
+                                graph.destroy_annotation(&id).expect(
+                                    "cannot reach this branch if graph did not contain the annotation",
+                                );
+                                logln!(console, LogType::Info, "destroy annotation {id}");
+                                self.is_modified = true;
+                            } else if input.primary.is_starting()
+                                && let Some(id) = region_header_at(&graph, theme, world_pos)
+                            {
+                                graph.destroy_region(&id).expect(
+                                    "cannot reach this branch if graph did not contain the region",
+                                );
+                                logln!(console, LogType::Info, "destroy region {id}");
+                                self.is_modified = true;
+                            }
+                        } else if let Some(EraseSweep {
+                            count: (nodes, wires),
+                            last_pos,
+                            ..
+                        }) = sweep
+                            && last_pos.take().is_some()
+                            && *nodes + *wires > 1
+                        {
+                            // the whole drag is reported as one change
+                            logln!(
+                                console,
+                                LogType::Info,
+                                "erase {nodes} nodes and {wires} wires in one sweep; the context \
+                                 menu can put them back"
+                            );
+                        }
                     }
-                    if input.primary.is_ending()
-                        && let Some(EditDragging { temp_pos: _, id }) = target.take()
-                    {
-                        let new_position = self
-                            .screen_to_world(input.cursor)
-                            .as_ivec2()
-                            .snap(GRID_SIZE.into());
-                        graph
-                            .translate_node(&id, new_position, console)
-                            .expect("edit mode target node should be valid");
+
+                    Tool::Edit {
+                        target,
+                        wire_target,
+                        region_target,
+                        marquee,
+                    } => {
+                        if input.rotate_node.is_starting()
+                            && let Some(&id) = graph.find_node_at(pos)
+                        {
+                            // rotating a selected node rotates the whole selection
+                            let targets = if self.selection.contains(&id) {
+                                self.selection.iter().copied().collect()
+                            } else {
+                                vec![id]
+                            };
+                            for id in targets {
+                                if let Some(node) = graph.node_mut(&id) {
+                                    node.set_orientation(node.orientation().rotated_cw());
+                                }
+                            }
+                            self.is_modified = true;
+                        }
+
+                        if input.secondary.is_starting()
+                            && input.alternate.is_active()
+                            && let Some(&id) = graph.find_node_at(pos)
+                        {
+                            // like rotating, replacing a selected node's gate replaces the
+                            // whole selection's
+                            let targets = if self.selection.contains(&id) {
+                                self.selection.iter().copied().collect()
+                            } else {
+                                vec![id]
+                            };
+                            let replaced = graph.replace_nodes_gate(
+                                &targets,
+                                toolpane.gate.with_ntd(toolpane.ntd),
+                                console,
+                            );
+                            if !replaced.is_empty() {
+                                self.replaced_gates = replaced;
+                                is_dirty = true;
+                            }
+                        }
+
+                        if input.primary.is_starting() && input.alternate.is_active() {
+                            // toggle selection instead of dragging
+                            if let Some(&id) = graph.find_node_at(pos) {
+                                if !self.selection.remove(&id) {
+                                    self.selection.insert(id);
+                                }
+                            } else {
+                                self.selection.clear();
+                                *marquee = Some(world_pos);
+                            }
+                        } else if input.primary.is_starting() {
+                            if let Some(&id) = graph.find_node_at(pos) {
+                                *target = Some(EditDragging {
+                                    temp_pos: Vector2::default(),
+                                    id,
+                                });
+                                // stays in the properties panel after the drag, to be edited there
+                                self.inspected = Some(id);
+                            } else {
+                                *wire_target = Self::wire_handle_at(&graph, world_pos);
+                                if wire_target.is_none()
+                                    && let Some(id) = region_header_at(&graph, theme, world_pos)
+                                    && let Some(region) = graph.region(&id)
+                                {
+                                    *region_target = Some(RegionDragging {
+                                        id,
+                                        grab_offset: world_pos - region.bounds().min.as_vec2(),
+                                    });
+                                }
+                            }
+                        }
+                        if input.primary.is_ending()
+                            && let Some(RegionDragging { id, grab_offset }) = region_target.take()
+                        {
+                            let corner = (world_pos - grab_offset) / f32::from(grid_size);
+                            let new_position = IVec2::new(
+                                corner.x.round() as i32 * i32::from(grid_size),
+                                corner.y.round() as i32 * i32::from(grid_size),
+                            );
+                            graph
+                                .translate_region(&id, new_position, console)
+                                .expect("edit mode target region should be valid");
+                            self.is_modified = true;
+                        }
+                        if input.primary.is_ending()
+                            && let Some(WireDragging { id, handle }) = wire_target.take()
+                            && let Some(wire) = graph.wire(&id)
+                        {
+                            let wire_ref = GraphRef(*graph.id()).wire(id);
+                            match handle {
+                                WireHandle::Elbow => {
+                                    let [start_pos, _, end_pos] = wire
+                                        .points(&graph, cell_center)
+                                        .expect("all wires should be valid");
+                                    let elbow = Elbow::nearest(start_pos, end_pos, world_pos);
+                                    if elbow != wire.elbow || wire.route().is_some() {
+                                        graph
+                                            .wire_mut(&id)
+                                            .expect("wire should be valid")
+                                            .set_elbow(elbow);
+                                        logln!(console, LogType::Info, "reshape wire {wire_ref}");
+                                        self.is_modified = true;
+                                    }
+                                }
+                                WireHandle::End(end) => {
+                                    let other_end = match end {
+                                        WireEnd::Src => *wire.dst(),
+                                        WireEnd::Dst => *wire.src(),
+                                    };
+                                    match graph.find_node_at(pos).copied() {
+                                        Some(node) if node == other_end => {
+                                            logln!(
+                                                console,
+                                                LogType::Warning,
+                                                "cannot wire a node to itself"
+                                            );
+                                        }
+                                        Some(node) => {
+                                            is_dirty |= graph
+                                                .reattach_wire(&id, end, node, console)
+                                                .is_ok();
+                                        }
+                                        None => {}
+                                    }
+                                }
+                            }
+                        }
+                        if input.primary.is_ending()
+                            && let Some(EditDragging { temp_pos: _, id }) = target.take()
+                        {
+                            let new_position = self.snap.node_position(world_pos, grid_size);
+                            graph
+                                .translate_node(&id, new_position, console)
+                                .expect("edit mode target node should be valid");
+                            self.is_modified = true;
+                        }
+
+                        if let Some(EditDragging { temp_pos, id: _ }) = target.as_mut() {
+                            *temp_pos = self.screen_to_world(input.cursor) - cell_center;
+                        }
+
+                        if let Some(start) = *marquee {
+                            self.selection = graph
+                                .nodes_in_bounds(marquee_bounds(start, world_pos, cell_center))
+                                .copied()
+                                .collect();
+                            if !input.primary.is_active() {
+                                *marquee = None;
+                            }
+                        }
                     }
 
-                    if let Some(EditDragging { temp_pos, id: _ }) = target.as_mut() {
-                        *temp_pos = self.screen_to_world(input.cursor)
-                            - rvec2(GRID_SIZE / 2, GRID_SIZE / 2);
+                    Tool::Annotate {
+                        target,
+                        grab_offset,
+                    } => {
+                        if input.primary.is_starting() {
+                            if let Some(id) = annotation_at(&graph, theme, world_pos) {
+                                let position = graph
+                                    .annotation(&id)
+                                    .expect("annotation_at should only give valid annotations")
+                                    .position();
+                                *grab_offset = Some(world_pos - position.as_vec2());
+                                *target = Some(id);
+                            } else {
+                                let position = self.snap.node_position(world_pos, grid_size);
+                                let id = *graph
+                                    .create_annotation(
+                                        position,
+                                        DEFAULT_ANNOTATION_TEXT.to_string(),
+                                        console,
+                                    )
+                                    .id();
+                                *target = Some(id);
+                                self.is_modified = true;
+                            }
+                        }
+                        if input.primary.is_ending()
+                            && let Some(offset) = grab_offset.take()
+                            && let Some(id) = *target
+                        {
+                            let corner = (world_pos - offset) / f32::from(grid_size);
+                            let new_position = IVec2::new(
+                                corner.x.round() as i32 * i32::from(grid_size),
+                                corner.y.round() as i32 * i32::from(grid_size),
+                            );
+                            if graph.annotation(&id).map(Annotation::position) != Some(new_position)
+                            {
+                                graph.translate_annotation(&id, new_position, console);
+                                self.is_modified = true;
+                            }
+                        }
+                    }
+
+                    Tool::Interact { held } => {
+                        if input.primary.is_starting()
+                            && let Some(&id) = graph.find_node_at(pos)
+                            && graph
+                                .node(&id)
+                                .is_some_and(|node| node.gate().is_interactive())
+                        {
+                            let node = graph.node_mut(&id).expect("all nodes should be valid");
+                            match node.gate_mut() {
+                                GateInstance::Switch { on } => {
+                                    *on = !*on;
+                                    is_dirty = true;
+                                }
+                                GateInstance::Button { pressed } => {
+                                    *pressed = true;
+                                    // let go by the main loop, since the cursor may leave the tab
+                                    *held = Some((self.graph.clone(), id));
+                                    is_pressed = true;
+                                }
+                                _ => {}
+                            };
+                        }
                     }
                 }
+            }
+        }
+        self.is_modified |= is_dirty;
+        is_dirty || is_pressed
+    }
+
+    /// Draws details of the node under the cursor beside it, unless the cursor is busy with a
+    /// menu or a drag.
+    pub fn draw_node_tooltip<D: RaylibDraw>(
+        &self,
+        d: &mut D,
+        bounds: &Bounds,
+        theme: &Theme,
+        input: &Inputs,
+        toolpane: &ToolPane,
+    ) {
+        if self.context_menu.is_some()
+            || toolpane.tool.is_dragging()
+            || !bounds.contains(input.cursor)
+        {
+            return;
+        }
+        let Some(graph) = self.graph.upgrade() else {
+            return;
+        };
+        let Ok(graph) = graph.try_read() else {
+            return;
+        };
+        let pos = self.screen_to_world(input.cursor).as_ivec2();
+        if let Some(id) = graph.find_node_at(pos)
+            && let Some(lines) = node_tooltip(&graph, theme, id)
+        {
+            ui::draw_tooltip(d, theme, input.cursor, bounds, &lines);
+        }
+    }
 
-                Tool::Interact {} => {
-                    if input.primary.is_starting()
-                        && let Some(&id) = graph.find_node_at(pos)
-                        && graph.is_inputless(&id)
-                    {
-                        let node = graph.node_mut(&id).expect("all nodes should be valid");
-                        match node.gate_mut() {
-                            gate @ GateInstance::Or => {
-                                *gate = GateInstance::Nor;
-                                is_dirty = true;
-                            }
-                            gate @ GateInstance::Nor => {
-                                *gate = GateInstance::Or;
-                                is_dirty = true;
-                            }
-                            _ => {}
-                        };
-                    }
-                }
-            }
+    /// Draws the context menu, if open, above everything else on screen.
+    pub fn draw_context_menu<D: RaylibDraw>(&self, d: &mut D, theme: &Theme, input: &Inputs) {
+        if let Some(menu) = &self.context_menu {
+            menu.draw(d, theme, input);
         }
-        is_dirty
     }
 
     pub fn draw<D: RaylibDraw>(
@@ -328,42 +2346,148 @@ impl EditorTab {
             Color::WHITE,
         );
         let mut d = d.begin_mode2D(self.camera());
-        let zoom_exp = self.zoom_exp().ceil() as i32;
-        let scale_and_width =
-            NodeIconSheetSetId::from_zoom_exp(zoom_exp).map(|scale| (scale, scale.icon_width()));
         if let Some(graph) = self.graph.upgrade() {
             let graph = graph.try_read().unwrap();
+            let grid_size = graph.grid_size();
+            let cell_center = graph.cell_center();
+            let (zoom_exp, icons) = self.node_icons(theme, grid_size);
+            // too far out to make out icons, elbows, hops or badges, so none of them are drawn
+            let is_far = zoom_exp < FAR_ZOOM_EXP;
+
+            let pixel = self.camera().zoom.recip();
+            let wire_scale = theme.wire_scale();
+
+            // regions
+            for region in graph.regions_ordered() {
+                let min = match &toolpane.tool {
+                    Tool::Edit {
+                        region_target: Some(RegionDragging { id, grab_offset }),
+                        ..
+                    } if id == region.id() => self.screen_to_world(input.cursor) - *grab_offset,
+                    _ => region.bounds().min.as_vec2(),
+                };
+                draw_region(&mut d, theme, region, min, pixel);
+            }
 
             // tool - background layer
             match &toolpane.tool {
-                Tool::Create { current_node: _ } => {}
-                Tool::Erase {} => {}
-                Tool::Edit { target: _ } => {}
-                Tool::Interact {} => {}
-            }
-
-            // wires
-            for wire in graph.wires_iter() {
-                let state = graph
-                    .node(wire.src())
-                    .expect("every wire src should be valid")
-                    .state();
-                wire.draw(
+                Tool::Create { .. } => {}
+                Tool::Erase { .. } => {}
+                Tool::Edit { .. } => {}
+                Tool::Interact { .. } => {}
+                Tool::Annotate { .. } => {}
+            }
+
+            // inactive wires and node backgrounds, already drawn onto the static layer
+            let min = self.screen_to_world(bounds.min);
+            d.draw_texture_pro(
+                self.layer.texture(),
+                Rectangle::new(
+                    0.0,
+                    0.0,
+                    self.layer.width() as f32,
+                    -self.layer.height() as f32,
+                ),
+                Rectangle::new(
+                    min.x,
+                    min.y,
+                    self.layer.width() as f32 * pixel,
+                    self.layer.height() as f32 * pixel,
+                ),
+                Vector2::zero(),
+                0.0,
+                Color::WHITE,
+            );
+
+            // active wires, over their inactive selves on the static layer, or every wire if fading
+            for wire in graph
+                .wires_iter()
+                .filter(|wire| theme.wire_fade || graph.displayed_state(wire.src()))
+            {
+                draw_wire(
                     &mut d,
                     &graph,
-                    rvec2(GRID_SIZE / 2, GRID_SIZE / 2),
-                    if state {
-                        theme.active
-                    } else {
-                        theme.foreground
-                    },
+                    wire,
+                    wire_color(theme, &graph, wire.src()),
+                    wire_scale,
+                    pixel,
+                    is_far,
                 )
                 .expect("all wires should be valid");
             }
 
+            let visible = {
+                let min = self.screen_to_world(bounds.min);
+                let max = self.screen_to_world(bounds.max);
+                Rectangle::new(min.x, min.y, max.x - min.x, max.y - min.y)
+            };
+
+            // hops where unrelated wires cross
+            if theme.wire_hops && !is_far {
+                let radius = f32::from(grid_size) * HOP_RADIUS * wire_scale;
+                let color_of = |wire: &Wire| wire_color(theme, &graph, wire.src());
+                // the wire underneath is put back along twice the radius, so hops need that room
+                for hop in graph.find_hops(cell_center, visible, radius * 2.0) {
+                    let over = graph
+                        .wire(&hop.over)
+                        .expect("hops should be of valid wires");
+                    let under = graph
+                        .wire(&hop.under)
+                        .expect("hops should be of valid wires");
+                    let over_thick = over
+                        .thickness(&graph, wire_scale, pixel)
+                        .expect("all wires should be valid");
+                    let under_thick = under
+                        .thickness(&graph, wire_scale, pixel)
+                        .expect("all wires should be valid");
+                    // cut the crossing out of both wires, then put back the one underneath
+                    d.draw_line_ex(
+                        hop.pos - hop.over_dir * radius,
+                        hop.pos + hop.over_dir * radius,
+                        over_thick + 2.0 * pixel,
+                        theme.background,
+                    );
+                    d.draw_line_ex(
+                        hop.pos - hop.under_dir * (radius * 2.0),
+                        hop.pos + hop.under_dir * (radius * 2.0),
+                        under_thick,
+                        color_of(under),
+                    );
+                    // half a circle on the upper side, or the left of a vertical wire
+                    let angle = hop.over_dir.y.atan2(hop.over_dir.x).to_degrees();
+                    d.draw_ring(
+                        hop.pos,
+                        (radius - over_thick * 0.5).max(0.0),
+                        radius + over_thick * 0.5,
+                        angle + 180.0,
+                        angle + 360.0,
+                        8,
+                        color_of(over),
+                    );
+                }
+            }
+
             // tool - wire layer
             match &toolpane.tool {
-                Tool::Create { current_node } => {
+                Tool::Create {
+                    stamp: Some(stamp), ..
+                } => {
+                    let positions = stamp
+                        .positions(grid_size, self.stamp_origin(input, grid_size))
+                        .collect::<Vec<_>>();
+                    for wire in &stamp.wires {
+                        if let (Some(src), Some(dst)) =
+                            (positions.get(wire.src), positions.get(wire.dst))
+                        {
+                            d.draw_line_v(
+                                src.as_vec2() + cell_center,
+                                dst.as_vec2() + cell_center,
+                                theme.foreground.alpha(0.5),
+                            );
+                        }
+                    }
+                }
+                Tool::Create { current_node, .. } => {
                     if let Some(&current_node) = current_node.as_ref() {
                         Wire::draw_immediate(
                             &mut d,
@@ -372,7 +2496,7 @@ impl EditorTab {
                                 .expect("current node should always be valid")
                                 .position()
                                 .as_vec2()
-                                + rvec2(GRID_SIZE / 2, GRID_SIZE / 2),
+                                + cell_center,
                             self.screen_to_world(input.cursor),
                             toolpane.elbow,
                             theme.foreground,
@@ -380,30 +2504,102 @@ impl EditorTab {
                     }
                 }
 
-                Tool::Erase {} => {}
+                Tool::Erase { .. } => {
+                    let world_pos = self.screen_to_world(input.cursor);
+                    if graph.find_node_at(world_pos.as_ivec2()).is_none()
+                        && let Some(wire) = graph
+                            .find_wire_at(world_pos, wire_tolerance(grid_size))
+                            .and_then(|id| graph.wire(id))
+                    {
+                        wire.draw(
+                            &mut d,
+                            &graph,
+                            cell_center,
+                            theme.destructive,
+                            wire_scale,
+                            pixel,
+                        )
+                        .expect("all wires should be valid");
+                    }
+                }
+
+                Tool::Edit {
+                    target,
+                    wire_target,
+                    region_target: _,
+                    marquee: _,
+                } => {
+                    let world_pos = self.screen_to_world(input.cursor);
+                    if let Some(WireDragging { id, handle }) = wire_target
+                        && let Some(wire) = graph.wire(id)
+                    {
+                        let [start_pos, _, end_pos] = wire
+                            .points(&graph, cell_center)
+                            .expect("all wires should be valid");
+                        let (start_pos, end_pos, elbow) = match handle {
+                            WireHandle::Elbow => (
+                                start_pos,
+                                end_pos,
+                                Elbow::nearest(start_pos, end_pos, world_pos),
+                            ),
+                            WireHandle::End(WireEnd::Src) => (world_pos, end_pos, wire.elbow),
+                            WireHandle::End(WireEnd::Dst) => (start_pos, world_pos, wire.elbow),
+                        };
+                        Wire::draw_immediate(&mut d, start_pos, end_pos, elbow, theme.special);
+                    } else if target.is_none()
+                        && graph.find_node_at(world_pos.as_ivec2()).is_none()
+                        && let Some(WireDragging { id, handle }) =
+                            Self::wire_handle_at(&graph, world_pos)
+                    {
+                        let wire = graph.wire(&id).expect("wire handle should be valid");
+                        wire.draw(
+                            &mut d,
+                            &graph,
+                            cell_center,
+                            theme.interact,
+                            wire_scale,
+                            pixel,
+                        )
+                        .expect("all wires should be valid");
+                        if handle == WireHandle::Elbow {
+                            let [_, elbow_pos, _] = wire
+                                .points(&graph, cell_center)
+                                .expect("all wires should be valid");
+                            d.draw_circle_v(elbow_pos, wire_tolerance(grid_size), theme.interact);
+                        }
+                    }
 
-                Tool::Edit { target } => {
                     if let Some(EditDragging { temp_pos, id }) = target {
+                        let orientation = graph
+                            .node(id)
+                            .expect("node being dragged should be valid")
+                            .orientation();
                         for (_, wire, flow) in graph.wires_of(id) {
                             let (start_pos, end_pos) = match flow {
-                                Flow::Input => (
-                                    graph
-                                        .node(wire.src())
-                                        .expect("all wires should be valid")
-                                        .position()
-                                        .as_vec2()
-                                        + rvec2(GRID_SIZE / 2, GRID_SIZE / 2),
-                                    *temp_pos + rvec2(GRID_SIZE / 2, GRID_SIZE / 2),
-                                ),
-                                Flow::Output => (
-                                    *temp_pos + rvec2(GRID_SIZE / 2, GRID_SIZE / 2),
-                                    graph
-                                        .node(wire.dst())
-                                        .expect("all wires should be valid")
-                                        .position()
-                                        .as_vec2()
-                                        + rvec2(GRID_SIZE / 2, GRID_SIZE / 2),
-                                ),
+                                Flow::Input => {
+                                    let src =
+                                        graph.node(wire.src()).expect("all wires should be valid");
+                                    (
+                                        src.position().as_vec2()
+                                            + cell_center
+                                            + src.orientation().anchor(WireEnd::Src, grid_size),
+                                        *temp_pos
+                                            + cell_center
+                                            + orientation.anchor(WireEnd::Dst, grid_size),
+                                    )
+                                }
+                                Flow::Output => {
+                                    let dst =
+                                        graph.node(wire.dst()).expect("all wires should be valid");
+                                    (
+                                        *temp_pos
+                                            + cell_center
+                                            + orientation.anchor(WireEnd::Src, grid_size),
+                                        dst.position().as_vec2()
+                                            + cell_center
+                                            + dst.orientation().anchor(WireEnd::Dst, grid_size),
+                                    )
+                                }
                                 Flow::Loop => {
                                     todo!()
                                 }
@@ -420,21 +2616,18 @@ impl EditorTab {
                         let rec = Rectangle {
                             x: temp_pos.x,
                             y: temp_pos.y,
-                            width: GRID_SIZE.into(),
-                            height: GRID_SIZE.into(),
+                            width: grid_size.into(),
+                            height: grid_size.into(),
                         };
                         let color = theme.special;
-                        if let Some((scale, icon_width)) = scale_and_width {
+                        if let Some(icons) = &icons {
+                            let (dest, origin, rotation) = icon_placement(rec, node.orientation());
                             d.draw_texture_pro(
-                                &theme.node_icons[scale][NodeIconSheetId::Basic],
-                                node.gate()
-                                    .as_gate()
-                                    .id()
-                                    .icon_cell_irec(icon_width)
-                                    .as_rec(),
-                                rec,
-                                Vector2::zero(),
-                                0.0,
+                                icons.basic,
+                                icons.src_rec(node.gate().as_gate().id()),
+                                dest,
+                                origin,
+                                rotation,
                                 color,
                             );
                         } else {
@@ -443,29 +2636,53 @@ impl EditorTab {
                     }
                 }
 
-                Tool::Interact {} => {}
+                Tool::Interact { .. } => {}
+                Tool::Annotate { .. } => {}
             }
 
-            // nodes
+            // nodes, skipping ones entirely out of view
+            let view = self.node_view(bounds, grid_size);
             match &toolpane.tool {
+                // nothing but untextured quads, so that raylib batches them into one draw call
+                _ if is_far => {
+                    // at least a pixel, so that nodes don't vanish at the farthest zoom
+                    let size = f32::from(grid_size).max(pixel);
+                    for node in graph.nodes_in_bounds(view).map(|id| {
+                        graph
+                            .node(id)
+                            .expect("nodes_in_bounds should only give valid nodes")
+                    }) {
+                        let node_position = node.position().as_vec2();
+                        let color = if self.selection.contains(node.id()) {
+                            theme.interact
+                        } else if graph.displayed_state(node.id()) {
+                            theme.active
+                        } else {
+                            theme.foreground
+                        };
+                        d.draw_rectangle_v(node_position, Vector2::new(size, size), color);
+                    }
+                }
+
                 Tool::Interact { .. } => {
-                    for node in graph.nodes_iter() {
+                    for node in graph.nodes_in_bounds(view).map(|id| {
+                        graph
+                            .node(id)
+                            .expect("nodes_in_bounds should only give valid nodes")
+                    }) {
                         match node.gate() {
                             GateInstance::Led { color } => {
                                 let node_position = node.position().as_vec2();
                                 let rec = Rectangle {
                                     x: node_position.x,
                                     y: node_position.y,
-                                    width: GRID_SIZE.into(),
-                                    height: GRID_SIZE.into(),
+                                    width: grid_size.into(),
+                                    height: grid_size.into(),
                                 };
                                 let (count, sum) = graph.wires_to(node.id()).fold(
                                     (0, 0),
                                     |(n, acc), (_, wire)| {
-                                        let state = graph
-                                            .node(wire.src())
-                                            .expect("all wires should be valid")
-                                            .state();
+                                        let state = graph.displayed_state(wire.src());
                                         (n + 1, acc + usize::from(state))
                                     },
                                 );
@@ -482,38 +2699,37 @@ impl EditorTab {
                                 );
                             }
 
-                            GateInstance::Or | GateInstance::Nor
-                                if graph.is_inputless(node.id()) =>
-                            {
+                            GateInstance::Switch { .. } | GateInstance::Button { .. } => {
                                 let node_position = node.position().as_vec2();
                                 let rec = Rectangle {
                                     x: node_position.x,
                                     y: node_position.y,
-                                    width: GRID_SIZE.into(),
-                                    height: GRID_SIZE.into(),
+                                    width: grid_size.into(),
+                                    height: grid_size.into(),
+                                };
+                                let color = if graph.displayed_state(node.id()) {
+                                    theme.active
+                                } else {
+                                    theme.available
                                 };
-                                let color = theme.available;
-                                if let Some((scale, icon_width)) = scale_and_width {
-                                    let src_rec = node
-                                        .gate()
-                                        .as_gate()
-                                        .id()
-                                        .icon_cell_irec(icon_width)
-                                        .as_rec();
+                                if let Some(icons) = &icons {
+                                    let (dest, origin, rotation) =
+                                        icon_placement(rec, node.orientation());
+                                    let src_rec = icons.src_rec(node.gate().as_gate().id());
                                     d.draw_texture_pro(
-                                        &theme.node_icons[scale][NodeIconSheetId::Background],
+                                        icons.background,
                                         src_rec,
-                                        rec,
-                                        Vector2::zero(),
-                                        0.0,
+                                        dest,
+                                        origin,
+                                        rotation,
                                         theme.background,
                                     );
                                     d.draw_texture_pro(
-                                        &theme.node_icons[scale][NodeIconSheetId::Basic],
+                                        icons.basic,
                                         src_rec,
-                                        rec,
-                                        Vector2::zero(),
-                                        0.0,
+                                        dest,
+                                        origin,
+                                        rotation,
                                         color,
                                     );
                                 } else {
@@ -524,12 +2740,12 @@ impl EditorTab {
                             _ => {
                                 let node_position = node.position().as_vec2();
                                 let rec = Rectangle {
-                                    x: node_position.x + f32::from(GRID_SIZE) * (0.5 - 0.25 * 0.5),
-                                    y: node_position.y + f32::from(GRID_SIZE) * (0.5 - 0.25 * 0.5),
-                                    width: f32::from(GRID_SIZE) * 0.25,
-                                    height: f32::from(GRID_SIZE) * 0.25,
+                                    x: node_position.x + f32::from(grid_size) * (0.5 - 0.25 * 0.5),
+                                    y: node_position.y + f32::from(grid_size) * (0.5 - 0.25 * 0.5),
+                                    width: f32::from(grid_size) * 0.25,
+                                    height: f32::from(grid_size) * 0.25,
                                 };
-                                let color = if node.state() {
+                                let color = if graph.displayed_state(node.id()) {
                                     theme.active
                                 } else {
                                     theme.foreground1
@@ -541,104 +2757,276 @@ impl EditorTab {
                 }
 
                 _ => {
-                    for node in graph.nodes_iter() {
+                    let over_limit = graph.soft_limit_violations();
+                    let in_loop = graph
+                        .combinational_loops()
+                        .into_iter()
+                        .flatten()
+                        .collect::<FxHashSet<_>>();
+                    for node in graph.nodes_in_bounds(view).map(|id| {
+                        graph
+                            .node(id)
+                            .expect("nodes_in_bounds should only give valid nodes")
+                    }) {
                         let node_position = node.position().as_vec2();
                         let rec = Rectangle {
                             x: node_position.x,
                             y: node_position.y,
-                            width: GRID_SIZE.into(),
-                            height: GRID_SIZE.into(),
+                            width: grid_size.into(),
+                            height: grid_size.into(),
                         };
-                        let color = if node.state() {
+                        let color = if graph.displayed_state(node.id()) {
                             theme.active
                         } else {
                             theme.foreground
                         };
-                        if let Some((scale, icon_width)) = scale_and_width {
-                            let src_rec = node
-                                .gate()
-                                .as_gate()
-                                .id()
-                                .icon_cell_irec(icon_width)
-                                .as_rec();
-                            d.draw_texture_pro(
-                                &theme.node_icons[scale][NodeIconSheetId::Background],
-                                src_rec,
-                                rec,
-                                Vector2::zero(),
-                                0.0,
-                                theme.background,
-                            );
+                        if let Some(icons) = &icons {
+                            // the background is on the static layer
+                            let (dest, origin, rotation) = icon_placement(rec, node.orientation());
+                            let src_rec = icons.src_rec(node.gate().as_gate().id());
                             if self.selection.contains(node.id()) {
                                 d.draw_texture_pro(
-                                    &theme.node_icons[scale][NodeIconSheetId::Highlight],
+                                    icons.highlight,
                                     src_rec,
-                                    rec,
-                                    Vector2::zero(),
-                                    0.0,
+                                    dest,
+                                    origin,
+                                    rotation,
                                     theme.interact,
                                 );
                             }
-                            d.draw_texture_pro(
-                                &theme.node_icons[scale][NodeIconSheetId::Basic],
-                                src_rec,
-                                rec,
-                                Vector2::zero(),
-                                0.0,
-                                color,
-                            );
-                            if let Some(color) = match *node.gate() {
-                                GateInstance::Or
-                                | GateInstance::And
-                                | GateInstance::Nor
-                                | GateInstance::Xor
-                                | GateInstance::Battery
-                                | GateInstance::Delay { .. } => None,
-
-                                GateInstance::Resistor { resistance: n }
-                                | GateInstance::Led { color: n } => Some(
-                                    theme
-                                        .resistance
-                                        .get(n as usize)
-                                        .copied()
-                                        .expect("gate should never contain invalid NT data"),
-                                ),
-
-                                GateInstance::Capacitor { capacity, stored } => Some(
-                                    theme
-                                        .active
-                                        .alpha(u8::from(stored) as f32 / u8::from(capacity) as f32),
-                                ),
-                            } {
+                            d.draw_texture_pro(icons.basic, src_rec, dest, origin, rotation, color);
+                            if let Some(color) = ntd_tint(theme, node.gate()) {
                                 d.draw_texture_pro(
-                                    &theme.node_icons[scale][NodeIconSheetId::Ntd],
-                                    src_rec,
-                                    rec,
-                                    Vector2::zero(),
-                                    0.0,
-                                    color,
+                                    icons.ntd, src_rec, dest, origin, rotation, color,
                                 );
                             }
                         } else {
                             d.draw_rectangle_rec(rec, color);
                         }
+                        if in_loop.contains(node.id()) {
+                            // outlined, since the corners already have badges
+                            d.draw_rectangle_lines_ex(
+                                rec,
+                                f32::from(grid_size) * 0.125,
+                                theme.caution,
+                            );
+                        }
+                        if over_limit.contains(node.id()) {
+                            // badge in the top-right corner
+                            let size = f32::from(grid_size) * 0.375;
+                            d.draw_rectangle_rec(
+                                Rectangle {
+                                    x: rec.x + rec.width - size,
+                                    y: rec.y,
+                                    width: size,
+                                    height: size,
+                                },
+                                theme.caution,
+                            );
+                        }
+                        if graph.is_probed(node.id()) {
+                            // badge in the bottom-left corner, clear of the limit badge
+                            let size = f32::from(grid_size) * 0.375;
+                            d.draw_rectangle_rec(
+                                Rectangle {
+                                    x: rec.x,
+                                    y: rec.y + rec.height - size,
+                                    width: size,
+                                    height: size,
+                                },
+                                theme.special,
+                            );
+                        }
+                    }
+                }
+            }
+
+            // low vision outlines, just outside each node so they don't cover its icon
+            if theme.low_vision && !is_far {
+                let thick = pixel * 2.0;
+                for node in graph.nodes_in_bounds(view).map(|id| {
+                    graph
+                        .node(id)
+                        .expect("nodes_in_bounds should only give valid nodes")
+                }) {
+                    let node_position = node.position().as_vec2();
+                    d.draw_rectangle_lines_ex(
+                        Rectangle {
+                            x: node_position.x - thick,
+                            y: node_position.y - thick,
+                            width: f32::from(grid_size) + 2.0 * thick,
+                            height: f32::from(grid_size) + 2.0 * thick,
+                        },
+                        thick,
+                        theme.foreground,
+                    );
+                }
+            }
+
+            // junction dots, over the nodes so that the half on a node's edge isn't hidden
+            if theme.junction_dots && !is_far {
+                let radius = f32::from(grid_size) * JUNCTION_RADIUS * wire_scale;
+                for junction in graph.find_junctions(cell_center, visible) {
+                    d.draw_circle_v(
+                        junction.pos,
+                        radius,
+                        if junction.active {
+                            theme.active
+                        } else {
+                            theme.foreground
+                        },
+                    );
+                }
+            }
+
+            // node names, centered above each named node once its icon can be made out
+            if icons.is_some() {
+                let font_size = f32::from(grid_size) * 0.5;
+                for node in graph.nodes_in_bounds(view).map(|id| {
+                    graph
+                        .node(id)
+                        .expect("nodes_in_bounds should only give valid nodes")
+                }) {
+                    let Some(name) = node.name() else {
+                        continue;
+                    };
+                    let width =
+                        RaylibFont::measure_text(&theme.general_font, name, font_size, 0.0).x;
+                    let node_position = node.position().as_vec2();
+                    d.draw_text_ex(
+                        &theme.general_font,
+                        name,
+                        Vector2::new(
+                            node_position.x + 0.5 * (f32::from(grid_size) - width),
+                            node_position.y - font_size,
+                        ),
+                        font_size,
+                        0.0,
+                        theme.foreground,
+                    );
+                }
+            }
+
+            // eval order overlay
+            if let Some(shown_at) = self.eval_order_overlay
+                && !graph.is_eval_order_dirty()
+            {
+                let order = graph.eval_order();
+                let sweep = (!order.is_empty()).then(|| {
+                    let steps = shown_at.elapsed().as_millis() / EVAL_SWEEP_STEP.as_millis();
+                    (steps % order.len() as u128) as usize
+                });
+                let font_size = f32::from(grid_size) * 0.5;
+                for (n, id) in order.iter().enumerate() {
+                    let node = graph
+                        .node(id)
+                        .expect("all nodes in eval_order should be valid");
+                    let node_position = node.position().as_vec2();
+                    if sweep == Some(n) {
+                        d.draw_rectangle_lines_ex(
+                            Rectangle {
+                                x: node_position.x,
+                                y: node_position.y,
+                                width: grid_size.into(),
+                                height: grid_size.into(),
+                            },
+                            1.0,
+                            theme.special,
+                        );
                     }
+                    d.draw_text_ex(
+                        &theme.general_font,
+                        &n.to_string(),
+                        node_position,
+                        font_size,
+                        0.0,
+                        if sweep.is_some_and(|sweep| n <= sweep) {
+                            theme.special
+                        } else {
+                            theme.foreground2
+                        },
+                    );
                 }
             }
 
             // tool - nodes layer
             match &toolpane.tool {
-                Tool::Create { current_node: _ } => {}
-                Tool::Erase {} => {}
-                Tool::Edit { target: _ } => {}
-                Tool::Interact {} => {}
+                Tool::Create {
+                    stamp: Some(stamp), ..
+                } => {
+                    let size = f32::from(grid_size);
+                    for position in stamp.positions(grid_size, self.stamp_origin(input, grid_size))
+                    {
+                        let rec = Rectangle::new(position.x as f32, position.y as f32, size, size);
+                        d.draw_rectangle_rec(rec, theme.interact.alpha(0.25));
+                        d.draw_rectangle_lines_ex(rec, pixel, theme.interact);
+                    }
+                }
+                Tool::Create { .. } => {}
+                Tool::Erase { .. } => {}
+                Tool::Edit {
+                    marquee: Some(start),
+                    ..
+                } => {
+                    let end = self.screen_to_world(input.cursor);
+                    let rec = Rectangle::from(Bounds::new(
+                        Vector2::new(start.x.min(end.x), start.y.min(end.y)),
+                        Vector2::new(start.x.max(end.x), start.y.max(end.y)),
+                    ));
+                    d.draw_rectangle_rec(rec, theme.interact.alpha(0.125));
+                    d.draw_rectangle_lines_ex(rec, 1.0 / self.camera().zoom, theme.interact);
+                }
+                Tool::Edit { .. } => {}
+                Tool::Interact { .. } => {}
+                Tool::Annotate { .. } => {}
+            }
+
+            // annotations
+            for annotation in graph.annotations_ordered() {
+                let (min, is_target) = match &toolpane.tool {
+                    Tool::Annotate {
+                        target: Some(id),
+                        grab_offset,
+                    } if id == annotation.id() => (
+                        grab_offset.map_or(annotation.position().as_vec2(), |offset| {
+                            self.screen_to_world(input.cursor) - offset
+                        }),
+                        true,
+                    ),
+                    _ => (annotation.position().as_vec2(), false),
+                };
+                theme
+                    .general_font
+                    .draw_text(&mut d, &annotation.text, min, theme.foreground);
+                if is_target {
+                    d.draw_rectangle_lines_ex(
+                        annotation_rec(theme, annotation, min),
+                        pixel,
+                        theme.interact,
+                    );
+                }
+            }
+
+            // key cursor, just outside its cell like a low vision outline
+            if let Some(KeyCursor { cell, .. }) = self.key_cursor {
+                let thick = pixel * 2.0;
+                d.draw_rectangle_lines_ex(
+                    Rectangle {
+                        x: cell.x as f32 - thick,
+                        y: cell.y as f32 - thick,
+                        width: f32::from(grid_size) + 2.0 * thick,
+                        height: f32::from(grid_size) + 2.0 * thick,
+                    },
+                    thick,
+                    theme.interact,
+                );
             }
 
-            if let Some(id) = graph.find_node_at(
-                self.screen_to_world(input.cursor)
-                    .as_ivec2()
-                    .snap(GRID_SIZE.into()),
-            ) && (!matches!(toolpane.tool, Tool::Interact { .. }) || graph.is_inputless(id))
+            if let Some(id) = graph.find_node_at(self.screen_to_world(input.cursor).as_ivec2())
+                && (!matches!(toolpane.tool, Tool::Interact { .. })
+                    || graph
+                        .node(id)
+                        .is_some_and(|node| node.gate().is_interactive()))
             {
                 let node = graph
                     .node(id)
@@ -647,21 +3035,21 @@ impl EditorTab {
                 let rec = Rectangle {
                     x: node_position.x,
                     y: node_position.y,
-                    width: GRID_SIZE.into(),
-                    height: GRID_SIZE.into(),
+                    width: grid_size.into(),
+                    height: grid_size.into(),
+                };
+                let color = match toolpane.tool {
+                    Tool::Erase { .. } => theme.destructive,
+                    _ => theme.interact,
                 };
-                let color = theme.interact;
-                if let Some((scale, icon_width)) = scale_and_width {
+                if let Some(icons) = &icons {
+                    let (dest, origin, rotation) = icon_placement(rec, node.orientation());
                     d.draw_texture_pro(
-                        &theme.node_icons[scale][NodeIconSheetId::Highlight],
-                        node.gate()
-                            .as_gate()
-                            .id()
-                            .icon_cell_irec(icon_width)
-                            .as_rec(),
-                        rec,
-                        Vector2::zero(),
-                        0.0,
+                        icons.highlight,
+                        icons.src_rec(node.gate().as_gate().id()),
+                        dest,
+                        origin,
+                        rotation,
                         color,
                     );
                 } else {
@@ -677,12 +3065,52 @@ pub enum Tab {
     Editor(EditorTab),
 }
 
+impl Tab {
+    #[inline]
+    pub fn title(&self) -> String {
+        match self {
+            Tab::Editor(tab) => tab.title(),
+        }
+    }
+
+    #[inline]
+    pub fn badge(&self) -> Option<String> {
+        match self {
+            Tab::Editor(tab) => tab.badge(),
+        }
+    }
+
+    #[inline]
+    pub const fn is_modified(&self) -> bool {
+        match self {
+            Tab::Editor(tab) => tab.is_modified(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct TabList {
     panel: Panel,
     tabs: Vec<Tab>,
     /// ignore if `tabs` is empty
     focused: usize,
+    /// Index of the tab being dragged along the tab bar
+    dragging: Option<usize>,
+    /// Index of the tab with unsaved changes that was asked to close once, and closes if asked
+    /// again
+    closing: Option<usize>,
+    /// Snapping of new editors
+    default_snap: SnapMode,
+}
+
+/// Where each part of the tab bar is, in screen space.
+#[derive(Debug)]
+struct TabBarLayout {
+    /// `(tab, badge, close button)` for each tab
+    tabs: Vec<(Rectangle, Option<(String, Rectangle)>, Rectangle)>,
+    new_tab: Rectangle,
+    /// Shows the focused tab's zoom and opens the zoom dropdown when clicked
+    zoom: Option<(String, Rectangle)>,
 }
 
 impl Extend<Tab> for TabList {
@@ -737,6 +3165,9 @@ impl TabList {
             panel,
             tabs: Vec::new(),
             focused: 0,
+            dragging: None,
+            closing: None,
+            default_snap: SnapMode::Cell,
         }
     }
 
@@ -748,9 +3179,18 @@ impl TabList {
             panel,
             tabs: Vec::from_iter(tabs),
             focused: 0,
+            dragging: None,
+            closing: None,
+            default_snap: SnapMode::Cell,
         }
     }
 
+    /// Sets the snapping of editors opened from now on.
+    #[inline]
+    pub fn set_default_snap(&mut self, snap: SnapMode) {
+        self.default_snap = snap;
+    }
+
     #[inline]
     pub const fn panel(&self) -> &Panel {
         &self.panel
@@ -804,6 +3244,16 @@ impl TabList {
         }
     }
 
+    /// Index of the focused tab, or [`None`] if there are no tabs.
+    #[inline]
+    pub const fn focused_index(&self) -> Option<usize> {
+        if self.tabs.is_empty() {
+            None
+        } else {
+            Some(self.focused)
+        }
+    }
+
     /// Returns an error if `tab` is out of range
     #[inline]
     pub const fn focus(&mut self, tab: usize) -> Result<(), ()> {
@@ -839,8 +3289,9 @@ impl TabList {
 
     #[inline]
     pub fn remove(&mut self, index: usize) -> Tab {
+        self.closing = None;
         let removed = self.tabs.remove(index);
-        if self.focused > index {
+        if self.focused > index || (self.focused == self.tabs.len() && self.focused > 0) {
             self.focused -= 1;
         }
         removed
@@ -848,6 +3299,7 @@ impl TabList {
 
     #[inline]
     pub fn retain<F: FnMut(&Tab) -> bool>(&mut self, mut f: F) {
+        self.closing = None;
         let mut i = 0;
         let mut shift = 0;
         self.tabs.retain_mut(|tab| {
@@ -887,11 +3339,12 @@ impl TabList {
         if from_index < self.tabs.len() && to_index < self.tabs.len() {
             let (dir, range, rotate): (_, _, fn(&mut [Tab], usize)) =
                 match from_index.cmp(&to_index) {
-                    Less => (-1, from_index..to_index, <[_]>::rotate_left),
+                    Less => (-1, from_index..=to_index, <[_]>::rotate_left),
                     Equal => return Ok(()),
-                    Greater => (1, to_index..from_index, <[_]>::rotate_right),
+                    Greater => (1, to_index..=from_index, <[_]>::rotate_right),
                 };
 
+            self.closing = None;
             let slice = &mut self.tabs[range.clone()];
             rotate(slice, 1);
             if self.focused == from_index {
@@ -922,6 +3375,22 @@ impl TabList {
         })
     }
 
+    /// Focuses a tab showing `graph`, preferring the focused tab, and returns it.
+    /// Returns [`None`] if no tab shows `graph`.
+    pub fn focus_graph(&mut self, graph: &Weak<RwLock<Graph>>) -> Option<&mut EditorTab> {
+        let shows_graph = |tab: &Tab| match tab {
+            Tab::Editor(tab) => tab.graph.ptr_eq(graph),
+        };
+        if !self.focused_tab().is_some_and(shows_graph) {
+            let index = self.tabs.iter().position(shows_graph)?;
+            self.focus(index).expect("position should be in range");
+        }
+        match self.focused_tab_mut() {
+            Some(Tab::Editor(tab)) => Some(tab),
+            None => None,
+        }
+    }
+
     #[inline]
     pub fn editors_of_graph(
         &self,
@@ -943,4 +3412,370 @@ impl TabList {
             _ => None,
         })
     }
+
+    /// Creates an editor for `graph` sized to the panel, then pushes and focuses it.
+    pub fn push_editor(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        graph: Weak<RwLock<Graph>>,
+    ) -> Result<&mut EditorTab, raylib::error::Error> {
+        let bounds = self.panel.bounds();
+        let mut tab = EditorTab::new(
+            rl,
+            thread,
+            bounds.width().ceil() as u32,
+            bounds.height().ceil() as u32,
+            graph,
+        )?;
+        tab.snap = self.default_snap;
+        self.tabs.push(Tab::Editor(tab));
+        self.focused = self.tabs.len() - 1;
+        match self.tabs.last_mut().expect("just pushed") {
+            Tab::Editor(tab) => Ok(tab),
+        }
+    }
+
+    /// Opens another view of the focused tab's graph next to it and focuses the new view.
+    pub fn open_view(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) -> Result<(), raylib::error::Error> {
+        if let Some(Tab::Editor(tab)) = self.focused_tab() {
+            let view = tab.new_view(rl, thread)?;
+            self.insert(self.focused + 1, Tab::Editor(view));
+            self.focused += 1;
+        }
+        Ok(())
+    }
+
+    /// Focuses the scratch tab, creating it and its graph in `graphs` if there is none open.
+    pub fn open_scratch(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        graphs: &mut GraphList,
+    ) -> Result<(), raylib::error::Error> {
+        if let Some(index) = self
+            .tabs
+            .iter()
+            .position(|tab| matches!(tab, Tab::Editor(tab) if tab.is_scratch))
+        {
+            self.focus(index).expect("position should be in range");
+        } else {
+            let graph = Arc::downgrade(graphs.create_scratch_graph());
+            self.push_editor(rl, thread, graph)?.is_scratch = true;
+        }
+        Ok(())
+    }
+
+    /// Moves the camera of every synced view to match the focused tab, if it is synced too.
+    pub fn sync_cameras(&mut self) {
+        if self.tabs.is_empty() {
+            return;
+        }
+        let (before, rest) = self.tabs.split_at_mut(self.focused);
+        let (focused, after) = rest.split_first_mut().expect("focused tab should exist");
+        match focused {
+            Tab::Editor(focused) if focused.camera_sync => {
+                for tab in before.iter_mut().chain(after) {
+                    match tab {
+                        Tab::Editor(tab) => {
+                            if tab.camera_sync && tab.graph.ptr_eq(&focused.graph) {
+                                tab.follow_camera(focused);
+                            }
+                        }
+                    }
+                }
+            }
+            Tab::Editor(_) => {}
+        }
+    }
+
+    /// Closes the tab at `index`, removing its graph from `graphs` if no other tab shows it.
+    ///
+    /// The last tab of a graph with unsaved changes only closes when asked twice in a row, so
+    /// that the changes aren't thrown away by accident.
+    pub fn close(&mut self, index: usize, graphs: &mut GraphList, console: &mut Console) {
+        let title = self.tabs[index].title();
+        let is_losing_changes = match &self.tabs[index] {
+            Tab::Editor(tab) => {
+                tab.is_modified() && self.editors_of_graph(&tab.graph).nth(1).is_none()
+            }
+        };
+        if is_losing_changes && self.closing != Some(index) {
+            self.closing = Some(index);
+            logln!(
+                console,
+                LogType::Warning,
+                "{title} has unsaved changes; close it again to throw them away"
+            );
+            return;
+        }
+        match self.remove(index) {
+            Tab::Editor(tab) => {
+                if is_losing_changes {
+                    logln!(
+                        console,
+                        LogType::Warning,
+                        "closed {title} with unsaved changes"
+                    );
+                } else {
+                    logln!(console, LogType::Info, "closed {title}");
+                }
+                if self.editors_of_graph(&tab.graph).next().is_none()
+                    && let Some(graph) = tab.graph.upgrade()
+                {
+                    graphs.retain(|g| !Arc::ptr_eq(g, &graph));
+                }
+            }
+        }
+    }
+
+    /// Height of the strip of tab titles along the top of the panel.
+    #[inline]
+    pub fn bar_height(theme: &Theme) -> f32 {
+        theme.general_font.line_height() + theme.title_padding.vertical()
+    }
+
+    #[inline]
+    pub fn bar_bounds(&self, theme: &Theme) -> Bounds {
+        let bounds = self.panel.bounds();
+        Bounds::new(
+            bounds.min,
+            Vector2::new(bounds.max.x, bounds.min.y + Self::bar_height(theme)),
+        )
+    }
+
+    fn bar_layout(&self, theme: &Theme) -> TabBarLayout {
+        let bar = self.bar_bounds(theme);
+        let padding = theme.title_padding;
+        let button_size = theme.general_font.line_height();
+        let mut x = bar.min.x;
+        let tabs = self
+            .tabs
+            .iter()
+            .map(|tab| {
+                let text_width = theme.general_font.measure_text(&tab.title()).x;
+                let badge = tab.badge().map(|badge| {
+                    let badge_rec = Rectangle::new(
+                        x + padding.left + text_width + padding.left,
+                        bar.min.y + padding.top,
+                        theme.general_font.measure_text(&badge).x + padding.horizontal(),
+                        button_size,
+                    );
+                    (badge, badge_rec)
+                });
+                let badge_width = badge
+                    .as_ref()
+                    .map_or(0.0, |(_, badge_rec)| padding.left + badge_rec.width);
+                let width =
+                    padding.horizontal() + text_width + badge_width + padding.left + button_size;
+                let tab_rec = Rectangle::new(x, bar.min.y, width, bar.height());
+                let close_rec = Rectangle::new(
+                    x + width - padding.right - button_size,
+                    bar.min.y + padding.top,
+                    button_size,
+                    button_size,
+                );
+                x += width;
+                (tab_rec, badge, close_rec)
+            })
+            .collect();
+        let new_tab = Rectangle::new(x, bar.min.y, bar.height(), bar.height());
+        let zoom = self.focused_tab().map(|tab| {
+            let label = match tab {
+                Tab::Editor(tab) => zoom_percent(tab.zoom_exp()),
+            };
+            let width = theme.general_font.measure_text(&label).x + padding.horizontal();
+            let rec = Rectangle::new(new_tab.x + new_tab.width, bar.min.y, width, bar.height());
+            (label, rec)
+        });
+        TabBarLayout {
+            tabs,
+            new_tab,
+            zoom,
+        }
+    }
+
+    /// Focuses, closes, reorders and creates tabs from the tab bar.
+    ///
+    /// Returns `true` if the tab bar used the input, in which case the focused tab should not.
+    pub fn tick_bar(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        console: &mut Console,
+        graphs: &mut GraphList,
+        theme: &Theme,
+        input: &Inputs,
+    ) -> bool {
+        let layout = self.bar_layout(theme);
+        let hovered = layout
+            .tabs
+            .iter()
+            .position(|(tab_rec, ..)| Bounds::from(*tab_rec).contains(input.cursor));
+
+        if let Some(from) = self.dragging {
+            if input.primary.is_active() {
+                if let Some(to) = hovered
+                    && to != from
+                {
+                    self.reorder(from, to)
+                        .expect("dragged and hovered tabs should exist");
+                    self.dragging = Some(to);
+                }
+                return true;
+            }
+            self.dragging = None;
+        }
+
+        // clicking anything but a close button takes back asking to close a tab
+        if input.primary.is_starting()
+            && !hovered.is_some_and(|index| {
+                let (_, _, close_rec) = layout.tabs[index];
+                Bounds::from(close_rec).contains(input.cursor)
+            })
+        {
+            self.closing = None;
+        }
+
+        if !self.bar_bounds(theme).contains(input.cursor) {
+            return false;
+        }
+
+        if let Some(index) = hovered {
+            let (_, _, close_rec) = layout.tabs[index];
+            if input.close_tab.is_starting()
+                || (input.primary.is_starting() && Bounds::from(close_rec).contains(input.cursor))
+            {
+                self.close(index, graphs, console);
+            } else if input.primary.is_starting() {
+                self.focus(index).expect("hovered tab should exist");
+                self.dragging = Some(index);
+            }
+        } else if input.primary.is_starting() && Bounds::from(layout.new_tab).contains(input.cursor)
+        {
+            let graph = Arc::downgrade(graphs.create_graph());
+            if let Err(e) = self.push_editor(rl, thread, graph) {
+                logln!(console, LogType::Error, "failed to create tab: {e}");
+            }
+        } else if input.primary.is_starting()
+            && let Some((_, zoom_rec)) = layout.zoom
+            && Bounds::from(zoom_rec).contains(input.cursor)
+            && let Some(Tab::Editor(tab)) = self.focused_tab_mut()
+        {
+            tab.open_zoom_menu(Vector2::new(zoom_rec.x, zoom_rec.y + zoom_rec.height));
+        }
+        true
+    }
+
+    pub fn draw_bar<D: RaylibDraw>(&self, d: &mut D, theme: &Theme, input: &Inputs) {
+        let bar = self.bar_bounds(theme);
+        let layout = self.bar_layout(theme);
+        d.draw_rectangle_rec(Rectangle::from(bar), theme.background2);
+        for (index, (tab, (tab_rec, badge, close_rec))) in
+            self.tabs.iter().zip(layout.tabs).enumerate()
+        {
+            let is_focused = index == self.focused;
+            let is_hovered = Bounds::from(tab_rec).contains(input.cursor);
+            if is_focused {
+                d.draw_rectangle_rec(tab_rec, theme.background1);
+            } else if is_hovered {
+                d.draw_rectangle_rec(tab_rec, theme.background3);
+            }
+            theme.general_font.draw_text(
+                d,
+                &tab.title(),
+                Vector2::new(
+                    tab_rec.x + theme.title_padding.left,
+                    tab_rec.y + theme.title_padding.top,
+                ),
+                if is_focused {
+                    theme.foreground
+                } else {
+                    theme.foreground2
+                },
+            );
+            if let Some((badge, badge_rec)) = badge {
+                d.draw_rectangle_rec(
+                    badge_rec,
+                    if is_hovered && !is_focused {
+                        theme.background2
+                    } else {
+                        theme.background3
+                    },
+                );
+                theme.general_font.draw_text(
+                    d,
+                    &badge,
+                    Vector2::new(badge_rec.x + theme.title_padding.left, badge_rec.y),
+                    theme.foreground2,
+                );
+            }
+            let is_close_hovered = Bounds::from(close_rec).contains(input.cursor);
+            let is_closing = self.closing == Some(index);
+            if tab.is_modified() && !is_close_hovered && !is_closing {
+                // unsaved changes, until the close button is pointed at
+                d.draw_circle_v(
+                    Vector2::new(
+                        close_rec.x + 0.5 * close_rec.width,
+                        close_rec.y + 0.5 * close_rec.height,
+                    ),
+                    0.25 * close_rec.width,
+                    theme.foreground2,
+                );
+            } else if is_focused || is_hovered || is_closing {
+                let close_size = theme.general_font.measure_text("x");
+                theme.general_font.draw_text(
+                    d,
+                    "x",
+                    Vector2::new(
+                        close_rec.x + 0.5 * (close_rec.width - close_size.x),
+                        close_rec.y + 0.5 * (close_rec.height - close_size.y),
+                    ),
+                    if is_close_hovered || is_closing {
+                        theme.destructive
+                    } else {
+                        theme.foreground2
+                    },
+                );
+            }
+        }
+        let new_tab = layout.new_tab;
+        let plus_size = theme.general_font.measure_text("+");
+        theme.general_font.draw_text(
+            d,
+            "+",
+            Vector2::new(
+                new_tab.x + 0.5 * (new_tab.width - plus_size.x),
+                new_tab.y + 0.5 * (new_tab.height - plus_size.y),
+            ),
+            if Bounds::from(new_tab).contains(input.cursor) {
+                theme.foreground
+            } else {
+                theme.foreground2
+            },
+        );
+        if let Some((label, zoom_rec)) = &layout.zoom {
+            let is_hovered = Bounds::from(*zoom_rec).contains(input.cursor);
+            if is_hovered {
+                d.draw_rectangle_rec(*zoom_rec, theme.background3);
+            }
+            theme.general_font.draw_text(
+                d,
+                label,
+                Vector2::new(
+                    zoom_rec.x + theme.title_padding.left,
+                    zoom_rec.y + theme.title_padding.top,
+                ),
+                if is_hovered {
+                    theme.foreground
+                } else {
+                    theme.foreground2
+                },
+            );
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use crate::{
     icon_sheets::{ButtonIconSheetId, ButtonIconSheets, NodeIconSheetSet, NodeIconSheetSets},
+    rich_text::TextStyle,
     ui::{Orientation, Padding, Visibility},
 };
 use raylib::prelude::*;
@@ -345,21 +346,38 @@ impl CustomColors for Color {}
 #[derive(Debug, Serialize)]
 pub struct ThemeFont {
     pub path: Option<PathBuf>,
+    /// Font for bold [rich text](crate::rich_text). Without one, bold text is drawn twice, a
+    /// pixel apart.
+    #[serde(rename = "bold")]
+    pub bold_path: Option<PathBuf>,
+    /// Font for italic [rich text](crate::rich_text). Without one, italic text is drawn upright.
+    #[serde(rename = "italic")]
+    pub italic_path: Option<PathBuf>,
     pub font_size: f32,
     pub char_spacing: f32,
     pub line_spacing: f32,
     #[serde(skip)]
     pub font: OptionalFont,
+    /// [`OptionalFont::Unloaded`] if there is no `bold_path`
+    #[serde(skip)]
+    pub bold: OptionalFont,
+    /// [`OptionalFont::Unloaded`] if there is no `italic_path`
+    #[serde(skip)]
+    pub italic: OptionalFont,
 }
 
 impl Default for ThemeFont {
     fn default() -> Self {
         Self {
             path: None,
+            bold_path: None,
+            italic_path: None,
             font_size: 10.0,
             char_spacing: 1.0,
             line_spacing: 2.0,
             font: OptionalFont::Unloaded,
+            bold: OptionalFont::Unloaded,
+            italic: OptionalFont::Unloaded,
         }
     }
 }
@@ -382,6 +400,8 @@ impl<'de> Visitor<'de> for ThemeFontVisitor {
         #[serde(rename_all = "snake_case")]
         enum FieldIdent {
             Path,
+            Bold,
+            Italic,
             FontSize,
             CharSpacing,
             LineSpacing,
@@ -390,12 +410,16 @@ impl<'de> Visitor<'de> for ThemeFontVisitor {
         }
 
         let mut path = None;
+        let mut bold_path = None;
+        let mut italic_path = None;
         let mut font_size = None;
         let mut char_spacing = None;
         let mut line_spacing = None;
         while let Some(key) = map.next_key()? {
             match key {
                 FieldIdent::Path => path = Some(map.next_value()?),
+                FieldIdent::Bold => bold_path = Some(map.next_value()?),
+                FieldIdent::Italic => italic_path = Some(map.next_value()?),
                 FieldIdent::FontSize => font_size = Some(map.next_value()?),
                 FieldIdent::CharSpacing => char_spacing = Some(map.next_value()?),
                 FieldIdent::LineSpacing => line_spacing = Some(map.next_value()?),
@@ -409,10 +433,14 @@ impl<'de> Visitor<'de> for ThemeFontVisitor {
 
         Ok(ThemeFont {
             path,
+            bold_path,
+            italic_path,
             font_size,
             char_spacing,
             line_spacing,
             font: OptionalFont::Unloaded,
+            bold: OptionalFont::Unloaded,
+            italic: OptionalFont::Unloaded,
         })
     }
 }
@@ -436,10 +464,14 @@ impl Clone for ThemeFont {
     fn clone(&self) -> Self {
         Self {
             path: self.path.clone(),
+            bold_path: self.bold_path.clone(),
+            italic_path: self.italic_path.clone(),
             font_size: self.font_size,
             char_spacing: self.char_spacing,
             line_spacing: self.line_spacing,
             font: OptionalFont::Unloaded,
+            bold: OptionalFont::Unloaded,
+            italic: OptionalFont::Unloaded,
         }
     }
 }
@@ -490,9 +522,32 @@ impl AsMut<ffi::Font> for ThemeFont {
 impl RaylibFont for ThemeFont {}
 
 impl ThemeFont {
-    #[inline]
+    /// The same font drawn `scale` times larger, not loaded yet.
+    #[must_use]
+    pub fn scaled(&self, scale: f32) -> Self {
+        Self {
+            path: self.path.clone(),
+            bold_path: self.bold_path.clone(),
+            italic_path: self.italic_path.clone(),
+            font_size: self.font_size * scale,
+            char_spacing: self.char_spacing * scale,
+            line_spacing: self.line_spacing * scale,
+            font: OptionalFont::Unloaded,
+            bold: OptionalFont::Unloaded,
+            italic: OptionalFont::Unloaded,
+        }
+    }
+
     pub fn reload(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
         self.font = OptionalFont::load(rl, thread, self.path.as_ref());
+        self.bold = match &self.bold_path {
+            Some(path) => OptionalFont::load(rl, thread, Some(path)),
+            None => OptionalFont::Unloaded,
+        };
+        self.italic = match &self.italic_path {
+            Some(path) => OptionalFont::load(rl, thread, Some(path)),
+            None => OptionalFont::Unloaded,
+        };
     }
 
     #[inline]
@@ -517,6 +572,61 @@ impl ThemeFont {
             tint,
         );
     }
+
+    /// The variant of the font for `style`, or [`None`] for the regular font. Bold is preferred
+    /// over italic when text is both and there are both variants.
+    fn variant(&self, style: TextStyle) -> Option<&OptionalFont> {
+        [(style.is_bold, &self.bold), (style.is_italic, &self.italic)]
+            .into_iter()
+            .find(|(is_styled, font)| *is_styled && !matches!(font, OptionalFont::Unloaded))
+            .map(|(_, font)| font)
+    }
+
+    /// Whether bold text is drawn twice instead, for lack of a bold variant.
+    #[inline]
+    fn is_faux_bold(&self, style: TextStyle) -> bool {
+        style.is_bold && matches!(self.bold, OptionalFont::Unloaded)
+    }
+
+    pub fn measure_styled_text(&self, text: &str, style: TextStyle) -> Vector2 {
+        let mut size = match self.variant(style) {
+            Some(font) => font.measure_text(text, self.font_size, self.char_spacing),
+            None => self.measure_text(text),
+        };
+        if self.is_faux_bold(style) && !text.is_empty() {
+            size.x += 1.0;
+        }
+        size
+    }
+
+    pub fn draw_styled_text<D: RaylibDraw>(
+        &self,
+        d: &mut D,
+        text: &str,
+        position: Vector2,
+        tint: Color,
+        style: TextStyle,
+    ) {
+        let font = self.variant(style).unwrap_or(&self.font);
+        d.draw_text_ex(
+            font,
+            text,
+            position,
+            self.font_size,
+            self.char_spacing,
+            tint,
+        );
+        if self.is_faux_bold(style) {
+            d.draw_text_ex(
+                font,
+                text,
+                position + Vector2::new(1.0, 0.0),
+                self.font_size,
+                self.char_spacing,
+                tint,
+            );
+        }
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -845,13 +955,20 @@ struct ThemeLoader {
     pub toolpane_button_gap: Option<f32>,
     pub properties_padding: Option<Padding>,
     pub properties_section_gap: Option<f32>,
+    pub panel_resize_margin: Option<f32>,
     pub button_icons: Option<ThemeButtonIcons>,
     pub node_icons: Option<ThemeNodeIcons>,
+    pub low_vision: Option<bool>,
+    pub wire_hops: Option<bool>,
+    pub junction_dots: Option<bool>,
+    pub wire_fade: Option<bool>,
 }
 
 impl From<ThemeLoader> for Theme {
     fn from(value: ThemeLoader) -> Self {
         let base = value.base.unwrap_or_default().theme();
+        let low_vision = value.low_vision.unwrap_or(base.low_vision);
+        let console_font = value.console_font.unwrap_or(base.console_font);
         Self {
             background: value.background.map_or(base.background, Into::into),
             background1: value.background1.map_or(base.background1, Into::into),
@@ -892,7 +1009,11 @@ impl From<ThemeLoader> for Theme {
             properties_header_font: value
                 .properties_header_font
                 .unwrap_or(base.properties_header_font),
-            console_font: value.console_font.unwrap_or(base.console_font),
+            console_font: if low_vision {
+                console_font.scaled(LOW_VISION_FONT_SCALE)
+            } else {
+                console_font
+            },
             console_padding: value.console_padding.unwrap_or(base.console_padding),
             title_padding: value.title_padding.unwrap_or(base.title_padding),
             button_icon_scale: value.button_icon_scale.unwrap_or(base.button_icon_scale),
@@ -916,14 +1037,24 @@ impl From<ThemeLoader> for Theme {
             properties_section_gap: value
                 .properties_section_gap
                 .unwrap_or(base.properties_section_gap),
+            panel_resize_margin: value
+                .panel_resize_margin
+                .unwrap_or(base.panel_resize_margin),
             node_icons: value.node_icons.unwrap_or(base.node_icons),
             button_icons: value.button_icons.unwrap_or(base.button_icons),
+            low_vision,
+            wire_hops: value.wire_hops.unwrap_or(base.wire_hops),
+            junction_dots: value.junction_dots.unwrap_or(base.junction_dots),
+            wire_fade: value.wire_fade.unwrap_or(base.wire_fade),
+            ui_scale: 1.0,
         }
     }
 }
 
 impl From<Theme> for ThemeLoader {
-    fn from(value: Theme) -> Self {
+    fn from(mut value: Theme) -> Self {
+        // saved at the scale it was written with
+        value.set_ui_scale(1.0);
         Self {
             base: None,
             background: Some(value.background.into()),
@@ -959,7 +1090,12 @@ impl From<Theme> for ThemeLoader {
             general_font: Some(value.general_font),
             title_font: Some(value.title_font),
             properties_header_font: Some(value.properties_header_font),
-            console_font: Some(value.console_font),
+            // saved at the size it was written with
+            console_font: Some(if value.low_vision {
+                value.console_font.scaled(LOW_VISION_FONT_SCALE.recip())
+            } else {
+                value.console_font
+            }),
             console_padding: Some(value.console_padding),
             title_padding: Some(value.title_padding),
             button_icon_scale: Some(value.button_icon_scale),
@@ -971,12 +1107,31 @@ impl From<Theme> for ThemeLoader {
             toolpane_button_gap: Some(value.toolpane_button_gap),
             properties_padding: Some(value.properties_padding),
             properties_section_gap: Some(value.properties_section_gap),
+            panel_resize_margin: Some(value.panel_resize_margin),
             node_icons: Some(value.node_icons),
             button_icons: Some(value.button_icons),
+            low_vision: Some(value.low_vision),
+            wire_hops: Some(value.wire_hops),
+            junction_dots: Some(value.junction_dots),
+            wire_fade: Some(value.wire_fade),
         }
     }
 }
 
+/// How much larger [`Theme::console_font`] is in [`Theme::low_vision`] mode.
+const LOW_VISION_FONT_SCALE: f32 = 1.5;
+
+/// The least [`Theme::resize_margin`] can be in [`Theme::low_vision`] mode.
+const LOW_VISION_RESIZE_MARGIN: f32 = 6.0;
+
+/// The range [`Theme::ui_scale`] is kept in.
+const MIN_UI_SCALE: f32 = 0.5;
+const MAX_UI_SCALE: f32 = 4.0;
+
+/// The [`Theme::ui_scale`] from which the larger button icons are used, since the smaller ones
+/// would have to be stretched to half again their size or more.
+const LARGE_ICONS_UI_SCALE: f32 = 1.5;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(from = "ThemeLoader", into = "ThemeLoader")]
 pub struct Theme {
@@ -1017,8 +1172,22 @@ pub struct Theme {
     pub toolpane_button_gap: f32,
     pub properties_padding: Padding,
     pub properties_section_gap: f32,
+    /// How far from a panel's edge, in pixels, the edge can be grabbed to resize the panel
+    pub panel_resize_margin: f32,
     pub button_icons: ThemeButtonIcons,
     pub node_icons: ThemeNodeIcons,
+    /// Thicker wires, outlined nodes, wider panel edges to resize by, and a larger console font
+    pub low_vision: bool,
+    /// Draw a wire hopping over any unrelated wire it crosses
+    pub wire_hops: bool,
+    /// Draw a dot where more than one wire leaves or reaches the same side of a node
+    pub junction_dots: bool,
+    /// Fade wires toward the background over the ticks after their source last changed state, so
+    /// that signals can be seen moving through the graph
+    pub wire_fade: bool,
+    /// How many times larger than written the fonts, paddings and gaps are, from the config or
+    /// the monitor's DPI. Themes are always saved unscaled.
+    pub ui_scale: f32,
 }
 
 impl Default for Theme {
@@ -1029,6 +1198,68 @@ impl Default for Theme {
 }
 
 impl Theme {
+    /// How many times thicker than usual wires are drawn.
+    #[inline]
+    pub const fn wire_scale(&self) -> f32 {
+        if self.low_vision { 2.0 } else { 1.0 }
+    }
+
+    /// [`Self::panel_resize_margin`], widened in [`Self::low_vision`] mode.
+    #[inline]
+    pub const fn resize_margin(&self) -> f32 {
+        if self.low_vision && self.panel_resize_margin < LOW_VISION_RESIZE_MARGIN {
+            LOW_VISION_RESIZE_MARGIN
+        } else {
+            self.panel_resize_margin
+        }
+    }
+
+    /// Scales the fonts, paddings and gaps to `scale` times the size they were written as. The
+    /// fonts need to be reloaded after.
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        let scale = scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+        let factor = scale / self.ui_scale;
+        if factor == 1.0 {
+            return;
+        }
+        self.ui_scale = scale;
+        for font in [
+            &mut self.general_font,
+            &mut self.title_font,
+            &mut self.properties_header_font,
+            &mut self.console_font,
+        ] {
+            *font = font.scaled(factor);
+        }
+        for padding in [
+            &mut self.console_padding,
+            &mut self.title_padding,
+            &mut self.toolpane_padding,
+            &mut self.properties_padding,
+        ] {
+            *padding = padding.scaled(factor);
+        }
+        for length in [
+            &mut self.toolpane_group_expanded_gap,
+            &mut self.toolpane_group_collapsed_gap,
+            &mut self.toolpane_button_gap,
+            &mut self.properties_section_gap,
+            &mut self.panel_resize_margin,
+        ] {
+            *length *= factor;
+        }
+    }
+
+    /// [`Self::button_icon_scale`], or the larger icons if the UI is scaled up enough to need them.
+    #[inline]
+    pub fn button_icon_sheet(&self) -> ButtonIconSheetId {
+        if self.ui_scale >= LARGE_ICONS_UI_SCALE {
+            ButtonIconSheetId::X32
+        } else {
+            self.button_icon_scale
+        }
+    }
+
     pub fn reload_assets(
         &mut self,
         rl: &mut RaylibHandle,
@@ -1111,8 +1342,14 @@ impl Theme {
                 bottom: 5.0,
             },
             properties_section_gap: 20.0,
+            panel_resize_margin: 1.5,
             button_icons: ThemeButtonIcons::default(),
             node_icons: ThemeNodeIcons::default(),
+            low_vision: false,
+            wire_hops: false,
+            junction_dots: false,
+            wire_fade: false,
+            ui_scale: 1.0,
         }
     }
 
@@ -1143,7 +1380,8 @@ impl Theme {
     }
 }
 
-fn parse_color(s: &str) -> Result<Color, ()> {
+/// Parses `#RRGGBB`, `#RRGGBBAA`, or `rgba(r, g, b, a)`.
+pub fn parse_color(s: &str) -> Result<Color, ()> {
     if let Some(s) = s.strip_prefix('#') {
         Color::from_hex(s).map_err(|_| ())
     } else if let Some(s) = s.strip_prefix("rgba(").and_then(|s| s.strip_suffix(")")) {
@@ -1199,6 +1437,42 @@ pub enum ColorId {
     Resistance9,
 }
 
+impl ColorId {
+    /// Every color, in declaration order.
+    pub const ALL: [ColorId; 30] = [
+        ColorId::Background,
+        ColorId::Background1,
+        ColorId::Background2,
+        ColorId::Background3,
+        ColorId::Foreground3,
+        ColorId::Foreground2,
+        ColorId::Foreground1,
+        ColorId::Foreground,
+        ColorId::Input,
+        ColorId::Output,
+        ColorId::Available,
+        ColorId::Interact,
+        ColorId::Active,
+        ColorId::Error,
+        ColorId::Destructive,
+        ColorId::Special,
+        ColorId::HyperRef,
+        ColorId::DeadLink,
+        ColorId::Caution,
+        ColorId::BlueprintsBackground,
+        ColorId::Resistance0,
+        ColorId::Resistance1,
+        ColorId::Resistance2,
+        ColorId::Resistance3,
+        ColorId::Resistance4,
+        ColorId::Resistance5,
+        ColorId::Resistance6,
+        ColorId::Resistance7,
+        ColorId::Resistance8,
+        ColorId::Resistance9,
+    ];
+}
+
 impl std::fmt::Display for ColorId {
     #[inline]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -0,0 +1,372 @@
+//! The screen opened by the toolpane's theme button, for picking every theme color while
+//! seeing the change applied.
+
+use crate::{
+    config::{self, CONFIG_PATH},
+    console::{Console, LogType},
+    input::Inputs,
+    ivec::Bounds,
+    logln,
+    tab::TabList,
+    theme::{ColorId, Theme},
+};
+use raylib::prelude::*;
+
+/// Space between the screen and the window's edges, leaving the editor visible around it.
+const MARGIN: f32 = 40.0;
+const TITLE: &str = "Theme";
+const HINT: &str = "Click a color to pick it. Changes apply immediately; Revert undoes them.";
+const CHANNELS: [&str; 4] = ["R", "G", "B", "A"];
+
+fn channel(color: Color, n: usize) -> u8 {
+    [color.r, color.g, color.b, color.a][n]
+}
+
+fn with_channel(mut color: Color, n: usize, value: u8) -> Color {
+    *[&mut color.r, &mut color.g, &mut color.b, &mut color.a][n] = value;
+    color
+}
+
+fn hex(Color { r, g, b, a }: Color) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HeaderButton {
+    Close,
+    Save,
+    /// Puts back the colors as they were last saved, or when the editor opened
+    Revert,
+}
+
+impl HeaderButton {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Close => "Close",
+            Self::Save => "Save",
+            Self::Revert => "Revert",
+        }
+    }
+}
+
+/// Where everything in the editor goes for a size of window.
+struct Layout {
+    frame: Rectangle,
+    title_position: Vector2,
+    hint_position: Vector2,
+    /// The header buttons, right to left
+    buttons: [(HeaderButton, Rectangle); 3],
+    /// Where rows are visible
+    list: Bounds,
+    row_height: f32,
+    preview: Rectangle,
+    hex_position: Vector2,
+    /// The slider track of each channel
+    sliders: [Rectangle; 4],
+}
+
+/// Lists every theme color, with sliders for the channels of the selected one.
+#[derive(Debug, Clone)]
+pub struct ThemeEditor {
+    /// Each of [`ColorId::ALL`] as it was last saved, or when the editor opened
+    original: Vec<Color>,
+    selected: ColorId,
+    /// Index of the channel whose slider is held
+    dragging: Option<usize>,
+    /// Pixels the list is scrolled down by
+    scroll: f32,
+    /// Whether colors changed since opening or saving
+    is_unsaved: bool,
+}
+
+impl ThemeEditor {
+    pub fn new(theme: &Theme) -> Self {
+        Self {
+            original: ColorId::ALL.iter().map(|&id| theme[id]).collect(),
+            selected: ColorId::ALL[0],
+            dragging: None,
+            scroll: 0.0,
+            is_unsaved: false,
+        }
+    }
+
+    fn layout(&self, theme: &Theme, window: Bounds) -> Layout {
+        let padding = theme.title_padding;
+        let frame = Rectangle::new(
+            window.min.x + MARGIN,
+            window.min.y + MARGIN,
+            (window.width() - 2.0 * MARGIN).max(0.0),
+            (window.height() - 2.0 * MARGIN).max(0.0),
+        );
+        let title_size = theme.title_font.measure_text(TITLE);
+        let title_position = Vector2::new(frame.x + padding.left, frame.y + padding.top);
+        let row_height = theme.general_font.line_height() + padding.vertical();
+
+        let mut right = frame.x + frame.width - padding.right;
+        let buttons = [
+            HeaderButton::Close,
+            HeaderButton::Save,
+            HeaderButton::Revert,
+        ]
+        .map(|button| {
+            let width = theme.general_font.measure_text(button.label()).x + padding.horizontal();
+            right -= width;
+            let rec = Rectangle::new(right, frame.y + padding.top, width, row_height);
+            right -= padding.right;
+            (button, rec)
+        });
+
+        let header_bottom = title_position.y + title_size.y.max(row_height) + padding.bottom;
+        let hint_position = Vector2::new(frame.x + padding.left, header_bottom);
+        let body_top = hint_position.y + theme.general_font.line_height() + padding.vertical();
+        let body_bottom = (frame.y + frame.height - padding.bottom).max(body_top);
+        let middle = frame.x + frame.width * 0.5;
+
+        let picker_left = middle + padding.left;
+        let picker_width = (frame.x + frame.width - padding.right - picker_left).max(0.0);
+        let preview = Rectangle::new(picker_left, body_top, picker_width, 3.0 * row_height);
+        let hex_position = Vector2::new(picker_left, preview.y + preview.height + padding.top);
+        let label_width = CHANNELS
+            .iter()
+            .map(|label| theme.general_font.measure_text(label).x)
+            .fold(0.0, f32::max)
+            + padding.horizontal();
+        let sliders_top = hex_position.y + row_height;
+        let sliders = std::array::from_fn(|n| {
+            Rectangle::new(
+                picker_left + label_width,
+                sliders_top + n as f32 * row_height + padding.top,
+                (picker_width - label_width).max(0.0),
+                row_height - padding.vertical(),
+            )
+        });
+
+        Layout {
+            frame,
+            title_position,
+            hint_position,
+            buttons,
+            list: Bounds::new(
+                Vector2::new(frame.x + padding.left, body_top),
+                Vector2::new(middle, body_bottom),
+            ),
+            row_height,
+            preview,
+            hex_position,
+            sliders,
+        }
+    }
+
+    fn hovered_row(&self, layout: &Layout, cursor: Vector2) -> Option<usize> {
+        if !layout.list.contains(cursor) {
+            return None;
+        }
+        let n = ((cursor.y - layout.list.min.y + self.scroll) / layout.row_height) as usize;
+        (n < ColorId::ALL.len()).then_some(n)
+    }
+
+    /// Takes all input while open. Returns whether the editor should stay open.
+    pub fn tick(
+        &mut self,
+        rl: &RaylibHandle,
+        console: &mut Console,
+        theme: &mut Theme,
+        tabs: &mut TabList,
+        input: &Inputs,
+        window: Bounds,
+    ) -> bool {
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            return false;
+        }
+
+        let layout = self.layout(theme, window);
+        let max_scroll =
+            (ColorId::ALL.len() as f32 * layout.row_height - layout.list.height()).max(0.0);
+        self.scroll =
+            (self.scroll - input.scroll_console * layout.row_height).clamp(0.0, max_scroll);
+
+        if !input.primary.is_active() {
+            self.dragging = None;
+        } else if input.primary.is_starting() {
+            self.dragging = layout
+                .sliders
+                .iter()
+                .position(|rec| Bounds::from(*rec).contains(input.cursor));
+        }
+
+        if let Some(n) = self.dragging {
+            let track = layout.sliders[n];
+            let t = ((input.cursor.x - track.x) / track.width).clamp(0.0, 1.0);
+            let color = with_channel(theme[self.selected], n, (t * 255.0).round() as u8);
+            if color != theme[self.selected] {
+                theme[self.selected] = color;
+                // the grid is drawn ahead of time with the background colors
+                for tab in tabs.editors_mut() {
+                    tab.mark_grid_dirty();
+                }
+                self.is_unsaved = true;
+            }
+        } else if input.primary.is_starting() {
+            let clicked = layout
+                .buttons
+                .iter()
+                .find(|(_, rec)| Bounds::from(*rec).contains(input.cursor))
+                .map(|(button, _)| *button);
+            match clicked {
+                Some(HeaderButton::Close) => return false,
+                Some(HeaderButton::Save) => {
+                    logln!(
+                        console,
+                        LogType::Attempt,
+                        "Saving theme to {CONFIG_PATH}..."
+                    );
+                    match config::save_entry("theme", &*theme) {
+                        Ok(()) => {
+                            logln!(console, LogType::Success, "Theme saved.");
+                            self.original = ColorId::ALL.iter().map(|&id| theme[id]).collect();
+                            self.is_unsaved = false;
+                        }
+                        Err(e) => logln!(console, LogType::Error, "failed to save theme: {e}"),
+                    }
+                }
+                Some(HeaderButton::Revert) => {
+                    for (&id, &color) in ColorId::ALL.iter().zip(&self.original) {
+                        theme[id] = color;
+                    }
+                    for tab in tabs.editors_mut() {
+                        tab.mark_grid_dirty();
+                    }
+                    self.is_unsaved = false;
+                    logln!(console, LogType::Info, "reverted theme colors");
+                }
+                None => {
+                    if let Some(n) = self.hovered_row(&layout, input.cursor) {
+                        self.selected = ColorId::ALL[n];
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    pub fn draw<D: RaylibDraw>(&self, d: &mut D, theme: &Theme, input: &Inputs, window: Bounds) {
+        let layout = self.layout(theme, window);
+        let padding = theme.title_padding;
+
+        d.draw_rectangle_rec(layout.frame, theme.background2);
+        d.draw_rectangle_rec(
+            Rectangle::new(
+                layout.frame.x + 1.0,
+                layout.frame.y + 1.0,
+                layout.frame.width - 2.0,
+                layout.frame.height - 2.0,
+            ),
+            theme.background1,
+        );
+
+        let title = if self.is_unsaved {
+            format!("{TITLE} (unsaved)")
+        } else {
+            TITLE.to_string()
+        };
+        theme
+            .title_font
+            .draw_text(d, &title, layout.title_position, theme.foreground);
+        theme
+            .general_font
+            .draw_text(d, HINT, layout.hint_position, theme.foreground2);
+
+        for (button, rec) in layout.buttons {
+            let is_hovered = Bounds::from(rec).contains(input.cursor);
+            d.draw_rectangle_rec(
+                rec,
+                if is_hovered {
+                    theme.background3
+                } else {
+                    theme.background2
+                },
+            );
+            theme.general_font.draw_text(
+                d,
+                button.label(),
+                Vector2::new(rec.x + padding.left, rec.y + padding.top),
+                theme.foreground,
+            );
+        }
+
+        // picker
+        let color = theme[self.selected];
+        d.draw_rectangle_rec(layout.preview, color);
+        d.draw_rectangle_lines_ex(layout.preview, 1.0, theme.foreground3);
+        theme.general_font.draw_text(
+            d,
+            &format!("{}: {}", self.selected, hex(color)),
+            layout.hex_position,
+            theme.foreground,
+        );
+        for (n, (track, label)) in layout.sliders.iter().zip(CHANNELS).enumerate() {
+            theme.general_font.draw_text(
+                d,
+                label,
+                Vector2::new(
+                    track.x - theme.general_font.measure_text(label).x - padding.right,
+                    track.y,
+                ),
+                theme.foreground2,
+            );
+            d.draw_rectangle_gradient_h(
+                track.x as i32,
+                track.y as i32,
+                track.width as i32,
+                track.height as i32,
+                with_channel(color, n, 0),
+                with_channel(color, n, 255),
+            );
+            d.draw_rectangle_lines_ex(*track, 1.0, theme.foreground3);
+            let x = track.x + track.width * f32::from(channel(color, n)) / 255.0;
+            d.draw_rectangle_rec(
+                Rectangle::new(x - 1.0, track.y, 2.0, track.height),
+                theme.foreground,
+            );
+        }
+
+        // list
+        let hovered = self.hovered_row(&layout, input.cursor);
+        let list = Rectangle::from(layout.list);
+        let mut d = d.begin_scissor_mode(
+            list.x as i32,
+            list.y as i32,
+            list.width as i32,
+            list.height as i32,
+        );
+        for (n, &id) in ColorId::ALL.iter().enumerate() {
+            let rec = Rectangle::new(
+                list.x,
+                list.y + n as f32 * layout.row_height - self.scroll,
+                list.width,
+                layout.row_height,
+            );
+            if rec.y + rec.height < list.y || rec.y > list.y + list.height {
+                continue;
+            }
+            if id == self.selected || hovered == Some(n) {
+                d.draw_rectangle_rec(rec, theme.background3);
+            }
+            let swatch = Rectangle::new(
+                rec.x + padding.left,
+                rec.y + padding.top,
+                rec.height - padding.vertical(),
+                rec.height - padding.vertical(),
+            );
+            d.draw_rectangle_rec(swatch, theme[id]);
+            d.draw_rectangle_lines_ex(swatch, 1.0, theme.foreground3);
+            theme.general_font.draw_text(
+                &mut d,
+                &id.to_string(),
+                Vector2::new(swatch.x + swatch.width + padding.left, rec.y + padding.top),
+                theme.foreground,
+            );
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Errors and warnings shown for a few seconds in the bottom-right corner of the window as they
+//! are logged, so that they are noticed even while the console is closed or collapsed. Clicking
+//! a toast dismisses it, and one stays up for as long as the cursor is over it.
+
+use crate::{
+    console::{LogRecord, LogType},
+    input::Inputs,
+    ivec::Bounds,
+    properties::wrap_text,
+    rich_text::RichStr,
+    theme::Theme,
+};
+use raylib::prelude::*;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// How long a toast stays up unless it is dismissed or hovered.
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+/// Most toasts shown at once, after which the oldest are dismissed.
+const MAX_TOASTS: usize = 4;
+const TOAST_WIDTH: f32 = 320.0;
+/// Space between toasts, and between them and the edges of the window.
+const TOAST_MARGIN: f32 = 8.0;
+/// Width of the stripe down the left of a toast, in the color of its level.
+const STRIPE_WIDTH: f32 = 3.0;
+
+#[derive(Debug)]
+struct Toast {
+    level: LogType,
+    /// The message without escape codes
+    message: String,
+    expires: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct Toasts {
+    /// Oldest first
+    toasts: VecDeque<Toast>,
+}
+
+impl Toasts {
+    /// Shows `record` if it is an error or warning.
+    pub fn push(&mut self, record: &LogRecord) {
+        if !matches!(record.level, LogType::Error | LogType::Warning) {
+            return;
+        }
+        if self.toasts.len() == MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.toasts.push_back(Toast {
+            level: record.level,
+            message: RichStr::new(&record.message).plain_text(),
+            expires: Instant::now() + TOAST_DURATION,
+        });
+    }
+
+    /// The index of each toast, where it goes and its wrapped text, stacked up from the
+    /// bottom-right corner of `window` with the newest at the bottom.
+    fn layout(&self, theme: &Theme, window: &Bounds) -> Vec<(usize, Rectangle, String)> {
+        let font = &theme.general_font;
+        let padding = theme.title_padding;
+        let width = TOAST_WIDTH.min(window.width() - 2.0 * TOAST_MARGIN);
+        let text_width = width - STRIPE_WIDTH - padding.horizontal();
+        let mut bottom = window.max.y - TOAST_MARGIN;
+        self.toasts
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(n, toast)| {
+                let text = wrap_text(
+                    &format!("{}: {}", toast.level, toast.message),
+                    text_width,
+                    font,
+                );
+                let height = font.measure_text(&text).y + padding.vertical();
+                bottom -= height;
+                let rec =
+                    Rectangle::new(window.max.x - TOAST_MARGIN - width, bottom, width, height);
+                bottom -= TOAST_MARGIN;
+                (n, rec, text)
+            })
+            .collect()
+    }
+
+    /// The index of the toast under `cursor`, if there is one.
+    fn hovered(&self, theme: &Theme, window: &Bounds, cursor: Vector2) -> Option<usize> {
+        self.layout(theme, window)
+            .into_iter()
+            .find(|(_, rec, _)| rec.check_collision_point_rec(cursor))
+            .map(|(n, _, _)| n)
+    }
+
+    /// Whether the cursor is over a toast, in which case the toast takes the input rather than
+    /// what is under it.
+    #[inline]
+    pub fn is_hovered(&self, theme: &Theme, window: &Bounds, cursor: Vector2) -> bool {
+        self.hovered(theme, window, cursor).is_some()
+    }
+
+    /// Dismisses the clicked toast and any that have run out of time, except the hovered one.
+    pub fn tick(&mut self, theme: &Theme, input: &Inputs, window: &Bounds) {
+        let hovered = self.hovered(theme, window, input.cursor);
+        if let Some(n) = hovered
+            && input.primary.is_starting()
+        {
+            self.toasts.remove(n);
+            return;
+        }
+        let now = Instant::now();
+        let mut n = 0;
+        self.toasts.retain(|toast| {
+            let is_kept = toast.expires > now || hovered == Some(n);
+            n += 1;
+            is_kept
+        });
+    }
+
+    /// Dismisses the toasts that have run out of time, for while they can't be hovered.
+    pub fn expire(&mut self) {
+        let now = Instant::now();
+        self.toasts.retain(|toast| toast.expires > now);
+    }
+
+    pub fn draw<D: RaylibDraw>(&self, d: &mut D, theme: &Theme, input: &Inputs, window: &Bounds) {
+        let font = &theme.general_font;
+        let padding = theme.title_padding;
+        for (n, rec, text) in self.layout(theme, window) {
+            let is_hovered = rec.check_collision_point_rec(input.cursor);
+            d.draw_rectangle_rec(
+                rec,
+                if is_hovered {
+                    theme.foreground2
+                } else {
+                    theme.background2
+                },
+            );
+            d.draw_rectangle_rec(
+                Rectangle::new(rec.x + 1.0, rec.y + 1.0, rec.width - 2.0, rec.height - 2.0),
+                theme.background1,
+            );
+            d.draw_rectangle_rec(
+                Rectangle::new(rec.x, rec.y, STRIPE_WIDTH, rec.height),
+                self.toasts[n].level.color().get(theme),
+            );
+            font.draw_text(
+                d,
+                &text,
+                Vector2::new(rec.x + STRIPE_WIDTH + padding.left, rec.y + padding.top),
+                theme.foreground,
+            );
+        }
+    }
+}
@@ -1,6 +1,14 @@
-use crate::graph::node::NodeId;
+use crate::graph::{
+    Erased, Graph,
+    annotation::AnnotationId,
+    model::GraphModel,
+    node::{GateInstance, NodeId},
+    region::RegionId,
+    wire::{WireEnd, WireId},
+};
 use raylib::prelude::*;
 use serde_derive::{Deserialize, Serialize};
+use std::sync::{RwLock, Weak};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -10,6 +18,7 @@ pub enum ToolId {
     Erase,
     Edit,
     Interact,
+    Annotate,
 }
 
 impl std::fmt::Display for ToolId {
@@ -20,6 +29,7 @@ impl std::fmt::Display for ToolId {
             ToolId::Erase => "erase",
             ToolId::Edit => "edit",
             ToolId::Interact => "ineteract",
+            ToolId::Annotate => "annotate",
         }
         .fmt(f)
     }
@@ -35,19 +45,40 @@ impl std::str::FromStr for ToolId {
             "erase" => Ok(ToolId::Erase),
             "edit" => Ok(ToolId::Edit),
             "ineteract" => Ok(ToolId::Interact),
+            "annotate" => Ok(ToolId::Annotate),
             _ => Err(()),
         }
     }
 }
 
 impl ToolId {
+    pub const ALL: [Self; 5] = [
+        Self::Create,
+        Self::Erase,
+        Self::Edit,
+        Self::Interact,
+        Self::Annotate,
+    ];
+
     #[inline]
     pub const fn init(self) -> Tool {
         match self {
-            ToolId::Create => Tool::Create { current_node: None },
-            ToolId::Erase => Tool::Erase {},
-            ToolId::Edit => Tool::Edit { target: None },
-            ToolId::Interact => Tool::Interact {},
+            ToolId::Create => Tool::Create {
+                current_node: None,
+                stamp: None,
+            },
+            ToolId::Erase => Tool::Erase { sweep: None },
+            ToolId::Edit => Tool::Edit {
+                target: None,
+                wire_target: None,
+                region_target: None,
+                marquee: None,
+            },
+            ToolId::Interact => Tool::Interact { held: None },
+            ToolId::Annotate => Tool::Annotate {
+                target: None,
+                grab_offset: None,
+            },
         }
     }
 }
@@ -58,18 +89,82 @@ pub struct EditDragging {
     pub id: NodeId,
 }
 
+/// The part of a wire grabbed with the edit tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WireHandle {
+    /// Dropping picks the elbow style closest to the cursor.
+    Elbow,
+    /// Dropping on a node moves that end of the wire to it.
+    End(WireEnd),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WireDragging {
+    pub id: WireId,
+    pub handle: WireHandle,
+}
+
+/// What one drag of the erase tool erased, to be put back from the context menu.
+#[derive(Debug, Clone)]
+pub struct EraseSweep {
+    /// The graph swept over, the only one the sweep can be put back into
+    pub graph: Weak<RwLock<Graph>>,
+    pub erased: Erased,
+    /// How many nodes and wires were swept over, not counting the wires of erased nodes
+    pub count: (usize, usize),
+    /// Where the cursor was in the world last frame, so that a fast drag erases everything it
+    /// crosses. [`None`] once primary input ends.
+    pub last_pos: Option<Vector2>,
+}
+
+/// A region grabbed by its label with the edit tool.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegionDragging {
+    pub id: RegionId,
+    /// From the region's top-left corner to where it was grabbed, in world units
+    pub grab_offset: Vector2,
+}
+
 #[derive(Debug, Clone)]
 pub enum Tool {
-    Create { current_node: Option<NodeId> },
-    Erase {},
-    Edit { target: Option<EditDragging> },
-    Interact {},
+    Create {
+        current_node: Option<NodeId>,
+        /// Copy of a selection placed with every click instead of a single node
+        stamp: Option<GraphModel>,
+    },
+    Erase {
+        /// What is being erased by sweeping over it while primary input is held, and after
+        /// that, what the last sweep erased
+        sweep: Option<EraseSweep>,
+    },
+    Edit {
+        target: Option<EditDragging>,
+        wire_target: Option<WireDragging>,
+        region_target: Option<RegionDragging>,
+        /// Where a box selection started, in world space, while it is being dragged
+        marquee: Option<Vector2>,
+    },
+    Interact {
+        /// The button node being held down and the graph it is in, to be let go by
+        /// [`Tool::release_button`] once primary input ends
+        held: Option<(Weak<RwLock<Graph>>, NodeId)>,
+    },
+    Annotate {
+        /// The annotation being edited in the properties panel
+        target: Option<AnnotationId>,
+        /// From the target's position to where it was grabbed, in world units, while it is
+        /// being dragged
+        grab_offset: Option<Vector2>,
+    },
 }
 
 impl Default for Tool {
     #[inline]
     fn default() -> Self {
-        Self::Create { current_node: None }
+        Self::Create {
+            current_node: None,
+            stamp: None,
+        }
     }
 }
 
@@ -81,6 +176,63 @@ impl Tool {
             Tool::Erase { .. } => ToolId::Erase,
             Tool::Edit { .. } => ToolId::Edit,
             Tool::Interact { .. } => ToolId::Interact,
+            Tool::Annotate { .. } => ToolId::Annotate,
+        }
+    }
+
+    /// Whether the tool is in the middle of dragging something with primary input.
+    #[inline]
+    pub const fn is_dragging(&self) -> bool {
+        matches!(
+            self,
+            Tool::Edit {
+                target: Some(_),
+                ..
+            } | Tool::Edit {
+                wire_target: Some(_),
+                ..
+            } | Tool::Edit {
+                region_target: Some(_),
+                ..
+            } | Tool::Edit {
+                marquee: Some(_),
+                ..
+            } | Tool::Erase {
+                sweep: Some(EraseSweep {
+                    last_pos: Some(_),
+                    ..
+                })
+            } | Tool::Interact { held: Some(_) }
+                | Tool::Annotate {
+                    grab_offset: Some(_),
+                    ..
+                }
+        )
+    }
+
+    /// Lets go of the button held down with the interact tool, wherever the cursor is and
+    /// whichever tab is focused. Returns whether there was one. A graph left broken by a crash
+    /// is left alone, like the tools leave it alone while ticking.
+    pub fn release_button(&mut self) -> bool {
+        let Tool::Interact { held } = self else {
+            return false;
+        };
+        let Some((graph, id)) = held.take() else {
+            return false;
+        };
+        let Some(graph) = graph.upgrade() else {
+            return false;
+        };
+        let Ok(mut graph) = graph.write() else {
+            return false;
+        };
+        if let Some(node) = graph.node_mut(&id)
+            && let GateInstance::Button { pressed } = node.gate_mut()
+        {
+            *pressed = false;
+            true
+        } else {
+            false
         }
     }
 }
@@ -1,6 +1,10 @@
 use crate::{
+    blueprint::Blueprint,
+    config,
     console::{Console, GateRef, LogType, ToolRef},
+    file::FileAction,
     graph::{
+        GraphId,
         node::{Gate, GateId, Ntd},
         wire::Elbow,
     },
@@ -18,7 +22,9 @@ use raylib::prelude::*;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Button {
     pub text: Option<&'static str>,
+    /// Key of the tooltip in the [locale](crate::locale)
     pub tooltip: Option<&'static str>,
+    /// Key of the description in the [locale](crate::locale)
     pub desc: Option<&'static str>,
     pub color: Option<ColorRef>,
     pub icon: Option<ButtonIconId>,
@@ -59,6 +65,17 @@ pub enum ButtonAction {
     Blueprints,
     Clipboard,
     Settings,
+    Theme,
+    Export,
+    File(FileAction),
+}
+
+/// A gate button held down, which moves to wherever it is dragged within its group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GateDrag {
+    gate: GateId,
+    /// Whether the button has moved since it was pressed
+    has_moved: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -67,11 +84,18 @@ pub struct ToolPane {
     pub tool: Tool,
     pub gate: Gate,
     pub ntd: Ntd,
+    /// The graph run by the IC gate, kept while other gates are selected
+    pub ic_graph: GraphId,
     pub elbow: Elbow,
+    /// Whether the create tool routes new wires around nodes instead of shaping them by
+    /// [`Self::elbow`]
+    pub auto_route: bool,
+    pub blueprint: Option<Blueprint>,
     pub orientation: Orientation,
     pub visibility: Visibility,
     pub scale: ButtonIconSheetId,
     pub button_groups: Vec<ButtonGroup>,
+    gate_drag: Option<GateDrag>,
 }
 
 impl PanelContent for ToolPane {
@@ -105,8 +129,14 @@ impl ToolPane {
             panel,
             tool,
             ntd: gate.ntd().unwrap_or_default(),
+            ic_graph: match gate {
+                Gate::Ic { graph } => graph,
+                _ => GraphId::INVALID,
+            },
             gate,
             elbow,
+            auto_route: false,
+            blueprint: None,
             orientation,
             visibility,
             scale,
@@ -162,6 +192,14 @@ impl ToolPane {
                             icon: Some(ButtonIconId::Clipboard),
                             action: ButtonAction::Clipboard,
                         },
+                        Button {
+                            text: Some("N"),
+                            tooltip: Some("button.annotate"),
+                            desc: None,
+                            color: None,
+                            icon: None,
+                            action: ButtonAction::SetTool(ToolId::Annotate),
+                        },
                     ],
                 },
                 ButtonGroup {
@@ -239,6 +277,62 @@ impl ToolPane {
                             icon: Some(ButtonIconId::Battery),
                             action: ButtonAction::SetGate(GateId::Battery),
                         },
+                        Button {
+                            text: None,
+                            tooltip: None,
+                            desc: None,
+                            color: None,
+                            icon: Some(ButtonIconId::Ic),
+                            action: ButtonAction::SetGate(GateId::Ic),
+                        },
+                        Button {
+                            text: None,
+                            tooltip: None,
+                            desc: None,
+                            color: None,
+                            icon: Some(ButtonIconId::Split),
+                            action: ButtonAction::SetGate(GateId::Split),
+                        },
+                        Button {
+                            text: None,
+                            tooltip: None,
+                            desc: None,
+                            color: None,
+                            icon: Some(ButtonIconId::Merge),
+                            action: ButtonAction::SetGate(GateId::Merge),
+                        },
+                        Button {
+                            text: None,
+                            tooltip: None,
+                            desc: None,
+                            color: None,
+                            icon: Some(ButtonIconId::Input),
+                            action: ButtonAction::SetGate(GateId::Input),
+                        },
+                        Button {
+                            text: None,
+                            tooltip: None,
+                            desc: None,
+                            color: None,
+                            icon: Some(ButtonIconId::Output),
+                            action: ButtonAction::SetGate(GateId::Output),
+                        },
+                        Button {
+                            text: None,
+                            tooltip: None,
+                            desc: None,
+                            color: None,
+                            icon: Some(ButtonIconId::Switch),
+                            action: ButtonAction::SetGate(GateId::Switch),
+                        },
+                        Button {
+                            text: None,
+                            tooltip: None,
+                            desc: None,
+                            color: None,
+                            icon: Some(ButtonIconId::Button),
+                            action: ButtonAction::SetGate(GateId::Button),
+                        },
                     ],
                 },
                 ButtonGroup {
@@ -327,17 +421,126 @@ impl ToolPane {
                     ],
                 },
                 ButtonGroup {
-                    rev_rows: bool::default(), // only one item in row anyway
-                    buttons: vec![Button {
-                        text: None,
-                        tooltip: None,
-                        desc: None,
-                        color: None,
-                        icon: Some(ButtonIconId::Settings),
-                        action: ButtonAction::Settings,
-                    }],
+                    rev_rows: false,
+                    buttons: vec![
+                        Button {
+                            text: Some("S"),
+                            tooltip: Some("button.save"),
+                            desc: None,
+                            color: None,
+                            icon: None,
+                            action: ButtonAction::File(FileAction::Save),
+                        },
+                        Button {
+                            text: Some("A"),
+                            tooltip: Some("button.save_as"),
+                            desc: None,
+                            color: None,
+                            icon: None,
+                            action: ButtonAction::File(FileAction::SaveAs),
+                        },
+                        Button {
+                            text: Some("O"),
+                            tooltip: Some("button.open"),
+                            desc: None,
+                            color: None,
+                            icon: None,
+                            action: ButtonAction::File(FileAction::Open),
+                        },
+                        Button {
+                            text: Some("E"),
+                            tooltip: Some("button.export"),
+                            desc: None,
+                            color: None,
+                            icon: None,
+                            action: ButtonAction::Export,
+                        },
+                    ],
+                },
+                ButtonGroup {
+                    rev_rows: bool::default(), // only one row anyway
+                    buttons: vec![
+                        Button {
+                            text: None,
+                            tooltip: None,
+                            desc: None,
+                            color: None,
+                            icon: Some(ButtonIconId::Settings),
+                            action: ButtonAction::Settings,
+                        },
+                        Button {
+                            text: Some("T"),
+                            tooltip: Some("button.theme"),
+                            desc: None,
+                            color: None,
+                            icon: None,
+                            action: ButtonAction::Theme,
+                        },
+                    ],
                 },
             ],
+            gate_drag: None,
+        }
+    }
+
+    /// The group holding the gate buttons.
+    fn gate_group_mut(&mut self) -> Option<&mut ButtonGroup> {
+        self.button_groups.iter_mut().find(|group| {
+            group
+                .buttons
+                .iter()
+                .any(|button| matches!(button.action, ButtonAction::SetGate(_)))
+        })
+    }
+
+    /// The gates in the order their buttons are listed.
+    pub fn gate_order(&self) -> Vec<GateId> {
+        self.button_groups
+            .iter()
+            .flat_map(|group| &group.buttons)
+            .filter_map(|button| match button.action {
+                ButtonAction::SetGate(gate_id) => Some(gate_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Lists the gate buttons in `order`, followed by any gates it leaves out in their current
+    /// order.
+    pub fn set_gate_order(&mut self, order: &[GateId]) {
+        if let Some(group) = self.gate_group_mut() {
+            group.buttons.sort_by_key(|button| match button.action {
+                ButtonAction::SetGate(gate_id) => order
+                    .iter()
+                    .position(|&x| x == gate_id)
+                    .unwrap_or(order.len()),
+                _ => order.len(),
+            });
+        }
+    }
+
+    /// Whether a gate button is held down, so the toolpane should keep taking input even
+    /// when the cursor leaves it.
+    #[inline]
+    pub const fn is_dragging_gate(&self) -> bool {
+        self.gate_drag.is_some()
+    }
+
+    /// Moves the dragged gate's button to where `target`'s is.
+    fn move_gate(&mut self, gate: GateId, target: GateId) {
+        if let Some(group) = self.gate_group_mut() {
+            let position = |gate_id| {
+                group
+                    .buttons
+                    .iter()
+                    .position(|button| button.action == ButtonAction::SetGate(gate_id))
+            };
+            if let Some(from) = position(gate)
+                && let Some(to) = position(target)
+            {
+                let button = group.buttons.remove(from);
+                group.buttons.insert(to, button);
+            }
         }
     }
 
@@ -345,17 +548,45 @@ impl ToolPane {
     pub fn set_tool(&mut self, tool_id: ToolId, console: &mut Console) -> bool {
         let change = self.tool.id() != tool_id;
         if change {
+            self.tool.release_button();
             self.tool = tool_id.init();
             logln!(console, LogType::Info, "set tool to {}", ToolRef(tool_id));
         }
         change
     }
 
+    /// The gate `gate_id` would be set to, with the current non-transistor data or IC graph.
+    #[inline]
+    pub const fn gate_of(&self, gate_id: GateId) -> Gate {
+        match gate_id {
+            GateId::Ic => Gate::Ic {
+                graph: self.ic_graph,
+            },
+            _ => gate_id.to_gate(self.ntd),
+        }
+    }
+
     #[inline]
     pub fn set_gate(&mut self, gate_id: GateId, console: &mut Console) -> bool {
         let change = self.gate.id() != gate_id;
         if change {
-            self.gate = gate_id.to_gate(self.ntd);
+            self.gate = self.gate_of(gate_id);
+            logln!(console, LogType::Info, "set gate to {}", GateRef(self.gate));
+        }
+        change
+    }
+
+    /// Sets the gate along with its non-transistor data or graph.
+    pub fn set_exact_gate(&mut self, gate: Gate, console: &mut Console) -> bool {
+        let change = self.gate != gate;
+        if change {
+            self.gate = gate;
+            if let Some(ntd) = gate.ntd() {
+                self.ntd = ntd;
+            }
+            if let Gate::Ic { graph } = gate {
+                self.ic_graph = graph;
+            }
             logln!(console, LogType::Info, "set gate to {}", GateRef(self.gate));
         }
         change
@@ -377,6 +608,17 @@ impl ToolPane {
         change
     }
 
+    #[inline]
+    pub fn set_blueprint(&mut self, blueprint: Blueprint, console: &mut Console) {
+        logln!(
+            console,
+            LogType::Info,
+            "set blueprint to \"{}\"",
+            blueprint.name
+        );
+        self.blueprint = Some(blueprint);
+    }
+
     /// get `position` from [`Self::bounds`]
     pub fn buttons(
         &self,
@@ -478,7 +720,46 @@ impl ToolPane {
         }
     }
 
-    pub fn tick(&mut self, console: &mut Console, theme: &Theme, input: &Inputs) {
+    /// Returns the action of the clicked button if it needs more than the toolpane to perform,
+    /// such as [`ButtonAction::File`], [`ButtonAction::Blueprints`], [`ButtonAction::Clipboard`],
+    /// [`ButtonAction::Settings`], [`ButtonAction::Theme`] and [`ButtonAction::Export`].
+    pub fn tick(
+        &mut self,
+        console: &mut Console,
+        theme: &Theme,
+        input: &Inputs,
+    ) -> Option<ButtonAction> {
+        if let Some(mut drag) = self.gate_drag {
+            if input.primary.is_active() {
+                let bounds = self.panel.content_bounds(theme);
+                let target = self
+                    .buttons(bounds.min, theme)
+                    .find_map(|(button_rec, button)| match button.action {
+                        ButtonAction::SetGate(gate_id)
+                            if Bounds::from(button_rec).contains(input.cursor) =>
+                        {
+                            Some(gate_id)
+                        }
+                        _ => None,
+                    });
+                if let Some(target) = target
+                    && target != drag.gate
+                {
+                    self.move_gate(drag.gate, target);
+                    drag.has_moved = true;
+                }
+                self.gate_drag = Some(drag);
+            } else {
+                self.gate_drag = None;
+                if drag.has_moved
+                    && let Err(e) = config::save_entry("gate_order", &self.gate_order())
+                {
+                    logln!(console, LogType::Error, "failed to save gate order: {e}");
+                }
+            }
+            return None;
+        }
+
         if input.primary.is_starting() {
             let bounds = self.panel.content_bounds(theme);
             let action = self
@@ -495,22 +776,24 @@ impl ToolPane {
                     }
                     ButtonAction::SetGate(gate_id) => {
                         self.set_gate(gate_id, console);
+                        self.gate_drag = Some(GateDrag {
+                            gate: gate_id,
+                            has_moved: false,
+                        });
                     }
                     ButtonAction::SetNtd(data) => {
                         self.set_ntd(data, console);
                     }
-                    ButtonAction::Blueprints => {
-                        // TODO
-                    }
-                    ButtonAction::Clipboard => {
-                        // TODO
-                    }
-                    ButtonAction::Settings => {
-                        // TODO
-                    }
+                    ButtonAction::Blueprints => return Some(action),
+                    ButtonAction::Clipboard => return Some(action),
+                    ButtonAction::Settings => return Some(action),
+                    ButtonAction::Theme => return Some(action),
+                    ButtonAction::Export => return Some(action),
+                    ButtonAction::File(_) => return Some(action),
                 }
             }
         }
+        None
     }
 
     pub fn draw<D>(&self, d: &mut D, input: &Inputs, theme: &Theme)
@@ -527,6 +810,9 @@ impl ToolPane {
                     ButtonAction::Blueprints => false,
                     ButtonAction::Clipboard => false,
                     ButtonAction::Settings => false,
+                    ButtonAction::Theme => false,
+                    ButtonAction::Export => false,
+                    ButtonAction::File(_) => false,
                 };
                 if let Some(icon) = button.icon {
                     d.draw_texture_pro(
@@ -548,11 +834,12 @@ impl ToolPane {
                         width,
                         height,
                     } = button_rec;
-                    if let Some(outline) = match (is_selected, is_hovered) {
+                    let outline = match (is_selected, is_hovered) {
                         (true, false) => Some(theme.foreground),
                         (false, true) | (true, true) => Some(theme.foreground1),
                         (false, false) => None,
-                    } {
+                    };
+                    if let Some(outline) = outline {
                         d.draw_rectangle_rec(Rectangle::new(x, y, width, height), outline);
                     }
                     if let Some(color) = button.color {
@@ -560,9 +847,24 @@ impl ToolPane {
                             Rectangle::new(x + 1.0, y + 1.0, width - 2.0, height - 2.0),
                             color.get(theme),
                         );
+                    } else if let Some(text) = button.text {
+                        let text_size = theme.general_font.measure_text(text);
+                        theme.general_font.draw_text(
+                            d,
+                            text,
+                            Vector2::new(
+                                x + 0.5 * (width - text_size.x),
+                                y + 0.5 * (height - text_size.y),
+                            ),
+                            if outline.is_some() {
+                                theme.background
+                            } else {
+                                theme.foreground2
+                            },
+                        );
                     }
                 }
             }
-        })
+        });
     }
 }
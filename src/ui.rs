@@ -1,9 +1,18 @@
-use crate::{input::Inputs, ivec::Bounds, theme::Theme};
+use crate::{input::Inputs, ivec::Bounds, locale::tr, rich_text::RichStr, theme::Theme};
 use raylib::prelude::*;
 use serde_derive::{Deserialize, Serialize};
 
 pub type SizingBound = fn(&Theme, f32, f32) -> Option<f32>;
 
+/// Limits of sizes that have none of their own, like those of panels moved to a new side.
+const DEFAULT_MIN: SizingBound = |_, _, _| Some(0.0);
+const DEFAULT_MAX: SizingBound = |_, container_size, _| Some(container_size);
+
+/// Pixels per frame a panel is resized by while [`Inputs::resize_panel`] is held.
+const KEYBOARD_RESIZE_SPEED: f32 = 4.0;
+/// Pixels between the cursor and the tooltip beside it.
+const TOOLTIP_OFFSET: f32 = 12.0;
+
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct ExactSizing {
     pub val: f32,
@@ -54,6 +63,14 @@ pub enum NcSizing {
 }
 
 impl NcSizing {
+    #[inline]
+    pub const fn exact_mut(&mut self) -> Option<&mut ExactSizing> {
+        match self {
+            Self::Exact(x) => Some(x),
+            Self::FitContent => None,
+        }
+    }
+
     #[inline]
     pub const fn get(self, content_size: f32) -> f32 {
         match self {
@@ -73,6 +90,14 @@ pub enum Sizing {
 }
 
 impl Sizing {
+    #[inline]
+    pub const fn exact_mut(&mut self) -> Option<&mut ExactSizing> {
+        match self {
+            Self::Exact(x) => Some(x),
+            Self::FitContent | Self::Fill => None,
+        }
+    }
+
     #[inline]
     pub const fn get(self, container_size: f32, content_size: f32) -> f32 {
         match self {
@@ -125,6 +150,54 @@ pub enum Anchoring {
 }
 
 impl Anchoring {
+    /// Against the side or corner of the container named by `region`, at `size`.
+    pub const fn docked(region: RectHoverRegion, size: Vector2) -> Self {
+        let w = Sizing::Exact(ExactSizing {
+            val: size.x,
+            min: None,
+            max: None,
+        });
+        let h = Sizing::Exact(ExactSizing {
+            val: size.y,
+            min: None,
+            max: None,
+        });
+        match region {
+            RectHoverRegion::Left => Self::Left { w },
+            RectHoverRegion::TopLeft => Self::TopLeft { w, h },
+            RectHoverRegion::Top => Self::Top { h },
+            RectHoverRegion::TopRight => Self::TopRight { w, h },
+            RectHoverRegion::Right => Self::Right { w },
+            RectHoverRegion::BottomRight => Self::BottomRight { w, h },
+            RectHoverRegion::Bottom => Self::Bottom { h },
+            RectHoverRegion::BottomLeft => Self::BottomLeft { w, h },
+        }
+    }
+
+    /// `(width, height)`, where they are exact.
+    pub const fn exact_sizes_mut(
+        &mut self,
+    ) -> (Option<&mut ExactSizing>, Option<&mut ExactSizing>) {
+        match self {
+            Self::Fill => (None, None),
+            Self::Left { w } | Self::Right { w } => (w.exact_mut(), None),
+            Self::Top { h } | Self::Bottom { h } => (None, h.exact_mut()),
+            Self::TopLeft { w, h }
+            | Self::TopRight { w, h }
+            | Self::BottomRight { w, h }
+            | Self::BottomLeft { w, h } => (w.exact_mut(), h.exact_mut()),
+            Self::Floating { w, h, .. } => (w.exact_mut(), h.exact_mut()),
+        }
+    }
+
+    /// Multiplies the exact sizes by `scale`, such as by the UI scale.
+    pub fn scale_sizes(&mut self, scale: f32) {
+        let (w, h) = self.exact_sizes_mut();
+        for sizing in [w, h].into_iter().flatten() {
+            sizing.val *= scale;
+        }
+    }
+
     /// `(self bounds, remaining container bounds)`
     ///
     /// remaining container bounds is [`None`] if `self` is floating or doesn't split the container
@@ -281,6 +354,17 @@ impl Padding {
         }
     }
 
+    /// Every side `scale` times wider.
+    #[inline]
+    pub const fn scaled(self, scale: f32) -> Self {
+        Self {
+            left: self.left * scale,
+            top: self.top * scale,
+            right: self.right * scale,
+            bottom: self.bottom * scale,
+        }
+    }
+
     #[inline]
     pub const fn horizontal(&self) -> f32 {
         self.left + self.right
@@ -364,11 +448,18 @@ pub trait PanelContent {
 
 #[derive(Debug, Clone, Copy)]
 pub struct Panel {
+    /// Key of the title in the [locale](crate::locale), or empty for no title
     pub title: &'static str,
     pub anchoring: Anchoring,
     pub padding: fn(&Theme) -> Padding,
     bounds: Bounds,
     pub hover: Option<RectHover>,
+    /// Behind another panel in a [stack](crate::dock::DockLayout::stacks), taking up no space
+    is_hidden: bool,
+    /// Shrunk down to its title strip by the button beside the title
+    pub is_collapsed: bool,
+    /// Turned off by its toggle, taking up no space
+    pub is_closed: bool,
 }
 
 impl Panel {
@@ -379,6 +470,29 @@ impl Panel {
             padding,
             bounds: Bounds::default(),
             hover: None,
+            is_hidden: false,
+            is_collapsed: false,
+            is_closed: false,
+        }
+    }
+
+    #[inline]
+    pub const fn is_shown(&self) -> bool {
+        !self.is_hidden && !self.is_closed
+    }
+
+    /// Closes the panel if it is open, or opens it if it is closed.
+    #[inline]
+    pub fn toggle_closed(&mut self) {
+        self.is_closed = !self.is_closed;
+        self.hover = None;
+    }
+
+    #[inline]
+    pub fn set_hidden(&mut self, is_hidden: bool) {
+        self.is_hidden = is_hidden;
+        if is_hidden {
+            self.hover = None;
         }
     }
 
@@ -390,10 +504,17 @@ impl Panel {
         container: &Bounds,
         content_size: Vector2,
     ) -> Option<Bounds> {
+        if !self.is_shown() {
+            self.bounds = Bounds::default();
+            return None;
+        }
         let padding = (self.padding)(theme);
-        let (bounds, new_container) = self
-            .anchoring
-            .bounds(container, content_size + padding.size());
+        let anchoring = if self.is_collapsed {
+            self.collapsed_anchoring(theme)
+        } else {
+            self.anchoring
+        };
+        let (bounds, new_container) = anchoring.bounds(container, content_size + padding.size());
         self.bounds = bounds;
         new_container
     }
@@ -403,6 +524,52 @@ impl Panel {
         &self.bounds
     }
 
+    /// [`Self::anchoring`] shrunk down to the title strip and collapse button, against the same
+    /// edges of the container.
+    fn collapsed_anchoring(&self, theme: &Theme) -> Anchoring {
+        let strip = title_tab_size(theme, self.title);
+        let exact = |val| ExactSizing {
+            val,
+            min: None,
+            max: None,
+        };
+        let mut anchoring = self.anchoring;
+        match &mut anchoring {
+            Anchoring::Left { w } | Anchoring::Right { w } => {
+                *w = Sizing::Exact(exact(strip.x + strip.y));
+            }
+            Anchoring::Top { h }
+            | Anchoring::Bottom { h }
+            | Anchoring::TopLeft { h, .. }
+            | Anchoring::TopRight { h, .. }
+            | Anchoring::BottomLeft { h, .. }
+            | Anchoring::BottomRight { h, .. } => *h = Sizing::Exact(exact(strip.y)),
+            Anchoring::Floating { h, .. } => *h = NcSizing::Exact(exact(strip.y)),
+            Anchoring::Fill => {}
+        }
+        anchoring
+    }
+
+    /// Anchors the panel like `anchoring`. Limits aren't saved with sizes, so each exact size
+    /// takes the limits of the panel's current size on the same axis, or stays within the container
+    /// if there is none.
+    pub fn reanchor(&mut self, mut anchoring: Anchoring) {
+        let limits = |current: Option<&mut ExactSizing>| {
+            current.map_or((Some(DEFAULT_MIN), Some(DEFAULT_MAX)), |x| (x.min, x.max))
+        };
+        let (current_w, current_h) = self.anchoring.exact_sizes_mut();
+        let (w_limits, h_limits) = (limits(current_w), limits(current_h));
+        let (w, h) = anchoring.exact_sizes_mut();
+        for (size, (min, max)) in [(w, w_limits), (h, h_limits)] {
+            if let Some(size) = size {
+                size.min = min;
+                size.max = max;
+            }
+        }
+        self.anchoring = anchoring;
+        self.hover = None;
+    }
+
     #[inline]
     pub const fn is_dragging(&self) -> bool {
         matches!(
@@ -414,11 +581,14 @@ impl Panel {
         )
     }
 
+    /// Whether `overlapping` is over the panel or close enough to its edge to resize it.
     #[inline]
-    pub const fn interactable(&self, overlapping: Vector2) -> bool {
-        self.bounds
-            .pad(&Padding::amount(-1.5))
-            .contains(overlapping)
+    pub const fn interactable(&self, theme: &Theme, overlapping: Vector2) -> bool {
+        self.is_shown()
+            && self
+                .bounds
+                .pad(&Padding::amount(-theme.resize_margin()))
+                .contains(overlapping)
     }
 
     #[inline]
@@ -434,11 +604,27 @@ impl Panel {
         container: &Bounds,
         content_size: Vector2,
     ) {
+        if !self.is_shown() {
+            return;
+        }
+        if input.primary.is_starting()
+            && self
+                .collapse_rec(theme)
+                .is_some_and(|rec| rec.check_collision_point_rec(input.cursor))
+        {
+            self.is_collapsed = !self.is_collapsed;
+            self.hover = None;
+            return;
+        }
+        if self.is_collapsed {
+            return;
+        }
         // TODO: does it make more sense to have dedicated inputs for this?
         if !self.hover.is_some_and(|hover| hover.is_dragging) {
+            let margin = theme.resize_margin();
             self.hover = if self
                 .bounds
-                .pad(&Padding::amount(-1.5))
+                .pad(&Padding::amount(-margin))
                 .contains(input.cursor)
             {
                 let [hovering_left, hovering_top, hovering_right, hovering_bottom] = [
@@ -447,7 +633,7 @@ impl Panel {
                     input.cursor.x - self.bounds.max.x,
                     input.cursor.y - self.bounds.max.y,
                 ]
-                .map(|p| (-1.5..=1.5).contains(&p));
+                .map(|p| (-margin..=margin).contains(&p));
                 match &self.anchoring {
                     // combos first
                     Anchoring::TopLeft {
@@ -516,10 +702,20 @@ impl Panel {
                     } if hovering_top => Some(RectHoverRegion::Top),
 
                     Anchoring::Floating {
-                        w: NcSizing::Exact(_w),
-                        h: NcSizing::Exact(_h),
+                        w: NcSizing::Exact(_),
+                        h: NcSizing::Exact(_),
                         ..
-                    } => todo!(),
+                    } => match (hovering_left, hovering_top, hovering_right, hovering_bottom) {
+                        (true, true, _, _) => Some(RectHoverRegion::TopLeft),
+                        (_, true, true, _) => Some(RectHoverRegion::TopRight),
+                        (true, _, _, true) => Some(RectHoverRegion::BottomLeft),
+                        (_, _, true, true) => Some(RectHoverRegion::BottomRight),
+                        (true, _, _, _) => Some(RectHoverRegion::Left),
+                        (_, true, _, _) => Some(RectHoverRegion::Top),
+                        (_, _, true, _) => Some(RectHoverRegion::Right),
+                        (_, _, _, true) => Some(RectHoverRegion::Bottom),
+                        _ => None,
+                    },
 
                     _ => None,
                 }
@@ -538,6 +734,18 @@ impl Panel {
             hover.is_dragging = false;
         }
 
+        if input.resize_panel != Vector2::zero()
+            && !self.is_dragging()
+            && self.interactable(theme, input.cursor)
+        {
+            self.nudge_size(
+                theme,
+                container,
+                content_size,
+                input.resize_panel * KEYBOARD_RESIZE_SPEED,
+            );
+        }
+
         if let Some(hover) = &self.hover
             && hover.is_dragging
         {
@@ -562,7 +770,7 @@ impl Panel {
                     theme,
                     container.width(),
                     content_size.x,
-                    input.cursor.x - self.bounds.max.x,
+                    input.cursor.x - self.bounds.min.x,
                 );
             };
             let clamp_bottom = |h: &mut ExactSizing| {
@@ -570,10 +778,55 @@ impl Panel {
                     theme,
                     container.height(),
                     content_size.y,
-                    input.cursor.y - self.bounds.max.y,
+                    input.cursor.y - self.bounds.min.y,
                 );
             };
 
+            // floating panels can be resized from every side, moving whichever edges aren't held
+            if let Anchoring::Floating {
+                x,
+                y,
+                w: NcSizing::Exact(w),
+                h: NcSizing::Exact(h),
+            } = &mut self.anchoring
+            {
+                let Bounds { min, max } = self.bounds;
+                let region = hover.region;
+                if matches!(
+                    region,
+                    RectHoverRegion::TopLeft | RectHoverRegion::Left | RectHoverRegion::BottomLeft
+                ) {
+                    clamp_left(w);
+                    *x = max.x - w.val;
+                }
+                if matches!(
+                    region,
+                    RectHoverRegion::TopLeft | RectHoverRegion::Top | RectHoverRegion::TopRight
+                ) {
+                    clamp_top(h);
+                    *y = max.y - h.val;
+                }
+                if matches!(
+                    region,
+                    RectHoverRegion::TopRight
+                        | RectHoverRegion::Right
+                        | RectHoverRegion::BottomRight
+                ) {
+                    clamp_right(w);
+                    *x = min.x;
+                }
+                if matches!(
+                    region,
+                    RectHoverRegion::BottomLeft
+                        | RectHoverRegion::Bottom
+                        | RectHoverRegion::BottomRight
+                ) {
+                    clamp_bottom(h);
+                    *y = min.y;
+                }
+                return;
+            }
+
             match (hover.region, &mut self.anchoring) {
                 (
                     RectHoverRegion::TopLeft,
@@ -686,6 +939,49 @@ impl Panel {
         }
     }
 
+    /// Moves whichever edges of the panel aren't against its container by `delta`.
+    fn nudge_size(
+        &mut self,
+        theme: &Theme,
+        container: &Bounds,
+        content_size: Vector2,
+        delta: Vector2,
+    ) {
+        let (w, h, direction) = match &mut self.anchoring {
+            Anchoring::Left { w } => (Some(w), None, Vector2::new(1.0, 0.0)),
+            Anchoring::Right { w } => (Some(w), None, Vector2::new(-1.0, 0.0)),
+            Anchoring::Top { h } => (None, Some(h), Vector2::new(0.0, 1.0)),
+            Anchoring::Bottom { h } => (None, Some(h), Vector2::new(0.0, -1.0)),
+            Anchoring::TopLeft { w, h } => (Some(w), Some(h), Vector2::new(1.0, 1.0)),
+            Anchoring::TopRight { w, h } => (Some(w), Some(h), Vector2::new(-1.0, 1.0)),
+            Anchoring::BottomLeft { w, h } => (Some(w), Some(h), Vector2::new(1.0, -1.0)),
+            Anchoring::BottomRight { w, h } => (Some(w), Some(h), Vector2::new(-1.0, -1.0)),
+            Anchoring::Fill | Anchoring::Floating { .. } => return,
+        };
+
+        if let Some(Sizing::Exact(w)) = w
+            && delta.x != 0.0
+        {
+            w.val = w.clamp(
+                theme,
+                container.width(),
+                content_size.x,
+                w.val + delta.x * direction.x,
+            );
+        }
+
+        if let Some(Sizing::Exact(h)) = h
+            && delta.y != 0.0
+        {
+            h.val = h.clamp(
+                theme,
+                container.height(),
+                content_size.y,
+                h.val + delta.y * direction.y,
+            );
+        }
+    }
+
     pub fn tick_resize_set<'a, I>(mut container: Bounds, theme: &Theme, input: &Inputs, panels: I)
     where
         I: IntoIterator<Item = &'a mut dyn PanelContent>,
@@ -703,7 +999,32 @@ impl Panel {
         }
     }
 
-    pub fn draw<T, D, F>(&self, d: &mut D, theme: &Theme, content: F) -> T
+    /// The tab in the top-right corner holding the title, if there is a title.
+    pub fn title_rec(&self, theme: &Theme) -> Option<Rectangle> {
+        (!self.title.is_empty()).then(|| {
+            let size = title_tab_size(theme, self.title);
+            Rectangle::new(
+                self.bounds.max.x - size.x,
+                self.bounds.min.y,
+                size.x,
+                size.y,
+            )
+        })
+    }
+
+    /// The button left of the title that collapses and expands the panel, if it has a title and
+    /// doesn't fill its container.
+    pub fn collapse_rec(&self, theme: &Theme) -> Option<Rectangle> {
+        if matches!(self.anchoring, Anchoring::Fill) {
+            return None;
+        }
+        self.title_rec(theme).map(|title| {
+            Rectangle::new(title.x - title.height, title.y, title.height, title.height)
+        })
+    }
+
+    /// Returns [`None`] without drawing `content` if the panel is collapsed.
+    pub fn draw<T, D, F>(&self, d: &mut D, theme: &Theme, content: F) -> Option<T>
     where
         D: RaylibDraw,
         F: FnOnce(&mut D, Bounds, &Theme) -> T,
@@ -724,33 +1045,121 @@ impl Panel {
         }
 
         // content
-        let res = content(d, self.content_bounds(theme), theme);
+        let res = (!self.is_collapsed).then(|| content(d, self.content_bounds(theme), theme));
+
+        // edge that would be resized
+        if let Some(hover) = &self.hover {
+            let color = if hover.is_dragging {
+                theme.interact
+            } else {
+                theme.interact.alpha(0.5)
+            };
+            let Bounds { min, max } = self.bounds;
+            let [left, top, right, bottom] = [
+                Rectangle::new(min.x, min.y, 1.0, max.y - min.y),
+                Rectangle::new(min.x, min.y, max.x - min.x, 1.0),
+                Rectangle::new(max.x - 1.0, min.y, 1.0, max.y - min.y),
+                Rectangle::new(min.x, max.y - 1.0, max.x - min.x, 1.0),
+            ];
+            let edges: &[Rectangle] = match hover.region {
+                RectHoverRegion::Left => &[left],
+                RectHoverRegion::TopLeft => &[top, left],
+                RectHoverRegion::Top => &[top],
+                RectHoverRegion::TopRight => &[top, right],
+                RectHoverRegion::Right => &[right],
+                RectHoverRegion::BottomRight => &[bottom, right],
+                RectHoverRegion::Bottom => &[bottom],
+                RectHoverRegion::BottomLeft => &[bottom, left],
+            };
+            for edge in edges {
+                d.draw_rectangle_rec(*edge, color);
+            }
+        }
 
         // title
-        if !self.title.is_empty() {
-            let title_text_size = theme.title_font.measure_text(self.title);
-            let title_width = title_text_size.x + theme.title_padding.horizontal();
-            let title_height = title_text_size.y + theme.title_padding.vertical();
-            d.draw_rectangle_rec(
-                Rectangle::new(
-                    self.bounds.max.x - title_width,
-                    self.bounds.min.y,
-                    title_width,
-                    title_height,
+        if let Some(title_rec) = self.title_rec(theme) {
+            d.draw_rectangle_rec(title_rec, theme.background2);
+            RichStr::new(tr(self.title)).draw(
+                d,
+                theme,
+                &theme.title_font,
+                Vector2::new(
+                    title_rec.x + theme.title_padding.left,
+                    title_rec.y + theme.title_padding.top,
                 ),
-                theme.background2,
+                theme.foreground,
             );
+        }
+
+        if let Some(button) = self.collapse_rec(theme) {
+            d.draw_rectangle_rec(button, theme.background2);
+            let icon = if self.is_collapsed { "+" } else { "-" };
+            let icon_size = theme.title_font.measure_text(icon);
             theme.title_font.draw_text(
                 d,
-                self.title,
+                icon,
                 Vector2::new(
-                    self.bounds.max.x - title_width + theme.title_padding.left,
-                    self.bounds.min.y + theme.title_padding.top,
+                    button.x + (button.width - icon_size.x) * 0.5,
+                    button.y + (button.height - icon_size.y) * 0.5,
                 ),
-                theme.foreground,
+                theme.foreground2,
             );
         }
 
         res
     }
 }
+
+/// Size of the tab holding the title with the [locale](crate::locale) key `title` along the top of
+/// a panel. Titles are [rich text](RichStr), so they can be emphasized.
+pub fn title_tab_size(theme: &Theme, title: &'static str) -> Vector2 {
+    RichStr::new(tr(title)).measure(&theme.title_font) + theme.title_padding.size()
+}
+
+/// Draws `lines` in a box beside `cursor`, flipped to the other side of it on either axis where
+/// the box would leave `container`.
+pub fn draw_tooltip<D: RaylibDraw>(
+    d: &mut D,
+    theme: &Theme,
+    cursor: Vector2,
+    container: &Bounds,
+    lines: &[(Color, String)],
+) {
+    let font = &theme.general_font;
+    let padding = theme.title_padding;
+    let text_width = lines
+        .iter()
+        .map(|(_, line)| font.measure_text(line).x)
+        .fold(0.0, f32::max);
+    let size = Vector2::new(text_width, lines.len() as f32 * font.line_height()) + padding.size();
+    let place = |cursor: f32, size: f32, min: f32, max: f32| {
+        let after = cursor + TOOLTIP_OFFSET;
+        if after + size <= max {
+            after
+        } else {
+            (cursor - TOOLTIP_OFFSET - size).max(min)
+        }
+    };
+    let rec = Rectangle::new(
+        place(cursor.x, size.x, container.min.x, container.max.x),
+        place(cursor.y, size.y, container.min.y, container.max.y),
+        size.x,
+        size.y,
+    );
+    d.draw_rectangle_rec(rec, theme.background2);
+    d.draw_rectangle_rec(
+        Rectangle::new(rec.x + 1.0, rec.y + 1.0, rec.width - 2.0, rec.height - 2.0),
+        theme.background1,
+    );
+    for (n, (color, line)) in lines.iter().enumerate() {
+        font.draw_text(
+            d,
+            line,
+            Vector2::new(
+                rec.x + padding.left,
+                rec.y + padding.top + n as f32 * font.line_height(),
+            ),
+            *color,
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! A timing diagram of the focused tab's watched nodes: one row per node, showing whether it was
+//! on or off after each of the last [`WAVEFORM_TICKS`] ticks, scrolling left as the graph is
+//! evaluated.
+//!
+//! Nodes are watched from their context menu or the properties panel, or by right-clicking a
+//! reference to them in the log.
+
+use crate::{
+    tab::EditorTab,
+    theme::Theme,
+    ui::{Panel, PanelContent},
+};
+use raylib::prelude::*;
+
+/// How many ticks of each watched node's state are kept and plotted.
+pub const WAVEFORM_TICKS: usize = 64;
+
+#[derive(Debug)]
+pub struct Waveform {
+    pub panel: Panel,
+}
+
+impl PanelContent for Waveform {
+    #[inline]
+    fn panel(&self) -> &Panel {
+        &self.panel
+    }
+
+    #[inline]
+    fn panel_mut(&mut self) -> &mut Panel {
+        &mut self.panel
+    }
+
+    #[inline]
+    fn content_size(&self, _theme: &Theme) -> Vector2 {
+        Vector2::zero()
+    }
+}
+
+impl Waveform {
+    pub const fn new(panel: Panel) -> Self {
+        Self { panel }
+    }
+
+    pub fn draw<D: RaylibDraw>(&self, d: &mut D, theme: &Theme, tab: Option<&EditorTab>) {
+        self.panel.draw(d, theme, |d, bounds, theme| {
+            let Some(tab) = tab else {
+                return;
+            };
+            let Some(graph) = tab.graph.upgrade() else {
+                return;
+            };
+            let Ok(graph) = graph.try_read() else {
+                return;
+            };
+            let font = &theme.general_font;
+            let Rectangle {
+                x,
+                y,
+                width,
+                height,
+            } = Rectangle::from(bounds);
+            let mut d = d.begin_scissor_mode(x as i32, y as i32, width as i32, height as i32);
+
+            let mut rows = tab
+                .watched()
+                .filter_map(|(id, watched)| Some((graph.node(id)?, watched)))
+                .collect::<Vec<_>>();
+            if rows.is_empty() {
+                font.draw_text(
+                    &mut d,
+                    "watch a node to plot it here",
+                    bounds.min,
+                    theme.foreground3,
+                );
+                return;
+            }
+            // in reading order, so that rows don't swap places from frame to frame
+            rows.sort_unstable_by_key(|(node, _)| {
+                let position = node.position();
+                (position.y, position.x)
+            });
+            let labels = rows
+                .iter()
+                .map(|(node, _)| format!("{} {}", node.id(), node.gate().as_gate()))
+                .collect::<Vec<_>>();
+            let label_width = labels
+                .iter()
+                .map(|label| font.measure_text(label).x)
+                .fold(0.0, f32::max)
+                + font.line_spacing;
+            let row_height = font.line_height() + font.line_spacing;
+            let step = (bounds.width() - label_width).max(0.0) / WAVEFORM_TICKS as f32;
+
+            for (n, ((_, watched), label)) in rows.iter().zip(&labels).enumerate() {
+                let top = bounds.min.y + n as f32 * row_height;
+                font.draw_text(&mut d, label, Vector2::new(x, top), theme.foreground2);
+                let (high, low) = (top + 1.0, top + font.line_height() - 1.0);
+                // the newest tick is at the right edge
+                let start = bounds.max.x - watched.ticks.len() as f32 * step;
+                let mut last = None;
+                for (tick, &state) in watched.ticks.iter().enumerate() {
+                    let left = start + tick as f32 * step;
+                    if last.is_some_and(|last| last != state) {
+                        d.draw_line_v(
+                            Vector2::new(left, high),
+                            Vector2::new(left, low),
+                            theme.foreground2,
+                        );
+                    }
+                    let (level, color) = if state {
+                        (high, theme.active)
+                    } else {
+                        (low, theme.foreground2)
+                    };
+                    d.draw_line_v(
+                        Vector2::new(left, level),
+                        Vector2::new(left + step, level),
+                        color,
+                    );
+                    last = Some(state);
+                }
+            }
+        });
+    }
+}